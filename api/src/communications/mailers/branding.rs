@@ -0,0 +1,166 @@
+use bigneon_db::models::{Organization, OrganizationEmailSettings};
+use config::Config;
+use diesel::pg::PgConnection;
+use errors::*;
+use utils::communication::*;
+use uuid::Uuid;
+
+/// Transactional emails organizations can replace with their own SendGrid templates
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum BrandedTemplate {
+    PurchaseCompleted,
+    TransferTickets,
+    OrgInvite,
+}
+
+impl BrandedTemplate {
+    fn default_template_id(&self, config: &Config) -> String {
+        match self {
+            BrandedTemplate::PurchaseCompleted => {
+                config.sendgrid_template_bn_purchase_completed.clone()
+            }
+            BrandedTemplate::TransferTickets => {
+                config.sendgrid_template_bn_transfer_tickets.clone()
+            }
+            BrandedTemplate::OrgInvite => config.sendgrid_template_bn_org_invite.clone(),
+        }
+    }
+
+    fn organization_template_id(&self, settings: &OrganizationEmailSettings) -> Option<String> {
+        match self {
+            BrandedTemplate::PurchaseCompleted => settings.purchase_completed_template_id.clone(),
+            BrandedTemplate::TransferTickets => settings.transfer_tickets_template_id.clone(),
+            BrandedTemplate::OrgInvite => settings.org_invite_template_id.clone(),
+        }
+    }
+
+    /// Example data matching what each mailer provides, used when previewing templates
+    pub fn sample_template_data(&self, config: &Config) -> TemplateData {
+        let mut template_data = TemplateData::new();
+        template_data.insert("name".to_string(), "Sample".to_string());
+        match self {
+            BrandedTemplate::PurchaseCompleted => {
+                template_data.insert("ticket_count".to_string(), "2".to_string());
+                template_data.insert("total_fees".to_string(), "2.00".to_string());
+                template_data.insert("total_price".to_string(), "42.00".to_string());
+                template_data.insert(
+                    "item_breakdown".to_string(),
+                    r#"<table style="width:100%"><tbody><tr><th>Units</th><th>Description</th><th>Unit Price</th><th>Total</th></tr><tr><th align="center">2</th><th>General Admission</th><th align="right">$20.00</th><th align="right">$40.00</th></tr></tbody></table>"#.to_string(),
+                );
+                template_data.insert(
+                    "tickets_link".to_string(),
+                    format!("{}/hub", config.front_end_url),
+                );
+            }
+            BrandedTemplate::TransferTickets => {
+                template_data.insert("sender_name".to_string(), "Sample Sender".to_string());
+                template_data.insert(
+                    "receive_tickets_link".to_string(),
+                    format!("{}/tickets/receive", config.front_end_url),
+                );
+            }
+            BrandedTemplate::OrgInvite => {
+                template_data.insert(
+                    "invite_link_accept".to_string(),
+                    format!("{}/invites/accept", config.front_end_url),
+                );
+            }
+        }
+        template_data
+    }
+}
+
+/// Sender identity, template and branding data for an email sent on behalf of an organization.
+/// Anything the organization has not configured falls back to the platform defaults.
+pub struct EmailBranding {
+    pub source_email: String,
+    pub source_name: Option<String>,
+    pub template_id: String,
+    /// Set when the organization's own template is used, as it only exists in their account
+    pub sendgrid_organization_id: Option<Uuid>,
+    pub template_data: TemplateData,
+}
+
+impl EmailBranding {
+    pub fn new(
+        template: BrandedTemplate,
+        organization: Option<&Organization>,
+        config: &Config,
+        conn: &PgConnection,
+    ) -> Result<EmailBranding, BigNeonError> {
+        let mut branding = EmailBranding {
+            source_email: config.communication_default_source_email.clone(),
+            source_name: None,
+            template_id: template.default_template_id(config),
+            sendgrid_organization_id: None,
+            template_data: TemplateData::new(),
+        };
+
+        let organization = match organization {
+            Some(organization) => organization,
+            None => return Ok(branding),
+        };
+        branding
+            .template_data
+            .insert("org".to_string(), organization.name.clone());
+
+        let settings =
+            match OrganizationEmailSettings::find_by_organization_id(organization.id, conn)? {
+                Some(settings) => settings,
+                None => return Ok(branding),
+            };
+
+        branding.source_name = settings.from_name.clone();
+        for (key, value) in vec![
+            ("logo_url", &settings.logo_url),
+            ("primary_color", &settings.primary_color),
+            ("secondary_color", &settings.secondary_color),
+        ] {
+            if let Some(value) = value {
+                branding
+                    .template_data
+                    .insert(key.to_string(), value.clone());
+            }
+        }
+
+        // Custom templates live in the organization's SendGrid account so need their API key.
+        // The sender address is only honored there as well, the platform account must not send
+        // as arbitrary addresses the organization has not verified.
+        if let Some(template_id) = template.organization_template_id(&settings) {
+            if organization.sendgrid_api_key.is_some() {
+                branding.template_id = template_id;
+                branding.sendgrid_organization_id = Some(organization.id);
+                if let Some(ref from_email) = settings.from_email {
+                    branding.source_email = from_email.clone();
+                }
+            }
+        }
+
+        Ok(branding)
+    }
+
+    /// Builds the email, the branding values are added to the template data unless the mailer
+    /// already supplied them
+    pub fn communication(
+        self,
+        title: String,
+        destinations: CommAddress,
+        mut template_data: TemplateData,
+    ) -> Communication {
+        for (key, value) in self.template_data {
+            template_data.entry(key).or_insert(value);
+        }
+        let mut communication = Communication::new(
+            CommunicationType::EmailTemplate,
+            title,
+            None,
+            Some(CommAddress::from(self.source_email)),
+            destinations,
+            Some(self.template_id),
+            Some(vec![template_data]),
+        );
+        communication.source_name = self.source_name;
+        communication.sendgrid_organization_id = self.sendgrid_organization_id;
+        communication
+    }
+}
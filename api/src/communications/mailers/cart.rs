@@ -1,6 +1,8 @@
 use bigneon_db::models::enums::OrderItemTypes;
-use bigneon_db::models::DisplayOrder;
+use bigneon_db::models::{DisplayOrder, Organization};
+use communications::mailers::branding::*;
 use config::Config;
+use diesel::pg::PgConnection;
 use errors::*;
 use utils::communication::*;
 
@@ -8,12 +10,18 @@ pub fn purchase_completed(
     user_first_name: &String,
     user_email: String,
     display_order: DisplayOrder,
+    organization: Option<&Organization>,
     config: &Config,
+    conn: &PgConnection,
 ) -> Result<Communication, BigNeonError> {
-    let source = CommAddress::from(config.communication_default_source_email.clone());
+    let branding = EmailBranding::new(
+        BrandedTemplate::PurchaseCompleted,
+        organization,
+        config,
+        conn,
+    )?;
     let destinations = CommAddress::from(user_email);
     let title = "BigNeon Purchase Completed".to_string();
-    let template_id = config.sendgrid_template_bn_purchase_completed.clone();
     let mut template_data = TemplateData::new();
     template_data.insert(String::from("name"), user_first_name.clone());
     //Construct an itemised breakdown using a HTML table
@@ -68,13 +76,5 @@ pub fn purchase_completed(
     );
 
     // TODO: Perhaps move this to an event subscription
-    Ok(Communication::new(
-        CommunicationType::EmailTemplate,
-        title,
-        None,
-        Some(source),
-        destinations,
-        Some(template_id),
-        Some(vec![template_data]),
-    ))
+    Ok(branding.communication(title, destinations, template_data))
 }
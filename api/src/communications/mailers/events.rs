@@ -0,0 +1,26 @@
+use config::Config;
+use diesel::pg::PgConnection;
+use errors::*;
+use utils::communication::*;
+
+pub fn event_rescheduled(
+    config: &Config,
+    email: String,
+    event_name: &str,
+    message: &str,
+    conn: &PgConnection,
+) -> Result<(), BigNeonError> {
+    let source = CommAddress::from(config.communication_default_source_email.clone());
+    let destinations = CommAddress::from(email);
+    let title = format!("{} has been rescheduled", event_name);
+    Communication::new(
+        CommunicationType::Email,
+        title,
+        Some(message.to_string()),
+        Some(source),
+        destinations,
+        None,
+        None,
+    )
+    .queue(conn)
+}
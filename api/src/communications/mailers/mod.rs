@@ -1,4 +1,6 @@
+pub mod branding;
 pub mod cart;
+pub mod events;
 pub mod orders;
 pub mod organization_invites;
 pub mod tickets;
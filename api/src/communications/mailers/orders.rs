@@ -85,3 +85,33 @@ pub fn refund_email(
     )
     .queue(conn)
 }
+
+pub fn refund_request_denied_email(
+    user_first_name: &String,
+    user_email: String,
+    review_notes: Option<&String>,
+    config: &Config,
+    conn: &PgConnection,
+) -> Result<(), BigNeonError> {
+    let source = CommAddress::from(config.communication_default_source_email.clone());
+    let destinations = CommAddress::from(user_email);
+    let title = "BigNeon Refund Request".to_string();
+    let mut body = format!(
+        "Hi {}, unfortunately your refund request was not approved.",
+        user_first_name
+    );
+    if let Some(review_notes) = review_notes {
+        body.push_str(&format!(" Reason: {}", review_notes));
+    }
+
+    Communication::new(
+        CommunicationType::Email,
+        title,
+        Some(body),
+        Some(source),
+        destinations,
+        None,
+        None,
+    )
+    .queue(conn)
+}
@@ -0,0 +1,33 @@
+use bigneon_db::models::User;
+use diesel::pg::PgConnection;
+use errors::*;
+use itertools::Itertools;
+use utils::communication::CommAddress;
+use utils::communication::Communication;
+use utils::communication::CommunicationType;
+
+pub fn event_rescheduled(
+    to_user: &User,
+    message: &str,
+    conn: &PgConnection,
+) -> Result<(), BigNeonError> {
+    let tokens = to_user
+        .push_notification_tokens(conn)?
+        .into_iter()
+        .map(|pt| pt.token)
+        .collect_vec();
+
+    if tokens.len() > 0 {
+        Communication::new(
+            CommunicationType::Push,
+            message.to_string(),
+            None,
+            None,
+            CommAddress::from_vec(tokens),
+            None,
+            None,
+        )
+        .queue(conn)?;
+    }
+    Ok(())
+}
@@ -1,2 +1,4 @@
+pub use self::event_rescheduled::*;
 pub use self::tickets_received::*;
+mod event_rescheduled;
 mod tickets_received;
@@ -0,0 +1,26 @@
+use config::Config;
+use diesel::pg::PgConnection;
+use errors::*;
+use utils::communication::CommAddress;
+use utils::communication::Communication;
+use utils::communication::CommunicationType;
+
+pub fn event_rescheduled(
+    config: &Config,
+    phone: String,
+    message: &str,
+    conn: &PgConnection,
+) -> Result<(), BigNeonError> {
+    let source = CommAddress::from(config.communication_default_source_phone.clone());
+    let destinations = CommAddress::from(phone);
+    Communication::new(
+        CommunicationType::Sms,
+        message.to_string(),
+        None,
+        Some(source),
+        destinations,
+        None,
+        None,
+    )
+    .queue(conn)
+}
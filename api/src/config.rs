@@ -1,3 +1,4 @@
+use bigneon_db::services::DatabaseLedgerClient;
 use dotenv::dotenv;
 use std::env;
 use tari_client::{HttpTariClient, TariClient, TariTestClient};
@@ -9,6 +10,15 @@ pub enum Environment {
     Production,
 }
 
+/// Ledger that ticket assets are recorded on
+#[derive(Clone, Debug, PartialEq)]
+pub enum LedgerBackend {
+    /// Tari blockchain reached over JSON-RPC at `TARI_URL`
+    Tari,
+    /// Append-only signed ledger stored in the database
+    Database,
+}
+
 #[derive(Clone)]
 pub struct Config {
     pub allowed_origins: String,
@@ -24,6 +34,10 @@ pub struct Config {
     pub globee_api_key: String,
     pub globee_base_url: String,
     pub validate_ipns: bool,
+    pub paypal_client_id: String,
+    pub paypal_secret: String,
+    pub paypal_base_url: String,
+    pub paypal_webhook_id: Option<String>,
     pub api_base_url: String,
     pub google_recaptcha_secret_key: Option<String>,
     pub http_keep_alive: usize,
@@ -32,6 +46,7 @@ pub struct Config {
     pub stripe_secret_key: String,
     pub token_secret: String,
     pub token_issuer: String,
+    pub ledger_backend: LedgerBackend,
     pub tari_client: Box<TariClient + Send + Sync>,
     pub communication_default_source_email: String,
     pub communication_default_source_phone: String,
@@ -43,6 +58,7 @@ pub struct Config {
     pub sendgrid_template_bn_transfer_tickets: String,
     pub sendgrid_template_bn_password_reset: String,
     pub sendgrid_template_bn_user_invite: String,
+    pub sendgrid_webhook_token: Option<String>,
     pub spotify_auth_token: Option<String>,
     pub twilio_account_id: String,
     pub twilio_api_key: String,
@@ -73,10 +89,15 @@ const FACEBOOK_APP_SECRET: &str = "FACEBOOK_APP_SECRET";
 const GLOBEE_API_KEY: &str = "GLOBEE_API_KEY";
 const GLOBEE_BASE_URL: &str = "GLOBEE_BASE_URL";
 const VALIDATE_IPNS: &str = "VALIDATE_IPNS";
+const PAYPAL_CLIENT_ID: &str = "PAYPAL_CLIENT_ID";
+const PAYPAL_SECRET: &str = "PAYPAL_SECRET";
+const PAYPAL_BASE_URL: &str = "PAYPAL_BASE_URL";
+const PAYPAL_WEBHOOK_ID: &str = "PAYPAL_WEBHOOK_ID";
 const API_BASE_URL: &str = "API_BASE_URL";
 const GOOGLE_RECAPTCHA_SECRET_KEY: &str = "GOOGLE_RECAPTCHA_SECRET_KEY";
 const PRIMARY_CURRENCY: &str = "PRIMARY_CURRENCY";
 const STRIPE_SECRET_KEY: &str = "STRIPE_SECRET_KEY";
+const LEDGER_BACKEND: &str = "LEDGER_BACKEND";
 const TARI_URL: &str = "TARI_URL";
 const TEST_DATABASE_URL: &str = "TEST_DATABASE_URL";
 const TOKEN_SECRET: &str = "TOKEN_SECRET";
@@ -99,6 +120,8 @@ const SENDGRID_TEMPLATE_BN_ORG_INVITE: &str = "SENDGRID_TEMPLATE_BN_ORG_INVITE";
 const SENDGRID_TEMPLATE_BN_TRANSFER_TICKETS: &str = "SENDGRID_TEMPLATE_BN_TRANSFER_TICKETS";
 const SENDGRID_TEMPLATE_BN_PASSWORD_RESET: &str = "SENDGRID_TEMPLATE_BN_PASSWORD_RESET";
 const SENDGRID_TEMPLATE_BN_USER_INVITE: &str = "SENDGRID_TEMPLATE_BN_USER_INVITE";
+// Shared secret SendGrid passes as the `token` query parameter of the event webhook
+const SENDGRID_WEBHOOK_TOKEN: &str = "SENDGRID_WEBHOOK_TOKEN";
 
 //Spotify settings
 const SPOTIFY_AUTH_TOKEN: &str = "SPOTIFY_AUTH_TOKEN";
@@ -154,14 +177,28 @@ impl Config {
         let front_end_url =
             env::var(&FRONT_END_URL).unwrap_or_else(|_| panic!("Front end url must be defined"));
 
-        let tari_uri =
-            env::var(&TARI_URL).unwrap_or_else(|_| panic!("{} must be defined.", TARI_URL));
+        let ledger_backend = match env::var(&LEDGER_BACKEND)
+            .unwrap_or_else(|_| "tari".to_string())
+            .to_lowercase()
+            .as_str()
+        {
+            "tari" => LedgerBackend::Tari,
+            "database" => LedgerBackend::Database,
+            backend => panic!("{} is not a supported {}", backend, LEDGER_BACKEND),
+        };
 
-        let tari_client = match environment {
-            Environment::Test => {
-                Box::new(TariTestClient::new(tari_uri)) as Box<TariClient + Send + Sync>
+        // Tests use the Tari test client unless the database ledger is asked for explicitly
+        let tari_client = match (&environment, &ledger_backend) {
+            (_, LedgerBackend::Database) => {
+                Box::new(DatabaseLedgerClient::new(database_url.clone()))
+                    as Box<TariClient + Send + Sync>
+            }
+            (Environment::Test, LedgerBackend::Tari) => {
+                Box::new(TariTestClient::new("TEST".to_string())) as Box<TariClient + Send + Sync>
             }
-            _ => {
+            (_, LedgerBackend::Tari) => {
+                let tari_uri =
+                    env::var(&TARI_URL).unwrap_or_else(|_| panic!("{} must be defined.", TARI_URL));
                 if tari_uri == "TEST" {
                     Box::new(TariTestClient::new(tari_uri)) as Box<TariClient + Send + Sync>
                 } else {
@@ -177,6 +214,16 @@ impl Config {
             _ => "https://test.globee.com/payment-api/v1/".to_string(),
         });
 
+        let paypal_client_id =
+            env::var(&PAYPAL_CLIENT_ID).unwrap_or_else(|_| "<paypal not enabled>".to_string());
+        let paypal_secret =
+            env::var(&PAYPAL_SECRET).unwrap_or_else(|_| "<paypal not enabled>".to_string());
+        let paypal_base_url = env::var(&PAYPAL_BASE_URL).unwrap_or_else(|_| match environment {
+            Environment::Production => "https://api.paypal.com/".to_string(),
+            _ => "https://api.sandbox.paypal.com/".to_string(),
+        });
+        let paypal_webhook_id = env::var(&PAYPAL_WEBHOOK_ID).ok();
+
         let branch_io_base_url =
             env::var(&BRANCH_IO_BASE_URL).unwrap_or("https://api2.branch.io/v1".to_string());
         let branch_io_branch_key = env::var(&BRANCH_IO_BRANCH_KEY)
@@ -223,6 +270,7 @@ impl Config {
             .unwrap_or_else(|_| panic!("{} must be defined.", SENDGRID_TEMPLATE_BN_PASSWORD_RESET));
         let sendgrid_template_bn_user_invite = env::var(&SENDGRID_TEMPLATE_BN_USER_INVITE)
             .unwrap_or_else(|_| panic!("{} must be defined.", SENDGRID_TEMPLATE_BN_USER_INVITE));
+        let sendgrid_webhook_token = env::var(&SENDGRID_WEBHOOK_TOKEN).ok();
 
         let spotify_auth_token = env::var(&SPOTIFY_AUTH_TOKEN).ok();
 
@@ -291,6 +339,10 @@ impl Config {
             globee_base_url,
             branch_io_base_url,
             validate_ipns,
+            paypal_client_id,
+            paypal_secret,
+            paypal_base_url,
+            paypal_webhook_id,
             api_base_url,
             google_recaptcha_secret_key,
             http_keep_alive,
@@ -300,6 +352,7 @@ impl Config {
             token_secret,
             token_issuer,
             front_end_url,
+            ledger_backend,
             tari_client,
             communication_default_source_email,
             communication_default_source_phone,
@@ -311,6 +364,7 @@ impl Config {
             sendgrid_template_bn_transfer_tickets,
             sendgrid_template_bn_password_reset,
             sendgrid_template_bn_user_invite,
+            sendgrid_webhook_token,
             spotify_auth_token,
             twilio_api_key,
             twilio_account_id,
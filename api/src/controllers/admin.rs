@@ -1,8 +1,12 @@
-use actix_web::HttpResponse;
+use actix_web::{http::StatusCode, HttpResponse, Query, State};
 use auth::user::User as AuthUser;
-use bigneon_db::models::{DomainAction, Report, Scopes};
+use bigneon_db::models::*;
 use db::Connection;
+use domain_events::executors::reconcile_blockchain_assets;
 use errors::*;
+use models::WebPayload;
+use server::AppState;
+use uuid::Uuid;
 
 pub fn admin_ticket_count(
     (connection, user): (Connection, AuthUser),
@@ -25,3 +29,78 @@ pub fn admin_stuck_domain_actions(
     let result = DomainAction::find_stuck(connection)?;
     Ok(HttpResponse::Ok().json(result))
 }
+
+#[derive(Debug, Serialize)]
+pub struct AssetBlockchainStatus {
+    #[serde(flatten)]
+    pub summary: AssetSyncSummary,
+    /// Supply reported by the blockchain, `None` if the asset is not on chain or could not be read
+    pub blockchain_supply: Option<u64>,
+    pub blockchain_error: Option<String>,
+    /// True when the blockchain does not match the tickets issued locally
+    pub drifted: bool,
+}
+
+/// Compares each asset's ticket instances with its state on the blockchain. Filter by event
+/// with the `event_id` tag.
+pub fn admin_blockchain_assets(
+    (connection, query, user, state): (
+        Connection,
+        Query<PagingParameters>,
+        AuthUser,
+        State<AppState>,
+    ),
+) -> Result<WebPayload<AssetBlockchainStatus>, BigNeonError> {
+    let connection = connection.get();
+    //Check if they have org admin permissions
+    user.requires_scope(Scopes::OrgAdmin)?;
+    let event_id = match query.get_tag("event_id") {
+        Some(event_id) => Some(event_id.parse::<Uuid>()?),
+        None => None,
+    };
+
+    let summaries = Asset::sync_summaries(event_id, query.page(), query.limit(), connection)?;
+    let mut data = Vec::new();
+    for summary in summaries.data {
+        let mut blockchain_supply = None;
+        let mut blockchain_error = None;
+        if let Some(ref blockchain_asset_id) = summary.blockchain_asset_id {
+            let event = Event::find(summary.event_id, connection)?;
+            let wallet = event.issuer_wallet(connection)?;
+            match state.config.tari_client.get_asset_info(
+                &wallet.secret_key,
+                &wallet.public_key,
+                blockchain_asset_id,
+            ) {
+                Ok(asset_info) => blockchain_supply = Some(asset_info.total_supply),
+                Err(e) => blockchain_error = Some(e.to_string()),
+            }
+        }
+        let drifted = summary.failed_sync_count > 0
+            || blockchain_supply
+                .map(|supply| supply < summary.ticket_count as u64)
+                .unwrap_or(summary.pending_sync_count == 0);
+        data.push(AssetBlockchainStatus {
+            summary,
+            blockchain_supply,
+            blockchain_error,
+            drifted,
+        });
+    }
+
+    let mut payload = Payload::new(data, summaries.paging);
+    payload.paging.tags = query.tags.clone();
+    Ok(WebPayload::new(StatusCode::OK, payload))
+}
+
+/// Queues a reconciliation run, which requeues failed blockchain syncs and repairs assets that
+/// are missing on chain or have drifted
+pub fn admin_reconcile_blockchain_assets(
+    (connection, user): (Connection, AuthUser),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    //Check if they have org admin permissions
+    user.requires_scope(Scopes::OrgAdmin)?;
+    let action = reconcile_blockchain_assets::queue_reconciliation(None, connection)?;
+    Ok(HttpResponse::Accepted().json(json!({ "domain_action_id": action.id })))
+}
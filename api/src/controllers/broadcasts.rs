@@ -10,6 +10,7 @@ use errors::BigNeonError;
 use extractors::Json;
 use models::{PathParameters, WebPayload};
 use reqwest::StatusCode;
+use uuid::Uuid;
 
 #[derive(Deserialize, Serialize)]
 pub struct NewBroadcastData {
@@ -19,6 +20,9 @@ pub struct NewBroadcastData {
     pub send_at: Option<NaiveDateTime>,
     pub message: Option<String>,
     pub channel: Option<BroadcastChannel>,
+    /// Sends to the fans in this segment of the organization instead of the default audience
+    #[serde(default)]
+    pub fan_segment_id: Option<Uuid>,
 }
 
 pub fn create(
@@ -33,7 +37,7 @@ pub fn create(
     let organization = Organization::find_for_event(path.id, connection)?;
 
     user.requires_scope_for_organization(Scopes::EventBroadcast, &organization, connection)?;
-    let push_notification = Broadcast::create(
+    let mut push_notification = Broadcast::create(
         path.id,
         json.notification_type.clone(),
         json.channel
@@ -45,8 +49,9 @@ pub fn create(
         json.message.clone(),
         json.send_at.clone(),
         None,
-    )
-    .commit(connection)?;
+    );
+    push_notification.fan_segment_id = json.fan_segment_id;
+    let push_notification = push_notification.commit(connection)?;
 
     Ok(HttpResponse::Created().json(json!(push_notification)))
 }
@@ -6,6 +6,7 @@ use bigneon_db::models::User as DbUser;
 use bigneon_db::models::*;
 use bigneon_db::utils::errors::Optional;
 use bigneon_db::utils::rand::random_alpha_string;
+use chrono::Utc;
 use config::Config;
 use db::Connection;
 use diesel::pg::PgConnection;
@@ -17,11 +18,11 @@ use log::Level::Debug;
 use log::Level::Info;
 use models::RequestInfo;
 use payments::AuthThenCompletePaymentBehavior;
+use payments::ChargeAuthResult;
 use payments::PaymentProcessor;
 use payments::PaymentProcessorBehavior;
 use payments::RedirectToPaymentPageBehavior;
 use server::AppState;
-use std::collections::HashMap;
 use utils::ServiceLocator;
 use uuid::Uuid;
 
@@ -206,6 +207,10 @@ pub fn show((connection, user): (Connection, User)) -> Result<HttpResponse, BigN
 #[derive(Deserialize)]
 pub struct CheckoutCartRequest {
     pub method: PaymentRequest,
+    /// Whether the buyer agreed to marketing from the organizations in the cart, left
+    /// unchanged when not asked
+    #[serde(default)]
+    pub marketing_consent: Option<bool>,
 }
 
 #[derive(Deserialize)]
@@ -236,6 +241,8 @@ pub enum PaymentRequest {
     PaymentMethod {
         #[serde(default, deserialize_with = "deserialize_unless_blank")]
         provider: Option<PaymentProviders>,
+        #[serde(default)]
+        payment_method_id: Option<Uuid>,
     },
     // Only for 0 amount carts
     Free,
@@ -286,31 +293,20 @@ pub fn checkout(
         );
     }
 
-    let order_items = order.items(connection.get())?;
-
-    //Assemble token ids and ticket instance ids for each asset in the order
-    let mut tokens_per_asset: HashMap<Uuid, Vec<u64>> = HashMap::new();
-    let mut wallet_id_per_asset: HashMap<Uuid, Uuid> = HashMap::new();
-    for oi in &order_items {
-        let tickets = TicketInstance::find_for_order_item(oi.id, connection.get())?;
-        for ticket in &tickets {
-            tokens_per_asset
-                .entry(ticket.asset_id)
-                .or_insert_with(|| Vec::new())
-                .push(ticket.token_id as u64);
-            wallet_id_per_asset
-                .entry(ticket.asset_id)
-                .or_insert(ticket.wallet_id);
-        }
-    }
-    info!("CART: Verifying asset");
-    //Just confirming that the asset is setup correctly before proceeding to payment.
-    for asset_id in tokens_per_asset.keys() {
-        let asset = Asset::find(*asset_id, connection.get())?;
-        if asset.blockchain_asset_id.is_none() {
-            return application::internal_server_error(
-                "Could not complete this checkout because the asset has not been assigned on the blockchain",
-            );
+    // Box office sales are made on behalf of someone else, who was not asked for consent
+    let is_box_office_sale = match req.method {
+        PaymentRequest::External { .. } => true,
+        _ => false,
+    };
+    if let (Some(consented), false) = (req.marketing_consent, is_box_office_sale) {
+        for organization in order.organizations(connection.get())? {
+            MarketingConsent::record(
+                user.id(),
+                organization.id,
+                consented,
+                MarketingConsentSources::Checkout,
+                connection.get(),
+            )?;
         }
     }
 
@@ -343,16 +339,39 @@ pub fn checkout(
                 &request_info,
             )?
         }
-        PaymentRequest::PaymentMethod { provider } => {
+        PaymentRequest::PaymentMethod {
+            provider,
+            payment_method_id,
+        } => {
             info!("CART: Received provider payment");
-            let provider: PaymentProviders = match provider {
-                Some(provider) => provider.clone(),
-                None => match user
+            let payment_method = match (payment_method_id, provider) {
+                (Some(payment_method_id), _) => {
+                    let payment_method = PaymentMethod::find(*payment_method_id, connection.get())?;
+                    if payment_method.user_id != user.id() {
+                        return application::forbidden(
+                            "This payment method does not belong to you",
+                        );
+                    }
+                    payment_method
+                }
+                (None, Some(provider)) => match user
+                    .user
+                    .payment_method(*provider, connection.get())
+                    .optional()?
+                {
+                    Some(payment_method) => payment_method,
+                    None => {
+                        return application::unprocessable(
+                            "Could not complete this cart because stored provider does not exist",
+                        );
+                    }
+                },
+                (None, None) => match user
                     .user
                     .default_payment_method(connection.get())
                     .optional()?
                 {
-                    Some(payment_method) => payment_method.name,
+                    Some(payment_method) => payment_method,
                     None => {
                         return application::unprocessable(
                             "Could not complete this cart because user has no default payment method",
@@ -367,8 +386,8 @@ pub fn checkout(
                 None,
                 &user,
                 &state.config.primary_currency,
-                provider.clone(),
-                true,
+                payment_method.name,
+                Some(&payment_method),
                 false,
                 false,
                 &state.service_locator,
@@ -383,7 +402,7 @@ pub fn checkout(
             &user,
             &state.config.primary_currency,
             *provider,
-            false,
+            None,
             false,
             false,
             &state.service_locator,
@@ -402,7 +421,7 @@ pub fn checkout(
             &user,
             &state.config.primary_currency,
             *provider,
-            false,
+            None,
             *save_payment_method,
             *set_default,
             &state.service_locator,
@@ -511,7 +530,7 @@ fn checkout_payment_processor(
     auth_user: &User,
     currency: &str,
     provider: PaymentProviders,
-    use_stored_payment: bool,
+    stored_payment_method: Option<&PaymentMethod>,
     save_payment_method: bool,
     set_default: bool,
     service_locator: &ServiceLocator,
@@ -551,20 +570,9 @@ fn checkout_payment_processor(
             return redirect_to_payment_page(&*behavior, &auth_user.user, order, conn.get(), config);
         }
         PaymentProcessorBehavior::AuthThenComplete(behavior) => {
-            let token = if use_stored_payment {
+            let token = if let Some(payment_method) = stored_payment_method {
                 info!("CART: Using stored payment");
-                match auth_user
-                    .user
-                    .payment_method(provider, connection)
-                    .optional()?
-                {
-                    Some(payment_method) => payment_method.provider,
-                    None => {
-                        return application::unprocessable(
-                            "Could not complete this cart because stored provider does not exist",
-                        );
-                    }
-                }
+                payment_method.provider.clone()
             } else {
                 info!("CART: Not using stored payment");
                 let token = match token {
@@ -578,54 +586,17 @@ fn checkout_payment_processor(
 
                 if save_payment_method {
                     info!("CART: User has requested to save the payment method");
-                    match auth_user
-                        .user
-                        .payment_method(provider, connection)
-                        .optional()?
-                    {
-                        Some(payment_method) => {
-                            let behavior = match client.behavior() {
-                        PaymentProcessorBehavior::AuthThenComplete(b) => b,
-                        _ =>  return application::unprocessable(
-                            "Could not complete this cart using saved payment methods is not supported for this payment processor",
-                        )
-                    };
-                            let client_response = behavior.update_repeat_token(
-                                &payment_method.provider,
-                                token,
-                                "Big Neon something",
-                            )?;
-                            let payment_method_parameters = PaymentMethodEditableAttributes {
-                                provider_data: Some(client_response.to_json()?),
-                            };
-                            payment_method.update(
-                                &payment_method_parameters,
-                                auth_user.id(),
-                                connection,
-                            )?;
-
-                            payment_method.provider
-                        }
-                        None => {
-                            let behavior = match client.behavior() {
-                        PaymentProcessorBehavior::AuthThenComplete(b) => b,
-                        _ =>  return application::unprocessable(
-                            "Could not complete this cart using saved payment methods is not supported for this payment processor",
-                        )
-                    };
-                            let repeat_token =
-                                behavior.create_token_for_repeat_charges(token, "Big Neon")?;
-                            let _payment_method = PaymentMethod::create(
-                                auth_user.id(),
-                                provider,
-                                set_default,
-                                repeat_token.token.clone(),
-                                repeat_token.to_json()?,
-                            )
-                            .commit(auth_user.id(), connection)?;
-                            repeat_token.token
-                        }
-                    }
+                    let repeat_token =
+                        behavior.create_token_for_repeat_charges(token, "Big Neon")?;
+                    PaymentMethod::create(
+                        auth_user.id(),
+                        provider,
+                        set_default,
+                        repeat_token.token.clone(),
+                        repeat_token.to_json()?,
+                    )
+                    .commit(auth_user.id(), connection)?;
+                    repeat_token.token
                 } else {
                     token.to_string()
                 }
@@ -658,9 +629,11 @@ fn auth_then_complete(
     let connection = conn.get();
     info!("CART: Auth'ing to payment provider");
     let amount = order.calculate_total(connection)?;
+    let application_fee = order.company_fees_in_cents(connection)?;
     let auth_result = client.auth(
         &token,
         amount,
+        application_fee,
         currency,
         "Big Neon Tickets",
         order.purchase_metadata(connection)?,
@@ -672,7 +645,12 @@ fn auth_then_complete(
         amount,
         client.payment_provider(),
         auth_result.id.clone(),
-        PaymentStatus::Authorized,
+        // Payments awaiting customer action must not clear the cart
+        if auth_result.requires_action {
+            PaymentStatus::Requested
+        } else {
+            PaymentStatus::Authorized
+        },
         auth_result.to_json()?,
         connection,
     ) {
@@ -683,16 +661,45 @@ fn auth_then_complete(
         }
     };
 
+    if auth_result.requires_action {
+        info!("CART: Payment requires customer action");
+        payment.mark_requires_action(auth_result.to_json()?, Some(auth_user.id()), connection)?;
+        return payment_action_required(order.id, &auth_result, auth_user, connection);
+    }
+
+    complete_authed_payment(
+        client,
+        payment,
+        &auth_result.id,
+        order.id,
+        auth_user,
+        conn,
+        payment_processor,
+        request_info,
+    )
+}
+
+fn complete_authed_payment(
+    client: &AuthThenCompletePaymentBehavior,
+    payment: Payment,
+    auth_token: &str,
+    order_id: Uuid,
+    auth_user: &User,
+    conn: &Connection,
+    payment_processor: &PaymentProcessor,
+    request_info: &RequestInfo,
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = conn.get();
     conn.commit_transaction()?;
     conn.begin_transaction()?;
 
     info!("CART: Completing auth with payment provider");
-    let charge_result = client.complete_authed_charge(&auth_result.id)?;
+    let charge_result = client.complete_authed_charge(auth_token)?;
     info!("CART: Completing payment on order");
     info!("charge_result:{:?}", charge_result);
     match payment.mark_complete(charge_result.to_json()?, Some(auth_user.id()), connection) {
         Ok(_) => {
-            let mut order = Order::find(order.id, connection)?;
+            let mut order = Order::find(order_id, connection)?;
             order.set_user_agent(request_info.user_agent.clone(), true, connection)?;
             Ok(HttpResponse::Ok().json(json!(order.for_display(
                 None,
@@ -701,12 +708,133 @@ fn auth_then_complete(
             )?)))
         }
         Err(e) => {
-            payment_processor.refund(&auth_result.id)?;
+            payment_processor.refund(auth_token)?;
             Err(e.into())
         }
     }
 }
 
+#[derive(Serialize)]
+pub struct PaymentActionRequiredResponse {
+    #[serde(flatten)]
+    pub order: DisplayOrder,
+    pub requires_action: bool,
+    /// Used by the client to complete authentication with the payment provider
+    pub client_secret: Option<String>,
+}
+
+fn payment_action_required(
+    order_id: Uuid,
+    auth_result: &ChargeAuthResult,
+    auth_user: &User,
+    conn: &PgConnection,
+) -> Result<HttpResponse, BigNeonError> {
+    let order = Order::find(order_id, conn)?;
+    Ok(HttpResponse::Ok().json(PaymentActionRequiredResponse {
+        order: order.for_display(None, auth_user.id(), conn)?,
+        requires_action: true,
+        client_secret: auth_result.client_secret.clone(),
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct ConfirmCheckoutRequest {
+    pub order_id: Uuid,
+}
+
+/// Completes a checkout once the customer has finished the action requested by the payment
+/// provider during checkout, e.g. a 3-D Secure challenge
+pub fn confirm_checkout(
+    (connection, json, user, state, request_info): (
+        Connection,
+        Json<ConfirmCheckoutRequest>,
+        User,
+        State<AppState>,
+        RequestInfo,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let conn = connection.get();
+    let mut order = Order::find(json.order_id, conn)?;
+    if order.user_id != user.id() {
+        return application::forbidden("This cart does not belong to you");
+    } else if order.status != OrderStatus::PendingPayment {
+        return application::unprocessable(
+            "Could not confirm this cart because it is not awaiting payment",
+        );
+    } else if order
+        .expires_at
+        .map(|e| e < Utc::now().naive_utc())
+        .unwrap_or(false)
+    {
+        return application::unprocessable("Could not confirm this cart because it has expired");
+    }
+
+    let payment = match order
+        .payments(conn)?
+        .into_iter()
+        .find(|p| p.status == PaymentStatus::RequiresAction)
+    {
+        Some(p) => p,
+        None => {
+            return application::unprocessable(
+                "Could not confirm this cart because no payment is awaiting confirmation",
+            );
+        }
+    };
+    let auth_token = match payment.external_reference.clone() {
+        Some(r) => r,
+        None => return application::internal_server_error("Payment has no external reference"),
+    };
+
+    let mut organizations = order.organizations(conn)?;
+    if organizations.len() != 1 {
+        return application::unprocessable(
+            "Can't currently handle more than one organization at the moment",
+        );
+    }
+    let payment_processor = state
+        .service_locator
+        .create_payment_processor(payment.provider, &organizations.remove(0))?;
+    let client = match payment_processor.behavior() {
+        PaymentProcessorBehavior::AuthThenComplete(behavior) => behavior,
+        PaymentProcessorBehavior::RedirectToPaymentPage(_) => {
+            return application::unprocessable(
+                "Payment provider does not support confirming payments",
+            );
+        }
+    };
+
+    info!("CART: Confirming payment with payment provider");
+    let auth_result = match client.confirm_auth(&auth_token) {
+        Ok(r) => r,
+        Err(e) => {
+            // Return the order to the cart so the customer can try another payment method
+            payment.mark_cancelled(json!({ "error": e.to_string() }), Some(user.id()), conn)?;
+            order.reset_to_draft(Some(user.id()), conn)?;
+            connection.commit_transaction()?;
+            connection.begin_transaction()?;
+            return Err(e.into());
+        }
+    };
+
+    if auth_result.requires_action {
+        payment.mark_requires_action(auth_result.to_json()?, Some(user.id()), conn)?;
+        return payment_action_required(order.id, &auth_result, &user, conn);
+    }
+
+    payment.mark_authorized(auth_result.to_json()?, Some(user.id()), conn)?;
+    complete_authed_payment(
+        &*client,
+        payment,
+        &auth_token,
+        order.id,
+        &user,
+        &connection,
+        &*payment_processor,
+        &request_info,
+    )
+}
+
 fn redirect_to_payment_page(
     client: &RedirectToPaymentPageBehavior,
     user: &DbUser,
@@ -722,7 +850,8 @@ fn redirect_to_payment_page(
 
     let email = user.email.as_ref().unwrap().to_string();
 
-    let ipn = Some(format!("{}/ipns/globee", config.api_base_url));
+    let provider_name = client.payment_provider().to_string().to_lowercase();
+    let ipn = Some(format!("{}/ipns/{}", config.api_base_url, provider_name));
 
     let nonce = random_alpha_string(12);
     let response = client.create_payment_request(
@@ -749,7 +878,7 @@ fn redirect_to_payment_page(
         conn,
     )?;
 
-    let external_reference = format!("globee-{}", response.id);
+    let external_reference = format!("{}-{}", provider_name, response.id);
 
     order.add_provider_payment(
         Some(external_reference),
@@ -22,6 +22,16 @@ pub struct CreateCodeRequest {
     pub end_date: NaiveDateTime,
     pub max_tickets_per_user: Option<u32>,
     pub ticket_type_ids: Vec<Uuid>,
+    #[serde(default)]
+    pub min_quantity: Option<u32>,
+    #[serde(default)]
+    pub buy_quantity: Option<u32>,
+    #[serde(default)]
+    pub get_quantity: Option<u32>,
+    #[serde(default)]
+    pub max_order_discount_in_cents: Option<u32>,
+    #[serde(default)]
+    pub waive_fees: bool,
 }
 
 #[derive(Clone, Default, Deserialize, Serialize)]
@@ -38,6 +48,15 @@ pub struct UpdateCodeRequest {
     #[serde(default, deserialize_with = "double_option::deserialize")]
     pub max_tickets_per_user: Option<Option<u32>>,
     pub ticket_type_ids: Option<Vec<Uuid>>,
+    #[serde(default, deserialize_with = "double_option::deserialize")]
+    pub min_quantity: Option<Option<u32>>,
+    #[serde(default, deserialize_with = "double_option::deserialize")]
+    pub buy_quantity: Option<Option<u32>>,
+    #[serde(default, deserialize_with = "double_option::deserialize")]
+    pub get_quantity: Option<Option<u32>>,
+    #[serde(default, deserialize_with = "double_option::deserialize")]
+    pub max_order_discount_in_cents: Option<Option<u32>>,
+    pub waive_fees: Option<bool>,
 }
 
 impl From<UpdateCodeRequest> for UpdateCodeAttributes {
@@ -55,6 +74,13 @@ impl From<UpdateCodeRequest> for UpdateCodeAttributes {
             max_tickets_per_user: attributes
                 .max_tickets_per_user
                 .map(|m| m.map(|m2| m2 as i64)),
+            min_quantity: attributes.min_quantity.map(|m| m.map(|m2| m2 as i64)),
+            buy_quantity: attributes.buy_quantity.map(|b| b.map(|b2| b2 as i64)),
+            get_quantity: attributes.get_quantity.map(|g| g.map(|g2| g2 as i64)),
+            max_order_discount_in_cents: attributes
+                .max_order_discount_in_cents
+                .map(|m| m.map(|m2| m2 as i64)),
+            waive_fees: attributes.waive_fees,
         }
     }
 }
@@ -91,7 +117,7 @@ pub fn create(
         conn,
     )?;
 
-    let code = Code::create(
+    let mut new_code = Code::create(
         req.name.clone(),
         path.id,
         req.code_type,
@@ -102,8 +128,13 @@ pub fn create(
         req.start_date,
         req.end_date,
         req.max_tickets_per_user,
-    )
-    .commit(conn)?;
+    );
+    new_code.min_quantity = req.min_quantity.map(|m| m as i64);
+    new_code.buy_quantity = req.buy_quantity.map(|b| b as i64);
+    new_code.get_quantity = req.get_quantity.map(|g| g as i64);
+    new_code.max_order_discount_in_cents = req.max_order_discount_in_cents.map(|m| m as i64);
+    new_code.waive_fees = req.waive_fees;
+    let code = new_code.commit(conn)?;
 
     code.update_ticket_types(req.ticket_type_ids.clone(), conn)?;
     application::created(json!(code.for_display(conn)?))
@@ -127,7 +158,7 @@ pub fn update(
         conn,
     )?;
 
-    let code = code.update(req.clone().into(), conn)?;
+    let code = code.update(Some(user.id()), req.clone().into(), conn)?;
 
     if let Some(ref ticket_type_ids) = req.ticket_type_ids {
         code.update_ticket_types(ticket_type_ids.clone(), conn)?;
@@ -1,12 +1,19 @@
-use actix_web::{http::StatusCode, HttpResponse, Path, Query};
+use actix_web::{http::StatusCode, HttpResponse, Path, Query, State};
 use auth::user::User;
+use bigneon_db::models::User as DbUser;
 use bigneon_db::models::*;
 use chrono::prelude::*;
+use communications::{mailers, pushers, smsers};
 use controllers::holds::UpdateHoldRequest;
 use db::Connection;
-use errors::BigNeonError;
+use diesel::pg::PgConnection;
+use errors::*;
 use extractors::*;
+use helpers::application;
 use models::{PathParameters, WebPayload, WebResult};
+use server::AppState;
+use utils::csv;
+use uuid::Uuid;
 
 pub fn index(
     (conn, path, query_parameters, user): (
@@ -28,15 +35,12 @@ pub fn index(
         conn,
     )?;
 
-    let mut list = Vec::<DisplayHold>::new();
-    for hold in comps.data {
-        if hold.status == HoldStatus::Deleted {
-            continue;
-        }
-        let r = hold.into_display(conn)?;
-
-        list.push(r);
-    }
+    let comps_data = comps
+        .data
+        .into_iter()
+        .filter(|hold| hold.status != HoldStatus::Deleted)
+        .collect();
+    let list = Hold::into_displays(comps_data, conn)?;
 
     let payload = Payload::new(list, comps.paging);
 
@@ -92,6 +96,188 @@ pub fn create(
     ))
 }
 
+#[derive(Default, Deserialize, Serialize)]
+pub struct IssueCompRequest {
+    pub name: String,
+    #[serde(default, deserialize_with = "deserialize_unless_blank")]
+    pub email: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_unless_blank")]
+    pub phone: Option<String>,
+    pub quantity: u32,
+    #[serde(default, deserialize_with = "deserialize_unless_blank")]
+    pub redemption_code: Option<String>,
+}
+
+/// Guest list with a header row containing `name`, `quantity` and `email` and/or `phone`
+/// columns, a `redemption_code` column is optional
+#[derive(Default, Deserialize, Serialize)]
+pub struct BulkIssueCompsRequest {
+    pub csv: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct IssuedCompResponse {
+    pub comp: DisplayHold,
+    pub order_id: Uuid,
+    pub recipient_user_id: Uuid,
+    pub transfer_key: Uuid,
+}
+
+/// Creates a comp and delivers its tickets straight to the recipient
+pub fn issue(
+    (conn, req, path, user, state): (
+        Connection,
+        Json<IssueCompRequest>,
+        Path<PathParameters>,
+        User,
+        State<AppState>,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let conn = conn.get();
+    let hold = Hold::find(path.id, conn)?;
+    let organization = hold.organization(conn)?;
+    user.requires_scope_for_organization(Scopes::CompWrite, &organization, conn)?;
+
+    let issued_comp = issue_comp(&hold, req.into_inner(), &user, &organization, &state, conn)?;
+    application::created(json!(issued_comp))
+}
+
+pub fn issue_bulk(
+    (conn, req, path, user, state): (
+        Connection,
+        Json<BulkIssueCompsRequest>,
+        Path<PathParameters>,
+        User,
+        State<AppState>,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let conn = conn.get();
+    let hold = Hold::find(path.id, conn)?;
+    let organization = hold.organization(conn)?;
+    user.requires_scope_for_organization(Scopes::CompWrite, &organization, conn)?;
+
+    // Check the whole guest list before issuing anything
+    let mut requests = Vec::new();
+    for (index, row) in csv::parse(&req.csv)?.into_iter().enumerate() {
+        let field = |name: &str| {
+            row.get(name)
+                .filter(|value| !value.is_empty())
+                .map(|value| value.to_string())
+        };
+        let name = match field("name") {
+            Some(name) => name,
+            None => {
+                return application::unprocessable(&format!(
+                    "Guest {} is missing a name",
+                    index + 1
+                ));
+            }
+        };
+        let quantity = match field("quantity").and_then(|q| q.parse::<u32>().ok()) {
+            Some(quantity) if quantity > 0 => quantity,
+            _ => {
+                return application::unprocessable(&format!(
+                    "Guest {} does not have a valid quantity",
+                    index + 1
+                ));
+            }
+        };
+        requests.push(IssueCompRequest {
+            name,
+            email: field("email"),
+            phone: field("phone"),
+            quantity,
+            redemption_code: field("redemption_code"),
+        });
+    }
+    if requests.is_empty() {
+        return application::unprocessable("Guest list is empty");
+    }
+
+    let mut issued_comps = Vec::new();
+    for request in requests {
+        issued_comps.push(issue_comp(
+            &hold,
+            request,
+            &user,
+            &organization,
+            &state,
+            conn,
+        )?);
+    }
+    application::created(json!(issued_comps))
+}
+
+fn issue_comp(
+    hold: &Hold,
+    request: IssueCompRequest,
+    user: &User,
+    organization: &Organization,
+    state: &AppState,
+    conn: &PgConnection,
+) -> Result<IssuedCompResponse, BigNeonError> {
+    let issued_comp = hold.issue_comp(
+        user.id(),
+        request.name,
+        request.email,
+        request.phone,
+        request.quantity,
+        request.redemption_code,
+        conn,
+    )?;
+    send_issued_comp(&issued_comp, &user.user, organization, state, conn)?;
+
+    Ok(IssuedCompResponse {
+        order_id: issued_comp.order.id,
+        recipient_user_id: issued_comp.recipient.id,
+        transfer_key: issued_comp.transfer_authorization.transfer_key,
+        comp: issued_comp.comp.into_display(conn)?,
+    })
+}
+
+/// Tells the recipient about their tickets with the usual ticket transfer email or SMS, which
+/// lets recipients without an account claim them. Recipients with an account are also sent a
+/// push notification.
+fn send_issued_comp(
+    issued_comp: &IssuedComp,
+    from_user: &DbUser,
+    organization: &Organization,
+    state: &AppState,
+    conn: &PgConnection,
+) -> Result<(), BigNeonError> {
+    if !issued_comp.recipient_created {
+        pushers::tickets_received(&issued_comp.recipient, from_user, conn)?;
+    }
+
+    let authorization = &issued_comp.transfer_authorization;
+    if let Some(ref email) = issued_comp.comp.email {
+        mailers::tickets::send_tickets(
+            &state.config,
+            email.clone(),
+            &authorization.sender_user_id.to_string(),
+            authorization.num_tickets,
+            &authorization.transfer_key.to_string(),
+            &authorization.signature,
+            from_user,
+            Some(organization),
+            conn,
+        )?;
+    } else if let Some(ref phone) = issued_comp.comp.phone {
+        smsers::tickets::send_tickets(
+            &state.config,
+            phone.clone(),
+            &authorization.sender_user_id.to_string(),
+            authorization.num_tickets,
+            &authorization.transfer_key.to_string(),
+            &authorization.signature,
+            from_user,
+            conn,
+            &*state.service_locator.create_deep_linker()?,
+        )?;
+    }
+    Ok(())
+}
+
 pub fn update(
     (conn, req, path, user): (
         Connection,
@@ -106,7 +292,7 @@ pub fn update(
     user.requires_scope_for_organization(Scopes::CompWrite, &comp.organization(conn)?, conn)?;
     let req = req.into_inner();
     let quantity = req.quantity;
-    let hold = comp.update(req.into(), conn)?;
+    let hold = comp.update(Some(user.id()), req.into(), conn)?;
     if quantity.is_some() {
         hold.set_quantity(Some(user.id()), quantity.unwrap(), conn)?;
     }
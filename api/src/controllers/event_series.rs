@@ -0,0 +1,151 @@
+use actix_web::{HttpResponse, Path};
+use auth::user::User as AuthUser;
+use bigneon_db::prelude::*;
+use controllers::ticket_types;
+use db::Connection;
+use diesel::PgConnection;
+use errors::*;
+use extractors::*;
+use helpers::application;
+use models::PathParameters;
+
+#[derive(Deserialize, Serialize)]
+pub struct CreateEventSeriesRequest {
+    pub name: String,
+    pub recurrence_rule: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct EventSeriesResponse {
+    pub event_series: EventSeries,
+    pub events: Vec<Event>,
+}
+
+pub fn index(
+    (connection, path, user): (Connection, Path<PathParameters>, AuthUser),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let organization = Organization::find(path.id, connection)?;
+    user.requires_scope_for_organization(Scopes::OrgReadEvents, &organization, connection)?;
+
+    let event_series = EventSeries::find_for_organization(organization.id, connection)?;
+    Ok(HttpResponse::Ok().json(&event_series))
+}
+
+/// Creates a series from an existing event and generates its occurrences
+pub fn create(
+    (connection, path, json, user): (
+        Connection,
+        Path<PathParameters>,
+        Json<CreateEventSeriesRequest>,
+        AuthUser,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let event = Event::find(path.id, connection)?;
+    let organization = event.organization(connection)?;
+    user.requires_scope_for_organization_event(
+        Scopes::EventWrite,
+        &organization,
+        &event,
+        connection,
+    )?;
+
+    let event_series = EventSeries::create(&event, json.name.clone(), json.recurrence_rule.clone())
+        .commit(Some(user.id()), connection)?;
+    generate_events(&event_series, &user, connection)?;
+
+    application::created(json!(EventSeriesResponse {
+        events: event_series.events(connection)?,
+        event_series,
+    }))
+}
+
+pub fn show(
+    (connection, path, user): (Connection, Path<PathParameters>, AuthUser),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let event_series = EventSeries::find(path.id, connection)?;
+    let organization = event_series.organization(connection)?;
+    user.requires_scope_for_organization(Scopes::OrgReadEvents, &organization, connection)?;
+
+    Ok(HttpResponse::Ok().json(EventSeriesResponse {
+        events: event_series.events(connection)?,
+        event_series,
+    }))
+}
+
+pub fn update(
+    (connection, path, json, user): (
+        Connection,
+        Path<PathParameters>,
+        Json<EventSeriesEditableAttributes>,
+        AuthUser,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let event_series = EventSeries::find(path.id, connection)?;
+    let organization = event_series.organization(connection)?;
+    user.requires_scope_for_organization(Scopes::EventWrite, &organization, connection)?;
+
+    let event_series = event_series.update(Some(user.id()), json.into_inner(), connection)?;
+    Ok(HttpResponse::Ok().json(&event_series))
+}
+
+/// Removes the series, its events are kept
+pub fn destroy(
+    (connection, path, user): (Connection, Path<PathParameters>, AuthUser),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let event_series = EventSeries::find(path.id, connection)?;
+    let organization = event_series.organization(connection)?;
+    user.requires_scope_for_organization(Scopes::EventWrite, &organization, connection)?;
+
+    event_series.destroy(Some(user.id()), connection)?;
+    Ok(HttpResponse::Ok().json(json!({})))
+}
+
+/// Generates any occurrences that are missing, e.g. after the recurrence rule was extended
+pub fn generate(
+    (connection, path, user): (Connection, Path<PathParameters>, AuthUser),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let event_series = EventSeries::find(path.id, connection)?;
+    let organization = event_series.organization(connection)?;
+    user.requires_scope_for_organization(Scopes::EventWrite, &organization, connection)?;
+
+    let events = generate_events(&event_series, &user, connection)?;
+    application::created(json!(events))
+}
+
+/// Bulk edits all occurrences of the series that have not started yet
+pub fn update_events(
+    (connection, path, json, user): (
+        Connection,
+        Path<PathParameters>,
+        Json<EventEditableAttributes>,
+        AuthUser,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let event_series = EventSeries::find(path.id, connection)?;
+    let organization = event_series.organization(connection)?;
+    user.requires_scope_for_organization(Scopes::EventWrite, &organization, connection)?;
+
+    let events =
+        event_series.update_future_events(Some(user.id()), json.into_inner(), connection)?;
+    Ok(HttpResponse::Ok().json(&events))
+}
+
+fn generate_events(
+    event_series: &EventSeries,
+    user: &AuthUser,
+    connection: &PgConnection,
+) -> Result<Vec<Event>, BigNeonError> {
+    let events = event_series.generate_events(Some(user.id()), connection)?;
+    for event in events.iter() {
+        ticket_types::create_tari_assets(event, connection)?;
+    }
+
+    Ok(events)
+}
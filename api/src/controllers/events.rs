@@ -1,9 +1,10 @@
-use actix_web::{http::StatusCode, HttpResponse, Path, Query, State};
+use actix_web::{http::header, http::StatusCode, HttpResponse, Path, Query, State};
 use auth::user::User as AuthUser;
 use bigneon_db::prelude::*;
 use chrono::prelude::*;
 use chrono::Duration;
 use controllers::organizations::DisplayOrganizationUser;
+use controllers::ticket_types;
 use db::Connection;
 use diesel::PgConnection;
 use errors::*;
@@ -14,7 +15,7 @@ use serde_json::Value;
 use serde_with::{self, CommaSeparator};
 use server::AppState;
 use std::collections::HashMap;
-use utils::{marketing_contacts, ServiceLocator};
+use utils::{csv, marketing_contacts, ServiceLocator};
 use uuid::Uuid;
 
 #[derive(Deserialize)]
@@ -37,6 +38,25 @@ pub struct SearchParameters {
     sort: Option<String>,
     dir: Option<SortingDir>,
     past_or_upcoming: Option<String>,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+    radius_in_km: Option<f64>,
+    min_price_in_cents: Option<i64>,
+    max_price_in_cents: Option<i64>,
+    #[serde(default, deserialize_with = "deserialize_unless_blank")]
+    age_limit: Option<String>,
+    event_type: Option<EventTypes>,
+    availability: Option<EventAvailability>,
+    /// Include counts by event type, age limit and availability in the response
+    facets: Option<bool>,
+}
+
+#[derive(Serialize)]
+struct EventSearchPayload {
+    #[serde(flatten)]
+    payload: Payload<EventVenueEntry>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    facets: Option<EventSearchFacets>,
 }
 
 #[derive(Serialize)]
@@ -155,10 +175,18 @@ pub fn index(
     {
         "event_start" => EventSearchSortField::EventStart,
         "name" => EventSearchSortField::Name,
+        "relevance" => EventSearchSortField::Relevance,
+        "distance" => EventSearchSortField::Distance,
         _ => EventSearchSortField::EventStart,
     };
+    // Most relevant events are listed first unless asked otherwise
+    let default_dir = if sort_field == EventSearchSortField::Relevance {
+        SortingDir::Desc
+    } else {
+        SortingDir::Asc
+    };
 
-    let events = Event::search(
+    let (events, facets) = Event::search_with_facets(
         query.query.clone(),
         query.region_id,
         query.organization_id,
@@ -171,9 +199,20 @@ pub fn index(
             Some(query.status.clone())
         },
         sort_field,
-        query.dir.clone().unwrap_or(SortingDir::Asc),
+        query.dir.clone().unwrap_or(default_dir),
         user.clone(),
         past_or_upcoming,
+        EventSearchFilters {
+            latitude: query.latitude,
+            longitude: query.longitude,
+            radius_in_km: query.radius_in_km,
+            min_price_in_cents: query.min_price_in_cents,
+            max_price_in_cents: query.max_price_in_cents,
+            age_limit: query.age_limit.clone(),
+            event_type: query.event_type,
+            availability: query.availability,
+        },
+        query.facets.unwrap_or(false),
         connection,
     )?;
 
@@ -184,7 +223,7 @@ pub fn index(
     payload.paging.total = payload.data.len() as u64;
     payload.paging.limit = 100;
 
-    Ok(HttpResponse::Ok().json(&payload))
+    Ok(HttpResponse::Ok().json(&EventSearchPayload { payload, facets }))
 }
 
 #[derive(Deserialize)]
@@ -468,12 +507,11 @@ pub struct TicketRedeemRequest {
 }
 
 pub fn redeem_ticket(
-    (connection, parameters, redeem_parameters, auth_user, state): (
+    (connection, parameters, redeem_parameters, auth_user): (
         Connection,
         Path<RedeemTicketPathParameters>,
         Json<TicketRedeemRequest>,
         AuthUser,
-        State<AppState>,
     ),
 ) -> Result<HttpResponse, BigNeonError> {
     let connection = connection.get();
@@ -504,19 +542,15 @@ pub fn redeem_ticket(
     match result {
         RedeemResults::TicketRedeemSuccess => {
             //Redeem ticket on chain
-            let asset = Asset::find(ticket.asset_id, connection)?;
-            match asset.blockchain_asset_id {
-                Some(a) => {
-                    let wallet = Wallet::find(ticket.wallet_id, connection)?;
-                    state.config.tari_client.modify_asset_redeem_token(&wallet.secret_key, &wallet.public_key,
-                                                                       &a,
-                                                                       vec![ticket.token_id as u64],
-                    )?;
-
-                    Ok(HttpResponse::Ok().json(redeemable))
-                }
-                None => Ok(HttpResponse::BadRequest().json(json!({ "error": "Could not complete this checkout because the asset has not been assigned on the blockchain.".to_string()}))),
-            }
+            Asset::find(ticket.asset_id, connection)?.queue_blockchain_sync(
+                BlockchainOperation::RedeemTokens {
+                    wallet_id: ticket.wallet_id,
+                    token_ids: vec![ticket.token_id as u64],
+                },
+                connection,
+            )?;
+
+            Ok(HttpResponse::Ok().json(redeemable))
         }
         RedeemResults::TicketAlreadyRedeemed => Ok(HttpResponse::Conflict()
             .json(json!({"error": "Ticket has already been redeemed.".to_string()}))),
@@ -624,6 +658,78 @@ pub fn create(
     Ok(HttpResponse::Created().json(&event))
 }
 
+#[derive(Deserialize, Serialize)]
+pub struct CloneEventRequest {
+    pub event_start: NaiveDateTime,
+    #[serde(default)]
+    pub include_codes: bool,
+}
+
+/// Copies the event, its ticket types, pricing, holds, artists and optionally its codes into a
+/// new draft event starting at `event_start`
+pub fn clone(
+    (connection, parameters, json, user): (
+        Connection,
+        Path<PathParameters>,
+        Json<CloneEventRequest>,
+        AuthUser,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let event = Event::find(parameters.id, connection)?;
+    let organization = event.organization(connection)?;
+    user.requires_scope_for_organization_event(
+        Scopes::EventWrite,
+        &organization,
+        &event,
+        connection,
+    )?;
+
+    let new_event = event.clone_event(
+        Some(user.id()),
+        json.event_start,
+        json.include_codes,
+        connection,
+    )?;
+    ticket_types::create_tari_assets(&new_event, connection)?;
+
+    Ok(HttpResponse::Created().json(&new_event))
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct RescheduleEventRequest {
+    pub event_start: NaiveDateTime,
+    /// Ticket holders can refund their own tickets until this time
+    pub refund_window_ends_at: Option<NaiveDateTime>,
+}
+
+pub fn reschedule(
+    (connection, parameters, json, user): (
+        Connection,
+        Path<PathParameters>,
+        Json<RescheduleEventRequest>,
+        AuthUser,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let event = Event::find(parameters.id, connection)?;
+    let organization = event.organization(connection)?;
+    user.requires_scope_for_organization_event(
+        Scopes::EventWrite,
+        &organization,
+        &event,
+        connection,
+    )?;
+
+    let updated_event = event.reschedule(
+        Some(user.id()),
+        json.event_start,
+        json.refund_window_ends_at,
+        connection,
+    )?;
+    Ok(HttpResponse::Ok().json(&updated_event))
+}
+
 #[derive(Deserialize, Debug, Default)]
 pub struct UpdateArtistsRequest {
     pub artist_id: Uuid,
@@ -678,6 +784,82 @@ pub fn cancel(
     Ok(HttpResponse::Ok().json(&updated_event))
 }
 
+/// Progress of the refunds queued when the event was cancelled
+pub fn refunds(
+    (connection, parameters, user): (Connection, Path<PathParameters>, AuthUser),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let event = Event::find(parameters.id, connection)?;
+    let organization = event.organization(connection)?;
+    user.requires_scope_for_organization_event(
+        Scopes::OrderRefund,
+        &organization,
+        &event,
+        connection,
+    )?;
+
+    Ok(HttpResponse::Ok().json(&event.refund_progress(connection)?))
+}
+
+/// Requeues refunds that failed and queues refunds for orders paid since the event was cancelled
+pub fn queue_refunds(
+    (connection, parameters, user): (Connection, Path<PathParameters>, AuthUser),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let event = Event::find(parameters.id, connection)?;
+    let organization = event.organization(connection)?;
+    user.requires_scope_for_organization_event(
+        Scopes::OrderRefund,
+        &organization,
+        &event,
+        connection,
+    )?;
+
+    event.queue_refunds(Some(user.id()), connection)?;
+    Ok(HttpResponse::Ok().json(&event.refund_progress(connection)?))
+}
+
+pub fn refund_failures(
+    (connection, parameters, user): (Connection, Path<PathParameters>, AuthUser),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let event = Event::find(parameters.id, connection)?;
+    let organization = event.organization(connection)?;
+    user.requires_scope_for_organization_event(
+        Scopes::OrderRefund,
+        &organization,
+        &event,
+        connection,
+    )?;
+
+    let mut csv =
+        "order_id,user_email,status,attempt_count,last_failure_reason,failed_at\n".to_string();
+    for failure in event.refund_progress(connection)?.failures {
+        let order = Order::find(failure.order_id, connection)?;
+        let user = User::find(
+            order.on_behalf_of_user_id.unwrap_or(order.user_id),
+            connection,
+        )?;
+        csv.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            failure.order_id,
+            csv::escape(&user.email.unwrap_or_default()),
+            failure.status,
+            failure.attempt_count,
+            csv::escape(&failure.last_failure_reason.unwrap_or_default()),
+            failure.failed_at,
+        ));
+    }
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/csv")
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"refund-failures-{}.csv\"", event.id),
+        )
+        .body(csv))
+}
+
 pub fn list_interested_users(
     (connection, path_parameters, query, user): (
         Connection,
@@ -904,6 +1086,7 @@ pub fn holds(
     let organization = &event.organization(conn)?;
     user.requires_scope_for_organization_event(Scopes::HoldRead, &organization, &event, conn)?;
     let holds = Hold::find_for_event(path.id, conn)?;
+    let mut analytics = CodeAnalytics::find_for_event_holds(path.id, conn)?;
     let mut ticket_type_ids: Vec<Uuid> = holds.iter().map(|h| h.ticket_type_id).collect();
     ticket_type_ids.sort();
     ticket_type_ids.dedup();
@@ -935,6 +1118,7 @@ pub fn holds(
         pub available: u32,
         pub quantity: u32,
         pub parent_hold_id: Option<Uuid>,
+        pub analytics: CodeAnalytics,
     }
 
     let mut list = Vec::<R>::new();
@@ -960,6 +1144,7 @@ pub fn holds(
             available,
             quantity,
             parent_hold_id: hold.parent_hold_id,
+            analytics: analytics.remove(&hold.id).unwrap_or_default(),
         };
 
         list.push(r);
@@ -1081,16 +1266,17 @@ pub fn remove_user(
         .into_iter()
         .filter(|id| *id != event.id)
         .collect();
-    organization.remove_user(path.user_id, connection)?;
-
     if external_user.event_ids.len() != 0 {
         organization.add_user(
+            Some(user.id()),
             external_user.user_id,
             external_user.role,
             external_user.event_ids,
             connection,
         )?;
-    };
+    } else {
+        organization.remove_user(Some(user.id()), path.user_id, connection)?;
+    }
     Ok(HttpResponse::Ok().json(&organization))
 }
 
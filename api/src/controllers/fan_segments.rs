@@ -0,0 +1,163 @@
+use actix_web::{http::header, http::StatusCode, HttpResponse, Path, Query};
+use auth::user::User as AuthUser;
+use bigneon_db::prelude::*;
+use db::Connection;
+use domain_events::executors::marketing_contacts::queue_fan_segment_sync;
+use errors::*;
+use extractors::*;
+use helpers::application;
+use models::{PathParameters, WebPayload};
+use utils::csv;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateFanSegmentRequest {
+    pub name: String,
+    #[serde(default)]
+    pub filters: FanSegmentFilters,
+}
+
+pub fn index(
+    (connection, path, user): (Connection, Path<PathParameters>, AuthUser),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let organization = Organization::find(path.id, connection)?;
+    user.requires_scope_for_organization(Scopes::OrgFans, &organization, connection)?;
+
+    Ok(HttpResponse::Ok().json(FanSegment::find_for_organization(
+        organization.id,
+        connection,
+    )?))
+}
+
+pub fn create(
+    (connection, path, json, user): (
+        Connection,
+        Path<PathParameters>,
+        Json<CreateFanSegmentRequest>,
+        AuthUser,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let organization = Organization::find(path.id, connection)?;
+    user.requires_scope_for_organization(Scopes::OrgFans, &organization, connection)?;
+
+    let json = json.into_inner();
+    let fan_segment = FanSegment::create(organization.id, json.name, json.filters, user.id())
+        .commit(connection)?;
+    application::created(json!(fan_segment))
+}
+
+pub fn show(
+    (connection, path, user): (Connection, Path<PathParameters>, AuthUser),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let fan_segment = FanSegment::find(path.id, connection)?;
+    let organization = fan_segment.organization(connection)?;
+    user.requires_scope_for_organization(Scopes::OrgFans, &organization, connection)?;
+
+    Ok(HttpResponse::Ok().json(&fan_segment))
+}
+
+pub fn update(
+    (connection, path, json, user): (
+        Connection,
+        Path<PathParameters>,
+        Json<FanSegmentEditableAttributes>,
+        AuthUser,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let fan_segment = FanSegment::find(path.id, connection)?;
+    let organization = fan_segment.organization(connection)?;
+    user.requires_scope_for_organization(Scopes::OrgFans, &organization, connection)?;
+
+    let fan_segment = fan_segment.update(json.into_inner(), connection)?;
+    Ok(HttpResponse::Ok().json(&fan_segment))
+}
+
+pub fn destroy(
+    (connection, path, user): (Connection, Path<PathParameters>, AuthUser),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let fan_segment = FanSegment::find(path.id, connection)?;
+    let organization = fan_segment.organization(connection)?;
+    user.requires_scope_for_organization(Scopes::OrgFans, &organization, connection)?;
+
+    fan_segment.destroy(connection)?;
+    application::no_content()
+}
+
+/// Fans currently matching the segment's filters
+pub fn fans(
+    (connection, path, query, user): (
+        Connection,
+        Path<PathParameters>,
+        Query<PagingParameters>,
+        AuthUser,
+    ),
+) -> Result<WebPayload<DisplayFan>, BigNeonError> {
+    let connection = connection.get();
+    let fan_segment = FanSegment::find(path.id, connection)?;
+    let organization = fan_segment.organization(connection)?;
+    user.requires_scope_for_organization(Scopes::OrgFans, &organization, connection)?;
+
+    let payload = fan_segment.members(query.page(), query.limit(), connection)?;
+    Ok(WebPayload::new(StatusCode::OK, payload))
+}
+
+pub fn export(
+    (connection, path, user): (Connection, Path<PathParameters>, AuthUser),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let fan_segment = FanSegment::find(path.id, connection)?;
+    let organization = fan_segment.organization(connection)?;
+    user.requires_scope_for_organization(Scopes::OrgFans, &organization, connection)?;
+
+    let mut csv = "first_name,last_name,email,phone,order_count,revenue_in_cents,first_order_time,last_order_time\n".to_string();
+    for fan in fan_segment.all_members(connection)? {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{}\n",
+            csv::escape(&fan.first_name.unwrap_or_default()),
+            csv::escape(&fan.last_name.unwrap_or_default()),
+            csv::escape(&fan.email.unwrap_or_default()),
+            csv::escape(&fan.phone.unwrap_or_default()),
+            fan.order_count.unwrap_or(0),
+            fan.revenue_in_cents.unwrap_or(0),
+            fan.first_order_time
+                .map(|t| t.to_string())
+                .unwrap_or_default(),
+            fan.last_order_time
+                .map(|t| t.to_string())
+                .unwrap_or_default(),
+        ));
+    }
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/csv")
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!(
+                "attachment; filename=\"fan-segment-{}.csv\"",
+                fan_segment.id
+            ),
+        )
+        .body(csv))
+}
+
+/// Queues copying the segment's fans to the organization's marketing contacts
+pub fn sync(
+    (connection, path, user): (Connection, Path<PathParameters>, AuthUser),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let fan_segment = FanSegment::find(path.id, connection)?;
+    let organization = fan_segment.organization(connection)?;
+    user.requires_scope_for_organization(Scopes::OrgFans, &organization, connection)?;
+
+    if organization.sendgrid_api_key.is_none() && organization.mailchimp_api_key.is_none() {
+        return application::unprocessable(
+            "Organization does not have a sendgrid or mailchimp api key",
+        );
+    }
+    queue_fan_segment_sync(&fan_segment, connection)?;
+    Ok(HttpResponse::Accepted().finish())
+}
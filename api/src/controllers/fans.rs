@@ -0,0 +1,154 @@
+use actix_web::{HttpResponse, Path};
+use auth::user::User as AuthUser;
+use bigneon_db::prelude::*;
+use db::Connection;
+use diesel::PgConnection;
+use errors::*;
+use extractors::*;
+use helpers::application;
+use models::{OrganizationFanPathParameters, PathParameters};
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AddFanTagRequest {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct FanNoteRequest {
+    pub note: String,
+}
+
+/// Tag names used by the organization, for suggesting tags and building segments
+pub fn organization_tags(
+    (connection, path, user): (Connection, Path<PathParameters>, AuthUser),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let organization = Organization::find(path.id, connection)?;
+    user.requires_scope_for_organization(Scopes::OrgFans, &organization, connection)?;
+
+    Ok(HttpResponse::Ok().json(FanTag::names_for_organization(organization.id, connection)?))
+}
+
+pub fn tags(
+    (connection, path, user): (Connection, Path<OrganizationFanPathParameters>, AuthUser),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let organization = Organization::find(path.id, connection)?;
+    user.requires_scope_for_organization(Scopes::OrgFans, &organization, connection)?;
+
+    Ok(HttpResponse::Ok().json(FanTag::find_for_fan(
+        organization.id,
+        path.user_id,
+        connection,
+    )?))
+}
+
+pub fn add_tag(
+    (connection, path, json, user): (
+        Connection,
+        Path<OrganizationFanPathParameters>,
+        Json<AddFanTagRequest>,
+        AuthUser,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let organization = Organization::find(path.id, connection)?;
+    user.requires_scope_for_organization(Scopes::OrgFans, &organization, connection)?;
+    if !is_fan(&organization, path.user_id, connection)? {
+        return application::forbidden("Fan does not belong to this organization");
+    }
+
+    let tag =
+        FanTag::create(organization.id, path.user_id, &json.name, user.id()).commit(connection)?;
+    application::created(json!(tag))
+}
+
+pub fn remove_tag(
+    (connection, path, user): (Connection, Path<PathParameters>, AuthUser),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let tag = FanTag::find(path.id, connection)?;
+    let organization = Organization::find(tag.organization_id, connection)?;
+    user.requires_scope_for_organization(Scopes::OrgFans, &organization, connection)?;
+
+    tag.destroy(connection)?;
+    application::no_content()
+}
+
+pub fn notes(
+    (connection, path, user): (Connection, Path<OrganizationFanPathParameters>, AuthUser),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let organization = Organization::find(path.id, connection)?;
+    user.requires_scope_for_organization(Scopes::OrgFans, &organization, connection)?;
+
+    Ok(HttpResponse::Ok().json(FanNote::find_for_fan(
+        organization.id,
+        path.user_id,
+        connection,
+    )?))
+}
+
+pub fn add_note(
+    (connection, path, json, user): (
+        Connection,
+        Path<OrganizationFanPathParameters>,
+        Json<FanNoteRequest>,
+        AuthUser,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let organization = Organization::find(path.id, connection)?;
+    user.requires_scope_for_organization(Scopes::OrgFans, &organization, connection)?;
+    if !is_fan(&organization, path.user_id, connection)? {
+        return application::forbidden("Fan does not belong to this organization");
+    }
+
+    let note = FanNote::create(
+        organization.id,
+        path.user_id,
+        json.into_inner().note,
+        user.id(),
+    )
+    .commit(connection)?;
+    application::created(json!(note))
+}
+
+pub fn update_note(
+    (connection, path, json, user): (
+        Connection,
+        Path<PathParameters>,
+        Json<FanNoteRequest>,
+        AuthUser,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let note = FanNote::find(path.id, connection)?;
+    let organization = Organization::find(note.organization_id, connection)?;
+    user.requires_scope_for_organization(Scopes::OrgFans, &organization, connection)?;
+
+    let note = note.update(json.into_inner().note, connection)?;
+    Ok(HttpResponse::Ok().json(&note))
+}
+
+pub fn remove_note(
+    (connection, path, user): (Connection, Path<PathParameters>, AuthUser),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let note = FanNote::find(path.id, connection)?;
+    let organization = Organization::find(note.organization_id, connection)?;
+    user.requires_scope_for_organization(Scopes::OrgFans, &organization, connection)?;
+
+    note.destroy(connection)?;
+    application::no_content()
+}
+
+fn is_fan(
+    organization: &Organization,
+    user_id: Uuid,
+    connection: &PgConnection,
+) -> Result<bool, BigNeonError> {
+    let user = User::find(user_id, connection)?;
+    Ok(organization.has_fan(&user, connection)?)
+}
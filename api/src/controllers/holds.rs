@@ -6,7 +6,7 @@ use db::Connection;
 use errors::BigNeonError;
 use extractors::*;
 use helpers::application;
-use models::PathParameters;
+use models::{HoldReleasePathParameters, PathParameters};
 use serde_with::rust::double_option;
 use uuid::Uuid;
 
@@ -142,7 +142,7 @@ pub fn update(
         conn,
     )?;
     let quantity = req.quantity;
-    let hold = hold.update(req.into_inner().into(), conn)?;
+    let hold = hold.update(Some(user.id()), req.into_inner().into(), conn)?;
     if let Some(quantity) = quantity {
         hold.set_quantity(Some(user.id()), quantity, conn)?;
     }
@@ -258,3 +258,65 @@ pub fn destroy(
     hold.destroy(Some(user.id()), conn)?;
     Ok(HttpResponse::Ok().finish())
 }
+
+#[derive(Deserialize, Serialize)]
+pub struct CreateHoldReleaseRequest {
+    pub release_at: Option<NaiveDateTime>,
+    pub quantity: Option<u32>,
+}
+
+pub fn releases(
+    (conn, path, user): (Connection, Path<PathParameters>, User),
+) -> Result<HttpResponse, BigNeonError> {
+    let conn = conn.get();
+    let hold = Hold::find(path.id, conn)?;
+    user.requires_scope_for_organization_event(
+        Scopes::HoldRead,
+        &hold.organization(conn)?,
+        &hold.event(conn)?,
+        conn,
+    )?;
+
+    Ok(HttpResponse::Ok().json(HoldRelease::summary_for_hold(&hold, conn)?))
+}
+
+pub fn create_release(
+    (conn, req, path, user): (
+        Connection,
+        Json<CreateHoldReleaseRequest>,
+        Path<PathParameters>,
+        User,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let conn = conn.get();
+    let hold = Hold::find(path.id, conn)?;
+    user.requires_scope_for_organization_event(
+        Scopes::HoldWrite,
+        &hold.organization(conn)?,
+        &hold.event(conn)?,
+        conn,
+    )?;
+
+    let release = hold.schedule_release(Some(user.id()), req.release_at, req.quantity, conn)?;
+    application::created(json!(release))
+}
+
+pub fn cancel_release(
+    (conn, path, user): (Connection, Path<HoldReleasePathParameters>, User),
+) -> Result<HttpResponse, BigNeonError> {
+    let conn = conn.get();
+    let hold = Hold::find(path.id, conn)?;
+    user.requires_scope_for_organization_event(
+        Scopes::HoldWrite,
+        &hold.organization(conn)?,
+        &hold.event(conn)?,
+        conn,
+    )?;
+
+    let release = HoldRelease::find(path.release_id, conn)?;
+    if release.hold_id != hold.id {
+        return application::unprocessable("Release does not belong to this hold");
+    }
+
+    Ok(HttpResponse::Ok().json(release.cancel(Some(user.id()), conn)?))
+}
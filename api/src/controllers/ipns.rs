@@ -1,11 +1,15 @@
-use actix_web::HttpResponse;
+use actix_web::{HttpRequest, HttpResponse, Query, State};
 use bigneon_db::prelude::*;
 use bigneon_db::utils::dates::IntoDateBuilder;
 use db::Connection;
 use errors::BigNeonError;
 use extractors::Json;
 use globee::GlobeeIpnRequest;
+use helpers::application;
 use log::Level::Debug;
+use paypal::WebhookHeaders;
+use serde_json;
+use server::AppState;
 use uuid::Uuid;
 
 pub fn globee(
@@ -35,3 +39,138 @@ pub fn globee(
 
     Ok(HttpResponse::Ok().finish())
 }
+
+#[derive(Deserialize, Serialize)]
+pub struct PaypalWebhookPayload {
+    pub headers: Option<WebhookHeaders>,
+    // Kept as received, PayPal verifies the signature against the original event
+    pub event: serde_json::Value,
+}
+
+pub fn paypal(
+    (request, data, conn): (HttpRequest<AppState>, Json<serde_json::Value>, Connection),
+) -> Result<HttpResponse, BigNeonError> {
+    let event = data.into_inner();
+    jlog!(Debug, "PayPal webhook received", { "event": &event });
+
+    let header = |name: &str| {
+        request
+            .headers()
+            .get(name)
+            .and_then(|h| h.to_str().ok())
+            .map(|h| h.to_string())
+    };
+    // Headers are kept with the event so that its signature can be verified when it is processed
+    let headers = match (
+        header("paypal-auth-algo"),
+        header("paypal-cert-url"),
+        header("paypal-transmission-id"),
+        header("paypal-transmission-sig"),
+        header("paypal-transmission-time"),
+    ) {
+        (
+            Some(auth_algo),
+            Some(cert_url),
+            Some(transmission_id),
+            Some(transmission_sig),
+            Some(transmission_time),
+        ) => Some(WebhookHeaders {
+            auth_algo,
+            cert_url,
+            transmission_id,
+            transmission_sig,
+            transmission_time,
+        }),
+        _ => None,
+    };
+
+    let mut action = DomainAction::create(
+        None,
+        DomainActionTypes::PaypalWebhook,
+        None,
+        json!(PaypalWebhookPayload { headers, event }),
+        Some(Tables::Orders.to_string()),
+        None,
+    );
+    action.expires_at = action.scheduled_at.into_builder().add_days(30).finish();
+    action.max_attempt_count = 5;
+    action.commit(conn.get())?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[derive(Deserialize)]
+pub struct SendgridWebhookParameters {
+    pub token: Option<String>,
+}
+
+/// Event posted by SendGrid, only the fields needed for suppressions are read
+#[derive(Deserialize, Serialize)]
+pub struct SendgridEvent {
+    pub email: String,
+    pub event: String,
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// For bounces, either `bounce` or `blocked` for temporary failures
+    #[serde(default, rename = "type")]
+    pub bounce_type: Option<String>,
+}
+
+pub fn sendgrid(
+    (state, query, data, conn): (
+        State<AppState>,
+        Query<SendgridWebhookParameters>,
+        Json<Vec<SendgridEvent>>,
+        Connection,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    match state.config.sendgrid_webhook_token {
+        Some(ref token) => {
+            if query.token.as_ref() != Some(token) {
+                return application::unauthorized_with_message(
+                    "Invalid SendGrid webhook token",
+                    None,
+                    None,
+                );
+            }
+        }
+        None => {
+            if state.config.validate_ipns {
+                return application::unauthorized_with_message(
+                    "SENDGRID_WEBHOOK_TOKEN must be configured to accept SendGrid webhooks",
+                    None,
+                    None,
+                );
+            }
+        }
+    }
+
+    let conn = conn.get();
+    for event in data.into_inner() {
+        jlog!(Debug, "SendGrid event received", { "event": &event });
+        let reason = match event.event.as_str() {
+            "bounce" => {
+                if event.bounce_type.as_ref().map(|t| t.as_str()) == Some("blocked") {
+                    continue;
+                }
+                EmailSuppressionReasons::Bounce
+            }
+            "spamreport" => EmailSuppressionReasons::SpamReport,
+            "unsubscribe" | "group_unsubscribe" => EmailSuppressionReasons::Unsubscribe,
+            "group_resubscribe" => {
+                // Bounces and spam reports still apply after resubscribing
+                for suppression in EmailSuppression::find_by_email(&event.email, conn)? {
+                    if suppression.reason == EmailSuppressionReasons::Unsubscribe {
+                        suppression.destroy(conn)?;
+                    }
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        EmailSuppression::create(&event.email, reason, "SendGrid", event.reason.clone())
+            .commit(conn)?;
+    }
+
+    Ok(HttpResponse::Ok().finish())
+}
@@ -5,8 +5,11 @@ pub mod broadcasts;
 pub mod cart;
 pub mod codes;
 pub mod comps;
+pub mod event_series;
 pub mod events;
 pub mod external;
+pub mod fan_segments;
+pub mod fans;
 pub mod holds;
 pub mod ipns;
 pub mod orders;
@@ -16,6 +19,7 @@ pub mod password_resets;
 pub mod payment_methods;
 pub mod payments;
 pub mod redemption_codes;
+pub mod refund_requests;
 pub mod regions;
 pub mod reports;
 pub mod settlements;
@@ -23,6 +27,8 @@ pub mod stages;
 pub mod status;
 pub mod ticket_types;
 pub mod tickets;
+pub mod unique_codes;
 pub mod user_invites;
 pub mod users;
 pub mod venues;
+pub mod wallets;
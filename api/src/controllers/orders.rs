@@ -1,8 +1,6 @@
 use actix_web::{HttpResponse, Path, Query, State};
 use auth::user::User;
-use bigneon_db::models::User as DbUser;
 use bigneon_db::models::*;
-use communications::mailers;
 use config::Environment;
 use db::Connection;
 use diesel::Connection as DieselConnection;
@@ -12,8 +10,8 @@ use helpers::application;
 use log::Level::Debug;
 use models::PathParameters;
 use server::AppState;
-use std::cmp;
 use std::collections::HashMap;
+use utils::refunds;
 use uuid::Uuid;
 
 pub fn index(
@@ -138,13 +136,14 @@ pub fn refund(
     }
 
     // Check for any organizations where user lacks order refund access
+    let events = Event::find_by_order_item_ids(&order_item_ids, connection)?;
     let mut authorized_to_refund_items = !organization_map.is_empty();
-    for event in Event::find_by_order_item_ids(&order_item_ids, connection)? {
+    for event in events.iter() {
         if let Some(organization) = organization_map.get(&event.organization_id) {
             if !user.has_scope_for_organization_event(
                 Scopes::OrderRefund,
                 &organization,
-                &event,
+                event,
                 connection,
             )? {
                 authorized_to_refund_items = false;
@@ -156,6 +155,23 @@ pub fn refund(
         }
     }
 
+    // Holders can refund their own tickets while rescheduled events have a refund window open
+    if !authorized_to_refund_items
+        && !events.is_empty()
+        && order.on_behalf_of_user_id.unwrap_or(order.user_id) == user.id()
+    {
+        authorized_to_refund_items = true;
+        for event in events.iter() {
+            if !event.refund_window_open(connection)? {
+                authorized_to_refund_items = false;
+                break;
+            }
+        }
+        if authorized_to_refund_items {
+            order.validate_self_serve_refund(&items, connection)?;
+        }
+    }
+
     if !authorized_to_refund_items {
         let mut details_data = HashMap::new();
         details_data.insert("order_id", json!(path.id));
@@ -163,182 +179,13 @@ pub fn refund(
         return application::unauthorized(Some(user), Some(details_data));
     }
 
-    let ticket_instance_ids = items
-        .iter()
-        .filter(|i| i.ticket_instance_id.is_some())
-        .map(|i| i.ticket_instance_id.unwrap())
-        .collect::<Vec<Uuid>>();
-
-    // Refund amount is fee inclusive if fee no longer applies to the order
-    let refund_due = order.refund(items, user.id(), connection)?;
-
-    // Transfer tickets back to the organization wallets
-    let mut tokens_per_asset: HashMap<Uuid, Vec<u64>> = HashMap::new();
-    let mut wallet_id_per_asset: HashMap<Uuid, Uuid> = HashMap::new();
-    let mut ticket_instances_per_asset: HashMap<Uuid, Vec<TicketInstance>> = HashMap::new();
-    let refunded_tickets =
-        RefundedTicket::find_by_ticket_instance_ids(ticket_instance_ids, connection)?
-            .into_iter()
-            .filter(|refund_data| refund_data.ticket_refunded_at.is_some());
-    for refunded_ticket in refunded_tickets {
-        let ticket = TicketInstance::find(refunded_ticket.ticket_instance_id, connection)?;
-        tokens_per_asset
-            .entry(ticket.asset_id)
-            .or_insert_with(|| Vec::new())
-            .push(ticket.token_id as u64);
-        wallet_id_per_asset
-            .entry(ticket.asset_id)
-            .or_insert(ticket.wallet_id);
-        ticket_instances_per_asset
-            .entry(ticket.asset_id)
-            .or_insert_with(|| Vec::new())
-            .push(ticket);
-    }
-    let mut modified_tokens: HashMap<Uuid, Vec<u64>> = HashMap::new();
-
-    let mut refund_breakdown: HashMap<PaymentMethods, u32> = HashMap::new();
-    let mut payment_remaining_balance_map: HashMap<Option<String>, i64> = HashMap::new();
-    let mut amount_refunded = 0;
-
-    // Begin transaction, if it fails at this point all transferred tickets are returned to wallets
-    match connection.transaction::<_, BigNeonError, _>(|| {
-        for (asset_id, token_ids) in &tokens_per_asset {
-            let organization_id = Organization::find_by_asset_id(*asset_id, connection)?.id;
-            let organization_wallet =
-                Wallet::find_default_for_organization(organization_id, connection)?;
-            let asset = Asset::find(*asset_id, connection)?;
-            match asset.blockchain_asset_id {
-                Some(a) => {
-                    let wallet_id = match wallet_id_per_asset.get(asset_id) {
-                        Some(w) => w.clone(),
-                        None => return Err(application::internal_server_error::<HttpResponse>(
-                            "Could not complete this refund because wallet id not found for asset",
-                        ).unwrap_err()),
-                    };
-                    let user_wallet = Wallet::find(wallet_id, connection)?;
-                    state.config.tari_client.transfer_tokens(&user_wallet.secret_key, &user_wallet.public_key,
-                                                             &a,
-                                                             token_ids.clone(),
-                                                             organization_wallet.public_key.clone(),
-                    )?;
-                    modified_tokens.insert(*asset_id, token_ids.clone());
-                    match ticket_instances_per_asset.get(asset_id) {
-                        Some(ticket_instances) => {
-                            for ticket_instance in ticket_instances {
-                                ticket_instance.set_wallet(&organization_wallet, connection)?;
-                            }
-                        }
-                        None => return Err(application::internal_server_error::<HttpResponse>(
-                            "No ticket instances exist for transferred tokens",
-                        ).unwrap_err()),
-                    }
-                },
-                None => return Err(application::internal_server_error::<HttpResponse>(
-                    "Could not complete this refund because the asset is not assigned on the blockchain",
-                ).unwrap_err())
-            }
-        }
-
-        // Perform refunds
-
-        // Negative payments / refunds cancel out remaining payment balance
-        for payment in order.payments(connection)? {
-            // Ignore payments that were only authorized
-            if payment.status == PaymentStatus::Authorized {
-                continue;
-            }
-
-            *payment_remaining_balance_map
-                .entry(payment.external_reference)
-                .or_insert(0) += payment.amount;
-        }
-
-        for payment in order.payments(connection)? {
-            if payment.status != PaymentStatus::Completed {
-                continue;
-            }
-
-            let remaining_balance = payment_remaining_balance_map
-                .get(&payment.external_reference)
-                .map(|n| *n)
-                .unwrap_or(0);
-            if remaining_balance == 0 {
-                continue;
-            }
-
-            let amount_to_refund = cmp::min(refund_due - amount_refunded, remaining_balance as u32);
-            let mut refund_data = None;
-            if payment.payment_method == PaymentMethods::CreditCard {
-
-                let mut organizations = order.organizations(connection)?;
-                if organizations.len() != 1 {
-                    return Err(application::internal_server_error::<HttpResponse>("Cannot process refunds for orders that contain more than one event").unwrap_err());
-                }
-                let organization = organizations.remove(0);
-                let client = &state
-                    .service_locator
-                    .create_payment_processor(payment.provider, &organization)?;
-
-                refund_data = match payment.external_reference {
-                    Some(ref external_reference) => Some(
-                        client
-                            .partial_refund(external_reference, amount_to_refund)?
-                            .to_json()?,
-                    ),
-                    None => {
-                        return Err(application::internal_server_error::<HttpResponse>(&format!(
-                            "Unable to refund amount owed payment {} lacks external reference",
-                            payment.id
-                        )).unwrap_err())
-                    }
-                };
-            }
-            payment.log_refund(user.id(), amount_to_refund, refund_data, connection)?;
-            *refund_breakdown.entry(payment.payment_method).or_insert(0) += amount_to_refund;
-            amount_refunded += amount_to_refund;
-        }
-
-        if amount_refunded < refund_due {
-            return Err(application::internal_server_error::<HttpResponse>(&format!(
-                "Unable to refund amount owed {} refunded, {} due",
-                amount_refunded, refund_due
-            )).unwrap_err());
-        }
-
-        Ok(())
-    }) {
-        Err(error) => {
-            for (asset_id, token_ids) in &modified_tokens {
-                let organization_id = Organization::find_by_asset_id(*asset_id, connection)?.id;
-                let organization_wallet =
-                    Wallet::find_default_for_organization(organization_id, connection)?;
-                let asset = Asset::find(*asset_id, connection)?;
-                match asset.blockchain_asset_id {
-                    Some(a) => {
-                        let wallet_id = match wallet_id_per_asset.get(asset_id) {
-                            Some(w) => w.clone(),
-                            None => return application::internal_server_error(
-                                "Could not complete this refund because wallet id not found for asset",
-                            ),
-                        };
-                        let user_wallet = Wallet::find(wallet_id, connection)?;
-                        state.config.tari_client.transfer_tokens(&organization_wallet.secret_key, &organization_wallet.public_key,
-                                                                 &a,
-                                                                 token_ids.clone(),
-                                                                 user_wallet.public_key.clone(),
-                        )?;
-                    },
-                    None => return application::internal_server_error(
-                        "Could not complete this refund because the asset is not assigned on the blockchain",
-                    ),
-                }
-            }
-
-            // Return error
-            return Err(error);
-        }
-        _ => (),
-    }
+    let (amount_refunded, refund_breakdown) = refunds::refund_order_items(
+        &order,
+        items,
+        user.id(),
+        &state.service_locator,
+        connection,
+    )?;
 
     // Commit changes as payment completed
     if state.config.environment != Environment::Test {
@@ -346,25 +193,8 @@ pub fn refund(
         conn.begin_transaction()?;
     }
 
-    // Reload order
-    let order = Order::find(order.id, connection)?;
-    let display_order = order.for_display(None, user.id(), connection)?;
-    let user = DbUser::find(
-        order.on_behalf_of_user_id.unwrap_or(order.user_id),
-        connection,
-    )?;
-
     // Communicate refund to user
-    if let (Some(first_name), Some(email)) = (user.first_name, user.email) {
-        mailers::orders::refund_email(
-            &first_name,
-            email,
-            display_order,
-            amount_refunded,
-            &state.config,
-            connection,
-        )?;
-    }
+    refunds::send_refund_email(&order, amount_refunded, &state.config, connection)?;
 
     Ok(HttpResponse::Ok().json(json!(RefundResponse {
         amount_refunded,
@@ -281,6 +281,7 @@ pub fn accept_request(
                 invite_details.change_invite_status(1, connection)?;
                 let org = Organization::find(invite_details.organization_id, connection)?;
                 org.add_user(
+                    Some(u.id()),
                     u.id(),
                     invite_details.roles,
                     invite_details.event_ids,
@@ -2,6 +2,7 @@ use actix_web::{http::StatusCode, HttpResponse, Path, Query, State};
 use auth::user::User;
 use bigneon_db::models::*;
 use chrono::NaiveDateTime;
+use communications::mailers::branding::{BrandedTemplate, EmailBranding};
 use db::Connection;
 use errors::*;
 use extractors::*;
@@ -9,11 +10,17 @@ use helpers::application;
 use models::WebPayload;
 use models::{OrganizationUserPathParameters, PathParameters};
 use server::AppState;
+use std::cmp;
+use utils::communication::TemplateData;
 use utils::marketing_contacts;
+use utils::sendgrid::templates::{self as sendgrid_templates, SGTemplate};
 use uuid::Uuid;
 
 const LOG_TARGET: &'static str = "bigneon::controllers::organizations";
 
+/// Largest page of audit log entries returned at once
+const MAX_AUDIT_LOG_PAGE_SIZE: u32 = 500;
+
 #[derive(Deserialize)]
 pub struct AddUserRequest {
     pub user_id: Uuid,
@@ -59,6 +66,10 @@ pub struct NewOrganizationRequest {
     #[serde(default, deserialize_with = "deserialize_unless_blank")]
     pub globee_api_key: Option<String>,
     pub max_instances_per_ticket_type: Option<i64>,
+    #[serde(default)]
+    pub refund_request_auto_approve_days: Option<i32>,
+    #[serde(default, deserialize_with = "deserialize_unless_blank")]
+    pub mailchimp_api_key: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -158,6 +169,10 @@ pub fn create(
             Some(x) => x,
             None => state.config.max_instances_per_ticket_type,
         }),
+        refund_request_auto_approve_days: new_organization
+            .refund_request_auto_approve_days
+            .map(Some),
+        mailchimp_api_key: new_organization.mailchimp_api_key.clone(),
     };
 
     let mut organization = new_organization_with_fee_schedule.commit(
@@ -193,6 +208,7 @@ pub fn update(
     }
 
     let mut updated_organization = organization.update(
+        Some(user.id()),
         organization_update,
         &state.config.api_keys_encryption_key,
         conn,
@@ -201,22 +217,15 @@ pub fn update(
     organization.decrypt(&state.config.api_keys_encryption_key)?;
     updated_organization.decrypt(&state.config.api_keys_encryption_key)?;
 
-    // If we have a new/changed sendgrid api key,
+    // If we have a new/changed sendgrid or mailchimp api key,
     // we create a domain action for each event
     // TODO: OrganizationUpdated domain event should be triggered in the model #DomainEvents
-    match organization.sendgrid_api_key {
-        Some(ref old_key) => {
-            if let Some(ref new_key) = updated_organization.sendgrid_api_key {
-                if new_key != old_key {
-                    enqueue_create_marketing_list(&connection, &updated_organization)?;
-                }
-            }
-        }
-        None => {
-            if updated_organization.sendgrid_api_key.is_some() {
-                enqueue_create_marketing_list(&connection, &updated_organization)?;
-            }
-        }
+    let sendgrid_key_changed = updated_organization.sendgrid_api_key.is_some()
+        && updated_organization.sendgrid_api_key != organization.sendgrid_api_key;
+    let mailchimp_key_changed = updated_organization.mailchimp_api_key.is_some()
+        && updated_organization.mailchimp_api_key != organization.mailchimp_api_key;
+    if sendgrid_key_changed || mailchimp_key_changed {
+        enqueue_create_marketing_list(&connection, &updated_organization)?;
     }
 
     Ok(HttpResponse::Ok().json(&updated_organization))
@@ -316,6 +325,7 @@ pub fn add_or_replace_user(
     }
 
     organization.add_user(
+        Some(user.id()),
         req.user_id,
         req.roles,
         req.event_ids.unwrap_or(Vec::new()),
@@ -331,7 +341,7 @@ pub fn remove_user(
     let organization = Organization::find(parameters.id, connection)?;
     user.requires_scope_for_organization(Scopes::OrgUsers, &organization, connection)?;
 
-    let organization = organization.remove_user(parameters.user_id, connection)?;
+    let organization = organization.remove_user(Some(user.id()), parameters.user_id, connection)?;
     Ok(HttpResponse::Ok().json(&organization))
 }
 
@@ -428,7 +438,11 @@ pub fn add_fee_schedule(
     let fee_schedule = new_fee_schedule.commit(user.id(), connection)?;
     let fee_schedule_ranges = fee_schedule.ranges(connection)?;
 
-    Organization::find(parameters.id, connection)?.add_fee_schedule(&fee_schedule, connection)?;
+    Organization::find(parameters.id, connection)?.add_fee_schedule(
+        Some(user.id()),
+        &fee_schedule,
+        connection,
+    )?;
 
     Ok(HttpResponse::Created().json(FeeScheduleWithRanges {
         id: fee_schedule.id,
@@ -464,3 +478,267 @@ pub fn search_fans(
     )?;
     Ok(WebPayload::new(StatusCode::OK, payload))
 }
+
+#[derive(Deserialize)]
+pub struct AuditLogQueryParameters {
+    pub user_id: Option<Uuid>,
+    pub main_table: Option<Tables>,
+    pub event_type: Option<DomainEventTypes>,
+    pub start_utc: Option<NaiveDateTime>,
+    pub end_utc: Option<NaiveDateTime>,
+    pub page: Option<u32>,
+    pub limit: Option<u32>,
+}
+
+pub fn audit_log(
+    (connection, path, query, user): (
+        Connection,
+        Path<PathParameters>,
+        Query<AuditLogQueryParameters>,
+        User,
+    ),
+) -> Result<WebPayload<DisplayAuditLogEntry>, BigNeonError> {
+    let connection = connection.get();
+    let organization = Organization::find(path.id, connection)?;
+    user.requires_scope_for_organization(Scopes::OrgAdmin, &organization, connection)?;
+
+    let query = query.into_inner();
+    let payload = DomainEvent::find_for_organization(
+        organization.id,
+        query.user_id,
+        query.main_table,
+        query.event_type,
+        query.start_utc,
+        query.end_utc,
+        query.page.unwrap_or(0),
+        cmp::min(query.limit.unwrap_or(100), MAX_AUDIT_LOG_PAGE_SIZE),
+        connection,
+    )?;
+    Ok(WebPayload::new(StatusCode::OK, payload))
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct StripeConnectStatus {
+    pub account_id: Option<String>,
+    pub enabled: bool,
+    pub details_submitted: bool,
+}
+
+pub fn show_stripe_connect(
+    (state, connection, path, user): (State<AppState>, Connection, Path<PathParameters>, User),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let mut organization = Organization::find(path.id, connection)?;
+    user.requires_scope_for_organization(Scopes::OrgWrite, &organization, connection)?;
+
+    let account_id = match organization.stripe_connect_account_id.clone() {
+        Some(account_id) => account_id,
+        None => {
+            return Ok(HttpResponse::Ok().json(StripeConnectStatus {
+                account_id: None,
+                enabled: false,
+                details_submitted: false,
+            }));
+        }
+    };
+
+    // Onboarding happens on Stripe's side, so refresh the status whenever it is viewed
+    let account = state
+        .service_locator
+        .create_stripe_client()
+        .retrieve_account(&account_id)?;
+    let enabled = account.charges_enabled && account.payouts_enabled;
+    if enabled != organization.stripe_connect_enabled {
+        organization =
+            organization.update_stripe_connect_account(&account_id, enabled, connection)?;
+    }
+
+    Ok(HttpResponse::Ok().json(StripeConnectStatus {
+        account_id: organization.stripe_connect_account_id,
+        enabled: organization.stripe_connect_enabled,
+        details_submitted: account.details_submitted,
+    }))
+}
+
+pub fn create_stripe_connect_link(
+    (state, connection, path, user): (State<AppState>, Connection, Path<PathParameters>, User),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let organization = Organization::find(path.id, connection)?;
+    user.requires_scope_for_organization(Scopes::OrgWrite, &organization, connection)?;
+
+    let client = state.service_locator.create_stripe_client();
+    let account_id = match organization.stripe_connect_account_id {
+        Some(ref account_id) => account_id.clone(),
+        None => {
+            let account = client.create_connected_account(
+                user.user.email.as_ref().map(|e| e.as_str()),
+                None,
+                vec![("organization_id".to_string(), organization.id.to_string())],
+            )?;
+            organization.update_stripe_connect_account(&account.id, false, connection)?;
+            account.id
+        }
+    };
+
+    let organization_url = format!(
+        "{}/admin/organizations/{}/stripe_connect",
+        state.config.front_end_url, organization.id
+    );
+    let link = client.create_account_link(
+        &account_id,
+        &format!("{}?refresh=true", organization_url),
+        &format!("{}?complete=true", organization_url),
+    )?;
+
+    Ok(HttpResponse::Created().json(link))
+}
+
+#[derive(Deserialize)]
+pub struct BalanceTransactionQueryParameters {
+    pub start_utc: Option<NaiveDateTime>,
+    pub end_utc: Option<NaiveDateTime>,
+    pub starting_after: Option<String>,
+    pub limit: Option<u32>,
+}
+
+pub fn stripe_connect_balance_transactions(
+    (state, connection, path, query, user): (
+        State<AppState>,
+        Connection,
+        Path<PathParameters>,
+        Query<BalanceTransactionQueryParameters>,
+        User,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let organization = Organization::find(path.id, connection)?;
+    user.requires_scope_for_organization(Scopes::OrgFinancialReports, &organization, connection)?;
+
+    let account_id = match organization.stripe_connect_account_id {
+        Some(ref account_id) => account_id,
+        None => {
+            return application::unprocessable(
+                "Organization does not have a Stripe Connect account",
+            );
+        }
+    };
+
+    let transactions = state
+        .service_locator
+        .create_stripe_client()
+        .list_balance_transactions(
+            account_id,
+            query.start_utc.map(|d| d.timestamp()),
+            query.end_utc.map(|d| d.timestamp()),
+            query.starting_after.as_ref().map(|s| s.as_str()),
+            // Stripe rejects list requests for more than 100 records
+            cmp::min(query.limit.unwrap_or(100), 100),
+        )?;
+
+    Ok(HttpResponse::Ok().json(transactions))
+}
+
+pub fn show_email_settings(
+    (connection, path, user): (Connection, Path<PathParameters>, User),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let organization = Organization::find(path.id, connection)?;
+    user.requires_scope_for_organization(Scopes::OrgWrite, &organization, connection)?;
+
+    let settings = OrganizationEmailSettings::find_by_organization_id(organization.id, connection)?;
+    Ok(HttpResponse::Ok().json(settings))
+}
+
+pub fn update_email_settings(
+    (connection, path, json, user): (
+        Connection,
+        Path<PathParameters>,
+        Json<OrganizationEmailSettingsEditableAttributes>,
+        User,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let organization = Organization::find(path.id, connection)?;
+    user.requires_scope_for_organization(Scopes::OrgWrite, &organization, connection)?;
+
+    let settings = OrganizationEmailSettings::create_or_update(
+        organization.id,
+        json.into_inner(),
+        user.id(),
+        connection,
+    )?;
+    Ok(HttpResponse::Ok().json(settings))
+}
+
+#[derive(Deserialize)]
+pub struct EmailPreviewQueryParameters {
+    pub template: BrandedTemplate,
+}
+
+#[derive(Serialize)]
+pub struct EmailPreview {
+    pub template_id: String,
+    pub from_email: String,
+    pub from_name: Option<String>,
+    pub subject: Option<String>,
+    pub html: Option<String>,
+    pub template_data: TemplateData,
+}
+
+/// Renders the template an organization's email would be sent with, using sample data
+pub fn preview_email(
+    (state, connection, path, query, user): (
+        State<AppState>,
+        Connection,
+        Path<PathParameters>,
+        Query<EmailPreviewQueryParameters>,
+        User,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let mut organization = Organization::find(path.id, connection)?;
+    user.requires_scope_for_organization(Scopes::OrgWrite, &organization, connection)?;
+
+    let branding = EmailBranding::new(
+        query.template,
+        Some(&organization),
+        &state.config,
+        connection,
+    )?;
+    let mut template_data = query.template.sample_template_data(&state.config);
+    for (key, value) in branding.template_data {
+        template_data.entry(key).or_insert(value);
+    }
+
+    let sendgrid_api_key = match branding.sendgrid_organization_id {
+        Some(_) => {
+            organization.decrypt(&state.config.api_keys_encryption_key)?;
+            organization.sendgrid_api_key.unwrap_or_default()
+        }
+        None => state.config.sendgrid_api_key.clone(),
+    };
+    let template = SGTemplate::find(&sendgrid_api_key, &branding.template_id)?;
+    let (subject, html) = match template.active_version() {
+        Some(version) => (
+            version
+                .subject
+                .as_ref()
+                .map(|s| sendgrid_templates::render(s, &template_data)),
+            version
+                .html_content
+                .as_ref()
+                .map(|h| sendgrid_templates::render(h, &template_data)),
+        ),
+        None => (None, None),
+    };
+
+    Ok(HttpResponse::Ok().json(EmailPreview {
+        template_id: branding.template_id,
+        from_email: branding.source_email,
+        from_name: branding.source_name,
+        subject,
+        html,
+        template_data,
+    }))
+}
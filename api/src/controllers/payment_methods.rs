@@ -1,11 +1,94 @@
-use actix_web::HttpResponse;
+use actix_web::{HttpResponse, Json, Path, State};
 use auth::user::User;
-use bigneon_db::models::ForDisplay;
+use bigneon_db::models::*;
 use db::Connection;
 use errors::*;
+use helpers::application;
+use models::PathParameters;
+use payments::PaymentProcessorBehavior;
+use server::AppState;
 
 pub fn index((connection, auth_user): (Connection, User)) -> Result<HttpResponse, BigNeonError> {
     let connection = connection.get();
     let payment_methods = &auth_user.user.payment_methods(connection).for_display()?;
     Ok(HttpResponse::Ok().json(payment_methods))
 }
+
+#[derive(Deserialize)]
+pub struct CreatePaymentMethodRequest {
+    pub provider: PaymentProviders,
+    pub token: String,
+    #[serde(default)]
+    pub set_default: bool,
+}
+
+pub fn create(
+    (connection, json, auth_user, state): (
+        Connection,
+        Json<CreatePaymentMethodRequest>,
+        User,
+        State<AppState>,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let client = state
+        .service_locator
+        .create_stored_payment_processor(json.provider)?;
+    let behavior = match client.behavior() {
+        PaymentProcessorBehavior::AuthThenComplete(behavior) => behavior,
+        _ => {
+            return application::unprocessable(
+                "Saved payment methods are not supported for this payment processor",
+            );
+        }
+    };
+
+    let repeat_token = behavior.create_token_for_repeat_charges(&json.token, "Big Neon")?;
+    let payment_method = PaymentMethod::create(
+        auth_user.id(),
+        json.provider,
+        json.set_default,
+        repeat_token.token.clone(),
+        repeat_token.to_json()?,
+    )
+    .commit(auth_user.id(), connection)?;
+
+    Ok(HttpResponse::Created().json(payment_method.for_display()?))
+}
+
+pub fn make_default(
+    (connection, path, auth_user): (Connection, Path<PathParameters>, User),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let payment_method = PaymentMethod::find(path.id, connection)?;
+    if payment_method.user_id != auth_user.id() {
+        return application::forbidden("This payment method does not belong to you");
+    }
+
+    let payment_method = payment_method.set_default(auth_user.id(), connection)?;
+    Ok(HttpResponse::Ok().json(payment_method.for_display()?))
+}
+
+pub fn destroy(
+    (connection, path, auth_user, state): (Connection, Path<PathParameters>, User, State<AppState>),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let payment_method = PaymentMethod::find(path.id, connection)?;
+    if payment_method.user_id != auth_user.id() {
+        return application::forbidden("This payment method does not belong to you");
+    }
+
+    payment_method.destroy(auth_user.id(), connection)?;
+
+    // Providers without stored payment support have nothing to remove on their side
+    if let Ok(client) = state
+        .service_locator
+        .create_stored_payment_processor(payment_method.name)
+    {
+        if let PaymentProcessorBehavior::AuthThenComplete(behavior) = client.behavior() {
+            behavior.remove_repeat_token(&payment_method.provider)?;
+        }
+    }
+
+    Ok(HttpResponse::Ok().finish())
+}
@@ -7,6 +7,8 @@ use bigneon_db::prelude::*;
 use errors::*;
 use extractors::OptionalUser;
 use helpers::application;
+use log::Level::Error;
+use payments::PaymentProcessorBehavior;
 use server::AppState;
 use uuid::Uuid;
 
@@ -45,12 +47,48 @@ pub fn callback(
         None => return application::not_found(),
     };
 
-    // We specifically don't count this as a payment confirmation, that will be done via the IPN
-    // Just redirect to page accordingly
+    // For most providers we specifically don't count this as a payment confirmation, that will be
+    // done via the IPN. Providers that require the payment to be captured on return do so here.
 
     if query.success {
         if payment.status == PaymentStatus::Requested {
-            payment.mark_pending_ipn(user.id(), conn)?;
+            let mut organizations = order.organizations(conn)?;
+            if organizations.len() != 1 {
+                return application::internal_server_error(
+                    "Orders containing more than one organization are not supported",
+                );
+            }
+            let client = state
+                .service_locator
+                .create_payment_processor(payment.provider, &organizations.remove(0))?;
+            let completed = match client.behavior() {
+                PaymentProcessorBehavior::RedirectToPaymentPage(behavior) => {
+                    let prefix = format!("{}-", payment.provider.to_string().to_lowercase());
+                    let provider_reference = payment
+                        .external_reference
+                        .as_ref()
+                        .map(|r| r.trim_start_matches(prefix.as_str()).to_string())
+                        .unwrap_or_default();
+                    behavior.complete_payment_request(&provider_reference)
+                }
+                PaymentProcessorBehavior::AuthThenComplete(_) => Ok(None),
+            };
+
+            match completed {
+                Ok(Some(charge_result)) => {
+                    payment.mark_complete(charge_result.to_json()?, user.id(), conn)?
+                }
+                Ok(None) => payment.mark_pending_ipn(user.id(), conn)?,
+                Err(e) => {
+                    jlog!(Error, "Could not complete payment on return from provider", {"order_id": order.id, "payment_id": payment.id, "error": e.to_string()});
+                    payment.mark_cancelled(json!({ "error": e.to_string() }), user.id(), conn)?;
+                    return application::redirect(&format!(
+                        "{}/events/{}/tickets/confirmation",
+                        state.config.front_end_url,
+                        order.main_event_id(conn)?
+                    ));
+                }
+            }
         }
         application::redirect(&format!(
             "{}/events/{}/tickets/success",
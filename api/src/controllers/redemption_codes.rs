@@ -75,6 +75,11 @@ pub fn show(
             Code::find_by_redemption_code_with_availability(&path.code, query.event_id, conn)
                 .optional()?
         {
+            // Generated unique codes share the parent code's rules but are redeemed individually
+            let redemption_code = match code_available.unique_code_id {
+                Some(_) => path.code.to_uppercase(),
+                None => code_available.code.redemption_code.clone(),
+            };
             let mut ticket_types = Vec::new();
             for ticket_type in TicketType::find_for_code(code_available.code.id, conn)? {
                 ticket_types.push(UserDisplayTicketType::from_ticket_type(
@@ -86,13 +91,13 @@ pub fn show(
                     )?,
                     false,
                     // Passing None for redemption_code as it makes this discount inclusive and we're breaking apart discount here
-                    Some(code_available.code.redemption_code.clone()),
+                    Some(redemption_code.clone()),
                     conn,
                 )?);
             }
             RedemptionCodeResponse::Code {
                 ticket_types,
-                redemption_code: code_available.code.redemption_code.clone(),
+                redemption_code,
                 max_uses: code_available.code.max_uses,
                 available: code_available.available,
                 discount_in_cents: code_available.code.discount_in_cents,
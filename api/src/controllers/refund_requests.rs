@@ -0,0 +1,216 @@
+use actix_web::{http::StatusCode, HttpResponse, Path, Query, State};
+use auth::user::User as AuthUser;
+use bigneon_db::prelude::*;
+use communications::mailers;
+use db::Connection;
+use diesel::PgConnection;
+use errors::*;
+use extractors::*;
+use helpers::application;
+use log::Level::Warn;
+use models::{PathParameters, WebPayload};
+use server::AppState;
+use utils::refunds;
+use uuid::Uuid;
+
+#[derive(Deserialize, Serialize)]
+pub struct CreateRefundRequestRequest {
+    pub ticket_instance_ids: Vec<Uuid>,
+    pub reason: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ApproveRefundRequestsRequest {
+    pub refund_request_ids: Vec<Uuid>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct DenyRefundRequestsRequest {
+    pub refund_request_ids: Vec<Uuid>,
+    pub review_notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct ReviewRefundRequestResult {
+    pub refund_request_id: Uuid,
+    pub status: RefundRequestStatus,
+    pub error: Option<String>,
+}
+
+/// Lets the owner of an order request a refund for some of its tickets. Requests that meet the
+/// organization's auto approval rules are refunded straight away.
+pub fn create(
+    (connection, path, json, user, state): (
+        Connection,
+        Path<PathParameters>,
+        Json<CreateRefundRequestRequest>,
+        AuthUser,
+        State<AppState>,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let conn = connection.get();
+    let order = Order::find(path.id, conn)?;
+    if order.on_behalf_of_user_id.unwrap_or(order.user_id) != user.id() {
+        return application::unauthorized(Some(user), None);
+    }
+
+    let json = json.into_inner();
+    let mut refund_request =
+        RefundRequest::create(order.id, user.id(), json.ticket_instance_ids, json.reason)
+            .commit(conn)?;
+
+    if refund_request.qualifies_for_auto_approval(conn)? {
+        match refunds::approve_refund_request(
+            &refund_request,
+            None,
+            &state.config,
+            &state.service_locator,
+            &connection,
+        ) {
+            Ok(approved_refund_request) => refund_request = approved_refund_request,
+            // Left pending so that the organization can review it
+            Err(e) => {
+                jlog!(Warn, "Could not automatically approve refund request", {"refund_request_id": refund_request.id, "error": e.to_string()});
+            }
+        }
+    }
+
+    application::created(json!(refund_request))
+}
+
+pub fn index_for_order(
+    (connection, path, user): (Connection, Path<PathParameters>, AuthUser),
+) -> Result<HttpResponse, BigNeonError> {
+    let conn = connection.get();
+    let order = Order::find(path.id, conn)?;
+    if order.on_behalf_of_user_id.unwrap_or(order.user_id) != user.id() {
+        return application::unauthorized(Some(user), None);
+    }
+
+    Ok(HttpResponse::Ok().json(&RefundRequest::find_for_order(order.id, conn)?))
+}
+
+/// The organization's refund request queue, filtered by the `status` tag
+pub fn index(
+    (connection, path, query, user): (
+        Connection,
+        Path<PathParameters>,
+        Query<PagingParameters>,
+        AuthUser,
+    ),
+) -> Result<WebPayload<RefundRequest>, BigNeonError> {
+    let conn = connection.get();
+    let organization = Organization::find(path.id, conn)?;
+    user.requires_scope_for_organization(Scopes::OrderRefund, &organization, conn)?;
+
+    let status = match query.get_tag("status") {
+        Some(status) => Some(status.parse::<RefundRequestStatus>()?),
+        None => None,
+    };
+    let payload = RefundRequest::find_for_organization(
+        organization.id,
+        status,
+        query.page(),
+        query.limit(),
+        conn,
+    )?;
+
+    Ok(WebPayload::new(StatusCode::OK, payload))
+}
+
+/// Approves the requests and refunds their tickets. Each request is processed on its own so a
+/// failed refund leaves that request pending without affecting the others.
+pub fn approve(
+    (connection, path, json, user, state): (
+        Connection,
+        Path<PathParameters>,
+        Json<ApproveRefundRequestsRequest>,
+        AuthUser,
+        State<AppState>,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let conn = connection.get();
+    let organization = Organization::find(path.id, conn)?;
+    user.requires_scope_for_organization(Scopes::OrderRefund, &organization, conn)?;
+
+    let mut results = Vec::new();
+    for refund_request in find_for_review(&json.refund_request_ids, &organization, conn)? {
+        let result = refunds::approve_refund_request(
+            &refund_request,
+            Some(user.id()),
+            &state.config,
+            &state.service_locator,
+            &connection,
+        );
+        results.push(match result {
+            Ok(refund_request) => ReviewRefundRequestResult {
+                refund_request_id: refund_request.id,
+                status: refund_request.status,
+                error: None,
+            },
+            Err(e) => ReviewRefundRequestResult {
+                refund_request_id: refund_request.id,
+                status: refund_request.status,
+                error: Some(e.to_string()),
+            },
+        });
+    }
+
+    Ok(HttpResponse::Ok().json(&results))
+}
+
+pub fn deny(
+    (connection, path, json, user, state): (
+        Connection,
+        Path<PathParameters>,
+        Json<DenyRefundRequestsRequest>,
+        AuthUser,
+        State<AppState>,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let conn = connection.get();
+    let organization = Organization::find(path.id, conn)?;
+    user.requires_scope_for_organization(Scopes::OrderRefund, &organization, conn)?;
+
+    let mut results = Vec::new();
+    for refund_request in find_for_review(&json.refund_request_ids, &organization, conn)? {
+        let refund_request = refund_request.deny(user.id(), json.review_notes.clone(), conn)?;
+        let fan = User::find(refund_request.user_id, conn)?;
+        if let (Some(first_name), Some(email)) = (fan.first_name, fan.email) {
+            mailers::orders::refund_request_denied_email(
+                &first_name,
+                email,
+                refund_request.review_notes.as_ref(),
+                &state.config,
+                conn,
+            )?;
+        }
+        results.push(ReviewRefundRequestResult {
+            refund_request_id: refund_request.id,
+            status: refund_request.status,
+            error: None,
+        });
+    }
+
+    Ok(HttpResponse::Ok().json(&results))
+}
+
+fn find_for_review(
+    refund_request_ids: &[Uuid],
+    organization: &Organization,
+    conn: &PgConnection,
+) -> Result<Vec<RefundRequest>, BigNeonError> {
+    let mut result = Vec::new();
+    for id in refund_request_ids {
+        let refund_request = RefundRequest::find(*id, conn)?;
+        if refund_request.organization_id != organization.id {
+            return Err(ApplicationError::new_with_type(
+                ApplicationErrorType::Unprocessable,
+                "Refund request does not belong to this organization".to_string(),
+            )
+            .into());
+        }
+        result.push(refund_request);
+    }
+    Ok(result)
+}
@@ -1,6 +1,6 @@
 use actix_web::{HttpResponse, Path, Query};
 use auth::user::User as AuthUser;
-use bigneon_db::models::{Event, Organization, Report, Scopes};
+use bigneon_db::models::{Event, EventSeries, Organization, Report, Scopes};
 use chrono::prelude::*;
 use db::Connection;
 use errors::*;
@@ -15,6 +15,7 @@ pub struct ReportQueryParameters {
     pub start_utc: Option<NaiveDateTime>,
     pub end_utc: Option<NaiveDateTime>,
     pub event_id: Option<Uuid>,
+    pub event_series_id: Option<Uuid>,
 }
 
 pub fn get_report(
@@ -29,11 +30,13 @@ pub fn get_report(
         "box_office_sales_summary" => box_office_sales_summary((connection, query, path, user)),
         "transaction_details" => transaction_detail_report((connection, query, path, user)),
         "event_summary" => event_summary_report((connection, query, path, user)),
+        "event_series_summary" => event_series_summary_report((connection, query, path, user)),
         "weekly_settlement" => weekly_settlement_report((connection, query, path, user)),
         "ticket_count" => ticket_counts((connection, query, path, user)),
         "audit_report" => audit_report((connection, query, path, user)),
         "reconciliation_summary" => reconciliation_summary_report((connection, query, path, user)),
         "reconciliation_details" => reconciliation_detail_report((connection, query, path, user)),
+        "code_analytics" => code_analytics_report((connection, query, path, user)),
         _ => application::not_found(),
     }
 }
@@ -127,6 +130,38 @@ pub fn event_summary_report(
     Ok(HttpResponse::Ok().json(result))
 }
 
+pub fn event_series_summary_report(
+    (connection, query, path, user): (
+        Connection,
+        Query<ReportQueryParameters>,
+        Path<PathParameters>,
+        AuthUser,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let organization = Organization::find(path.id, connection)?;
+    user.requires_scope_for_organization(Scopes::OrgReports, &organization, connection)?;
+    let event_series = match query.event_series_id {
+        Some(event_series_id) => EventSeries::find(event_series_id, connection)?,
+        None => return application::unprocessable("event_series_id parameter is required"),
+    };
+    if event_series.organization_id != organization.id {
+        return application::unauthorized_with_message(
+            "Event series does not belong to this organization",
+            Some(user),
+            None,
+        );
+    }
+
+    let result = Report::event_series_summary_report(
+        event_series.id,
+        query.start_utc,
+        query.end_utc,
+        connection,
+    )?;
+    Ok(HttpResponse::Ok().json(result))
+}
+
 pub fn audit_report(
     (connection, query, path, user): (
         Connection,
@@ -262,3 +297,36 @@ pub fn reconciliation_detail_report(
         Report::reconciliation_detail_report(path.id, query.start_utc, query.end_utc, connection)?;
     Ok(HttpResponse::Ok().json(result))
 }
+
+pub fn code_analytics_report(
+    (connection, query, path, user): (
+        Connection,
+        Query<ReportQueryParameters>,
+        Path<PathParameters>,
+        AuthUser,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    //Check if they have org admin permissions
+    let organization = Organization::find(path.id, connection)?;
+    if let Some(event_id) = query.event_id {
+        let event = Event::find(event_id, connection)?;
+        user.requires_scope_for_organization_event(
+            Scopes::EventFinancialReports,
+            &organization,
+            &event,
+            connection,
+        )?;
+    } else {
+        user.requires_scope_for_organization(Scopes::OrgReports, &organization, connection)?;
+    }
+
+    let result = Report::code_analytics_report(
+        query.event_id,
+        Some(path.id),
+        query.start_utc,
+        query.end_utc,
+        connection,
+    )?;
+    Ok(HttpResponse::Ok().json(result))
+}
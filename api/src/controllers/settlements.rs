@@ -77,6 +77,6 @@ pub fn destroy(
     let connection = connection.get();
     user.requires_scope(Scopes::OrgAdmin)?;
     let settlement = Settlement::read(path.id, connection)?;
-    settlement.destroy(connection)?;
+    settlement.destroy(Some(user.id()), connection)?;
     Ok(HttpResponse::Ok().json({}))
 }
@@ -1,4 +1,4 @@
-use actix_web::{HttpResponse, Path, Query, State};
+use actix_web::{HttpResponse, Path, Query};
 use auth::user::User;
 use bigneon_db::models::*;
 use chrono::prelude::*;
@@ -9,8 +9,6 @@ use extractors::*;
 use helpers::application;
 use log::Level::Debug;
 use models::{AdminDisplayTicketType, EventTicketPathParameters, PathParameters};
-use server::AppState;
-use tari_client::MessagePayloadCreateAsset as TariNewAsset;
 use uuid::Uuid;
 
 #[derive(Deserialize)]
@@ -36,6 +34,8 @@ pub struct CreateTicketTypeRequest {
     pub price_in_cents: i64,
     pub sold_out_behavior: SoldOutBehavior,
     pub is_private: bool,
+    #[serde(default)]
+    pub refundable: bool,
 }
 
 #[derive(Deserialize, Serialize)]
@@ -62,6 +62,7 @@ pub struct UpdateTicketTypeRequest {
     pub price_in_cents: Option<i64>,
     pub sold_out_behavior: Option<SoldOutBehavior>,
     pub is_private: Option<bool>,
+    pub refundable: Option<bool>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -70,12 +71,11 @@ pub struct DisplayCreatedTicket {
 }
 
 pub fn create(
-    (connection, path, data, user, state): (
+    (connection, path, data, user): (
         Connection,
         Path<PathParameters>,
         Json<CreateTicketTypeRequest>,
         User,
-        State<AppState>,
     ),
 ) -> Result<HttpResponse, BigNeonError> {
     let connection = connection.get();
@@ -99,7 +99,7 @@ pub fn create(
     let org_wallet = Wallet::find_default_for_organization(event.organization_id, connection)?;
 
     //Add new ticket type
-    let ticket_type = event.add_ticket_type(
+    let mut ticket_type = event.add_ticket_type(
         data.name.clone(),
         data.description.clone(),
         data.capacity,
@@ -113,6 +113,16 @@ pub fn create(
         data.is_private,
         connection,
     )?;
+    if data.refundable {
+        ticket_type = ticket_type.update(
+            Some(user.id()),
+            TicketTypeEditableAttributes {
+                refundable: Some(true),
+                ..Default::default()
+            },
+            connection,
+        )?;
+    }
     //Add each ticket pricing entry for newly created ticket type
     for current_pricing_entry in &data.ticket_pricing {
         let _pricing_result = ticket_type.add_ticket_pricing(
@@ -128,25 +138,53 @@ pub fn create(
 
     ticket_type.validate_ticket_pricing(connection)?;
 
-    // TODO: move this to an async processor...
-
-    let tari_asset_id = state.config.tari_client.create_asset(
-        &org_wallet.secret_key,
-        &org_wallet.public_key,
-        TariNewAsset {
-            name: format!("{}.{}", event.id, data.name),
-            total_supply: data.capacity as u64,
-            authorised_signers: Vec::new(),
-            rule_flags: 0,
-            rule_metadata: "".to_string(),
-            expiry_date: data.end_date.timestamp(),
-        },
+    create_tari_asset(
+        &org_wallet,
+        &event,
+        &ticket_type,
+        data.capacity,
+        connection,
     )?;
-    let asset = Asset::find_by_ticket_type(ticket_type.id, connection)?;
-    let _asset = asset.update_blockchain_id(tari_asset_id, connection)?;
     Ok(HttpResponse::Created().json(DisplayCreatedTicket { id: ticket_type.id }))
 }
 
+/// Queues creation of the blockchain asset backing a newly created ticket type
+pub fn create_tari_asset(
+    org_wallet: &Wallet,
+    event: &Event,
+    ticket_type: &TicketType,
+    capacity: u32,
+    connection: &PgConnection,
+) -> Result<(), BigNeonError> {
+    let asset = Asset::find_by_ticket_type(ticket_type.id, connection)?;
+    asset.queue_blockchain_sync(
+        BlockchainOperation::CreateAsset {
+            wallet_id: org_wallet.id,
+            name: format!("{}.{}", event.id, ticket_type.name),
+            total_supply: capacity as u64,
+            expiry_date: ticket_type.end_date.timestamp(),
+        },
+        connection,
+    )?;
+    Ok(())
+}
+
+/// Queues creation of the blockchain assets for all ticket types of an event copied from another
+/// event
+pub fn create_tari_assets(event: &Event, connection: &PgConnection) -> Result<(), BigNeonError> {
+    let org_wallet = event.issuer_wallet(connection)?;
+    for ticket_type in event.ticket_types(false, None, connection)? {
+        create_tari_asset(
+            &org_wallet,
+            event,
+            &ticket_type,
+            ticket_type.valid_ticket_count(connection)?,
+            connection,
+        )?;
+    }
+    Ok(())
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct TicketTypesResponse {
     pub ticket_types: Vec<AdminDisplayTicketType>,
@@ -189,12 +227,7 @@ pub fn index(
 }
 
 pub fn cancel(
-    (connection, path, user, state): (
-        Connection,
-        Path<EventTicketPathParameters>,
-        User,
-        State<AppState>,
-    ),
+    (connection, path, user): (Connection, Path<EventTicketPathParameters>, User),
 ) -> Result<HttpResponse, BigNeonError> {
     let connection = connection.get();
     let event = Event::find(path.event_id, connection)?;
@@ -222,7 +255,6 @@ pub fn cancel(
 
     let valid_unsold_ticket_count = ticket_type.valid_unsold_ticket_count(connection)?;
     nullify_tickets(
-        state,
         organization,
         ticket_type,
         valid_unsold_ticket_count,
@@ -234,12 +266,11 @@ pub fn cancel(
 }
 
 pub fn update(
-    (connection, path, data, user, state): (
+    (connection, path, data, user): (
         Connection,
         Path<EventTicketPathParameters>,
         Json<UpdateTicketTypeRequest>,
         User,
-        State<AppState>,
     ),
 ) -> Result<HttpResponse, BigNeonError> {
     let connection = connection.get();
@@ -281,22 +312,16 @@ pub fn update(
                 connection,
             )?;
             //Issue more tickets on chain
-            match asset.blockchain_asset_id {
-                Some(a) => {
-                    state.config.tari_client.modify_asset_increase_supply(&org_wallet.secret_key,
-                                                                          &org_wallet.public_key,
-                                                                          &a,
-                                                                          requested_capacity as u64,
-                    )?
+            asset.queue_blockchain_sync(
+                BlockchainOperation::IncreaseSupply {
+                    wallet_id: org_wallet.id,
+                    total_supply: requested_capacity as u64,
                 },
-                None => return application::internal_server_error(
-                    "Could not complete capacity increase because the asset has not been assigned on the blockchain",
-                ),
-            }
+                connection,
+            )?;
         } else if valid_ticket_count > requested_capacity {
             let nullify_ticket_count = valid_ticket_count - requested_capacity;
             nullify_tickets(
-                state,
                 organization,
                 ticket_type.clone(),
                 nullify_ticket_count,
@@ -317,8 +342,9 @@ pub fn update(
         price_in_cents: data.price_in_cents,
         sold_out_behavior: data.sold_out_behavior,
         is_private: data.is_private,
+        refundable: data.refundable,
     };
-    let updated_ticket_type = ticket_type.update(update_parameters, connection)?;
+    let updated_ticket_type = ticket_type.update(Some(user.id()), update_parameters, connection)?;
 
     if let Some(ref data_ticket_pricing) = data.ticket_pricing {
         //Retrieve the current list of pricing associated with this ticket_type and remove unwanted pricing
@@ -353,7 +379,11 @@ pub fn update(
                     .iter()
                     .position(|ref r| r.id == current_ticket_pricing_id);
                 match found_index {
-                    Some(index) => ticket_pricing[index].update(update_parameters, connection)?,
+                    Some(index) => ticket_pricing[index].update(
+                        Some(user.id()),
+                        update_parameters,
+                        connection,
+                    )?,
                     None => {
                         return application::internal_server_error(&format!(
                             "Unable to find specified ticket pricing with id {}",
@@ -396,7 +426,6 @@ pub fn update(
 }
 
 fn nullify_tickets(
-    state: State<AppState>,
     organization: Organization,
     ticket_type: TicketType,
     quantity: u32,
@@ -409,24 +438,13 @@ fn nullify_tickets(
     let tickets = TicketInstance::nullify_tickets(asset.id, quantity, user_id, connection)?;
     //Nullify tickets on chain
     if tickets.len() == quantity as usize {
-        let tari_ids: Vec<u64> = (0..tickets.len())
-            .map(|i| tickets[i as usize].token_id as u64)
-            .collect();
-        match asset.blockchain_asset_id {
-            Some(a) => {
-                state.config.tari_client.modify_asset_nullify_tokens(
-                    &org_wallet.secret_key,
-                    &org_wallet.public_key,
-                    &a,
-                    tari_ids,
-                )?;
-            }
-            None => {
-                application::internal_server_error::<HttpResponse>(
-                    "Could not complete capacity increase because the asset has not been assigned on the blockchain",
-                )?;
-            }
-        }
+        asset.queue_blockchain_sync(
+            BlockchainOperation::NullifyTokens {
+                wallet_id: org_wallet.id,
+                token_ids: tickets.iter().map(|t| t.token_id as u64).collect(),
+            },
+            connection,
+        )?;
     } else {
         application::internal_server_error::<HttpResponse>(&format!(
             "Unable to nullify the requested number ({}) of ticket instances",
@@ -16,7 +16,6 @@ use regex::Regex;
 use serde_json::Value;
 use server::AppState;
 use std::collections::HashMap;
-use tari_client::TariClient;
 use uuid::Uuid;
 
 #[derive(Deserialize)]
@@ -122,6 +121,41 @@ pub fn show_redeemable_ticket(
     Ok(HttpResponse::Ok().json(&redeemable_ticket))
 }
 
+#[derive(Deserialize)]
+pub struct OwnershipProofParameters {
+    pub validity_period_in_seconds: Option<u32>,
+}
+
+pub fn ownership_proof(
+    (connection, parameters, query, auth_user): (
+        Connection,
+        Path<PathParameters>,
+        Query<OwnershipProofParameters>,
+        User,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let proof = TicketInstance::create_ownership_proof(
+        parameters.id,
+        auth_user.id(),
+        query
+            .validity_period_in_seconds
+            .unwrap_or(DEFAULT_OWNERSHIP_PROOF_VALIDITY_SECONDS),
+        connection,
+    )?;
+
+    Ok(HttpResponse::Ok().json(&proof))
+}
+
+/// Public check that a ticket is genuine and owned by the holder presenting the proof
+pub fn verify(
+    (connection, proof): (Connection, Json<TicketOwnershipProof>),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let authenticity = TicketInstance::verify_ownership_proof(&proof, connection)?;
+    Ok(HttpResponse::Ok().json(&authenticity))
+}
+
 pub fn send_via_email_or_phone(
     (connection, send_tickets_request, auth_user, state): (
         Connection,
@@ -154,19 +188,7 @@ pub fn send_via_email_or_phone(
 
             let receiver_wallet = user.default_wallet(connection)?;
 
-            // TODO change blockchain client to do transfers from multiple wallets at once
-            for (sender_wallet_id, tickets) in
-                &ticket_instances.into_iter().group_by(|ti| ti.wallet_id)
-            {
-                let sender_wallet = Wallet::find(sender_wallet_id, connection)?;
-                transfer_tickets_on_blockchain(
-                    &tickets.collect_vec(),
-                    connection,
-                    &*state.config.tari_client,
-                    &sender_wallet,
-                    &receiver_wallet,
-                )?;
-            }
+            transfer_tickets_on_blockchain(&ticket_instances, connection, &receiver_wallet)?;
 
             pushers::tickets_received(&user, &auth_user.user, connection)?;
         } else {
@@ -180,6 +202,23 @@ pub fn send_via_email_or_phone(
                 Some("Email"),
                 connection,
             )?;
+
+            // Tickets for a single organization are sent with that organization's branding
+            let ticket_instances =
+                TicketInstance::find_by_ids(&send_tickets_request.ticket_ids, connection)?;
+            let mut organizations: Vec<Organization> = Vec::new();
+            for asset_id in ticket_instances.iter().map(|ti| ti.asset_id).unique() {
+                let organization = Organization::find_by_asset_id(asset_id, connection)?;
+                if !organizations.iter().any(|o| o.id == organization.id) {
+                    organizations.push(organization);
+                }
+            }
+            let organization = if organizations.len() == 1 {
+                organizations.pop()
+            } else {
+                None
+            };
+
             mailers::tickets::send_tickets(
                 &state.config,
                 send_tickets_request.email_or_phone.clone(),
@@ -188,6 +227,7 @@ pub fn send_via_email_or_phone(
                 &authorization.transfer_key.to_string(),
                 &authorization.signature,
                 &auth_user.user,
+                organization.as_ref(),
                 connection,
             )?;
         }
@@ -252,18 +292,17 @@ pub fn transfer_authorization(
 }
 
 pub fn receive_transfer(
-    (connection, transfer_authorization, auth_user, state): (
+    (connection, transfer_authorization, auth_user): (
         Connection,
         Json<TransferAuthorization>,
         User,
-        State<AppState>,
     ),
 ) -> Result<HttpResponse, BigNeonError> {
     auth_user.requires_scope(Scopes::TicketTransfer)?;
     let connection = connection.get();
 
     let sender_wallet =
-        Wallet::find_default_for_user(transfer_authorization.sender_user_id, connection)?;
+        Wallet::find_custodial_for_user(transfer_authorization.sender_user_id, connection)?;
     let receiver_wallet = Wallet::find_default_for_user(auth_user.id(), connection)?;
 
     let tickets = TicketInstance::receive_ticket_transfer(
@@ -273,13 +312,7 @@ pub fn receive_transfer(
         connection,
     )?;
 
-    transfer_tickets_on_blockchain(
-        &tickets,
-        connection,
-        &*state.config.tari_client,
-        &sender_wallet,
-        &receiver_wallet,
-    )?;
+    transfer_tickets_on_blockchain(&tickets, connection, &receiver_wallet)?;
 
     Ok(HttpResponse::Ok().finish())
 }
@@ -287,34 +320,27 @@ pub fn receive_transfer(
 fn transfer_tickets_on_blockchain(
     tickets: &[TicketInstance],
     connection: &PgConnection,
-    tari_client: &TariClient,
-    sender_wallet: &Wallet,
     receiver_wallet: &Wallet,
 ) -> Result<(), BigNeonError> {
-    //Assemble token ids and ticket instance ids for each asset in the order
-    let mut tokens_per_asset: HashMap<Uuid, Vec<u64>> = HashMap::new();
+    //Assemble token ids for each asset and the wallet the tickets were held in
+    let mut tokens_per_asset: HashMap<(Uuid, Uuid), Vec<u64>> = HashMap::new();
     for ticket in tickets {
         tokens_per_asset
-            .entry(ticket.asset_id)
+            .entry((ticket.asset_id, ticket.wallet_id))
             .or_insert_with(|| Vec::new())
             .push(ticket.token_id as u64);
     }
 
-    //Transfer each ticket on chain in batches per asset
-    for (asset_id, token_ids) in &tokens_per_asset {
-        let asset = Asset::find(*asset_id, connection)?;
-        match asset.blockchain_asset_id {
-            Some(a) => {
-                tari_client.transfer_tokens(&sender_wallet.secret_key, &sender_wallet.public_key,
-                                                         &a,
-                                                         token_ids.clone(),
-                                                         receiver_wallet.public_key.clone(),
-                )?
+    //Queue the transfer of each ticket on chain in batches per asset
+    for ((asset_id, sender_wallet_id), token_ids) in tokens_per_asset {
+        Asset::find(asset_id, connection)?.queue_blockchain_sync(
+            BlockchainOperation::TransferTokens {
+                sender_wallet_id,
+                receiver_wallet_id: receiver_wallet.id,
+                token_ids,
             },
-            None => return Err(ApplicationError::new(
-                "Could not complete ticket transfer because the asset has not been assigned on the blockchain".to_string()
-            ).into()),
-        }
+            connection,
+        )?;
     }
     Ok(())
 }
@@ -0,0 +1,112 @@
+use actix_web::{http::header, http::StatusCode, HttpResponse, Path, Query};
+use auth::user::User;
+use bigneon_db::models::*;
+use db::Connection;
+use errors::BigNeonError;
+use extractors::*;
+use helpers::application;
+use models::{PathParameters, WebPayload};
+
+#[derive(Default, Deserialize, Serialize)]
+pub struct GenerateUniqueCodesRequest {
+    pub quantity: u32,
+    pub prefix: Option<String>,
+    pub character_set: Option<String>,
+    pub length: Option<u32>,
+}
+
+pub fn index(
+    (conn, path, query_parameters, user): (
+        Connection,
+        Path<PathParameters>,
+        Query<PagingParameters>,
+        User,
+    ),
+) -> Result<WebPayload<DisplayUniqueCode>, BigNeonError> {
+    let conn = conn.get();
+    let code = Code::find(path.id, conn)?;
+    user.requires_scope_for_organization_event(
+        Scopes::CodeRead,
+        &code.organization(conn)?,
+        &code.event(conn)?,
+        conn,
+    )?;
+
+    let unique_codes = UniqueCode::find_for_code(
+        code.id,
+        query_parameters.page(),
+        query_parameters.limit(),
+        conn,
+    )?;
+    Ok(WebPayload::new(StatusCode::OK, unique_codes))
+}
+
+pub fn create(
+    (conn, req, path, user): (
+        Connection,
+        Json<GenerateUniqueCodesRequest>,
+        Path<PathParameters>,
+        User,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let conn = conn.get();
+    let code = Code::find(path.id, conn)?;
+    user.requires_scope_for_organization_event(
+        Scopes::CodeWrite,
+        &code.organization(conn)?,
+        &code.event(conn)?,
+        conn,
+    )?;
+
+    let req = req.into_inner();
+    let unique_codes = UniqueCode::generate(
+        &code,
+        req.quantity,
+        req.prefix,
+        req.character_set,
+        req.length,
+        user.id(),
+        conn,
+    )?;
+    application::created(json!(unique_codes))
+}
+
+/// CSV of the code's generated codes for handing out to partners
+pub fn export(
+    (conn, path, user): (Connection, Path<PathParameters>, User),
+) -> Result<HttpResponse, BigNeonError> {
+    let conn = conn.get();
+    let code = Code::find(path.id, conn)?;
+    user.requires_scope_for_organization_event(
+        Scopes::CodeRead,
+        &code.organization(conn)?,
+        &code.event(conn)?,
+        conn,
+    )?;
+
+    // Generated codes only contain letters, numbers and dashes so need no escaping
+    let mut csv = "redemption_code,created_at,order_id,redeemed_at\n".to_string();
+    for unique_code in UniqueCode::find_all_for_code(code.id, conn)? {
+        csv.push_str(&format!(
+            "{},{},{},{}\n",
+            unique_code.redemption_code,
+            unique_code.created_at,
+            unique_code
+                .order_id
+                .map(|id| id.to_string())
+                .unwrap_or_default(),
+            unique_code
+                .redeemed_at
+                .map(|date| date.to_string())
+                .unwrap_or_default(),
+        ));
+    }
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/csv")
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"codes-{}.csv\"", code.id),
+        )
+        .body(csv))
+}
@@ -1,6 +1,6 @@
 use actix_web;
 use actix_web::Responder;
-use actix_web::{http::StatusCode, HttpRequest, HttpResponse, Path, Query};
+use actix_web::{http::header, http::StatusCode, HttpRequest, HttpResponse, Path, Query};
 use auth::user::User as AuthUser;
 use bigneon_db::prelude::*;
 use communications::mailers;
@@ -59,6 +59,20 @@ pub fn current_user(
     current_user_from_user(&auth_user.user, connection)
 }
 
+pub fn recommendations(
+    (connection, query_parameters, auth_user): (Connection, Query<PagingParameters>, AuthUser),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let recommendations = EventRecommendation::find_for_user(
+        auth_user.id(),
+        query_parameters.page(),
+        query_parameters.limit(),
+        connection,
+    )?;
+
+    Ok(HttpResponse::Ok().json(&recommendations))
+}
+
 pub fn profile(
     (connection, path, auth_user): (Connection, Path<OrganizationFanPathParameters>, AuthUser),
 ) -> Result<HttpResponse, BigNeonError> {
@@ -118,6 +132,41 @@ pub fn update_current_user(
     Ok(current_user)
 }
 
+pub fn export_current_user(
+    (connection, auth_user): (Connection, AuthUser),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let export = UserDataExport::for_user(&auth_user.user, connection)?;
+
+    Ok(HttpResponse::Ok()
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!(
+                "attachment; filename=\"bigneon-export-{}.json\"",
+                auth_user.id()
+            ),
+        )
+        .json(&export))
+}
+
+pub fn delete_current_user(
+    (connection, auth_user): (Connection, AuthUser),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    // Organizations would be left without their member, they have to remove them first
+    if !auth_user.user.organizations(connection)?.is_empty() {
+        return application::unprocessable(
+            "Users belonging to an organization cannot delete their account",
+        );
+    }
+
+    let action = auth_user
+        .user
+        .queue_deletion(Some(auth_user.id()), connection)?;
+
+    Ok(HttpResponse::Accepted().json(json!({ "domain_action_id": action.id })))
+}
+
 pub fn show(
     (connection, parameters, auth_user): (Connection, Path<PathParameters>, AuthUser),
 ) -> Result<HttpResponse, BigNeonError> {
@@ -238,9 +287,13 @@ pub fn register(
         }
     }
 
+    let marketing_consent_organization = find_marketing_consent_organization(
+        parameters.marketing_consent_organization_id,
+        connection.get(),
+    )?;
     let new_user: NewUser = parameters.into_inner().into();
-    match new_user.commit(connection.get()) {
-        Ok(_) => (),
+    let user = match new_user.commit(connection.get()) {
+        Ok(u) => u,
         Err(e) => match e.error_code {
             ErrorCode::DuplicateKeyError => {
                 return application::unprocessable("A user with this email already exists");
@@ -248,6 +301,11 @@ pub fn register(
             _ => return Err(e.into()),
         },
     };
+    record_registration_marketing_consent(
+        &user,
+        marketing_consent_organization.as_ref(),
+        connection.get(),
+    )?;
 
     if let (Some(first_name), Some(email)) = (new_user.first_name, new_user.email) {
         mailers::user::user_registered(first_name, email, &state.config, connection.get())?;
@@ -286,9 +344,13 @@ pub fn register_and_login(
 
     let email = parameters.email.clone();
     let password = parameters.password.clone();
+    let marketing_consent_organization = find_marketing_consent_organization(
+        parameters.marketing_consent_organization_id,
+        connection.get(),
+    )?;
     let new_user: NewUser = parameters.into_inner().into();
-    match new_user.commit(connection.get()) {
-        Ok(_) => (),
+    let user = match new_user.commit(connection.get()) {
+        Ok(u) => u,
         Err(e) => match e.error_code {
             ErrorCode::DuplicateKeyError => {
                 return application::unprocessable("A user with this email already exists");
@@ -296,6 +358,11 @@ pub fn register_and_login(
             _ => return Err(e.into()),
         },
     };
+    record_registration_marketing_consent(
+        &user,
+        marketing_consent_organization.as_ref(),
+        connection.get(),
+    )?;
     let json = Json(LoginRequest::new(&email, &password));
     let token_response =
         auth::token((http_request.clone(), connection.clone(), json, request_info))?;
@@ -307,6 +374,35 @@ pub fn register_and_login(
     Ok(HttpResponse::Created().json(token_response))
 }
 
+/// Checked before the user is created so an unknown organization does not leave a half
+/// registered user behind
+fn find_marketing_consent_organization(
+    organization_id: Option<Uuid>,
+    conn: &PgConnection,
+) -> Result<Option<Organization>, BigNeonError> {
+    match organization_id {
+        Some(organization_id) => Ok(Some(Organization::find(organization_id, conn)?)),
+        None => Ok(None),
+    }
+}
+
+fn record_registration_marketing_consent(
+    user: &User,
+    organization: Option<&Organization>,
+    conn: &PgConnection,
+) -> Result<(), BigNeonError> {
+    if let Some(organization) = organization {
+        MarketingConsent::record(
+            user.id,
+            organization.id,
+            true,
+            MarketingConsentSources::Registration,
+            conn,
+        )?;
+    }
+    Ok(())
+}
+
 fn current_user_from_user(
     user: &User,
     connection: &PgConnection,
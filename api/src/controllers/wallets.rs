@@ -0,0 +1,66 @@
+use actix_web::{HttpResponse, Path};
+use auth::user::User;
+use bigneon_db::models::*;
+use db::Connection;
+use errors::*;
+use extractors::*;
+use helpers::application;
+use models::PathParameters;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct WalletsResponse {
+    pub wallets: Vec<DisplayWallet>,
+    pub tokens: Vec<WalletToken>,
+}
+
+pub fn index((connection, auth_user): (Connection, User)) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let wallets = Wallet::find_for_user(auth_user.id(), connection)?
+        .into_iter()
+        .map(|w| w.into())
+        .collect();
+    let tokens = Wallet::tokens_for_user(auth_user.id(), connection)?;
+    Ok(HttpResponse::Ok().json(WalletsResponse { wallets, tokens }))
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RegisterSelfCustodyKeyRequest {
+    pub public_key: String,
+    /// Signature of the user id followed by the public key, made with the matching secret key
+    pub signature: String,
+}
+
+pub fn register_self_custody_key(
+    (connection, json, auth_user): (Connection, Json<RegisterSelfCustodyKeyRequest>, User),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let json = json.into_inner();
+    let wallet = Wallet::register_self_custody_key(
+        auth_user.id(),
+        json.public_key,
+        json.signature,
+        connection,
+    )?;
+    Ok(HttpResponse::Created().json(DisplayWallet::from(wallet)))
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ExportWalletRequest {
+    pub passphrase: String,
+}
+
+pub fn export(
+    (connection, path, json, auth_user): (
+        Connection,
+        Path<PathParameters>,
+        Json<ExportWalletRequest>,
+        User,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let wallet = Wallet::find(path.id, connection)?;
+    if wallet.user_id != Some(auth_user.id()) {
+        return application::forbidden("This wallet does not belong to you");
+    }
+    Ok(HttpResponse::Ok().json(wallet.export(&json.passphrase)?))
+}
@@ -2,6 +2,7 @@ use bigneon_db::prelude::*;
 use chrono::prelude::*;
 use db::Connection;
 use errors::BigNeonError;
+use futures::future;
 use futures::Async;
 use futures::Future;
 
@@ -13,6 +14,7 @@ pub struct ExecutorFuture {
     action: DomainAction,
     conn: Connection,
     inner: Box<Future<Item = (), Error = BigNeonError>>,
+    rescheduled_until: Option<NaiveDateTime>,
 }
 
 unsafe impl Send for ExecutorFuture {}
@@ -28,8 +30,21 @@ impl ExecutorFuture {
             conn,
             started_at: Utc::now().naive_utc(),
             inner: future,
+            rescheduled_until: None,
         }
     }
+
+    /// Leaves the action pending so it runs again after `blocked_until`, without using up one
+    /// of its attempts
+    pub fn rescheduled(
+        action: DomainAction,
+        conn: Connection,
+        blocked_until: NaiveDateTime,
+    ) -> ExecutorFuture {
+        let mut executor_future = ExecutorFuture::new(action, conn, Box::new(future::ok(())));
+        executor_future.rescheduled_until = Some(blocked_until);
+        executor_future
+    }
 }
 
 impl Future for ExecutorFuture {
@@ -40,6 +55,17 @@ impl Future for ExecutorFuture {
         match self.inner.poll() {
             Ok(inner) => match inner {
                 Async::Ready(r) => {
+                    if let Some(blocked_until) = self.rescheduled_until {
+                        jlog!(Info,
+                        "bigneon::domain_actions",
+                        "Action rescheduled",
+                         { "domain_action_id": self.action.id,
+                          "blocked_until": blocked_until
+                           });
+                        self.action.reschedule(blocked_until, &self.conn.get())?;
+                        self.conn.commit_transaction()?;
+                        return Ok(Async::Ready(r));
+                    }
                     jlog!(Info,
                     "bigneon::domain_actions",
                      "Action succeeded",
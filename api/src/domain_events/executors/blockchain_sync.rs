@@ -0,0 +1,223 @@
+use bigneon_db::prelude::*;
+use chrono::prelude::*;
+use chrono::Duration;
+use config::{Config, Environment};
+use db::Connection;
+use diesel::PgConnection;
+use domain_events::executor_future::ExecutorFuture;
+use domain_events::routing::DomainActionExecutor;
+use errors::*;
+use futures::future;
+use log::Level::Error;
+use serde_json;
+use tari_client::MessagePayloadCreateAsset as TariNewAsset;
+use uuid::Uuid;
+
+/// Delay before a sync waiting on earlier syncs for the same asset is run again
+const WAITING_SYNC_DELAY_SECONDS: i64 = 30;
+
+pub struct BlockchainSyncExecutor {
+    config: Config,
+}
+
+impl DomainActionExecutor for BlockchainSyncExecutor {
+    fn execute(&self, action: DomainAction, conn: Connection) -> ExecutorFuture {
+        match self.perform_job(&action, &conn) {
+            Ok(Some(blocked_until)) => ExecutorFuture::rescheduled(action, conn, blocked_until),
+            Ok(None) => ExecutorFuture::new(action, conn, Box::new(future::ok(()))),
+            Err(e) => {
+                jlog!(Error, "Blockchain sync action failed", {"action_id": action.id, "main_table_id": action.main_table_id, "attempt_count": action.attempt_count, "error": e.to_string()});
+                if action.attempt_count + 1 >= action.max_attempt_count {
+                    if let Err(e) = self.mark_failed(&action, &conn) {
+                        jlog!(Error, "Could not mark asset as failed", {"action_id": action.id, "main_table_id": action.main_table_id, "error": e.to_string()});
+                    }
+                }
+                ExecutorFuture::new(action, conn, Box::new(future::err(e)))
+            }
+        }
+    }
+}
+
+impl BlockchainSyncExecutor {
+    pub fn new(config: Config) -> BlockchainSyncExecutor {
+        BlockchainSyncExecutor { config }
+    }
+
+    /// Returns when to run the sync again if it has to wait for earlier syncs for the asset
+    fn perform_job(
+        &self,
+        action: &DomainAction,
+        conn: &Connection,
+    ) -> Result<Option<NaiveDateTime>, BigNeonError> {
+        let connection = conn.get();
+        let asset = Asset::find(self.asset_id(action)?, connection)?;
+        let operation: BlockchainOperation = serde_json::from_value(action.payload.clone())?;
+
+        // Operations on an asset are applied in the order they were queued. Expired syncs are
+        // left for reconciliation.
+        let now = Utc::now().naive_utc();
+        let other_syncs: Vec<DomainAction> = asset
+            .blockchain_syncs(connection)?
+            .into_iter()
+            .filter(|a| a.id != action.id)
+            .collect();
+        let is_queued =
+            |a: &DomainAction| a.status == DomainActionStatus::Pending && a.expires_at > now;
+        if other_syncs
+            .iter()
+            .any(|a| is_queued(a) && a.created_at < action.created_at)
+        {
+            return Ok(Some(now + Duration::seconds(WAITING_SYNC_DELAY_SECONDS)));
+        }
+
+        let tari_client = &self.config.tari_client;
+        match operation {
+            BlockchainOperation::CreateAsset {
+                wallet_id,
+                name,
+                total_supply,
+                expiry_date,
+            } => {
+                // A previous attempt may have created the asset before failing
+                if asset.blockchain_asset_id.is_none() {
+                    let wallet = Wallet::find(wallet_id, connection)?;
+                    let tari_asset_id = tari_client.create_asset(
+                        &wallet.secret_key,
+                        &wallet.public_key,
+                        TariNewAsset {
+                            name,
+                            total_supply,
+                            authorised_signers: Vec::new(),
+                            rule_flags: 0,
+                            rule_metadata: "".to_string(),
+                            expiry_date,
+                        },
+                    )?;
+                    asset.update_blockchain_id(tari_asset_id, connection)?;
+                }
+            }
+            BlockchainOperation::IncreaseSupply {
+                wallet_id,
+                total_supply,
+            } => {
+                let wallet = Wallet::find(wallet_id, connection)?;
+                tari_client.modify_asset_increase_supply(
+                    &wallet.secret_key,
+                    &wallet.public_key,
+                    &self.blockchain_asset_id(&asset)?,
+                    total_supply,
+                )?;
+            }
+            BlockchainOperation::NullifyTokens {
+                wallet_id,
+                token_ids,
+            } => {
+                let wallet = Wallet::find(wallet_id, connection)?;
+                tari_client.modify_asset_nullify_tokens(
+                    &wallet.secret_key,
+                    &wallet.public_key,
+                    &self.blockchain_asset_id(&asset)?,
+                    token_ids,
+                )?;
+            }
+            BlockchainOperation::RedeemTokens {
+                wallet_id,
+                token_ids,
+            } => {
+                let wallet = self.signing_wallet(&asset, wallet_id, connection)?;
+                tari_client.modify_asset_redeem_token(
+                    &wallet.secret_key,
+                    &wallet.public_key,
+                    &self.blockchain_asset_id(&asset)?,
+                    token_ids,
+                )?;
+            }
+            BlockchainOperation::TransferTokens {
+                sender_wallet_id,
+                receiver_wallet_id,
+                token_ids,
+            } => {
+                // Only the holder can transfer their tokens so Big Neon cannot move tokens out
+                // of a self-custodied wallet
+                let sender_wallet = Wallet::find(sender_wallet_id, connection)?;
+                if sender_wallet.self_custodied {
+                    return Err(ApplicationError::new(
+                        "Tokens in a self-custodied wallet can only be transferred by their holder"
+                            .to_string(),
+                    )
+                    .into());
+                }
+                let receiver_wallet = Wallet::find(receiver_wallet_id, connection)?;
+                tari_client.transfer_tokens(
+                    &sender_wallet.secret_key,
+                    &sender_wallet.public_key,
+                    &self.blockchain_asset_id(&asset)?,
+                    token_ids,
+                    receiver_wallet.public_key,
+                )?;
+            }
+        }
+
+        // The asset stays pending while other syncs are queued and failed until they are retried
+        let status = if other_syncs.iter().any(|a| is_queued(a)) {
+            AssetStatus::Pending
+        } else if other_syncs
+            .iter()
+            .any(|a| a.status != DomainActionStatus::Success)
+        {
+            AssetStatus::Failed
+        } else {
+            AssetStatus::Synced
+        };
+        Asset::find(asset.id, connection)?.update_status(status, connection)?;
+
+        Ok(None)
+    }
+
+    /// Marks the asset as failed once the action has used its last attempt. Committed straight
+    /// away as the action's own changes are rolled back.
+    fn mark_failed(&self, action: &DomainAction, conn: &Connection) -> Result<(), BigNeonError> {
+        if self.config.environment != Environment::Test {
+            conn.rollback_transaction()?;
+            conn.begin_transaction()?;
+        }
+        Asset::find(self.asset_id(action)?, conn.get())?
+            .update_status(AssetStatus::Failed, conn.get())?;
+        if self.config.environment != Environment::Test {
+            conn.commit_transaction()?;
+            conn.begin_transaction()?;
+        }
+        Ok(())
+    }
+
+    /// Big Neon cannot sign for self-custodied wallets, so redemptions of tokens held in them are
+    /// signed by the event's issuer instead
+    fn signing_wallet(
+        &self,
+        asset: &Asset,
+        wallet_id: Uuid,
+        connection: &PgConnection,
+    ) -> Result<Wallet, BigNeonError> {
+        let wallet = Wallet::find(wallet_id, connection)?;
+        if !wallet.self_custodied {
+            return Ok(wallet);
+        }
+        let ticket_type = TicketType::find(asset.ticket_type_id, connection)?;
+        Ok(Event::find(ticket_type.event_id, connection)?.issuer_wallet(connection)?)
+    }
+
+    fn asset_id(&self, action: &DomainAction) -> Result<Uuid, BigNeonError> {
+        Ok(action.main_table_id.ok_or(ApplicationError::new(
+            "No asset id attached to domain action".to_string(),
+        ))?)
+    }
+
+    fn blockchain_asset_id(&self, asset: &Asset) -> Result<String, BigNeonError> {
+        Ok(asset
+            .blockchain_asset_id
+            .clone()
+            .ok_or(ApplicationError::new(
+                "Asset has not been created on the blockchain yet".to_string(),
+            ))?)
+    }
+}
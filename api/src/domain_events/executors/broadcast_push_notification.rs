@@ -6,7 +6,9 @@ use errors::*;
 use futures::future;
 use itertools::Itertools;
 use log::Level::Error;
+use std::collections::HashSet;
 use utils::communication::*;
+use uuid::Uuid;
 
 pub struct BroadcastPushNotificationExecutor {}
 
@@ -46,10 +48,33 @@ impl BroadcastPushNotificationExecutor {
             ),
         };
 
+        let audience_type = match broadcast.fan_segment_id {
+            Some(_) => BroadcastAudience::FanSegment,
+            None => audience_type,
+        };
+
         let audience = match audience_type {
             BroadcastAudience::PeopleAtTheEvent => {
                 Event::checked_in_users(broadcast.event_id, conn.get())?
             }
+            BroadcastAudience::FanSegment => {
+                let fan_segment_id = broadcast.fan_segment_id.ok_or(ApplicationError::new(
+                    "No fan segment attached to broadcast".to_string(),
+                ))?;
+                let fan_segment = FanSegment::find(fan_segment_id, conn.get())?;
+                // Only fans who opted in to the organization's marketing are sent broadcasts
+                let consented_user_ids: HashSet<Uuid> =
+                    MarketingConsent::consented_user_ids(fan_segment.organization_id, conn.get())?
+                        .into_iter()
+                        .collect();
+                let mut users = Vec::new();
+                for fan in fan_segment.all_members(conn.get())? {
+                    if consented_user_ids.contains(&fan.user_id) {
+                        users.push(User::find(fan.user_id, conn.get())?);
+                    }
+                }
+                users
+            }
         };
 
         for user in audience {
@@ -60,19 +85,22 @@ impl BroadcastPushNotificationExecutor {
                 .collect_vec();
 
             if tokens.len() > 0 {
+                let mut communication = Communication::new(
+                    CommunicationType::Push,
+                    message.to_string(),
+                    None,
+                    None,
+                    CommAddress::from_vec(tokens),
+                    None,
+                    None,
+                );
+                // Fan segment broadcasts are promotional rather than about tickets already held
+                communication.marketing = audience_type == BroadcastAudience::FanSegment;
                 DomainAction::create(
                     None,
                     DomainActionTypes::Communication,
                     Some(CommunicationChannelType::Push),
-                    serde_json::to_value(Communication::new(
-                        CommunicationType::Push,
-                        message.to_string(),
-                        None,
-                        None,
-                        CommAddress::from_vec(tokens),
-                        None,
-                        None,
-                    ))?,
+                    serde_json::to_value(communication)?,
                     Some(Tables::Events.to_string()),
                     Some(action_data.event_id),
                 )
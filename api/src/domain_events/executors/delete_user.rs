@@ -0,0 +1,78 @@
+use bigneon_db::prelude::*;
+use config::Config;
+use db::Connection;
+use domain_events::executor_future::ExecutorFuture;
+use domain_events::routing::DomainActionExecutor;
+use errors::*;
+use futures::future;
+use log::Level::Error;
+use payments::PaymentProcessorBehavior;
+use serde_json;
+use utils::marketing_contacts::provider_for_organization;
+use utils::ServiceLocator;
+
+pub struct DeleteUserExecutor {
+    config: Config,
+    service_locator: ServiceLocator,
+}
+
+impl DomainActionExecutor for DeleteUserExecutor {
+    fn execute(&self, action: DomainAction, conn: Connection) -> ExecutorFuture {
+        match self.perform_job(&action, &conn) {
+            Ok(_) => ExecutorFuture::new(action, conn, Box::new(future::ok(()))),
+            Err(e) => {
+                jlog!(Error, "Delete user action failed", {"action_id": action.id, "main_table_id": action.main_table_id, "error": e.to_string()});
+                ExecutorFuture::new(action, conn, Box::new(future::err(e)))
+            }
+        }
+    }
+}
+
+impl DeleteUserExecutor {
+    pub fn new(config: Config) -> DeleteUserExecutor {
+        DeleteUserExecutor {
+            service_locator: ServiceLocator::new(&config),
+            config,
+        }
+    }
+
+    fn perform_job(&self, action: &DomainAction, conn: &Connection) -> Result<(), BigNeonError> {
+        let connection = conn.get();
+        let user_id = action.main_table_id.ok_or(ApplicationError::new(
+            "No user id attached to domain action".to_string(),
+        ))?;
+        let payload: DeleteUserPayload = serde_json::from_value(action.payload.clone())?;
+        let user = User::find(user_id, connection)?;
+
+        // Stored cards live with the provider, remove them there before the local records go.
+        // Retries after a failed anonymization find the customers already removed, which the
+        // provider treats as success.
+        for payment_method in user.payment_methods(connection)? {
+            if let Ok(client) = self
+                .service_locator
+                .create_stored_payment_processor(payment_method.name)
+            {
+                if let PaymentProcessorBehavior::AuthThenComplete(behavior) = client.behavior() {
+                    behavior.remove_repeat_token(&payment_method.provider)?;
+                }
+            }
+        }
+
+        // Fans are only copied to the marketing lists of organizations they gave consent to
+        if let Some(ref email) = user.email {
+            for consent in MarketingConsent::find_for_user(user.id, connection)? {
+                let mut organization = Organization::find(consent.organization_id, connection)?;
+                organization.decrypt(&self.config.api_keys_encryption_key)?;
+                if let Some(provider) =
+                    provider_for_organization(&organization, &self.config, connection)?
+                {
+                    provider.remove_contact(email)?;
+                }
+            }
+        }
+
+        user.anonymize(payload.requested_by_user_id, connection)?;
+
+        Ok(())
+    }
+}
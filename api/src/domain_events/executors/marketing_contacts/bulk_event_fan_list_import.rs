@@ -5,12 +5,13 @@ use db::Connection;
 use diesel::PgConnection;
 use domain_events::executor_future::ExecutorFuture;
 use domain_events::routing::DomainActionExecutor;
-use errors::{ApplicationError, BigNeonError};
+use errors::BigNeonError;
 use futures::future;
 use log::Level::*;
 use std::default::Default;
-use utils::marketing_contacts::BulkEventFanListImportAction;
-use utils::sendgrid::contacts::{SGContact, SGContactList};
+use utils::marketing_contacts::{
+    event_list_name, marketable_contacts, provider_for_organization, BulkEventFanListImportAction,
+};
 use uuid::Uuid;
 
 const LOG_TARGET: &'static str = "bigneon::domain_actions::marketing_contacts";
@@ -85,10 +86,10 @@ impl BulkEventFanListImportExecutor {
 
         org.decrypt(&self.config.api_keys_encryption_key)?;
 
-        let api_key = match org.sendgrid_api_key {
+        let provider = match provider_for_organization(&org, &self.config, conn)? {
             Some(v) => v,
             None => {
-                jlog!(Info, LOG_TARGET, &format!("No sendgrid api key for org {}", event.organization_id), { "event_id": event.id });
+                jlog!(Info, LOG_TARGET, &format!("No marketing contacts api key for org {}", event.organization_id), { "event_id": event.id });
                 return Ok(());
             }
         };
@@ -96,40 +97,25 @@ impl BulkEventFanListImportExecutor {
         let (fans, total) = event.search_fans(None, None, None, None, None, conn)?;
 
         if total > 0 {
-            let contacts = fans
-                .into_iter()
-                .filter(|fan| fan.email.is_some())
-                .map(|fan| SGContact::new(fan.email.unwrap(), fan.first_name, fan.last_name))
-                .collect::<Vec<SGContact>>();
-
-            let result = SGContact::create_many(&api_key, contacts)?;
-
-            if event.sendgrid_list_id.is_none() {
-                return Err(ApplicationError::new(
-                    "Event has no sendgrid list id. Cannot add recipients to list".to_string(),
-                )
-                .into());
-            }
-
-            let sg_list_id = event.sendgrid_list_id.unwrap();
-            jlog!(Debug, LOG_TARGET, &format!("Fetching sendgrid list {}", sg_list_id), {
-                "action_id": action.id,
-                "sendgrid_list_id": sg_list_id,
-                "event_id": event.id,
-                "organization_id": event.organization_id,
-            });
+            // Only fans who opted in to the organization's marketing are exported
+            let contacts = marketable_contacts(event.organization_id, fans, conn)?;
 
-            let sg_list = SGContactList::get_by_id(&api_key, sg_list_id as u64)?;
+            if !contacts.is_empty() {
+                let list_id = provider.find_or_create_list(&event_list_name(&event))?;
+                jlog!(Debug, LOG_TARGET, &format!("Adding recipients to {} list {}", provider.name(), list_id), {
+                    "action_id": action.id,
+                    "list_id": list_id,
+                    "event_id": event.id,
+                    "organization_id": event.organization_id,
+                });
 
-            if !result.persisted_recipients.is_empty() {
-                sg_list.add_recipients(&api_key, result.persisted_recipients)?;
+                let result = provider.add_contacts(&list_id, contacts)?;
 
-                jlog!(Info, LOG_TARGET, &format!("Added {} recipients to sendgrid list '{}'", result.new_count, sg_list.id), {
+                jlog!(Info, LOG_TARGET, &format!("Added {} recipients to {} list '{}'", result.added_count, provider.name(), list_id), {
                     "action_id": action.id,
                     "error_count": result.error_count,
-                    // "error_emails": result.errors.iter().map(|e| e.error_indices).flatten().
-                    "new_count": result.new_count,
-                    "sendgrid_list_id": sg_list.id,
+                    "new_count": result.added_count,
+                    "list_id": list_id,
                     "event_id": event.id,
                     "organization_id": event.organization_id,
                 });
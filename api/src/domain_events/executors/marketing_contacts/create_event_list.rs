@@ -8,14 +8,14 @@ use errors::BigNeonError;
 use futures::future;
 use log::Level::*;
 use std::default::Default;
-use utils::marketing_contacts::BulkEventFanListImportAction;
-use utils::sendgrid::contacts::{SGContactList, SGContactListResponse};
+use utils::marketing_contacts::{
+    event_list_name, provider_for_organization, BulkEventFanListImportAction,
+};
 use uuid::Uuid;
 
 use super::bulk_event_fan_list_import::BulkEventFanListImportPayload;
 
 const LOG_TARGET: &'static str = "bigneon::domain_actions::marketing_contacts";
-const DATE_FORMAT: &'static str = "%b %e, %Y"; // Jun 1, 2019
 
 pub struct CreateEventListExecutor {
     config: Config,
@@ -70,37 +70,41 @@ impl CreateEventListExecutor {
 
         org.decrypt(&self.config.api_keys_encryption_key)?;
 
-        let api_key = match org.sendgrid_api_key {
+        let provider = match provider_for_organization(&org, &self.config, conn)? {
             Some(v) => v,
             None => {
-                jlog!(Info, LOG_TARGET, &format!("No sendgrid api key for org {}", event.organization_id), { "event_id": event.id });
+                jlog!(Info, LOG_TARGET, &format!("No marketing contacts api key for org {}", event.organization_id), { "event_id": event.id });
                 return Ok(());
             }
         };
 
-        jlog!(Info, LOG_TARGET, &format!("Ensuring that event {} has a sendgrid list", event.id), {
+        jlog!(Info, LOG_TARGET, &format!("Ensuring that event {} has a {} list", event.id, provider.name()), {
             "action_id": action.id,
             "event_id": event.id,
             "organization_id": event.organization_id,
         });
 
-        let sg_list = self.ensure_event_list(api_key.as_str(), &event)?;
-        jlog!(Info, LOG_TARGET, &format!("Creating sendgrid list '{}'", sg_list.name), {
+        let list_name = event_list_name(&event);
+        let list_id = provider.find_or_create_list(&list_name)?;
+        jlog!(Info, LOG_TARGET, &format!("Using {} list '{}'", provider.name(), list_name), {
             "action_id": action.id,
             "event_id": event.id,
             "organization_id": event.organization_id,
         });
 
-        if event.sendgrid_list_id.is_none() || event.sendgrid_list_id.unwrap() != sg_list.id as i64
-        {
-            event.update(
-                None,
-                EventEditableAttributes {
-                    sendgrid_list_id: Some(sg_list.id as i64),
-                    ..Default::default()
-                },
-                conn,
-            )?;
+        // Other providers look their lists up by name, only the sendgrid list id is kept
+        if org.sendgrid_api_key.is_some() {
+            let sg_list_id = list_id.parse::<i64>().ok();
+            if sg_list_id.is_some() && event.sendgrid_list_id != sg_list_id {
+                event.update(
+                    None,
+                    EventEditableAttributes {
+                        sendgrid_list_id: sg_list_id,
+                        ..Default::default()
+                    },
+                    conn,
+                )?;
+            }
         }
 
         jlog!(Info, LOG_TARGET, &format!("Enqueing MarketingContactCreateEventList domain action for event={}", event.id), {
@@ -130,17 +134,4 @@ impl CreateEventListExecutor {
         let import_payload = BulkEventFanListImportPayload::new(payload.event_id);
         BulkEventFanListImportAction::from_payload(import_payload).enqueue(conn)
     }
-
-    fn ensure_event_list(
-        &self,
-        api_key: &str,
-        event: &Event,
-    ) -> Result<SGContactListResponse, BigNeonError> {
-        let date_str = event
-            .event_start
-            .map(|date| date.format(DATE_FORMAT).to_string())
-            .unwrap();
-        let name = format!("{} ({})", event.name, date_str);
-        SGContactList::new(name).create_or_return(api_key)
-    }
 }
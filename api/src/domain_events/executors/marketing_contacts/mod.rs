@@ -1,7 +1,11 @@
 pub mod bulk_event_fan_list_import;
 pub mod create_event_list;
+pub mod sync_fan_segment;
 
 pub use self::bulk_event_fan_list_import::{
     BulkEventFanListImportExecutor, BulkEventFanListImportPayload,
 };
 pub use self::create_event_list::{CreateEventListExecutor, CreateEventListPayload};
+pub use self::sync_fan_segment::{
+    queue_fan_segment_sync, SyncFanSegmentExecutor, SyncFanSegmentPayload,
+};
@@ -0,0 +1,124 @@
+use bigneon_db::prelude::*;
+use config::Config;
+use db::Connection;
+use diesel::PgConnection;
+use domain_events::executor_future::ExecutorFuture;
+use domain_events::routing::DomainActionExecutor;
+use errors::BigNeonError;
+use futures::future;
+use log::Level::*;
+use utils::marketing_contacts::{marketable_contacts, provider_for_organization};
+use uuid::Uuid;
+
+const LOG_TARGET: &'static str = "bigneon::domain_actions::marketing_contacts";
+
+pub struct SyncFanSegmentExecutor {
+    config: Config,
+}
+
+impl SyncFanSegmentExecutor {
+    pub fn new(config: Config) -> Self {
+        Self { config }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SyncFanSegmentPayload {
+    pub fan_segment_id: Uuid,
+}
+
+impl DomainActionExecutor for SyncFanSegmentExecutor {
+    fn execute(&self, action: DomainAction, conn: Connection) -> ExecutorFuture {
+        match self.perform_job(&action, &conn) {
+            Ok(_) => ExecutorFuture::new(action, conn, Box::new(future::ok(()))),
+            Err(e) => {
+                jlog!(Error, LOG_TARGET, "Error in SyncFanSegmentExecutor", {
+                    "innerError": format!("{}", e)
+                });
+                ExecutorFuture::new(action, conn, Box::new(future::err(e)))
+            }
+        }
+    }
+}
+
+impl SyncFanSegmentExecutor {
+    /// Copies the fans in the segment who opted in to marketing to a list named after the
+    /// segment, creating the list the first time the segment is synced
+    fn perform_job(
+        &self,
+        action: &DomainAction,
+        connection: &Connection,
+    ) -> Result<(), BigNeonError> {
+        let payload = serde_json::from_value::<SyncFanSegmentPayload>(action.payload.clone())?;
+        let conn = connection.get();
+
+        let fan_segment = FanSegment::find(payload.fan_segment_id, conn)?;
+        let mut org = fan_segment.organization(conn)?;
+        org.decrypt(&self.config.api_keys_encryption_key)?;
+
+        let provider = match provider_for_organization(&org, &self.config, conn)? {
+            Some(v) => v,
+            None => {
+                jlog!(Info, LOG_TARGET, &format!("No marketing contacts api key for org {}", org.id), { "fan_segment_id": fan_segment.id });
+                return Ok(());
+            }
+        };
+
+        let list_id =
+            provider.find_or_create_list(&format!("{} ({})", fan_segment.name, org.name))?;
+        // Other providers look their lists up by name, only the sendgrid list id is kept
+        if org.sendgrid_api_key.is_some() {
+            if let Ok(sg_list_id) = list_id.parse::<i64>() {
+                if fan_segment.sendgrid_list_id != Some(sg_list_id) {
+                    fan_segment.set_sendgrid_list_id(sg_list_id, conn)?;
+                }
+            }
+        }
+
+        let contacts = marketable_contacts(org.id, fan_segment.all_members(conn)?, conn)?;
+        if contacts.is_empty() {
+            return Ok(());
+        }
+
+        let result = provider.add_contacts(&list_id, contacts)?;
+
+        jlog!(Info, LOG_TARGET, &format!("Synced fan segment {} to {} list '{}'", fan_segment.id, provider.name(), list_id), {
+            "action_id": action.id,
+            "error_count": result.error_count,
+            "new_count": result.added_count,
+            "list_id": list_id,
+            "fan_segment_id": fan_segment.id,
+            "organization_id": fan_segment.organization_id,
+        });
+
+        Ok(())
+    }
+}
+
+/// Queues a sync of the segment unless one is already pending
+pub fn queue_fan_segment_sync(
+    fan_segment: &FanSegment,
+    conn: &PgConnection,
+) -> Result<Option<DomainAction>, BigNeonError> {
+    if DomainAction::has_pending_action(
+        DomainActionTypes::MarketingContactsSyncFanSegment,
+        Tables::FanSegments.table_name(),
+        fan_segment.id,
+        conn,
+    )? {
+        return Ok(None);
+    }
+
+    let action = DomainAction::create(
+        None,
+        DomainActionTypes::MarketingContactsSyncFanSegment,
+        None,
+        json!(SyncFanSegmentPayload {
+            fan_segment_id: fan_segment.id,
+        }),
+        Some(Tables::FanSegments.table_name()),
+        Some(fan_segment.id),
+    )
+    .commit(conn)?;
+    Ok(Some(action))
+}
@@ -1,5 +1,13 @@
+pub mod blockchain_sync;
 pub mod broadcast_push_notification;
+pub mod delete_user;
 pub mod marketing_contacts;
 pub mod process_payment_ipn;
+pub mod process_paypal_webhook;
+pub mod reconcile_blockchain_assets;
+pub mod refund_cancelled_event_order;
+pub mod release_hold_inventory;
 pub mod send_communication;
+pub mod send_event_rescheduled;
 pub mod send_order_complete;
+pub mod update_event_recommendations;
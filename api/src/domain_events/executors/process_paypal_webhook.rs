@@ -0,0 +1,135 @@
+use futures::future;
+use log::Level::{Debug, Error};
+use uuid::Uuid;
+
+use bigneon_db::prelude::*;
+use config::Config;
+use controllers::ipns::PaypalWebhookPayload;
+use db::Connection;
+use domain_events::executor_future::ExecutorFuture;
+use domain_events::routing::DomainActionExecutor;
+use errors::ApplicationError;
+use errors::BigNeonError;
+use payments::paypal::PAYPAL_EXTERNAL_REFERENCE_PREFIX;
+use paypal::{Capture, PaypalClient, WebhookEvent};
+
+pub struct ProcessPaypalWebhookExecutor {
+    paypal_client_id: String,
+    paypal_secret: String,
+    paypal_base_url: String,
+    paypal_webhook_id: Option<String>,
+    validate_ipn: bool,
+}
+
+impl DomainActionExecutor for ProcessPaypalWebhookExecutor {
+    fn execute(&self, action: DomainAction, conn: Connection) -> ExecutorFuture {
+        match self.perform_job(&action, &conn) {
+            Ok(_) => ExecutorFuture::new(action, conn, Box::new(future::ok(()))),
+            Err(e) => {
+                jlog!(Error, "PayPal webhook processor failed", {"action_id": action.id, "error": e.to_string()});
+                ExecutorFuture::new(action, conn, Box::new(future::err(e)))
+            }
+        }
+    }
+}
+
+impl ProcessPaypalWebhookExecutor {
+    pub fn new(config: &Config) -> ProcessPaypalWebhookExecutor {
+        ProcessPaypalWebhookExecutor {
+            paypal_client_id: config.paypal_client_id.clone(),
+            paypal_secret: config.paypal_secret.clone(),
+            paypal_base_url: config.paypal_base_url.clone(),
+            paypal_webhook_id: config.paypal_webhook_id.clone(),
+            validate_ipn: config.validate_ipns,
+        }
+    }
+
+    pub fn perform_job(
+        &self,
+        action: &DomainAction,
+        conn: &Connection,
+    ) -> Result<(), BigNeonError> {
+        let payload: PaypalWebhookPayload = serde_json::from_value(action.payload.clone())?;
+
+        if self.validate_ipn {
+            let webhook_id = self
+                .paypal_webhook_id
+                .as_ref()
+                .ok_or(ApplicationError::new(
+                    "PAYPAL_WEBHOOK_ID must be configured to validate PayPal webhooks".to_string(),
+                ))?;
+            let headers = payload.headers.as_ref().ok_or(ApplicationError::new(
+                "Invalid webhook, PayPal transmission headers are missing".to_string(),
+            ))?;
+            let client = PaypalClient::new(
+                self.paypal_client_id.clone(),
+                self.paypal_secret.clone(),
+                self.paypal_base_url.clone(),
+            );
+            client.verify_webhook_signature(webhook_id, headers, &payload.event)?;
+        }
+
+        let event: WebhookEvent = serde_json::from_value(payload.event.clone())?;
+        jlog!(Debug, "Processing PayPal webhook", {"event_id": &event.id, "event_type": &event.event_type});
+
+        let status = match event.event_type.as_str() {
+            "PAYMENT.CAPTURE.COMPLETED" => PaymentStatus::Completed,
+            "PAYMENT.CAPTURE.PENDING" => PaymentStatus::PendingConfirmation,
+            "PAYMENT.CAPTURE.DENIED" | "PAYMENT.CAPTURE.DECLINED" => PaymentStatus::Cancelled,
+            // Refunds are initiated and recorded by us, nothing further to do for other events
+            _ => {
+                jlog!(Debug, "Ignoring PayPal webhook", {"event_id": &event.id, "event_type": &event.event_type});
+                return Ok(());
+            }
+        };
+
+        let capture: Capture = serde_json::from_value(event.resource.clone())?;
+        let order_id = Uuid::parse_str(capture.custom_id.as_ref().ok_or(
+            ApplicationError::new("PayPal capture did not include a custom_id".to_string()),
+        )?)?;
+        let paypal_order_id = capture.order_id().ok_or(ApplicationError::new(
+            "PayPal capture did not include the related order id".to_string(),
+        ))?;
+        let amount = capture
+            .amount
+            .as_ref()
+            .and_then(|a| a.to_cents())
+            .unwrap_or(0);
+
+        let connection = conn.get();
+        let mut order = Order::find(order_id, connection)?;
+        let external_reference = format!("{}{}", PAYPAL_EXTERNAL_REFERENCE_PREFIX, paypal_order_id);
+
+        // The order associated with this payment may have expired before the webhook is received,
+        // the payment is still recorded so that it can be used as store credit in future.
+        let payment = match Payment::find_by_order(order_id, &external_reference, connection)
+            .optional()?
+        {
+            Some(p) => p,
+            None => {
+                jlog!(Debug, "PayPal webhook: No payment found, creating new payment", {"event_id": &event.id, "order_id": order_id});
+                order.add_provider_payment(
+                    Some(external_reference.clone()),
+                    PaymentProviders::Paypal,
+                    None,
+                    amount,
+                    PaymentStatus::Requested,
+                    None,
+                    payload.event.clone(),
+                    connection,
+                )?
+            }
+        };
+
+        if status == PaymentStatus::Completed {
+            if payment.amount != amount {
+                payment.update_amount(None, amount, connection)?;
+            }
+            payment.mark_complete(payload.event, None, connection)?;
+        } else if payment.status != PaymentStatus::Completed {
+            payment.add_ipn(status, payload.event, None, connection)?;
+        }
+
+        Ok(())
+    }
+}
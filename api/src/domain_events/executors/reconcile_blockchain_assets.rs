@@ -0,0 +1,202 @@
+use bigneon_db::prelude::*;
+use chrono::prelude::*;
+use chrono::Duration;
+use config::Config;
+use db::Connection;
+use diesel::PgConnection;
+use domain_events::executor_future::ExecutorFuture;
+use domain_events::routing::DomainActionExecutor;
+use errors::*;
+use futures::future;
+use log::Level::{Error, Warn};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+/// Delay between reconciliation runs
+const RECONCILIATION_INTERVAL_MINUTES: i64 = 60;
+
+pub struct ReconcileBlockchainAssetsExecutor {
+    config: Config,
+}
+
+impl DomainActionExecutor for ReconcileBlockchainAssetsExecutor {
+    fn execute(&self, action: DomainAction, conn: Connection) -> ExecutorFuture {
+        match self.perform_job(&action, &conn) {
+            Ok(_) => ExecutorFuture::new(action, conn, Box::new(future::ok(()))),
+            Err(e) => {
+                jlog!(Error, "Reconcile blockchain assets action failed", {"action_id": action.id, "error": e.to_string()});
+                ExecutorFuture::new(action, conn, Box::new(future::err(e)))
+            }
+        }
+    }
+}
+
+impl ReconcileBlockchainAssetsExecutor {
+    pub fn new(config: Config) -> ReconcileBlockchainAssetsExecutor {
+        ReconcileBlockchainAssetsExecutor { config }
+    }
+
+    fn perform_job(&self, action: &DomainAction, conn: &Connection) -> Result<(), BigNeonError> {
+        let connection = conn.get();
+        for asset in Asset::find_unsynced(connection)? {
+            // A single asset failing to reconcile should not hold up the others
+            if let Err(e) = self.reconcile(&asset, connection) {
+                jlog!(Warn, "Could not reconcile asset", {"asset_id": asset.id, "error": e.to_string()});
+            }
+        }
+        for asset in Asset::find_synced(connection)? {
+            if let Err(e) = self.reconcile_token_owners(&asset, connection) {
+                jlog!(Warn, "Could not reconcile asset token owners", {"asset_id": asset.id, "error": e.to_string()});
+            }
+        }
+
+        // Schedule the next run unless one has been queued already
+        let scheduled =
+            DomainAction::find_scheduled(DomainActionTypes::ReconcileBlockchainAssets, connection)?;
+        if !scheduled.iter().any(|a| a.id != action.id) {
+            queue_reconciliation(
+                Some(Utc::now().naive_utc() + Duration::minutes(RECONCILIATION_INTERVAL_MINUTES)),
+                connection,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Requeues failed syncs, then repairs assets missing on the blockchain or with a lower
+    /// supply on chain than the tickets issued locally
+    fn reconcile(&self, asset: &Asset, connection: &PgConnection) -> Result<(), BigNeonError> {
+        let now = Utc::now().naive_utc();
+        let in_progress = asset
+            .blockchain_syncs(connection)?
+            .iter()
+            .any(|a| a.status == DomainActionStatus::Pending && a.expires_at > now);
+        if in_progress || asset.retry_failed_blockchain_syncs(connection)? > 0 {
+            return Ok(());
+        }
+
+        let ticket_type = TicketType::find(asset.ticket_type_id, connection)?;
+        let event = Event::find(ticket_type.event_id, connection)?;
+        let wallet = event.issuer_wallet(connection)?;
+        let ticket_count = ticket_type.ticket_count(connection)? as u64;
+        match asset.blockchain_asset_id {
+            None => {
+                asset.queue_blockchain_sync(
+                    BlockchainOperation::CreateAsset {
+                        wallet_id: wallet.id,
+                        name: format!("{}.{}", event.id, ticket_type.name),
+                        total_supply: ticket_type.valid_ticket_count(connection)? as u64,
+                        expiry_date: ticket_type.end_date.timestamp(),
+                    },
+                    connection,
+                )?;
+            }
+            Some(ref blockchain_asset_id) => {
+                let asset_info = self.config.tari_client.get_asset_info(
+                    &wallet.secret_key,
+                    &wallet.public_key,
+                    blockchain_asset_id,
+                )?;
+                if asset_info.total_supply < ticket_count {
+                    asset.queue_blockchain_sync(
+                        BlockchainOperation::IncreaseSupply {
+                            wallet_id: wallet.id,
+                            total_supply: ticket_count,
+                        },
+                        connection,
+                    )?;
+                } else {
+                    asset.update_status(AssetStatus::Synced, connection)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Transfers tokens owned by a different wallet on the blockchain than the one holding the
+    /// ticket locally, for example after a transfer sync was lost
+    fn reconcile_token_owners(
+        &self,
+        asset: &Asset,
+        connection: &PgConnection,
+    ) -> Result<(), BigNeonError> {
+        let blockchain_asset_id = match asset.blockchain_asset_id {
+            Some(ref blockchain_asset_id) => blockchain_asset_id,
+            None => return Ok(()),
+        };
+        let token_wallets = asset.token_wallets(connection)?;
+        if token_wallets.is_empty() {
+            return Ok(());
+        }
+
+        let ticket_type = TicketType::find(asset.ticket_type_id, connection)?;
+        let issuer_wallet =
+            Event::find(ticket_type.event_id, connection)?.issuer_wallet(connection)?;
+        let owners: HashMap<u64, String> = self
+            .config
+            .tari_client
+            .get_tokens(
+                &issuer_wallet.secret_key,
+                &issuer_wallet.public_key,
+                blockchain_asset_id,
+                token_wallets
+                    .iter()
+                    .map(|(token_id, _)| *token_id)
+                    .collect(),
+            )?
+            .into_iter()
+            .map(|token| (token.id, token.owner))
+            .collect();
+
+        // Only the owner can transfer a token, so transfers are signed by the wallet holding it
+        // on the blockchain. Tokens held by self-custodied or unknown wallets are left alone.
+        let mut transfers: BTreeMap<(Uuid, Uuid), Vec<u64>> = BTreeMap::new();
+        for (token_id, wallet) in token_wallets {
+            let owner = match owners.get(&token_id) {
+                Some(owner) if *owner != wallet.public_key => owner,
+                _ => continue,
+            };
+            match Wallet::find_by_public_key(owner, connection)? {
+                Some(ref owner_wallet) if !owner_wallet.self_custodied => transfers
+                    .entry((owner_wallet.id, wallet.id))
+                    .or_insert_with(Vec::new)
+                    .push(token_id),
+                _ => {
+                    jlog!(Warn, "Token is not held by a custodial wallet", {"asset_id": asset.id, "token_id": token_id});
+                }
+            }
+        }
+        for ((sender_wallet_id, receiver_wallet_id), token_ids) in transfers {
+            asset.queue_blockchain_sync(
+                BlockchainOperation::TransferTokens {
+                    sender_wallet_id,
+                    receiver_wallet_id,
+                    token_ids,
+                },
+                connection,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Queues a reconciliation run, immediately unless `scheduled_at` is given
+pub fn queue_reconciliation(
+    scheduled_at: Option<NaiveDateTime>,
+    connection: &PgConnection,
+) -> Result<DomainAction, BigNeonError> {
+    let mut action = DomainAction::create(
+        None,
+        DomainActionTypes::ReconcileBlockchainAssets,
+        None,
+        json!({}),
+        None,
+        None,
+    );
+    if let Some(scheduled_at) = scheduled_at {
+        action.schedule_at(scheduled_at);
+    }
+    Ok(action.commit(connection)?)
+}
@@ -0,0 +1,127 @@
+use bigneon_db::prelude::*;
+use config::{Config, Environment};
+use db::Connection;
+use domain_events::executor_future::ExecutorFuture;
+use domain_events::routing::DomainActionExecutor;
+use errors::*;
+use futures::future;
+use log::Level::Error;
+use serde_json;
+use utils::refunds;
+use utils::ServiceLocator;
+
+pub struct RefundCancelledEventOrderExecutor {
+    config: Config,
+    service_locator: ServiceLocator,
+}
+
+impl DomainActionExecutor for RefundCancelledEventOrderExecutor {
+    fn execute(&self, action: DomainAction, conn: Connection) -> ExecutorFuture {
+        match self.perform_job(&action, &conn) {
+            Ok(_) => ExecutorFuture::new(action, conn, Box::new(future::ok(()))),
+            Err(e) => {
+                jlog!(Error, "Refund cancelled event order action failed", {"action_id": action.id, "main_table_id": action.main_table_id, "error": e.to_string()});
+                ExecutorFuture::new(action, conn, Box::new(future::err(e)))
+            }
+        }
+    }
+}
+
+impl RefundCancelledEventOrderExecutor {
+    pub fn new(config: Config) -> RefundCancelledEventOrderExecutor {
+        RefundCancelledEventOrderExecutor {
+            service_locator: ServiceLocator::new(&config),
+            config,
+        }
+    }
+
+    fn perform_job(&self, action: &DomainAction, conn: &Connection) -> Result<(), BigNeonError> {
+        let connection = conn.get();
+        let event_id = action.main_table_id.ok_or(ApplicationError::new(
+            "No event id attached to domain action".to_string(),
+        ))?;
+        let payload: RefundCancelledEventOrderPayload =
+            serde_json::from_value(action.payload.clone())?;
+        let order = Order::find(payload.order_id, connection)?;
+
+        // Only this event's items are refunded, other events in the order are left as is. Event
+        // fees are refunded by the order once none of its tickets remain.
+        let mut items = Vec::new();
+        let mut transferred_ticket_ids = Vec::new();
+        for order_item in order.items(connection)? {
+            if order_item.event_id != Some(event_id)
+                || order_item.item_type != OrderItemTypes::Tickets
+            {
+                continue;
+            }
+            let ticket_instances = TicketInstance::find_for_order_item(order_item.id, connection)?;
+            let refunded_tickets = RefundedTicket::find_by_ticket_instance_ids(
+                ticket_instances.iter().map(|t| t.id).collect(),
+                connection,
+            )?;
+            for ticket_instance in ticket_instances {
+                let already_refunded = refunded_tickets.iter().any(|r| {
+                    r.ticket_instance_id == ticket_instance.id && r.ticket_refunded_at.is_some()
+                });
+                if already_refunded {
+                    continue;
+                }
+                // Transferred tickets are not eligible for automated refunds, they are left for
+                // the organizer to settle manually
+                if ticket_instance.was_transferred(connection)? {
+                    transferred_ticket_ids.push(ticket_instance.id);
+                    continue;
+                }
+                items.push(RefundItem {
+                    order_item_id: order_item.id,
+                    ticket_instance_id: Some(ticket_instance.id),
+                });
+            }
+        }
+
+        if !items.is_empty() {
+            let user_id = order.on_behalf_of_user_id.unwrap_or(order.user_id);
+            let (amount_refunded, _) = refunds::refund_order_items(
+                &order,
+                items,
+                user_id,
+                &self.service_locator,
+                connection,
+            )?;
+
+            // Commit changes as payment completed
+            if self.config.environment != Environment::Test {
+                conn.commit_transaction()?;
+                conn.begin_transaction()?;
+            }
+
+            refunds::send_refund_email(&order, amount_refunded, &self.config, connection)?;
+        }
+
+        // Recorded against the order so it shows up in the event's refund failure export instead
+        // of being reported as fully refunded
+        if !transferred_ticket_ids.is_empty() {
+            let ticket_ids: Vec<String> = transferred_ticket_ids
+                .iter()
+                .map(|id| id.to_string())
+                .collect();
+            DomainEvent::create(
+                DomainEventTypes::OrderManualRefundRequired,
+                format!(
+                    "Transferred tickets require a manual refund: {}",
+                    ticket_ids.join(", ")
+                ),
+                Tables::Orders,
+                Some(order.id),
+                None,
+                Some(json!({
+                    "event_id": event_id,
+                    "ticket_instance_ids": transferred_ticket_ids
+                })),
+            )
+            .commit(connection)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+use bigneon_db::prelude::*;
+use db::Connection;
+use domain_events::executor_future::ExecutorFuture;
+use domain_events::routing::DomainActionExecutor;
+use errors::*;
+use futures::future;
+use log::Level::Error;
+
+pub struct ReleaseHoldInventoryExecutor {}
+
+impl DomainActionExecutor for ReleaseHoldInventoryExecutor {
+    fn execute(&self, action: DomainAction, conn: Connection) -> ExecutorFuture {
+        match self.perform_job(&action, &conn) {
+            Ok(_) => ExecutorFuture::new(action, conn, Box::new(future::ok(()))),
+            Err(e) => {
+                jlog!(Error, "Release hold inventory action failed", {"action_id": action.id, "main_table_id": action.main_table_id, "error": e.to_string()});
+                ExecutorFuture::new(action, conn, Box::new(future::err(e)))
+            }
+        }
+    }
+}
+
+impl ReleaseHoldInventoryExecutor {
+    pub fn new() -> ReleaseHoldInventoryExecutor {
+        ReleaseHoldInventoryExecutor {}
+    }
+
+    fn perform_job(&self, action: &DomainAction, conn: &Connection) -> Result<(), BigNeonError> {
+        let hold_release_id = action.main_table_id.ok_or(ApplicationError::new(
+            "No hold release id attached to domain action".to_string(),
+        ))?;
+        HoldRelease::find(hold_release_id, conn.get())?.release(conn.get())?;
+
+        Ok(())
+    }
+}
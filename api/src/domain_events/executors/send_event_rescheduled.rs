@@ -0,0 +1,83 @@
+use bigneon_db::prelude::*;
+use chrono::prelude::*;
+use communications::{mailers, pushers, smsers};
+use config::Config;
+use db::Connection;
+use domain_events::executor_future::ExecutorFuture;
+use domain_events::routing::DomainActionExecutor;
+use errors::*;
+use futures::future;
+use log::Level::Error;
+
+pub struct SendEventRescheduledExecutor {
+    config: Config,
+}
+
+impl DomainActionExecutor for SendEventRescheduledExecutor {
+    fn execute(&self, action: DomainAction, conn: Connection) -> ExecutorFuture {
+        match self.perform_job(&action, &conn) {
+            Ok(_) => ExecutorFuture::new(action, conn, Box::new(future::ok(()))),
+            Err(e) => {
+                jlog!(Error, "Send event rescheduled action failed", {"action_id": action.id, "main_table_id": action.main_table_id, "error": e.to_string()});
+                ExecutorFuture::new(action, conn, Box::new(future::err(e)))
+            }
+        }
+    }
+}
+
+impl SendEventRescheduledExecutor {
+    pub fn new(config: Config) -> SendEventRescheduledExecutor {
+        SendEventRescheduledExecutor { config }
+    }
+
+    fn perform_job(&self, action: &DomainAction, conn: &Connection) -> Result<(), BigNeonError> {
+        let conn = conn.get();
+        let reschedule_id = action.main_table_id.ok_or(ApplicationError::new(
+            "No event reschedule id attached to domain action".to_string(),
+        ))?;
+        let reschedule = EventReschedule::find(reschedule_id, conn)?;
+        let event = reschedule.event(conn)?;
+
+        // Dates are shown in the venue's timezone
+        let venue = event.venue(conn)?;
+        let format_date = |date: NaiveDateTime| {
+            let format = "%A %-d %B %Y at %-I:%M %p";
+            match Event::localized_time_from_venue(&Some(date), &venue) {
+                Some(local_date) => local_date.format(format).to_string(),
+                None => date.format(format).to_string(),
+            }
+        };
+
+        let mut message = format!(
+            "{} has been rescheduled from {} to {}. Your tickets are valid for the new date.",
+            event.name,
+            format_date(reschedule.previous_event_start),
+            format_date(reschedule.new_event_start)
+        );
+        if let Some(refund_window_ends_at) = reschedule.refund_window_ends_at {
+            message.push_str(&format!(
+                " If you can no longer attend, you can refund your tickets from {}/orders until {}.",
+                self.config.front_end_url,
+                format_date(refund_window_ends_at)
+            ));
+        }
+
+        for user in Event::ticket_holders(event.id, conn)? {
+            if let Some(ref email) = user.email {
+                mailers::events::event_rescheduled(
+                    &self.config,
+                    email.clone(),
+                    &event.name,
+                    &message,
+                    conn,
+                )?;
+            }
+            if let Some(ref phone) = user.phone {
+                smsers::events::event_rescheduled(&self.config, phone.clone(), &message, conn)?;
+            }
+            pushers::event_rescheduled(&user, &message, conn)?;
+        }
+
+        Ok(())
+    }
+}
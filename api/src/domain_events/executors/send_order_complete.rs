@@ -62,44 +62,55 @@ impl SendOrderCompleteExecutor {
             }
         }
 
-        let tari_client = self.config.tari_client.clone();
-
         let new_owner_wallet = Wallet::find_default_for_user(
             order.on_behalf_of_user_id.unwrap_or(order.user_id),
             conn,
         )?;
 
-        for (asset_id, token_ids) in &tokens_per_asset {
-            let asset = Asset::find(*asset_id, conn)?;
-            match asset.blockchain_asset_id {
-                    Some(a) => {
-                        let wallet_id = match wallet_id_per_asset.get(asset_id) {
-                            Some(w) => w.clone(),
-                            None => return Err(ApplicationError::new(
-                                "Could not complete this checkout because wallet id not found for asset".to_string()).into())
-                            ,
-                        };
-                        let org_wallet = Wallet::find(wallet_id, conn)?;
-                        tari_client.transfer_tokens(&org_wallet.secret_key, &org_wallet.public_key,
-                                                    &a,
-                                                    token_ids.clone(),
-                                                    new_owner_wallet.public_key.clone(),
-                        )?
-                    },
-                    None => return Err(ApplicationError::new(
-                        "Could not complete this checkout because the asset has not been assigned on the blockchain".to_string()
-                    ).into()),
+        for (asset_id, token_ids) in tokens_per_asset {
+            let wallet_id = match wallet_id_per_asset.get(&asset_id) {
+                Some(w) => w.clone(),
+                None => {
+                    return Err(ApplicationError::new(
+                        "Could not complete this checkout because wallet id not found for asset"
+                            .to_string(),
+                    )
+                    .into());
                 }
+            };
+            Asset::find(asset_id, conn)?.queue_blockchain_sync(
+                BlockchainOperation::TransferTokens {
+                    sender_wallet_id: wallet_id,
+                    receiver_wallet_id: new_owner_wallet.id,
+                    token_ids,
+                },
+                conn,
+            )?;
         }
 
         let display_order = order.for_display(None, order.user_id, conn)?;
 
         let user = User::find(order.on_behalf_of_user_id.unwrap_or(order.user_id), conn)?;
 
+        // Orders for a single organization are sent with that organization's branding
+        let mut organizations = order.organizations(conn)?;
+        let organization = if organizations.len() == 1 {
+            organizations.pop()
+        } else {
+            None
+        };
+
         //Communicate purchase completed to user
         if let (Some(first_name), Some(email)) = (user.first_name, user.email) {
-            mailers::cart::purchase_completed(&first_name, email, display_order, &self.config)?
-                .queue(conn)?;
+            mailers::cart::purchase_completed(
+                &first_name,
+                email,
+                display_order,
+                organization.as_ref(),
+                &self.config,
+                conn,
+            )?
+            .queue(conn)?;
         }
         Ok(())
     }
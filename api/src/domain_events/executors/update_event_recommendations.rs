@@ -0,0 +1,164 @@
+use bigneon_db::prelude::*;
+use chrono::prelude::*;
+use chrono::Duration;
+use db::Connection as DbConnection;
+use diesel::Connection;
+use diesel::PgConnection;
+use domain_events::executor_future::ExecutorFuture;
+use domain_events::routing::DomainActionExecutor;
+use errors::*;
+use futures::future;
+use log::Level::{Error, Warn};
+use serde_json;
+use std::collections::HashMap;
+use utils::spotify;
+use uuid::Uuid;
+
+/// Delay between recalculating recommendations
+const RECOMMENDATION_INTERVAL_HOURS: i64 = 24;
+/// Users recalculated by each action so a run fits within the action's busy window, the rest
+/// are left to follow up actions
+const RECOMMENDATION_BATCH_SIZE: u32 = 100;
+
+pub struct UpdateEventRecommendationsExecutor {}
+
+impl DomainActionExecutor for UpdateEventRecommendationsExecutor {
+    fn execute(&self, action: DomainAction, conn: DbConnection) -> ExecutorFuture {
+        match self.perform_job(&action, &conn) {
+            Ok(_) => ExecutorFuture::new(action, conn, Box::new(future::ok(()))),
+            Err(e) => {
+                jlog!(Error, "Update event recommendations action failed", {"action_id": action.id, "error": e.to_string()});
+                ExecutorFuture::new(action, conn, Box::new(future::err(e)))
+            }
+        }
+    }
+}
+
+impl UpdateEventRecommendationsExecutor {
+    pub fn new() -> UpdateEventRecommendationsExecutor {
+        UpdateEventRecommendationsExecutor {}
+    }
+
+    fn perform_job(&self, action: &DomainAction, conn: &DbConnection) -> Result<(), BigNeonError> {
+        let connection = conn.get();
+        let payload: UpdateEventRecommendationsPayload =
+            serde_json::from_value(action.payload.clone())?;
+
+        // The batch runs in a savepoint so a failed batch is rolled back without losing the
+        // action queued after it
+        let batch = connection.transaction::<_, BigNeonError, _>(|| {
+            self.update_batch(payload.after_user_id, connection)
+        });
+        match batch {
+            Ok(Some(last_user_id)) => {
+                queue_event_recommendations_update(None, Some(last_user_id), connection)?;
+            }
+            Ok(None) => self.schedule_next_run(action, connection)?,
+            Err(e) => {
+                jlog!(Error, "Could not update event recommendations", {"action_id": action.id, "error": e.to_string()});
+                self.schedule_next_run(action, connection)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Updates the next batch of users, returning the last user updated if more remain
+    fn update_batch(
+        &self,
+        after_user_id: Option<Uuid>,
+        connection: &PgConnection,
+    ) -> Result<Option<Uuid>, BigNeonError> {
+        let user_ids = EventRecommendation::find_users_to_update(
+            after_user_id,
+            RECOMMENDATION_BATCH_SIZE,
+            connection,
+        )?;
+
+        // Related artists are shared by many fans so are only looked up once per batch
+        let mut related_artists: HashMap<String, Vec<String>> = HashMap::new();
+        for user_id in &user_ids {
+            // A single user failing should not hold up the others, so each user is updated in
+            // a savepoint that is rolled back on failure
+            let result = connection.transaction::<_, BigNeonError, _>(|| {
+                self.update_for_user(*user_id, &mut related_artists, connection)
+            });
+            if let Err(e) = result {
+                jlog!(Warn, "Could not update event recommendations", {"user_id": user_id, "error": e.to_string()});
+            }
+        }
+
+        if user_ids.len() < RECOMMENDATION_BATCH_SIZE as usize {
+            return Ok(None);
+        }
+        Ok(user_ids.last().cloned())
+    }
+
+    /// Schedules the next full recalculation unless one has been queued already
+    fn schedule_next_run(
+        &self,
+        action: &DomainAction,
+        connection: &PgConnection,
+    ) -> Result<(), BigNeonError> {
+        let scheduled = DomainAction::find_scheduled(
+            DomainActionTypes::UpdateEventRecommendations,
+            connection,
+        )?;
+        if !scheduled.iter().any(|a| a.id != action.id) {
+            queue_event_recommendations_update(
+                Some(Utc::now().naive_utc() + Duration::hours(RECOMMENDATION_INTERVAL_HOURS)),
+                None,
+                connection,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn update_for_user(
+        &self,
+        user_id: Uuid,
+        related_artists: &mut HashMap<String, Vec<String>>,
+        connection: &PgConnection,
+    ) -> Result<(), BigNeonError> {
+        let mut related_spotify_ids = Vec::new();
+        for spotify_id in EventRecommendation::affinity_spotify_ids(user_id, connection)? {
+            if !related_artists.contains_key(&spotify_id) {
+                // Recommendations are still useful without related artists if Spotify is down
+                let ids = spotify::SINGLETON
+                    .related_artist_ids(&spotify_id)
+                    .unwrap_or_else(|e| {
+                        jlog!(Warn, "Could not load related artists from Spotify", {"spotify_id": &spotify_id, "error": e.to_string()});
+                        Vec::new()
+                    });
+                related_artists.insert(spotify_id.clone(), ids);
+            }
+            related_spotify_ids.extend(related_artists[&spotify_id].iter().cloned());
+        }
+        related_spotify_ids.sort();
+        related_spotify_ids.dedup();
+
+        EventRecommendation::update_for_user(user_id, &related_spotify_ids, connection)?;
+        Ok(())
+    }
+}
+
+/// Queues a recalculation of recommendations, immediately unless `scheduled_at` is given. The
+/// recalculation starts with the users after `after_user_id`, or the first user if not given.
+pub fn queue_event_recommendations_update(
+    scheduled_at: Option<NaiveDateTime>,
+    after_user_id: Option<Uuid>,
+    connection: &PgConnection,
+) -> Result<DomainAction, BigNeonError> {
+    let mut action = DomainAction::create(
+        None,
+        DomainActionTypes::UpdateEventRecommendations,
+        None,
+        json!(UpdateEventRecommendationsPayload { after_user_id }),
+        None,
+        None,
+    );
+    if let Some(scheduled_at) = scheduled_at {
+        action.schedule_at(scheduled_at);
+    }
+    Ok(action.commit(connection)?)
+}
@@ -4,13 +4,21 @@ use config::Config;
 use db::Connection;
 use domain_events::errors::DomainActionError;
 use domain_events::executor_future::ExecutorFuture;
+use domain_events::executors::blockchain_sync::BlockchainSyncExecutor;
 use domain_events::executors::broadcast_push_notification::BroadcastPushNotificationExecutor;
+use domain_events::executors::delete_user::DeleteUserExecutor;
 use domain_events::executors::marketing_contacts::{
-    BulkEventFanListImportExecutor, CreateEventListExecutor,
+    BulkEventFanListImportExecutor, CreateEventListExecutor, SyncFanSegmentExecutor,
 };
 use domain_events::executors::process_payment_ipn::ProcessPaymentIPNExecutor;
+use domain_events::executors::process_paypal_webhook::ProcessPaypalWebhookExecutor;
+use domain_events::executors::reconcile_blockchain_assets::ReconcileBlockchainAssetsExecutor;
+use domain_events::executors::refund_cancelled_event_order::RefundCancelledEventOrderExecutor;
+use domain_events::executors::release_hold_inventory::ReleaseHoldInventoryExecutor;
 use domain_events::executors::send_communication::SendCommunicationExecutor;
+use domain_events::executors::send_event_rescheduled::SendEventRescheduledExecutor;
 use domain_events::executors::send_order_complete::SendOrderCompleteExecutor;
+use domain_events::executors::update_event_recommendations::UpdateEventRecommendationsExecutor;
 use std::borrow::Borrow;
 use std::collections::HashMap;
 
@@ -57,22 +65,36 @@ impl DomainActionRouter {
         let find_executor = |action_type| -> Box<DomainActionExecutor> {
             let conf = conf.clone();
             match action_type {
+                BlockchainSync => Box::new(BlockchainSyncExecutor::new(conf)),
                 Communication => Box::new(SendCommunicationExecutor::new(conf)),
                 BroadcastPushNotification => Box::new(BroadcastPushNotificationExecutor::new()),
+                DeleteUser => Box::new(DeleteUserExecutor::new(conf)),
 
                 MarketingContactsBulkEventFanListImport => {
                     Box::new(BulkEventFanListImportExecutor::new(conf))
                 }
                 MarketingContactsCreateEventList => Box::new(CreateEventListExecutor::new(conf)),
+                MarketingContactsSyncFanSegment => Box::new(SyncFanSegmentExecutor::new(conf)),
                 PaymentProviderIPN => Box::new(ProcessPaymentIPNExecutor::new(&conf)),
+                PaypalWebhook => Box::new(ProcessPaypalWebhookExecutor::new(&conf)),
+                ReconcileBlockchainAssets => Box::new(ReconcileBlockchainAssetsExecutor::new(conf)),
+                RefundCancelledEventOrder => Box::new(RefundCancelledEventOrderExecutor::new(conf)),
+                ReleaseHoldInventory => Box::new(ReleaseHoldInventoryExecutor::new()),
+                SendEventRescheduledCommunication => {
+                    Box::new(SendEventRescheduledExecutor::new(conf))
+                }
                 SendPurchaseCompletedCommunication => {
                     Box::new(SendOrderCompleteExecutor::new(conf))
-                } //
-                  // DO NOT add
-                  // _ =>
+                }
+                UpdateEventRecommendations => Box::new(UpdateEventRecommendationsExecutor::new()),
+                // DO NOT add
+                // _ =>
             }
         };
 
+        self.add_executor(BlockchainSync, find_executor(BlockchainSync))
+            .expect("Configuration error");
+
         self.add_executor(Communication, find_executor(Communication))
             .expect("Configuration error");
 
@@ -82,6 +104,9 @@ impl DomainActionRouter {
         )
         .expect("Configuration error");
 
+        self.add_executor(DeleteUser, find_executor(DeleteUser))
+            .expect("Configuration error");
+
         self.add_executor(
             MarketingContactsCreateEventList,
             find_executor(MarketingContactsCreateEventList),
@@ -94,13 +119,49 @@ impl DomainActionRouter {
         )
         .expect("Configuration error");
 
+        self.add_executor(
+            MarketingContactsSyncFanSegment,
+            find_executor(MarketingContactsSyncFanSegment),
+        )
+        .expect("Configuration error");
+
         self.add_executor(PaymentProviderIPN, find_executor(PaymentProviderIPN))
             .expect("Configuration error");
 
+        self.add_executor(PaypalWebhook, find_executor(PaypalWebhook))
+            .expect("Configuration error");
+
+        self.add_executor(
+            ReconcileBlockchainAssets,
+            find_executor(ReconcileBlockchainAssets),
+        )
+        .expect("Configuration error");
+
+        self.add_executor(
+            RefundCancelledEventOrder,
+            find_executor(RefundCancelledEventOrder),
+        )
+        .expect("Configuration error");
+
+        self.add_executor(ReleaseHoldInventory, find_executor(ReleaseHoldInventory))
+            .expect("Configuration error");
+
+        self.add_executor(
+            SendEventRescheduledCommunication,
+            find_executor(SendEventRescheduledCommunication),
+        )
+        .expect("Configuration error");
+
         self.add_executor(
             SendPurchaseCompletedCommunication,
             find_executor(SendPurchaseCompletedCommunication),
         )
         .expect("Configuration error");
+
+        self.add_executor(
+            UpdateEventRecommendations,
+            find_executor(UpdateEventRecommendations),
+        )
+        .expect("Configuration error");
     }
 }
@@ -10,12 +10,14 @@ use jwt::errors::Error as JwtError;
 use lettre::smtp::error::Error as SmtpError;
 use lettre_email::error::Error as EmailBuilderError;
 use payments::PaymentProcessorError;
+use paypal::PaypalError;
 use r2d2;
 use reqwest::header::ToStrError as ReqwestToStrError;
 use reqwest::Error as ReqwestError;
 use serde_json::Error as SerdeError;
 use std::error::Error;
 use std::fmt;
+use stripe::StripeError;
 use tari_client::TariError;
 use uuid::ParseError as UuidParseError;
 
@@ -41,10 +43,12 @@ error_conversion!(EmailBuilderError);
 error_conversion!(EnumParseError);
 error_conversion!(JwtError);
 error_conversion!(PaymentProcessorError);
+error_conversion!(PaypalError);
 error_conversion!(ReqwestError);
 error_conversion!(ReqwestToStrError);
 error_conversion!(SerdeError);
 error_conversion!(SmtpError);
+error_conversion!(StripeError);
 error_conversion!(TariError);
 error_conversion!(UuidParseError);
 error_conversion!(GlobeeError);
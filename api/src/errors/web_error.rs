@@ -9,6 +9,7 @@ use jwt::errors::{Error as JwtError, ErrorKind as JwtErrorKind};
 use lettre::smtp::error::Error as SmtpError;
 use lettre_email::error::Error as EmailBuilderError;
 use payments::PaymentProcessorError;
+use paypal::PaypalError;
 use r2d2;
 use reqwest::header::ToStrError as ReqwestToStrError;
 use reqwest::Error as ReqwestError;
@@ -74,6 +75,13 @@ impl ConvertToWebError for GlobeeError {
     }
 }
 
+impl ConvertToWebError for PaypalError {
+    fn to_response(&self) -> HttpResponse {
+        error!("PayPal error: {}", self);
+        internal_error("Internal error")
+    }
+}
+
 impl ConvertToWebError for BranchError {
     fn to_response(&self) -> HttpResponse {
         error!("Branch error: {}", self);
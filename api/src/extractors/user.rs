@@ -33,6 +33,10 @@ impl FromRequest<AppState> for User {
                         .map_err(|e| BigNeonError::from(e))?;
                         let connection = req.connection()?;
                         match DbUser::find(token.claims.get_id()?, connection.get()) {
+                            // Deleted and disabled accounts keep their records but cannot be used
+                            Ok(ref user) if !user.active => {
+                                Err(ErrorUnauthorized("User account is disabled"))
+                            }
                             Ok(user) => Ok(User::new(user, req)
                                 .map_err(|_| ErrorUnauthorized("User has invalid role data"))?),
                             Err(e) => Err(ErrorInternalServerError(e)),
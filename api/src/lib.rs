@@ -30,6 +30,7 @@ extern crate log;
 #[macro_use]
 extern crate logging;
 
+extern crate paypal;
 extern crate r2d2;
 extern crate regex;
 extern crate reqwest;
@@ -50,3 +50,9 @@ pub struct CompPathParameters {
     pub hold_id: Uuid,
     pub comp_id: Uuid,
 }
+
+#[derive(Deserialize)]
+pub struct HoldReleasePathParameters {
+    pub id: Uuid, // Hold Id
+    pub release_id: Uuid,
+}
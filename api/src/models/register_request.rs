@@ -1,4 +1,5 @@
 use bigneon_db::models::{deserialize_unless_blank, NewUser, User};
+use uuid::Uuid;
 
 #[derive(Deserialize)]
 pub struct RegisterRequest {
@@ -13,6 +14,9 @@ pub struct RegisterRequest {
     #[serde(rename = "g-recaptcha-response")]
     #[serde(default, deserialize_with = "deserialize_unless_blank")]
     pub captcha_response: Option<String>,
+    /// Organization the user agreed to receive marketing from while registering
+    #[serde(default)]
+    pub marketing_consent_organization_id: Option<Uuid>,
 }
 
 impl From<RegisterRequest> for NewUser {
@@ -43,6 +47,7 @@ impl RegisterRequest {
             phone: Some(phone.to_string()),
             password: password.to_string(),
             captcha_response,
+            marketing_consent_organization_id: None,
         }
     }
 }
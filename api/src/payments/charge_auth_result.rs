@@ -1,9 +1,23 @@
 pub struct ChargeAuthResult {
     pub id: String,
     pub raw: String,
+    /// Set when the customer must complete an additional step, such as 3-D Secure
+    /// authentication, before the charge is authorized
+    pub requires_action: bool,
+    /// Secret the client uses to complete the required action with the provider
+    pub client_secret: Option<String>,
 }
 
 impl ChargeAuthResult {
+    pub fn new(id: String, raw: String) -> ChargeAuthResult {
+        ChargeAuthResult {
+            id,
+            raw,
+            requires_action: false,
+            client_secret: None,
+        }
+    }
+
     pub fn to_json(&self) -> Result<serde_json::Value, serde_json::Error> {
         serde_json::from_str(&self.raw)
     }
@@ -87,6 +87,14 @@ impl RedirectToPaymentPageBehavior for GlobeePaymentProcessorBehavior {
             expires_at: result.expires_at,
         })
     }
+
+    fn complete_payment_request(
+        &self,
+        _id: &str,
+    ) -> Result<Option<ChargeResult>, PaymentProcessorError> {
+        // Payment is confirmed by Globee's IPN
+        Ok(None)
+    }
 }
 
 impl From<GlobeeError> for PaymentProcessorError {
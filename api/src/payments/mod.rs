@@ -10,6 +10,7 @@ mod charge_result;
 pub mod globee;
 pub mod payment_processor;
 mod payment_processor_error;
+pub mod paypal;
 mod repeat_charge_token;
 pub mod stripe;
 mod update_metadata_result;
@@ -24,15 +24,24 @@ pub trait AuthThenCompletePaymentBehavior {
         description: &str,
     ) -> Result<RepeatChargeToken, PaymentProcessorError>;
 
+    fn remove_repeat_token(&self, repeat_token: &str) -> Result<(), PaymentProcessorError>;
+
+    /// `application_fee` is the platform's share of `amount`, used by processors that pay
+    /// the remainder out to the organization directly.
     fn auth(
         &self,
         token: &str,
         amount: i64,
+        application_fee: i64,
         currency: &str,
         description: &str,
         metadata: Vec<(String, String)>,
     ) -> Result<ChargeAuthResult, PaymentProcessorError>;
 
+    /// Re-attempts authorization once the customer has completed the action requested by
+    /// `auth`, e.g. a 3-D Secure challenge.
+    fn confirm_auth(&self, auth_token: &str) -> Result<ChargeAuthResult, PaymentProcessorError>;
+
     fn complete_authed_charge(
         &self,
         auth_token: &str,
@@ -51,6 +60,14 @@ pub trait RedirectToPaymentPageBehavior {
         success_url: Option<String>,
         cancel_url: Option<String>,
     ) -> Result<RedirectInfo, PaymentProcessorError>;
+
+    /// Called when the user returns from the payment page. Providers that require the payment to
+    /// be captured at this point return the result, those that confirm payment through IPNs
+    /// return `None`.
+    fn complete_payment_request(
+        &self,
+        id: &str,
+    ) -> Result<Option<ChargeResult>, PaymentProcessorError>;
 }
 
 pub trait PaymentProcessor {
@@ -1,3 +1,4 @@
+use serde_json;
 use std::error::Error;
 use std::fmt;
 
@@ -25,3 +26,13 @@ impl fmt::Display for PaymentProcessorError {
         }
     }
 }
+
+impl From<serde_json::Error> for PaymentProcessorError {
+    fn from(e: serde_json::Error) -> Self {
+        PaymentProcessorError {
+            description: e.to_string(),
+            cause: Some(Box::new(e)),
+            validation_response: None,
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use bigneon_db::models::PaymentProviders;
+use chrono::prelude::*;
+use chrono::Duration;
+use payments::*;
+use paypal::*;
+use serde_json;
+use uuid::Uuid;
+
+/// Prefix used when storing PayPal order ids as payment external references
+pub const PAYPAL_EXTERNAL_REFERENCE_PREFIX: &str = "paypal-";
+
+pub struct PaypalPaymentProcessor {
+    client: PaypalClient,
+    currency: String,
+}
+
+impl PaypalPaymentProcessor {
+    pub fn new(
+        client_id: String,
+        secret: String,
+        base_url: String,
+        currency: String,
+    ) -> PaypalPaymentProcessor {
+        PaypalPaymentProcessor {
+            client: PaypalClient::new(client_id, secret, base_url),
+            currency,
+        }
+    }
+
+    fn refund_order(
+        &self,
+        auth_token: &str,
+        amount: Option<u32>,
+    ) -> Result<ChargeAuthResult, PaymentProcessorError> {
+        let order_id = auth_token.trim_start_matches(PAYPAL_EXTERNAL_REFERENCE_PREFIX);
+        let order = self.client.get_order(order_id)?;
+        let capture = match order
+            .captures()
+            .into_iter()
+            .find(|c| c.status == "COMPLETED")
+        {
+            Some(capture) => capture.clone(),
+            None => {
+                return Err(PaymentProcessorError {
+                    description: format!("PayPal order {} has no completed capture", order_id),
+                    cause: None,
+                    validation_response: None,
+                });
+            }
+        };
+
+        let refund = self.client.refund_capture(
+            &capture.id,
+            amount.map(|a| Money::from_cents(a as i64, &self.currency)),
+        )?;
+        Ok(ChargeAuthResult::new(
+            refund.id.clone(),
+            serde_json::to_string(&refund)?,
+        ))
+    }
+}
+
+impl PaymentProcessor for PaypalPaymentProcessor {
+    fn behavior(&self) -> PaymentProcessorBehavior {
+        PaymentProcessorBehavior::RedirectToPaymentPage(Box::new(PaypalPaymentProcessorBehavior {
+            client: self.client.clone(),
+            currency: self.currency.clone(),
+        }))
+    }
+
+    fn refund(&self, auth_token: &str) -> Result<ChargeAuthResult, PaymentProcessorError> {
+        self.refund_order(auth_token, None)
+    }
+
+    fn partial_refund(
+        &self,
+        auth_token: &str,
+        amount: u32,
+    ) -> Result<ChargeAuthResult, PaymentProcessorError> {
+        self.refund_order(auth_token, Some(amount))
+    }
+
+    fn update_metadata(
+        &self,
+        _charge_id: &str,
+        _metadata: Vec<(String, String)>,
+    ) -> Result<UpdateMetadataResult, PaymentProcessorError> {
+        Err(PaymentProcessorError {
+            description: "Updating metadata is not supported by this gateway".to_string(),
+            cause: None,
+            validation_response: None,
+        })
+    }
+}
+
+pub struct PaypalPaymentProcessorBehavior {
+    client: PaypalClient,
+    currency: String,
+}
+
+impl RedirectToPaymentPageBehavior for PaypalPaymentProcessorBehavior {
+    fn payment_provider(&self) -> PaymentProviders {
+        PaymentProviders::Paypal
+    }
+
+    fn create_payment_request(
+        &self,
+        total: f64,
+        _email: String,
+        order_id: Uuid,
+        // PayPal sends webhooks to the url configured for the application rather than per order
+        _ipn_url: Option<String>,
+        success_url: Option<String>,
+        cancel_url: Option<String>,
+    ) -> Result<RedirectInfo, PaymentProcessorError> {
+        let (success_url, cancel_url) = match (success_url, cancel_url) {
+            (Some(success_url), Some(cancel_url)) => (success_url, cancel_url),
+            _ => {
+                return Err(PaymentProcessorError {
+                    description: "PayPal requires both a success and cancel url".to_string(),
+                    cause: None,
+                    validation_response: None,
+                });
+            }
+        };
+
+        let order = self.client.create_order(CreateOrderRequest::new(
+            Money::from_cents((total * 100_f64).round() as i64, &self.currency),
+            order_id.to_string(),
+            Some("Big Neon Tickets".to_string()),
+            success_url,
+            cancel_url,
+        ))?;
+
+        let redirect_url = match order.approve_url() {
+            Some(url) => url.to_string(),
+            None => {
+                return Err(PaymentProcessorError {
+                    description: "PayPal did not return an approval url".to_string(),
+                    cause: None,
+                    validation_response: None,
+                });
+            }
+        };
+
+        Ok(RedirectInfo {
+            id: order.id,
+            redirect_url,
+            // PayPal orders that are not approved expire after 3 hours
+            expires_at: Utc::now().naive_utc() + Duration::hours(3),
+        })
+    }
+
+    fn complete_payment_request(
+        &self,
+        id: &str,
+    ) -> Result<Option<ChargeResult>, PaymentProcessorError> {
+        let order = self.client.capture_order(id)?;
+        if order.status != "COMPLETED" {
+            return Err(PaymentProcessorError {
+                description: format!("PayPal order {} was not completed", order.id),
+                cause: None,
+                validation_response: Some("Payment could not be completed".to_string()),
+            });
+        }
+
+        Ok(Some(ChargeResult {
+            id: order.id.clone(),
+            raw: serde_json::to_string(&order)?,
+        }))
+    }
+}
+
+impl From<PaypalError> for PaymentProcessorError {
+    fn from(p: PaypalError) -> Self {
+        PaymentProcessorError {
+            description: p.to_string(),
+            cause: Some(Box::new(p)),
+            validation_response: None,
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use bigneon_db::models::PaymentProviders;
 use payments::*;
+use stripe::PaymentIntent;
+use stripe::PaymentIntentStatus;
 use stripe::StripeClient;
 use stripe::StripeError;
 
@@ -35,24 +37,72 @@ impl From<StripeError> for PaymentProcessorError {
 
 pub struct StripePaymentProcessor {
     client: StripeClient,
+    connected_account_id: Option<String>,
 }
 
 impl StripePaymentProcessor {
-    pub fn new(stripe_secret_key: String) -> StripePaymentProcessor {
+    /// When `connected_account_id` is provided, charges are created as destination charges
+    /// paying out to that Stripe Connect account.
+    pub fn new(
+        stripe_secret_key: String,
+        connected_account_id: Option<String>,
+    ) -> StripePaymentProcessor {
         StripePaymentProcessor {
             client: StripeClient::new(stripe_secret_key),
+            connected_account_id,
         }
     }
+
+    /// Payments made through payment intents store the intent id, refunds and metadata
+    /// updates are applied to the charge it created. Also returns whether the charge is a
+    /// destination charge, only payment intents are created with a destination.
+    fn resolve_charge(&self, reference: &str) -> Result<(String, bool), PaymentProcessorError> {
+        if !reference.starts_with("pi_") {
+            return Ok((reference.to_string(), false));
+        }
+        let payment_intent = self.client.retrieve_payment_intent(reference)?;
+        match payment_intent.charge_id {
+            Some(charge_id) => Ok((charge_id, payment_intent.transfer_destination.is_some())),
+            None => Err(PaymentProcessorError {
+                description: format!("Payment intent {} has no charge", reference),
+                cause: None,
+                validation_response: None,
+            }),
+        }
+    }
+
+    fn refund_charge(
+        &self,
+        reference: &str,
+        amount: Option<u32>,
+    ) -> Result<ChargeAuthResult, PaymentProcessorError> {
+        let (charge_id, is_destination_charge) = self.resolve_charge(reference)?;
+        let charge_id = charge_id.as_str();
+        // Charges made before the organization connected its account have no transfer to reverse
+        let result = if is_destination_charge {
+            self.client
+                .refund_with_transfer_reversal(charge_id, amount)?
+        } else {
+            match amount {
+                Some(amount) => self.client.partial_refund(charge_id, amount)?,
+                None => self.client.refund(charge_id)?,
+            }
+        };
+
+        Ok(ChargeAuthResult::new(result.id, result.raw_data))
+    }
 }
 
 pub struct StripePaymentBehavior {
     client: StripeClient,
+    connected_account_id: Option<String>,
 }
 
 impl PaymentProcessor for StripePaymentProcessor {
     fn behavior(&self) -> PaymentProcessorBehavior {
         PaymentProcessorBehavior::AuthThenComplete(Box::new(StripePaymentBehavior {
             client: self.client.clone(),
+            connected_account_id: self.connected_account_id.clone(),
         }))
     }
 
@@ -61,9 +111,10 @@ impl PaymentProcessor for StripePaymentProcessor {
         charge_id: &str,
         metadata: Vec<(String, String)>,
     ) -> Result<UpdateMetadataResult, PaymentProcessorError> {
+        let (charge_id, _) = self.resolve_charge(charge_id)?;
         Ok(self
             .client
-            .update_metadata(charge_id, metadata)
+            .update_metadata(&charge_id, metadata)
             .map(|r| UpdateMetadataResult {
                 id: r.id,
                 raw: r.raw_data,
@@ -71,10 +122,7 @@ impl PaymentProcessor for StripePaymentProcessor {
     }
 
     fn refund(&self, auth_token: &str) -> Result<ChargeAuthResult, PaymentProcessorError> {
-        Ok(self.client.refund(auth_token).map(|r| ChargeAuthResult {
-            id: r.id,
-            raw: r.raw_data,
-        })?)
+        self.refund_charge(auth_token, None)
     }
 
     fn partial_refund(
@@ -82,13 +130,7 @@ impl PaymentProcessor for StripePaymentProcessor {
         auth_token: &str,
         amount: u32,
     ) -> Result<ChargeAuthResult, PaymentProcessorError> {
-        Ok(self
-            .client
-            .partial_refund(auth_token, amount)
-            .map(|r| ChargeAuthResult {
-                id: r.id,
-                raw: r.raw_data,
-            })?)
+        self.refund_charge(auth_token, Some(amount))
     }
 }
 
@@ -130,30 +172,76 @@ impl<'a> AuthThenCompletePaymentBehavior for StripePaymentBehavior {
             })?)
     }
 
+    fn remove_repeat_token(&self, repeat_token: &str) -> Result<(), PaymentProcessorError> {
+        Ok(self.client.delete_customer(repeat_token)?)
+    }
+
     fn auth(
         &self,
         token: &str,
         amount: i64,
+        application_fee: i64,
         currency: &str,
         description: &str,
         metadata: Vec<(String, String)>,
     ) -> Result<ChargeAuthResult, PaymentProcessorError> {
+        let destination = self
+            .connected_account_id
+            .as_ref()
+            .map(|connected_account_id| (connected_account_id.as_str(), application_fee));
+        let payment_intent = self.client.create_payment_intent(
+            token,
+            amount,
+            currency,
+            description,
+            destination,
+            metadata,
+        )?;
+
+        payment_intent_auth_result(payment_intent)
+    }
+
+    fn confirm_auth(&self, auth_token: &str) -> Result<ChargeAuthResult, PaymentProcessorError> {
+        payment_intent_auth_result(self.client.confirm_payment_intent(auth_token)?)
+    }
+
+    fn complete_authed_charge(
+        &self,
+        auth_token: &str,
+    ) -> Result<ChargeResult, PaymentProcessorError> {
         Ok(self
             .client
-            .auth(token, amount, currency, description, metadata)
-            .map(|r| ChargeAuthResult {
+            .capture_payment_intent(auth_token)
+            .map(|r| ChargeResult {
                 id: r.id,
                 raw: r.raw_data,
             })?)
     }
+}
 
-    fn complete_authed_charge(
-        &self,
-        _auth_token: &str,
-    ) -> Result<ChargeResult, PaymentProcessorError> {
-        Ok(self.client.complete(_auth_token).map(|r| ChargeResult {
-            id: r.id,
-            raw: r.raw_data,
-        })?)
+fn payment_intent_auth_result(
+    payment_intent: PaymentIntent,
+) -> Result<ChargeAuthResult, PaymentProcessorError> {
+    match payment_intent.status {
+        PaymentIntentStatus::RequiresCapture => Ok(ChargeAuthResult::new(
+            payment_intent.id,
+            payment_intent.raw_data,
+        )),
+        PaymentIntentStatus::RequiresAction | PaymentIntentStatus::RequiresSourceAction => {
+            Ok(ChargeAuthResult {
+                id: payment_intent.id,
+                raw: payment_intent.raw_data,
+                requires_action: true,
+                client_secret: payment_intent.client_secret,
+            })
+        }
+        status => Err(PaymentProcessorError {
+            description: format!(
+                "Payment intent {} could not be authorized, status: {:?}",
+                payment_intent.id, status
+            ),
+            cause: None,
+            validation_response: Some("Payment could not be authorized".to_string()),
+        }),
     }
 }
@@ -6,7 +6,14 @@ use server::AppState;
 pub fn routes(app: &mut CorsBuilder<AppState>) -> App<AppState> {
     // Please try to keep in alphabetical order
 
-    app.resource("/admin/stuck_domain_actions", |r| {
+    app.resource("/admin/blockchain/assets", |r| {
+        r.method(Method::GET).with(admin::admin_blockchain_assets);
+    })
+    .resource("/admin/blockchain/reconcile", |r| {
+        r.method(Method::POST)
+            .with(admin::admin_reconcile_blockchain_assets);
+    })
+    .resource("/admin/stuck_domain_actions", |r| {
         r.method(Method::GET)
             .with(admin::admin_stuck_domain_actions);
     })
@@ -48,16 +55,37 @@ pub fn routes(app: &mut CorsBuilder<AppState>) -> App<AppState> {
     .resource("/cart/checkout", |r| {
         r.method(Method::POST).with(cart::checkout);
     })
+    .resource("/cart/checkout/confirm", |r| {
+        r.method(Method::POST).with(cart::confirm_checkout);
+    })
     .resource("/codes/{id}", |r| {
         r.method(Method::GET).with(codes::show);
         r.method(Method::PUT).with(codes::update);
         r.method(Method::DELETE).with(codes::destroy);
     })
+    .resource("/codes/{id}/unique_codes", |r| {
+        r.method(Method::GET).with(unique_codes::index);
+        r.method(Method::POST).with(unique_codes::create);
+    })
+    .resource("/codes/{id}/unique_codes/export", |r| {
+        r.method(Method::GET).with(unique_codes::export);
+    })
     .resource("/comps/{id}", |r| {
         r.method(Method::GET).with(comps::show);
         r.method(Method::PATCH).with(comps::update);
         r.method(Method::DELETE).with(comps::destroy);
     })
+    .resource("/event_series/{id}", |r| {
+        r.method(Method::GET).with(event_series::show);
+        r.method(Method::PATCH).with(event_series::update);
+        r.method(Method::DELETE).with(event_series::destroy);
+    })
+    .resource("/event_series/{id}/events", |r| {
+        r.method(Method::PATCH).with(event_series::update_events);
+    })
+    .resource("/event_series/{id}/generate", |r| {
+        r.method(Method::POST).with(event_series::generate);
+    })
     .resource("/events", |r| {
         r.method(Method::GET).with(events::index);
         r.method(Method::POST).with(events::create);
@@ -74,6 +102,9 @@ pub fn routes(app: &mut CorsBuilder<AppState>) -> App<AppState> {
         r.method(Method::POST).with(events::add_artist);
         r.method(Method::PUT).with(events::update_artists);
     })
+    .resource("/events/{id}/clone", |r| {
+        r.method(Method::POST).with(events::clone);
+    })
     .resource("/events/{id}/codes", |r| {
         r.method(Method::GET).with(events::codes);
         r.method(Method::POST).with(codes::create);
@@ -106,6 +137,19 @@ pub fn routes(app: &mut CorsBuilder<AppState>) -> App<AppState> {
     .resource("/events/{id}/redeem/{ticket_instance_id}", |r| {
         r.method(Method::POST).with(events::redeem_ticket);
     })
+    .resource("/events/{id}/refunds", |r| {
+        r.method(Method::GET).with(events::refunds);
+        r.method(Method::POST).with(events::queue_refunds);
+    })
+    .resource("/events/{id}/refunds/failures", |r| {
+        r.method(Method::GET).with(events::refund_failures);
+    })
+    .resource("/events/{id}/reschedule", |r| {
+        r.method(Method::POST).with(events::reschedule);
+    })
+    .resource("/events/{id}/series", |r| {
+        r.method(Method::POST).with(event_series::create);
+    })
     .resource("/events/{id}/tickets", |r| {
         r.method(Method::GET).with(tickets::index);
     })
@@ -136,6 +180,27 @@ pub fn routes(app: &mut CorsBuilder<AppState>) -> App<AppState> {
     .resource("/external/facebook/web_login", |r| {
         r.method(Method::POST).with(external::facebook::web_login)
     })
+    .resource("/fan_notes/{id}", |r| {
+        r.method(Method::PUT).with(fans::update_note);
+        r.method(Method::DELETE).with(fans::remove_note);
+    })
+    .resource("/fan_segments/{id}/export", |r| {
+        r.method(Method::GET).with(fan_segments::export);
+    })
+    .resource("/fan_segments/{id}/fans", |r| {
+        r.method(Method::GET).with(fan_segments::fans);
+    })
+    .resource("/fan_segments/{id}/sync", |r| {
+        r.method(Method::POST).with(fan_segments::sync);
+    })
+    .resource("/fan_segments/{id}", |r| {
+        r.method(Method::GET).with(fan_segments::show);
+        r.method(Method::PUT).with(fan_segments::update);
+        r.method(Method::DELETE).with(fan_segments::destroy);
+    })
+    .resource("/fan_tags/{id}", |r| {
+        r.method(Method::DELETE).with(fans::remove_tag);
+    })
     .resource("/invitations/{id}", |r| {
         r.method(Method::GET).with(organization_invites::view);
     })
@@ -146,10 +211,29 @@ pub fn routes(app: &mut CorsBuilder<AppState>) -> App<AppState> {
     .resource("/ipns/globee", |r| {
         r.method(Method::POST).with(ipns::globee);
     })
+    .resource("/ipns/paypal", |r| {
+        r.method(Method::POST).with(ipns::paypal);
+    })
+    .resource("/ipns/sendgrid", |r| {
+        r.method(Method::POST).with(ipns::sendgrid);
+    })
     .resource("/holds/{id}/comps", |r| {
         r.method(Method::GET).with(comps::index);
         r.method(Method::POST).with(comps::create);
     })
+    .resource("/holds/{id}/comps/issue", |r| {
+        r.method(Method::POST).with(comps::issue);
+    })
+    .resource("/holds/{id}/comps/issue_bulk", |r| {
+        r.method(Method::POST).with(comps::issue_bulk);
+    })
+    .resource("/holds/{id}/releases", |r| {
+        r.method(Method::GET).with(holds::releases);
+        r.method(Method::POST).with(holds::create_release);
+    })
+    .resource("/holds/{id}/releases/{release_id}", |r| {
+        r.method(Method::DELETE).with(holds::cancel_release);
+    })
     .resource("/holds/{id}/split", |r| {
         r.method(Method::POST).with(holds::split);
     })
@@ -167,6 +251,10 @@ pub fn routes(app: &mut CorsBuilder<AppState>) -> App<AppState> {
     .resource("/orders/{id}/refund", |r| {
         r.method(Method::PATCH).with(orders::refund);
     })
+    .resource("/orders/{id}/refund_requests", |r| {
+        r.method(Method::GET).with(refund_requests::index_for_order);
+        r.method(Method::POST).with(refund_requests::create);
+    })
     .resource("/orders/{id}/tickets", |r| {
         r.method(Method::GET).with(orders::tickets);
     })
@@ -174,16 +262,46 @@ pub fn routes(app: &mut CorsBuilder<AppState>) -> App<AppState> {
         r.method(Method::GET).with(orders::show);
         r.method(Method::PATCH).with(orders::update);
     })
+    .resource("/organizations/{id}/audit_log", |r| {
+        r.method(Method::GET).with(organizations::audit_log);
+    })
     .resource("/organizations/{id}/artists", |r| {
         r.method(Method::GET).with(artists::show_from_organizations);
         r.method(Method::POST).with(organizations::add_artist);
     })
+    .resource("/organizations/{id}/email_settings/preview", |r| {
+        r.method(Method::GET).with(organizations::preview_email);
+    })
+    .resource("/organizations/{id}/email_settings", |r| {
+        r.method(Method::GET)
+            .with(organizations::show_email_settings);
+        r.method(Method::PUT)
+            .with(organizations::update_email_settings);
+    })
+    .resource("/organizations/{id}/event_series", |r| {
+        r.method(Method::GET).with(event_series::index);
+    })
     .resource("/organizations/{id}/events", |r| {
         r.method(Method::GET).with(events::show_from_organizations);
     })
+    .resource("/organizations/{id}/fan_segments", |r| {
+        r.method(Method::GET).with(fan_segments::index);
+        r.method(Method::POST).with(fan_segments::create);
+    })
+    .resource("/organizations/{id}/fan_tags", |r| {
+        r.method(Method::GET).with(fans::organization_tags);
+    })
     .resource("/organizations/{id}/fans/{user_id}/history", |r| {
         r.method(Method::GET).with(users::history);
     })
+    .resource("/organizations/{id}/fans/{user_id}/notes", |r| {
+        r.method(Method::GET).with(fans::notes);
+        r.method(Method::POST).with(fans::add_note);
+    })
+    .resource("/organizations/{id}/fans/{user_id}/tags", |r| {
+        r.method(Method::GET).with(fans::tags);
+        r.method(Method::POST).with(fans::add_tag);
+    })
     .resource("/organizations/{id}/fans/{user_id}", |r| {
         r.method(Method::GET).with(users::profile);
     })
@@ -197,6 +315,15 @@ pub fn routes(app: &mut CorsBuilder<AppState>) -> App<AppState> {
     .resource("/organizations/{id}/invites/{invite_id}", |r| {
         r.method(Method::DELETE).with(organization_invites::destroy);
     })
+    .resource("/organizations/{id}/refund_requests", |r| {
+        r.method(Method::GET).with(refund_requests::index);
+    })
+    .resource("/organizations/{id}/refund_requests/approve", |r| {
+        r.method(Method::POST).with(refund_requests::approve);
+    })
+    .resource("/organizations/{id}/refund_requests/deny", |r| {
+        r.method(Method::POST).with(refund_requests::deny);
+    })
     .resource("/organizations/{id}/settlements", |r| {
         r.method(Method::GET).with(settlements::index);
         r.method(Method::POST).with(settlements::create);
@@ -204,6 +331,19 @@ pub fn routes(app: &mut CorsBuilder<AppState>) -> App<AppState> {
     .resource("/organizations/{id}/settlements/prepare", |r| {
         r.method(Method::POST).with(settlements::prepare);
     })
+    .resource("/organizations/{id}/stripe_connect", |r| {
+        r.method(Method::GET)
+            .with(organizations::show_stripe_connect);
+        r.method(Method::POST)
+            .with(organizations::create_stripe_connect_link);
+    })
+    .resource(
+        "/organizations/{id}/stripe_connect/balance_transactions",
+        |r| {
+            r.method(Method::GET)
+                .with(organizations::stripe_connect_balance_transactions);
+        },
+    )
     .resource("/organizations/{id}/invites", |r| {
         r.method(Method::GET).with(organization_invites::index);
         r.method(Method::POST).with(organization_invites::create);
@@ -238,8 +378,15 @@ pub fn routes(app: &mut CorsBuilder<AppState>) -> App<AppState> {
     .resource("/payments/callback/{nonce}/{id}", |r| {
         r.method(Method::GET).with(payments::callback);
     })
+    .resource("/payment_methods/{id}/make_default", |r| {
+        r.method(Method::POST).with(payment_methods::make_default);
+    })
+    .resource("/payment_methods/{id}", |r| {
+        r.method(Method::DELETE).with(payment_methods::destroy);
+    })
     .resource("/payment_methods", |r| {
         r.method(Method::GET).with(payment_methods::index);
+        r.method(Method::POST).with(payment_methods::create);
     })
     .resource("/redemption_codes/{code}", |r| {
         r.method(Method::GET).with(redemption_codes::show)
@@ -275,18 +422,31 @@ pub fn routes(app: &mut CorsBuilder<AppState>) -> App<AppState> {
         r.method(Method::POST)
             .with(tickets::send_via_email_or_phone);
     })
+    .resource("/tickets/verify", |r| {
+        r.method(Method::POST).with(tickets::verify);
+    })
     .resource("/tickets/{id}", |r| {
         r.method(Method::GET).with(tickets::show);
     })
     .resource("/tickets", |r| {
         r.method(Method::GET).with(tickets::index);
     })
+    .resource("/tickets/{id}/proof", |r| {
+        r.method(Method::GET).with(tickets::ownership_proof);
+    })
     .resource("/tickets/{id}/redeem", |r| {
         r.method(Method::GET).with(tickets::show_redeemable_ticket);
     })
     .resource("/users/me", |r| {
         r.method(Method::GET).with(users::current_user);
         r.method(Method::PUT).with(users::update_current_user);
+        r.method(Method::DELETE).with(users::delete_current_user);
+    })
+    .resource("/users/me/export", |r| {
+        r.method(Method::GET).with(users::export_current_user);
+    })
+    .resource("/users/me/recommendations", |r| {
+        r.method(Method::GET).with(users::recommendations);
     })
     .resource("/users/register", |r| {
         r.method(Method::POST).with(users::register)
@@ -335,6 +495,14 @@ pub fn routes(app: &mut CorsBuilder<AppState>) -> App<AppState> {
         r.method(Method::GET).with(venues::index);
         r.method(Method::POST).with(venues::create);
     })
+    .resource("/wallets/{id}/export", |r| {
+        r.method(Method::POST).with(wallets::export);
+    })
+    .resource("/wallets", |r| {
+        r.method(Method::GET).with(wallets::index);
+        r.method(Method::POST)
+            .with(wallets::register_self_custody_key);
+    })
     .register()
     .default_resource(|r| {
         r.method(Method::GET)
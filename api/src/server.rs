@@ -55,6 +55,11 @@ impl Server {
             domain_action_monitor.start()
         }
 
+        info!(
+            "Recording ticket assets on the {:?} ledger",
+            config.ledger_backend
+        );
+
         if config.spotify_auth_token.is_some() {
             let token = config.spotify_auth_token.clone().unwrap();
             spotify::SINGLETON.set_auth_token(&token);
@@ -15,6 +15,7 @@ use utils::twilio;
 mod communication_type;
 pub use self::communication_type::*;
 use utils::expo;
+use uuid::Uuid;
 
 pub type TemplateData = HashMap<String, String>;
 
@@ -68,6 +69,14 @@ pub struct Communication {
     pub destinations: CommAddress,
     pub template_id: Option<String>,
     pub template_data: Option<Vec<TemplateData>>,
+    #[serde(default)]
+    pub source_name: Option<String>,
+    /// When set, the email is sent through this organization's SendGrid account
+    #[serde(default)]
+    pub sendgrid_organization_id: Option<Uuid>,
+    /// Marketing emails are also withheld from addresses that unsubscribed
+    #[serde(default)]
+    pub marketing: bool,
 }
 
 impl Communication {
@@ -88,6 +97,9 @@ impl Communication {
             destinations,
             template_id,
             template_data,
+            source_name: None,
+            sendgrid_organization_id: None,
+            marketing: false,
         }
     }
 
@@ -109,11 +121,51 @@ impl Communication {
         Ok(())
     }
 
+    /// Drops suppressed email destinations along with their template data. Bounced and spam
+    /// reporting addresses are always dropped, unsubscribes only for marketing emails. Other
+    /// communication types are left untouched.
+    pub fn remove_suppressed_destinations(
+        &mut self,
+        conn: &PgConnection,
+    ) -> Result<(), BigNeonError> {
+        match self.comm_type {
+            CommunicationType::Email | CommunicationType::EmailTemplate => (),
+            _ => return Ok(()),
+        }
+
+        let suppressed_emails = EmailSuppression::suppressed_emails(
+            &self.destinations.addresses,
+            self.marketing,
+            conn,
+        )?;
+        if suppressed_emails.is_empty() {
+            return Ok(());
+        }
+
+        let is_suppressed =
+            |address: &String| suppressed_emails.contains(&address.trim().to_lowercase());
+        if let Some(ref mut template_data) = self.template_data {
+            // Template data is matched to destinations by position
+            if template_data.len() == self.destinations.addresses.len() {
+                let mut index = 0;
+                let addresses = &self.destinations.addresses;
+                template_data.retain(|_| {
+                    let keep = !is_suppressed(&addresses[index]);
+                    index += 1;
+                    keep
+                });
+            }
+        }
+        self.destinations.addresses.retain(|a| !is_suppressed(a));
+        Ok(())
+    }
+
     pub fn send_async(
         domain_action: &DomainAction,
         config: &Config,
+        conn: &PgConnection,
     ) -> impl Future<Item = (), Error = BigNeonError> {
-        let communication: Communication =
+        let mut communication: Communication =
             match serde_json::from_value(domain_action.payload.clone()) {
                 Ok(v) => v,
                 Err(e) => return Either::A(future::err(e.into())),
@@ -125,11 +177,26 @@ impl Communication {
                 let res = match config.block_external_comms {
                     true => Either::A(future::ok(())), //Disable communication system when block_external_comms is true,
                     _ => {
+                        if let Err(e) = communication.remove_suppressed_destinations(conn) {
+                            return Either::A(future::err(e));
+                        }
                         let destination_addresses = communication.destinations.get();
+                        if destination_addresses.is_empty() {
+                            return Either::A(future::ok(()));
+                        }
+                        let sendgrid_api_key = match communication.sendgrid_organization_id {
+                            Some(organization_id) => {
+                                match organization_sendgrid_api_key(organization_id, config, conn) {
+                                    Ok(key) => key,
+                                    Err(e) => return Either::A(future::err(e)),
+                                }
+                            }
+                            None => config.sendgrid_api_key.clone(),
+                        };
 
                         let future = match communication.comm_type {
                             CommunicationType::Email => sendgrid::send_email_async(
-                                &config.sendgrid_api_key,
+                                &sendgrid_api_key,
                                 communication.source.as_ref().unwrap().get_first().unwrap(),
                                 destination_addresses,
                                 communication.title.clone(),
@@ -137,8 +204,9 @@ impl Communication {
                             ),
                             CommunicationType::EmailTemplate => {
                                 sendgrid::send_email_template_async(
-                                    &config.sendgrid_api_key,
+                                    &sendgrid_api_key,
                                     communication.source.as_ref().unwrap().get_first().unwrap(),
+                                    communication.source_name.clone(),
                                     &destination_addresses,
                                     communication.template_id.clone().unwrap(),
                                     communication.template_data.as_ref().unwrap(),
@@ -164,3 +232,20 @@ impl Communication {
         }
     }
 }
+
+fn organization_sendgrid_api_key(
+    organization_id: Uuid,
+    config: &Config,
+    conn: &PgConnection,
+) -> Result<String, BigNeonError> {
+    let mut organization = Organization::find(organization_id, conn)?;
+    organization.decrypt(&config.api_keys_encryption_key)?;
+    match organization.sendgrid_api_key {
+        Some(key) => Ok(key),
+        None => Err(ApplicationError::new(format!(
+            "Organization {} does not have a SendGrid API key",
+            organization_id
+        ))
+        .into()),
+    }
+}
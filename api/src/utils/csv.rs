@@ -0,0 +1,102 @@
+use errors::*;
+use std::collections::HashMap;
+
+/// Parses CSV content with a header row into one map per row keyed by the lower cased header.
+/// Fields may be quoted with `"`, a quote inside a quoted field is written as `""`.
+pub fn parse(content: &str) -> Result<Vec<HashMap<String, String>>, ApplicationError> {
+    let mut lines = content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+    let headers: Vec<String> = match lines.next() {
+        Some((_, line)) => split_line(line)?
+            .into_iter()
+            .map(|header| header.to_lowercase())
+            .collect(),
+        None => return Ok(Vec::new()),
+    };
+
+    let mut rows = Vec::new();
+    for (index, line) in lines {
+        let fields = split_line(line)?;
+        if fields.len() != headers.len() {
+            return Err(ApplicationError::new_with_type(
+                ApplicationErrorType::Unprocessable,
+                format!(
+                    "Line {} has {} fields but the header has {}",
+                    index + 1,
+                    fields.len(),
+                    headers.len()
+                ),
+            ));
+        }
+        rows.push(headers.iter().cloned().zip(fields.into_iter()).collect());
+    }
+    Ok(rows)
+}
+
+fn split_line(line: &str) -> Result<Vec<String>, ApplicationError> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => {
+                fields.push(field.trim().to_string());
+                field = String::new();
+            }
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(ApplicationError::new_with_type(
+            ApplicationErrorType::Unprocessable,
+            format!("Unterminated quote in line: {}", line),
+        ));
+    }
+    fields.push(field.trim().to_string());
+    Ok(fields)
+}
+
+/// Quotes `field` for writing to a CSV file if it contains a separator, quote or line break
+pub fn escape(field: &str) -> String {
+    if field.contains(|c: char| c == ',' || c == '"' || c == '\n' || c == '\r') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[test]
+fn test_parse() {
+    let rows = parse(
+        "Name,Email,Quantity\r\n\"Smith, Jane\",jane@example.com,2\n\n\"Bob \"\"The Builder\"\"\",,1\n",
+    )
+    .unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0]["name"], "Smith, Jane");
+    assert_eq!(rows[0]["email"], "jane@example.com");
+    assert_eq!(rows[0]["quantity"], "2");
+    assert_eq!(rows[1]["name"], "Bob \"The Builder\"");
+    assert_eq!(rows[1]["email"], "");
+
+    assert!(parse("name,email\nJane").is_err());
+    assert!(parse("name\n\"Jane").is_err());
+}
+
+#[test]
+fn test_escape() {
+    assert_eq!(escape("Jane"), "Jane");
+    assert_eq!(escape("Smith, Jane"), "\"Smith, Jane\"");
+    assert_eq!(escape("Bob \"The Builder\""), "\"Bob \"\"The Builder\"\"\"");
+    assert_eq!(
+        parse(&format!("name\n{}", escape("a, \"b\""))).unwrap()[0]["name"],
+        "a, \"b\""
+    );
+}
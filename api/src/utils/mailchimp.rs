@@ -0,0 +1,230 @@
+use errors::*;
+use log::Level::Debug;
+use regex::Regex;
+use reqwest::{Client, RequestBuilder};
+use serde_json;
+use utils::marketing_contacts::{
+    MarketingContact, MarketingContactsImport, MarketingContactsProvider,
+};
+
+const LOG_TARGET: &'static str = "bigneon::utils::mailchimp";
+// Mailchimp accepts at most 500 members per batch subscribe request
+const BATCH_SIZE: usize = 500;
+
+/// Postal contact details Mailchimp requires for every list, shown in the footer of campaigns
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct MailchimpListContact {
+    pub company: String,
+    pub address1: String,
+    pub city: String,
+    pub state: String,
+    pub zip: String,
+    pub country: String,
+}
+
+#[derive(Deserialize)]
+struct MailchimpList {
+    id: String,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct MailchimpListsResponse {
+    lists: Vec<MailchimpList>,
+}
+
+#[derive(Deserialize)]
+struct MailchimpMember {
+    id: String,
+    list_id: String,
+}
+
+#[derive(Deserialize)]
+struct MailchimpMembers {
+    members: Vec<MailchimpMember>,
+}
+
+#[derive(Deserialize)]
+struct MailchimpSearchMembersResponse {
+    exact_matches: MailchimpMembers,
+}
+
+#[derive(Deserialize)]
+struct MailchimpBatchSubscribeResponse {
+    total_created: u32,
+    error_count: u32,
+}
+
+pub struct MailchimpContactsProvider {
+    api_key: String,
+    contact: MailchimpListContact,
+    from_name: String,
+    from_email: String,
+}
+
+impl MailchimpContactsProvider {
+    pub fn new(
+        api_key: String,
+        contact: MailchimpListContact,
+        from_name: String,
+        from_email: String,
+    ) -> Self {
+        Self {
+            api_key,
+            contact,
+            from_name,
+            from_email,
+        }
+    }
+
+    fn fetch_lists(&self) -> Result<Vec<MailchimpList>, BigNeonError> {
+        let client = Client::new();
+        let url = format!(
+            "{}/lists?count=1000&fields=lists.id,lists.name",
+            api_url(&self.api_key)?
+        );
+        let response: MailchimpListsResponse = self
+            .authorize(client.get(&url))
+            .send()?
+            .error_for_status()?
+            .json()?;
+        Ok(response.lists)
+    }
+
+    fn authorize(&self, req: RequestBuilder) -> RequestBuilder {
+        // Mailchimp ignores the user name, only the key is checked
+        req.basic_auth("bigneon", Some(&self.api_key))
+    }
+}
+
+impl MarketingContactsProvider for MailchimpContactsProvider {
+    fn name(&self) -> &'static str {
+        "Mailchimp"
+    }
+
+    fn find_or_create_list(&self, name: &str) -> Result<String, BigNeonError> {
+        if let Some(list) = self.fetch_lists()?.into_iter().find(|l| l.name == name) {
+            return Ok(list.id);
+        }
+
+        let client = Client::new();
+        let body = json!({
+            "name": name,
+            "contact": self.contact,
+            "permission_reminder": format!(
+                "You are receiving this email because you opted in to updates from {}",
+                self.contact.company
+            ),
+            "campaign_defaults": {
+                "from_name": self.from_name,
+                "from_email": self.from_email,
+                "subject": "",
+                "language": "en",
+            },
+            "email_type_option": false,
+        });
+        let list: MailchimpList = self
+            .authorize(client.post(&format!("{}/lists", api_url(&self.api_key)?)))
+            .json(&body)
+            .send()?
+            .error_for_status()?
+            .json()?;
+        Ok(list.id)
+    }
+
+    fn add_contacts(
+        &self,
+        list_id: &str,
+        contacts: Vec<MarketingContact>,
+    ) -> Result<MarketingContactsImport, BigNeonError> {
+        let client = Client::new();
+        let url = format!("{}/lists/{}", api_url(&self.api_key)?, list_id);
+        let mut import = MarketingContactsImport::default();
+        for batch in contacts.chunks(BATCH_SIZE) {
+            let members: Vec<serde_json::Value> = batch
+                .iter()
+                .map(|c| {
+                    json!({
+                        "email_address": c.email,
+                        "status": "subscribed",
+                        "merge_fields": {
+                            "FNAME": c.first_name.clone().unwrap_or_default(),
+                            "LNAME": c.last_name.clone().unwrap_or_default(),
+                        },
+                    })
+                })
+                .collect();
+            let response: MailchimpBatchSubscribeResponse = self
+                .authorize(client.post(&url))
+                .json(&json!({ "members": members, "update_existing": true }))
+                .send()?
+                .error_for_status()?
+                .json()?;
+            jlog!(Debug, LOG_TARGET, "Subscribed batch to mailchimp list", {
+                "list_id": list_id,
+                "total_created": response.total_created,
+                "error_count": response.error_count
+            });
+            import.added_count += response.total_created;
+            import.error_count += response.error_count;
+        }
+        Ok(import)
+    }
+
+    fn remove_contact(&self, email: &str) -> Result<(), BigNeonError> {
+        let client = Client::new();
+        let url = format!("{}/search-members", api_url(&self.api_key)?);
+        let response: MailchimpSearchMembersResponse = self
+            .authorize(client.get(&url))
+            .query(&[
+                ("query", email),
+                (
+                    "fields",
+                    "exact_matches.members.id,exact_matches.members.list_id",
+                ),
+            ])
+            .send()?
+            .error_for_status()?
+            .json()?;
+        for member in response.exact_matches.members {
+            let url = format!(
+                "{}/lists/{}/members/{}/actions/delete-permanent",
+                api_url(&self.api_key)?,
+                member.list_id,
+                member.id
+            );
+            self.authorize(client.post(&url))
+                .send()?
+                .error_for_status()?;
+        }
+        Ok(())
+    }
+}
+
+/// Keys end with the data center hosting the account, e.g. `0123456789abcdef-us6`. The data
+/// center becomes part of the host name so anything other than letters followed by digits is
+/// rejected.
+fn api_url(api_key: &str) -> Result<String, BigNeonError> {
+    lazy_static! {
+        static ref DATA_CENTER: Regex = Regex::new(r"^[a-z]+[0-9]+$").unwrap();
+    }
+    match api_key.rfind('-') {
+        Some(index) if DATA_CENTER.is_match(&api_key[index + 1..]) => Ok(format!(
+            "https://{}.api.mailchimp.com/3.0",
+            &api_key[index + 1..]
+        )),
+        _ => Err(ApplicationError::new("Invalid Mailchimp API key".to_string()).into()),
+    }
+}
+
+#[test]
+fn test_api_url() {
+    assert_eq!(
+        api_url("0123456789abcdef-us6").unwrap(),
+        "https://us6.api.mailchimp.com/3.0"
+    );
+    assert!(api_url("0123456789abcdef").is_err());
+    assert!(api_url("0123456789abcdef-").is_err());
+    assert!(api_url("0123456789abcdef-evil.com/x?").is_err());
+    assert!(api_url("0123456789abcdef-us6.attacker.com#").is_err());
+}
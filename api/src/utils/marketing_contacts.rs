@@ -1,15 +1,195 @@
 use bigneon_db::models::enums::Tables;
 use chrono::prelude::*;
+use config::Config;
 use diesel::PgConnection;
-use errors::BigNeonError;
+use errors::{ApplicationError, BigNeonError};
+use std::collections::HashSet;
 use std::default::Default;
 use uuid::Uuid;
 
 use bigneon_db::models::enums::DomainActionTypes;
-use bigneon_db::models::DomainAction;
+use bigneon_db::models::{
+    DisplayFan, DomainAction, EmailSuppression, Event, MarketingConsent, Organization,
+    OrganizationEmailSettings,
+};
 use domain_events::executors::marketing_contacts::{
     BulkEventFanListImportPayload, CreateEventListPayload,
 };
+use utils::mailchimp::{MailchimpContactsProvider, MailchimpListContact};
+use utils::sendgrid::contacts::{SGContact, SGContactList};
+
+const DATE_FORMAT: &'static str = "%b %e, %Y"; // Jun 1, 2019
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MarketingContact {
+    pub email: String,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MarketingContactsImport {
+    pub added_count: u32,
+    pub error_count: u32,
+}
+
+/// Email marketing service that an organization's fans are copied to
+pub trait MarketingContactsProvider {
+    fn name(&self) -> &'static str;
+
+    /// Returns the id of the list with this name, creating the list if it does not exist yet
+    fn find_or_create_list(&self, name: &str) -> Result<String, BigNeonError>;
+
+    /// Subscribes the contacts to the list, adding them to the account first if needed
+    fn add_contacts(
+        &self,
+        list_id: &str,
+        contacts: Vec<MarketingContact>,
+    ) -> Result<MarketingContactsImport, BigNeonError>;
+
+    /// Permanently removes the contact from the account and all of its lists
+    fn remove_contact(&self, email: &str) -> Result<(), BigNeonError>;
+}
+
+pub struct SendGridContactsProvider {
+    api_key: String,
+}
+
+impl SendGridContactsProvider {
+    pub fn new(api_key: String) -> Self {
+        Self { api_key }
+    }
+}
+
+impl MarketingContactsProvider for SendGridContactsProvider {
+    fn name(&self) -> &'static str {
+        "SendGrid"
+    }
+
+    fn find_or_create_list(&self, name: &str) -> Result<String, BigNeonError> {
+        let list = SGContactList::new(name.to_string()).create_or_return(&self.api_key)?;
+        Ok(list.id.to_string())
+    }
+
+    fn add_contacts(
+        &self,
+        list_id: &str,
+        contacts: Vec<MarketingContact>,
+    ) -> Result<MarketingContactsImport, BigNeonError> {
+        let contacts = contacts
+            .into_iter()
+            .map(|c| SGContact::new(c.email, c.first_name, c.last_name))
+            .collect();
+        let result = SGContact::create_many(&self.api_key, contacts)?;
+        if !result.persisted_recipients.is_empty() {
+            let list_id = list_id.parse::<u64>().map_err(|_| {
+                ApplicationError::new(format!("Invalid sendgrid list id {}", list_id))
+            })?;
+            SGContactList::get_by_id(&self.api_key, list_id)?
+                .add_recipients(&self.api_key, result.persisted_recipients)?;
+        }
+        Ok(MarketingContactsImport {
+            added_count: result.new_count,
+            error_count: result.error_count,
+        })
+    }
+
+    fn remove_contact(&self, email: &str) -> Result<(), BigNeonError> {
+        SGContact::delete_by_email(&self.api_key, email)
+    }
+}
+
+/// Provider the organization's fans are synced to, or `None` if it has not configured one.
+/// SendGrid is used when the organization has keys for both SendGrid and Mailchimp. The
+/// organization's keys must already be decrypted.
+pub fn provider_for_organization(
+    organization: &Organization,
+    config: &Config,
+    conn: &PgConnection,
+) -> Result<Option<Box<MarketingContactsProvider>>, BigNeonError> {
+    if let Some(ref api_key) = organization.sendgrid_api_key {
+        return Ok(Some(Box::new(SendGridContactsProvider::new(
+            api_key.clone(),
+        ))));
+    }
+
+    if let Some(ref api_key) = organization.mailchimp_api_key {
+        let email_settings =
+            OrganizationEmailSettings::find_by_organization_id(organization.id, conn)?;
+        let from_email = email_settings
+            .as_ref()
+            .and_then(|s| s.from_email.clone())
+            .unwrap_or(config.communication_default_source_email.clone());
+        let from_name = email_settings
+            .as_ref()
+            .and_then(|s| s.from_name.clone())
+            .unwrap_or(organization.name.clone());
+        let contact = MailchimpListContact {
+            company: organization.name.clone(),
+            address1: organization.address.clone().unwrap_or_default(),
+            city: organization.city.clone().unwrap_or_default(),
+            state: organization.state.clone().unwrap_or_default(),
+            zip: organization.postal_code.clone().unwrap_or_default(),
+            country: organization.country.clone().unwrap_or_default(),
+        };
+        return Ok(Some(Box::new(MailchimpContactsProvider::new(
+            api_key.clone(),
+            contact,
+            from_name,
+            from_email,
+        ))));
+    }
+
+    Ok(None)
+}
+
+pub fn event_list_name(event: &Event) -> String {
+    match event.event_start {
+        Some(event_start) => format!("{} ({})", event.name, event_start.format(DATE_FORMAT)),
+        None => event.name.clone(),
+    }
+}
+
+/// Fans that opted in to the organization's marketing and whose email has not unsubscribed,
+/// bounced or reported spam
+pub fn marketable_contacts(
+    organization_id: Uuid,
+    fans: Vec<DisplayFan>,
+    conn: &PgConnection,
+) -> Result<Vec<MarketingContact>, BigNeonError> {
+    let consented_user_ids: HashSet<Uuid> =
+        MarketingConsent::consented_user_ids(organization_id, conn)?
+            .into_iter()
+            .collect();
+    let fans: Vec<DisplayFan> = fans
+        .into_iter()
+        .filter(|fan| fan.email.is_some() && consented_user_ids.contains(&fan.user_id))
+        .collect();
+
+    let emails: Vec<String> = fans.iter().filter_map(|fan| fan.email.clone()).collect();
+    let suppressed_emails: HashSet<String> =
+        EmailSuppression::suppressed_emails(&emails, true, conn)?
+            .into_iter()
+            .collect();
+
+    Ok(fans
+        .into_iter()
+        .filter_map(|fan| match fan.email {
+            Some(email) => {
+                if suppressed_emails.contains(&email.trim().to_lowercase()) {
+                    None
+                } else {
+                    Some(MarketingContact {
+                        email,
+                        first_name: fan.first_name,
+                        last_name: fan.last_name,
+                    })
+                }
+            }
+            None => None,
+        })
+        .collect())
+}
 
 // This will be replaced once DomainEvents are functioning
 pub struct CreateEventMarketingListAction {
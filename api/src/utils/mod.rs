@@ -1,10 +1,13 @@
 pub use self::service_locator::*;
 
 pub mod communication;
+pub mod csv;
 pub mod deep_linker;
 pub mod expo;
 pub mod google_recaptcha;
+pub mod mailchimp;
 pub mod marketing_contacts;
+pub mod refunds;
 pub mod sendgrid;
 mod service_locator;
 pub mod spotify;
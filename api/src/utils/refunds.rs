@@ -0,0 +1,277 @@
+use actix_web::HttpResponse;
+use bigneon_db::models::*;
+use communications::mailers;
+use config::{Config, Environment};
+use db::Connection as DbConnection;
+use diesel::Connection;
+use diesel::PgConnection;
+use errors::*;
+use helpers::application;
+use std::cmp;
+use std::collections::HashMap;
+use utils::ServiceLocator;
+use uuid::Uuid;
+
+/// Refunds `items` of `order`, returning the amount refunded and how much was refunded per payment
+/// method. Refunded tickets are returned to the organization wallets and their blockchain
+/// transfers queued before the payments are refunded through their payment processors. If
+/// refunding the payments fails the tickets and queued transfers are rolled back.
+pub fn refund_order_items(
+    order: &Order,
+    items: Vec<RefundItem>,
+    user_id: Uuid,
+    service_locator: &ServiceLocator,
+    connection: &PgConnection,
+) -> Result<(u32, HashMap<PaymentMethods, u32>), BigNeonError> {
+    let ticket_instance_ids = items
+        .iter()
+        .filter(|i| i.ticket_instance_id.is_some())
+        .map(|i| i.ticket_instance_id.unwrap())
+        .collect::<Vec<Uuid>>();
+
+    // Big Neon cannot transfer tokens out of self-custodied wallets to return them to the
+    // organization
+    for ticket_instance_id in &ticket_instance_ids {
+        let ticket = TicketInstance::find(*ticket_instance_id, connection)?;
+        if Wallet::find(ticket.wallet_id, connection)?.self_custodied {
+            return Err(application::unprocessable::<HttpResponse>(
+                "Tickets held in a self-custodied wallet cannot be refunded",
+            )
+            .unwrap_err());
+        }
+    }
+
+    // Refund amount is fee inclusive if fee no longer applies to the order. Orders spanning
+    // organizations are refunded through each organization's own payment processor.
+    let mut refunds_due: Vec<(Option<Uuid>, u32)> = order
+        .refund_by_organization(items, user_id, connection)?
+        .into_iter()
+        .collect();
+    let refund_due: u32 = refunds_due.iter().map(|(_, amount)| amount).sum();
+
+    // Transfer tickets back to the organization wallets
+    let mut tokens_per_asset: HashMap<Uuid, Vec<u64>> = HashMap::new();
+    let mut wallet_id_per_asset: HashMap<Uuid, Uuid> = HashMap::new();
+    let mut ticket_instances_per_asset: HashMap<Uuid, Vec<TicketInstance>> = HashMap::new();
+    let refunded_tickets =
+        RefundedTicket::find_by_ticket_instance_ids(ticket_instance_ids, connection)?
+            .into_iter()
+            .filter(|refund_data| refund_data.ticket_refunded_at.is_some());
+    for refunded_ticket in refunded_tickets {
+        let ticket = TicketInstance::find(refunded_ticket.ticket_instance_id, connection)?;
+        tokens_per_asset
+            .entry(ticket.asset_id)
+            .or_insert_with(|| Vec::new())
+            .push(ticket.token_id as u64);
+        wallet_id_per_asset
+            .entry(ticket.asset_id)
+            .or_insert(ticket.wallet_id);
+        ticket_instances_per_asset
+            .entry(ticket.asset_id)
+            .or_insert_with(|| Vec::new())
+            .push(ticket);
+    }
+
+    let mut refund_breakdown: HashMap<PaymentMethods, u32> = HashMap::new();
+    let mut payment_remaining_balance_map: HashMap<Option<String>, i64> = HashMap::new();
+    let mut amount_refunded = 0;
+
+    // Begin transaction, if it fails at this point the queued blockchain transfers are discarded
+    connection.transaction::<_, BigNeonError, _>(|| {
+        for (asset_id, token_ids) in &tokens_per_asset {
+            let organization_id = Organization::find_by_asset_id(*asset_id, connection)?.id;
+            let organization_wallet =
+                Wallet::find_default_for_organization(organization_id, connection)?;
+            let wallet_id = match wallet_id_per_asset.get(asset_id) {
+                Some(w) => w.clone(),
+                None => {
+                    return Err(application::internal_server_error::<HttpResponse>(
+                        "Could not complete this refund because wallet id not found for asset",
+                    )
+                    .unwrap_err())
+                }
+            };
+            Asset::find(*asset_id, connection)?.queue_blockchain_sync(
+                BlockchainOperation::TransferTokens {
+                    sender_wallet_id: wallet_id,
+                    receiver_wallet_id: organization_wallet.id,
+                    token_ids: token_ids.clone(),
+                },
+                connection,
+            )?;
+            match ticket_instances_per_asset.get(asset_id) {
+                Some(ticket_instances) => {
+                    for ticket_instance in ticket_instances {
+                        ticket_instance.set_wallet(&organization_wallet, connection)?;
+                    }
+                }
+                None => {
+                    return Err(application::internal_server_error::<HttpResponse>(
+                        "No ticket instances exist for transferred tokens",
+                    )
+                    .unwrap_err())
+                }
+            }
+        }
+
+        // Perform refunds
+
+        // Negative payments / refunds cancel out remaining payment balance
+        for payment in order.payments(connection)? {
+            // Ignore payments that were only authorized
+            if payment.status == PaymentStatus::Authorized {
+                continue;
+            }
+
+            *payment_remaining_balance_map
+                .entry(payment.external_reference)
+                .or_insert(0) += payment.amount;
+        }
+
+        for payment in order.payments(connection)? {
+            if payment.status != PaymentStatus::Completed {
+                continue;
+            }
+
+            let remaining_balance = payment_remaining_balance_map
+                .get(&payment.external_reference)
+                .map(|n| *n)
+                .unwrap_or(0);
+            if remaining_balance <= 0 {
+                continue;
+            }
+
+            let mut payment_balance = remaining_balance as u32;
+            for (organization_id, organization_refund_due) in refunds_due.iter_mut() {
+                let amount_to_refund = cmp::min(*organization_refund_due, payment_balance);
+                if amount_to_refund == 0 {
+                    continue;
+                }
+
+                let mut refund_data = None;
+                if payment.payment_method == PaymentMethods::CreditCard
+                    || (payment.payment_method == PaymentMethods::Provider
+                        && ServiceLocator::is_refund_supported(payment.provider.to_string()))
+                {
+                    let organization = refund_organization(order, *organization_id, connection)?;
+                    let client = &service_locator
+                        .create_payment_processor(payment.provider, &organization)?;
+
+                    refund_data = match payment.external_reference {
+                        Some(ref external_reference) => Some(
+                            client
+                                .partial_refund(external_reference, amount_to_refund)?
+                                .to_json()?,
+                        ),
+                        None => {
+                            let message = format!(
+                                "Unable to refund amount owed payment {} lacks external reference",
+                                payment.id
+                            );
+                            return Err(application::internal_server_error::<HttpResponse>(
+                                &message,
+                            )
+                            .unwrap_err());
+                        }
+                    };
+                }
+                payment.log_refund(user_id, amount_to_refund, refund_data, connection)?;
+                *refund_breakdown.entry(payment.payment_method).or_insert(0) += amount_to_refund;
+                amount_refunded += amount_to_refund;
+                payment_balance -= amount_to_refund;
+                *organization_refund_due -= amount_to_refund;
+            }
+        }
+
+        if amount_refunded < refund_due {
+            return Err(application::internal_server_error::<HttpResponse>(&format!(
+                "Unable to refund amount owed {} refunded, {} due",
+                amount_refunded, refund_due
+            ))
+            .unwrap_err());
+        }
+
+        Ok(())
+    })?;
+
+    Ok((amount_refunded, refund_breakdown))
+}
+
+/// Organization whose payment processor refunds its part of `order`. Items not tied to an event
+/// are refunded through the order's first organization.
+fn refund_organization(
+    order: &Order,
+    organization_id: Option<Uuid>,
+    connection: &PgConnection,
+) -> Result<Organization, BigNeonError> {
+    match organization_id {
+        Some(organization_id) => Ok(Organization::find(organization_id, connection)?),
+        None => match order.organizations(connection)?.into_iter().next() {
+            Some(organization) => Ok(organization),
+            None => Err(application::internal_server_error::<HttpResponse>(
+                "Could not find an organization to refund this order through",
+            )
+            .unwrap_err()),
+        },
+    }
+}
+
+/// Approves `refund_request` and refunds its tickets, notifying the fan by email. Refunds are
+/// committed once the payment has been refunded. `reviewed_by_user_id` is `None` when the
+/// request was approved automatically.
+pub fn approve_refund_request(
+    refund_request: &RefundRequest,
+    reviewed_by_user_id: Option<Uuid>,
+    config: &Config,
+    service_locator: &ServiceLocator,
+    conn: &DbConnection,
+) -> Result<RefundRequest, BigNeonError> {
+    let connection = conn.get();
+    let order = refund_request.order(connection)?;
+    let (refund_request, amount_refunded) = connection.transaction::<_, BigNeonError, _>(|| {
+        let (amount_refunded, _) = refund_order_items(
+            &order,
+            refund_request.refund_items(connection)?,
+            reviewed_by_user_id.unwrap_or(refund_request.user_id),
+            service_locator,
+            connection,
+        )?;
+        Ok((
+            refund_request.approve(reviewed_by_user_id, connection)?,
+            amount_refunded,
+        ))
+    })?;
+
+    // Commit changes as payment completed
+    if config.environment != Environment::Test {
+        conn.commit_transaction()?;
+        conn.begin_transaction()?;
+    }
+
+    send_refund_email(&order, amount_refunded, config, connection)?;
+    Ok(refund_request)
+}
+
+/// Emails the owner of `order` a breakdown of the refunded order
+pub fn send_refund_email(
+    order: &Order,
+    amount_refunded: u32,
+    config: &Config,
+    connection: &PgConnection,
+) -> Result<(), BigNeonError> {
+    let user_id = order.on_behalf_of_user_id.unwrap_or(order.user_id);
+    let display_order =
+        Order::find(order.id, connection)?.for_display(None, user_id, connection)?;
+    let user = User::find(user_id, connection)?;
+    if let (Some(first_name), Some(email)) = (user.first_name, user.email) {
+        mailers::orders::refund_email(
+            &first_name,
+            email,
+            display_order,
+            amount_refunded,
+            config,
+            connection,
+        )?;
+    }
+    Ok(())
+}
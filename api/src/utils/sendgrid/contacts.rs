@@ -23,6 +23,11 @@ pub struct SGErrorDetail {
     pub error_indices: Option<Vec<i64>>,
 }
 
+#[derive(Clone, Deserialize)]
+pub struct SGRecipient {
+    pub id: String,
+}
+
 #[derive(Clone, Default, Deserialize)]
 pub struct SGCreateContactResponse {
     pub new_count: u32,
@@ -79,6 +84,34 @@ impl SGContact {
         .map(|json| json.into())
     }
 
+    /// Removes every recipient with this email from the account, and so from all of its lists
+    pub fn delete_by_email(api_key: &str, email: &str) -> Result<(), BigNeonError> {
+        let client = Client::new();
+        let req = client
+            .get(&Self::api_url(Some("search".to_string())))
+            .query(&[("email", email)]);
+        let recipient_ids: Vec<String> = match send_request_json(api_key, req)?.get("recipients") {
+            Some(recipients) => serde_json::from_value::<Vec<SGRecipient>>(recipients.clone())
+                .map_err(|err| ApplicationError::new(format!("{}", err)))?
+                .into_iter()
+                .map(|r| r.id)
+                .collect(),
+            None => Vec::new(),
+        };
+        if recipient_ids.is_empty() {
+            return Ok(());
+        }
+
+        // Sendgrid sends back a blank response on success
+        let req = client
+            .delete(&Self::api_url(None))
+            .body(json!(recipient_ids).to_string());
+        send_request(api_key, req)
+            .and_then(|r| r.error_for_status())
+            .map(|_r| ())
+            .map_err(|err| ApplicationError::new(err.to_string()).into())
+    }
+
     fn api_url(recipient_id: Option<String>) -> String {
         let base_url = SENDGRID_API_URL.to_owned();
         let id = recipient_id
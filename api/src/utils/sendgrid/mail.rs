@@ -102,6 +102,7 @@ pub fn send_email_template(
 pub fn send_email_template_async(
     sg_api_key: &str,
     source_email_address: String,
+    source_name: Option<String>,
     dest_email_addresses: &[String],
     template_id: String,
     template_data: &[TemplateData],
@@ -115,7 +116,10 @@ pub fn send_email_template_async(
         ))
     } else {
         let mut sg_message = SGMailMessage::new();
-        sg_message.from = SGEmail::from(source_email_address);
+        sg_message.from = SGEmail {
+            email: source_email_address,
+            name: source_name,
+        };
         sg_message.template_id = Some(template_id);
 
         for i in 0..dest_email_addresses.len() {
@@ -1,2 +1,3 @@
 pub mod contacts;
 pub mod mail;
+pub mod templates;
@@ -0,0 +1,74 @@
+use errors::*;
+use regex::{Captures, Regex};
+use reqwest::Client;
+use utils::communication::TemplateData;
+
+const SENDGRID_API_URL: &'static str = "https://api.sendgrid.com/v3";
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct SGTemplateVersion {
+    pub id: String,
+    pub active: u8,
+    pub subject: Option<String>,
+    pub html_content: Option<String>,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct SGTemplate {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub versions: Vec<SGTemplateVersion>,
+}
+
+impl SGTemplate {
+    pub fn find(api_key: &str, template_id: &str) -> Result<SGTemplate, BigNeonError> {
+        let client = Client::new();
+        client
+            .get(&format!("{}/templates/{}", SENDGRID_API_URL, template_id))
+            .header("Authorization", format!("Bearer {}", api_key))
+            .header("user-agent", "sendgrid-rs")
+            .send()
+            .and_then(|r| r.error_for_status())
+            .and_then(|mut r| r.json())
+            .map_err(|err| ApplicationError::new(err.to_string()).into())
+    }
+
+    pub fn active_version(&self) -> Option<&SGTemplateVersion> {
+        self.versions.iter().find(|v| v.active == 1)
+    }
+}
+
+/// Substitutes `{{name}}` and `{{{name}}}` placeholders with the matching template data.
+/// Handlebars helpers and blocks are not evaluated, so the result is only an approximation
+/// of what SendGrid will send.
+pub fn render(content: &str, template_data: &TemplateData) -> String {
+    lazy_static! {
+        static ref PLACEHOLDER: Regex = Regex::new(r"\{\{\{?\s*([A-Za-z0-9_]+)\s*\}?\}\}").unwrap();
+    }
+    PLACEHOLDER
+        .replace_all(content, |captures: &Captures| {
+            template_data
+                .get(&captures[1])
+                .cloned()
+                .unwrap_or_else(|| captures[0].to_string())
+        })
+        .to_string()
+}
+
+#[test]
+fn test_render() {
+    let mut template_data = TemplateData::new();
+    template_data.insert("name".to_string(), "Jane".to_string());
+    template_data.insert(
+        "logo_url".to_string(),
+        "https://example.com/logo.png".to_string(),
+    );
+    assert_eq!(
+        render(
+            r#"<img src="{{{logo_url}}}"/> Hi {{ name }}, {{#if org_name}}{{org_name}}{{/if}}"#,
+            &template_data
+        ),
+        r#"<img src="https://example.com/logo.png"/> Hi Jane, {{#if org_name}}{{org_name}}{{/if}}"#
+    );
+}
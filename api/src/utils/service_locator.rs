@@ -2,8 +2,10 @@ use bigneon_db::prelude::*;
 use config::Config;
 use errors::*;
 use payments::globee::GlobeePaymentProcessor;
+use payments::paypal::PaypalPaymentProcessor;
 use payments::stripe::StripePaymentProcessor;
 use payments::PaymentProcessor;
+use stripe::StripeClient;
 use utils::deep_linker::BranchDeepLinker;
 use utils::deep_linker::DeepLinker;
 
@@ -11,6 +13,10 @@ pub struct ServiceLocator {
     stripe_secret_key: String,
     globee_api_key: String,
     globee_base_url: String,
+    paypal_client_id: String,
+    paypal_secret: String,
+    paypal_base_url: String,
+    primary_currency: String,
     branch_io_base_url: String,
     branch_io_branch_key: String,
     api_keys_encryption_key: String,
@@ -22,6 +28,10 @@ impl ServiceLocator {
             stripe_secret_key: config.stripe_secret_key.clone(),
             globee_api_key: config.globee_api_key.clone(),
             globee_base_url: config.globee_base_url.clone(),
+            paypal_client_id: config.paypal_client_id.clone(),
+            paypal_secret: config.paypal_secret.clone(),
+            paypal_base_url: config.paypal_base_url.clone(),
+            primary_currency: config.primary_currency.clone(),
             branch_io_base_url: config.branch_io_base_url.clone(),
             branch_io_branch_key: config.branch_io_branch_key.clone(),
             api_keys_encryption_key: config.api_keys_encryption_key.clone(),
@@ -36,6 +46,9 @@ impl ServiceLocator {
         match provider {
             PaymentProviders::Stripe => Ok(Box::new(StripePaymentProcessor::new(
                 self.stripe_secret_key.clone(),
+                organization
+                    .stripe_connect_destination()
+                    .map(|a| a.to_string()),
             ))),
             PaymentProviders::Globee => {
                 let mut org = organization.clone();
@@ -47,6 +60,12 @@ impl ServiceLocator {
                     self.globee_base_url.clone(),
                 )))
             }
+            PaymentProviders::Paypal => Ok(Box::new(PaypalPaymentProcessor::new(
+                self.paypal_client_id.clone(),
+                self.paypal_secret.clone(),
+                self.paypal_base_url.clone(),
+                self.primary_currency.clone(),
+            ))),
             // External is not valid for service locator
             PaymentProviders::Free | PaymentProviders::External => {
                 return Err(ApplicationError::new("Unknown payment provider".into()).into());
@@ -54,6 +73,28 @@ impl ServiceLocator {
         }
     }
 
+    /// Payment processor used to manage a user's stored payment methods, which are not tied
+    /// to any one organization.
+    pub fn create_stored_payment_processor(
+        &self,
+        provider: PaymentProviders,
+    ) -> Result<Box<PaymentProcessor>, BigNeonError> {
+        match provider {
+            PaymentProviders::Stripe => Ok(Box::new(StripePaymentProcessor::new(
+                self.stripe_secret_key.clone(),
+                None,
+            ))),
+            _ => Err(ApplicationError::new(
+                "Payment provider does not support stored payment methods".into(),
+            )
+            .into()),
+        }
+    }
+
+    pub fn create_stripe_client(&self) -> StripeClient {
+        StripeClient::new(self.stripe_secret_key.clone())
+    }
+
     pub fn create_deep_linker(&self) -> Result<Box<DeepLinker>, BigNeonError> {
         Ok(Box::new(BranchDeepLinker::new(
             self.branch_io_base_url.clone(),
@@ -65,6 +106,7 @@ impl ServiceLocator {
         match provider.to_lowercase().as_str() {
             "stripe" => true,
             "globee" => false,
+            "paypal" => true,
             "external" => false,
             _ => false,
         }
@@ -202,6 +202,52 @@ impl Spotify {
         }
     }
 
+    /// Spotify ids of artists related to the given artist
+    pub fn related_artist_ids(&self, artist_id: &str) -> Result<Vec<String>, BigNeonError> {
+        {
+            // Without an auth token there are simply no related artists
+            if self.auth_token.read().unwrap().is_none() {
+                return Ok(vec![]);
+            }
+        }
+
+        self.connect()?;
+
+        // Lock access token for reading
+        let access_token = self.access_token.read().unwrap();
+
+        let reqwest_client = Client::new();
+        let url = format!(
+            "https://api.spotify.com/v1/artists/{}/related-artists",
+            artist_id
+        );
+        let res = reqwest_client
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", &*access_token.token))
+            .send()?
+            .text()?;
+
+        let result: Value = serde_json::from_str(&res)?;
+        if result.get("error").is_some() {
+            return Err(ApplicationError::new(
+                result["error"]["message"]
+                    .as_str()
+                    .unwrap_or("Invalid Spotify Response")
+                    .to_string(),
+            )
+            .into());
+        }
+        Ok(result["artists"]
+            .as_array()
+            .map(|artists| {
+                artists
+                    .iter()
+                    .filter_map(|a| a["id"].as_str().map(|s| s.to_string()))
+                    .collect()
+            })
+            .unwrap_or(Vec::new()))
+    }
+
     pub fn get_image_from_artist(
         image_array: &Value,
         min_width: Option<i64>,
@@ -119,6 +119,7 @@ fn index_with_org_linked_and_private_venues() {
 
     //now with user that DOES belong to org
     let _ = org1.add_user(
+        None,
         user_id,
         vec![Roles::OrgMember],
         Vec::new(),
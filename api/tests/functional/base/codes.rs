@@ -89,6 +89,11 @@ pub fn create(role: Roles, should_test_succeed: bool) {
         end_date,
         max_tickets_per_user: None,
         ticket_type_ids: vec![ticket_type_id],
+        min_quantity: None,
+        buy_quantity: None,
+        get_quantity: None,
+        max_order_discount_in_cents: None,
+        waive_fees: false,
     });
 
     let test_request = TestRequest::create();
@@ -1,5 +1,7 @@
 use actix_web::{http::StatusCode, FromRequest, HttpResponse, Path, Query};
-use bigneon_api::controllers::comps::{self, NewCompRequest};
+use bigneon_api::controllers::comps::{
+    self, BulkIssueCompsRequest, IssueCompRequest, IssuedCompResponse, NewCompRequest,
+};
 use bigneon_api::controllers::holds::UpdateHoldRequest;
 use bigneon_api::extractors::*;
 use bigneon_api::models::PathParameters;
@@ -151,6 +153,128 @@ pub fn create(role: Roles, should_test_succeed: bool) {
     }
 }
 
+pub fn issue(role: Roles, should_test_succeed: bool) {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let hold = database
+        .create_hold()
+        .with_hold_type(HoldTypes::Comp)
+        .finish();
+    let event = Event::find(hold.event_id, connection).unwrap();
+    let organization = event.organization(connection).unwrap();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+
+    let json = Json(IssueCompRequest {
+        name: "Comp Example".to_string(),
+        email: Some("email@address.com".to_string()),
+        phone: None,
+        quantity: 2,
+        redemption_code: None,
+    });
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = hold.id;
+
+    let response: HttpResponse = comps::issue((
+        database.connection.clone().into(),
+        json,
+        path,
+        auth_user,
+        test_request.extract_state(),
+    ))
+    .into();
+
+    if should_test_succeed {
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = support::unwrap_body_to_string(&response).unwrap();
+        let issued_comp: IssuedCompResponse = serde_json::from_str(&body).unwrap();
+        assert_eq!(issued_comp.comp.parent_hold_id, Some(hold.id));
+        assert_eq!(issued_comp.comp.quantity, 2);
+        assert_eq!(issued_comp.comp.available, 0);
+
+        let order = Order::find(issued_comp.order_id, connection).unwrap();
+        assert_eq!(order.status, OrderStatus::Paid);
+        assert_eq!(order.user_id, user.id);
+        assert_eq!(
+            order.on_behalf_of_user_id,
+            Some(issued_comp.recipient_user_id)
+        );
+        let recipient = User::find(issued_comp.recipient_user_id, connection).unwrap();
+        let wallet = recipient.default_wallet(connection).unwrap();
+        let ticket_ids = TicketInstance::find_ids_for_order(order.id, connection).unwrap();
+        for ticket_id in ticket_ids {
+            let ticket = TicketInstance::find(ticket_id, connection).unwrap();
+            assert_eq!(ticket.wallet_id, wallet.id);
+            assert_eq!(ticket.transfer_key, Some(issued_comp.transfer_key));
+        }
+    } else {
+        support::expects_unauthorized(&response);
+    }
+}
+
+pub fn issue_bulk() {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let hold = database
+        .create_hold()
+        .with_hold_type(HoldTypes::Comp)
+        .finish();
+    let event = Event::find(hold.event_id, connection).unwrap();
+    let organization = event.organization(connection).unwrap();
+    let auth_user =
+        support::create_auth_user_from_user(&user, Roles::OrgOwner, Some(&organization), &database);
+
+    let json = Json(BulkIssueCompsRequest {
+        csv: "name,email,phone,quantity\nJane Smith,jane@example.com,,2\nBob,,+15555555555,1\n"
+            .to_string(),
+    });
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = hold.id;
+    let response: HttpResponse = comps::issue_bulk((
+        database.connection.clone().into(),
+        json,
+        path,
+        auth_user.clone(),
+        test_request.extract_state(),
+    ))
+    .into();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let issued_comps: Vec<IssuedCompResponse> = serde_json::from_str(&body).unwrap();
+    assert_eq!(issued_comps.len(), 2);
+    assert_eq!(issued_comps[0].comp.name, "Jane Smith");
+    assert_eq!(issued_comps[1].comp.phone, Some("+15555555555".to_string()));
+
+    // Nothing is issued when a guest is invalid
+    let json = Json(BulkIssueCompsRequest {
+        csv: "name,email,quantity\nJane Smith,jane@example.com,2\nBob,bob@example.com,0\n"
+            .to_string(),
+    });
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = hold.id;
+    let response: HttpResponse = comps::issue_bulk((
+        database.connection.clone().into(),
+        json,
+        path,
+        auth_user,
+        test_request.extract_state(),
+    ))
+    .into();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(
+        Hold::find_by_parent_id(hold.id, HoldTypes::Comp, 0, 100, connection)
+            .unwrap()
+            .data
+            .len(),
+        2
+    );
+}
+
 pub fn destroy(role: Roles, should_succeed: bool) {
     let database = TestDatabase::new();
     let connection = database.connection.get();
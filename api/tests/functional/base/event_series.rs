@@ -0,0 +1,189 @@
+use actix_web::{http::StatusCode, FromRequest, HttpResponse, Path};
+use bigneon_api::controllers::event_series::{self, CreateEventSeriesRequest, EventSeriesResponse};
+use bigneon_api::extractors::*;
+use bigneon_api::models::PathParameters;
+use bigneon_db::models::*;
+use chrono::prelude::*;
+use chrono::Duration;
+use serde_json;
+use support;
+use support::database::TestDatabase;
+use support::test_request::TestRequest;
+
+pub fn create(role: Roles, should_test_succeed: bool) {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let event = database
+        .create_event()
+        .with_organization(&organization)
+        .with_event_start(Utc::now().naive_utc() + Duration::days(1))
+        .finish();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+
+    let test_request = TestRequest::create_with_uri_custom_params("/", vec!["id"]);
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = event.id;
+    let json = Json(CreateEventSeriesRequest {
+        name: "Weekly".to_string(),
+        recurrence_rule: "FREQ=WEEKLY;COUNT=3".to_string(),
+    });
+
+    let response: HttpResponse =
+        event_series::create((database.connection.clone().into(), path, json, auth_user)).into();
+
+    if should_test_succeed {
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = support::unwrap_body_to_string(&response).unwrap();
+        let response: EventSeriesResponse = serde_json::from_str(&body).unwrap();
+        assert_eq!(response.event_series.name, "Weekly");
+        assert_eq!(response.event_series.template_event_id, event.id);
+        assert_eq!(response.events.len(), 3);
+        assert_eq!(
+            Event::find(event.id, connection).unwrap().event_series_id,
+            Some(response.event_series.id)
+        );
+    } else {
+        support::expects_unauthorized(&response);
+        assert!(
+            EventSeries::find_for_organization(organization.id, connection)
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
+
+pub fn update(role: Roles, should_test_succeed: bool) {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let event = database
+        .create_event()
+        .with_organization(&organization)
+        .with_event_start(Utc::now().naive_utc() + Duration::days(1))
+        .finish();
+    let series = EventSeries::create(
+        &event,
+        "Weekly".to_string(),
+        "FREQ=WEEKLY;COUNT=3".to_string(),
+    )
+    .commit(None, connection)
+    .unwrap();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+
+    let test_request = TestRequest::create_with_uri_custom_params("/", vec!["id"]);
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = series.id;
+    let json = Json(EventSeriesEditableAttributes {
+        name: Some("Friday nights".to_string()),
+        ..Default::default()
+    });
+
+    let response: HttpResponse =
+        event_series::update((database.connection.clone().into(), path, json, auth_user)).into();
+
+    if should_test_succeed {
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = support::unwrap_body_to_string(&response).unwrap();
+        let updated_series: EventSeries = serde_json::from_str(&body).unwrap();
+        assert_eq!(updated_series.name, "Friday nights");
+    } else {
+        support::expects_unauthorized(&response);
+        assert_eq!(
+            EventSeries::find(series.id, connection).unwrap().name,
+            "Weekly"
+        );
+    }
+}
+
+pub fn destroy(role: Roles, should_test_succeed: bool) {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let event = database
+        .create_event()
+        .with_organization(&organization)
+        .with_event_start(Utc::now().naive_utc() + Duration::days(1))
+        .finish();
+    let series = EventSeries::create(
+        &event,
+        "Weekly".to_string(),
+        "FREQ=WEEKLY;COUNT=3".to_string(),
+    )
+    .commit(None, connection)
+    .unwrap();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+
+    let test_request = TestRequest::create_with_uri_custom_params("/", vec!["id"]);
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = series.id;
+
+    let response: HttpResponse =
+        event_series::destroy((database.connection.clone().into(), path, auth_user)).into();
+
+    if should_test_succeed {
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(EventSeries::find(series.id, connection).is_err());
+        // The events themselves are kept
+        let event = Event::find(event.id, connection).unwrap();
+        assert_eq!(event.event_series_id, None);
+    } else {
+        support::expects_unauthorized(&response);
+        assert!(EventSeries::find(series.id, connection).is_ok());
+    }
+}
+
+pub fn update_events(role: Roles, should_test_succeed: bool) {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let event = database
+        .create_event()
+        .with_organization(&organization)
+        .with_event_start(Utc::now().naive_utc() + Duration::days(1))
+        .finish();
+    let series = EventSeries::create(
+        &event,
+        "Weekly".to_string(),
+        "FREQ=WEEKLY;COUNT=3".to_string(),
+    )
+    .commit(None, connection)
+    .unwrap();
+    series.generate_events(None, connection).unwrap();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+
+    let test_request = TestRequest::create_with_uri_custom_params("/", vec!["id"]);
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = series.id;
+    let json = Json(EventEditableAttributes {
+        name: Some("New name".to_string()),
+        ..Default::default()
+    });
+
+    let response: HttpResponse =
+        event_series::update_events((database.connection.clone().into(), path, json, auth_user))
+            .into();
+
+    if should_test_succeed {
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = support::unwrap_body_to_string(&response).unwrap();
+        let events: Vec<Event> = serde_json::from_str(&body).unwrap();
+        assert_eq!(events.len(), 3);
+        for event in series.events(connection).unwrap() {
+            assert_eq!(event.name, "New name");
+        }
+    } else {
+        support::expects_unauthorized(&response);
+        for event in series.events(connection).unwrap() {
+            assert_ne!(event.name, "New name");
+        }
+    }
+}
@@ -172,6 +172,120 @@ pub fn cancel(role: Roles, should_test_succeed: bool) {
     }
 }
 
+pub fn clone(role: Roles, should_test_succeed: bool) {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let event = database
+        .create_event()
+        .with_organization(&organization)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = event.id;
+    let event_start = event.event_start.unwrap() + Duration::days(7);
+    let json = Json(CloneEventRequest {
+        event_start,
+        include_codes: false,
+    });
+
+    let response: HttpResponse =
+        events::clone((database.connection.clone().into(), path, json, auth_user)).into();
+    if should_test_succeed {
+        let body = support::unwrap_body_to_string(&response).unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let new_event: Event = serde_json::from_str(&body).unwrap();
+        assert_ne!(new_event.id, event.id);
+        assert_eq!(new_event.status, EventStatus::Draft);
+        assert_eq!(new_event.event_start, Some(event_start));
+        assert_eq!(
+            new_event
+                .ticket_types(false, None, connection)
+                .unwrap()
+                .len(),
+            1
+        );
+    } else {
+        support::expects_unauthorized(&response);
+    }
+}
+
+pub fn reschedule(role: Roles, should_test_succeed: bool) {
+    let database = TestDatabase::new();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let event = database
+        .create_event()
+        .with_organization(&organization)
+        .with_event_start(Utc::now().naive_utc() + Duration::days(10))
+        .finish();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = event.id;
+    let event_start = event.event_start.unwrap() + Duration::days(7);
+    let json = Json(RescheduleEventRequest {
+        event_start,
+        refund_window_ends_at: Some(Utc::now().naive_utc() + Duration::days(5)),
+    });
+
+    let response: HttpResponse =
+        events::reschedule((database.connection.into(), path, json, auth_user)).into();
+    if should_test_succeed {
+        let body = support::unwrap_body_to_string(&response).unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let updated_event: Event = serde_json::from_str(&body).unwrap();
+        assert_eq!(updated_event.event_start, Some(event_start));
+        assert_eq!(
+            updated_event.override_status,
+            Some(EventOverrideStatus::Rescheduled)
+        );
+    } else {
+        support::expects_unauthorized(&response);
+    }
+}
+
+pub fn refunds(role: Roles, should_test_succeed: bool) {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let event = database
+        .create_event()
+        .with_organization(&organization)
+        .with_ticket_pricing()
+        .finish();
+    database.create_order().for_event(&event).is_paid().finish();
+    event.cancel(None, connection).unwrap();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = event.id;
+
+    let response: HttpResponse =
+        events::refunds((database.connection.into(), path, auth_user)).into();
+    if should_test_succeed {
+        let body = support::unwrap_body_to_string(&response).unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let progress: EventRefundProgress = serde_json::from_str(&body).unwrap();
+        assert_eq!(progress.total, 1);
+        assert_eq!(progress.pending, 1);
+        assert!(progress.failures.is_empty());
+    } else {
+        support::expects_unauthorized(&response);
+    }
+}
+
 pub fn add_artist(role: Roles, should_test_succeed: bool) {
     let database = TestDatabase::new();
     let user = database.create_user().finish();
@@ -618,6 +732,7 @@ pub fn holds(role: Roles, should_test_succeed: bool) {
         pub available: u32,
         pub quantity: u32,
         pub parent_hold_id: Option<Uuid>,
+        pub analytics: CodeAnalytics,
     }
 
     let ticket_type = UserDisplayTicketType::from_ticket_type(
@@ -655,6 +770,7 @@ pub fn holds(role: Roles, should_test_succeed: bool) {
             available: 10,
             quantity: 10,
             parent_hold_id: None,
+            analytics: CodeAnalytics::default(),
         },
         R {
             id: hold2.id,
@@ -673,6 +789,7 @@ pub fn holds(role: Roles, should_test_succeed: bool) {
             available: 10,
             quantity: 10,
             parent_hold_id: None,
+            analytics: CodeAnalytics::default(),
         },
     ];
 
@@ -0,0 +1,284 @@
+use actix_web::{http::StatusCode, FromRequest, HttpResponse, Path, Query};
+use bigneon_api::controllers::fan_segments::{self, *};
+use bigneon_api::extractors::*;
+use bigneon_api::models::PathParameters;
+use bigneon_db::models::*;
+use serde_json;
+use support;
+use support::database::TestDatabase;
+use support::test_request::TestRequest;
+
+pub fn index(role: Roles, should_test_succeed: bool) {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let fan_segment = FanSegment::create(
+        organization.id,
+        "Segment".to_string(),
+        Default::default(),
+        user.id,
+    )
+    .commit(connection)
+    .unwrap();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = organization.id;
+    let response: HttpResponse =
+        fan_segments::index((database.connection.clone().into(), path, auth_user)).into();
+
+    if should_test_succeed {
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = support::unwrap_body_to_string(&response).unwrap();
+        let found_fan_segments: Vec<FanSegment> = serde_json::from_str(&body).unwrap();
+        assert_eq!(found_fan_segments, vec![fan_segment]);
+    } else {
+        support::expects_unauthorized(&response);
+    }
+}
+
+pub fn create(role: Roles, should_test_succeed: bool) {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = organization.id;
+    let response: HttpResponse = fan_segments::create((
+        database.connection.clone().into(),
+        path,
+        Json(CreateFanSegmentRequest {
+            name: "Segment".to_string(),
+            filters: Default::default(),
+        }),
+        auth_user,
+    ))
+    .into();
+
+    if should_test_succeed {
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = support::unwrap_body_to_string(&response).unwrap();
+        let fan_segment: FanSegment = serde_json::from_str(&body).unwrap();
+        assert_eq!(fan_segment.name, "Segment");
+        assert_eq!(fan_segment.organization_id, organization.id);
+    } else {
+        support::expects_unauthorized(&response);
+        assert!(
+            FanSegment::find_for_organization(organization.id, connection)
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
+
+pub fn show(role: Roles, should_test_succeed: bool) {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let fan_segment = FanSegment::create(
+        organization.id,
+        "Segment".to_string(),
+        Default::default(),
+        user.id,
+    )
+    .commit(connection)
+    .unwrap();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = fan_segment.id;
+    let response: HttpResponse =
+        fan_segments::show((database.connection.clone().into(), path, auth_user)).into();
+
+    if should_test_succeed {
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = support::unwrap_body_to_string(&response).unwrap();
+        let found_fan_segment: FanSegment = serde_json::from_str(&body).unwrap();
+        assert_eq!(found_fan_segment, fan_segment);
+    } else {
+        support::expects_unauthorized(&response);
+    }
+}
+
+pub fn update(role: Roles, should_test_succeed: bool) {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let fan_segment = FanSegment::create(
+        organization.id,
+        "Segment".to_string(),
+        Default::default(),
+        user.id,
+    )
+    .commit(connection)
+    .unwrap();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = fan_segment.id;
+    let response: HttpResponse = fan_segments::update((
+        database.connection.clone().into(),
+        path,
+        Json(FanSegmentEditableAttributes {
+            name: Some("New name".to_string()),
+            ..Default::default()
+        }),
+        auth_user,
+    ))
+    .into();
+
+    if should_test_succeed {
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = support::unwrap_body_to_string(&response).unwrap();
+        let updated_fan_segment: FanSegment = serde_json::from_str(&body).unwrap();
+        assert_eq!(updated_fan_segment.name, "New name");
+    } else {
+        support::expects_unauthorized(&response);
+        assert_eq!(
+            FanSegment::find(fan_segment.id, connection).unwrap().name,
+            "Segment"
+        );
+    }
+}
+
+pub fn destroy(role: Roles, should_test_succeed: bool) {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let fan_segment = FanSegment::create(
+        organization.id,
+        "Segment".to_string(),
+        Default::default(),
+        user.id,
+    )
+    .commit(connection)
+    .unwrap();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = fan_segment.id;
+    let response: HttpResponse =
+        fan_segments::destroy((database.connection.clone().into(), path, auth_user)).into();
+
+    if should_test_succeed {
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(FanSegment::find(fan_segment.id, connection).is_err());
+    } else {
+        support::expects_unauthorized(&response);
+        assert!(FanSegment::find(fan_segment.id, connection).is_ok());
+    }
+}
+
+pub fn fans(role: Roles, should_test_succeed: bool) {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let event = database
+        .create_event()
+        .with_organization(&organization)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let fan = database.create_user().finish();
+    database
+        .create_order()
+        .for_user(&fan)
+        .for_event(&event)
+        .is_paid()
+        .finish();
+    let fan_segment = FanSegment::create(
+        organization.id,
+        "Segment".to_string(),
+        Default::default(),
+        user.id,
+    )
+    .commit(connection)
+    .unwrap();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+
+    let test_request = TestRequest::create_with_uri(&format!("/limits?"));
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = fan_segment.id;
+    let query_parameters = Query::<PagingParameters>::extract(&test_request.request).unwrap();
+    let response = fan_segments::fans((
+        database.connection.clone().into(),
+        path,
+        query_parameters,
+        auth_user,
+    ));
+
+    if should_test_succeed {
+        let response = response.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.payload().paging.total, 1);
+        assert_eq!(response.payload().data[0].user_id, fan.id);
+    } else {
+        assert_eq!(
+            response.err().unwrap().to_string(),
+            "User does not have the required permissions"
+        );
+    }
+}
+
+pub fn export(role: Roles, should_test_succeed: bool) {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let event = database
+        .create_event()
+        .with_organization(&organization)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let fan = database.create_user().finish();
+    database
+        .create_order()
+        .for_user(&fan)
+        .for_event(&event)
+        .is_paid()
+        .finish();
+    let fan_segment = FanSegment::create(
+        organization.id,
+        "Segment".to_string(),
+        Default::default(),
+        user.id,
+    )
+    .commit(connection)
+    .unwrap();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = fan_segment.id;
+    let response: HttpResponse =
+        fan_segments::export((database.connection.clone().into(), path, auth_user)).into();
+
+    if should_test_succeed {
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = support::unwrap_body_to_string(&response).unwrap();
+        assert_eq!(body.lines().count(), 2);
+        assert!(body.contains(&fan.email.unwrap()));
+    } else {
+        support::expects_unauthorized(&response);
+    }
+}
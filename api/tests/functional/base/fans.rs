@@ -0,0 +1,299 @@
+use actix_web::{http::StatusCode, FromRequest, HttpResponse, Path};
+use bigneon_api::controllers::fans::{self, *};
+use bigneon_api::extractors::*;
+use bigneon_api::models::{OrganizationFanPathParameters, PathParameters};
+use bigneon_db::models::*;
+use serde_json;
+use support;
+use support::database::TestDatabase;
+use support::test_request::TestRequest;
+
+pub fn organization_tags(role: Roles, should_test_succeed: bool) {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let fan = database.create_user().finish();
+    FanTag::create(organization.id, fan.id, "VIP", user.id)
+        .commit(connection)
+        .unwrap();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = organization.id;
+    let response: HttpResponse =
+        fans::organization_tags((database.connection.clone().into(), path, auth_user)).into();
+
+    if should_test_succeed {
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = support::unwrap_body_to_string(&response).unwrap();
+        let names: Vec<String> = serde_json::from_str(&body).unwrap();
+        assert_eq!(names, vec!["VIP".to_string()]);
+    } else {
+        support::expects_unauthorized(&response);
+    }
+}
+
+pub fn tags(role: Roles, should_test_succeed: bool) {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let fan = database.create_user().finish();
+    let tag = FanTag::create(organization.id, fan.id, "VIP", user.id)
+        .commit(connection)
+        .unwrap();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+
+    let test_request = TestRequest::create_with_uri_custom_params("/", vec!["id", "user_id"]);
+    let mut path = Path::<OrganizationFanPathParameters>::extract(&test_request.request).unwrap();
+    path.id = organization.id;
+    path.user_id = fan.id;
+    let response: HttpResponse =
+        fans::tags((database.connection.clone().into(), path, auth_user)).into();
+
+    if should_test_succeed {
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = support::unwrap_body_to_string(&response).unwrap();
+        let tags: Vec<FanTag> = serde_json::from_str(&body).unwrap();
+        assert_eq!(tags, vec![tag]);
+    } else {
+        support::expects_unauthorized(&response);
+    }
+}
+
+pub fn add_tag(role: Roles, should_test_succeed: bool) {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let event = database
+        .create_event()
+        .with_organization(&organization)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let fan = database.create_user().finish();
+    database
+        .create_order()
+        .for_user(&fan)
+        .for_event(&event)
+        .is_paid()
+        .finish();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+
+    let test_request = TestRequest::create_with_uri_custom_params("/", vec!["id", "user_id"]);
+    let mut path = Path::<OrganizationFanPathParameters>::extract(&test_request.request).unwrap();
+    path.id = organization.id;
+    path.user_id = fan.id;
+    let response: HttpResponse = fans::add_tag((
+        database.connection.clone().into(),
+        path,
+        Json(AddFanTagRequest {
+            name: "VIP".to_string(),
+        }),
+        auth_user,
+    ))
+    .into();
+
+    if should_test_succeed {
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = support::unwrap_body_to_string(&response).unwrap();
+        let tag: FanTag = serde_json::from_str(&body).unwrap();
+        assert_eq!(tag.name, "VIP");
+        assert_eq!(tag.user_id, fan.id);
+    } else {
+        support::expects_unauthorized(&response);
+        assert!(FanTag::find_for_fan(organization.id, fan.id, connection)
+            .unwrap()
+            .is_empty());
+    }
+}
+
+pub fn remove_tag(role: Roles, should_test_succeed: bool) {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let fan = database.create_user().finish();
+    let tag = FanTag::create(organization.id, fan.id, "VIP", user.id)
+        .commit(connection)
+        .unwrap();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = tag.id;
+    let response: HttpResponse =
+        fans::remove_tag((database.connection.clone().into(), path, auth_user)).into();
+
+    if should_test_succeed {
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(FanTag::find(tag.id, connection).is_err());
+    } else {
+        support::expects_unauthorized(&response);
+        assert!(FanTag::find(tag.id, connection).is_ok());
+    }
+}
+
+pub fn notes(role: Roles, should_test_succeed: bool) {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let fan = database.create_user().finish();
+    let note = FanNote::create(
+        organization.id,
+        fan.id,
+        "Regular at the bar".to_string(),
+        user.id,
+    )
+    .commit(connection)
+    .unwrap();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+
+    let test_request = TestRequest::create_with_uri_custom_params("/", vec!["id", "user_id"]);
+    let mut path = Path::<OrganizationFanPathParameters>::extract(&test_request.request).unwrap();
+    path.id = organization.id;
+    path.user_id = fan.id;
+    let response: HttpResponse =
+        fans::notes((database.connection.clone().into(), path, auth_user)).into();
+
+    if should_test_succeed {
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = support::unwrap_body_to_string(&response).unwrap();
+        let notes: Vec<FanNote> = serde_json::from_str(&body).unwrap();
+        assert_eq!(notes, vec![note]);
+    } else {
+        support::expects_unauthorized(&response);
+    }
+}
+
+pub fn add_note(role: Roles, should_test_succeed: bool) {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let event = database
+        .create_event()
+        .with_organization(&organization)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let fan = database.create_user().finish();
+    database
+        .create_order()
+        .for_user(&fan)
+        .for_event(&event)
+        .is_paid()
+        .finish();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+
+    let test_request = TestRequest::create_with_uri_custom_params("/", vec!["id", "user_id"]);
+    let mut path = Path::<OrganizationFanPathParameters>::extract(&test_request.request).unwrap();
+    path.id = organization.id;
+    path.user_id = fan.id;
+    let response: HttpResponse = fans::add_note((
+        database.connection.clone().into(),
+        path,
+        Json(FanNoteRequest {
+            note: "Regular at the bar".to_string(),
+        }),
+        auth_user,
+    ))
+    .into();
+
+    if should_test_succeed {
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = support::unwrap_body_to_string(&response).unwrap();
+        let note: FanNote = serde_json::from_str(&body).unwrap();
+        assert_eq!(
+            FanNote::find_for_fan(organization.id, fan.id, connection).unwrap(),
+            vec![note]
+        );
+    } else {
+        support::expects_unauthorized(&response);
+        assert!(FanNote::find_for_fan(organization.id, fan.id, connection)
+            .unwrap()
+            .is_empty());
+    }
+}
+
+pub fn update_note(role: Roles, should_test_succeed: bool) {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let fan = database.create_user().finish();
+    let note = FanNote::create(
+        organization.id,
+        fan.id,
+        "Regular at the bar".to_string(),
+        user.id,
+    )
+    .commit(connection)
+    .unwrap();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = note.id;
+    let response: HttpResponse = fans::update_note((
+        database.connection.clone().into(),
+        path,
+        Json(FanNoteRequest {
+            note: "Prefers the balcony".to_string(),
+        }),
+        auth_user,
+    ))
+    .into();
+
+    let found_note = FanNote::find(note.id, connection).unwrap();
+    if should_test_succeed {
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(found_note.note, "Prefers the balcony");
+    } else {
+        support::expects_unauthorized(&response);
+        assert_eq!(found_note.note, "Regular at the bar");
+    }
+}
+
+pub fn remove_note(role: Roles, should_test_succeed: bool) {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let fan = database.create_user().finish();
+    let note = FanNote::create(
+        organization.id,
+        fan.id,
+        "Regular at the bar".to_string(),
+        user.id,
+    )
+    .commit(connection)
+    .unwrap();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = note.id;
+    let response: HttpResponse =
+        fans::remove_note((database.connection.clone().into(), path, auth_user)).into();
+
+    if should_test_succeed {
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(FanNote::find(note.id, connection).is_err());
+    } else {
+        support::expects_unauthorized(&response);
+        assert!(FanNote::find(note.id, connection).is_ok());
+    }
+}
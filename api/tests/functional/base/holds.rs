@@ -4,6 +4,8 @@ use bigneon_api::controllers::holds::*;
 use bigneon_api::extractors::*;
 use bigneon_api::models::PathParameters;
 use bigneon_db::models::*;
+use chrono::prelude::*;
+use chrono::Duration;
 use serde_json;
 use support;
 use support::database::TestDatabase;
@@ -93,3 +95,38 @@ pub fn update(role: Roles, should_test_succeed: bool) {
         support::expects_unauthorized(&response);
     }
 }
+
+pub fn create_release(role: Roles, should_test_succeed: bool) {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let hold = database.create_hold().finish();
+    let event = Event::find(hold.event_id, connection).unwrap();
+    let organization = event.organization(connection).unwrap();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+    let release_at = Utc::now().naive_utc() + Duration::days(1);
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = hold.id;
+
+    let json = Json(CreateHoldReleaseRequest {
+        release_at: Some(release_at),
+        quantity: Some(2),
+    });
+
+    let response: HttpResponse =
+        holds::create_release((database.connection.clone(), json, path, auth_user)).into();
+    let body = support::unwrap_body_to_string(&response).unwrap();
+
+    if should_test_succeed {
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let release: HoldRelease = serde_json::from_str(&body).unwrap();
+        assert_eq!(release.hold_id, hold.id);
+        assert_eq!(release.quantity, Some(2));
+        assert_eq!(release.status, HoldReleaseStatus::Pending);
+    } else {
+        support::expects_unauthorized(&response);
+    }
+}
@@ -2,15 +2,20 @@ pub mod artists;
 pub mod cart;
 pub mod codes;
 pub mod comps;
+pub mod event_series;
 pub mod events;
+pub mod fan_segments;
+pub mod fans;
 pub mod holds;
 pub mod orders;
 pub mod organization_invites;
 pub mod organizations;
+pub mod refund_requests;
 pub mod regions;
 pub mod reports;
 pub mod stages;
 pub mod ticket_types;
 pub mod tickets;
+pub mod unique_codes;
 pub mod users;
 pub mod venues;
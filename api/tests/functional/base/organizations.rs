@@ -1,5 +1,6 @@
 use actix_web::ResponseError;
 use actix_web::{http::StatusCode, FromRequest, HttpResponse, Path, Query};
+use bigneon_api::communications::mailers::branding::BrandedTemplate;
 use bigneon_api::controllers::organizations;
 use bigneon_api::controllers::organizations::*;
 use bigneon_api::extractors::*;
@@ -184,6 +185,8 @@ pub fn create(role: Roles, should_test_succeed: bool) {
         timezone: None,
         globee_api_key: None,
         max_instances_per_ticket_type: Some(11000),
+        refund_request_auto_approve_days: None,
+        mailchimp_api_key: None,
     });
 
     let test_request = TestRequest::create_with_uri("/organizations");
@@ -233,6 +236,8 @@ pub fn update(role: Roles, should_succeed: bool) {
         cc_fee_percent: Some(5.5),
         globee_api_key: Some(Some("Itsasecret".to_string())),
         max_instances_per_ticket_type: None,
+        refund_request_auto_approve_days: None,
+        mailchimp_api_key: None,
     });
 
     let response: HttpResponse = organizations::update((
@@ -287,6 +292,8 @@ pub fn update_with_max_tickets_per_ticket_type(role: Roles, should_succeed: bool
         cc_fee_percent: Some(5.5),
         globee_api_key: Some(Some("Itsasecret".to_string())),
         max_instances_per_ticket_type: Some(11000),
+        refund_request_auto_approve_days: None,
+        mailchimp_api_key: None,
     });
 
     let response: HttpResponse = organizations::update((
@@ -596,3 +603,211 @@ pub fn add_fee_schedule(role: Roles, should_succeed: bool) {
     let result: FeeScheduleWithRanges = serde_json::from_str(&body).unwrap();
     assert_eq!(result.name, "Fees".to_string());
 }
+
+pub fn update_email_settings(role: Roles, should_succeed: bool) {
+    let database = TestDatabase::new();
+    let user = database.create_user().finish();
+    let organization = database
+        .create_organization()
+        .with_sendgrid_api_key("SG.key")
+        .finish();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = organization.id;
+    let json = Json(OrganizationEmailSettingsEditableAttributes {
+        from_email: Some(Some("tickets@example.com".to_string())),
+        from_name: Some(Some("Example Venue".to_string())),
+        logo_url: Some(Some("https://example.com/logo.png".to_string())),
+        purchase_completed_template_id: Some(Some("d-123".to_string())),
+        ..Default::default()
+    });
+
+    let response: HttpResponse = organizations::update_email_settings((
+        database.connection.clone().into(),
+        path,
+        json,
+        auth_user,
+    ))
+    .into();
+    if !should_succeed {
+        support::expects_unauthorized(&response);
+        return;
+    }
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let settings: OrganizationEmailSettings = serde_json::from_str(&body).unwrap();
+    assert_eq!(settings.organization_id, organization.id);
+    assert_eq!(settings.from_email, Some("tickets@example.com".to_string()));
+    assert_eq!(
+        OrganizationEmailSettings::find_by_organization_id(
+            organization.id,
+            database.connection.get()
+        )
+        .unwrap(),
+        Some(settings)
+    );
+}
+
+pub fn preview_email_unauthorized(role: Roles) {
+    let database = TestDatabase::new();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+
+    let test_request = TestRequest::create_with_uri("/email_preview?template=PurchaseCompleted");
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = organization.id;
+    let query = Query::<EmailPreviewQueryParameters>::extract(&test_request.request).unwrap();
+    assert_eq!(query.template, BrandedTemplate::PurchaseCompleted);
+    let response: HttpResponse = organizations::preview_email((
+        test_request.extract_state(),
+        database.connection.clone().into(),
+        path,
+        query,
+        auth_user,
+    ))
+    .into();
+    support::expects_unauthorized(&response);
+}
+
+pub fn audit_log(role: Roles, should_succeed: bool) {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+    organization
+        .update(
+            Some(user.id),
+            OrganizationEditableAttributes {
+                name: Some("New name".to_string()),
+                ..Default::default()
+            },
+            &"encryption_key".to_string(),
+            connection,
+        )
+        .unwrap();
+
+    let test_request = TestRequest::create_with_uri(&format!(
+        "/organizations/{}/audit_log?event_type=OrganizationUpdated&user_id={}&limit=100000",
+        organization.id, user.id
+    ));
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = organization.id;
+    let query = Query::<AuditLogQueryParameters>::extract(&test_request.request).unwrap();
+    let response =
+        organizations::audit_log((database.connection.clone().into(), path, query, auth_user));
+
+    if !should_succeed {
+        let http_response = response.err().unwrap().error_response();
+        support::expects_unauthorized(&http_response);
+        return;
+    }
+
+    let response = response.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let payload = response.payload();
+    assert_eq!(payload.paging.total, 1);
+    // Oversized pages are capped
+    assert_eq!(payload.paging.limit, 500);
+    assert_eq!(
+        payload.data[0].event_type,
+        DomainEventTypes::OrganizationUpdated
+    );
+    assert_eq!(payload.data[0].user.as_ref().map(|u| u.id), Some(user.id));
+    assert_eq!(
+        payload.data[0].event_data.as_ref().unwrap()["changes"]["name"]["after"],
+        json!("New name")
+    );
+}
+
+pub fn show_stripe_connect(role: Roles, should_succeed: bool) {
+    let database = TestDatabase::new();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = organization.id;
+    let response: HttpResponse = organizations::show_stripe_connect((
+        test_request.extract_state(),
+        database.connection.clone().into(),
+        path,
+        auth_user,
+    ))
+    .into();
+
+    if !should_succeed {
+        support::expects_unauthorized(&response);
+        return;
+    }
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let status: StripeConnectStatus = serde_json::from_str(&body).unwrap();
+    assert_eq!(
+        status,
+        StripeConnectStatus {
+            account_id: None,
+            enabled: false,
+            details_submitted: false,
+        }
+    );
+}
+
+pub fn create_stripe_connect_link_unauthorized(role: Roles) {
+    let database = TestDatabase::new();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = organization.id;
+    let response: HttpResponse = organizations::create_stripe_connect_link((
+        test_request.extract_state(),
+        database.connection.clone().into(),
+        path,
+        auth_user,
+    ))
+    .into();
+    support::expects_unauthorized(&response);
+
+    let organization = Organization::find(organization.id, database.connection.get()).unwrap();
+    assert_eq!(organization.stripe_connect_account_id, None);
+}
+
+pub fn stripe_connect_balance_transactions(role: Roles, should_succeed: bool) {
+    let database = TestDatabase::new();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+
+    let test_request = TestRequest::create_with_uri("/balance_transactions?limit=500");
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = organization.id;
+    let query = Query::<BalanceTransactionQueryParameters>::extract(&test_request.request).unwrap();
+    let response: HttpResponse = organizations::stripe_connect_balance_transactions((
+        test_request.extract_state(),
+        database.connection.clone().into(),
+        path,
+        query,
+        auth_user,
+    ))
+    .into();
+
+    if !should_succeed {
+        support::expects_unauthorized(&response);
+        return;
+    }
+    // Nothing to list until the organization has connected an account
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
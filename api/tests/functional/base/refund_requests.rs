@@ -0,0 +1,197 @@
+use actix_web::{http::StatusCode, FromRequest, HttpResponse, Path, Query};
+use bigneon_api::controllers::refund_requests::{self, *};
+use bigneon_api::errors::*;
+use bigneon_api::extractors::*;
+use bigneon_api::models::*;
+use bigneon_db::models::*;
+use serde_json;
+use support;
+use support::database::TestDatabase;
+use support::test_request::TestRequest;
+
+pub fn index(role: Roles, should_test_succeed: bool) {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let event = database
+        .create_event()
+        .with_organization(&organization)
+        .with_ticket_pricing()
+        .finish();
+    let ticket_type = &event.ticket_types(true, None, connection).unwrap()[0];
+    let fan = database.create_user().finish();
+    let order = database
+        .create_order()
+        .for_user(&fan)
+        .for_event(&event)
+        .is_paid()
+        .finish();
+    let tickets = order.tickets(ticket_type.id, connection).unwrap();
+    let refund_request = RefundRequest::create(
+        order.id,
+        fan.id,
+        vec![tickets[0].id],
+        "Can no longer attend".to_string(),
+    )
+    .commit(connection)
+    .unwrap();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+
+    let test_request = TestRequest::create_with_uri("/?status=Pending");
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = organization.id;
+    let query_parameters = Query::<PagingParameters>::extract(&test_request.request).unwrap();
+    let response: Result<WebPayload<RefundRequest>, BigNeonError> = refund_requests::index((
+        database.connection.clone().into(),
+        path,
+        query_parameters,
+        auth_user,
+    ));
+
+    if should_test_succeed {
+        let response = response.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.payload().data, vec![refund_request]);
+        assert_eq!(response.payload().paging.total, 1);
+    } else {
+        support::expects_unauthorized(&response.unwrap_err().into_inner().to_response());
+    }
+}
+
+pub fn approve(role: Roles, should_test_succeed: bool) {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let event = database
+        .create_event()
+        .with_organization(&organization)
+        .with_ticket_pricing()
+        .finish();
+    let ticket_type = &event.ticket_types(true, None, connection).unwrap()[0];
+    let fan = database.create_user().finish();
+    let order = database
+        .create_order()
+        .for_user(&fan)
+        .for_event(&event)
+        .is_paid()
+        .finish();
+    let tickets = order.tickets(ticket_type.id, connection).unwrap();
+    let refund_request = RefundRequest::create(
+        order.id,
+        fan.id,
+        vec![tickets[0].id],
+        "Can no longer attend".to_string(),
+    )
+    .commit(connection)
+    .unwrap();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = organization.id;
+    let json = Json(ApproveRefundRequestsRequest {
+        refund_request_ids: vec![refund_request.id],
+    });
+    let response: HttpResponse = refund_requests::approve((
+        database.connection.clone().into(),
+        path,
+        json,
+        auth_user,
+        test_request.extract_state(),
+    ))
+    .into();
+
+    if should_test_succeed {
+        let body = support::unwrap_body_to_string(&response).unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let results: Vec<ReviewRefundRequestResult> = serde_json::from_str(&body).unwrap();
+        assert_eq!(
+            results,
+            vec![ReviewRefundRequestResult {
+                refund_request_id: refund_request.id,
+                status: RefundRequestStatus::Approved,
+                error: None,
+            }]
+        );
+        let refund_request = RefundRequest::find(refund_request.id, connection).unwrap();
+        assert_eq!(refund_request.reviewed_by_user_id, Some(user.id));
+        let refunded_tickets =
+            RefundedTicket::find_by_ticket_instance_ids(vec![tickets[0].id], connection).unwrap();
+        assert_eq!(refunded_tickets.len(), 1);
+    } else {
+        support::expects_unauthorized(&response);
+        let refund_request = RefundRequest::find(refund_request.id, connection).unwrap();
+        assert_eq!(refund_request.status, RefundRequestStatus::Pending);
+    }
+}
+
+pub fn deny(role: Roles, should_test_succeed: bool) {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let event = database
+        .create_event()
+        .with_organization(&organization)
+        .with_ticket_pricing()
+        .finish();
+    let ticket_type = &event.ticket_types(true, None, connection).unwrap()[0];
+    let fan = database.create_user().finish();
+    let order = database
+        .create_order()
+        .for_user(&fan)
+        .for_event(&event)
+        .is_paid()
+        .finish();
+    let tickets = order.tickets(ticket_type.id, connection).unwrap();
+    let refund_request = RefundRequest::create(
+        order.id,
+        fan.id,
+        vec![tickets[0].id],
+        "Can no longer attend".to_string(),
+    )
+    .commit(connection)
+    .unwrap();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = organization.id;
+    let json = Json(DenyRefundRequestsRequest {
+        refund_request_ids: vec![refund_request.id],
+        review_notes: Some("Outside of the refund policy".to_string()),
+    });
+    let response: HttpResponse = refund_requests::deny((
+        database.connection.clone().into(),
+        path,
+        json,
+        auth_user,
+        test_request.extract_state(),
+    ))
+    .into();
+
+    if should_test_succeed {
+        let body = support::unwrap_body_to_string(&response).unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let results: Vec<ReviewRefundRequestResult> = serde_json::from_str(&body).unwrap();
+        assert_eq!(
+            results,
+            vec![ReviewRefundRequestResult {
+                refund_request_id: refund_request.id,
+                status: RefundRequestStatus::Denied,
+                error: None,
+            }]
+        );
+        let refund_request = RefundRequest::find(refund_request.id, connection).unwrap();
+        assert_eq!(refund_request.reviewed_by_user_id, Some(user.id));
+    } else {
+        support::expects_unauthorized(&response);
+        let refund_request = RefundRequest::find(refund_request.id, connection).unwrap();
+        assert_eq!(refund_request.status, RefundRequestStatus::Pending);
+    }
+}
@@ -23,7 +23,6 @@ pub fn create(role: Roles, should_test_succeed: bool) {
 
     //Construct Ticket creation and pricing request
     let test_request = TestRequest::create();
-    let state = test_request.extract_state();
     let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
     path.id = event.id;
     let mut ticket_pricing: Vec<CreateTicketPricingRequest> = Vec::new();
@@ -56,13 +55,13 @@ pub fn create(role: Roles, should_test_succeed: bool) {
         price_in_cents: 20000,
         sold_out_behavior: SoldOutBehavior::ShowSoldOut,
         is_private: false,
+        refundable: false,
     };
     let response: HttpResponse = ticket_types::create((
         database.connection.into(),
         path,
         Json(request_data),
         auth_user,
-        state,
     ))
     .into();
 
@@ -134,6 +133,7 @@ pub fn update(role: Roles, should_test_succeed: bool) {
         price_in_cents: Some(15000),
         sold_out_behavior: Some(SoldOutBehavior::Hide),
         is_private: Some(false),
+        refundable: None,
     };
     let request_json = serde_json::to_string(&request_data).unwrap();
 
@@ -143,7 +143,6 @@ pub fn update(role: Roles, should_test_succeed: bool) {
         path,
         Json(request_data),
         auth_user,
-        request.extract_state(),
     ))
     .into();
 
@@ -182,6 +181,7 @@ pub fn update(role: Roles, should_test_succeed: bool) {
         price_in_cents: Some(updated_ticket_type.price_in_cents),
         sold_out_behavior: Some(SoldOutBehavior::Hide),
         is_private: Some(false),
+        refundable: None,
     };
     let updated_json = serde_json::to_string(&updated_data).unwrap();
 
@@ -215,14 +215,13 @@ pub fn cancel(role: Roles, should_test_succeed: bool) {
     //Construct update request
     let test_request =
         TestRequest::create_with_uri_custom_params("/", vec!["event_id", "ticket_type_id"]);
-    let state = test_request.extract_state();
     let mut path = Path::<EventTicketPathParameters>::extract(&test_request.request).unwrap();
     path.event_id = event.id;
     path.ticket_type_id = created_ticket_type.id;
 
     //Send update request
     let response: HttpResponse =
-        ticket_types::cancel((database.connection.clone().into(), path, auth_user, state)).into();
+        ticket_types::cancel((database.connection.clone().into(), path, auth_user)).into();
 
     let updated_ticket_type = &event.ticket_types(true, None, conn).unwrap()[0];
 
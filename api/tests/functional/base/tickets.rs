@@ -105,7 +105,6 @@ pub fn redeem_ticket(role: Roles, should_test_succeed: bool) {
         path,
         Json(request_data),
         auth_user.clone(),
-        request.extract_state(),
     ))
     .into();
 
@@ -122,11 +121,23 @@ pub fn redeem_ticket(role: Roles, should_test_succeed: bool) {
             path2,
             Json(request_data),
             auth_user,
-            request.extract_state(),
         ))
         .into();
 
         assert_eq!(response.status(), StatusCode::OK);
+
+        // Redemption is synced to the blockchain asynchronously
+        let asset = Asset::find(ticket.asset_id, conn).unwrap();
+        let blockchain_syncs = asset.blockchain_syncs(conn).unwrap();
+        let operation: BlockchainOperation =
+            serde_json::from_value(blockchain_syncs[0].payload.clone()).unwrap();
+        assert_eq!(
+            operation,
+            BlockchainOperation::RedeemTokens {
+                wallet_id: ticket.wallet_id,
+                token_ids: vec![ticket.token_id as u64],
+            }
+        );
     } else {
         support::expects_unauthorized(&response);
     }
@@ -0,0 +1,84 @@
+use actix_web::{http::StatusCode, FromRequest, HttpResponse, Path, Query};
+use bigneon_api::controllers::unique_codes::{self, GenerateUniqueCodesRequest};
+use bigneon_api::extractors::*;
+use bigneon_api::models::PathParameters;
+use bigneon_db::models::*;
+use serde_json;
+use support;
+use support::database::TestDatabase;
+use support::test_request::TestRequest;
+
+pub fn index(role: Roles, should_test_succeed: bool) {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let event = database.create_event().finish();
+    let organization = event.organization(connection).unwrap();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+    let code = database.create_code().with_event(&event).finish();
+    UniqueCode::generate(&code, 3, None, None, None, user.id, connection).unwrap();
+
+    let test_request = TestRequest::create_with_uri(&format!("/limits?"));
+    let query_parameters = Query::<PagingParameters>::extract(&test_request.request).unwrap();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = code.id;
+
+    let response = unique_codes::index((
+        database.connection.clone().into(),
+        path,
+        query_parameters,
+        auth_user,
+    ));
+
+    if should_test_succeed {
+        let response = response.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.payload().paging.total, 3);
+        assert_eq!(
+            response.payload().data,
+            UniqueCode::find_all_for_code(code.id, connection).unwrap()
+        );
+    } else {
+        assert_eq!(
+            response.err().unwrap().to_string(),
+            "User does not have the required permissions"
+        );
+    }
+}
+
+pub fn create(role: Roles, should_test_succeed: bool) {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let event = database.create_event().finish();
+    let organization = event.organization(connection).unwrap();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+    let code = database.create_code().with_event(&event).finish();
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = code.id;
+    let json = Json(GenerateUniqueCodesRequest {
+        quantity: 10,
+        prefix: Some("SPONSOR".to_string()),
+        ..Default::default()
+    });
+
+    let response: HttpResponse =
+        unique_codes::create((database.connection.clone().into(), json, path, auth_user)).into();
+
+    if should_test_succeed {
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = support::unwrap_body_to_string(&response).unwrap();
+        let unique_codes: Vec<UniqueCode> = serde_json::from_str(&body).unwrap();
+        assert_eq!(unique_codes.len(), 10);
+        assert!(unique_codes
+            .iter()
+            .all(|unique_code| unique_code.code_id == code.id
+                && unique_code.redemption_code.starts_with("SPONSOR")));
+    } else {
+        support::expects_unauthorized(&response);
+    }
+}
@@ -366,7 +366,9 @@ fn add_with_increment() {
         increment: Some(4),
         ..Default::default()
     };
-    let ticket_type = ticket_type.update(update_parameters, connection).unwrap();
+    let ticket_type = ticket_type
+        .update(None, update_parameters, connection)
+        .unwrap();
     let ticket_type_id = ticket_type.id;
 
     let input = Json(cart::UpdateCartRequest {
@@ -430,7 +432,9 @@ fn update_with_increment_failure_invalid_quantity() {
         increment: Some(4),
         ..Default::default()
     };
-    let ticket_type = ticket_type.update(update_parameters, connection).unwrap();
+    let ticket_type = ticket_type
+        .update(None, update_parameters, connection)
+        .unwrap();
     let ticket_type_id = ticket_type.id;
 
     let input = Json(cart::UpdateCartRequest {
@@ -695,7 +699,9 @@ fn remove_with_increment() {
         increment: Some(4),
         ..Default::default()
     };
-    let ticket_type = ticket_type.update(update_parameters, connection).unwrap();
+    let ticket_type = ticket_type
+        .update(None, update_parameters, connection)
+        .unwrap();
     let ticket_type_id = ticket_type.id;
     let user = database.create_user().finish();
     let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
@@ -791,7 +797,9 @@ fn remove_with_increment_failure_invalid_quantity() {
         increment: Some(4),
         ..Default::default()
     };
-    let ticket_type = ticket_type.update(update_parameters, connection).unwrap();
+    let ticket_type = ticket_type
+        .update(None, update_parameters, connection)
+        .unwrap();
     let ticket_type_id = ticket_type.id;
     let user = database.create_user().finish();
     let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
@@ -885,6 +893,7 @@ fn checkout_external() {
             phone: None,
             note: None,
         },
+        marketing_consent: None,
     });
 
     // Must be admin to check out external
@@ -938,6 +947,7 @@ fn checkout_external_with_free_cart() {
             phone: None,
             note: None,
         },
+        marketing_consent: None,
     });
 
     // Must be admin to check out external
@@ -991,6 +1001,7 @@ fn checkout_paid_fails_with_free_cart() {
             save_payment_method: false,
             set_default: false,
         },
+        marketing_consent: None,
     });
 
     // Must be admin to check out external
@@ -1042,6 +1053,7 @@ fn checkout_free() {
 
     let input = Json(cart::CheckoutCartRequest {
         method: PaymentRequest::Free,
+        marketing_consent: None,
     });
 
     let user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
@@ -1068,6 +1080,46 @@ fn checkout_free() {
     assert_eq!(payment.provider, PaymentProviders::Free);
 }
 
+#[test]
+fn checkout_records_marketing_consent() {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let event = database
+        .create_event()
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let user = database.create_user().finish();
+    database
+        .create_cart()
+        .with_free_items()
+        .for_user(&user)
+        .for_event(&event)
+        .finish();
+    let request = TestRequest::create();
+
+    let input = Json(cart::CheckoutCartRequest {
+        method: PaymentRequest::Free,
+        marketing_consent: Some(true),
+    });
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+    let response = cart::checkout((
+        database.connection.clone().into(),
+        input,
+        auth_user,
+        request.extract_state(),
+        RequestInfo { user_agent: None },
+    ))
+    .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let consents = MarketingConsent::find_for_user(user.id, connection).unwrap();
+    assert_eq!(consents.len(), 1);
+    assert_eq!(consents[0].organization_id, event.organization_id);
+    assert!(consents[0].consented);
+    assert_eq!(consents[0].source, MarketingConsentSources::Checkout);
+}
+
 #[test]
 fn checkout_free_for_paid_items() {
     let database = TestDatabase::new();
@@ -1089,6 +1141,7 @@ fn checkout_free_for_paid_items() {
 
     let input = Json(cart::CheckoutCartRequest {
         method: PaymentRequest::Free,
+        marketing_consent: None,
     });
 
     let user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
@@ -1227,6 +1280,7 @@ fn checkout_fails_for_invalid_items() {
             phone: None,
             note: None,
         },
+        marketing_consent: None,
     });
 
     // Must be admin to check out external
@@ -1255,6 +1309,56 @@ fn checkout_fails_for_invalid_items() {
     let cart = Order::find(cart.id, connection).unwrap();
     assert_eq!(cart.status, OrderStatus::Draft);
 }
+
+#[test]
+fn confirm_checkout_for_other_users_order() {
+    let database = TestDatabase::new();
+    let user = database.create_user().finish();
+    let user2 = database.create_user().finish();
+    let order = database.create_order().for_user(&user2).finish();
+
+    let request = TestRequest::create();
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+    let response: HttpResponse = cart::confirm_checkout((
+        database.connection.clone().into(),
+        Json(ConfirmCheckoutRequest { order_id: order.id }),
+        auth_user,
+        request.extract_state(),
+        RequestInfo { user_agent: None },
+    ))
+    .into();
+    support::expects_forbidden(&response, Some("This cart does not belong to you"));
+}
+
+#[test]
+fn confirm_checkout_when_not_awaiting_payment() {
+    let database = TestDatabase::new();
+    let user = database.create_user().finish();
+    let order = database.create_order().for_user(&user).finish();
+    assert_eq!(order.status, OrderStatus::Draft);
+
+    let request = TestRequest::create();
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+    let response: HttpResponse = cart::confirm_checkout((
+        database.connection.clone().into(),
+        Json(ConfirmCheckoutRequest { order_id: order.id }),
+        auth_user,
+        request.extract_state(),
+        RequestInfo { user_agent: None },
+    ))
+    .into();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let expected_json = HttpResponse::new(StatusCode::UNPROCESSABLE_ENTITY)
+        .into_builder()
+        .json(json!({
+            "error": "Could not confirm this cart because it is not awaiting payment"
+        }));
+    let expected_text = unwrap_body_to_string(&expected_json).unwrap();
+    let body = unwrap_body_to_string(&response).unwrap();
+    assert_eq!(body, expected_text);
+}
+
 #[test]
 fn checkout_provider_globee() {
     let database = TestDatabase::new();
@@ -1278,6 +1382,7 @@ fn checkout_provider_globee() {
         method: PaymentRequest::Provider {
             provider: PaymentProviders::Globee,
         },
+        marketing_consent: None,
     });
 
     // Must be admin to check out external
@@ -1378,3 +1483,102 @@ fn checkout_provider_globee() {
     let order = Order::find(order.id, conn).unwrap();
     assert_eq!(order.status, OrderStatus::Paid);
 }
+
+#[test]
+fn checkout_with_payment_method_id() {
+    let database = TestDatabase::new();
+    let conn = database.connection.get();
+    let event = database
+        .create_event()
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+
+    let user = database.create_user().finish();
+    database
+        .create_payment_method()
+        .with_user(&user)
+        .make_default()
+        .finish();
+    let payment_method = database
+        .create_payment_method()
+        .with_name(PaymentProviders::Globee)
+        .with_user(&user)
+        .finish();
+    let order = database
+        .create_cart()
+        .for_user(&user)
+        .for_event(&event)
+        .finish();
+    let request = TestRequest::create();
+
+    let input = Json(cart::CheckoutCartRequest {
+        method: PaymentRequest::PaymentMethod {
+            provider: None,
+            payment_method_id: Some(payment_method.id),
+        },
+        marketing_consent: None,
+    });
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+
+    let response = cart::checkout((
+        database.connection.clone().into(),
+        input,
+        auth_user,
+        request.extract_state(),
+        RequestInfo { user_agent: None },
+    ))
+    .unwrap();
+
+    // The requested method is charged rather than the default card, Globee redirects to pay
+    assert_eq!(response.status(), StatusCode::OK);
+    let order = Order::find(order.id, conn).unwrap();
+    assert_eq!(order.status, OrderStatus::Draft);
+    assert!(order.checkout_url.is_some());
+}
+
+#[test]
+fn checkout_with_payment_method_id_for_other_user() {
+    let database = TestDatabase::new();
+    let conn = database.connection.get();
+    let event = database
+        .create_event()
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+
+    let user = database.create_user().finish();
+    let payment_method = database.create_payment_method().finish();
+    let order = database
+        .create_cart()
+        .for_user(&user)
+        .for_event(&event)
+        .finish();
+    let request = TestRequest::create();
+
+    let input = Json(cart::CheckoutCartRequest {
+        method: PaymentRequest::PaymentMethod {
+            provider: None,
+            payment_method_id: Some(payment_method.id),
+        },
+        marketing_consent: None,
+    });
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+
+    let response: HttpResponse = cart::checkout((
+        database.connection.clone().into(),
+        input,
+        auth_user,
+        request.extract_state(),
+        RequestInfo { user_agent: None },
+    ))
+    .into();
+
+    support::expects_forbidden(
+        &response,
+        Some("This payment method does not belong to you"),
+    );
+    let order = Order::find(order.id, conn).unwrap();
+    assert_eq!(order.status, OrderStatus::Draft);
+    assert!(order.payments(conn).unwrap().is_empty());
+}
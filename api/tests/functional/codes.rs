@@ -199,6 +199,11 @@ fn create_with_validation_errors() {
         end_date,
         max_tickets_per_user: None,
         ticket_type_ids: vec![ticket_type_id],
+        min_quantity: None,
+        buy_quantity: None,
+        get_quantity: None,
+        max_order_discount_in_cents: None,
+        waive_fees: false,
     });
 
     let test_request = TestRequest::create();
@@ -240,6 +245,11 @@ fn create_with_validation_errors() {
         end_date,
         max_tickets_per_user: None,
         ticket_type_ids: vec![ticket_type_id],
+        min_quantity: None,
+        buy_quantity: None,
+        get_quantity: None,
+        max_order_discount_in_cents: None,
+        waive_fees: false,
     });
 
     let test_request = TestRequest::create();
@@ -271,6 +281,11 @@ fn create_with_validation_errors() {
         end_date,
         max_tickets_per_user: None,
         ticket_type_ids: vec![ticket_type_id],
+        min_quantity: None,
+        buy_quantity: None,
+        get_quantity: None,
+        max_order_discount_in_cents: None,
+        waive_fees: false,
     });
 
     let test_request = TestRequest::create();
@@ -312,6 +327,11 @@ fn create_fails_adding_ticket_type_id_from_other_event() {
         end_date,
         max_tickets_per_user: None,
         ticket_type_ids: vec![ticket_type_id],
+        min_quantity: None,
+        buy_quantity: None,
+        get_quantity: None,
+        max_order_discount_in_cents: None,
+        waive_fees: false,
     });
 
     let test_request = TestRequest::create();
@@ -92,6 +92,52 @@ mod show_tests {
     }
 }
 
+#[cfg(test)]
+mod issue_tests {
+    use super::*;
+    #[test]
+    fn issue_org_member() {
+        base::comps::issue(Roles::OrgMember, true);
+    }
+    #[test]
+    fn issue_admin() {
+        base::comps::issue(Roles::Admin, true);
+    }
+    #[test]
+    fn issue_user() {
+        base::comps::issue(Roles::User, false);
+    }
+    #[test]
+    fn issue_org_owner() {
+        base::comps::issue(Roles::OrgOwner, true);
+    }
+    #[test]
+    fn issue_door_person() {
+        base::comps::issue(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn issue_promoter() {
+        base::comps::issue(Roles::Promoter, true);
+    }
+    #[test]
+    fn issue_promoter_read_only() {
+        base::comps::issue(Roles::PromoterReadOnly, false);
+    }
+    #[test]
+    fn issue_org_admin() {
+        base::comps::issue(Roles::OrgAdmin, true);
+    }
+    #[test]
+    fn issue_box_office() {
+        base::comps::issue(Roles::OrgBoxOffice, false);
+    }
+}
+
+#[test]
+fn issue_bulk() {
+    base::comps::issue_bulk();
+}
+
 #[cfg(test)]
 mod create_tests {
     use super::*;
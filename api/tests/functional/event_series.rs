@@ -0,0 +1,166 @@
+use bigneon_db::models::*;
+use functional::base;
+
+#[cfg(test)]
+mod create_tests {
+    use super::*;
+    #[test]
+    fn create_org_member() {
+        base::event_series::create(Roles::OrgMember, true);
+    }
+    #[test]
+    fn create_admin() {
+        base::event_series::create(Roles::Admin, true);
+    }
+    #[test]
+    fn create_user() {
+        base::event_series::create(Roles::User, false);
+    }
+    #[test]
+    fn create_org_owner() {
+        base::event_series::create(Roles::OrgOwner, true);
+    }
+    #[test]
+    fn create_door_person() {
+        base::event_series::create(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn create_promoter() {
+        base::event_series::create(Roles::Promoter, true);
+    }
+    #[test]
+    fn create_promoter_read_only() {
+        base::event_series::create(Roles::PromoterReadOnly, false);
+    }
+    #[test]
+    fn create_org_admin() {
+        base::event_series::create(Roles::OrgAdmin, true);
+    }
+    #[test]
+    fn create_box_office() {
+        base::event_series::create(Roles::OrgBoxOffice, false);
+    }
+}
+
+#[cfg(test)]
+mod update_tests {
+    use super::*;
+    #[test]
+    fn update_org_member() {
+        base::event_series::update(Roles::OrgMember, true);
+    }
+    #[test]
+    fn update_admin() {
+        base::event_series::update(Roles::Admin, true);
+    }
+    #[test]
+    fn update_user() {
+        base::event_series::update(Roles::User, false);
+    }
+    #[test]
+    fn update_org_owner() {
+        base::event_series::update(Roles::OrgOwner, true);
+    }
+    #[test]
+    fn update_door_person() {
+        base::event_series::update(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn update_promoter() {
+        base::event_series::update(Roles::Promoter, true);
+    }
+    #[test]
+    fn update_promoter_read_only() {
+        base::event_series::update(Roles::PromoterReadOnly, false);
+    }
+    #[test]
+    fn update_org_admin() {
+        base::event_series::update(Roles::OrgAdmin, true);
+    }
+    #[test]
+    fn update_box_office() {
+        base::event_series::update(Roles::OrgBoxOffice, false);
+    }
+}
+
+#[cfg(test)]
+mod destroy_tests {
+    use super::*;
+    #[test]
+    fn destroy_org_member() {
+        base::event_series::destroy(Roles::OrgMember, true);
+    }
+    #[test]
+    fn destroy_admin() {
+        base::event_series::destroy(Roles::Admin, true);
+    }
+    #[test]
+    fn destroy_user() {
+        base::event_series::destroy(Roles::User, false);
+    }
+    #[test]
+    fn destroy_org_owner() {
+        base::event_series::destroy(Roles::OrgOwner, true);
+    }
+    #[test]
+    fn destroy_door_person() {
+        base::event_series::destroy(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn destroy_promoter() {
+        base::event_series::destroy(Roles::Promoter, true);
+    }
+    #[test]
+    fn destroy_promoter_read_only() {
+        base::event_series::destroy(Roles::PromoterReadOnly, false);
+    }
+    #[test]
+    fn destroy_org_admin() {
+        base::event_series::destroy(Roles::OrgAdmin, true);
+    }
+    #[test]
+    fn destroy_box_office() {
+        base::event_series::destroy(Roles::OrgBoxOffice, false);
+    }
+}
+
+#[cfg(test)]
+mod update_events_tests {
+    use super::*;
+    #[test]
+    fn update_events_org_member() {
+        base::event_series::update_events(Roles::OrgMember, true);
+    }
+    #[test]
+    fn update_events_admin() {
+        base::event_series::update_events(Roles::Admin, true);
+    }
+    #[test]
+    fn update_events_user() {
+        base::event_series::update_events(Roles::User, false);
+    }
+    #[test]
+    fn update_events_org_owner() {
+        base::event_series::update_events(Roles::OrgOwner, true);
+    }
+    #[test]
+    fn update_events_door_person() {
+        base::event_series::update_events(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn update_events_promoter() {
+        base::event_series::update_events(Roles::Promoter, true);
+    }
+    #[test]
+    fn update_events_promoter_read_only() {
+        base::event_series::update_events(Roles::PromoterReadOnly, false);
+    }
+    #[test]
+    fn update_events_org_admin() {
+        base::event_series::update_events(Roles::OrgAdmin, true);
+    }
+    #[test]
+    fn update_events_box_office() {
+        base::event_series::update_events(Roles::OrgBoxOffice, false);
+    }
+}
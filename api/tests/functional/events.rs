@@ -358,6 +358,69 @@ pub fn index_search_with_filter() {
     assert_eq!(body, expected_json);
 }
 
+#[test]
+pub fn index_with_facets() {
+    let database = TestDatabase::new();
+    let event_end = NaiveDate::from_ymd(2050, 7, 9).and_hms(9, 10, 11);
+    let event = database
+        .create_event()
+        .with_name("Jazz by the sea".to_string())
+        .with_event_end(event_end)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    database
+        .create_event()
+        .with_name("Jazz in the park".to_string())
+        .with_event_end(event_end)
+        .finish();
+    database
+        .create_event()
+        .with_name("Rock festival".to_string())
+        .with_event_end(event_end)
+        .finish();
+
+    let test_request = TestRequest::create_with_uri(
+        "/events?query=jazz&sort=relevance&facets=true&max_price_in_cents=1000000",
+    );
+    let parameters = Query::<SearchParameters>::extract(&test_request.request).unwrap();
+    let response: HttpResponse = events::index((
+        test_request.extract_state(),
+        database.connection.clone().into(),
+        parameters,
+        OptionalUser(None),
+    ))
+    .into();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let result: Value = serde_json::from_str(&body).unwrap();
+    let found_ids: Vec<Value> = result["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["id"].clone())
+        .collect();
+    assert_eq!(found_ids, vec![json!(event.id)]);
+    assert_eq!(result["facets"]["event_types"], json!({"Music": 1}));
+    assert_eq!(result["facets"]["availability"], json!({"Available": 1}));
+
+    // Facets are only calculated when requested
+    let test_request = TestRequest::create_with_uri("/events?query=jazz");
+    let parameters = Query::<SearchParameters>::extract(&test_request.request).unwrap();
+    let response: HttpResponse = events::index((
+        test_request.extract_state(),
+        database.connection.clone().into(),
+        parameters,
+        OptionalUser(None),
+    ))
+    .into();
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let result: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(result["data"].as_array().unwrap().len(), 2);
+    assert!(result.get("facets").is_none());
+}
+
 #[test]
 fn show() {
     let database = TestDatabase::new();
@@ -753,6 +816,74 @@ fn show_with_access_restricted_ticket_type_and_access_code() {
     assert_eq!(body, event_expected_json);
 }
 
+#[test]
+fn refund_failures() {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let event = database
+        .create_event()
+        .with_organization(&organization)
+        .with_ticket_pricing()
+        .finish();
+    let customer = database
+        .create_user()
+        .with_email("fan@example.com".to_string())
+        .finish();
+    let order = database
+        .create_order()
+        .for_user(&customer)
+        .for_event(&event)
+        .is_paid()
+        .finish();
+    let event = event.cancel(None, connection).unwrap();
+    let actions = DomainAction::find_by_main_table(
+        DomainActionTypes::RefundCancelledEventOrder,
+        Tables::Events.to_string(),
+        event.id,
+        connection,
+    )
+    .unwrap();
+    actions[0]
+        .set_errored("Card declined, contact issuer", connection)
+        .unwrap();
+    let auth_user =
+        support::create_auth_user_from_user(&user, Roles::OrgOwner, Some(&organization), &database);
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = event.id;
+
+    let response: HttpResponse =
+        events::refund_failures((database.connection.clone().into(), path, auth_user.clone()))
+            .into();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers().get("content-type").unwrap(), "text/csv");
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let lines: Vec<&str> = body.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(
+        lines[0],
+        "order_id,user_email,status,attempt_count,last_failure_reason,failed_at"
+    );
+    assert!(lines[1].starts_with(&format!(
+        "{},fan@example.com,Errored,0,\"Card declined, contact issuer\",",
+        order.id
+    )));
+
+    // Requeueing retries the failed refund
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = event.id;
+    let response: HttpResponse =
+        events::queue_refunds((database.connection.clone().into(), path, auth_user)).into();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let progress: EventRefundProgress = serde_json::from_str(&body).unwrap();
+    assert_eq!(progress.pending, 1);
+    assert_eq!(progress.failed, 0);
+}
+
 #[cfg(test)]
 mod show_box_office_pricing_tests {
     use super::*;
@@ -958,6 +1089,129 @@ mod cancel_tests {
     }
 }
 
+#[cfg(test)]
+mod clone_tests {
+    use super::*;
+    #[test]
+    fn clone_org_member() {
+        base::events::clone(Roles::OrgMember, true);
+    }
+    #[test]
+    fn clone_admin() {
+        base::events::clone(Roles::Admin, true);
+    }
+    #[test]
+    fn clone_user() {
+        base::events::clone(Roles::User, false);
+    }
+    #[test]
+    fn clone_org_owner() {
+        base::events::clone(Roles::OrgOwner, true);
+    }
+    #[test]
+    fn clone_door_person() {
+        base::events::clone(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn clone_promoter() {
+        base::events::clone(Roles::Promoter, true);
+    }
+    #[test]
+    fn clone_promoter_read_only() {
+        base::events::clone(Roles::PromoterReadOnly, false);
+    }
+    #[test]
+    fn clone_org_admin() {
+        base::events::clone(Roles::OrgAdmin, true);
+    }
+    #[test]
+    fn clone_box_office() {
+        base::events::clone(Roles::OrgBoxOffice, false);
+    }
+}
+
+#[cfg(test)]
+mod reschedule_tests {
+    use super::*;
+    #[test]
+    fn reschedule_org_member() {
+        base::events::reschedule(Roles::OrgMember, true);
+    }
+    #[test]
+    fn reschedule_admin() {
+        base::events::reschedule(Roles::Admin, true);
+    }
+    #[test]
+    fn reschedule_user() {
+        base::events::reschedule(Roles::User, false);
+    }
+    #[test]
+    fn reschedule_org_owner() {
+        base::events::reschedule(Roles::OrgOwner, true);
+    }
+    #[test]
+    fn reschedule_door_person() {
+        base::events::reschedule(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn reschedule_promoter() {
+        base::events::reschedule(Roles::Promoter, true);
+    }
+    #[test]
+    fn reschedule_promoter_read_only() {
+        base::events::reschedule(Roles::PromoterReadOnly, false);
+    }
+    #[test]
+    fn reschedule_org_admin() {
+        base::events::reschedule(Roles::OrgAdmin, true);
+    }
+    #[test]
+    fn reschedule_box_office() {
+        base::events::reschedule(Roles::OrgBoxOffice, false);
+    }
+}
+
+#[cfg(test)]
+mod refunds_tests {
+    use super::*;
+    #[test]
+    fn refunds_org_member() {
+        base::events::refunds(Roles::OrgMember, true);
+    }
+    #[test]
+    fn refunds_admin() {
+        base::events::refunds(Roles::Admin, true);
+    }
+    #[test]
+    fn refunds_user() {
+        base::events::refunds(Roles::User, false);
+    }
+    #[test]
+    fn refunds_org_owner() {
+        base::events::refunds(Roles::OrgOwner, true);
+    }
+    #[test]
+    fn refunds_door_person() {
+        base::events::refunds(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn refunds_promoter() {
+        base::events::refunds(Roles::Promoter, false);
+    }
+    #[test]
+    fn refunds_promoter_read_only() {
+        base::events::refunds(Roles::PromoterReadOnly, false);
+    }
+    #[test]
+    fn refunds_org_admin() {
+        base::events::refunds(Roles::OrgAdmin, true);
+    }
+    #[test]
+    fn refunds_box_office() {
+        base::events::refunds(Roles::OrgBoxOffice, false);
+    }
+}
+
 #[cfg(test)]
 mod add_artist_tests {
     use super::*;
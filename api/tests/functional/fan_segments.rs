@@ -0,0 +1,409 @@
+use actix_web::{http::StatusCode, FromRequest, HttpResponse, Path, Query};
+use bigneon_api::controllers::fan_segments::{self, *};
+use bigneon_api::extractors::*;
+use bigneon_api::models::PathParameters;
+use bigneon_db::models::*;
+use functional::base;
+use serde_json;
+use support;
+use support::database::TestDatabase;
+use support::test_request::TestRequest;
+
+#[cfg(test)]
+mod index_tests {
+    use super::*;
+    #[test]
+    fn index_org_member() {
+        base::fan_segments::index(Roles::OrgMember, true);
+    }
+    #[test]
+    fn index_admin() {
+        base::fan_segments::index(Roles::Admin, true);
+    }
+    #[test]
+    fn index_user() {
+        base::fan_segments::index(Roles::User, false);
+    }
+    #[test]
+    fn index_org_owner() {
+        base::fan_segments::index(Roles::OrgOwner, true);
+    }
+    #[test]
+    fn index_door_person() {
+        base::fan_segments::index(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn index_promoter() {
+        base::fan_segments::index(Roles::Promoter, false);
+    }
+    #[test]
+    fn index_promoter_read_only() {
+        base::fan_segments::index(Roles::PromoterReadOnly, false);
+    }
+    #[test]
+    fn index_org_admin() {
+        base::fan_segments::index(Roles::OrgAdmin, true);
+    }
+    #[test]
+    fn index_box_office() {
+        base::fan_segments::index(Roles::OrgBoxOffice, false);
+    }
+}
+
+#[cfg(test)]
+mod create_tests {
+    use super::*;
+    #[test]
+    fn create_org_member() {
+        base::fan_segments::create(Roles::OrgMember, true);
+    }
+    #[test]
+    fn create_admin() {
+        base::fan_segments::create(Roles::Admin, true);
+    }
+    #[test]
+    fn create_user() {
+        base::fan_segments::create(Roles::User, false);
+    }
+    #[test]
+    fn create_org_owner() {
+        base::fan_segments::create(Roles::OrgOwner, true);
+    }
+    #[test]
+    fn create_door_person() {
+        base::fan_segments::create(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn create_promoter() {
+        base::fan_segments::create(Roles::Promoter, false);
+    }
+    #[test]
+    fn create_promoter_read_only() {
+        base::fan_segments::create(Roles::PromoterReadOnly, false);
+    }
+    #[test]
+    fn create_org_admin() {
+        base::fan_segments::create(Roles::OrgAdmin, true);
+    }
+    #[test]
+    fn create_box_office() {
+        base::fan_segments::create(Roles::OrgBoxOffice, false);
+    }
+}
+
+#[cfg(test)]
+mod show_tests {
+    use super::*;
+    #[test]
+    fn show_org_member() {
+        base::fan_segments::show(Roles::OrgMember, true);
+    }
+    #[test]
+    fn show_admin() {
+        base::fan_segments::show(Roles::Admin, true);
+    }
+    #[test]
+    fn show_user() {
+        base::fan_segments::show(Roles::User, false);
+    }
+    #[test]
+    fn show_org_owner() {
+        base::fan_segments::show(Roles::OrgOwner, true);
+    }
+    #[test]
+    fn show_door_person() {
+        base::fan_segments::show(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn show_promoter() {
+        base::fan_segments::show(Roles::Promoter, false);
+    }
+    #[test]
+    fn show_promoter_read_only() {
+        base::fan_segments::show(Roles::PromoterReadOnly, false);
+    }
+    #[test]
+    fn show_org_admin() {
+        base::fan_segments::show(Roles::OrgAdmin, true);
+    }
+    #[test]
+    fn show_box_office() {
+        base::fan_segments::show(Roles::OrgBoxOffice, false);
+    }
+}
+
+#[cfg(test)]
+mod update_tests {
+    use super::*;
+    #[test]
+    fn update_org_member() {
+        base::fan_segments::update(Roles::OrgMember, true);
+    }
+    #[test]
+    fn update_admin() {
+        base::fan_segments::update(Roles::Admin, true);
+    }
+    #[test]
+    fn update_user() {
+        base::fan_segments::update(Roles::User, false);
+    }
+    #[test]
+    fn update_org_owner() {
+        base::fan_segments::update(Roles::OrgOwner, true);
+    }
+    #[test]
+    fn update_door_person() {
+        base::fan_segments::update(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn update_promoter() {
+        base::fan_segments::update(Roles::Promoter, false);
+    }
+    #[test]
+    fn update_promoter_read_only() {
+        base::fan_segments::update(Roles::PromoterReadOnly, false);
+    }
+    #[test]
+    fn update_org_admin() {
+        base::fan_segments::update(Roles::OrgAdmin, true);
+    }
+    #[test]
+    fn update_box_office() {
+        base::fan_segments::update(Roles::OrgBoxOffice, false);
+    }
+}
+
+#[cfg(test)]
+mod destroy_tests {
+    use super::*;
+    #[test]
+    fn destroy_org_member() {
+        base::fan_segments::destroy(Roles::OrgMember, true);
+    }
+    #[test]
+    fn destroy_admin() {
+        base::fan_segments::destroy(Roles::Admin, true);
+    }
+    #[test]
+    fn destroy_user() {
+        base::fan_segments::destroy(Roles::User, false);
+    }
+    #[test]
+    fn destroy_org_owner() {
+        base::fan_segments::destroy(Roles::OrgOwner, true);
+    }
+    #[test]
+    fn destroy_door_person() {
+        base::fan_segments::destroy(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn destroy_promoter() {
+        base::fan_segments::destroy(Roles::Promoter, false);
+    }
+    #[test]
+    fn destroy_promoter_read_only() {
+        base::fan_segments::destroy(Roles::PromoterReadOnly, false);
+    }
+    #[test]
+    fn destroy_org_admin() {
+        base::fan_segments::destroy(Roles::OrgAdmin, true);
+    }
+    #[test]
+    fn destroy_box_office() {
+        base::fan_segments::destroy(Roles::OrgBoxOffice, false);
+    }
+}
+
+#[cfg(test)]
+mod fans_tests {
+    use super::*;
+    #[test]
+    fn fans_org_member() {
+        base::fan_segments::fans(Roles::OrgMember, true);
+    }
+    #[test]
+    fn fans_admin() {
+        base::fan_segments::fans(Roles::Admin, true);
+    }
+    #[test]
+    fn fans_user() {
+        base::fan_segments::fans(Roles::User, false);
+    }
+    #[test]
+    fn fans_org_owner() {
+        base::fan_segments::fans(Roles::OrgOwner, true);
+    }
+    #[test]
+    fn fans_door_person() {
+        base::fan_segments::fans(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn fans_promoter() {
+        base::fan_segments::fans(Roles::Promoter, false);
+    }
+    #[test]
+    fn fans_promoter_read_only() {
+        base::fan_segments::fans(Roles::PromoterReadOnly, false);
+    }
+    #[test]
+    fn fans_org_admin() {
+        base::fan_segments::fans(Roles::OrgAdmin, true);
+    }
+    #[test]
+    fn fans_box_office() {
+        base::fan_segments::fans(Roles::OrgBoxOffice, false);
+    }
+}
+
+#[cfg(test)]
+mod export_tests {
+    use super::*;
+    #[test]
+    fn export_org_member() {
+        base::fan_segments::export(Roles::OrgMember, true);
+    }
+    #[test]
+    fn export_admin() {
+        base::fan_segments::export(Roles::Admin, true);
+    }
+    #[test]
+    fn export_user() {
+        base::fan_segments::export(Roles::User, false);
+    }
+    #[test]
+    fn export_org_owner() {
+        base::fan_segments::export(Roles::OrgOwner, true);
+    }
+    #[test]
+    fn export_door_person() {
+        base::fan_segments::export(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn export_promoter() {
+        base::fan_segments::export(Roles::Promoter, false);
+    }
+    #[test]
+    fn export_promoter_read_only() {
+        base::fan_segments::export(Roles::PromoterReadOnly, false);
+    }
+    #[test]
+    fn export_org_admin() {
+        base::fan_segments::export(Roles::OrgAdmin, true);
+    }
+    #[test]
+    fn export_box_office() {
+        base::fan_segments::export(Roles::OrgBoxOffice, false);
+    }
+}
+
+#[test]
+fn create() {
+    let database = TestDatabase::new();
+    let organization = database.create_organization().finish();
+    let auth_user = support::create_auth_user(Roles::OrgMember, Some(&organization), &database);
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = organization.id;
+    let filters = FanSegmentFilters {
+        min_revenue_in_cents: Some(5000),
+        ..Default::default()
+    };
+    let response: HttpResponse = fan_segments::create((
+        database.connection.clone().into(),
+        path,
+        Json(CreateFanSegmentRequest {
+            name: "Big spenders".to_string(),
+            filters: filters.clone(),
+        }),
+        auth_user,
+    ))
+    .into();
+
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let fan_segment: FanSegment = serde_json::from_str(&body).unwrap();
+    assert_eq!(fan_segment.organization_id, organization.id);
+    assert_eq!(fan_segment.parsed_filters().unwrap(), filters);
+}
+
+#[test]
+fn create_requires_organization_access() {
+    let database = TestDatabase::new();
+    let organization = database.create_organization().finish();
+    let auth_user = support::create_auth_user(Roles::User, None, &database);
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = organization.id;
+    let response: HttpResponse = fan_segments::create((
+        database.connection.clone().into(),
+        path,
+        Json(CreateFanSegmentRequest {
+            name: "Segment".to_string(),
+            filters: Default::default(),
+        }),
+        auth_user,
+    ))
+    .into();
+
+    support::expects_unauthorized(&response);
+}
+
+#[test]
+fn fans_and_export() {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let organization = database.create_organization().finish();
+    let event = database
+        .create_event()
+        .with_organization(&organization)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let fan = database
+        .create_user()
+        .with_first_name("Jane")
+        .with_last_name("Fan")
+        .finish();
+    database
+        .create_order()
+        .for_user(&fan)
+        .for_event(&event)
+        .is_paid()
+        .finish();
+    let auth_user = support::create_auth_user(Roles::OrgMember, Some(&organization), &database);
+    let fan_segment = FanSegment::create(
+        organization.id,
+        "Everyone".to_string(),
+        Default::default(),
+        auth_user.id(),
+    )
+    .commit(connection)
+    .unwrap();
+
+    let test_request = TestRequest::create_with_uri(&format!("/limits?"));
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = fan_segment.id;
+    let query_parameters = Query::<PagingParameters>::extract(&test_request.request).unwrap();
+    let response = fan_segments::fans((
+        database.connection.clone().into(),
+        path,
+        query_parameters,
+        auth_user.clone(),
+    ))
+    .unwrap();
+    assert_eq!(response.payload().paging.total, 1);
+    assert_eq!(response.payload().data[0].user_id, fan.id);
+
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = fan_segment.id;
+    let response: HttpResponse =
+        fan_segments::export((database.connection.clone().into(), path, auth_user)).into();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let lines: Vec<&str> = body.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("first_name,last_name,email"));
+    assert!(lines[1].starts_with(&format!("Jane,Fan,{}", fan.email.clone().unwrap())));
+}
@@ -0,0 +1,458 @@
+use actix_web::{http::StatusCode, FromRequest, HttpResponse, Path};
+use bigneon_api::controllers::fans::{self, *};
+use bigneon_api::extractors::*;
+use bigneon_api::models::{OrganizationFanPathParameters, PathParameters};
+use bigneon_db::models::*;
+use functional::base;
+use serde_json;
+use support;
+use support::database::TestDatabase;
+use support::test_request::TestRequest;
+
+#[cfg(test)]
+mod organization_tags_tests {
+    use super::*;
+    #[test]
+    fn organization_tags_org_member() {
+        base::fans::organization_tags(Roles::OrgMember, true);
+    }
+    #[test]
+    fn organization_tags_admin() {
+        base::fans::organization_tags(Roles::Admin, true);
+    }
+    #[test]
+    fn organization_tags_user() {
+        base::fans::organization_tags(Roles::User, false);
+    }
+    #[test]
+    fn organization_tags_org_owner() {
+        base::fans::organization_tags(Roles::OrgOwner, true);
+    }
+    #[test]
+    fn organization_tags_door_person() {
+        base::fans::organization_tags(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn organization_tags_promoter() {
+        base::fans::organization_tags(Roles::Promoter, false);
+    }
+    #[test]
+    fn organization_tags_promoter_read_only() {
+        base::fans::organization_tags(Roles::PromoterReadOnly, false);
+    }
+    #[test]
+    fn organization_tags_org_admin() {
+        base::fans::organization_tags(Roles::OrgAdmin, true);
+    }
+    #[test]
+    fn organization_tags_box_office() {
+        base::fans::organization_tags(Roles::OrgBoxOffice, false);
+    }
+}
+
+#[cfg(test)]
+mod tags_tests {
+    use super::*;
+    #[test]
+    fn tags_org_member() {
+        base::fans::tags(Roles::OrgMember, true);
+    }
+    #[test]
+    fn tags_admin() {
+        base::fans::tags(Roles::Admin, true);
+    }
+    #[test]
+    fn tags_user() {
+        base::fans::tags(Roles::User, false);
+    }
+    #[test]
+    fn tags_org_owner() {
+        base::fans::tags(Roles::OrgOwner, true);
+    }
+    #[test]
+    fn tags_door_person() {
+        base::fans::tags(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn tags_promoter() {
+        base::fans::tags(Roles::Promoter, false);
+    }
+    #[test]
+    fn tags_promoter_read_only() {
+        base::fans::tags(Roles::PromoterReadOnly, false);
+    }
+    #[test]
+    fn tags_org_admin() {
+        base::fans::tags(Roles::OrgAdmin, true);
+    }
+    #[test]
+    fn tags_box_office() {
+        base::fans::tags(Roles::OrgBoxOffice, false);
+    }
+}
+
+#[cfg(test)]
+mod add_tag_tests {
+    use super::*;
+    #[test]
+    fn add_tag_org_member() {
+        base::fans::add_tag(Roles::OrgMember, true);
+    }
+    #[test]
+    fn add_tag_admin() {
+        base::fans::add_tag(Roles::Admin, true);
+    }
+    #[test]
+    fn add_tag_user() {
+        base::fans::add_tag(Roles::User, false);
+    }
+    #[test]
+    fn add_tag_org_owner() {
+        base::fans::add_tag(Roles::OrgOwner, true);
+    }
+    #[test]
+    fn add_tag_door_person() {
+        base::fans::add_tag(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn add_tag_promoter() {
+        base::fans::add_tag(Roles::Promoter, false);
+    }
+    #[test]
+    fn add_tag_promoter_read_only() {
+        base::fans::add_tag(Roles::PromoterReadOnly, false);
+    }
+    #[test]
+    fn add_tag_org_admin() {
+        base::fans::add_tag(Roles::OrgAdmin, true);
+    }
+    #[test]
+    fn add_tag_box_office() {
+        base::fans::add_tag(Roles::OrgBoxOffice, false);
+    }
+}
+
+#[cfg(test)]
+mod remove_tag_tests {
+    use super::*;
+    #[test]
+    fn remove_tag_org_member() {
+        base::fans::remove_tag(Roles::OrgMember, true);
+    }
+    #[test]
+    fn remove_tag_admin() {
+        base::fans::remove_tag(Roles::Admin, true);
+    }
+    #[test]
+    fn remove_tag_user() {
+        base::fans::remove_tag(Roles::User, false);
+    }
+    #[test]
+    fn remove_tag_org_owner() {
+        base::fans::remove_tag(Roles::OrgOwner, true);
+    }
+    #[test]
+    fn remove_tag_door_person() {
+        base::fans::remove_tag(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn remove_tag_promoter() {
+        base::fans::remove_tag(Roles::Promoter, false);
+    }
+    #[test]
+    fn remove_tag_promoter_read_only() {
+        base::fans::remove_tag(Roles::PromoterReadOnly, false);
+    }
+    #[test]
+    fn remove_tag_org_admin() {
+        base::fans::remove_tag(Roles::OrgAdmin, true);
+    }
+    #[test]
+    fn remove_tag_box_office() {
+        base::fans::remove_tag(Roles::OrgBoxOffice, false);
+    }
+}
+
+#[cfg(test)]
+mod notes_tests {
+    use super::*;
+    #[test]
+    fn notes_org_member() {
+        base::fans::notes(Roles::OrgMember, true);
+    }
+    #[test]
+    fn notes_admin() {
+        base::fans::notes(Roles::Admin, true);
+    }
+    #[test]
+    fn notes_user() {
+        base::fans::notes(Roles::User, false);
+    }
+    #[test]
+    fn notes_org_owner() {
+        base::fans::notes(Roles::OrgOwner, true);
+    }
+    #[test]
+    fn notes_door_person() {
+        base::fans::notes(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn notes_promoter() {
+        base::fans::notes(Roles::Promoter, false);
+    }
+    #[test]
+    fn notes_promoter_read_only() {
+        base::fans::notes(Roles::PromoterReadOnly, false);
+    }
+    #[test]
+    fn notes_org_admin() {
+        base::fans::notes(Roles::OrgAdmin, true);
+    }
+    #[test]
+    fn notes_box_office() {
+        base::fans::notes(Roles::OrgBoxOffice, false);
+    }
+}
+
+#[cfg(test)]
+mod add_note_tests {
+    use super::*;
+    #[test]
+    fn add_note_org_member() {
+        base::fans::add_note(Roles::OrgMember, true);
+    }
+    #[test]
+    fn add_note_admin() {
+        base::fans::add_note(Roles::Admin, true);
+    }
+    #[test]
+    fn add_note_user() {
+        base::fans::add_note(Roles::User, false);
+    }
+    #[test]
+    fn add_note_org_owner() {
+        base::fans::add_note(Roles::OrgOwner, true);
+    }
+    #[test]
+    fn add_note_door_person() {
+        base::fans::add_note(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn add_note_promoter() {
+        base::fans::add_note(Roles::Promoter, false);
+    }
+    #[test]
+    fn add_note_promoter_read_only() {
+        base::fans::add_note(Roles::PromoterReadOnly, false);
+    }
+    #[test]
+    fn add_note_org_admin() {
+        base::fans::add_note(Roles::OrgAdmin, true);
+    }
+    #[test]
+    fn add_note_box_office() {
+        base::fans::add_note(Roles::OrgBoxOffice, false);
+    }
+}
+
+#[cfg(test)]
+mod update_note_tests {
+    use super::*;
+    #[test]
+    fn update_note_org_member() {
+        base::fans::update_note(Roles::OrgMember, true);
+    }
+    #[test]
+    fn update_note_admin() {
+        base::fans::update_note(Roles::Admin, true);
+    }
+    #[test]
+    fn update_note_user() {
+        base::fans::update_note(Roles::User, false);
+    }
+    #[test]
+    fn update_note_org_owner() {
+        base::fans::update_note(Roles::OrgOwner, true);
+    }
+    #[test]
+    fn update_note_door_person() {
+        base::fans::update_note(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn update_note_promoter() {
+        base::fans::update_note(Roles::Promoter, false);
+    }
+    #[test]
+    fn update_note_promoter_read_only() {
+        base::fans::update_note(Roles::PromoterReadOnly, false);
+    }
+    #[test]
+    fn update_note_org_admin() {
+        base::fans::update_note(Roles::OrgAdmin, true);
+    }
+    #[test]
+    fn update_note_box_office() {
+        base::fans::update_note(Roles::OrgBoxOffice, false);
+    }
+}
+
+#[cfg(test)]
+mod remove_note_tests {
+    use super::*;
+    #[test]
+    fn remove_note_org_member() {
+        base::fans::remove_note(Roles::OrgMember, true);
+    }
+    #[test]
+    fn remove_note_admin() {
+        base::fans::remove_note(Roles::Admin, true);
+    }
+    #[test]
+    fn remove_note_user() {
+        base::fans::remove_note(Roles::User, false);
+    }
+    #[test]
+    fn remove_note_org_owner() {
+        base::fans::remove_note(Roles::OrgOwner, true);
+    }
+    #[test]
+    fn remove_note_door_person() {
+        base::fans::remove_note(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn remove_note_promoter() {
+        base::fans::remove_note(Roles::Promoter, false);
+    }
+    #[test]
+    fn remove_note_promoter_read_only() {
+        base::fans::remove_note(Roles::PromoterReadOnly, false);
+    }
+    #[test]
+    fn remove_note_org_admin() {
+        base::fans::remove_note(Roles::OrgAdmin, true);
+    }
+    #[test]
+    fn remove_note_box_office() {
+        base::fans::remove_note(Roles::OrgBoxOffice, false);
+    }
+}
+
+#[test]
+fn add_tag() {
+    let database = TestDatabase::new();
+    let organization = database.create_organization().finish();
+    let event = database
+        .create_event()
+        .with_organization(&organization)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let fan = database.create_user().finish();
+    database
+        .create_order()
+        .for_user(&fan)
+        .for_event(&event)
+        .is_paid()
+        .finish();
+    let auth_user = support::create_auth_user(Roles::OrgMember, Some(&organization), &database);
+
+    let test_request = TestRequest::create_with_uri_custom_params("/", vec!["id", "user_id"]);
+    let mut path = Path::<OrganizationFanPathParameters>::extract(&test_request.request).unwrap();
+    path.id = organization.id;
+    path.user_id = fan.id;
+    let response: HttpResponse = fans::add_tag((
+        database.connection.clone().into(),
+        path,
+        Json(AddFanTagRequest {
+            name: "VIP".to_string(),
+        }),
+        auth_user.clone(),
+    ))
+    .into();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let tag: FanTag = serde_json::from_str(&body).unwrap();
+    assert_eq!(tag.name, "VIP");
+    assert_eq!(tag.user_id, fan.id);
+    assert_eq!(tag.created_by_user_id, auth_user.id());
+
+    let mut path = Path::<OrganizationFanPathParameters>::extract(&test_request.request).unwrap();
+    path.id = organization.id;
+    path.user_id = fan.id;
+    let response: HttpResponse =
+        fans::tags((database.connection.clone().into(), path, auth_user.clone())).into();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let tags: Vec<FanTag> = serde_json::from_str(&body).unwrap();
+    assert_eq!(tags, vec![tag.clone()]);
+
+    let mut path = Path::<PathParameters>::extract(&TestRequest::create().request).unwrap();
+    path.id = tag.id;
+    let response: HttpResponse =
+        fans::remove_tag((database.connection.clone().into(), path, auth_user)).into();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+}
+
+#[test]
+fn add_tag_for_user_who_is_not_a_fan() {
+    let database = TestDatabase::new();
+    let organization = database.create_organization().finish();
+    let user = database.create_user().finish();
+    let auth_user = support::create_auth_user(Roles::OrgMember, Some(&organization), &database);
+
+    let test_request = TestRequest::create_with_uri_custom_params("/", vec!["id", "user_id"]);
+    let mut path = Path::<OrganizationFanPathParameters>::extract(&test_request.request).unwrap();
+    path.id = organization.id;
+    path.user_id = user.id;
+    let response: HttpResponse = fans::add_tag((
+        database.connection.clone().into(),
+        path,
+        Json(AddFanTagRequest {
+            name: "VIP".to_string(),
+        }),
+        auth_user,
+    ))
+    .into();
+    support::expects_forbidden(&response, Some("Fan does not belong to this organization"));
+}
+
+#[test]
+fn add_note() {
+    let database = TestDatabase::new();
+    let organization = database.create_organization().finish();
+    let event = database
+        .create_event()
+        .with_organization(&organization)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let fan = database.create_user().finish();
+    database
+        .create_order()
+        .for_user(&fan)
+        .for_event(&event)
+        .is_paid()
+        .finish();
+    let auth_user = support::create_auth_user(Roles::OrgMember, Some(&organization), &database);
+
+    let test_request = TestRequest::create_with_uri_custom_params("/", vec!["id", "user_id"]);
+    let mut path = Path::<OrganizationFanPathParameters>::extract(&test_request.request).unwrap();
+    path.id = organization.id;
+    path.user_id = fan.id;
+    let response: HttpResponse = fans::add_note((
+        database.connection.clone().into(),
+        path,
+        Json(FanNoteRequest {
+            note: "Regular at the bar".to_string(),
+        }),
+        auth_user,
+    ))
+    .into();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let note: FanNote = serde_json::from_str(&body).unwrap();
+    assert_eq!(
+        FanNote::find_for_fan(organization.id, fan.id, database.connection.get()).unwrap(),
+        vec![note]
+    );
+}
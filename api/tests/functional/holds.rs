@@ -91,6 +91,47 @@ mod update_tests {
     }
 }
 
+#[cfg(test)]
+mod create_release_tests {
+    use super::*;
+    #[test]
+    fn create_release_org_member() {
+        base::holds::create_release(Roles::OrgMember, true);
+    }
+    #[test]
+    fn create_release_admin() {
+        base::holds::create_release(Roles::Admin, true);
+    }
+    #[test]
+    fn create_release_user() {
+        base::holds::create_release(Roles::User, false);
+    }
+    #[test]
+    fn create_release_org_owner() {
+        base::holds::create_release(Roles::OrgOwner, true);
+    }
+    #[test]
+    fn create_release_door_person() {
+        base::holds::create_release(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn create_release_promoter() {
+        base::holds::create_release(Roles::Promoter, true);
+    }
+    #[test]
+    fn create_release_promoter_read_only() {
+        base::holds::create_release(Roles::PromoterReadOnly, false);
+    }
+    #[test]
+    fn create_release_org_admin() {
+        base::holds::create_release(Roles::OrgAdmin, true);
+    }
+    #[test]
+    fn create_release_box_office() {
+        base::holds::create_release(Roles::OrgBoxOffice, false);
+    }
+}
+
 #[test]
 fn create_with_validation_errors() {
     let database = TestDatabase::new();
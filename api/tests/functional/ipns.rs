@@ -0,0 +1,106 @@
+use actix_web::{http::StatusCode, FromRequest, HttpResponse, Query};
+use bigneon_api::controllers::ipns::{self, SendgridEvent, SendgridWebhookParameters};
+use bigneon_api::extractors::*;
+use bigneon_db::prelude::*;
+use support::database::TestDatabase;
+use support::test_request::TestRequest;
+
+fn sendgrid_event(email: &str, event: &str, bounce_type: Option<&str>) -> SendgridEvent {
+    SendgridEvent {
+        email: email.to_string(),
+        event: event.to_string(),
+        reason: None,
+        bounce_type: bounce_type.map(|t| t.to_string()),
+    }
+}
+
+#[test]
+fn sendgrid() {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    EmailSuppression::create(
+        "resubscribed@tari.com",
+        EmailSuppressionReasons::Unsubscribe,
+        "SendGrid",
+        None,
+    )
+    .commit(connection)
+    .unwrap();
+    for reason in vec![
+        EmailSuppressionReasons::Bounce,
+        EmailSuppressionReasons::Unsubscribe,
+    ] {
+        EmailSuppression::create("bounced-resubscribed@tari.com", reason, "SendGrid", None)
+            .commit(connection)
+            .unwrap();
+    }
+
+    let test_request = TestRequest::create_with_uri("/ipns/sendgrid?token=test_webhook_token");
+    let query = Query::<SendgridWebhookParameters>::extract(&test_request.request).unwrap();
+    let json = Json(vec![
+        sendgrid_event("Bounced@tari.com", "bounce", Some("bounce")),
+        sendgrid_event("blocked@tari.com", "bounce", Some("blocked")),
+        sendgrid_event("spam@tari.com", "spamreport", None),
+        sendgrid_event("unsubscribed@tari.com", "group_unsubscribe", None),
+        sendgrid_event("delivered@tari.com", "delivered", None),
+        sendgrid_event("resubscribed@tari.com", "group_resubscribe", None),
+        sendgrid_event("bounced-resubscribed@tari.com", "group_resubscribe", None),
+    ]);
+    let response: HttpResponse = ipns::sendgrid((
+        test_request.extract_state(),
+        query,
+        json,
+        database.connection.clone().into(),
+    ))
+    .into();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let reasons = |email: &str| {
+        EmailSuppression::find_by_email(email, connection)
+            .unwrap()
+            .into_iter()
+            .map(|s| s.reason)
+            .collect::<Vec<EmailSuppressionReasons>>()
+    };
+    assert_eq!(
+        reasons("bounced@tari.com"),
+        vec![EmailSuppressionReasons::Bounce]
+    );
+    assert!(reasons("blocked@tari.com").is_empty());
+    assert_eq!(
+        reasons("spam@tari.com"),
+        vec![EmailSuppressionReasons::SpamReport]
+    );
+    assert_eq!(
+        reasons("unsubscribed@tari.com"),
+        vec![EmailSuppressionReasons::Unsubscribe]
+    );
+    assert!(reasons("delivered@tari.com").is_empty());
+    assert!(reasons("resubscribed@tari.com").is_empty());
+    // Resubscribing only lifts the unsubscribe
+    assert_eq!(
+        reasons("bounced-resubscribed@tari.com"),
+        vec![EmailSuppressionReasons::Bounce]
+    );
+}
+
+#[test]
+fn sendgrid_with_invalid_token() {
+    let database = TestDatabase::new();
+    let test_request = TestRequest::create_with_uri("/ipns/sendgrid?token=wrong");
+    let query = Query::<SendgridWebhookParameters>::extract(&test_request.request).unwrap();
+    let json = Json(vec![sendgrid_event("spam@tari.com", "spamreport", None)]);
+    let response: HttpResponse = ipns::sendgrid((
+        test_request.extract_state(),
+        query,
+        json,
+        database.connection.clone().into(),
+    ))
+    .into();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert!(
+        EmailSuppression::find_by_email("spam@tari.com", database.connection.get())
+            .unwrap()
+            .is_empty()
+    );
+}
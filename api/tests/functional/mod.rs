@@ -4,19 +4,26 @@ mod base;
 mod cart;
 mod codes;
 mod comps;
+mod event_series;
 mod events;
+mod fan_segments;
+mod fans;
 mod holds;
+mod ipns;
 mod orders;
 mod organization_invites;
 mod organizations;
 mod password_resets;
 mod payment_methods;
 mod redemption_codes;
+mod refund_requests;
 mod regions;
 mod reports;
 mod stages;
 mod ticket_types;
 mod tickets;
+mod unique_codes;
 mod user_invites;
 mod users;
 mod venues;
+mod wallets;
@@ -2,6 +2,7 @@ use std::collections::HashMap;
 
 use actix_web::{http::StatusCode, FromRequest, HttpResponse, Path, Query};
 use chrono::prelude::*;
+use chrono::Duration;
 use diesel;
 use diesel::prelude::*;
 use serde_json;
@@ -427,6 +428,128 @@ pub fn refund_for_non_refundable_tickets() {
     assert_eq!(event_fee_item.refunded_quantity, 0);
 }
 
+#[test]
+pub fn refund_own_tickets_during_refund_window() {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let organization = database.create_organization().finish();
+    let event = database
+        .create_event()
+        .with_organization(&organization)
+        .with_event_start(Utc::now().naive_utc() + Duration::days(10))
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let event = Event::find(event.id, connection).unwrap();
+    let user = database.create_user().finish();
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
+    let ticket_type = &event.ticket_types(true, None, connection).unwrap()[0];
+    cart.update_quantities(
+        user.id,
+        &[UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 2,
+            redemption_code: None,
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+    let total = cart.calculate_total(connection).unwrap();
+    cart.add_external_payment(
+        Some("Test".to_string()),
+        ExternalPaymentType::CreditCard,
+        user.id,
+        total,
+        connection,
+    )
+    .unwrap();
+
+    let items = cart.items(&connection).unwrap();
+    let order_item = items
+        .iter()
+        .find(|i| i.ticket_type_id == Some(ticket_type.id))
+        .unwrap();
+    let tickets = TicketInstance::find_for_order_item(order_item.id, connection).unwrap();
+    let refund_items = vec![RefundItem {
+        order_item_id: order_item.id,
+        ticket_instance_id: Some(tickets[0].id),
+    }];
+
+    // Fans cannot refund their own tickets normally
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = cart.id;
+    let response: HttpResponse = orders::refund((
+        database.connection.clone(),
+        path,
+        Json(RefundAttributes {
+            items: refund_items.clone(),
+        }),
+        auth_user.clone(),
+        test_request.extract_state(),
+    ))
+    .into();
+    support::expects_unauthorized(&response);
+
+    // Rescheduling the event opens a refund window
+    event
+        .reschedule(
+            None,
+            event.event_start.unwrap() + Duration::days(7),
+            Some(Utc::now().naive_utc() + Duration::days(5)),
+            connection,
+        )
+        .unwrap();
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = cart.id;
+    let response: HttpResponse = orders::refund((
+        database.connection.clone(),
+        path,
+        Json(RefundAttributes {
+            items: refund_items,
+        }),
+        auth_user.clone(),
+        test_request.extract_state(),
+    ))
+    .into();
+    assert_eq!(response.status(), StatusCode::OK);
+    let order_item = OrderItem::find_in_order(cart.id, order_item.id, connection).unwrap();
+    assert_eq!(order_item.refunded_quantity, 1);
+
+    // Redeemed tickets cannot be refunded by their holder
+    TicketInstance::redeem_ticket(
+        tickets[1].id,
+        tickets[1].redeem_key.clone().unwrap(),
+        user.id,
+        connection,
+    )
+    .unwrap();
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = cart.id;
+    let response: HttpResponse = orders::refund((
+        database.connection.clone(),
+        path,
+        Json(RefundAttributes {
+            items: vec![RefundItem {
+                order_item_id: order_item.id,
+                ticket_instance_id: Some(tickets[1].id),
+            }],
+        }),
+        auth_user,
+        test_request.extract_state(),
+    ))
+    .into();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let order_item = OrderItem::find_in_order(cart.id, order_item.id, connection).unwrap();
+    assert_eq!(order_item.refunded_quantity, 1);
+}
+
 #[test]
 pub fn refund_hold_ticket() {
     let database = TestDatabase::new();
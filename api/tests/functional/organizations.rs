@@ -529,3 +529,225 @@ mod add_fee_schedule_tests {
         organizations::add_fee_schedule(Roles::OrgBoxOffice, false);
     }
 }
+
+#[cfg(test)]
+mod update_email_settings_tests {
+    use super::*;
+    #[test]
+    fn update_email_settings_org_member() {
+        organizations::update_email_settings(Roles::OrgMember, false);
+    }
+    #[test]
+    fn update_email_settings_admin() {
+        organizations::update_email_settings(Roles::Admin, true);
+    }
+    #[test]
+    fn update_email_settings_user() {
+        organizations::update_email_settings(Roles::User, false);
+    }
+    #[test]
+    fn update_email_settings_org_owner() {
+        organizations::update_email_settings(Roles::OrgOwner, true);
+    }
+    #[test]
+    fn update_email_settings_door_person() {
+        organizations::update_email_settings(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn update_email_settings_promoter() {
+        organizations::update_email_settings(Roles::Promoter, false);
+    }
+    #[test]
+    fn update_email_settings_promoter_read_only() {
+        organizations::update_email_settings(Roles::PromoterReadOnly, false);
+    }
+    #[test]
+    fn update_email_settings_org_admin() {
+        organizations::update_email_settings(Roles::OrgAdmin, true);
+    }
+    #[test]
+    fn update_email_settings_box_office() {
+        organizations::update_email_settings(Roles::OrgBoxOffice, false);
+    }
+}
+
+#[cfg(test)]
+mod preview_email_tests {
+    use super::*;
+    #[test]
+    fn preview_email_org_member() {
+        organizations::preview_email_unauthorized(Roles::OrgMember);
+    }
+    #[test]
+    fn preview_email_user() {
+        organizations::preview_email_unauthorized(Roles::User);
+    }
+    #[test]
+    fn preview_email_door_person() {
+        organizations::preview_email_unauthorized(Roles::DoorPerson);
+    }
+    #[test]
+    fn preview_email_promoter() {
+        organizations::preview_email_unauthorized(Roles::Promoter);
+    }
+    #[test]
+    fn preview_email_promoter_read_only() {
+        organizations::preview_email_unauthorized(Roles::PromoterReadOnly);
+    }
+    #[test]
+    fn preview_email_box_office() {
+        organizations::preview_email_unauthorized(Roles::OrgBoxOffice);
+    }
+}
+
+#[cfg(test)]
+mod audit_log_tests {
+    use super::*;
+    #[test]
+    fn audit_log_org_member() {
+        organizations::audit_log(Roles::OrgMember, false);
+    }
+    #[test]
+    fn audit_log_admin() {
+        organizations::audit_log(Roles::Admin, true);
+    }
+    #[test]
+    fn audit_log_user() {
+        organizations::audit_log(Roles::User, false);
+    }
+    #[test]
+    fn audit_log_org_owner() {
+        organizations::audit_log(Roles::OrgOwner, false);
+    }
+    #[test]
+    fn audit_log_door_person() {
+        organizations::audit_log(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn audit_log_promoter() {
+        organizations::audit_log(Roles::Promoter, false);
+    }
+    #[test]
+    fn audit_log_promoter_read_only() {
+        organizations::audit_log(Roles::PromoterReadOnly, false);
+    }
+    #[test]
+    fn audit_log_org_admin() {
+        organizations::audit_log(Roles::OrgAdmin, false);
+    }
+    #[test]
+    fn audit_log_box_office() {
+        organizations::audit_log(Roles::OrgBoxOffice, false);
+    }
+}
+
+#[cfg(test)]
+mod show_stripe_connect_tests {
+    use super::*;
+    #[test]
+    fn show_stripe_connect_org_member() {
+        organizations::show_stripe_connect(Roles::OrgMember, false);
+    }
+    #[test]
+    fn show_stripe_connect_admin() {
+        organizations::show_stripe_connect(Roles::Admin, true);
+    }
+    #[test]
+    fn show_stripe_connect_user() {
+        organizations::show_stripe_connect(Roles::User, false);
+    }
+    #[test]
+    fn show_stripe_connect_org_owner() {
+        organizations::show_stripe_connect(Roles::OrgOwner, true);
+    }
+    #[test]
+    fn show_stripe_connect_door_person() {
+        organizations::show_stripe_connect(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn show_stripe_connect_promoter() {
+        organizations::show_stripe_connect(Roles::Promoter, false);
+    }
+    #[test]
+    fn show_stripe_connect_promoter_read_only() {
+        organizations::show_stripe_connect(Roles::PromoterReadOnly, false);
+    }
+    #[test]
+    fn show_stripe_connect_org_admin() {
+        organizations::show_stripe_connect(Roles::OrgAdmin, true);
+    }
+    #[test]
+    fn show_stripe_connect_box_office() {
+        organizations::show_stripe_connect(Roles::OrgBoxOffice, false);
+    }
+}
+
+#[cfg(test)]
+mod create_stripe_connect_link_tests {
+    use super::*;
+    #[test]
+    fn create_stripe_connect_link_org_member() {
+        organizations::create_stripe_connect_link_unauthorized(Roles::OrgMember);
+    }
+    #[test]
+    fn create_stripe_connect_link_user() {
+        organizations::create_stripe_connect_link_unauthorized(Roles::User);
+    }
+    #[test]
+    fn create_stripe_connect_link_door_person() {
+        organizations::create_stripe_connect_link_unauthorized(Roles::DoorPerson);
+    }
+    #[test]
+    fn create_stripe_connect_link_promoter() {
+        organizations::create_stripe_connect_link_unauthorized(Roles::Promoter);
+    }
+    #[test]
+    fn create_stripe_connect_link_promoter_read_only() {
+        organizations::create_stripe_connect_link_unauthorized(Roles::PromoterReadOnly);
+    }
+    #[test]
+    fn create_stripe_connect_link_box_office() {
+        organizations::create_stripe_connect_link_unauthorized(Roles::OrgBoxOffice);
+    }
+}
+
+#[cfg(test)]
+mod stripe_connect_balance_transactions_tests {
+    use super::*;
+    #[test]
+    fn stripe_connect_balance_transactions_org_member() {
+        organizations::stripe_connect_balance_transactions(Roles::OrgMember, false);
+    }
+    #[test]
+    fn stripe_connect_balance_transactions_admin() {
+        organizations::stripe_connect_balance_transactions(Roles::Admin, true);
+    }
+    #[test]
+    fn stripe_connect_balance_transactions_user() {
+        organizations::stripe_connect_balance_transactions(Roles::User, false);
+    }
+    #[test]
+    fn stripe_connect_balance_transactions_org_owner() {
+        organizations::stripe_connect_balance_transactions(Roles::OrgOwner, false);
+    }
+    #[test]
+    fn stripe_connect_balance_transactions_door_person() {
+        organizations::stripe_connect_balance_transactions(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn stripe_connect_balance_transactions_promoter() {
+        organizations::stripe_connect_balance_transactions(Roles::Promoter, false);
+    }
+    #[test]
+    fn stripe_connect_balance_transactions_promoter_read_only() {
+        organizations::stripe_connect_balance_transactions(Roles::PromoterReadOnly, false);
+    }
+    #[test]
+    fn stripe_connect_balance_transactions_org_admin() {
+        organizations::stripe_connect_balance_transactions(Roles::OrgAdmin, false);
+    }
+    #[test]
+    fn stripe_connect_balance_transactions_box_office() {
+        organizations::stripe_connect_balance_transactions(Roles::OrgBoxOffice, false);
+    }
+}
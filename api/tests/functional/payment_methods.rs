@@ -1,9 +1,11 @@
-use actix_web::{http::StatusCode, HttpResponse};
+use actix_web::{http::StatusCode, FromRequest, HttpResponse, Path};
 use bigneon_api::controllers::payment_methods;
-use bigneon_db::models::{DisplayPaymentMethod, PaymentProviders, Roles};
+use bigneon_api::models::PathParameters;
+use bigneon_db::models::{DisplayPaymentMethod, PaymentMethod, PaymentProviders, Roles};
 use serde_json;
 use support;
 use support::database::TestDatabase;
+use support::test_request::TestRequest;
 
 #[test]
 fn index() {
@@ -31,3 +33,78 @@ fn index() {
     let body = support::unwrap_body_to_string(&response).unwrap();
     assert_eq!(body, payment_methods_expected_json);
 }
+
+#[test]
+fn make_default() {
+    let database = TestDatabase::new();
+    let user = database.create_user().finish();
+    let payment_method = database
+        .create_payment_method()
+        .with_user(&user)
+        .make_default()
+        .finish();
+    let payment_method2 = database.create_payment_method().with_user(&user).finish();
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = payment_method2.id;
+    let response: HttpResponse =
+        payment_methods::make_default((database.connection.clone().into(), path, auth_user)).into();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let display_payment_method: DisplayPaymentMethod = serde_json::from_str(&body).unwrap();
+    assert_eq!(display_payment_method.id, payment_method2.id);
+    assert!(display_payment_method.is_default);
+
+    let connection = database.connection.get();
+    assert!(
+        !PaymentMethod::find(payment_method.id, connection)
+            .unwrap()
+            .is_default
+    );
+}
+
+#[test]
+fn make_default_for_other_user() {
+    let database = TestDatabase::new();
+    let user = database.create_user().finish();
+    let payment_method = database.create_payment_method().finish();
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = payment_method.id;
+    let response: HttpResponse =
+        payment_methods::make_default((database.connection.into(), path, auth_user)).into();
+
+    support::expects_forbidden(
+        &response,
+        Some("This payment method does not belong to you"),
+    );
+}
+
+#[test]
+fn destroy() {
+    let database = TestDatabase::new();
+    let user = database.create_user().finish();
+    let payment_method = database
+        .create_payment_method()
+        .with_name(PaymentProviders::External)
+        .with_user(&user)
+        .finish();
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+
+    let test_request = TestRequest::create();
+    let state = test_request.extract_state();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = payment_method.id;
+    let response: HttpResponse =
+        payment_methods::destroy((database.connection.clone().into(), path, auth_user, state))
+            .into();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let connection = database.connection.get();
+    assert!(PaymentMethod::find(payment_method.id, connection).is_err());
+}
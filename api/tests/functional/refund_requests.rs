@@ -0,0 +1,357 @@
+use actix_web::{http::StatusCode, FromRequest, HttpResponse, Path};
+use bigneon_api::controllers::refund_requests::{self, *};
+use bigneon_api::extractors::*;
+use bigneon_api::models::PathParameters;
+use bigneon_db::models::*;
+use chrono::prelude::*;
+use chrono::Duration;
+use functional::base;
+use serde_json;
+use support;
+use support::database::TestDatabase;
+use support::test_request::TestRequest;
+
+#[cfg(test)]
+mod index_tests {
+    use super::*;
+    #[test]
+    fn index_org_member() {
+        base::refund_requests::index(Roles::OrgMember, true);
+    }
+    #[test]
+    fn index_admin() {
+        base::refund_requests::index(Roles::Admin, true);
+    }
+    #[test]
+    fn index_user() {
+        base::refund_requests::index(Roles::User, false);
+    }
+    #[test]
+    fn index_org_owner() {
+        base::refund_requests::index(Roles::OrgOwner, true);
+    }
+    #[test]
+    fn index_door_person() {
+        base::refund_requests::index(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn index_promoter() {
+        base::refund_requests::index(Roles::Promoter, false);
+    }
+    #[test]
+    fn index_promoter_read_only() {
+        base::refund_requests::index(Roles::PromoterReadOnly, false);
+    }
+    #[test]
+    fn index_org_admin() {
+        base::refund_requests::index(Roles::OrgAdmin, true);
+    }
+    #[test]
+    fn index_box_office() {
+        base::refund_requests::index(Roles::OrgBoxOffice, false);
+    }
+}
+
+#[cfg(test)]
+mod approve_tests {
+    use super::*;
+    #[test]
+    fn approve_org_member() {
+        base::refund_requests::approve(Roles::OrgMember, true);
+    }
+    #[test]
+    fn approve_admin() {
+        base::refund_requests::approve(Roles::Admin, true);
+    }
+    #[test]
+    fn approve_user() {
+        base::refund_requests::approve(Roles::User, false);
+    }
+    #[test]
+    fn approve_org_owner() {
+        base::refund_requests::approve(Roles::OrgOwner, true);
+    }
+    #[test]
+    fn approve_door_person() {
+        base::refund_requests::approve(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn approve_promoter() {
+        base::refund_requests::approve(Roles::Promoter, false);
+    }
+    #[test]
+    fn approve_promoter_read_only() {
+        base::refund_requests::approve(Roles::PromoterReadOnly, false);
+    }
+    #[test]
+    fn approve_org_admin() {
+        base::refund_requests::approve(Roles::OrgAdmin, true);
+    }
+    #[test]
+    fn approve_box_office() {
+        base::refund_requests::approve(Roles::OrgBoxOffice, false);
+    }
+}
+
+#[cfg(test)]
+mod deny_tests {
+    use super::*;
+    #[test]
+    fn deny_org_member() {
+        base::refund_requests::deny(Roles::OrgMember, true);
+    }
+    #[test]
+    fn deny_admin() {
+        base::refund_requests::deny(Roles::Admin, true);
+    }
+    #[test]
+    fn deny_user() {
+        base::refund_requests::deny(Roles::User, false);
+    }
+    #[test]
+    fn deny_org_owner() {
+        base::refund_requests::deny(Roles::OrgOwner, true);
+    }
+    #[test]
+    fn deny_door_person() {
+        base::refund_requests::deny(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn deny_promoter() {
+        base::refund_requests::deny(Roles::Promoter, false);
+    }
+    #[test]
+    fn deny_promoter_read_only() {
+        base::refund_requests::deny(Roles::PromoterReadOnly, false);
+    }
+    #[test]
+    fn deny_org_admin() {
+        base::refund_requests::deny(Roles::OrgAdmin, true);
+    }
+    #[test]
+    fn deny_box_office() {
+        base::refund_requests::deny(Roles::OrgBoxOffice, false);
+    }
+}
+
+#[test]
+pub fn create() {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let event = database.create_event().with_ticket_pricing().finish();
+    let ticket_type = &event.ticket_types(true, None, connection).unwrap()[0];
+    let user = database.create_user().finish();
+    let order = database
+        .create_order()
+        .for_user(&user)
+        .for_event(&event)
+        .quantity(2)
+        .is_paid()
+        .finish();
+    let tickets = order.tickets(ticket_type.id, connection).unwrap();
+
+    // Only the owner of the order can request a refund
+    let other_user = database.create_user().finish();
+    let auth_user = support::create_auth_user_from_user(&other_user, Roles::User, None, &database);
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = order.id;
+    let response: HttpResponse = refund_requests::create((
+        database.connection.clone(),
+        path,
+        Json(CreateRefundRequestRequest {
+            ticket_instance_ids: vec![tickets[0].id],
+            reason: "Can no longer attend".to_string(),
+        }),
+        auth_user,
+        test_request.extract_state(),
+    ))
+    .into();
+    support::expects_unauthorized(&response);
+
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = order.id;
+    let response: HttpResponse = refund_requests::create((
+        database.connection.clone(),
+        path,
+        Json(CreateRefundRequestRequest {
+            ticket_instance_ids: vec![tickets[0].id],
+            reason: "Can no longer attend".to_string(),
+        }),
+        auth_user,
+        test_request.extract_state(),
+    ))
+    .into();
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let refund_request: RefundRequest = serde_json::from_str(&body).unwrap();
+    assert_eq!(refund_request.status, RefundRequestStatus::Pending);
+    assert_eq!(refund_request.ticket_instance_ids, vec![tickets[0].id]);
+    assert_eq!(
+        RefundedTicket::find_by_ticket_instance_ids(vec![tickets[0].id], connection)
+            .unwrap()
+            .len(),
+        0
+    );
+}
+
+#[test]
+pub fn create_auto_approved() {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let organization = database
+        .create_organization()
+        .finish()
+        .update(
+            None,
+            OrganizationEditableAttributes {
+                refund_request_auto_approve_days: Some(Some(7)),
+                ..Default::default()
+            },
+            &"encryption_key".to_string(),
+            connection,
+        )
+        .unwrap();
+    let event = database
+        .create_event()
+        .with_organization(&organization)
+        .with_event_start(Utc::now().naive_utc() + Duration::days(10))
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let ticket_type = &event.ticket_types(true, None, connection).unwrap()[0];
+    let user = database.create_user().finish();
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
+    cart.update_quantities(
+        user.id,
+        &[UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 2,
+            redemption_code: None,
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+    let total = cart.calculate_total(connection).unwrap();
+    cart.add_external_payment(
+        Some("Test".to_string()),
+        ExternalPaymentType::CreditCard,
+        user.id,
+        total,
+        connection,
+    )
+    .unwrap();
+    let tickets = cart.tickets(ticket_type.id, connection).unwrap();
+
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = cart.id;
+    let response: HttpResponse = refund_requests::create((
+        database.connection.clone(),
+        path,
+        Json(CreateRefundRequestRequest {
+            ticket_instance_ids: vec![tickets[0].id],
+            reason: "Can no longer attend".to_string(),
+        }),
+        auth_user,
+        test_request.extract_state(),
+    ))
+    .into();
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let refund_request: RefundRequest = serde_json::from_str(&body).unwrap();
+    assert_eq!(refund_request.status, RefundRequestStatus::Approved);
+    assert_eq!(refund_request.reviewed_by_user_id, None);
+
+    let refunded_tickets =
+        RefundedTicket::find_by_ticket_instance_ids(vec![tickets[0].id], connection).unwrap();
+    assert_eq!(refunded_tickets.len(), 1);
+    assert!(refunded_tickets[0].ticket_refunded_at.is_some());
+}
+
+#[test]
+pub fn approve_multiple() {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let event = database
+        .create_event()
+        .with_organization(&organization)
+        .with_ticket_pricing()
+        .finish();
+    let ticket_type = &event.ticket_types(true, None, connection).unwrap()[0];
+    let fan = database.create_user().finish();
+    let order = database
+        .create_order()
+        .for_user(&fan)
+        .for_event(&event)
+        .quantity(3)
+        .is_paid()
+        .finish();
+    let tickets = order.tickets(ticket_type.id, connection).unwrap();
+    let create_refund_request = |ticket_instance_id| {
+        RefundRequest::create(
+            order.id,
+            fan.id,
+            vec![ticket_instance_id],
+            "Can no longer attend".to_string(),
+        )
+        .commit(connection)
+        .unwrap()
+    };
+    let refund_request = create_refund_request(tickets[0].id);
+    let refund_request2 = create_refund_request(tickets[1].id);
+    let denied_refund_request = create_refund_request(tickets[2].id)
+        .deny(user.id, None, connection)
+        .unwrap();
+    let auth_user =
+        support::create_auth_user_from_user(&user, Roles::OrgOwner, Some(&organization), &database);
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = organization.id;
+    let response: HttpResponse = refund_requests::approve((
+        database.connection.clone(),
+        path,
+        Json(ApproveRefundRequestsRequest {
+            refund_request_ids: vec![
+                refund_request.id,
+                denied_refund_request.id,
+                refund_request2.id,
+            ],
+        }),
+        auth_user,
+        test_request.extract_state(),
+    ))
+    .into();
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let results: Vec<ReviewRefundRequestResult> = serde_json::from_str(&body).unwrap();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].refund_request_id, refund_request.id);
+    assert_eq!(results[0].status, RefundRequestStatus::Approved);
+    assert_eq!(results[0].error, None);
+    // Requests that can no longer be approved are reported without stopping the others
+    assert_eq!(results[1].refund_request_id, denied_refund_request.id);
+    assert_eq!(results[1].status, RefundRequestStatus::Denied);
+    assert!(results[1].error.is_some());
+    assert_eq!(results[2].refund_request_id, refund_request2.id);
+    assert_eq!(results[2].status, RefundRequestStatus::Approved);
+    assert_eq!(results[2].error, None);
+
+    let refunded_tickets = RefundedTicket::find_by_ticket_instance_ids(
+        vec![tickets[0].id, tickets[1].id, tickets[2].id],
+        connection,
+    )
+    .unwrap();
+    assert_eq!(refunded_tickets.len(), 2);
+    assert!(refunded_tickets
+        .iter()
+        .all(|refunded_ticket| refunded_ticket.ticket_instance_id != tickets[2].id));
+}
@@ -188,7 +188,6 @@ pub fn create_with_validation_errors() {
         .finish();
     //Construct Ticket creation and pricing request
     let test_request = TestRequest::create();
-    let state = test_request.extract_state();
     let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
     path.id = event.id;
     let mut ticket_pricing: Vec<CreateTicketPricingRequest> = Vec::new();
@@ -215,13 +214,13 @@ pub fn create_with_validation_errors() {
         price_in_cents: 10000,
         sold_out_behavior: SoldOutBehavior::ShowSoldOut,
         is_private: false,
+        refundable: false,
     };
     let response: HttpResponse = ticket_types::create((
         database.connection.into(),
         path,
         Json(request_data),
         auth_user,
-        state,
     ))
     .into();
 
@@ -252,7 +251,6 @@ pub fn create_with_validation_errors_on_ticket_pricing() {
         .finish();
     //Construct Ticket creation and pricing request
     let test_request = TestRequest::create();
-    let state = test_request.extract_state();
     let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
     path.id = event.id;
     let mut ticket_pricing: Vec<CreateTicketPricingRequest> = Vec::new();
@@ -279,13 +277,13 @@ pub fn create_with_validation_errors_on_ticket_pricing() {
         price_in_cents: 10000,
         sold_out_behavior: SoldOutBehavior::ShowSoldOut,
         is_private: false,
+        refundable: false,
     };
     let response: HttpResponse = ticket_types::create((
         database.connection.into(),
         path,
         Json(request_data),
         auth_user,
-        state,
     ))
     .into();
 
@@ -319,7 +317,6 @@ pub fn create_with_overlapping_periods() {
         .finish();
     //Construct Ticket creation and pricing request
     let test_request = TestRequest::create();
-    let state = test_request.extract_state();
     let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
     path.id = event.id;
     let mut ticket_pricing: Vec<CreateTicketPricingRequest> = Vec::new();
@@ -352,13 +349,13 @@ pub fn create_with_overlapping_periods() {
         price_in_cents: 20000,
         sold_out_behavior: SoldOutBehavior::ShowSoldOut,
         is_private: false,
+        refundable: false,
     };
     let response: HttpResponse = ticket_types::create((
         database.connection.into(),
         path,
         Json(request_data),
         auth_user,
-        state,
     ))
     .into();
 
@@ -406,7 +403,6 @@ pub fn create_with_out_of_bounds_ticket_capacity() {
 
     //Construct Ticket creation and pricing request
     let test_request = TestRequest::create();
-    let state = test_request.extract_state();
     let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
     path.id = event.id;
     let mut ticket_pricing: Vec<CreateTicketPricingRequest> = Vec::new();
@@ -439,13 +435,13 @@ pub fn create_with_out_of_bounds_ticket_capacity() {
         price_in_cents: 20000,
         sold_out_behavior: SoldOutBehavior::ShowSoldOut,
         is_private: false,
+        refundable: false,
     };
     let response: HttpResponse = ticket_types::create((
         database.connection.into(),
         path,
         Json(request_data),
         auth_user,
-        state,
     ))
     .into();
 
@@ -503,6 +499,7 @@ pub fn update_with_invalid_id() {
         price_in_cents: Some(20000),
         sold_out_behavior: None,
         is_private: Some(false),
+        refundable: None,
     };
 
     //Send update request
@@ -511,7 +508,6 @@ pub fn update_with_invalid_id() {
         path,
         Json(request_data),
         auth_user,
-        request.extract_state(),
     ))
     .into();
 
@@ -571,6 +567,7 @@ pub fn update_with_validation_errors() {
         price_in_cents: Some(20000),
         sold_out_behavior: None,
         is_private: None,
+        refundable: None,
     };
 
     //Send update request
@@ -579,7 +576,6 @@ pub fn update_with_validation_errors() {
         path,
         Json(request_data),
         auth_user,
-        request.extract_state(),
     ))
     .into();
 
@@ -650,6 +646,7 @@ pub fn update_with_validation_errors_on_ticket_pricing() {
         price_in_cents: Some(20000),
         sold_out_behavior: None,
         is_private: None,
+        refundable: None,
     };
 
     //Send update request
@@ -658,7 +655,6 @@ pub fn update_with_validation_errors_on_ticket_pricing() {
         path,
         Json(request_data),
         auth_user,
-        request.extract_state(),
     ))
     .into();
 
@@ -741,6 +737,7 @@ pub fn update_with_overlapping_periods() {
         price_in_cents: Some(20000),
         sold_out_behavior: None,
         is_private: None,
+        refundable: None,
     };
 
     //Send update request
@@ -749,7 +746,6 @@ pub fn update_with_overlapping_periods() {
         path,
         Json(request_data),
         auth_user,
-        request.extract_state(),
     ))
     .into();
 
@@ -831,14 +827,13 @@ pub fn cancel_with_sold_tickets_and_hold() {
     //Construct update request
     let test_request =
         TestRequest::create_with_uri_custom_params("/", vec!["event_id", "ticket_type_id"]);
-    let state = test_request.extract_state();
     let mut path = Path::<EventTicketPathParameters>::extract(&test_request.request).unwrap();
     path.event_id = event.id;
     path.ticket_type_id = created_ticket_type.id;
 
     //Send update request
     let response: HttpResponse =
-        ticket_types::cancel((database.connection.clone().into(), path, auth_user, state)).into();
+        ticket_types::cancel((database.connection.clone().into(), path, auth_user)).into();
 
     let updated_ticket_type = &event.ticket_types(true, None, conn).unwrap()[0];
 
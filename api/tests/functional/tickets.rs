@@ -5,7 +5,7 @@ use uuid::Uuid;
 
 use bigneon_api::controllers::tickets::SendTicketsRequest;
 use bigneon_api::controllers::tickets::{
-    self, SearchParameters, ShowTicketResponse, TransferTicketRequest,
+    self, OwnershipProofParameters, SearchParameters, ShowTicketResponse, TransferTicketRequest,
 };
 use bigneon_api::extractors::*;
 use bigneon_api::models::{OptionalPathParameters, PathParameters};
@@ -529,7 +529,6 @@ fn send_to_existing_user() {
 #[test]
 fn receive_ticket_transfer() {
     let database = TestDatabase::new();
-    let request = TestRequest::create();
     let user = database.create_user().finish();
     let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
     let organization = database.create_organization().finish();
@@ -590,9 +589,78 @@ fn receive_ticket_transfer() {
         database.connection.clone().into(),
         Json(transfer_auth.clone()),
         auth_user2.clone(),
-        request.extract_state(),
     ))
     .unwrap();
 
     assert_eq!(response.status(), StatusCode::OK);
+
+    // Transfers are synced to the blockchain asynchronously
+    let asset = Asset::find(tickets[0].asset_id, conn).unwrap();
+    let operation: BlockchainOperation =
+        serde_json::from_value(asset.blockchain_syncs(conn).unwrap()[0].payload.clone()).unwrap();
+    match operation {
+        BlockchainOperation::TransferTokens {
+            sender_wallet_id,
+            receiver_wallet_id,
+            mut token_ids,
+        } => {
+            token_ids.sort();
+            let mut expected_token_ids =
+                vec![tickets[0].token_id as u64, tickets[1].token_id as u64];
+            expected_token_ids.sort();
+            assert_eq!(sender_wallet_id, user.default_wallet(conn).unwrap().id);
+            assert_eq!(receiver_wallet_id, user2.default_wallet(conn).unwrap().id);
+            assert_eq!(token_ids, expected_token_ids);
+        }
+        _ => panic!("Expected a token transfer"),
+    }
+}
+
+#[test]
+fn ownership_proof_and_verify() {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let event = database.create_event().with_ticket_pricing().finish();
+    let ticket_type = &event.ticket_types(true, None, connection).unwrap()[0];
+    let order = database
+        .create_order()
+        .for_user(&user)
+        .for_event(&event)
+        .is_paid()
+        .finish();
+    let ticket = &order.tickets(ticket_type.id, connection).unwrap()[0];
+
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+    let test_request = TestRequest::create_with_uri("/?validity_period_in_seconds=60");
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = ticket.id;
+    let query = Query::<OwnershipProofParameters>::extract(&test_request.request).unwrap();
+    let response =
+        tickets::ownership_proof((database.connection.clone().into(), path, query, auth_user))
+            .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let proof: TicketOwnershipProof = serde_json::from_str(&body).unwrap();
+    assert_eq!(proof.ticket_instance_id, ticket.id);
+
+    // Verification is public
+    let response = tickets::verify((database.connection.clone().into(), Json(proof))).unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let authenticity: TicketAuthenticity = serde_json::from_str(&body).unwrap();
+    assert!(authenticity.exists);
+    assert_eq!(authenticity.status, Some(TicketInstanceStatus::Purchased));
+    assert_eq!(authenticity.event.map(|e| e.id), Some(event.id));
+    assert!(authenticity.owned_by_signer);
+
+    // Other users cannot create proofs for the ticket
+    let user2 = database.create_user().finish();
+    let auth_user = support::create_auth_user_from_user(&user2, Roles::User, None, &database);
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = ticket.id;
+    let query = Query::<OwnershipProofParameters>::extract(&test_request.request).unwrap();
+    let response =
+        tickets::ownership_proof((database.connection.clone().into(), path, query, auth_user));
+    assert!(response.is_err());
 }
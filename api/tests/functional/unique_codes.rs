@@ -0,0 +1,121 @@
+use actix_web::{http::StatusCode, FromRequest, HttpResponse, Path};
+use bigneon_api::controllers::unique_codes;
+use bigneon_api::models::PathParameters;
+use bigneon_db::models::*;
+use functional::base;
+use support;
+use support::database::TestDatabase;
+use support::test_request::TestRequest;
+
+#[cfg(test)]
+mod index_tests {
+    use super::*;
+    #[test]
+    fn index_org_member() {
+        base::unique_codes::index(Roles::OrgMember, true);
+    }
+    #[test]
+    fn index_admin() {
+        base::unique_codes::index(Roles::Admin, true);
+    }
+    #[test]
+    fn index_user() {
+        base::unique_codes::index(Roles::User, false);
+    }
+    #[test]
+    fn index_org_owner() {
+        base::unique_codes::index(Roles::OrgOwner, true);
+    }
+    #[test]
+    fn index_door_person() {
+        base::unique_codes::index(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn index_promoter() {
+        base::unique_codes::index(Roles::Promoter, true);
+    }
+    #[test]
+    fn index_promoter_read_only() {
+        base::unique_codes::index(Roles::PromoterReadOnly, true);
+    }
+    #[test]
+    fn index_org_admin() {
+        base::unique_codes::index(Roles::OrgAdmin, true);
+    }
+    #[test]
+    fn index_box_office() {
+        base::unique_codes::index(Roles::OrgBoxOffice, false);
+    }
+}
+
+#[cfg(test)]
+mod create_tests {
+    use super::*;
+    #[test]
+    fn create_org_member() {
+        base::unique_codes::create(Roles::OrgMember, true);
+    }
+    #[test]
+    fn create_admin() {
+        base::unique_codes::create(Roles::Admin, true);
+    }
+    #[test]
+    fn create_user() {
+        base::unique_codes::create(Roles::User, false);
+    }
+    #[test]
+    fn create_org_owner() {
+        base::unique_codes::create(Roles::OrgOwner, true);
+    }
+    #[test]
+    fn create_door_person() {
+        base::unique_codes::create(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn create_promoter() {
+        base::unique_codes::create(Roles::Promoter, true);
+    }
+    #[test]
+    fn create_promoter_read_only() {
+        base::unique_codes::create(Roles::PromoterReadOnly, false);
+    }
+    #[test]
+    fn create_org_admin() {
+        base::unique_codes::create(Roles::OrgAdmin, true);
+    }
+    #[test]
+    fn create_box_office() {
+        base::unique_codes::create(Roles::OrgBoxOffice, false);
+    }
+}
+
+#[test]
+fn export() {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let event = database.create_event().finish();
+    let organization = event.organization(connection).unwrap();
+    let auth_user =
+        support::create_auth_user_from_user(&user, Roles::OrgOwner, Some(&organization), &database);
+    let code = database.create_code().with_event(&event).finish();
+    let generated_codes =
+        UniqueCode::generate(&code, 2, None, None, None, user.id, connection).unwrap();
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = code.id;
+
+    let response: HttpResponse =
+        unique_codes::export((database.connection.clone().into(), path, auth_user)).into();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let lines: Vec<&str> = body.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0], "redemption_code,created_at,order_id,redeemed_at");
+    for generated_code in generated_codes {
+        assert!(lines
+            .iter()
+            .any(|line| line.starts_with(&format!("{},", generated_code.redemption_code))));
+    }
+}
@@ -1,15 +1,18 @@
-use actix_web::{http::StatusCode, HttpResponse};
+use actix_web::{http::header, http::StatusCode, FromRequest, HttpResponse, Query};
 use bigneon_api::auth::TokenResponse;
 use bigneon_api::controllers::users;
 use bigneon_api::extractors::*;
 use bigneon_api::models::{RegisterRequest, RequestInfo, UserProfileAttributes};
 use bigneon_db::prelude::*;
+use chrono::prelude::*;
+use chrono::Duration;
 use functional::base;
 use serde_json;
 use std::collections::HashMap;
 use support;
 use support::database::TestDatabase;
 use support::test_request::TestRequest;
+use uuid::Uuid;
 
 use bigneon_api::errors::BigNeonError;
 
@@ -375,6 +378,7 @@ fn register_succeeds_without_name() {
         last_name: None,
         phone: None,
         captcha_response: None,
+        marketing_consent_organization_id: None,
     });
 
     let response: HttpResponse =
@@ -400,6 +404,62 @@ fn register_succeeds() {
     assert_eq!(response.status(), StatusCode::CREATED);
 }
 
+#[test]
+fn register_with_marketing_consent() {
+    let database = TestDatabase::new();
+    let organization = database.create_organization().finish();
+    let request = TestRequest::create();
+    let mut register_request = RegisterRequest::new(
+        &"First",
+        &"Last",
+        &"consent@localhost",
+        &"555",
+        &"not_important",
+        None,
+    );
+    register_request.marketing_consent_organization_id = Some(organization.id);
+
+    let response: HttpResponse = users::register((
+        request.request,
+        database.connection.clone().into(),
+        Json(register_request),
+    ))
+    .into();
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let connection = database.connection.get();
+    let user = User::find_by_email("consent@localhost", connection).unwrap();
+    let consents = MarketingConsent::find_for_user(user.id, connection).unwrap();
+    assert_eq!(consents.len(), 1);
+    assert_eq!(consents[0].organization_id, organization.id);
+    assert!(consents[0].consented);
+    assert_eq!(consents[0].source, MarketingConsentSources::Registration);
+}
+
+#[test]
+fn register_with_unknown_marketing_consent_organization() {
+    let database = TestDatabase::new();
+    let request = TestRequest::create();
+    let mut register_request = RegisterRequest::new(
+        &"First",
+        &"Last",
+        &"consent@localhost",
+        &"555",
+        &"not_important",
+        None,
+    );
+    register_request.marketing_consent_organization_id = Some(Uuid::new_v4());
+
+    let response: HttpResponse = users::register((
+        request.request,
+        database.connection.clone().into(),
+        Json(register_request),
+    ))
+    .into();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert!(User::find_by_email("consent@localhost", database.connection.get()).is_err());
+}
+
 #[test]
 fn register_succeeds_with_login() {
     let database = TestDatabase::new();
@@ -665,3 +725,114 @@ fn update_current_user_address_exists() {
     let response: HttpResponse = result.into();
     assert_eq!(response.status(), StatusCode::CONFLICT);
 }
+
+#[test]
+fn recommendations() {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let artist = database.create_artist().finish();
+    let past_event = database.create_event().finish();
+    past_event.add_artist(None, artist.id, connection).unwrap();
+    EventInterest::create(past_event.id, user.id)
+        .commit(connection)
+        .unwrap();
+    let event = database
+        .create_event()
+        .with_event_start(NaiveDateTime::from(
+            Utc::now().naive_utc() + Duration::days(7),
+        ))
+        .finish();
+    event.add_artist(None, artist.id, connection).unwrap();
+    EventRecommendation::update_for_user(user.id, &[], connection).unwrap();
+
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+    let test_request = TestRequest::create_with_uri(&format!("/limits?"));
+    let query_parameters = Query::<PagingParameters>::extract(&test_request.request).unwrap();
+    let response = users::recommendations((
+        database.connection.clone().into(),
+        query_parameters,
+        auth_user,
+    ))
+    .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let payload: Payload<DisplayEventRecommendation> = serde_json::from_str(&body).unwrap();
+    assert_eq!(payload.paging.total, 1);
+    assert_eq!(payload.data.len(), 1);
+    assert_eq!(payload.data[0].event.id, event.id);
+}
+
+#[test]
+fn export_current_user() {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let order = database.create_order().for_user(&user).is_paid().finish();
+    database.create_payment_method().with_user(&user).finish();
+
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+    let response =
+        users::export_current_user((database.connection.clone().into(), auth_user)).unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response
+            .headers()
+            .get(header::CONTENT_DISPOSITION)
+            .unwrap()
+            .to_str()
+            .unwrap(),
+        format!("attachment; filename=\"bigneon-export-{}.json\"", user.id)
+    );
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let export: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(export["profile"]["id"], json!(user.id));
+    assert_eq!(export["orders"][0]["id"], json!(order.id));
+    assert_eq!(export["payment_methods"].as_array().unwrap().len(), 1);
+    assert_eq!(
+        export["tickets"][0][1].as_array().unwrap().len(),
+        TicketInstance::find_for_user(user.id, connection)
+            .unwrap()
+            .len()
+    );
+}
+
+#[test]
+fn delete_current_user() {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+    let response =
+        users::delete_current_user((database.connection.clone().into(), auth_user)).unwrap();
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+    let actions = DomainAction::find_by_main_table(
+        DomainActionTypes::DeleteUser,
+        Tables::Users.to_string(),
+        user.id,
+        connection,
+    )
+    .unwrap();
+    assert_eq!(actions.len(), 1);
+    let payload: DeleteUserPayload = serde_json::from_value(actions[0].payload.clone()).unwrap();
+    assert_eq!(payload.requested_by_user_id, Some(user.id));
+}
+
+#[test]
+fn delete_current_user_organization_member() {
+    let database = TestDatabase::new();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let auth_user = support::create_auth_user_from_user(
+        &user,
+        Roles::OrgMember,
+        Some(&organization),
+        &database,
+    );
+
+    let response: HttpResponse =
+        users::delete_current_user((database.connection.clone().into(), auth_user)).into();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
@@ -80,6 +80,7 @@ fn index_with_org_linked_and_private_venues() {
 
     //now with user that DOES belong to org
     let _ = organization.add_user(
+        None,
         auth_user.id(),
         vec![Roles::OrgMember],
         Vec::new(),
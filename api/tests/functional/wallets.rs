@@ -0,0 +1,141 @@
+use actix_web::{http::StatusCode, FromRequest, HttpResponse, Path};
+use bigneon_api::controllers::wallets::{self, *};
+use bigneon_api::extractors::*;
+use bigneon_api::models::PathParameters;
+use bigneon_db::models::*;
+use bigneon_db::utils::encryption;
+use serde_json;
+use support;
+use support::database::TestDatabase;
+use support::test_request::TestRequest;
+use tari_client::*;
+
+#[test]
+fn index() {
+    let database = TestDatabase::new();
+    let user = database.create_user().finish();
+    let event = database
+        .create_event()
+        .with_ticket_pricing()
+        .with_tickets()
+        .finish();
+    database
+        .create_order()
+        .for_user(&user)
+        .for_event(&event)
+        .is_paid()
+        .finish();
+    let wallet = Wallet::find_default_for_user(user.id, database.connection.get()).unwrap();
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+
+    let response: HttpResponse = wallets::index((database.connection.into(), auth_user)).into();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let wallets_response: WalletsResponse = serde_json::from_str(&body).unwrap();
+    assert_eq!(
+        wallets_response.wallets,
+        vec![DisplayWallet::from(wallet.clone())]
+    );
+    assert_eq!(wallets_response.tokens.len(), 1);
+    assert_eq!(wallets_response.tokens[0].wallet_id, wallet.id);
+    assert_eq!(wallets_response.tokens[0].event_id, event.id);
+}
+
+#[test]
+fn export() {
+    let database = TestDatabase::new();
+    let user = database.create_user().finish();
+    let wallet = Wallet::find_default_for_user(user.id, database.connection.get()).unwrap();
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = wallet.id;
+    let response: HttpResponse = wallets::export((
+        database.connection.clone().into(),
+        path,
+        Json(ExportWalletRequest {
+            passphrase: "correct horse battery staple".to_string(),
+        }),
+        auth_user,
+    ))
+    .into();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let export: WalletExport = serde_json::from_str(&body).unwrap();
+    assert_eq!(export.public_key, wallet.public_key);
+    assert_eq!(
+        encryption::decrypt_with_passphrase(
+            &export.encrypted_secret_key,
+            "correct horse battery staple",
+            &export.salt,
+            export.iterations
+        )
+        .unwrap(),
+        wallet.secret_key
+    );
+}
+
+#[test]
+fn export_for_other_user() {
+    let database = TestDatabase::new();
+    let user = database.create_user().finish();
+    let other_user = database.create_user().finish();
+    let wallet = Wallet::find_default_for_user(other_user.id, database.connection.get()).unwrap();
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = wallet.id;
+    let response: HttpResponse = wallets::export((
+        database.connection.into(),
+        path,
+        Json(ExportWalletRequest {
+            passphrase: "correct horse battery staple".to_string(),
+        }),
+        auth_user,
+    ))
+    .into();
+
+    support::expects_forbidden(&response, Some("This wallet does not belong to you"));
+}
+
+#[test]
+fn register_self_custody_key() {
+    let database = TestDatabase::new();
+    let user = database.create_user().finish();
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+
+    let (secret_key, public_key) = cryptographic_keypair();
+    let public_key = convert_bytes_to_hexstring(&public_key);
+    let signature = convert_bytes_to_hexstring(
+        &cryptographic_signature(
+            &Wallet::self_custody_message(user.id, &public_key),
+            &secret_key,
+        )
+        .unwrap(),
+    );
+    let response: HttpResponse = wallets::register_self_custody_key((
+        database.connection.clone().into(),
+        Json(RegisterSelfCustodyKeyRequest {
+            public_key: public_key.clone(),
+            signature,
+        }),
+        auth_user,
+    ))
+    .into();
+
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let wallet: DisplayWallet = serde_json::from_str(&body).unwrap();
+    assert_eq!(wallet.public_key, public_key);
+    assert!(wallet.self_custodied);
+    assert_eq!(
+        Wallet::find_default_for_user(user.id, database.connection.get())
+            .unwrap()
+            .id,
+        wallet.id
+    );
+}
@@ -10,6 +10,7 @@ extern crate bigneon_api;
 extern crate bigneon_db;
 extern crate chrono;
 extern crate diesel;
+extern crate futures;
 #[macro_use]
 extern crate macros;
 #[macro_use]
@@ -18,6 +19,7 @@ extern crate serde_json;
 extern crate serde_derive;
 extern crate globee;
 extern crate jsonwebtoken as jwt;
+extern crate tari_client;
 extern crate uuid;
 extern crate validator;
 
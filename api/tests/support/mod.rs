@@ -68,7 +68,13 @@ pub fn create_auth_user_from_user(
         }
 
         organization
-            .add_user(user.id, vec![role], event_ids, database.connection.get())
+            .add_user(
+                None,
+                user.id,
+                vec![role],
+                event_ids,
+                database.connection.get(),
+            )
             .unwrap();
 
         AuthUser::new(user.clone(), &test_request.request).unwrap()
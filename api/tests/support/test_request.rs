@@ -24,6 +24,7 @@ impl TestRequest {
         config.token_issuer = "bn-api-test".into();
         config.api_keys_encryption_key = "test_encryption_key".to_string();
         config.google_recaptcha_secret_key = None;
+        config.sendgrid_webhook_token = Some("test_webhook_token".to_string());
         if config.spotify_auth_token.is_some() {
             spotify::SINGLETON.set_auth_token(&config.spotify_auth_token.clone().unwrap());
         }
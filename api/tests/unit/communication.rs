@@ -0,0 +1,163 @@
+use bigneon_api::config::{Config, Environment};
+use bigneon_api::utils::communication::*;
+use bigneon_db::prelude::*;
+use futures::Future;
+use support::database::TestDatabase;
+
+#[test]
+fn remove_suppressed_destinations() {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    EmailSuppression::create(
+        "unsubscribed@tari.com",
+        EmailSuppressionReasons::Unsubscribe,
+        "SendGrid",
+        None,
+    )
+    .commit(connection)
+    .unwrap();
+
+    let destinations = vec![
+        "Unsubscribed@tari.com".to_string(),
+        "subscribed@tari.com".to_string(),
+    ];
+    let template_data = destinations
+        .iter()
+        .map(|d| {
+            let mut data = TemplateData::new();
+            data.insert("email".to_string(), d.clone());
+            data
+        })
+        .collect::<Vec<TemplateData>>();
+    let mut communication = Communication::new(
+        CommunicationType::EmailTemplate,
+        "Title".to_string(),
+        None,
+        Some(CommAddress::from("noreply@bigneon.com".to_string())),
+        CommAddress::from_vec(destinations.clone()),
+        Some("template_id".to_string()),
+        Some(template_data.clone()),
+    );
+
+    // Unsubscribes only apply to marketing emails
+    communication
+        .remove_suppressed_destinations(connection)
+        .unwrap();
+    assert_eq!(
+        communication.destinations,
+        CommAddress::from_vec(destinations.clone())
+    );
+
+    communication.marketing = true;
+    communication
+        .remove_suppressed_destinations(connection)
+        .unwrap();
+    assert_eq!(
+        communication.destinations,
+        CommAddress::from("subscribed@tari.com".to_string())
+    );
+    assert_eq!(
+        communication.template_data,
+        Some(vec![template_data[1].clone()])
+    );
+
+    // Only emails are suppressed
+    let mut communication = Communication::new(
+        CommunicationType::Push,
+        "Title".to_string(),
+        None,
+        None,
+        CommAddress::from_vec(destinations.clone()),
+        None,
+        None,
+    );
+    communication.marketing = true;
+    communication
+        .remove_suppressed_destinations(connection)
+        .unwrap();
+    assert_eq!(
+        communication.destinations,
+        CommAddress::from_vec(destinations)
+    );
+}
+
+#[test]
+fn send_async_skips_suppressed_destinations() {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let mut config = Config::new(Environment::Test);
+    config.environment = Environment::Development;
+    config.block_external_comms = false;
+    EmailSuppression::create(
+        "unsubscribed@tari.com",
+        EmailSuppressionReasons::Unsubscribe,
+        "SendGrid",
+        None,
+    )
+    .commit(connection)
+    .unwrap();
+    EmailSuppression::create(
+        "spam@tari.com",
+        EmailSuppressionReasons::SpamReport,
+        "SendGrid",
+        None,
+    )
+    .commit(connection)
+    .unwrap();
+
+    // Nothing is sent when every destination is suppressed, otherwise SendGrid would be called
+    let mut communication = Communication::new(
+        CommunicationType::Email,
+        "Title".to_string(),
+        Some("Body".to_string()),
+        Some(CommAddress::from("noreply@bigneon.com".to_string())),
+        CommAddress::from_vec(vec![
+            "unsubscribed@tari.com".to_string(),
+            "spam@tari.com".to_string(),
+        ]),
+        None,
+        None,
+    );
+    communication.marketing = true;
+    let domain_action = DomainAction::create(
+        None,
+        DomainActionTypes::Communication,
+        Some(CommunicationChannelType::Email),
+        json!(communication),
+        None,
+        None,
+    )
+    .commit(connection)
+    .unwrap();
+    assert!(
+        Communication::send_async(&domain_action, &config, connection)
+            .wait()
+            .is_ok()
+    );
+
+    // Spam reports are honoured for transactional emails too
+    let communication = Communication::new(
+        CommunicationType::Email,
+        "Title".to_string(),
+        Some("Body".to_string()),
+        Some(CommAddress::from("noreply@bigneon.com".to_string())),
+        CommAddress::from("spam@tari.com".to_string()),
+        None,
+        None,
+    );
+    let domain_action = DomainAction::create(
+        None,
+        DomainActionTypes::Communication,
+        Some(CommunicationChannelType::Email),
+        json!(communication),
+        None,
+        None,
+    )
+    .commit(connection)
+    .unwrap();
+    assert!(
+        Communication::send_async(&domain_action, &config, connection)
+            .wait()
+            .is_ok()
+    );
+}
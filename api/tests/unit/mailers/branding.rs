@@ -0,0 +1,143 @@
+use bigneon_api::communications::mailers::branding::{BrandedTemplate, EmailBranding};
+use bigneon_api::config::{Config, Environment};
+use bigneon_db::models::*;
+use support::database::TestDatabase;
+
+#[test]
+fn new_without_organization() {
+    let config = Config::new(Environment::Test);
+    let database = TestDatabase::new();
+
+    let branding = EmailBranding::new(
+        BrandedTemplate::PurchaseCompleted,
+        None,
+        &config,
+        database.connection.get(),
+    )
+    .unwrap();
+    assert_eq!(
+        branding.source_email,
+        config.communication_default_source_email
+    );
+    assert_eq!(branding.source_name, None);
+    assert_eq!(
+        branding.template_id,
+        config.sendgrid_template_bn_purchase_completed
+    );
+    assert_eq!(branding.sendgrid_organization_id, None);
+    assert!(branding.template_data.is_empty());
+}
+
+#[test]
+fn new_without_email_settings() {
+    let config = Config::new(Environment::Test);
+    let database = TestDatabase::new();
+    let organization = database.create_organization().finish();
+
+    let branding = EmailBranding::new(
+        BrandedTemplate::TransferTickets,
+        Some(&organization),
+        &config,
+        database.connection.get(),
+    )
+    .unwrap();
+    assert_eq!(
+        branding.source_email,
+        config.communication_default_source_email
+    );
+    assert_eq!(branding.source_name, None);
+    assert_eq!(
+        branding.template_id,
+        config.sendgrid_template_bn_transfer_tickets
+    );
+    assert_eq!(branding.sendgrid_organization_id, None);
+    assert_eq!(branding.template_data.len(), 1);
+    assert_eq!(branding.template_data["org"], organization.name);
+}
+
+#[test]
+fn new_without_organization_template() {
+    let config = Config::new(Environment::Test);
+    let database = TestDatabase::new();
+    let user = database.create_user().finish();
+    let organization = database
+        .create_organization()
+        .with_sendgrid_api_key("SG.key")
+        .finish();
+    OrganizationEmailSettings::create_or_update(
+        organization.id,
+        OrganizationEmailSettingsEditableAttributes {
+            from_email: Some(Some("tickets@example.com".to_string())),
+            from_name: Some(Some("Example Venue".to_string())),
+            logo_url: Some(Some("https://example.com/logo.png".to_string())),
+            primary_color: Some(Some("#000000".to_string())),
+            org_invite_template_id: Some(Some("d-456".to_string())),
+            ..Default::default()
+        },
+        user.id,
+        database.connection.get(),
+    )
+    .unwrap();
+
+    // Only the invite template is replaced so the platform account sends the purchase email
+    let branding = EmailBranding::new(
+        BrandedTemplate::PurchaseCompleted,
+        Some(&organization),
+        &config,
+        database.connection.get(),
+    )
+    .unwrap();
+    assert_eq!(
+        branding.source_email,
+        config.communication_default_source_email
+    );
+    assert_eq!(branding.source_name, Some("Example Venue".to_string()));
+    assert_eq!(
+        branding.template_id,
+        config.sendgrid_template_bn_purchase_completed
+    );
+    assert_eq!(branding.sendgrid_organization_id, None);
+    assert_eq!(branding.template_data["org"], organization.name);
+    assert_eq!(
+        branding.template_data["logo_url"],
+        "https://example.com/logo.png"
+    );
+    assert_eq!(branding.template_data["primary_color"], "#000000");
+    assert!(!branding.template_data.contains_key("secondary_color"));
+}
+
+#[test]
+fn new_with_organization_template() {
+    let config = Config::new(Environment::Test);
+    let database = TestDatabase::new();
+    let user = database.create_user().finish();
+    let organization = database
+        .create_organization()
+        .with_sendgrid_api_key("SG.key")
+        .finish();
+    OrganizationEmailSettings::create_or_update(
+        organization.id,
+        OrganizationEmailSettingsEditableAttributes {
+            from_email: Some(Some("tickets@example.com".to_string())),
+            from_name: Some(Some("Example Venue".to_string())),
+            org_invite_template_id: Some(Some("d-456".to_string())),
+            ..Default::default()
+        },
+        user.id,
+        database.connection.get(),
+    )
+    .unwrap();
+
+    let branding = EmailBranding::new(
+        BrandedTemplate::OrgInvite,
+        Some(&organization),
+        &config,
+        database.connection.get(),
+    )
+    .unwrap();
+    assert_eq!(branding.source_email, "tickets@example.com");
+    assert_eq!(branding.source_name, Some("Example Venue".to_string()));
+    assert_eq!(branding.template_id, "d-456");
+    assert_eq!(branding.sendgrid_organization_id, Some(organization.id));
+    assert_eq!(branding.template_data["org"], organization.name);
+}
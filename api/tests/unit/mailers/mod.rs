@@ -1 +1,2 @@
+pub mod branding;
 pub mod user;
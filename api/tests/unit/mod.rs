@@ -1,3 +1,4 @@
+pub mod communication;
 pub mod helpers;
 pub mod mailers;
 pub mod models;
@@ -0,0 +1,3 @@
+ALTER TABLE organizations
+    DROP COLUMN stripe_connect_account_id,
+    DROP COLUMN stripe_connect_enabled;
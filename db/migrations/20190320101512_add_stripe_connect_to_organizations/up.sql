@@ -0,0 +1,3 @@
+ALTER TABLE organizations
+    ADD stripe_connect_account_id TEXT NULL,
+    ADD stripe_connect_enabled BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,3 @@
+DROP INDEX index_payment_methods_user_id_is_default;
+DROP INDEX index_payment_methods_user_id_name;
+CREATE UNIQUE INDEX index_payment_methods_user_id_name ON payment_methods (user_id, name);
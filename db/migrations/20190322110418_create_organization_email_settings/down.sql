@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS index_organization_email_settings_organization_id;
+DROP TABLE IF EXISTS organization_email_settings;
@@ -0,0 +1,16 @@
+CREATE TABLE organization_email_settings
+(
+    id                              UUID PRIMARY KEY DEFAULT gen_random_uuid() NOT NULL,
+    organization_id                 UUID      NOT NULL REFERENCES organizations (id),
+    from_email                      TEXT      NULL,
+    from_name                       TEXT      NULL,
+    logo_url                        TEXT      NULL,
+    primary_color                   TEXT      NULL,
+    secondary_color                 TEXT      NULL,
+    purchase_completed_template_id  TEXT      NULL,
+    transfer_tickets_template_id    TEXT      NULL,
+    org_invite_template_id          TEXT      NULL,
+    created_at                      TIMESTAMP NOT NULL DEFAULT now(),
+    updated_at                      TIMESTAMP NOT NULL DEFAULT now()
+);
+CREATE UNIQUE INDEX index_organization_email_settings_organization_id ON organization_email_settings (organization_id);
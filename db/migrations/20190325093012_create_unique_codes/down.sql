@@ -0,0 +1,24 @@
+ALTER TABLE unique_codes DROP CONSTRAINT redemption_code_unique_per_event;
+
+CREATE OR REPLACE FUNCTION redemption_code_unique_per_event(UUID, TEXT, TEXT) RETURNS BOOLEAN AS $$
+BEGIN
+    RETURN (
+        select not exists (
+          select redemption_code
+          from codes
+          where ((id <> $1 and $2 = 'codes') or $2 <> 'codes') and redemption_code = $3
+          union select redemption_code
+          from holds
+          where ((id <> $1 and $2 = 'holds') or $2 <> 'holds') and redemption_code = $3
+
+        )
+    );
+END $$ LANGUAGE 'plpgsql';
+
+DROP INDEX IF EXISTS index_order_items_unique_code_id;
+ALTER TABLE order_items
+    DROP COLUMN unique_code_id;
+
+DROP INDEX IF EXISTS index_unique_codes_redemption_code;
+DROP INDEX IF EXISTS index_unique_codes_code_id;
+DROP TABLE IF EXISTS unique_codes;
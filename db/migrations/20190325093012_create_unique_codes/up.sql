@@ -0,0 +1,33 @@
+CREATE TABLE unique_codes
+(
+    id              UUID PRIMARY KEY DEFAULT gen_random_uuid() NOT NULL,
+    code_id         UUID      NOT NULL REFERENCES codes (id) ON DELETE CASCADE,
+    redemption_code TEXT      NOT NULL,
+    created_at      TIMESTAMP NOT NULL DEFAULT now(),
+    updated_at      TIMESTAMP NOT NULL DEFAULT now()
+);
+CREATE INDEX index_unique_codes_code_id ON unique_codes (code_id);
+CREATE UNIQUE INDEX index_unique_codes_redemption_code ON unique_codes (redemption_code);
+
+ALTER TABLE order_items
+    ADD COLUMN unique_code_id UUID NULL REFERENCES unique_codes (id);
+CREATE INDEX index_order_items_unique_code_id ON order_items (unique_code_id);
+
+CREATE OR REPLACE FUNCTION redemption_code_unique_per_event(UUID, TEXT, TEXT) RETURNS BOOLEAN AS $$
+BEGIN
+    RETURN (
+        select not exists (
+          select redemption_code
+          from codes
+          where ((id <> $1 and $2 = 'codes') or $2 <> 'codes') and redemption_code = $3
+          union select redemption_code
+          from holds
+          where ((id <> $1 and $2 = 'holds') or $2 <> 'holds') and redemption_code = $3
+          union select redemption_code
+          from unique_codes
+          where ((id <> $1 and $2 = 'unique_codes') or $2 <> 'unique_codes') and redemption_code = $3
+        )
+    );
+END $$ LANGUAGE 'plpgsql';
+
+ALTER TABLE unique_codes ADD CONSTRAINT redemption_code_unique_per_event CHECK(redemption_code_unique_per_event(id, 'unique_codes', redemption_code));
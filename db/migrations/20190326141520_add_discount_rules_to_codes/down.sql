@@ -0,0 +1,7 @@
+ALTER TABLE codes
+  DROP CONSTRAINT buy_quantity_and_get_quantity,
+  DROP COLUMN min_quantity,
+  DROP COLUMN buy_quantity,
+  DROP COLUMN get_quantity,
+  DROP COLUMN max_order_discount_in_cents,
+  DROP COLUMN waive_fees;
@@ -0,0 +1,7 @@
+ALTER TABLE codes
+  ADD COLUMN min_quantity BIGINT NULL CHECK (min_quantity > 0),
+  ADD COLUMN buy_quantity BIGINT NULL CHECK (buy_quantity > 0),
+  ADD COLUMN get_quantity BIGINT NULL CHECK (get_quantity > 0),
+  ADD COLUMN max_order_discount_in_cents BIGINT NULL CHECK (max_order_discount_in_cents > 0),
+  ADD COLUMN waive_fees BOOLEAN NOT NULL DEFAULT false,
+  ADD CONSTRAINT buy_quantity_and_get_quantity CHECK ((buy_quantity IS NULL) = (get_quantity IS NULL));
@@ -0,0 +1,2 @@
+DROP FUNCTION IF EXISTS code_analytics(event_id UUID, organization_id UUID, start TIMESTAMP, "end" TIMESTAMP);
+DROP TABLE IF EXISTS code_applications;
@@ -0,0 +1,15 @@
+CREATE TABLE hold_releases
+(
+    id                UUID PRIMARY KEY     DEFAULT gen_random_uuid() NOT NULL,
+    hold_id           UUID        NOT NULL REFERENCES holds (id) ON DELETE CASCADE,
+    release_at        TIMESTAMP   NOT NULL,
+    -- NULL releases everything still available in the hold
+    quantity          BIGINT      NULL CHECK (quantity > 0),
+    status            VARCHAR(20) NOT NULL DEFAULT 'Pending',
+    released_quantity BIGINT      NULL,
+    used_quantity     BIGINT      NULL,
+    released_at       TIMESTAMP   NULL,
+    created_at        TIMESTAMP   NOT NULL DEFAULT now(),
+    updated_at        TIMESTAMP   NOT NULL DEFAULT now()
+);
+CREATE INDEX index_hold_releases_hold_id ON hold_releases (hold_id);
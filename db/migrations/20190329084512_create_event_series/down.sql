@@ -0,0 +1,3 @@
+ALTER TABLE events
+    DROP COLUMN event_series_id;
+DROP TABLE IF EXISTS event_series;
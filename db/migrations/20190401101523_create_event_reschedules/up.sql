@@ -0,0 +1,12 @@
+CREATE TABLE event_reschedules
+(
+    id                    UUID PRIMARY KEY   DEFAULT gen_random_uuid() NOT NULL,
+    event_id              UUID      NOT NULL REFERENCES events (id) ON DELETE CASCADE,
+    previous_event_start  TIMESTAMP NOT NULL,
+    new_event_start       TIMESTAMP NOT NULL,
+    -- Holders can refund their own tickets until this time
+    refund_window_ends_at TIMESTAMP NULL,
+    created_at            TIMESTAMP NOT NULL DEFAULT now(),
+    updated_at            TIMESTAMP NOT NULL DEFAULT now()
+);
+CREATE INDEX index_event_reschedules_event_id ON event_reschedules (event_id);
@@ -0,0 +1,8 @@
+ALTER TABLE ticket_types
+    DROP COLUMN refundable;
+ALTER TABLE organizations
+    DROP COLUMN refund_request_auto_approve_days;
+
+DROP INDEX IF EXISTS index_refund_requests_organization_id_status;
+DROP INDEX IF EXISTS index_refund_requests_order_id;
+DROP TABLE IF EXISTS refund_requests;
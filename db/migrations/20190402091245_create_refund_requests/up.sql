@@ -0,0 +1,24 @@
+CREATE TABLE refund_requests
+(
+    id                  UUID PRIMARY KEY   DEFAULT gen_random_uuid() NOT NULL,
+    order_id            UUID      NOT NULL REFERENCES orders (id),
+    organization_id     UUID      NOT NULL REFERENCES organizations (id),
+    user_id             UUID      NOT NULL REFERENCES users (id),
+    ticket_instance_ids UUID[]    NOT NULL,
+    reason              TEXT      NOT NULL,
+    status              TEXT      NOT NULL DEFAULT 'Pending',
+    -- Null when the request was approved automatically
+    reviewed_by_user_id UUID      NULL REFERENCES users (id),
+    reviewed_at         TIMESTAMP NULL,
+    review_notes        TEXT      NULL,
+    created_at          TIMESTAMP NOT NULL DEFAULT now(),
+    updated_at          TIMESTAMP NOT NULL DEFAULT now()
+);
+CREATE INDEX index_refund_requests_order_id ON refund_requests (order_id);
+CREATE INDEX index_refund_requests_organization_id_status ON refund_requests (organization_id, status);
+
+-- Auto approval rules
+ALTER TABLE organizations
+    ADD refund_request_auto_approve_days INTEGER NULL;
+ALTER TABLE ticket_types
+    ADD refundable BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,8 @@
+DELETE FROM domain_actions
+WHERE domain_action_type IN ('BlockchainSync', 'ReconcileBlockchainAssets');
+
+UPDATE assets
+SET status = 'Unsynced';
+
+ALTER TABLE assets
+    DROP last_synced_at;
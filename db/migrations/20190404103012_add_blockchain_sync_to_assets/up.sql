@@ -0,0 +1,11 @@
+ALTER TABLE assets
+    ADD last_synced_at TIMESTAMP NULL;
+
+-- Assets already assigned on the blockchain were created synchronously
+UPDATE assets
+SET status = 'Synced', last_synced_at = updated_at
+WHERE blockchain_asset_id IS NOT NULL;
+
+-- Start the recurring reconciliation of assets against the blockchain
+INSERT INTO domain_actions (domain_action_type, payload, scheduled_at, expires_at, attempt_count, max_attempt_count, status, blocked_until)
+VALUES ('ReconcileBlockchainAssets', '{}', now(), now() + INTERVAL '1 day', 0, 3, 'Pending', now());
@@ -0,0 +1,3 @@
+DROP TRIGGER prevent_ledger_entry_changes ON ledger_entries;
+DROP FUNCTION prevent_ledger_entry_changes();
+DROP TABLE ledger_entries;
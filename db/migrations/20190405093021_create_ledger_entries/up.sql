@@ -0,0 +1,28 @@
+-- Append-only signed ledger used in place of a blockchain
+CREATE TABLE ledger_entries
+(
+    id                 UUID PRIMARY KEY   DEFAULT gen_random_uuid() NOT NULL,
+    asset_id           TEXT      NOT NULL,
+    sequence           BIGINT    NOT NULL,
+    command            TEXT      NOT NULL,
+    payload            JSON      NOT NULL,
+    public_key         TEXT      NOT NULL,
+    -- Signature of the entry chained to the signature of the previous entry for the asset
+    signature          TEXT      NOT NULL,
+    previous_signature TEXT      NULL,
+    created_at         TIMESTAMP NOT NULL DEFAULT now()
+);
+CREATE UNIQUE INDEX index_ledger_entries_asset_id_sequence ON ledger_entries (asset_id, sequence);
+
+CREATE FUNCTION prevent_ledger_entry_changes() RETURNS TRIGGER AS
+$$
+BEGIN
+    RAISE EXCEPTION 'Ledger entries cannot be changed once written';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER prevent_ledger_entry_changes
+    BEFORE UPDATE OR DELETE
+    ON ledger_entries
+    FOR EACH ROW
+EXECUTE PROCEDURE prevent_ledger_entry_changes();
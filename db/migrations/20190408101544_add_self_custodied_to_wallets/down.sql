@@ -0,0 +1,2 @@
+ALTER TABLE wallets
+    DROP self_custodied;
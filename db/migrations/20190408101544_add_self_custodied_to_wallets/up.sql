@@ -0,0 +1,2 @@
+ALTER TABLE wallets
+    ADD self_custodied BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS index_venues_latitude_longitude;
+DROP FUNCTION IF EXISTS distance_in_km(FLOAT8, FLOAT8, FLOAT8, FLOAT8);
+DROP FUNCTION IF EXISTS event_search_document(UUID);
@@ -0,0 +1,25 @@
+-- Weighted full text document for an event, ranking the event and artist names above the venue
+-- name, top line info and additional info
+CREATE FUNCTION event_search_document(event_id UUID) RETURNS TSVECTOR AS
+$$
+SELECT setweight(to_tsvector('english', e.name), 'A')
+           || setweight(to_tsvector('english', coalesce((SELECT string_agg(a.name, ' ')
+                                                          FROM event_artists ea
+                                                                   JOIN artists a ON a.id = ea.artist_id
+                                                          WHERE ea.event_id = e.id), '')), 'A')
+           || setweight(to_tsvector('english', coalesce(v.name, '')), 'B')
+           || setweight(to_tsvector('english', coalesce(e.top_line_info, '')), 'C')
+           || setweight(to_tsvector('english', coalesce(e.additional_info, '')), 'D')
+FROM events e
+         LEFT JOIN venues v ON v.id = e.venue_id
+WHERE e.id = $1;
+$$ LANGUAGE SQL STABLE;
+
+-- Great circle distance between two points using the haversine formula
+CREATE FUNCTION distance_in_km(latitude1 FLOAT8, longitude1 FLOAT8, latitude2 FLOAT8, longitude2 FLOAT8) RETURNS FLOAT8 AS
+$$
+SELECT 2 * 6371 * asin(sqrt(power(sin(radians($3 - $1) / 2), 2)
+    + cos(radians($1)) * cos(radians($3)) * power(sin(radians($4 - $2) / 2), 2)));
+$$ LANGUAGE SQL IMMUTABLE;
+
+CREATE INDEX index_venues_latitude_longitude ON venues (latitude, longitude);
@@ -0,0 +1,4 @@
+DELETE FROM domain_actions
+WHERE domain_action_type = 'UpdateEventRecommendations';
+
+DROP TABLE IF EXISTS event_recommendations;
@@ -0,0 +1,15 @@
+-- Events recommended to each user, replaced whenever recommendations are recalculated
+CREATE TABLE event_recommendations
+(
+    id         UUID PRIMARY KEY DEFAULT gen_random_uuid() NOT NULL,
+    user_id    UUID      NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    event_id   UUID      NOT NULL REFERENCES events (id) ON DELETE CASCADE,
+    score      FLOAT8    NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT now()
+);
+CREATE UNIQUE INDEX index_event_recommendations_user_id_event_id ON event_recommendations (user_id, event_id);
+CREATE INDEX index_event_recommendations_event_id ON event_recommendations (event_id);
+
+-- Start the recurring calculation of recommendations
+INSERT INTO domain_actions (domain_action_type, payload, scheduled_at, expires_at, attempt_count, max_attempt_count, status, blocked_until)
+VALUES ('UpdateEventRecommendations', '{}', now(), now() + INTERVAL '1 day', 0, 3, 'Pending', now());
@@ -0,0 +1,6 @@
+ALTER TABLE broadcasts
+    DROP COLUMN fan_segment_id;
+
+DROP TABLE IF EXISTS fan_segments;
+DROP TABLE IF EXISTS fan_notes;
+DROP TABLE IF EXISTS fan_tags;
@@ -0,0 +1,43 @@
+-- Tags added to fans by organization staff
+CREATE TABLE fan_tags
+(
+    id                 UUID PRIMARY KEY DEFAULT gen_random_uuid() NOT NULL,
+    organization_id    UUID      NOT NULL REFERENCES organizations (id),
+    user_id            UUID      NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    name               TEXT      NOT NULL,
+    created_by_user_id UUID      NOT NULL REFERENCES users (id),
+    created_at         TIMESTAMP NOT NULL DEFAULT now()
+);
+CREATE UNIQUE INDEX index_fan_tags_organization_id_user_id_name ON fan_tags (organization_id, user_id, lower(name));
+CREATE INDEX index_fan_tags_user_id ON fan_tags (user_id);
+
+-- Notes written about fans by organization staff
+CREATE TABLE fan_notes
+(
+    id                 UUID PRIMARY KEY DEFAULT gen_random_uuid() NOT NULL,
+    organization_id    UUID      NOT NULL REFERENCES organizations (id),
+    user_id            UUID      NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    note               TEXT      NOT NULL,
+    created_by_user_id UUID      NOT NULL REFERENCES users (id),
+    created_at         TIMESTAMP NOT NULL DEFAULT now(),
+    updated_at         TIMESTAMP NOT NULL DEFAULT now()
+);
+CREATE INDEX index_fan_notes_organization_id_user_id ON fan_notes (organization_id, user_id);
+CREATE INDEX index_fan_notes_user_id ON fan_notes (user_id);
+
+-- Saved fan filters, usable as broadcast audiences and marketing lists
+CREATE TABLE fan_segments
+(
+    id                 UUID PRIMARY KEY DEFAULT gen_random_uuid() NOT NULL,
+    organization_id    UUID      NOT NULL REFERENCES organizations (id),
+    name               TEXT      NOT NULL,
+    filters            JSON      NOT NULL DEFAULT '{}',
+    sendgrid_list_id   BIGINT    NULL,
+    created_by_user_id UUID      NOT NULL REFERENCES users (id),
+    created_at         TIMESTAMP NOT NULL DEFAULT now(),
+    updated_at         TIMESTAMP NOT NULL DEFAULT now()
+);
+CREATE INDEX index_fan_segments_organization_id ON fan_segments (organization_id);
+
+ALTER TABLE broadcasts
+    ADD fan_segment_id UUID NULL REFERENCES fan_segments (id) ON DELETE SET NULL;
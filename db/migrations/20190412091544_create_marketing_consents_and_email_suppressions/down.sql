@@ -0,0 +1,9 @@
+ALTER TABLE organizations
+    DROP mailchimp_api_key;
+
+DROP INDEX IF EXISTS index_email_suppressions_email;
+DROP TABLE IF EXISTS email_suppressions;
+
+DROP INDEX IF EXISTS index_marketing_consents_organization_id;
+DROP INDEX IF EXISTS index_marketing_consents_user_id_organization_id;
+DROP TABLE IF EXISTS marketing_consents;
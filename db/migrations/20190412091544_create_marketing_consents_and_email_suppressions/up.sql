@@ -0,0 +1,28 @@
+-- Whether a user agreed to receive marketing from an organization, and where they were asked
+CREATE TABLE marketing_consents
+(
+    id              UUID PRIMARY KEY DEFAULT gen_random_uuid() NOT NULL,
+    user_id         UUID      NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    organization_id UUID      NOT NULL REFERENCES organizations (id),
+    consented       BOOLEAN   NOT NULL,
+    source          TEXT      NOT NULL,
+    created_at      TIMESTAMP NOT NULL DEFAULT now(),
+    updated_at      TIMESTAMP NOT NULL DEFAULT now()
+);
+CREATE UNIQUE INDEX index_marketing_consents_user_id_organization_id ON marketing_consents (user_id, organization_id);
+CREATE INDEX index_marketing_consents_organization_id ON marketing_consents (organization_id);
+
+-- Addresses that unsubscribed, bounced or reported spam. Emails are stored lowercased.
+CREATE TABLE email_suppressions
+(
+    id         UUID PRIMARY KEY DEFAULT gen_random_uuid() NOT NULL,
+    email      TEXT      NOT NULL,
+    reason     TEXT      NOT NULL,
+    source     TEXT      NOT NULL,
+    details    TEXT      NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT now()
+);
+CREATE UNIQUE INDEX index_email_suppressions_email ON email_suppressions (email);
+
+ALTER TABLE organizations
+    ADD mailchimp_api_key TEXT NULL;
@@ -0,0 +1,7 @@
+DROP INDEX IF EXISTS index_domain_events_organization_id_created_at;
+DROP TRIGGER IF EXISTS domain_events_set_organization_id ON domain_events;
+DROP FUNCTION IF EXISTS domain_events_set_organization_id();
+DROP FUNCTION IF EXISTS domain_event_organization_id(TEXT, UUID);
+
+ALTER TABLE domain_events
+    DROP COLUMN organization_id;
@@ -0,0 +1,90 @@
+DROP FUNCTION IF EXISTS code_analytics(event_id UUID, organization_id UUID, start TIMESTAMP, "end" TIMESTAMP);
+
+CREATE OR REPLACE FUNCTION code_analytics(event_id UUID, organization_id UUID, start TIMESTAMP, "end" TIMESTAMP, code_ids UUID[], hold_ids UUID[])
+  RETURNS TABLE
+          (
+            code_id                 UUID,
+            hold_id                 UUID,
+            event_id                UUID,
+            name                    TEXT,
+            redemption_code         TEXT,
+            tickets_sold            BIGINT,
+            gross_revenue_in_cents  BIGINT,
+            discount_given_in_cents BIGINT,
+            net_revenue_in_cents    BIGINT,
+            unique_buyers           BIGINT,
+            applications            BIGINT,
+            purchases               BIGINT,
+            conversion_rate         DOUBLE PRECISION
+          )
+AS
+$body$
+WITH sales AS (
+    SELECT oi.code_id,
+           oi.hold_id,
+           o.id                                                                          AS order_id,
+           COALESCE(o.on_behalf_of_user_id, o.user_id)                                   AS buyer_id,
+           oi.quantity - oi.refunded_quantity                                            AS quantity,
+           (oi.quantity - oi.refunded_quantity) * oi.unit_price_in_cents                 AS ticket_revenue_in_cents,
+           -- Holds and access codes discount the unit price, discount codes add a discount item
+           (oi.quantity - oi.refunded_quantity) * GREATEST(0, tp.price_in_cents - oi.unit_price_in_cents) AS price_discount_in_cents,
+           COALESCE((SELECT SUM((d.quantity - d.refunded_quantity) * d.unit_price_in_cents)
+                     FROM order_items d
+                     WHERE d.parent_id = oi.id AND d.item_type = 'Discount'), 0)         AS discount_item_in_cents,
+           COALESCE((SELECT SUM((f.quantity - f.refunded_quantity) * f.unit_price_in_cents)
+                     FROM order_items f
+                     WHERE f.parent_id = oi.id AND f.item_type = 'PerUnitFees'), 0)      AS fees_in_cents
+    FROM order_items oi
+           INNER JOIN orders o ON o.id = oi.order_id
+           INNER JOIN ticket_pricing tp ON tp.id = oi.ticket_pricing_id
+           INNER JOIN events e ON e.id = oi.event_id
+    WHERE o.status = 'Paid'
+      AND oi.item_type = 'Tickets'
+      AND (oi.code_id IS NOT NULL OR oi.hold_id IS NOT NULL)
+      AND ($1 IS NULL OR oi.event_id = $1)
+      AND ($2 IS NULL OR e.organization_id = $2)
+      AND (($5 IS NULL AND $6 IS NULL) OR oi.code_id = ANY ($5) OR oi.hold_id = ANY ($6))
+      AND ($3 IS NULL OR o.paid_at >= $3)
+      AND ($4 IS NULL OR o.paid_at <= $4)
+),
+     codes_and_holds AS (
+       SELECT c.id AS code_id, NULL :: UUID AS hold_id, c.event_id, c.name, c.redemption_code
+       FROM codes c
+       WHERE ($1 IS NULL OR c.event_id = $1)
+         AND (($5 IS NULL AND $6 IS NULL) OR c.id = ANY ($5))
+       UNION ALL
+       SELECT NULL :: UUID AS code_id, h.id AS hold_id, h.event_id, h.name, h.redemption_code
+       FROM holds h
+       WHERE ($1 IS NULL OR h.event_id = $1)
+         AND (($5 IS NULL AND $6 IS NULL) OR h.id = ANY ($6))
+     )
+SELECT ch.code_id,
+       ch.hold_id,
+       ch.event_id,
+       ch.name,
+       ch.redemption_code,
+       CAST(COALESCE(SUM(s.quantity), 0) AS BIGINT)                                                          AS tickets_sold,
+       CAST(COALESCE(SUM(s.ticket_revenue_in_cents + s.discount_item_in_cents + s.fees_in_cents), 0) AS BIGINT) AS gross_revenue_in_cents,
+       CAST(COALESCE(SUM(s.price_discount_in_cents - s.discount_item_in_cents), 0) AS BIGINT)                AS discount_given_in_cents,
+       CAST(COALESCE(SUM(s.ticket_revenue_in_cents + s.discount_item_in_cents), 0) AS BIGINT)                AS net_revenue_in_cents,
+       CAST(COUNT(DISTINCT s.buyer_id) AS BIGINT)                                                            AS unique_buyers,
+       CAST(a.applications AS BIGINT)                                                                        AS applications,
+       CAST(COUNT(DISTINCT s.order_id) AS BIGINT)                                                            AS purchases,
+       CAST(a.converted_applications AS DOUBLE PRECISION) / NULLIF(a.applications, 0)                         AS conversion_rate
+FROM codes_and_holds ch
+       INNER JOIN events e ON e.id = ch.event_id
+       LEFT JOIN sales s ON (s.code_id = ch.code_id OR s.hold_id = ch.hold_id)
+       LEFT JOIN LATERAL (
+         SELECT COUNT(ca.id)                                  AS applications,
+                COUNT(ca.id) FILTER (WHERE o.status = 'Paid') AS converted_applications
+         FROM code_applications ca
+                INNER JOIN orders o ON o.id = ca.order_id
+         WHERE (ca.code_id = ch.code_id OR ca.hold_id = ch.hold_id)
+           AND ($3 IS NULL OR ca.created_at >= $3)
+           AND ($4 IS NULL OR ca.created_at <= $4)
+         ) a ON TRUE
+WHERE ($1 IS NULL OR ch.event_id = $1)
+  AND ($2 IS NULL OR e.organization_id = $2)
+GROUP BY ch.code_id, ch.hold_id, ch.event_id, ch.name, ch.redemption_code, a.applications, a.converted_applications;
+$body$
+  LANGUAGE SQL;
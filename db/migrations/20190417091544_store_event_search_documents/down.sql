@@ -0,0 +1,27 @@
+DROP INDEX IF EXISTS index_events_search_document;
+DROP TRIGGER IF EXISTS venues_update_event_search_documents ON venues;
+DROP TRIGGER IF EXISTS artists_update_event_search_documents ON artists;
+DROP TRIGGER IF EXISTS event_artists_update_event_search_documents ON event_artists;
+DROP FUNCTION IF EXISTS update_event_search_documents();
+DROP TRIGGER IF EXISTS events_set_search_document ON events;
+DROP FUNCTION IF EXISTS events_set_search_document();
+
+ALTER TABLE events
+    DROP COLUMN search_document;
+
+DROP FUNCTION IF EXISTS event_search_document(UUID, TEXT, TEXT, TEXT, UUID);
+
+CREATE FUNCTION event_search_document(event_id UUID) RETURNS TSVECTOR AS
+$$
+SELECT setweight(to_tsvector('english', e.name), 'A')
+           || setweight(to_tsvector('english', coalesce((SELECT string_agg(a.name, ' ')
+                                                          FROM event_artists ea
+                                                                   JOIN artists a ON a.id = ea.artist_id
+                                                          WHERE ea.event_id = e.id), '')), 'A')
+           || setweight(to_tsvector('english', coalesce(v.name, '')), 'B')
+           || setweight(to_tsvector('english', coalesce(e.top_line_info, '')), 'C')
+           || setweight(to_tsvector('english', coalesce(e.additional_info, '')), 'D')
+FROM events e
+         LEFT JOIN venues v ON v.id = e.venue_id
+WHERE e.id = $1;
+$$ LANGUAGE SQL STABLE;
@@ -0,0 +1,71 @@
+-- Weighted full text document for an event, ranking the event and artist names above the venue
+-- name, top line info and additional info
+CREATE OR REPLACE FUNCTION event_search_document(event_id UUID, name TEXT, top_line_info TEXT, additional_info TEXT, venue_id UUID) RETURNS TSVECTOR AS
+$$
+SELECT setweight(to_tsvector('english', $2), 'A')
+           || setweight(to_tsvector('english', coalesce((SELECT string_agg(a.name, ' ')
+                                                          FROM event_artists ea
+                                                                   JOIN artists a ON a.id = ea.artist_id
+                                                          WHERE ea.event_id = $1), '')), 'A')
+           || setweight(to_tsvector('english', coalesce((SELECT v.name FROM venues v WHERE v.id = $5), '')), 'B')
+           || setweight(to_tsvector('english', coalesce($3, '')), 'C')
+           || setweight(to_tsvector('english', coalesce($4, '')), 'D');
+$$ LANGUAGE SQL STABLE;
+
+DROP FUNCTION IF EXISTS event_search_document(UUID);
+
+ALTER TABLE events
+    ADD search_document TSVECTOR;
+
+-- Keeps the document up to date as the event, its artists and its venue change
+CREATE OR REPLACE FUNCTION events_set_search_document() RETURNS TRIGGER AS $$
+BEGIN
+    NEW.search_document := event_search_document(NEW.id, NEW.name, NEW.top_line_info, NEW.additional_info, NEW.venue_id);
+    RETURN NEW;
+END $$ LANGUAGE 'plpgsql';
+
+CREATE TRIGGER events_set_search_document
+    BEFORE INSERT OR UPDATE OF name, top_line_info, additional_info, venue_id ON events
+    FOR EACH ROW EXECUTE PROCEDURE events_set_search_document();
+
+CREATE OR REPLACE FUNCTION update_event_search_documents() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_TABLE_NAME = 'event_artists' THEN
+        IF TG_OP IN ('UPDATE', 'DELETE') THEN
+            UPDATE events e
+            SET search_document = event_search_document(e.id, e.name, e.top_line_info, e.additional_info, e.venue_id)
+            WHERE e.id = OLD.event_id;
+        END IF;
+        IF TG_OP IN ('INSERT', 'UPDATE') THEN
+            UPDATE events e
+            SET search_document = event_search_document(e.id, e.name, e.top_line_info, e.additional_info, e.venue_id)
+            WHERE e.id = NEW.event_id;
+        END IF;
+    ELSIF TG_TABLE_NAME = 'artists' THEN
+        UPDATE events e
+        SET search_document = event_search_document(e.id, e.name, e.top_line_info, e.additional_info, e.venue_id)
+        WHERE e.id IN (SELECT ea.event_id FROM event_artists ea WHERE ea.artist_id = NEW.id);
+    ELSIF TG_TABLE_NAME = 'venues' THEN
+        UPDATE events e
+        SET search_document = event_search_document(e.id, e.name, e.top_line_info, e.additional_info, e.venue_id)
+        WHERE e.venue_id = NEW.id;
+    END IF;
+    RETURN NULL;
+END $$ LANGUAGE 'plpgsql';
+
+CREATE TRIGGER event_artists_update_event_search_documents
+    AFTER INSERT OR UPDATE OR DELETE ON event_artists
+    FOR EACH ROW EXECUTE PROCEDURE update_event_search_documents();
+
+CREATE TRIGGER artists_update_event_search_documents
+    AFTER UPDATE OF name ON artists
+    FOR EACH ROW EXECUTE PROCEDURE update_event_search_documents();
+
+CREATE TRIGGER venues_update_event_search_documents
+    AFTER UPDATE OF name ON venues
+    FOR EACH ROW EXECUTE PROCEDURE update_event_search_documents();
+
+UPDATE events
+SET search_document = event_search_document(id, name, top_line_info, additional_info, venue_id);
+
+CREATE INDEX index_events_search_document ON events USING GIN (search_document);
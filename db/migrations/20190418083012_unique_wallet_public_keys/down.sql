@@ -0,0 +1 @@
+DROP INDEX IF EXISTS index_wallets_public_key;
@@ -0,0 +1 @@
+CREATE UNIQUE INDEX index_wallets_public_key ON wallets (public_key);
@@ -0,0 +1,8 @@
+DELETE
+FROM email_suppressions es
+WHERE EXISTS(SELECT 1
+             FROM email_suppressions other
+             WHERE other.email = es.email
+               AND (other.created_at, other.id) < (es.created_at, es.id));
+DROP INDEX IF EXISTS index_email_suppressions_email_reason;
+CREATE UNIQUE INDEX index_email_suppressions_email ON email_suppressions (email);
@@ -0,0 +1,3 @@
+-- An address can be suppressed for several reasons, e.g. a bounce after unsubscribing
+DROP INDEX IF EXISTS index_email_suppressions_email;
+CREATE UNIQUE INDEX index_email_suppressions_email_reason ON email_suppressions (email, reason);
@@ -0,0 +1,80 @@
+-- Ticket type and ticket pricing domain events belong to the organization running the event
+CREATE OR REPLACE FUNCTION domain_event_organization_id(TEXT, UUID) RETURNS UUID AS $$
+BEGIN
+    RETURN CASE $1
+        WHEN 'Organizations' THEN $2
+        WHEN 'Events' THEN (SELECT organization_id FROM events WHERE id = $2)
+        WHEN 'EventSeries' THEN (SELECT organization_id FROM event_series WHERE id = $2)
+        WHEN 'FanSegments' THEN (SELECT organization_id FROM fan_segments WHERE id = $2)
+        -- Fee schedules are created before being assigned to an organization
+        WHEN 'FeeSchedules' THEN (
+            SELECT NULLIF(organization_id, '00000000-0000-0000-0000-000000000000') FROM fee_schedules WHERE id = $2
+        )
+        WHEN 'RefundRequests' THEN (SELECT organization_id FROM refund_requests WHERE id = $2)
+        WHEN 'Settlements' THEN (SELECT organization_id FROM settlements WHERE id = $2)
+        WHEN 'Broadcasts' THEN (
+            SELECT e.organization_id FROM broadcasts b JOIN events e ON b.event_id = e.id WHERE b.id = $2
+        )
+        WHEN 'Codes' THEN (
+            SELECT e.organization_id FROM codes c JOIN events e ON c.event_id = e.id WHERE c.id = $2
+        )
+        WHEN 'EventArtists' THEN (
+            SELECT e.organization_id FROM event_artists ea JOIN events e ON ea.event_id = e.id WHERE ea.id = $2
+        )
+        WHEN 'EventReschedules' THEN (
+            SELECT e.organization_id FROM event_reschedules er JOIN events e ON er.event_id = e.id WHERE er.id = $2
+        )
+        WHEN 'Holds' THEN (
+            SELECT e.organization_id FROM holds h JOIN events e ON h.event_id = e.id WHERE h.id = $2
+        )
+        WHEN 'HoldReleases' THEN (
+            SELECT e.organization_id
+            FROM hold_releases hr
+                JOIN holds h ON hr.hold_id = h.id
+                JOIN events e ON h.event_id = e.id
+            WHERE hr.id = $2
+        )
+        WHEN 'TicketTypes' THEN (
+            SELECT e.organization_id FROM ticket_types tt JOIN events e ON tt.event_id = e.id WHERE tt.id = $2
+        )
+        WHEN 'TicketPricing' THEN (
+            SELECT e.organization_id
+            FROM ticket_pricing tp
+                JOIN ticket_types tt ON tp.ticket_type_id = tt.id
+                JOIN events e ON tt.event_id = e.id
+            WHERE tp.id = $2
+        )
+        WHEN 'Assets' THEN (
+            SELECT e.organization_id
+            FROM assets a
+                JOIN ticket_types tt ON a.ticket_type_id = tt.id
+                JOIN events e ON tt.event_id = e.id
+            WHERE a.id = $2
+        )
+        WHEN 'TicketInstances' THEN (
+            SELECT e.organization_id
+            FROM ticket_instances ti
+                JOIN assets a ON ti.asset_id = a.id
+                JOIN ticket_types tt ON a.ticket_type_id = tt.id
+                JOIN events e ON tt.event_id = e.id
+            WHERE ti.id = $2
+        )
+        -- Orders spanning organizations are logged against the first one found
+        WHEN 'Orders' THEN (
+            SELECT e.organization_id
+            FROM order_items oi
+                JOIN events e ON oi.event_id = e.id
+            WHERE oi.order_id = $2
+            LIMIT 1
+        )
+        WHEN 'Payments' THEN (
+            SELECT e.organization_id
+            FROM payments p
+                JOIN order_items oi ON oi.order_id = p.order_id
+                JOIN events e ON oi.event_id = e.id
+            WHERE p.id = $2
+            LIMIT 1
+        )
+        ELSE NULL
+    END;
+END $$ LANGUAGE 'plpgsql';
@@ -0,0 +1,5 @@
+ALTER TABLE order_items
+    DROP CONSTRAINT order_items_code_id_fkey,
+    ADD CONSTRAINT order_items_code_id_fkey FOREIGN KEY (code_id) REFERENCES codes (id),
+    DROP CONSTRAINT order_items_unique_code_id_fkey,
+    ADD CONSTRAINT order_items_unique_code_id_fkey FOREIGN KEY (unique_code_id) REFERENCES unique_codes (id);
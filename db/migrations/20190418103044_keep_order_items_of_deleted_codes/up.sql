@@ -0,0 +1,6 @@
+-- Codes are hard deleted, order items that redeemed them keep their history without the reference
+ALTER TABLE order_items
+    DROP CONSTRAINT order_items_code_id_fkey,
+    ADD CONSTRAINT order_items_code_id_fkey FOREIGN KEY (code_id) REFERENCES codes (id) ON DELETE SET NULL,
+    DROP CONSTRAINT order_items_unique_code_id_fkey,
+    ADD CONSTRAINT order_items_unique_code_id_fkey FOREIGN KEY (unique_code_id) REFERENCES unique_codes (id) ON DELETE SET NULL;
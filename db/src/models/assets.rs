@@ -1,25 +1,90 @@
 use chrono::prelude::*;
+use chrono::Duration;
 use diesel;
 use diesel::expression::dsl;
 use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Nullable, Text, Timestamp, Uuid as dUuid};
 use models::*;
-use schema::assets;
+use schema::{assets, ticket_instances, wallets};
+use serde_json;
 use utils::errors::ConvertToDatabaseError;
 use utils::errors::DatabaseError;
 use utils::errors::ErrorCode;
 use uuid::Uuid;
 
+/// Blockchain syncs are retried with backoff for up to a week before the asset is marked failed
+const BLOCKCHAIN_SYNC_MAX_ATTEMPTS: i64 = 10;
+const BLOCKCHAIN_SYNC_EXPIRY_DAYS: i64 = 7;
+
 #[derive(Queryable, Identifiable, AsChangeset, Debug)]
 #[table_name = "assets"]
 pub struct Asset {
     pub id: Uuid,
-    ticket_type_id: Uuid,
+    pub ticket_type_id: Uuid,
     blockchain_name: String,
-    // TODO: This will be populated after it is created on the blockchain.
+    // Populated once the asset has been created on the blockchain
     pub blockchain_asset_id: Option<String>,
-    status: AssetStatus,
+    pub status: AssetStatus,
     created_at: NaiveDateTime,
     updated_at: NaiveDateTime,
+    pub last_synced_at: Option<NaiveDateTime>,
+}
+
+/// A change to an asset on the blockchain, performed asynchronously by the `BlockchainSync`
+/// domain action. Wallets are referenced by id so their keys are only loaded when the change
+/// is made.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "operation")]
+pub enum BlockchainOperation {
+    CreateAsset {
+        wallet_id: Uuid,
+        name: String,
+        total_supply: u64,
+        expiry_date: i64,
+    },
+    IncreaseSupply {
+        wallet_id: Uuid,
+        total_supply: u64,
+    },
+    NullifyTokens {
+        wallet_id: Uuid,
+        token_ids: Vec<u64>,
+    },
+    RedeemTokens {
+        wallet_id: Uuid,
+        token_ids: Vec<u64>,
+    },
+    TransferTokens {
+        sender_wallet_id: Uuid,
+        receiver_wallet_id: Uuid,
+        token_ids: Vec<u64>,
+    },
+}
+
+#[derive(Debug, Deserialize, PartialEq, QueryableByName, Serialize)]
+pub struct AssetSyncSummary {
+    #[sql_type = "dUuid"]
+    pub asset_id: Uuid,
+    #[sql_type = "dUuid"]
+    pub ticket_type_id: Uuid,
+    #[sql_type = "dUuid"]
+    pub event_id: Uuid,
+    #[sql_type = "Nullable<Text>"]
+    pub blockchain_asset_id: Option<String>,
+    #[sql_type = "Text"]
+    pub status: AssetStatus,
+    #[sql_type = "Nullable<Timestamp>"]
+    pub last_synced_at: Option<NaiveDateTime>,
+    /// Ticket instances issued locally, which should match the supply on chain
+    #[sql_type = "BigInt"]
+    pub ticket_count: i64,
+    #[sql_type = "BigInt"]
+    pub pending_sync_count: i64,
+    #[sql_type = "BigInt"]
+    pub failed_sync_count: i64,
+    #[serde(skip_serializing)]
+    #[sql_type = "BigInt"]
+    pub total: i64,
 }
 
 impl Asset {
@@ -50,6 +115,170 @@ impl Asset {
             .to_db_error(ErrorCode::QueryError, "Error loading asset")
     }
 
+    /// Assets that have not been synced or that have failed blockchain syncs
+    pub fn find_unsynced(conn: &PgConnection) -> Result<Vec<Asset>, DatabaseError> {
+        assets::table
+            .filter(assets::status.ne(AssetStatus::Synced))
+            .order_by(assets::created_at.asc())
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Error loading unsynced assets")
+    }
+
+    /// Assets with all of their blockchain syncs completed
+    pub fn find_synced(conn: &PgConnection) -> Result<Vec<Asset>, DatabaseError> {
+        assets::table
+            .filter(assets::status.eq(AssetStatus::Synced))
+            .filter(assets::blockchain_asset_id.is_not_null())
+            .order_by(assets::created_at.asc())
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Error loading synced assets")
+    }
+
+    /// The wallet holding each of the asset's tokens according to its ticket instances,
+    /// ordered by token id
+    pub fn token_wallets(&self, conn: &PgConnection) -> Result<Vec<(u64, Wallet)>, DatabaseError> {
+        let token_wallets: Vec<(i32, Wallet)> = ticket_instances::table
+            .inner_join(wallets::table)
+            .filter(ticket_instances::asset_id.eq(self.id))
+            .select((ticket_instances::token_id, wallets::all_columns))
+            .order_by(ticket_instances::token_id.asc())
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Error loading token wallets")?;
+        Ok(token_wallets
+            .into_iter()
+            .map(|(token_id, wallet)| (token_id as u64, wallet))
+            .collect())
+    }
+
+    /// Compares each asset's ticket instances with its queued and failed blockchain syncs,
+    /// optionally limited to a single event
+    pub fn sync_summaries(
+        event_id: Option<Uuid>,
+        page: u32,
+        limit: u32,
+        conn: &PgConnection,
+    ) -> Result<Payload<AssetSyncSummary>, DatabaseError> {
+        let query = r#"
+            SELECT
+                a.id AS asset_id,
+                a.ticket_type_id,
+                tt.event_id,
+                a.blockchain_asset_id,
+                a.status,
+                a.last_synced_at,
+                (SELECT count(*) FROM ticket_instances ti WHERE ti.asset_id = a.id) AS ticket_count,
+                (SELECT count(*) FROM domain_actions da
+                    WHERE da.main_table = 'Assets' AND da.main_table_id = a.id
+                    AND da.domain_action_type = 'BlockchainSync'
+                    AND da.status = 'Pending') AS pending_sync_count,
+                (SELECT count(*) FROM domain_actions da
+                    WHERE da.main_table = 'Assets' AND da.main_table_id = a.id
+                    AND da.domain_action_type = 'BlockchainSync'
+                    AND da.status IN ('RetriesExceeded', 'Errored')) AS failed_sync_count,
+                count(*) over() AS total
+            FROM assets a
+            JOIN ticket_types tt ON tt.id = a.ticket_type_id
+            WHERE $1 IS NULL OR tt.event_id = $1
+            ORDER BY a.created_at
+            LIMIT $2
+            OFFSET $3;
+        "#;
+
+        let results: Vec<AssetSyncSummary> = diesel::sql_query(query)
+            .bind::<Nullable<dUuid>, _>(event_id)
+            .bind::<BigInt, _>(limit as i64)
+            .bind::<BigInt, _>((page * limit) as i64)
+            .get_results(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load asset sync summaries")?;
+
+        let total = results.first().map(|r| r.total).unwrap_or(0);
+        let mut payload = Payload::new(results, Paging::new(page, limit));
+        payload.paging.total = total as u64;
+        Ok(payload)
+    }
+
+    /// Queues `operation` to be performed on the blockchain, retrying until it succeeds
+    pub fn queue_blockchain_sync(
+        &self,
+        operation: BlockchainOperation,
+        conn: &PgConnection,
+    ) -> Result<DomainAction, DatabaseError> {
+        let mut action = DomainAction::create(
+            None,
+            DomainActionTypes::BlockchainSync,
+            None,
+            serde_json::to_value(&operation)?,
+            Some(Tables::Assets.to_string()),
+            Some(self.id),
+        );
+        action.expires_at = Utc::now().naive_utc() + Duration::days(BLOCKCHAIN_SYNC_EXPIRY_DAYS);
+        action.max_attempt_count = BLOCKCHAIN_SYNC_MAX_ATTEMPTS;
+        let action = action.commit(conn)?;
+
+        self.update_status(AssetStatus::Pending, conn)?;
+        Ok(action)
+    }
+
+    /// Blockchain syncs for this asset, most recent first
+    pub fn blockchain_syncs(
+        &self,
+        conn: &PgConnection,
+    ) -> Result<Vec<DomainAction>, DatabaseError> {
+        DomainAction::find_by_main_table(
+            DomainActionTypes::BlockchainSync,
+            Tables::Assets.to_string(),
+            self.id,
+            conn,
+        )
+    }
+
+    /// Requeues the asset's failed blockchain syncs, keeping the order they were queued in.
+    /// Returns the number of syncs requeued.
+    pub fn retry_failed_blockchain_syncs(
+        &self,
+        conn: &PgConnection,
+    ) -> Result<usize, DatabaseError> {
+        let now = Utc::now().naive_utc();
+        let failed_syncs: Vec<DomainAction> = self
+            .blockchain_syncs(conn)?
+            .into_iter()
+            .filter(|a| {
+                a.status == DomainActionStatus::RetriesExceeded
+                    || a.status == DomainActionStatus::Errored
+                    || (a.status == DomainActionStatus::Pending && a.expires_at <= now)
+            })
+            .collect();
+
+        for action in failed_syncs.iter().rev() {
+            action.retry(now + Duration::days(BLOCKCHAIN_SYNC_EXPIRY_DAYS), conn)?;
+        }
+        if !failed_syncs.is_empty() {
+            self.update_status(AssetStatus::Pending, conn)?;
+        }
+
+        Ok(failed_syncs.len())
+    }
+
+    pub fn update_status(
+        &self,
+        status: AssetStatus,
+        conn: &PgConnection,
+    ) -> Result<Asset, DatabaseError> {
+        let last_synced_at = if status == AssetStatus::Synced {
+            Some(Utc::now().naive_utc())
+        } else {
+            self.last_synced_at
+        };
+        diesel::update(self)
+            .set((
+                assets::status.eq(status),
+                assets::last_synced_at.eq(last_synced_at),
+                assets::updated_at.eq(dsl::now),
+            ))
+            .get_result(conn)
+            .to_db_error(ErrorCode::UpdateError, "Could not update asset status")
+    }
+
     pub fn update_blockchain_id(
         &self,
         id: String,
@@ -20,6 +20,7 @@ pub struct NewBroadcast {
     pub send_at: Option<NaiveDateTime>,
     pub status: BroadcastStatus,
     pub progress: i32,
+    pub fan_segment_id: Option<Uuid>,
 }
 
 #[derive(Queryable, Identifiable, Insertable, Serialize, Deserialize, PartialEq, Debug)]
@@ -36,6 +37,8 @@ pub struct Broadcast {
     pub progress: i32,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    /// Sends to the fans in this segment instead of the default audience of the notification type
+    pub fan_segment_id: Option<Uuid>,
 }
 
 #[derive(AsChangeset, Default, Deserialize)]
@@ -72,6 +75,7 @@ impl Broadcast {
             send_at,
             status: status.unwrap_or(BroadcastStatus::Pending),
             progress: 0,
+            fan_segment_id: None,
         }
     }
 
@@ -161,6 +165,17 @@ impl Broadcast {
 
 impl NewBroadcast {
     pub fn commit(&self, connection: &PgConnection) -> Result<Broadcast, DatabaseError> {
+        if let Some(fan_segment_id) = self.fan_segment_id {
+            let fan_segment = FanSegment::find(fan_segment_id, connection)?;
+            let event = Event::find(self.event_id, connection)?;
+            if fan_segment.organization_id != event.organization_id {
+                return DatabaseError::validation_error(
+                    "fan_segment_id",
+                    "Fan segment does not belong to the event's organization",
+                );
+            }
+        }
+
         let result: Broadcast = DatabaseError::wrap(
             ErrorCode::InsertError,
             "Could not create new push notification",
@@ -0,0 +1,64 @@
+use chrono::NaiveDateTime;
+use diesel;
+use diesel::prelude::*;
+use models::*;
+use schema::code_applications;
+use utils::errors::*;
+use uuid::Uuid;
+
+/// A valid code or hold redemption code entered into an order, used to measure how often
+/// codes are converted into purchases
+#[derive(Associations, Clone, Debug, Identifiable, PartialEq, Queryable, Serialize)]
+#[belongs_to(Code)]
+#[belongs_to(Hold)]
+#[belongs_to(Order)]
+#[table_name = "code_applications"]
+pub struct CodeApplication {
+    pub id: Uuid,
+    pub code_id: Option<Uuid>,
+    pub hold_id: Option<Uuid>,
+    pub order_id: Uuid,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Insertable)]
+#[table_name = "code_applications"]
+struct NewCodeApplication {
+    code_id: Option<Uuid>,
+    hold_id: Option<Uuid>,
+    order_id: Uuid,
+}
+
+impl CodeApplication {
+    /// Records the code being applied to the order, applying it again to the same order is
+    /// not counted twice
+    pub(crate) fn record(
+        order_id: Uuid,
+        code_id: Option<Uuid>,
+        hold_id: Option<Uuid>,
+        conn: &PgConnection,
+    ) -> Result<(), DatabaseError> {
+        diesel::insert_into(code_applications::table)
+            .values(NewCodeApplication {
+                code_id,
+                hold_id,
+                order_id,
+            })
+            .on_conflict_do_nothing()
+            .execute(conn)
+            .to_db_error(ErrorCode::InsertError, "Could not record code application")?;
+        Ok(())
+    }
+
+    pub fn find_for_order(
+        order_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<CodeApplication>, DatabaseError> {
+        code_applications::table
+            .filter(code_applications::order_id.eq(order_id))
+            .order_by(code_applications::created_at)
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load code applications")
+    }
+}
@@ -2,10 +2,12 @@ use chrono::prelude::*;
 use diesel;
 use diesel::dsl;
 use diesel::prelude::*;
-use diesel::sql_types::{Array, BigInt, Nullable, Text, Timestamp, Uuid as dUuid};
+use diesel::sql_types::{Array, BigInt, Bool, Nullable, Text, Timestamp, Uuid as dUuid};
 use models::*;
 use schema::{codes, order_items, orders};
 use std::borrow::Cow;
+use std::cmp;
+use utils::audit;
 use utils::errors::*;
 use uuid::Uuid;
 use validator::*;
@@ -26,6 +28,15 @@ pub struct Code {
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
     pub discount_as_percentage: Option<i64>,
+    /// Discount only applies once at least this many tickets are bought with the code
+    pub min_quantity: Option<i64>,
+    /// Buy `buy_quantity` get `get_quantity` discounted, e.g. buy 4 get 1 with a 100% discount
+    pub buy_quantity: Option<i64>,
+    pub get_quantity: Option<i64>,
+    /// Upper limit on the discount given across all of an order's tickets
+    pub max_order_discount_in_cents: Option<i64>,
+    /// Per unit fees are not charged on discounted tickets
+    pub waive_fees: bool,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -33,6 +44,9 @@ pub struct CodeAvailability {
     #[serde(flatten)]
     pub code: Code,
     pub available: i64,
+    /// Set when the redemption code belongs to one of the code's generated unique codes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unique_code_id: Option<Uuid>,
 }
 
 #[derive(Debug, Deserialize, PartialEq, Queryable, Serialize, QueryableByName)]
@@ -59,12 +73,24 @@ pub struct DisplayCode {
     pub end_date: NaiveDateTime,
     #[sql_type = "Nullable<BigInt>"]
     pub max_tickets_per_user: Option<i64>,
+    #[sql_type = "Nullable<BigInt>"]
+    pub min_quantity: Option<i64>,
+    #[sql_type = "Nullable<BigInt>"]
+    pub buy_quantity: Option<i64>,
+    #[sql_type = "Nullable<BigInt>"]
+    pub get_quantity: Option<i64>,
+    #[sql_type = "Nullable<BigInt>"]
+    pub max_order_discount_in_cents: Option<i64>,
+    #[sql_type = "Bool"]
+    pub waive_fees: bool,
     #[sql_type = "Timestamp"]
     pub created_at: NaiveDateTime,
     #[sql_type = "Timestamp"]
     pub updated_at: NaiveDateTime,
     #[sql_type = "Array<dUuid>"]
     pub ticket_type_ids: Vec<Uuid>,
+    #[diesel(embed)]
+    pub analytics: CodeAnalytics,
 }
 
 #[derive(AsChangeset, Default, Deserialize, Validate)]
@@ -82,6 +108,11 @@ pub struct UpdateCodeAttributes {
     pub start_date: Option<NaiveDateTime>,
     pub end_date: Option<NaiveDateTime>,
     pub max_tickets_per_user: Option<Option<i64>>,
+    pub min_quantity: Option<Option<i64>>,
+    pub buy_quantity: Option<Option<i64>>,
+    pub get_quantity: Option<Option<i64>>,
+    pub max_order_discount_in_cents: Option<Option<i64>>,
+    pub waive_fees: Option<bool>,
 }
 
 impl Code {
@@ -90,11 +121,12 @@ impl Code {
         event_id: Option<Uuid>,
         conn: &PgConnection,
     ) -> Result<CodeAvailability, DatabaseError> {
-        let code: Code = match event_id {
+        let code: Option<Code> = match event_id {
             Some(e) => codes::table
                 .filter(codes::redemption_code.eq(redemption_code.to_uppercase()))
                 .filter(codes::event_id.eq(e))
                 .first(conn)
+                .optional()
                 .to_db_error(
                     ErrorCode::QueryError,
                     "Could not load code with that redeem code",
@@ -102,15 +134,35 @@ impl Code {
             None => codes::table
                 .filter(codes::redemption_code.eq(redemption_code.to_uppercase()))
                 .first(conn)
+                .optional()
                 .to_db_error(
                     ErrorCode::QueryError,
                     "Could not load code with that redeem code",
                 )?,
         };
 
-        let available = code.max_uses - Code::find_number_of_uses(code.id, None, conn)?;
+        if let Some(code) = code {
+            let available = code.max_uses - Code::find_number_of_uses(code.id, None, conn)?;
+            return Ok(CodeAvailability {
+                code,
+                available,
+                unique_code_id: None,
+            });
+        }
+
+        let unique_code = UniqueCode::find_by_redemption_code(redemption_code, event_id, conn)?;
+        let code = Code::find(unique_code.code_id, conn)?;
+        let available = if UniqueCode::find_number_of_uses(unique_code.id, None, conn)? > 0 {
+            0
+        } else {
+            code.max_uses - Code::find_number_of_uses(code.id, None, conn)?
+        };
 
-        Ok(CodeAvailability { code, available })
+        Ok(CodeAvailability {
+            code,
+            available,
+            unique_code_id: Some(unique_code.id),
+        })
     }
 
     pub fn find_number_of_uses(
@@ -179,9 +231,15 @@ impl Code {
             start_date: self.start_date,
             end_date: self.end_date,
             max_tickets_per_user: self.max_tickets_per_user,
+            min_quantity: self.min_quantity,
+            buy_quantity: self.buy_quantity,
+            get_quantity: self.get_quantity,
+            max_order_discount_in_cents: self.max_order_discount_in_cents,
+            waive_fees: self.waive_fees,
             created_at: self.created_at,
             updated_at: self.updated_at,
             ticket_type_ids: ticket_type_ids,
+            analytics: CodeAnalytics::find_for_code(self, conn)?,
         })
     }
 
@@ -208,6 +266,41 @@ impl Code {
             start_date,
             end_date,
             max_tickets_per_user: max_tickets_per_user.map(|max| max as i64),
+            min_quantity: None,
+            buy_quantity: None,
+            get_quantity: None,
+            max_order_discount_in_cents: None,
+            waive_fees: false,
+        }
+    }
+
+    /// Number of tickets out of `quantity` that receive the discount
+    pub fn discounted_quantity(&self, quantity: i64) -> i64 {
+        if quantity < self.min_quantity.unwrap_or(0) {
+            return 0;
+        }
+
+        match (self.buy_quantity, self.get_quantity) {
+            (Some(buy_quantity), Some(get_quantity)) => {
+                let group_size = buy_quantity + get_quantity;
+                (quantity / group_size) * get_quantity
+                    + cmp::max(0, quantity % group_size - buy_quantity)
+            }
+            _ => quantity,
+        }
+    }
+
+    /// Discount given on each discounted ticket
+    pub fn discount_per_unit(&self, unit_price_in_cents: i64) -> i64 {
+        if let Some(discount_percent) = self.discount_as_percentage {
+            cmp::min(
+                ((unit_price_in_cents as f32) * (discount_percent as f32) / 100.0f32) as i64,
+                unit_price_in_cents,
+            )
+        } else if let Some(discount_in_cents) = self.discount_in_cents {
+            cmp::min(discount_in_cents, unit_price_in_cents)
+        } else {
+            0
         }
     }
 
@@ -265,10 +358,24 @@ impl Code {
                     codes.start_date,
                     codes.end_date,
                     codes.max_tickets_per_user,
+                    codes.min_quantity,
+                    codes.buy_quantity,
+                    codes.get_quantity,
+                    codes.max_order_discount_in_cents,
+                    codes.waive_fees,
                     codes.created_at,
                     codes.updated_at,
-                    array(select ticket_type_id from ticket_type_codes where ticket_type_codes.code_id = codes.id) as ticket_type_ids
+                    array(select ticket_type_id from ticket_type_codes where ticket_type_codes.code_id = codes.id) as ticket_type_ids,
+                    a.tickets_sold,
+                    a.gross_revenue_in_cents,
+                    a.discount_given_in_cents,
+                    a.net_revenue_in_cents,
+                    a.unique_buyers,
+                    a.applications,
+                    a.purchases,
+                    a.conversion_rate
                 FROM codes
+                INNER JOIN code_analytics($1, NULL, NULL, NULL, NULL, NULL) a ON a.code_id = codes.id
                 WHERE
                     codes.event_id = $1
                     AND ($2 IS NULL OR codes.code_type = $2)
@@ -320,6 +427,66 @@ impl Code {
         Ok(())
     }
 
+    // Validate that the quantity based discount rules are only used with Discount codes and that
+    // buy and get quantities are given together.
+    pub fn discount_rules_valid(
+        code_type: CodeTypes,
+        min_quantity: Option<i64>,
+        buy_quantity: Option<i64>,
+        get_quantity: Option<i64>,
+        max_order_discount_in_cents: Option<i64>,
+        waive_fees: bool,
+    ) -> Result<(), ValidationError> {
+        let has_rules = min_quantity.is_some()
+            || buy_quantity.is_some()
+            || get_quantity.is_some()
+            || max_order_discount_in_cents.is_some()
+            || waive_fees;
+        if code_type != CodeTypes::Discount && has_rules {
+            let mut validation_error = create_validation_error(
+                "discount_rules_require_discount_type",
+                "Discount rules can only be used with the Discount code type",
+            );
+            validation_error.add_param(Cow::from("code_type"), &code_type);
+            return Err(validation_error);
+        }
+
+        if buy_quantity.is_some() != get_quantity.is_some() {
+            let mut validation_error = create_validation_error(
+                "buy_and_get_quantity_required",
+                "Buy quantity and get quantity must be provided together",
+            );
+            validation_error.add_param(Cow::from("buy_quantity"), &buy_quantity);
+            validation_error.add_param(Cow::from("get_quantity"), &get_quantity);
+            return Err(validation_error);
+        }
+
+        if vec![
+            min_quantity,
+            buy_quantity,
+            get_quantity,
+            max_order_discount_in_cents,
+        ]
+        .into_iter()
+        .any(|value| value.map(|v| v <= 0).unwrap_or(false))
+        {
+            let mut validation_error = create_validation_error(
+                "must_be_positive",
+                "Discount rule values must be greater than zero",
+            );
+            validation_error.add_param(Cow::from("min_quantity"), &min_quantity);
+            validation_error.add_param(Cow::from("buy_quantity"), &buy_quantity);
+            validation_error.add_param(Cow::from("get_quantity"), &get_quantity);
+            validation_error.add_param(
+                Cow::from("max_order_discount_in_cents"),
+                &max_order_discount_in_cents,
+            );
+            return Err(validation_error);
+        }
+
+        Ok(())
+    }
+
     fn validate_record(
         &self,
         update_attrs: &UpdateCodeAttributes,
@@ -348,6 +515,20 @@ impl Code {
                     .unwrap_or(self.discount_as_percentage),
             ),
         );
+        validation_errors = validators::append_validation_error(
+            validation_errors,
+            "discount_rules",
+            Code::discount_rules_valid(
+                self.code_type.clone(),
+                update_attrs.min_quantity.unwrap_or(self.min_quantity),
+                update_attrs.buy_quantity.unwrap_or(self.buy_quantity),
+                update_attrs.get_quantity.unwrap_or(self.get_quantity),
+                update_attrs
+                    .max_order_discount_in_cents
+                    .unwrap_or(self.max_order_discount_in_cents),
+                update_attrs.waive_fees.unwrap_or(self.waive_fees),
+            ),
+        );
         validation_errors = validators::append_validation_error(
             validation_errors,
             "redemption_code",
@@ -367,18 +548,31 @@ impl Code {
 
     pub fn update(
         &self,
+        current_user_id: Option<Uuid>,
         update_attrs: UpdateCodeAttributes,
         conn: &PgConnection,
     ) -> Result<Code, DatabaseError> {
         self.validate_record(&update_attrs, conn)?;
-        diesel::update(
+        let code: Code = diesel::update(
             codes::table
                 .filter(codes::id.eq(self.id))
                 .filter(codes::updated_at.eq(self.updated_at)),
         )
         .set((update_attrs, codes::updated_at.eq(dsl::now)))
         .get_result(conn)
-        .to_db_error(ErrorCode::UpdateError, "Could not update code")
+        .to_db_error(ErrorCode::UpdateError, "Could not update code")?;
+
+        DomainEvent::create(
+            DomainEventTypes::CodeUpdated,
+            format!("Code {} updated", code.name),
+            Tables::Codes,
+            Some(self.id),
+            current_user_id,
+            Some(json!({ "changes": audit::changes(self, &code, &[]) })),
+        )
+        .commit(conn)?;
+
+        Ok(code)
     }
 
     pub fn find(id: Uuid, conn: &PgConnection) -> Result<Code, DatabaseError> {
@@ -414,6 +608,11 @@ pub struct NewCode {
     pub start_date: NaiveDateTime,
     pub end_date: NaiveDateTime,
     pub max_tickets_per_user: Option<i64>,
+    pub min_quantity: Option<i64>,
+    pub buy_quantity: Option<i64>,
+    pub get_quantity: Option<i64>,
+    pub max_order_discount_in_cents: Option<i64>,
+    pub waive_fees: bool,
 }
 
 impl NewCode {
@@ -443,6 +642,18 @@ impl NewCode {
             "start_date",
             validators::start_date_valid(self.start_date, self.end_date),
         );
+        validation_errors = validators::append_validation_error(
+            validation_errors,
+            "discount_rules",
+            Code::discount_rules_valid(
+                self.code_type.clone(),
+                self.min_quantity,
+                self.buy_quantity,
+                self.get_quantity,
+                self.max_order_discount_in_cents,
+                self.waive_fees,
+            ),
+        );
         validation_errors = validators::append_validation_error(
             validation_errors,
             "redemption_code",
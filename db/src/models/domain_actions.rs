@@ -9,6 +9,9 @@ use utils::dates;
 use utils::errors::*;
 use uuid::Uuid;
 
+/// Delay before the first retry of a failed refund, doubled for each further attempt
+const RETRY_BACKOFF_SECONDS: i64 = 30;
+
 #[derive(Clone, Debug, Serialize, PartialEq, Identifiable, Queryable, QueryableByName)]
 #[table_name = "domain_actions"]
 pub struct DomainAction {
@@ -127,6 +130,36 @@ impl DomainAction {
             .to_db_error(ErrorCode::QueryError, "Error loading domain actions")
     }
 
+    /// Actions of `action_type` for the given main table record, most recent first
+    pub fn find_by_main_table(
+        action_type: DomainActionTypes,
+        main_table: String,
+        main_table_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<DomainAction>, DatabaseError> {
+        domain_actions::table
+            .filter(domain_actions::domain_action_type.eq(action_type))
+            .filter(domain_actions::main_table.eq(main_table))
+            .filter(domain_actions::main_table_id.eq(main_table_id))
+            .order_by(domain_actions::created_at.desc())
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Error loading domain actions")
+    }
+
+    /// Pending actions of `action_type` including those scheduled for later
+    pub fn find_scheduled(
+        action_type: DomainActionTypes,
+        conn: &PgConnection,
+    ) -> Result<Vec<DomainAction>, DatabaseError> {
+        domain_actions::table
+            .filter(domain_actions::domain_action_type.eq(action_type))
+            .filter(domain_actions::status.eq(DomainActionStatus::Pending))
+            .filter(domain_actions::expires_at.gt(dsl::now))
+            .order_by(domain_actions::scheduled_at.asc())
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Error loading domain actions")
+    }
+
     pub fn find(id: Uuid, conn: &PgConnection) -> Result<DomainAction, DatabaseError> {
         DatabaseError::wrap(
             ErrorCode::QueryError,
@@ -185,6 +218,16 @@ impl DomainAction {
                 ))
                 .get_result(conn)
                 .to_db_error(ErrorCode::UpdateError, "Could not update Domain Action")
+        } else if let Some(blocked_until) = self.retry_blocked_until() {
+            diesel::update(self)
+                .set((
+                    domain_actions::last_failure_reason.eq(reason),
+                    domain_actions::attempt_count.eq(self.attempt_count + 1),
+                    domain_actions::blocked_until.eq(blocked_until),
+                    domain_actions::updated_at.eq(dsl::now),
+                ))
+                .get_result(conn)
+                .to_db_error(ErrorCode::UpdateError, "Could not update Domain Action")
         } else {
             // Intentionally leave checked out
             diesel::update(self)
@@ -198,6 +241,20 @@ impl DomainAction {
         }
     }
 
+    /// Refunds and blockchain syncs call external services so their retries are delayed by
+    /// `RETRY_BACKOFF_SECONDS`, doubling with each attempt. Other actions stay checked out.
+    fn retry_blocked_until(&self) -> Option<NaiveDateTime> {
+        match self.domain_action_type {
+            DomainActionTypes::RefundCancelledEventOrder | DomainActionTypes::BlockchainSync => {
+                Some(
+                    Utc::now().naive_utc()
+                        + Duration::seconds(RETRY_BACKOFF_SECONDS << self.attempt_count.min(10)),
+                )
+            }
+            _ => None,
+        }
+    }
+
     /// Call this method to indicate that the action has errored and should not be retried.
     /// If there is a chance that the action could succeed at a later stage, use `failed()`
     /// instead
@@ -217,6 +274,40 @@ impl DomainAction {
             .to_db_error(ErrorCode::UpdateError, "Could not update Domain Action")
     }
 
+    /// Puts the action back in the queue until `blocked_until` without counting it as an
+    /// attempt, for actions that have to wait on other work before they can run
+    pub fn reschedule(
+        &self,
+        blocked_until: NaiveDateTime,
+        conn: &PgConnection,
+    ) -> Result<DomainAction, DatabaseError> {
+        diesel::update(self)
+            .set((
+                domain_actions::blocked_until.eq(blocked_until),
+                domain_actions::updated_at.eq(dsl::now),
+            ))
+            .get_result(conn)
+            .to_db_error(ErrorCode::UpdateError, "Could not update Domain Action")
+    }
+
+    /// Requeues an action that exceeded its retries or errored, resetting its attempts
+    pub fn retry(
+        &self,
+        expires_at: NaiveDateTime,
+        conn: &PgConnection,
+    ) -> Result<DomainAction, DatabaseError> {
+        diesel::update(self)
+            .set((
+                domain_actions::status.eq(DomainActionStatus::Pending),
+                domain_actions::attempt_count.eq(0),
+                domain_actions::expires_at.eq(expires_at),
+                domain_actions::blocked_until.eq(dates::now().add_seconds(-30).finish()),
+                domain_actions::updated_at.eq(dsl::now),
+            ))
+            .get_result(conn)
+            .to_db_error(ErrorCode::UpdateError, "Could not update Domain Action")
+    }
+
     pub fn update(
         &self,
         attributes: &DomainActionEditableAttributes,
@@ -1,9 +1,11 @@
 use chrono::prelude::*;
 use diesel;
 use diesel::expression::dsl;
+use diesel::pg::Pg;
 use diesel::prelude::*;
 use log::Level::Info;
 use models::enums::*;
+use models::{Paging, Payload};
 use schema::*;
 use serde_json;
 use std::cmp::Ordering;
@@ -22,6 +24,28 @@ pub struct DomainEvent {
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
     pub user_id: Option<Uuid>,
+    pub organization_id: Option<Uuid>,
+}
+
+/// Domain event as listed in an organization's audit log
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct DisplayAuditLogEntry {
+    pub id: Uuid,
+    pub event_type: DomainEventTypes,
+    pub display_text: String,
+    pub event_data: Option<serde_json::Value>,
+    pub main_table: Tables,
+    pub main_id: Option<Uuid>,
+    pub user: Option<AuditLogUser>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Queryable, Serialize)]
+pub struct AuditLogUser {
+    pub id: Uuid,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub email: Option<String>,
 }
 
 impl PartialOrd for DomainEvent {
@@ -46,6 +70,7 @@ impl DomainEvent {
             main_table,
             main_id,
             user_id,
+            organization_id: None,
         }
     }
 
@@ -70,6 +95,84 @@ impl DomainEvent {
             .to_db_error(ErrorCode::QueryError, "Could not load domain events")
     }
 
+    /// Audit log for an organization, newest first. Payment events are listed without their data as
+    /// it holds the payment provider's raw responses.
+    pub fn find_for_organization(
+        organization_id: Uuid,
+        user_id: Option<Uuid>,
+        main_table: Option<Tables>,
+        event_type: Option<DomainEventTypes>,
+        start_utc: Option<NaiveDateTime>,
+        end_utc: Option<NaiveDateTime>,
+        page: u32,
+        limit: u32,
+        conn: &PgConnection,
+    ) -> Result<Payload<DisplayAuditLogEntry>, DatabaseError> {
+        let filtered = |query: domain_events::BoxedQuery<'static, Pg>| {
+            let mut query = query.filter(domain_events::organization_id.eq(organization_id));
+            if let Some(user_id) = user_id {
+                query = query.filter(domain_events::user_id.eq(user_id));
+            }
+            if let Some(main_table) = main_table {
+                query = query.filter(domain_events::main_table.eq(main_table));
+            }
+            if let Some(event_type) = event_type {
+                query = query.filter(domain_events::event_type.eq(event_type));
+            }
+            if let Some(start_utc) = start_utc {
+                query = query.filter(domain_events::created_at.ge(start_utc));
+            }
+            if let Some(end_utc) = end_utc {
+                query = query.filter(domain_events::created_at.le(end_utc));
+            }
+            query
+        };
+
+        let total: i64 = filtered(domain_events::table.into_boxed())
+            .count()
+            .get_result(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not count audit log entries")?;
+
+        let domain_events: Vec<DomainEvent> = filtered(domain_events::table.into_boxed())
+            .order_by(domain_events::created_at.desc())
+            .then_order_by(domain_events::id)
+            .limit(limit as i64)
+            .offset(limit as i64 * page as i64)
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load audit log entries")?;
+
+        let user_ids: Vec<Uuid> = domain_events.iter().filter_map(|e| e.user_id).collect();
+        let users: Vec<AuditLogUser> = users::table
+            .filter(users::id.eq_any(user_ids))
+            .select((users::id, users::first_name, users::last_name, users::email))
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load audit log users")?;
+
+        let data = domain_events
+            .into_iter()
+            .map(|domain_event| DisplayAuditLogEntry {
+                user: domain_event
+                    .user_id
+                    .and_then(|user_id| users.iter().find(|u| u.id == user_id).cloned()),
+                event_data: if domain_event.main_table == Tables::Payments {
+                    None
+                } else {
+                    domain_event.event_data
+                },
+                id: domain_event.id,
+                event_type: domain_event.event_type,
+                display_text: domain_event.display_text,
+                main_table: domain_event.main_table,
+                main_id: domain_event.main_id,
+                created_at: domain_event.created_at,
+            })
+            .collect();
+
+        let mut paging = Paging::new(page, limit);
+        paging.total = total as u64;
+        Ok(Payload { paging, data })
+    }
+
     pub fn find_unpublished(
         limit: u32,
         conn: &PgConnection,
@@ -105,6 +208,8 @@ pub struct NewDomainEvent {
     pub main_table: Tables,
     pub main_id: Option<Uuid>,
     pub user_id: Option<Uuid>,
+    /// Worked out from the main record when not set
+    pub organization_id: Option<Uuid>,
 }
 
 impl NewDomainEvent {
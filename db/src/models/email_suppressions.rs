@@ -0,0 +1,104 @@
+use chrono::NaiveDateTime;
+use diesel;
+use diesel::prelude::*;
+use models::*;
+use schema::email_suppressions;
+use utils::errors::*;
+use uuid::Uuid;
+
+/// Address that must not be emailed again because it unsubscribed, bounced or reported spam
+#[derive(Clone, Debug, Deserialize, Identifiable, PartialEq, Queryable, Serialize)]
+pub struct EmailSuppression {
+    pub id: Uuid,
+    pub email: String,
+    pub reason: EmailSuppressionReasons,
+    pub source: String,
+    pub details: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Insertable)]
+#[table_name = "email_suppressions"]
+pub struct NewEmailSuppression {
+    pub email: String,
+    pub reason: EmailSuppressionReasons,
+    pub source: String,
+    pub details: Option<String>,
+}
+
+impl NewEmailSuppression {
+    /// Suppresses the address, returning `None` if it was already suppressed for this reason
+    pub fn commit(&self, conn: &PgConnection) -> Result<Option<EmailSuppression>, DatabaseError> {
+        if self.email.is_empty() {
+            return DatabaseError::validation_error("email", "Email cannot be blank");
+        }
+
+        diesel::insert_into(email_suppressions::table)
+            .values(self)
+            .on_conflict_do_nothing()
+            .get_result(conn)
+            .optional()
+            .to_db_error(ErrorCode::InsertError, "Could not create email suppression")
+    }
+}
+
+impl EmailSuppression {
+    pub fn create(
+        email: &str,
+        reason: EmailSuppressionReasons,
+        source: &str,
+        details: Option<String>,
+    ) -> NewEmailSuppression {
+        NewEmailSuppression {
+            email: email.trim().to_lowercase(),
+            reason,
+            source: source.to_string(),
+            details,
+        }
+    }
+
+    /// Suppressions of the address, one for each reason it was suppressed for
+    pub fn find_by_email(
+        email: &str,
+        conn: &PgConnection,
+    ) -> Result<Vec<EmailSuppression>, DatabaseError> {
+        email_suppressions::table
+            .filter(email_suppressions::email.eq(email.trim().to_lowercase()))
+            .order_by(email_suppressions::created_at)
+            .then_order_by(email_suppressions::id)
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load email suppressions")
+    }
+
+    /// Returns the given addresses that are suppressed, lowercased. Unsubscribes only apply to
+    /// `marketing` sends, bounced and spam reporting addresses are suppressed for everything.
+    pub fn suppressed_emails(
+        emails: &[String],
+        marketing: bool,
+        conn: &PgConnection,
+    ) -> Result<Vec<String>, DatabaseError> {
+        if emails.is_empty() {
+            return Ok(Vec::new());
+        }
+        let emails: Vec<String> = emails.iter().map(|e| e.trim().to_lowercase()).collect();
+        let mut query = email_suppressions::table
+            .filter(email_suppressions::email.eq_any(emails))
+            .select(email_suppressions::email)
+            .distinct()
+            .into_boxed();
+        if !marketing {
+            query =
+                query.filter(email_suppressions::reason.ne(EmailSuppressionReasons::Unsubscribe));
+        }
+        query
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load email suppressions")
+    }
+
+    /// Lifts the suppression, e.g. when the user subscribes again
+    pub fn destroy(&self, conn: &PgConnection) -> Result<usize, DatabaseError> {
+        diesel::delete(self)
+            .execute(conn)
+            .to_db_error(ErrorCode::DeleteError, "Could not remove email suppression")
+    }
+}
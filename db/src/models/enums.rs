@@ -68,29 +68,48 @@ macro_rules! string_enum {
     }
 }
 
-string_enum! { AssetStatus [Unsynced] }
-string_enum! { BroadcastAudience [ PeopleAtTheEvent ]}
+string_enum! { AssetStatus [Unsynced, Pending, Synced, Failed] }
+string_enum! { BroadcastAudience [ PeopleAtTheEvent, FanSegment ]}
 string_enum! { CartItemStatus [CodeExpired, HoldExpired, TicketNullified, TicketNotReserved, Valid] }
 string_enum! { CodeTypes [Access, Discount] }
 string_enum! { CommunicationChannelType [Email, Sms, Push]}
 string_enum! { DomainEventTypes [
+    CodeUpdated,
+    CompIssued,
     EventArtistCreated,
     EventArtistAdded,
     EventCancelled,
+    EventCloned,
     EventCreated,
     EventPublished,
+    EventRefundsQueued,
+    EventRescheduled,
+    EventSeriesCreated,
+    EventSeriesDeleted,
+    EventSeriesUpdated,
     EventUpdated,
     EventUnpublished,
     FeeScheduleCreated,
     HoldCreated,
     HoldDeleted,
     HoldQuantityChanged,
+    HoldReleaseCancelled,
+    HoldReleaseScheduled,
+    HoldReleased,
+    HoldUpdated,
     OrderBehalfOfUserChanged,
     OrderCompleted,
     OrderCreated,
+    OrderManualRefundRequired,
+    OrderRefunded,
     OrderStatusUpdated,
     OrderUpdated,
     OrganizationCreated,
+    OrganizationEmailSettingsUpdated,
+    OrganizationUpdated,
+    OrganizationUserAdded,
+    OrganizationUserRemoved,
+    OrganizationUserRolesChanged,
     PaymentCancelled,
     PaymentCreated,
     PaymentCompleted,
@@ -98,11 +117,19 @@ string_enum! { DomainEventTypes [
     PaymentProviderIPN,
     PaymentMethodCreated,
     PaymentMethodUpdated,
+    PaymentMethodDeleted,
     PaymentUpdated,
+    UniqueCodesGenerated,
+    UserDeleted,
     UserLogin,
     UserRegistration,
     LostPassword,
     PurchaseCompleted,
+    RefundRequestApproved,
+    RefundRequestCreated,
+    RefundRequestDenied,
+    SettlementCreated,
+    SettlementDeleted,
     TransferTicketStarted,
     TransferTicketCancelled,
     TransferTicketCompleted,
@@ -110,44 +137,60 @@ string_enum! { DomainEventTypes [
     TicketInstanceNullified,
     TicketInstancePurchased,
     TicketInstanceRedeemed,
-    TicketInstanceReleasedFromHold
+    TicketInstanceReleasedFromHold,
+    TicketPricingUpdated,
+    TicketTypeUpdated
 ]}
 string_enum! { DomainActionTypes [
+    BlockchainSync,
     BroadcastPushNotification,
     // Email/SMS/Push Communication
     Communication,
+    DeleteUser,
     // Marketing Contacts
     MarketingContactsCreateEventList,
     MarketingContactsBulkEventFanListImport,
+    MarketingContactsSyncFanSegment,
     PaymentProviderIPN,
-    SendPurchaseCompletedCommunication
+    PaypalWebhook,
+    ReconcileBlockchainAssets,
+    RefundCancelledEventOrder,
+    ReleaseHoldInventory,
+    SendEventRescheduledCommunication,
+    SendPurchaseCompletedCommunication,
+    UpdateEventRecommendations
 
 ]}
 string_enum! { BroadcastStatus [Pending, InProgress, Completed, Cancelled]}
 string_enum! { BroadcastChannel [PushNotification]}
 string_enum! { BroadcastType [LastCall]}
 string_enum! { DomainActionStatus [Pending, RetriesExceeded, Errored, Success, Cancelled]}
+string_enum! { EmailSuppressionReasons [Bounce, SpamReport, Unsubscribe] }
 string_enum! { EventStatus [Draft,Closed,Published,Offline]}
-string_enum! { EventSearchSortField [ Name, EventStart]}
+string_enum! { EventAvailability [Available, SoldOut]}
+string_enum! { EventSearchSortField [ Name, EventStart, Relevance, Distance]}
 string_enum! { EventOverrideStatus [PurchaseTickets,SoldOut,OnSaleSoon,TicketsAtTheDoor,Free,Rescheduled,Cancelled,OffSale,Ended]}
 string_enum! { EventTypes [ Music, Conference]}
 string_enum! { ExternalPaymentType [Cash, CreditCard, Voucher]}
 string_enum! { FanSortField [FirstName, LastName, Email, Phone, Orders, FirstOrder, LastOrder, Revenue] }
 string_enum! { HistoryType [Purchase]}
+string_enum! { HoldReleaseStatus [Pending, Released, Cancelled] }
 string_enum! { HoldTypes [Discount, Comp] }
 string_enum! { HoldStatus [Published, Deleted] }
+string_enum! { MarketingConsentSources [Checkout, Registration] }
 string_enum! { OrderStatus [Cancelled, Draft, Paid, PendingPayment] }
 string_enum! { OrderItemTypes [Tickets, PerUnitFees, EventFees, Discount]}
 string_enum! { OrderTypes [Cart, BackOffice] }
 string_enum! { PaymentMethods [CreditCard, External, Free, Provider] }
-string_enum! { PaymentProviders [External, Globee, Free, Stripe] }
-string_enum! { PaymentStatus [Authorized, Completed, Requested, Refunded, Unpaid, PendingConfirmation, Cancelled, Draft, Unknown, PendingIpn] }
+string_enum! { PaymentProviders [External, Globee, Free, Paypal, Stripe] }
+string_enum! { PaymentStatus [Authorized, Completed, Requested, Refunded, Unpaid, PendingConfirmation, Cancelled, Draft, Unknown, PendingIpn, RequiresAction] }
 string_enum! { PastOrUpcoming [Past,Upcoming]}
+string_enum! { RefundRequestStatus [Pending, Approved, Denied] }
 string_enum! { Roles [Admin, DoorPerson, OrgMember, OrgOwner, OrgAdmin, OrgBoxOffice, Promoter, PromoterReadOnly, User] }
 string_enum! { SettlementStatus[PendingSettlement, RequiresAudit, SettledInFull] }
 string_enum! { SettlementTransactionType[OrderItem, Manual, Report] }
 string_enum! { SortingDir[ Asc, Desc ] }
-string_enum! { Tables [Broadcasts, Events, EventArtists, FeeSchedules, Holds, Orders, Organizations, Payments, PaymentMethods, TicketInstances, Users] }
+string_enum! { Tables [Assets, Broadcasts, Codes, Events, EventArtists, EventReschedules, EventSeries, FanSegments, FeeSchedules, HoldReleases, Holds, Orders, Organizations, Payments, PaymentMethods, RefundRequests, Settlements, TicketInstances, TicketPricing, TicketTypes, Users] }
 string_enum! { TicketInstanceStatus [Available, Reserved, Purchased, Redeemed, Nullified]}
 string_enum! { TicketPricingStatus [Published, Deleted, Default] }
 string_enum! { TicketTypeStatus [NoActivePricing, Published, SoldOut, Cancelled] }
@@ -0,0 +1,258 @@
+use chrono::NaiveDateTime;
+use diesel;
+use diesel::dsl;
+use diesel::pg::types::sql_types::Array;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Double, Nullable, Text, Uuid as dUuid};
+use models::*;
+use schema::event_recommendations;
+use utils::errors::*;
+use uuid::Uuid;
+
+/// Recommendations kept for each user, best first
+const MAX_RECOMMENDATIONS_PER_USER: i64 = 50;
+
+#[derive(Clone, Debug, Deserialize, Identifiable, PartialEq, Queryable, Serialize)]
+pub struct EventRecommendation {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub event_id: Uuid,
+    pub score: f64,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct DisplayEventRecommendation {
+    #[serde(flatten)]
+    pub event: DisplayEvent,
+    pub score: f64,
+}
+
+/// Recalculations are split into batches of users, each batch continuing after the last user
+/// of the one before
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct UpdateEventRecommendationsPayload {
+    #[serde(default)]
+    pub after_user_id: Option<Uuid>,
+}
+
+#[derive(Insertable)]
+#[table_name = "event_recommendations"]
+struct NewEventRecommendation {
+    user_id: Uuid,
+    event_id: Uuid,
+    score: f64,
+}
+
+impl EventRecommendation {
+    /// Scores upcoming published events the user does not have tickets for. Artists the user has
+    /// shown interest in, bought tickets for or checked in to count the most, followed by
+    /// `related_spotify_ids`, their venues and regions, and events bought by fans who bought
+    /// tickets to the same events as the user.
+    pub fn calculate_for_user(
+        user_id: Uuid,
+        related_spotify_ids: &[String],
+        conn: &PgConnection,
+    ) -> Result<Vec<(Uuid, f64)>, DatabaseError> {
+        #[derive(QueryableByName)]
+        struct R {
+            #[sql_type = "dUuid"]
+            event_id: Uuid,
+            #[sql_type = "Double"]
+            score: f64,
+        }
+
+        let query = include_str!("../queries/calculate_event_recommendations.sql");
+        let results: Vec<R> = diesel::sql_query(query)
+            .bind::<dUuid, _>(user_id)
+            .bind::<Array<Text>, _>(related_spotify_ids)
+            .bind::<BigInt, _>(MAX_RECOMMENDATIONS_PER_USER)
+            .get_results(conn)
+            .to_db_error(
+                ErrorCode::QueryError,
+                "Could not calculate event recommendations",
+            )?;
+
+        Ok(results.into_iter().map(|r| (r.event_id, r.score)).collect())
+    }
+
+    /// Replaces the recommendations cached for the user
+    pub fn update_for_user(
+        user_id: Uuid,
+        related_spotify_ids: &[String],
+        conn: &PgConnection,
+    ) -> Result<Vec<EventRecommendation>, DatabaseError> {
+        let scores = EventRecommendation::calculate_for_user(user_id, related_spotify_ids, conn)?;
+
+        diesel::delete(
+            event_recommendations::table.filter(event_recommendations::user_id.eq(user_id)),
+        )
+        .execute(conn)
+        .to_db_error(
+            ErrorCode::DeleteError,
+            "Could not remove event recommendations",
+        )?;
+
+        if scores.is_empty() {
+            return Ok(Vec::new());
+        }
+        let recommendations: Vec<NewEventRecommendation> = scores
+            .into_iter()
+            .map(|(event_id, score)| NewEventRecommendation {
+                user_id,
+                event_id,
+                score,
+            })
+            .collect();
+        diesel::insert_into(event_recommendations::table)
+            .values(&recommendations)
+            .get_results(conn)
+            .to_db_error(
+                ErrorCode::InsertError,
+                "Could not create event recommendations",
+            )
+    }
+
+    /// Cached recommendations for events that are still upcoming and that the user has not bought
+    /// tickets for since they were calculated
+    pub fn find_for_user(
+        user_id: Uuid,
+        page: u32,
+        limit: u32,
+        conn: &PgConnection,
+    ) -> Result<Payload<DisplayEventRecommendation>, DatabaseError> {
+        use schema::*;
+
+        let query = || {
+            let owned_event_ids = ticket_instances::table
+                .inner_join(wallets::table.on(wallets::id.eq(ticket_instances::wallet_id)))
+                .inner_join(assets::table.on(assets::id.eq(ticket_instances::asset_id)))
+                .inner_join(ticket_types::table.on(ticket_types::id.eq(assets::ticket_type_id)))
+                .filter(wallets::user_id.eq(user_id))
+                .filter(ticket_instances::status.eq_any(vec![
+                    TicketInstanceStatus::Purchased,
+                    TicketInstanceStatus::Redeemed,
+                ]))
+                .select(ticket_types::event_id);
+
+            event_recommendations::table
+                .inner_join(events::table)
+                .filter(event_recommendations::user_id.eq(user_id))
+                .filter(events::event_start.gt(dsl::now.nullable()))
+                .filter(events::status.eq(EventStatus::Published))
+                .filter(events::cancelled_at.is_null())
+                .filter(dsl::not(events::id.eq_any(owned_event_ids)))
+        };
+
+        let total: i64 = query().count().get_result(conn).to_db_error(
+            ErrorCode::QueryError,
+            "Could not count event recommendations",
+        )?;
+
+        let results: Vec<(Event, f64)> = query()
+            .order_by(event_recommendations::score.desc())
+            .then_order_by(events::event_start.asc())
+            .limit(limit as i64)
+            .offset((limit * page) as i64)
+            .select((events::all_columns, event_recommendations::score))
+            .load(conn)
+            .to_db_error(
+                ErrorCode::QueryError,
+                "Could not load event recommendations",
+            )?;
+
+        let mut recommendations = Vec::new();
+        for (event, score) in results {
+            recommendations.push(DisplayEventRecommendation {
+                event: event.for_display(conn)?,
+                score,
+            });
+        }
+
+        let mut paging = Paging::new(page, limit);
+        paging.total = total as u64;
+        Ok(Payload {
+            paging,
+            data: recommendations,
+        })
+    }
+
+    /// Spotify ids of artists at events the user has shown interest in or bought tickets for,
+    /// used to find related artists
+    pub fn affinity_spotify_ids(
+        user_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<String>, DatabaseError> {
+        #[derive(QueryableByName)]
+        struct R {
+            #[sql_type = "Text"]
+            spotify_id: String,
+        }
+
+        let query = r#"
+            SELECT DISTINCT a.spotify_id
+            FROM artists a
+            JOIN event_artists ea ON ea.artist_id = a.id
+            WHERE a.spotify_id IS NOT NULL
+            AND ea.event_id IN (
+                SELECT ei.event_id FROM event_interest ei WHERE ei.user_id = $1
+                UNION
+                SELECT tt.event_id
+                FROM ticket_instances ti
+                JOIN wallets w ON w.id = ti.wallet_id
+                JOIN assets ast ON ast.id = ti.asset_id
+                JOIN ticket_types tt ON tt.id = ast.ticket_type_id
+                WHERE w.user_id = $1
+                AND ti.status IN ('Purchased', 'Redeemed')
+            );
+        "#;
+
+        let results: Vec<R> = diesel::sql_query(query)
+            .bind::<dUuid, _>(user_id)
+            .get_results(conn)
+            .to_db_error(
+                ErrorCode::QueryError,
+                "Could not load artists for event recommendations",
+            )?;
+        Ok(results.into_iter().map(|r| r.spotify_id).collect())
+    }
+
+    /// Users who have shown interest in an event or bought tickets, for whom recommendations can
+    /// be calculated. Returned in id order, `limit` at a time starting after `after_user_id`.
+    pub fn find_users_to_update(
+        after_user_id: Option<Uuid>,
+        limit: u32,
+        conn: &PgConnection,
+    ) -> Result<Vec<Uuid>, DatabaseError> {
+        #[derive(QueryableByName)]
+        struct R {
+            #[sql_type = "dUuid"]
+            user_id: Uuid,
+        }
+
+        let query = r#"
+            SELECT u.user_id FROM (
+                SELECT ei.user_id FROM event_interest ei
+                UNION
+                SELECT w.user_id
+                FROM ticket_instances ti
+                JOIN wallets w ON w.id = ti.wallet_id
+                WHERE w.user_id IS NOT NULL
+                AND ti.status IN ('Purchased', 'Redeemed')
+            ) u
+            WHERE $1 IS NULL OR u.user_id > $1
+            ORDER BY u.user_id
+            LIMIT $2;
+        "#;
+
+        let results: Vec<R> = diesel::sql_query(query)
+            .bind::<Nullable<dUuid>, _>(after_user_id)
+            .bind::<BigInt, _>(limit as i64)
+            .get_results(conn)
+            .to_db_error(
+                ErrorCode::QueryError,
+                "Could not load users for event recommendations",
+            )?;
+        Ok(results.into_iter().map(|r| r.user_id).collect())
+    }
+}
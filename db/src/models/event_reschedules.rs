@@ -0,0 +1,84 @@
+use chrono::prelude::*;
+use diesel;
+use diesel::prelude::*;
+use models::*;
+use schema::event_reschedules;
+use utils::errors::*;
+use uuid::Uuid;
+
+#[derive(Clone, Debug, Deserialize, Identifiable, PartialEq, Queryable, Serialize)]
+pub struct EventReschedule {
+    pub id: Uuid,
+    pub event_id: Uuid,
+    pub previous_event_start: NaiveDateTime,
+    pub new_event_start: NaiveDateTime,
+    pub refund_window_ends_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl EventReschedule {
+    pub fn create(
+        event_id: Uuid,
+        previous_event_start: NaiveDateTime,
+        new_event_start: NaiveDateTime,
+        refund_window_ends_at: Option<NaiveDateTime>,
+    ) -> NewEventReschedule {
+        NewEventReschedule {
+            event_id,
+            previous_event_start,
+            new_event_start,
+            refund_window_ends_at,
+        }
+    }
+
+    pub fn find(id: Uuid, conn: &PgConnection) -> Result<EventReschedule, DatabaseError> {
+        event_reschedules::table
+            .find(id)
+            .first(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load event reschedule")
+    }
+
+    /// Reschedules of the event, most recent first
+    pub fn find_for_event(
+        event_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<EventReschedule>, DatabaseError> {
+        event_reschedules::table
+            .filter(event_reschedules::event_id.eq(event_id))
+            .order_by(event_reschedules::created_at.desc())
+            .load(conn)
+            .to_db_error(
+                ErrorCode::QueryError,
+                "Could not load reschedules for event",
+            )
+    }
+
+    pub fn refund_window_open(&self) -> bool {
+        self.refund_window_ends_at
+            .map(|ends_at| ends_at > Utc::now().naive_utc())
+            .unwrap_or(false)
+    }
+
+    pub fn event(&self, conn: &PgConnection) -> Result<Event, DatabaseError> {
+        Event::find(self.event_id, conn)
+    }
+}
+
+#[derive(Insertable, Serialize)]
+#[table_name = "event_reschedules"]
+pub struct NewEventReschedule {
+    pub event_id: Uuid,
+    pub previous_event_start: NaiveDateTime,
+    pub new_event_start: NaiveDateTime,
+    pub refund_window_ends_at: Option<NaiveDateTime>,
+}
+
+impl NewEventReschedule {
+    pub fn commit(self, conn: &PgConnection) -> Result<EventReschedule, DatabaseError> {
+        diesel::insert_into(event_reschedules::table)
+            .values(&self)
+            .get_result(conn)
+            .to_db_error(ErrorCode::InsertError, "Could not create event reschedule")
+    }
+}
@@ -0,0 +1,357 @@
+use chrono::prelude::*;
+use chrono::Duration;
+use chrono_tz::Tz;
+use diesel;
+use diesel::dsl;
+use diesel::prelude::*;
+use models::*;
+use schema::{event_series, events};
+use utils::errors::*;
+use utils::recurrence::RecurrenceRule;
+use uuid::Uuid;
+
+#[derive(Clone, Debug, Deserialize, Identifiable, PartialEq, Queryable, Serialize)]
+#[table_name = "event_series"]
+pub struct EventSeries {
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    pub template_event_id: Uuid,
+    pub name: String,
+    pub recurrence_rule: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(AsChangeset, Default, Deserialize)]
+#[table_name = "event_series"]
+pub struct EventSeriesEditableAttributes {
+    pub name: Option<String>,
+    pub recurrence_rule: Option<String>,
+}
+
+impl EventSeries {
+    /// Creates a series that repeats `template_event` according to `recurrence_rule`, an RRULE
+    /// such as `FREQ=WEEKLY;BYDAY=FR;COUNT=10`. Occurrences recur in the venue's timezone.
+    pub fn create(template_event: &Event, name: String, recurrence_rule: String) -> NewEventSeries {
+        NewEventSeries {
+            organization_id: template_event.organization_id,
+            template_event_id: template_event.id,
+            name,
+            recurrence_rule,
+        }
+    }
+
+    pub fn find(id: Uuid, conn: &PgConnection) -> Result<EventSeries, DatabaseError> {
+        event_series::table
+            .find(id)
+            .first(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load event series")
+    }
+
+    pub fn find_for_organization(
+        organization_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<EventSeries>, DatabaseError> {
+        event_series::table
+            .filter(event_series::organization_id.eq(organization_id))
+            .order_by(event_series::name)
+            .load(conn)
+            .to_db_error(
+                ErrorCode::QueryError,
+                "Could not load event series for organization",
+            )
+    }
+
+    pub fn organization(&self, conn: &PgConnection) -> Result<Organization, DatabaseError> {
+        Organization::find(self.organization_id, conn)
+    }
+
+    pub fn template_event(&self, conn: &PgConnection) -> Result<Event, DatabaseError> {
+        Event::find(self.template_event_id, conn)
+    }
+
+    pub fn events(&self, conn: &PgConnection) -> Result<Vec<Event>, DatabaseError> {
+        events::table
+            .filter(events::event_series_id.eq(self.id))
+            .order_by(events::event_start.asc())
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load events for series")
+    }
+
+    pub fn parsed_recurrence_rule(&self) -> Result<RecurrenceRule, DatabaseError> {
+        self.recurrence_rule.parse()
+    }
+
+    /// Creates the occurrences of the series that do not exist yet by duplicating the template
+    /// event. Returns the newly created events.
+    pub fn generate_events(
+        &self,
+        current_user_id: Option<Uuid>,
+        conn: &PgConnection,
+    ) -> Result<Vec<Event>, DatabaseError> {
+        let rule = self.parsed_recurrence_rule()?;
+        let template = self.template_event(conn)?;
+        let template_start = match template.event_start {
+            Some(event_start) => event_start,
+            None => {
+                return DatabaseError::validation_error(
+                    "event_start",
+                    "Template event must have a start date",
+                );
+            }
+        };
+        let timezone: Tz = match template.venue(conn)? {
+            Some(venue) => venue.timezone.parse().unwrap_or(Tz::UTC),
+            None => Tz::UTC,
+        };
+
+        let existing_starts: Vec<Option<NaiveDateTime>> = self
+            .events(conn)?
+            .into_iter()
+            .map(|e| e.event_start)
+            .collect();
+        let local_start = timezone.from_utc_datetime(&template_start).naive_local();
+
+        let mut result = Vec::new();
+        for occurrence in rule.occurrences(local_start) {
+            // Occurrences that fall in a daylight savings gap do not exist locally
+            let event_start = match timezone.from_local_datetime(&occurrence).earliest() {
+                Some(event_start) => event_start.naive_utc(),
+                None => continue,
+            };
+            if existing_starts.contains(&Some(event_start)) {
+                continue;
+            }
+            result.push(template.duplicate(current_user_id, event_start, Some(self.id), conn)?);
+        }
+
+        Ok(result)
+    }
+
+    /// Applies `attributes` to every occurrence that has not started yet and copies the
+    /// template event's ticket types and pricing to them. Dates are specific to each occurrence
+    /// so they are not propagated, ticket dates are shifted along with the occurrence.
+    pub fn update_future_events(
+        &self,
+        current_user_id: Option<Uuid>,
+        attributes: EventEditableAttributes,
+        conn: &PgConnection,
+    ) -> Result<Vec<Event>, DatabaseError> {
+        let template = self.template_event(conn)?;
+        let attributes = EventEditableAttributes {
+            event_start: None,
+            door_time: None,
+            event_end: None,
+            publish_date: None,
+            redeem_date: None,
+            cancelled_at: None,
+            ..attributes
+        };
+
+        let now = Utc::now().naive_utc();
+        let mut result = Vec::new();
+        for event in self.events(conn)? {
+            if event.cancelled_at.is_some() || event.event_start.map(|s| s <= now).unwrap_or(true) {
+                continue;
+            }
+            if event.id != template.id {
+                let offset = event.event_start.unwrap_or(now) - template.event_start.unwrap_or(now);
+                EventSeries::sync_ticket_types(current_user_id, &template, &event, offset, conn)?;
+            }
+            result.push(event.update(current_user_id, attributes.clone(), conn)?);
+        }
+
+        Ok(result)
+    }
+
+    /// Copies the settings and pricing of the template's ticket types to the matching ticket
+    /// types of an occurrence starting `offset` after the template. Ticket types and pricing
+    /// are matched by name. Capacity changes and new ticket types are not propagated as they
+    /// need blockchain assets.
+    fn sync_ticket_types(
+        current_user_id: Option<Uuid>,
+        template: &Event,
+        event: &Event,
+        offset: Duration,
+        conn: &PgConnection,
+    ) -> Result<(), DatabaseError> {
+        let ticket_types = event.ticket_types(false, None, conn)?;
+        for template_ticket_type in template.ticket_types(false, None, conn)? {
+            if template_ticket_type.status == TicketTypeStatus::Cancelled {
+                continue;
+            }
+            let ticket_type = match ticket_types
+                .iter()
+                .find(|tt| tt.name == template_ticket_type.name)
+            {
+                Some(ticket_type) => ticket_type,
+                None => continue,
+            };
+            let ticket_type = ticket_type.update(
+                current_user_id,
+                TicketTypeEditableAttributes {
+                    name: None,
+                    description: Some(template_ticket_type.description.clone()),
+                    start_date: Some(template_ticket_type.start_date + offset),
+                    end_date: Some(template_ticket_type.end_date + offset),
+                    increment: Some(template_ticket_type.increment),
+                    limit_per_person: Some(template_ticket_type.limit_per_person),
+                    price_in_cents: Some(template_ticket_type.price_in_cents),
+                    sold_out_behavior: Some(template_ticket_type.sold_out_behavior),
+                    is_private: Some(template_ticket_type.is_private),
+                    refundable: Some(template_ticket_type.refundable),
+                },
+                conn,
+            )?;
+
+            let template_pricing = template_ticket_type.ticket_pricing(conn)?;
+            let ticket_pricing = ticket_type.ticket_pricing(conn)?;
+            for pricing in ticket_pricing.iter() {
+                if !template_pricing.iter().any(|tp| tp.name == pricing.name) {
+                    pricing.destroy(conn)?;
+                }
+            }
+            for template_pricing in template_pricing {
+                match ticket_pricing
+                    .iter()
+                    .find(|tp| tp.name == template_pricing.name)
+                {
+                    Some(pricing) => {
+                        pricing.update(
+                            current_user_id,
+                            TicketPricingEditableAttributes {
+                                name: None,
+                                price_in_cents: Some(template_pricing.price_in_cents),
+                                start_date: Some(template_pricing.start_date + offset),
+                                end_date: Some(template_pricing.end_date + offset),
+                                is_box_office_only: Some(template_pricing.is_box_office_only),
+                            },
+                            conn,
+                        )?;
+                    }
+                    None => {
+                        ticket_type.add_ticket_pricing(
+                            template_pricing.name,
+                            template_pricing.start_date + offset,
+                            template_pricing.end_date + offset,
+                            template_pricing.price_in_cents,
+                            template_pricing.is_box_office_only,
+                            None,
+                            conn,
+                        )?;
+                    }
+                }
+            }
+            ticket_type.validate_ticket_pricing(conn)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn update(
+        &self,
+        current_user_id: Option<Uuid>,
+        attributes: EventSeriesEditableAttributes,
+        conn: &PgConnection,
+    ) -> Result<EventSeries, DatabaseError> {
+        if let Some(ref recurrence_rule) = attributes.recurrence_rule {
+            recurrence_rule.parse::<RecurrenceRule>()?;
+        }
+
+        let result: EventSeries = diesel::update(self)
+            .set((&attributes, event_series::updated_at.eq(dsl::now)))
+            .get_result(conn)
+            .to_db_error(ErrorCode::UpdateError, "Could not update event series")?;
+
+        DomainEvent::create(
+            DomainEventTypes::EventSeriesUpdated,
+            format!("Event series '{}' updated", result.name),
+            Tables::EventSeries,
+            Some(self.id),
+            current_user_id,
+            Some(json!({"name": attributes.name, "recurrence_rule": attributes.recurrence_rule})),
+        )
+        .commit(conn)?;
+
+        Ok(result)
+    }
+
+    /// Removes the series, its events are kept as standalone events
+    pub fn destroy(
+        &self,
+        current_user_id: Option<Uuid>,
+        conn: &PgConnection,
+    ) -> Result<(), DatabaseError> {
+        diesel::delete(self)
+            .execute(conn)
+            .to_db_error(ErrorCode::DeleteError, "Could not remove event series")?;
+
+        DomainEvent::create(
+            DomainEventTypes::EventSeriesDeleted,
+            format!("Event series '{}' deleted", self.name),
+            Tables::EventSeries,
+            Some(self.id),
+            current_user_id,
+            None,
+        )
+        .commit(conn)?;
+
+        Ok(())
+    }
+}
+
+#[derive(Insertable, Serialize)]
+#[table_name = "event_series"]
+pub struct NewEventSeries {
+    pub organization_id: Uuid,
+    pub template_event_id: Uuid,
+    pub name: String,
+    pub recurrence_rule: String,
+}
+
+impl NewEventSeries {
+    pub fn commit(
+        &self,
+        current_user_id: Option<Uuid>,
+        conn: &PgConnection,
+    ) -> Result<EventSeries, DatabaseError> {
+        self.recurrence_rule.parse::<RecurrenceRule>()?;
+        let template = Event::find(self.template_event_id, conn)?;
+        if template.event_start.is_none() {
+            return DatabaseError::validation_error(
+                "event_start",
+                "Template event must have a start date",
+            );
+        }
+        if template.event_series_id.is_some() {
+            return DatabaseError::business_process_error(
+                "Event already belongs to an event series",
+            );
+        }
+
+        let result: EventSeries = diesel::insert_into(event_series::table)
+            .values(self)
+            .get_result(conn)
+            .to_db_error(ErrorCode::InsertError, "Could not create event series")?;
+
+        diesel::update(&template)
+            .set((
+                events::event_series_id.eq(result.id),
+                events::updated_at.eq(dsl::now),
+            ))
+            .execute(conn)
+            .to_db_error(ErrorCode::UpdateError, "Could not add event to series")?;
+
+        DomainEvent::create(
+            DomainEventTypes::EventSeriesCreated,
+            format!("Event series '{}' created", self.name),
+            Tables::EventSeries,
+            Some(result.id),
+            current_user_id,
+            Some(json!(self)),
+        )
+        .commit(conn)?;
+
+        Ok(result)
+    }
+}
@@ -6,7 +6,9 @@ use diesel::expression::dsl;
 use diesel::expression::sql_literal::sql;
 use diesel::pg::types::sql_types::Array;
 use diesel::prelude::*;
-use diesel::sql_types::{BigInt, Bool, Date, Integer, Nullable, Text, Timestamp, Uuid as dUuid};
+use diesel::sql_types::{
+    BigInt, Bool, Date, Double, Integer, Nullable, Text, Timestamp, Uuid as dUuid,
+};
 use log::Level;
 use models::*;
 use schema::{
@@ -14,12 +16,15 @@ use schema::{
     payments, ticket_types, venues,
 };
 use serde::Deserializer;
+use serde_json;
 use serde_json::Value;
 use serde_with::rust::double_option;
 use std::borrow::Cow;
-use std::cmp::Ordering;
+use std::cmp::{self, Ordering};
 use std::collections::HashMap;
 use time::Duration;
+use utils::audit;
+use utils::dates;
 use utils::errors::*;
 use utils::text;
 use uuid::Uuid;
@@ -27,6 +32,51 @@ use validator::{Validate, ValidationErrors};
 use validators;
 use validators::*;
 
+/// Used to bound distance searches by latitude and longitude
+const KM_PER_DEGREE_OF_LATITUDE: f64 = 111.045;
+
+/// Whether an event has published tickets that are not held, sold or reserved
+const EVENT_AVAILABLE_SQL: &str = r#"EXISTS (
+    SELECT 1
+    FROM ticket_types tt
+    JOIN assets a ON a.ticket_type_id = tt.id
+    JOIN ticket_instances ti ON ti.asset_id = a.id
+    WHERE tt.event_id = events.id
+    AND tt.status = 'Published'
+    AND tt.is_private = false
+    AND ti.hold_id IS NULL
+    AND (ti.status = 'Available' OR (ti.status = 'Reserved' AND ti.reserved_until < now()))
+)"#;
+
+/// Current public price of each of an event's published ticket types, matching
+/// `Event::ticket_pricing_range_by_events`
+const EVENT_TICKET_PRICES_SQL: &str = r#"
+    SELECT tp.price_in_cents
+    FROM ticket_types tt
+    JOIN ticket_pricing tp ON tp.id = (
+        SELECT tp.id FROM ticket_pricing tp
+        WHERE tp.ticket_type_id = tt.id
+        AND tp.start_date < now()
+        AND tp.end_date > now()
+        AND tp.status IN ('Default', 'Published')
+        AND tp.is_box_office_only = false
+        ORDER BY tp.status DESC
+        LIMIT 1
+    )
+    WHERE tt.event_id = events.id
+    AND tt.is_private = false
+    AND tt.status = 'Published'
+    AND (tt.sold_out_behavior != 'Hide'
+        OR EXISTS (
+            SELECT 1
+            FROM ticket_instances ti
+            JOIN assets a ON a.id = ti.asset_id
+            WHERE a.ticket_type_id = tt.id
+            AND (ti.status = 'Available' OR (ti.status = 'Reserved' AND ti.reserved_until < now()))
+            AND ti.hold_id IS NULL
+        ))
+"#;
+
 #[derive(Associations, Identifiable, Queryable, AsChangeset)]
 #[belongs_to(Organization)]
 #[derive(Clone, QueryableByName, Serialize, Deserialize, PartialEq, Debug)]
@@ -62,6 +112,27 @@ pub struct Event {
     pub event_type: EventTypes,
     pub cover_image_url: Option<String>,
     pub private_access_code: Option<String>,
+    pub event_series_id: Option<Uuid>,
+}
+
+/// Search filters beyond the ones every caller of `Event::search` provides
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct EventSearchFilters {
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub radius_in_km: Option<f64>,
+    pub min_price_in_cents: Option<i64>,
+    pub max_price_in_cents: Option<i64>,
+    pub age_limit: Option<String>,
+    pub event_type: Option<EventTypes>,
+    pub availability: Option<EventAvailability>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct EventSearchFacets {
+    pub event_types: HashMap<String, i64>,
+    pub age_limits: HashMap<String, i64>,
+    pub availability: HashMap<String, i64>,
 }
 
 impl PartialOrd for Event {
@@ -133,6 +204,8 @@ pub struct NewEvent {
     pub event_type: EventTypes,
     #[serde(default, deserialize_with = "deserialize_unless_blank")]
     pub private_access_code: Option<String>,
+    #[serde(default, skip_deserializing)]
+    pub event_series_id: Option<Uuid>,
 }
 
 impl NewEvent {
@@ -191,7 +264,7 @@ impl NewEvent {
     }
 }
 
-#[derive(AsChangeset, Default, Deserialize, Validate, Serialize)]
+#[derive(AsChangeset, Clone, Default, Deserialize, Validate, Serialize)]
 #[table_name = "events"]
 pub struct EventEditableAttributes {
     pub name: Option<String>,
@@ -253,6 +326,30 @@ pub struct EventLocalizedTimeStrings {
     pub door_time: Option<String>,
 }
 
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RefundCancelledEventOrderPayload {
+    pub order_id: Uuid,
+}
+
+#[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct EventRefundProgress {
+    pub total: u32,
+    pub pending: u32,
+    pub succeeded: u32,
+    pub failed: u32,
+    pub failures: Vec<EventRefundFailure>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct EventRefundFailure {
+    pub domain_action_id: Uuid,
+    pub order_id: Uuid,
+    pub status: DomainActionStatus,
+    pub attempt_count: i64,
+    pub last_failure_reason: Option<String>,
+    pub failed_at: NaiveDateTime,
+}
+
 impl Event {
     pub fn create(
         name: &str,
@@ -339,8 +436,9 @@ impl Event {
             Tables::Events,
             Some(self.id),
             current_user_id,
-            Some(json!(&event)),
-        );
+            Some(json!({ "changes": audit::changes(self, &result, &[]) })),
+        )
+        .commit(conn)?;
 
         Ok(result)
     }
@@ -583,9 +681,300 @@ impl Event {
         )
         .commit(conn)?;
 
+        event.queue_refunds(current_user_id, conn)?;
+
         Ok(event)
     }
 
+    /// Queues a refund of this event's items for every paid order of the cancelled event. Orders
+    /// that already have a pending or successful refund are skipped, so calling this again only
+    /// requeues failed refunds. Refunded tickets are nullified instead of returning to inventory.
+    pub fn queue_refunds(
+        &self,
+        current_user_id: Option<Uuid>,
+        conn: &PgConnection,
+    ) -> Result<Vec<DomainAction>, DatabaseError> {
+        if self.cancelled_at.is_none() {
+            return DatabaseError::business_process_error(
+                "Refunds can only be queued for cancelled events",
+            );
+        }
+
+        for ticket_type in self.ticket_types(false, None, conn)? {
+            if ticket_type.status != TicketTypeStatus::Cancelled {
+                ticket_type.cancel(conn)?;
+            }
+        }
+
+        let queued_order_ids: Vec<Uuid> = self
+            .order_refund_actions(conn)?
+            .into_iter()
+            .filter(|(_, action)| !Event::refund_failed(action))
+            .map(|(order_id, _)| order_id)
+            .collect();
+
+        let order_ids: Vec<Uuid> = orders::table
+            .inner_join(order_items::table.on(order_items::order_id.eq(orders::id)))
+            .filter(order_items::event_id.eq(self.id))
+            .filter(orders::status.eq(OrderStatus::Paid))
+            .select(orders::id)
+            .distinct()
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load orders for event")?;
+
+        let mut result = Vec::new();
+        for order_id in order_ids {
+            if queued_order_ids.contains(&order_id) {
+                continue;
+            }
+            let mut action = DomainAction::create(
+                None,
+                DomainActionTypes::RefundCancelledEventOrder,
+                None,
+                json!(RefundCancelledEventOrderPayload { order_id }),
+                Some(Tables::Events.to_string()),
+                Some(self.id),
+            );
+            // Allow refunds to be retried through longer payment provider outages
+            action.expires_at = dates::now().add_days(7).finish();
+            action.max_attempt_count = 8;
+            result.push(action.commit(conn)?);
+        }
+
+        if !result.is_empty() {
+            DomainEvent::create(
+                DomainEventTypes::EventRefundsQueued,
+                format!("Refunds queued for {} orders", result.len()),
+                Tables::Events,
+                Some(self.id),
+                current_user_id,
+                Some(json!({ "domain_action_ids": result.iter().map(|a| a.id).collect::<Vec<Uuid>>() })),
+            )
+            .commit(conn)?;
+        }
+
+        Ok(result)
+    }
+
+    /// Progress of the refunds queued by `queue_refunds`. Each order is counted once, so a
+    /// failed refund that was requeued is no longer listed as a failure. Refunds that left
+    /// transferred tickets for a manual refund are listed as failures too.
+    pub fn refund_progress(
+        &self,
+        conn: &PgConnection,
+    ) -> Result<EventRefundProgress, DatabaseError> {
+        let mut progress = EventRefundProgress::default();
+        for (order_id, action) in self.order_refund_actions(conn)? {
+            progress.total += 1;
+            if action.status == DomainActionStatus::Success {
+                match self.manual_refund_required(order_id, &action, conn)? {
+                    Some(manual_refund) => {
+                        progress.failed += 1;
+                        progress.failures.push(EventRefundFailure {
+                            domain_action_id: action.id,
+                            order_id,
+                            status: action.status,
+                            attempt_count: action.attempt_count,
+                            last_failure_reason: Some(manual_refund.display_text),
+                            failed_at: manual_refund.created_at,
+                        });
+                    }
+                    None => progress.succeeded += 1,
+                }
+            } else if !Event::refund_failed(&action) {
+                progress.pending += 1;
+            } else {
+                progress.failed += 1;
+                progress.failures.push(EventRefundFailure {
+                    domain_action_id: action.id,
+                    order_id,
+                    status: action.status,
+                    attempt_count: action.attempt_count,
+                    last_failure_reason: action.last_failure_reason,
+                    failed_at: action.updated_at,
+                });
+            }
+        }
+
+        Ok(progress)
+    }
+
+    /// The refund action of each order, preferring refunds that succeeded or are still pending
+    /// over ones that failed and otherwise taking the most recent
+    fn order_refund_actions(
+        &self,
+        conn: &PgConnection,
+    ) -> Result<Vec<(Uuid, DomainAction)>, DatabaseError> {
+        let mut result: Vec<(Uuid, DomainAction)> = Vec::new();
+        for action in DomainAction::find_by_main_table(
+            DomainActionTypes::RefundCancelledEventOrder,
+            Tables::Events.to_string(),
+            self.id,
+            conn,
+        )? {
+            let payload: RefundCancelledEventOrderPayload =
+                serde_json::from_value(action.payload.clone())?;
+            match result.iter().position(|(id, _)| *id == payload.order_id) {
+                Some(index) => {
+                    if Event::refund_failed(&result[index].1) && !Event::refund_failed(&action) {
+                        result[index].1 = action;
+                    }
+                }
+                None => result.push((payload.order_id, action)),
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Latest note left by the refund `action` that some of the order's tickets for this event
+    /// were transferred so must be refunded manually
+    fn manual_refund_required(
+        &self,
+        order_id: Uuid,
+        action: &DomainAction,
+        conn: &PgConnection,
+    ) -> Result<Option<DomainEvent>, DatabaseError> {
+        Ok(DomainEvent::find(
+            Tables::Orders,
+            Some(order_id),
+            Some(DomainEventTypes::OrderManualRefundRequired),
+            conn,
+        )?
+        .into_iter()
+        .filter(|domain_event| {
+            domain_event.created_at >= action.created_at
+                && domain_event
+                    .event_data
+                    .as_ref()
+                    .map(|data| data["event_id"] == json!(self.id))
+                    .unwrap_or(false)
+        })
+        .last())
+    }
+
+    /// Refunds that errored, ran out of retries or expired before they could be processed
+    fn refund_failed(action: &DomainAction) -> bool {
+        match action.status {
+            DomainActionStatus::Success => false,
+            DomainActionStatus::Pending => action.expires_at <= Utc::now().naive_utc(),
+            _ => true,
+        }
+    }
+
+    /// Moves the event to `event_start`. The door time, end and redeem dates move with it, as do
+    /// ticket type and pricing dates that are still in the future so windows that have opened
+    /// stay open. Ticket holders are notified by a queued action and, if `refund_window_ends_at`
+    /// is given, can refund their own tickets until then or the new start, whichever is first.
+    pub fn reschedule(
+        &self,
+        current_user_id: Option<Uuid>,
+        event_start: NaiveDateTime,
+        refund_window_ends_at: Option<NaiveDateTime>,
+        conn: &PgConnection,
+    ) -> Result<Event, DatabaseError> {
+        if self.cancelled_at.is_some() {
+            return DatabaseError::business_process_error("Cancelled events cannot be rescheduled");
+        }
+        let previous_event_start = match self.event_start {
+            Some(previous_event_start) => previous_event_start,
+            None => {
+                return DatabaseError::validation_error(
+                    "event_start",
+                    "Event must have a start date to be rescheduled",
+                );
+            }
+        };
+        // Holders cannot refund their own tickets once the event has started
+        let refund_window_ends_at = refund_window_ends_at.map(|d| cmp::min(d, event_start));
+        let now = Utc::now().naive_utc();
+        if refund_window_ends_at.map(|d| d <= now).unwrap_or(false) {
+            return DatabaseError::validation_error(
+                "refund_window_ends_at",
+                "Refund window must end in the future",
+            );
+        }
+
+        let offset = event_start - previous_event_start;
+        let shift = |date: NaiveDateTime| if date > now { date + offset } else { date };
+
+        // Ticket types first so that the pricing validation sees the new sales window
+        for ticket_type in self.ticket_types(false, None, conn)? {
+            let pricing = ticket_type.valid_ticket_pricing(false, conn)?;
+            ticket_type.update(
+                current_user_id,
+                TicketTypeEditableAttributes {
+                    start_date: Some(shift(ticket_type.start_date)),
+                    end_date: Some(shift(ticket_type.end_date)),
+                    ..Default::default()
+                },
+                conn,
+            )?;
+            for ticket_pricing in pricing {
+                ticket_pricing.update(
+                    current_user_id,
+                    TicketPricingEditableAttributes {
+                        start_date: Some(shift(ticket_pricing.start_date)),
+                        end_date: Some(shift(ticket_pricing.end_date)),
+                        ..Default::default()
+                    },
+                    conn,
+                )?;
+            }
+        }
+
+        let event = self.update(
+            current_user_id,
+            EventEditableAttributes {
+                event_start: Some(event_start),
+                door_time: self.door_time.map(|d| d + offset),
+                event_end: self.event_end.map(|d| d + offset),
+                redeem_date: self.redeem_date.map(|d| d + offset),
+                override_status: Some(Some(EventOverrideStatus::Rescheduled)),
+                ..Default::default()
+            },
+            conn,
+        )?;
+
+        let reschedule = EventReschedule::create(
+            self.id,
+            previous_event_start,
+            event_start,
+            refund_window_ends_at,
+        )
+        .commit(conn)?;
+
+        DomainAction::create(
+            None,
+            DomainActionTypes::SendEventRescheduledCommunication,
+            None,
+            json!({ "event_id": self.id }),
+            Some(Tables::EventReschedules.to_string()),
+            Some(reschedule.id),
+        )
+        .commit(conn)?;
+
+        DomainEvent::create(
+            DomainEventTypes::EventRescheduled,
+            format!("Event '{}' rescheduled", self.name),
+            Tables::Events,
+            Some(self.id),
+            current_user_id,
+            Some(json!(reschedule)),
+        )
+        .commit(conn)?;
+
+        Ok(event)
+    }
+
+    /// True while the latest reschedule of the event allows holders to refund their own tickets
+    pub fn refund_window_open(&self, conn: &PgConnection) -> Result<bool, DatabaseError> {
+        Ok(EventReschedule::find_for_event(self.id, conn)?
+            .first()
+            .map(|r| r.refund_window_open())
+            .unwrap_or(false))
+    }
+
     pub fn get_all_events_ending_between(
         organization_id: Uuid,
         start: NaiveDateTime,
@@ -1086,13 +1475,47 @@ impl Event {
         sort_direction: SortingDir,
         user: Option<User>,
         past_or_upcoming: PastOrUpcoming,
+        filters: EventSearchFilters,
         conn: &PgConnection,
     ) -> Result<Vec<Event>, DatabaseError> {
-        let sort_column = match sort_field {
-            EventSearchSortField::Name => "name",
-            EventSearchSortField::EventStart => "event_start",
-        };
+        Ok(Event::search_with_facets(
+            query_filter,
+            region_id,
+            organization_id,
+            venue_id,
+            start_time,
+            end_time,
+            status_filter,
+            sort_field,
+            sort_direction,
+            user,
+            past_or_upcoming,
+            filters,
+            false,
+            conn,
+        )?
+        .0)
+    }
 
+    /// Searches events the same as `search`, also counting the matches by event type, age limit
+    /// and availability when `include_facets` is set. Each facet is counted without its own
+    /// filter so the other options stay visible.
+    pub fn search_with_facets(
+        query_filter: Option<String>,
+        region_id: Option<Uuid>,
+        organization_id: Option<Uuid>,
+        venue_id: Option<Uuid>,
+        start_time: Option<NaiveDateTime>,
+        end_time: Option<NaiveDateTime>,
+        status_filter: Option<Vec<EventStatus>>,
+        sort_field: EventSearchSortField,
+        sort_direction: SortingDir,
+        user: Option<User>,
+        past_or_upcoming: PastOrUpcoming,
+        filters: EventSearchFilters,
+        include_facets: bool,
+        conn: &PgConnection,
+    ) -> Result<(Vec<Event>, Option<EventSearchFacets>), DatabaseError> {
         let mut start_time = start_time;
         let mut end_time = end_time;
         let beginning_of_time = NaiveDate::from_ymd(1900, 1, 1).and_hms(12, 0, 0);
@@ -1115,9 +1538,12 @@ impl Event {
         }
 
         let query_like = match query_filter {
-            Some(n) => format!("%{}%", text::escape_control_chars(&n)),
+            Some(ref n) => format!("%{}%", text::escape_control_chars(n)),
             None => "%".to_string(),
         };
+        let query_text = query_filter.unwrap_or("".to_string());
+
+        // Events matching every filter except the facets, which are applied below
         let mut query = events::table
             .left_join(venues::table.on(events::venue_id.eq(venues::id.nullable())))
             .inner_join(organizations::table.on(organizations::id.eq(events::organization_id)))
@@ -1144,14 +1570,15 @@ impl Event {
                                 .and(venues::name.ilike(query_like.clone())),
                         )
                         .sql(")"))
-                    .or(artists::id.is_not_null()),
+                    .or(artists::id.is_not_null())
+                    .or(sql("events.search_document @@ plainto_tsquery('english', ")
+                        .bind::<Text, _>(query_text.clone())
+                        .sql(")")),
             )
             .filter(events::event_end.ge(start_time.unwrap()))
             .filter(events::event_end.le(end_time.unwrap()))
-            .select(events::all_columns)
+            .select(events::id)
             .distinct()
-            .order_by(sql::<()>(&format!("{} {}", sort_column, sort_direction)))
-            .then_order_by(events::name.asc())
             .into_boxed();
 
         match user {
@@ -1198,9 +1625,193 @@ impl Event {
             query = query.filter(venues::region_id.eq(region_id));
         }
 
-        let result = query.load(conn);
+        if let (Some(latitude), Some(longitude), Some(radius_in_km)) =
+            (filters.latitude, filters.longitude, filters.radius_in_km)
+        {
+            // Bounding box narrowed down with the venue coordinates index before the exact
+            // distance is checked. Boxes crossing the antimeridian or reaching a pole are only
+            // bounded by latitude.
+            let latitude_delta = radius_in_km / KM_PER_DEGREE_OF_LATITUDE;
+            query = query.filter(
+                venues::latitude.between(latitude - latitude_delta, latitude + latitude_delta),
+            );
+            let longitude_delta =
+                radius_in_km / (KM_PER_DEGREE_OF_LATITUDE * latitude.to_radians().cos());
+            if longitude_delta.is_finite()
+                && longitude - longitude_delta >= -180.0
+                && longitude + longitude_delta <= 180.0
+            {
+                query = query.filter(
+                    venues::longitude
+                        .between(longitude - longitude_delta, longitude + longitude_delta),
+                );
+            }
+            query = query.filter(
+                sql::<Bool>("distance_in_km(")
+                    .bind::<Double, _>(latitude)
+                    .sql(", ")
+                    .bind::<Double, _>(longitude)
+                    .sql(", venues.latitude, venues.longitude) <= ")
+                    .bind::<Double, _>(radius_in_km),
+            );
+        }
+
+        // Events without tickets on sale have no price so are left out
+        if let Some(min_price_in_cents) = filters.min_price_in_cents {
+            query = query.filter(
+                sql::<Bool>(&format!(
+                    "(SELECT max(prices.price_in_cents) FROM ({}) prices) >= ",
+                    EVENT_TICKET_PRICES_SQL
+                ))
+                .bind::<BigInt, _>(min_price_in_cents),
+            );
+        }
+        if let Some(max_price_in_cents) = filters.max_price_in_cents {
+            query = query.filter(
+                sql::<Bool>(&format!(
+                    "(SELECT min(prices.price_in_cents) FROM ({}) prices) <= ",
+                    EVENT_TICKET_PRICES_SQL
+                ))
+                .bind::<BigInt, _>(max_price_in_cents),
+            );
+        }
+
+        let event_ids: Vec<Uuid> = query
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Unable to load all events")?;
 
-        DatabaseError::wrap(ErrorCode::QueryError, "Unable to load all events", result)
+        let mut query = events::table
+            .left_join(venues::table.on(events::venue_id.eq(venues::id.nullable())))
+            .filter(events::id.eq_any(event_ids.clone()))
+            .select(events::all_columns)
+            .into_boxed();
+
+        if let Some(age_limit) = filters.age_limit.clone() {
+            query = query.filter(events::age_limit.eq(age_limit));
+        }
+
+        if let Some(event_type) = filters.event_type {
+            query = query.filter(events::event_type.eq(event_type));
+        }
+
+        if let Some(availability) = filters.availability {
+            query = query.filter(sql::<Bool>(&match availability {
+                EventAvailability::Available => EVENT_AVAILABLE_SQL.to_string(),
+                EventAvailability::SoldOut => format!("NOT {}", EVENT_AVAILABLE_SQL),
+            }));
+        }
+
+        // Unscored events, such as those at venues without coordinates, are kept last. Ties are
+        // listed by event start.
+        query = match sort_field {
+            EventSearchSortField::Relevance if !query_text.is_empty() => query.order_by(
+                sql::<()>("ts_rank(events.search_document, plainto_tsquery('english', ")
+                    .bind::<Text, _>(query_text.clone())
+                    .sql(&format!(")) {} NULLS LAST", sort_direction)),
+            ),
+            EventSearchSortField::Distance
+                if filters.latitude.is_some() && filters.longitude.is_some() =>
+            {
+                query.order_by(
+                    sql::<()>("distance_in_km(")
+                        .bind::<Nullable<Double>, _>(filters.latitude)
+                        .sql(", ")
+                        .bind::<Nullable<Double>, _>(filters.longitude)
+                        .sql(&format!(
+                            ", venues.latitude, venues.longitude) {} NULLS LAST",
+                            sort_direction
+                        )),
+                )
+            }
+            EventSearchSortField::Name => {
+                query.order_by(sql::<()>(&format!("events.name {}", sort_direction)))
+            }
+            _ => query.order_by(sql::<()>(&format!("events.event_start {}", sort_direction))),
+        };
+
+        let events: Vec<Event> = query
+            .then_order_by(events::event_start.asc())
+            .then_order_by(events::name.asc())
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Unable to load all events")?;
+
+        let facets = if include_facets {
+            Some(Event::search_facets(event_ids, &filters, conn)?)
+        } else {
+            None
+        };
+
+        Ok((events, facets))
+    }
+
+    /// Counts `event_ids` by event type, age limit and availability. The facet filters other
+    /// than the one being counted are applied.
+    fn search_facets(
+        event_ids: Vec<Uuid>,
+        filters: &EventSearchFilters,
+        conn: &PgConnection,
+    ) -> Result<EventSearchFacets, DatabaseError> {
+        #[derive(QueryableByName)]
+        struct R {
+            #[sql_type = "Text"]
+            facet: String,
+            #[sql_type = "Text"]
+            value: String,
+            #[sql_type = "BigInt"]
+            count: i64,
+        }
+
+        let query = format!(
+            r#"
+            WITH matches AS (
+                SELECT events.event_type, events.age_limit, {} AS available
+                FROM events
+                WHERE events.id = ANY($1)
+            )
+            SELECT 'event_types' AS facet, event_type AS value, count(*) AS count
+            FROM matches
+            WHERE ($3 IS NULL OR age_limit = $3)
+            AND ($4 IS NULL OR available = $4)
+            GROUP BY event_type
+            UNION ALL
+            SELECT 'age_limits', age_limit, count(*)
+            FROM matches
+            WHERE age_limit IS NOT NULL
+            AND ($2 IS NULL OR event_type = $2)
+            AND ($4 IS NULL OR available = $4)
+            GROUP BY age_limit
+            UNION ALL
+            SELECT 'availability', CASE WHEN available THEN 'Available' ELSE 'SoldOut' END, count(*)
+            FROM matches
+            WHERE ($2 IS NULL OR event_type = $2)
+            AND ($3 IS NULL OR age_limit = $3)
+            GROUP BY available;
+        "#,
+            EVENT_AVAILABLE_SQL
+        );
+
+        let results: Vec<R> = diesel::sql_query(query)
+            .bind::<Array<dUuid>, _>(event_ids)
+            .bind::<Nullable<Text>, _>(filters.event_type.map(|t| t.to_string()))
+            .bind::<Nullable<Text>, _>(filters.age_limit.clone())
+            .bind::<Nullable<Bool>, _>(
+                filters
+                    .availability
+                    .map(|a| a == EventAvailability::Available),
+            )
+            .get_results(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load event search facets")?;
+
+        let mut facets = EventSearchFacets::default();
+        for r in results {
+            let counts = match r.facet.as_str() {
+                "event_types" => &mut facets.event_types,
+                "age_limits" => &mut facets.age_limits,
+                _ => &mut facets.availability,
+            };
+            counts.insert(r.value, r.count);
+        }
+        Ok(facets)
     }
 
     pub fn add_artist(
@@ -1249,6 +1860,24 @@ impl Event {
             .to_db_error(ErrorCode::QueryError, "Could not load checked in users")
     }
 
+    /// Users holding purchased tickets that have not been redeemed yet
+    pub fn ticket_holders(event_id: Uuid, conn: &PgConnection) -> Result<Vec<User>, DatabaseError> {
+        use schema::*;
+        ticket_instances::table
+            .inner_join(assets::table.inner_join(ticket_types::table))
+            .inner_join(
+                wallets::table
+                    .inner_join(users::table.on(wallets::user_id.eq(users::id.nullable())))
+                    .on(wallets::id.eq(ticket_instances::wallet_id)),
+            )
+            .filter(ticket_instances::status.eq(TicketInstanceStatus::Purchased))
+            .filter(ticket_types::event_id.eq(event_id))
+            .select(users::all_columns)
+            .distinct()
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load ticket holders")
+    }
+
     pub fn add_ticket_type(
         &self,
         name: String,
@@ -1283,6 +1912,214 @@ impl Event {
         Ok(ticket_type)
     }
 
+    /// Copies the event as a draft starting at `event_start`, along with its ticket types,
+    /// pricing and artists. All other dates are shifted by the same amount as the start.
+    /// Blockchain assets for the copied ticket types still need to be created by the caller.
+    pub fn duplicate(
+        &self,
+        current_user_id: Option<Uuid>,
+        event_start: NaiveDateTime,
+        event_series_id: Option<Uuid>,
+        conn: &PgConnection,
+    ) -> Result<Event, DatabaseError> {
+        Ok(self
+            .duplicate_with_ticket_types(current_user_id, event_start, event_series_id, conn)?
+            .0)
+    }
+
+    /// Deep copy of the event for organizers re-running it. On top of what `duplicate` copies,
+    /// the holds are recreated without any tickets in them and, if `include_codes` is set, the
+    /// codes are copied too. Copied holds and codes get new redemption codes as redemption
+    /// codes are unique across events. Comps are for specific people so they are not copied.
+    pub fn clone_event(
+        &self,
+        current_user_id: Option<Uuid>,
+        event_start: NaiveDateTime,
+        include_codes: bool,
+        conn: &PgConnection,
+    ) -> Result<Event, DatabaseError> {
+        let offset = event_start - self.event_start.unwrap_or(event_start);
+        let (event, ticket_type_ids) =
+            self.duplicate_with_ticket_types(current_user_id, event_start, None, conn)?;
+
+        // Parent holds sort first so their copies exist before their children are created
+        let mut holds: Vec<Hold> = Hold::find_for_event(self.id, conn)?
+            .into_iter()
+            .filter(|h| h.hold_type != HoldTypes::Comp)
+            .collect();
+        holds.sort_by_key(|h| h.parent_hold_id.is_some());
+        let mut hold_ids = HashMap::new();
+        for hold in holds {
+            let ticket_type_id = match ticket_type_ids.get(&hold.ticket_type_id) {
+                Some(ticket_type_id) => *ticket_type_id,
+                None => continue,
+            };
+            let parent_hold_id = match hold.parent_hold_id {
+                Some(parent_hold_id) => match hold_ids.get(&parent_hold_id) {
+                    Some(id) => Some(*id),
+                    None => continue,
+                },
+                None => None,
+            };
+            let new_hold = NewHold {
+                name: hold.name.clone(),
+                parent_hold_id,
+                event_id: event.id,
+                email: None,
+                phone: None,
+                redemption_code: match hold.redemption_code {
+                    Some(_) => Some(UniqueCode::generate_redemption_code(conn)?),
+                    None => None,
+                },
+                discount_in_cents: hold.discount_in_cents,
+                end_at: hold.end_at.map(|d| d + offset),
+                max_per_user: hold.max_per_user,
+                hold_type: hold.hold_type,
+                ticket_type_id,
+            }
+            .commit(current_user_id, conn)?;
+            hold_ids.insert(hold.id, new_hold.id);
+        }
+
+        if include_codes {
+            for code in Code::find_for_event(self.id, None, conn)? {
+                let new_code = NewCode {
+                    name: code.name,
+                    event_id: event.id,
+                    code_type: code.code_type,
+                    redemption_code: UniqueCode::generate_redemption_code(conn)?,
+                    max_uses: code.max_uses,
+                    discount_in_cents: code.discount_in_cents,
+                    discount_as_percentage: code.discount_as_percentage,
+                    start_date: code.start_date + offset,
+                    end_date: code.end_date + offset,
+                    max_tickets_per_user: code.max_tickets_per_user,
+                    min_quantity: code.min_quantity,
+                    buy_quantity: code.buy_quantity,
+                    get_quantity: code.get_quantity,
+                    max_order_discount_in_cents: code.max_order_discount_in_cents,
+                    waive_fees: code.waive_fees,
+                }
+                .commit(conn)?;
+                new_code.update_ticket_types(
+                    code.ticket_type_ids
+                        .iter()
+                        .filter_map(|id| ticket_type_ids.get(id).cloned())
+                        .collect(),
+                    conn,
+                )?;
+            }
+        }
+
+        DomainEvent::create(
+            DomainEventTypes::EventCloned,
+            format!("Event cloned from {}", self.name),
+            Tables::Events,
+            Some(event.id),
+            current_user_id,
+            Some(json!({ "source_event_id": self.id, "include_codes": include_codes })),
+        )
+        .commit(conn)?;
+
+        Ok(event)
+    }
+
+    /// Implementation of `duplicate`, also returning the ids of the copied ticket types keyed
+    /// by the ids of the originals
+    fn duplicate_with_ticket_types(
+        &self,
+        current_user_id: Option<Uuid>,
+        event_start: NaiveDateTime,
+        event_series_id: Option<Uuid>,
+        conn: &PgConnection,
+    ) -> Result<(Event, HashMap<Uuid, Uuid>), DatabaseError> {
+        let offset = event_start - self.event_start.unwrap_or(event_start);
+        let shift = |date: Option<NaiveDateTime>| date.map(|d| d + offset);
+
+        let event = NewEvent {
+            name: self.name.clone(),
+            organization_id: self.organization_id,
+            venue_id: self.venue_id,
+            event_start: Some(event_start),
+            door_time: shift(self.door_time),
+            status: EventStatus::Draft,
+            publish_date: shift(self.publish_date),
+            redeem_date: shift(self.redeem_date),
+            promo_image_url: self.promo_image_url.clone(),
+            cover_image_url: self.cover_image_url.clone(),
+            additional_info: self.additional_info.clone(),
+            age_limit: self.age_limit.clone(),
+            top_line_info: self.top_line_info.clone(),
+            video_url: self.video_url.clone(),
+            is_external: self.is_external,
+            external_url: self.external_url.clone(),
+            override_status: None,
+            event_end: shift(self.event_end),
+            event_type: self.event_type,
+            private_access_code: self.private_access_code.clone(),
+            event_series_id,
+        }
+        .commit(current_user_id, conn)?;
+
+        let wallet = self.issuer_wallet(conn)?;
+        let mut ticket_type_ids = HashMap::new();
+        for ticket_type in self.ticket_types(false, None, conn)? {
+            if ticket_type.status == TicketTypeStatus::Cancelled {
+                continue;
+            }
+            let mut new_ticket_type = event.add_ticket_type(
+                ticket_type.name.clone(),
+                ticket_type.description.clone(),
+                ticket_type.valid_ticket_count(conn)?,
+                ticket_type.start_date + offset,
+                ticket_type.end_date + offset,
+                wallet.id,
+                Some(ticket_type.increment),
+                ticket_type.limit_per_person,
+                ticket_type.price_in_cents,
+                ticket_type.sold_out_behavior,
+                ticket_type.is_private,
+                conn,
+            )?;
+            if ticket_type.refundable {
+                new_ticket_type = new_ticket_type.update(
+                    current_user_id,
+                    TicketTypeEditableAttributes {
+                        refundable: Some(true),
+                        ..Default::default()
+                    },
+                    conn,
+                )?;
+            }
+            ticket_type_ids.insert(ticket_type.id, new_ticket_type.id);
+            for pricing in ticket_type.valid_ticket_pricing(false, conn)? {
+                new_ticket_type.add_ticket_pricing(
+                    pricing.name,
+                    pricing.start_date + offset,
+                    pricing.end_date + offset,
+                    pricing.price_in_cents,
+                    pricing.is_box_office_only,
+                    None,
+                    conn,
+                )?;
+            }
+        }
+
+        for artist in self.artists(conn)? {
+            EventArtist::create(
+                event.id,
+                artist.artist.id,
+                artist.rank,
+                shift(artist.set_time),
+                artist.importance,
+                artist.stage_id,
+            )
+            .commit(current_user_id, conn)?;
+        }
+
+        Ok((event, ticket_type_ids))
+    }
+
     pub fn ticket_types(
         &self,
         filter_access_tokens: bool,
@@ -0,0 +1,95 @@
+use chrono::NaiveDateTime;
+use diesel;
+use diesel::dsl;
+use diesel::prelude::*;
+use schema::fan_notes;
+use utils::errors::*;
+use uuid::Uuid;
+
+/// Note written about a fan by staff of an organization, only visible to that organization
+#[derive(Clone, Debug, Deserialize, Identifiable, PartialEq, Queryable, Serialize)]
+pub struct FanNote {
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    pub user_id: Uuid,
+    pub note: String,
+    pub created_by_user_id: Uuid,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Insertable, Serialize)]
+#[table_name = "fan_notes"]
+pub struct NewFanNote {
+    pub organization_id: Uuid,
+    pub user_id: Uuid,
+    pub note: String,
+    pub created_by_user_id: Uuid,
+}
+
+impl NewFanNote {
+    pub fn commit(&self, conn: &PgConnection) -> Result<FanNote, DatabaseError> {
+        if self.note.trim().is_empty() {
+            return DatabaseError::validation_error("note", "Note cannot be blank");
+        }
+
+        diesel::insert_into(fan_notes::table)
+            .values(self)
+            .get_result(conn)
+            .to_db_error(ErrorCode::InsertError, "Could not create fan note")
+    }
+}
+
+impl FanNote {
+    pub fn create(
+        organization_id: Uuid,
+        user_id: Uuid,
+        note: String,
+        created_by_user_id: Uuid,
+    ) -> NewFanNote {
+        NewFanNote {
+            organization_id,
+            user_id,
+            note,
+            created_by_user_id,
+        }
+    }
+
+    pub fn find(id: Uuid, conn: &PgConnection) -> Result<FanNote, DatabaseError> {
+        fan_notes::table
+            .find(id)
+            .first(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load fan note")
+    }
+
+    /// Notes about the fan, newest first
+    pub fn find_for_fan(
+        organization_id: Uuid,
+        user_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<FanNote>, DatabaseError> {
+        fan_notes::table
+            .filter(fan_notes::organization_id.eq(organization_id))
+            .filter(fan_notes::user_id.eq(user_id))
+            .order_by(fan_notes::created_at.desc())
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load fan notes")
+    }
+
+    pub fn update(&self, note: String, conn: &PgConnection) -> Result<FanNote, DatabaseError> {
+        if note.trim().is_empty() {
+            return DatabaseError::validation_error("note", "Note cannot be blank");
+        }
+
+        diesel::update(self)
+            .set((fan_notes::note.eq(note), fan_notes::updated_at.eq(dsl::now)))
+            .get_result(conn)
+            .to_db_error(ErrorCode::UpdateError, "Could not update fan note")
+    }
+
+    pub fn destroy(&self, conn: &PgConnection) -> Result<usize, DatabaseError> {
+        diesel::delete(self)
+            .execute(conn)
+            .to_db_error(ErrorCode::DeleteError, "Could not remove fan note")
+    }
+}
@@ -0,0 +1,296 @@
+use chrono::NaiveDateTime;
+use diesel;
+use diesel::dsl;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Nullable, Text, Timestamp, Uuid as dUuid};
+use models::*;
+use schema::{broadcasts, fan_segments};
+use serde_json;
+use serde_json::Value;
+use utils::errors::*;
+use uuid::Uuid;
+
+/// Conditions a fan must meet to belong to a segment. Conditions that are not set are ignored.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct FanSegmentFilters {
+    /// Spent at least this much on the organization's events
+    #[serde(default)]
+    pub min_revenue_in_cents: Option<i64>,
+    /// Checked in to at least this many of the organization's events
+    #[serde(default)]
+    pub min_events_attended: Option<i64>,
+    /// Bought a ticket of this type
+    #[serde(default)]
+    pub ticket_type_id: Option<Uuid>,
+    /// Showed interest in this event but did not buy tickets for it
+    #[serde(default)]
+    pub interested_in_event_id: Option<Uuid>,
+    /// Tagged with this name by the organization's staff
+    #[serde(default)]
+    pub tag: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Identifiable, PartialEq, Queryable, Serialize)]
+pub struct FanSegment {
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    pub name: String,
+    pub filters: Value,
+    pub sendgrid_list_id: Option<i64>,
+    pub created_by_user_id: Uuid,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Default, Deserialize)]
+pub struct FanSegmentEditableAttributes {
+    pub name: Option<String>,
+    pub filters: Option<FanSegmentFilters>,
+}
+
+#[derive(Insertable, Serialize)]
+#[table_name = "fan_segments"]
+pub struct NewFanSegment {
+    pub organization_id: Uuid,
+    pub name: String,
+    pub filters: Value,
+    pub created_by_user_id: Uuid,
+}
+
+impl NewFanSegment {
+    pub fn commit(&self, conn: &PgConnection) -> Result<FanSegment, DatabaseError> {
+        if self.name.trim().is_empty() {
+            return DatabaseError::validation_error("name", "Segment name cannot be blank");
+        }
+        validate_filters(
+            self.organization_id,
+            &serde_json::from_value(self.filters.clone())?,
+            conn,
+        )?;
+
+        diesel::insert_into(fan_segments::table)
+            .values(self)
+            .get_result(conn)
+            .to_db_error(ErrorCode::InsertError, "Could not create fan segment")
+    }
+}
+
+impl FanSegment {
+    pub fn create(
+        organization_id: Uuid,
+        name: String,
+        filters: FanSegmentFilters,
+        created_by_user_id: Uuid,
+    ) -> NewFanSegment {
+        NewFanSegment {
+            organization_id,
+            name,
+            filters: json!(filters),
+            created_by_user_id,
+        }
+    }
+
+    pub fn find(id: Uuid, conn: &PgConnection) -> Result<FanSegment, DatabaseError> {
+        fan_segments::table
+            .find(id)
+            .first(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load fan segment")
+    }
+
+    pub fn find_for_organization(
+        organization_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<FanSegment>, DatabaseError> {
+        fan_segments::table
+            .filter(fan_segments::organization_id.eq(organization_id))
+            .order_by(fan_segments::name.asc())
+            .load(conn)
+            .to_db_error(
+                ErrorCode::QueryError,
+                "Could not load fan segments for organization",
+            )
+    }
+
+    pub fn organization(&self, conn: &PgConnection) -> Result<Organization, DatabaseError> {
+        Organization::find(self.organization_id, conn)
+    }
+
+    pub fn parsed_filters(&self) -> Result<FanSegmentFilters, DatabaseError> {
+        Ok(serde_json::from_value(self.filters.clone())?)
+    }
+
+    pub fn update(
+        &self,
+        attributes: FanSegmentEditableAttributes,
+        conn: &PgConnection,
+    ) -> Result<FanSegment, DatabaseError> {
+        let name = attributes.name.unwrap_or_else(|| self.name.clone());
+        if name.trim().is_empty() {
+            return DatabaseError::validation_error("name", "Segment name cannot be blank");
+        }
+        let filters = match attributes.filters {
+            Some(filters) => {
+                validate_filters(self.organization_id, &filters, conn)?;
+                json!(filters)
+            }
+            None => self.filters.clone(),
+        };
+
+        diesel::update(self)
+            .set((
+                fan_segments::name.eq(name),
+                fan_segments::filters.eq(filters),
+                fan_segments::updated_at.eq(dsl::now),
+            ))
+            .get_result(conn)
+            .to_db_error(ErrorCode::UpdateError, "Could not update fan segment")
+    }
+
+    pub fn set_sendgrid_list_id(
+        &self,
+        sendgrid_list_id: i64,
+        conn: &PgConnection,
+    ) -> Result<FanSegment, DatabaseError> {
+        diesel::update(self)
+            .set((
+                fan_segments::sendgrid_list_id.eq(sendgrid_list_id),
+                fan_segments::updated_at.eq(dsl::now),
+            ))
+            .get_result(conn)
+            .to_db_error(ErrorCode::UpdateError, "Could not update fan segment")
+    }
+
+    /// Removes the segment, cancelling broadcasts to it that have not been sent yet
+    pub fn destroy(&self, conn: &PgConnection) -> Result<usize, DatabaseError> {
+        let pending_broadcasts: Vec<Broadcast> = broadcasts::table
+            .filter(broadcasts::fan_segment_id.eq(self.id))
+            .filter(broadcasts::status.eq(BroadcastStatus::Pending))
+            .select(broadcasts::all_columns)
+            .load(conn)
+            .to_db_error(
+                ErrorCode::QueryError,
+                "Could not load broadcasts for fan segment",
+            )?;
+        for broadcast in pending_broadcasts {
+            broadcast.cancel(conn)?;
+        }
+
+        diesel::delete(self)
+            .execute(conn)
+            .to_db_error(ErrorCode::DeleteError, "Could not remove fan segment")
+    }
+
+    /// Fans currently matching the segment, ordered by name
+    pub fn members(
+        &self,
+        page: u32,
+        limit: u32,
+        conn: &PgConnection,
+    ) -> Result<Payload<DisplayFan>, DatabaseError> {
+        let (fans, total) = self.load_members(limit as i64, (page * limit) as i64, conn)?;
+        let mut paging = Paging::new(page, limit);
+        paging.total = total as u64;
+        Ok(Payload::new(fans, paging))
+    }
+
+    /// All fans currently matching the segment, for exports and audiences
+    pub fn all_members(&self, conn: &PgConnection) -> Result<Vec<DisplayFan>, DatabaseError> {
+        Ok(self.load_members(i64::max_value(), 0, conn)?.0)
+    }
+
+    fn load_members(
+        &self,
+        limit: i64,
+        offset: i64,
+        conn: &PgConnection,
+    ) -> Result<(Vec<DisplayFan>, i64), DatabaseError> {
+        #[derive(QueryableByName)]
+        struct R {
+            #[sql_type = "dUuid"]
+            user_id: Uuid,
+            #[sql_type = "Nullable<Text>"]
+            first_name: Option<String>,
+            #[sql_type = "Nullable<Text>"]
+            last_name: Option<String>,
+            #[sql_type = "Nullable<Text>"]
+            email: Option<String>,
+            #[sql_type = "Nullable<Text>"]
+            phone: Option<String>,
+            #[sql_type = "Nullable<Text>"]
+            thumb_profile_pic_url: Option<String>,
+            #[sql_type = "Timestamp"]
+            created_at: NaiveDateTime,
+            #[sql_type = "Nullable<BigInt>"]
+            order_count: Option<i64>,
+            #[sql_type = "Nullable<Timestamp>"]
+            first_order_time: Option<NaiveDateTime>,
+            #[sql_type = "Nullable<Timestamp>"]
+            last_order_time: Option<NaiveDateTime>,
+            #[sql_type = "Nullable<BigInt>"]
+            revenue_in_cents: Option<i64>,
+            #[sql_type = "BigInt"]
+            total: i64,
+        }
+
+        let filters = self.parsed_filters()?;
+        let query = include_str!("../queries/fan_segment_members.sql");
+        let results: Vec<R> = diesel::sql_query(query)
+            .bind::<dUuid, _>(self.organization_id)
+            .bind::<Nullable<BigInt>, _>(filters.min_revenue_in_cents)
+            .bind::<Nullable<BigInt>, _>(filters.min_events_attended)
+            .bind::<Nullable<dUuid>, _>(filters.ticket_type_id)
+            .bind::<Nullable<dUuid>, _>(filters.interested_in_event_id)
+            .bind::<Nullable<Text>, _>(filters.tag)
+            .bind::<BigInt, _>(limit)
+            .bind::<BigInt, _>(offset)
+            .get_results(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load fan segment members")?;
+
+        let total = results.first().map(|r| r.total).unwrap_or(0);
+        let organization_id = self.organization_id;
+        let fans = results
+            .into_iter()
+            .map(|r| DisplayFan {
+                user_id: r.user_id,
+                first_name: r.first_name,
+                last_name: r.last_name,
+                email: r.email,
+                phone: r.phone,
+                thumb_profile_pic_url: r.thumb_profile_pic_url,
+                organization_id,
+                order_count: r.order_count.map(|c| c as u32),
+                created_at: r.created_at,
+                first_order_time: r.first_order_time,
+                last_order_time: r.last_order_time,
+                revenue_in_cents: r.revenue_in_cents,
+            })
+            .collect();
+        Ok((fans, total))
+    }
+}
+
+/// Filters may only refer to the organization's own ticket types and events
+fn validate_filters(
+    organization_id: Uuid,
+    filters: &FanSegmentFilters,
+    conn: &PgConnection,
+) -> Result<(), DatabaseError> {
+    if let Some(ticket_type_id) = filters.ticket_type_id {
+        let ticket_type = TicketType::find(ticket_type_id, conn)?;
+        if Event::find(ticket_type.event_id, conn)?.organization_id != organization_id {
+            return DatabaseError::validation_error(
+                "ticket_type_id",
+                "Ticket type does not belong to this organization",
+            );
+        }
+    }
+    if let Some(event_id) = filters.interested_in_event_id {
+        if Event::find(event_id, conn)?.organization_id != organization_id {
+            return DatabaseError::validation_error(
+                "interested_in_event_id",
+                "Event does not belong to this organization",
+            );
+        }
+    }
+    Ok(())
+}
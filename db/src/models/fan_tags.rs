@@ -0,0 +1,116 @@
+use chrono::NaiveDateTime;
+use diesel;
+use diesel::dsl::sql;
+use diesel::prelude::*;
+use diesel::sql_types::{Bool, Text};
+use schema::fan_tags;
+use utils::errors::*;
+use uuid::Uuid;
+
+/// Tag added to a fan by staff of an organization, only visible to that organization
+#[derive(Clone, Debug, Deserialize, Identifiable, PartialEq, Queryable, Serialize)]
+pub struct FanTag {
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub created_by_user_id: Uuid,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Insertable, Serialize)]
+#[table_name = "fan_tags"]
+pub struct NewFanTag {
+    pub organization_id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub created_by_user_id: Uuid,
+}
+
+impl NewFanTag {
+    /// Adds the tag, returning the existing tag if the fan already has one with the same name
+    pub fn commit(&self, conn: &PgConnection) -> Result<FanTag, DatabaseError> {
+        if self.name.is_empty() {
+            return DatabaseError::validation_error("name", "Tag name cannot be blank");
+        }
+
+        let existing: Option<FanTag> = fan_tags::table
+            .filter(fan_tags::organization_id.eq(self.organization_id))
+            .filter(fan_tags::user_id.eq(self.user_id))
+            .filter(
+                sql::<Bool>("lower(fan_tags.name) = lower(")
+                    .bind::<Text, _>(&self.name)
+                    .sql(")"),
+            )
+            .first(conn)
+            .optional()
+            .to_db_error(ErrorCode::QueryError, "Could not load fan tag")?;
+        if let Some(tag) = existing {
+            return Ok(tag);
+        }
+
+        diesel::insert_into(fan_tags::table)
+            .values(self)
+            .get_result(conn)
+            .to_db_error(ErrorCode::InsertError, "Could not create fan tag")
+    }
+}
+
+impl FanTag {
+    pub fn create(
+        organization_id: Uuid,
+        user_id: Uuid,
+        name: &str,
+        created_by_user_id: Uuid,
+    ) -> NewFanTag {
+        NewFanTag {
+            organization_id,
+            user_id,
+            name: name.trim().to_string(),
+            created_by_user_id,
+        }
+    }
+
+    pub fn find(id: Uuid, conn: &PgConnection) -> Result<FanTag, DatabaseError> {
+        fan_tags::table
+            .find(id)
+            .first(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load fan tag")
+    }
+
+    pub fn find_for_fan(
+        organization_id: Uuid,
+        user_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<FanTag>, DatabaseError> {
+        fan_tags::table
+            .filter(fan_tags::organization_id.eq(organization_id))
+            .filter(fan_tags::user_id.eq(user_id))
+            .order_by(fan_tags::name.asc())
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load fan tags")
+    }
+
+    /// Distinct tag names used by the organization, for suggesting tags and building segments
+    pub fn names_for_organization(
+        organization_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<String>, DatabaseError> {
+        fan_tags::table
+            .filter(fan_tags::organization_id.eq(organization_id))
+            .select(fan_tags::name)
+            .distinct()
+            .order_by(fan_tags::name.asc())
+            .load(conn)
+            .to_db_error(
+                ErrorCode::QueryError,
+                "Could not load fan tags for organization",
+            )
+    }
+
+    pub fn destroy(&self, conn: &PgConnection) -> Result<usize, DatabaseError> {
+        diesel::delete(self)
+            .execute(conn)
+            .to_db_error(ErrorCode::DeleteError, "Could not remove fan tag")
+    }
+}
@@ -0,0 +1,250 @@
+use chrono::prelude::*;
+use diesel;
+use diesel::dsl;
+use diesel::prelude::*;
+use models::*;
+use schema::hold_releases;
+use std::cmp;
+use utils::errors::*;
+use uuid::Uuid;
+
+#[derive(Clone, Debug, Deserialize, Identifiable, PartialEq, Queryable, Serialize)]
+pub struct HoldRelease {
+    pub id: Uuid,
+    pub hold_id: Uuid,
+    pub release_at: NaiveDateTime,
+    pub quantity: Option<i64>,
+    pub status: HoldReleaseStatus,
+    pub released_quantity: Option<i64>,
+    pub used_quantity: Option<i64>,
+    pub released_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(AsChangeset, Default)]
+#[table_name = "hold_releases"]
+struct HoldReleaseEditableAttributes {
+    status: Option<HoldReleaseStatus>,
+    released_quantity: Option<Option<i64>>,
+    used_quantity: Option<Option<i64>>,
+    released_at: Option<Option<NaiveDateTime>>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct HoldReleaseSummary {
+    pub hold_id: Uuid,
+    pub quantity: u32,
+    pub available: u32,
+    pub used_quantity: u32,
+    pub released_quantity: u32,
+    pub releases: Vec<HoldRelease>,
+}
+
+impl HoldRelease {
+    /// Schedules a release of `quantity` tickets from the hold back to general sale at
+    /// `release_at`. If `quantity` is `None`, everything still available in the hold is released.
+    pub fn create(
+        hold_id: Uuid,
+        release_at: NaiveDateTime,
+        quantity: Option<u32>,
+    ) -> NewHoldRelease {
+        NewHoldRelease {
+            hold_id,
+            release_at,
+            quantity: quantity.map(|q| q as i64),
+        }
+    }
+
+    pub fn find(id: Uuid, conn: &PgConnection) -> Result<HoldRelease, DatabaseError> {
+        hold_releases::table
+            .find(id)
+            .first(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load hold release")
+    }
+
+    pub fn find_for_hold(
+        hold_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<HoldRelease>, DatabaseError> {
+        hold_releases::table
+            .filter(hold_releases::hold_id.eq(hold_id))
+            .order_by(hold_releases::release_at.asc())
+            .then_order_by(hold_releases::created_at.asc())
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load releases for hold")
+    }
+
+    /// Summarises how much of the hold has been used, how much has been released back to
+    /// general sale and which releases are still scheduled.
+    pub fn summary_for_hold(
+        hold: &Hold,
+        conn: &PgConnection,
+    ) -> Result<HoldReleaseSummary, DatabaseError> {
+        let (quantity, available) = hold.quantity(conn)?;
+        let releases = HoldRelease::find_for_hold(hold.id, conn)?;
+        let released_quantity = releases
+            .iter()
+            .map(|r| r.released_quantity.unwrap_or(0) as u32)
+            .sum();
+
+        Ok(HoldReleaseSummary {
+            hold_id: hold.id,
+            quantity,
+            available,
+            used_quantity: quantity - available,
+            released_quantity,
+            releases,
+        })
+    }
+
+    /// Returns the scheduled quantity of available tickets to general sale. Called by the
+    /// scheduled `ReleaseHoldInventory` domain action, so releases that are no longer pending
+    /// are left untouched.
+    pub fn release(&self, conn: &PgConnection) -> Result<HoldRelease, DatabaseError> {
+        if self.status != HoldReleaseStatus::Pending {
+            return Ok(self.clone());
+        }
+
+        let hold = Hold::find(self.hold_id, conn)?;
+        if hold.status == HoldStatus::Deleted {
+            return self.cancel(None, conn);
+        }
+
+        let (quantity, available) = hold.quantity(conn)?;
+        let released_quantity = match self.quantity {
+            Some(q) => cmp::min(q as u32, available),
+            None => available,
+        };
+        if released_quantity > 0 {
+            hold.set_quantity(None, quantity - released_quantity, conn)?;
+        }
+
+        let release = self.update(
+            HoldReleaseEditableAttributes {
+                status: Some(HoldReleaseStatus::Released),
+                released_quantity: Some(Some(released_quantity as i64)),
+                used_quantity: Some(Some((quantity - available) as i64)),
+                released_at: Some(Some(Utc::now().naive_utc())),
+            },
+            conn,
+        )?;
+
+        DomainEvent::create(
+            DomainEventTypes::HoldReleased,
+            format!("{} tickets released from hold", released_quantity),
+            Tables::Holds,
+            Some(hold.id),
+            None,
+            Some(json!({
+                "hold_release_id": release.id,
+                "released_quantity": released_quantity,
+                "used_quantity": quantity - available
+            })),
+        )
+        .commit(conn)?;
+
+        Ok(release)
+    }
+
+    pub fn cancel(
+        &self,
+        current_user_id: Option<Uuid>,
+        conn: &PgConnection,
+    ) -> Result<HoldRelease, DatabaseError> {
+        if self.status != HoldReleaseStatus::Pending {
+            return DatabaseError::business_process_error(
+                "Only pending hold releases can be cancelled",
+            );
+        }
+
+        let release = self.update(
+            HoldReleaseEditableAttributes {
+                status: Some(HoldReleaseStatus::Cancelled),
+                ..Default::default()
+            },
+            conn,
+        )?;
+
+        DomainEvent::create(
+            DomainEventTypes::HoldReleaseCancelled,
+            "Hold release cancelled".to_string(),
+            Tables::Holds,
+            Some(self.hold_id),
+            current_user_id,
+            Some(json!({ "hold_release_id": self.id })),
+        )
+        .commit(conn)?;
+
+        Ok(release)
+    }
+
+    fn update(
+        &self,
+        attributes: HoldReleaseEditableAttributes,
+        conn: &PgConnection,
+    ) -> Result<HoldRelease, DatabaseError> {
+        diesel::update(self)
+            .set((attributes, hold_releases::updated_at.eq(dsl::now)))
+            .get_result(conn)
+            .to_db_error(ErrorCode::UpdateError, "Could not update hold release")
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "hold_releases"]
+pub struct NewHoldRelease {
+    pub hold_id: Uuid,
+    pub release_at: NaiveDateTime,
+    pub quantity: Option<i64>,
+}
+
+impl NewHoldRelease {
+    pub fn commit(
+        self,
+        current_user_id: Option<Uuid>,
+        conn: &PgConnection,
+    ) -> Result<HoldRelease, DatabaseError> {
+        if self.quantity == Some(0) {
+            return DatabaseError::validation_error(
+                "quantity",
+                "Quantity to release must be greater than 0",
+            );
+        }
+
+        let release: HoldRelease = diesel::insert_into(hold_releases::table)
+            .values(&self)
+            .get_result(conn)
+            .to_db_error(ErrorCode::InsertError, "Could not create hold release")?;
+
+        let mut action = DomainAction::create(
+            None,
+            DomainActionTypes::ReleaseHoldInventory,
+            None,
+            json!({ "hold_id": release.hold_id }),
+            Some(Tables::HoldReleases.to_string()),
+            Some(release.id),
+        );
+        // Releases scheduled in the past run straight away
+        if release.release_at > Utc::now().naive_utc() {
+            action.schedule_at(release.release_at);
+        }
+        action.commit(conn)?;
+
+        DomainEvent::create(
+            DomainEventTypes::HoldReleaseScheduled,
+            "Hold release scheduled".to_string(),
+            Tables::Holds,
+            Some(release.hold_id),
+            current_user_id,
+            Some(json!({
+                "hold_release_id": release.id,
+                "release_at": release.release_at,
+                "quantity": release.quantity
+            })),
+        )
+        .commit(conn)?;
+
+        Ok(release)
+    }
+}
@@ -5,12 +5,16 @@ use diesel::prelude::*;
 use models::*;
 use schema::holds;
 use std::borrow::Cow;
+use utils::audit;
 use utils::errors::*;
 use uuid::Uuid;
 use validator::Validate;
 use validator::*;
 use validators::{self, *};
 
+/// Comp recipients have a week to claim their tickets, the same as regular ticket transfers
+const COMP_TRANSFER_VALIDITY_SECONDS: u32 = 604_800;
+
 #[derive(Clone, Deserialize, Identifiable, Queryable, Serialize, PartialEq, Debug)]
 pub struct Hold {
     pub id: Uuid,
@@ -110,10 +114,126 @@ impl Hold {
         Ok(new_hold)
     }
 
+    /// Creates a comp for a person and immediately delivers its tickets to their wallet with a
+    /// zero value back office order. A stub user is created for the person if no user has their
+    /// email or phone number. A transfer to the person's email, or phone if there is no email,
+    /// is authorized from their wallet so the tickets can be claimed from the notification.
+    pub fn issue_comp(
+        &self,
+        current_user_id: Uuid,
+        name: String,
+        email: Option<String>,
+        phone: Option<String>,
+        quantity: u32,
+        redemption_code: Option<String>,
+        conn: &PgConnection,
+    ) -> Result<IssuedComp, DatabaseError> {
+        let (address, sent_via) = match (&email, &phone) {
+            (Some(email), _) => (email.clone(), "Email"),
+            (None, Some(phone)) => (phone.clone(), "Phone"),
+            (None, None) => {
+                return DatabaseError::validation_error(
+                    "email",
+                    "Email or phone is required to issue a comp",
+                );
+            }
+        };
+
+        let redemption_code = match redemption_code {
+            Some(redemption_code) => redemption_code,
+            None => UniqueCode::generate_redemption_code(conn)?,
+        };
+        let comp = Hold::create_comp_for_person(
+            name.clone(),
+            Some(current_user_id),
+            self.id,
+            email.clone(),
+            phone.clone(),
+            redemption_code,
+            None,
+            None,
+            quantity,
+            conn,
+        )?;
+
+        let mut existing_user = None;
+        if let Some(ref email) = email {
+            existing_user = User::find_by_email(email, conn).optional()?;
+        }
+        if existing_user.is_none() {
+            if let Some(ref phone) = phone {
+                existing_user = User::find_by_phone(phone, conn).optional()?;
+            }
+        }
+        let recipient_created = existing_user.is_none();
+        let recipient = match existing_user {
+            Some(user) => user,
+            None => {
+                let mut names = name.trim().splitn(2, ' ');
+                User::create_stub(
+                    names.next().unwrap_or("").to_string(),
+                    names.next().unwrap_or("").trim().to_string(),
+                    email,
+                    phone,
+                    conn,
+                )?
+            }
+        };
+
+        let mut order = Order::create_back_office_order(current_user_id, conn)?;
+        order.set_behalf_of_user(recipient.clone(), current_user_id, conn)?;
+        order.update_quantities(
+            current_user_id,
+            &[UpdateOrderItem {
+                ticket_type_id: comp.ticket_type_id,
+                quantity,
+                redemption_code: comp.redemption_code.clone(),
+            }],
+            false,
+            false,
+            conn,
+        )?;
+        order.add_free_payment(false, current_user_id, conn)?;
+
+        let ticket_ids = TicketInstance::find_ids_for_order(order.id, conn)?;
+        let transfer_authorization = TicketInstance::authorize_ticket_transfer(
+            recipient.id,
+            &ticket_ids,
+            COMP_TRANSFER_VALIDITY_SECONDS,
+            Some(&address),
+            Some(sent_via),
+            conn,
+        )?;
+
+        DomainEvent::create(
+            DomainEventTypes::CompIssued,
+            format!("Comp {} issued", comp.name),
+            Tables::Holds,
+            Some(comp.id),
+            Some(current_user_id),
+            Some(json!({
+                "order_id": order.id,
+                "recipient_user_id": recipient.id,
+                "transfer_key": transfer_authorization.transfer_key,
+                "quantity": quantity,
+            })),
+        )
+        .commit(conn)?;
+
+        Ok(IssuedComp {
+            comp,
+            order,
+            recipient,
+            recipient_created,
+            transfer_authorization,
+        })
+    }
+
     /// Updates a hold. Note, the quantity in the hold must be updated using
     /// `set_quantity`.
     pub fn update(
         &self,
+        current_user_id: Option<Uuid>,
         update_attrs: UpdateHoldAttributes,
         conn: &PgConnection,
     ) -> Result<Hold, DatabaseError> {
@@ -125,14 +245,26 @@ impl Hold {
 
         self.validate_record(&update_attrs, conn)?;
 
-        diesel::update(
+        let hold: Hold = diesel::update(
             holds::table
                 .filter(holds::id.eq(self.id))
                 .filter(holds::updated_at.eq(self.updated_at)),
         )
         .set((update_attrs, holds::updated_at.eq(dsl::now)))
         .get_result(conn)
-        .to_db_error(ErrorCode::UpdateError, "Could not update hold")
+        .to_db_error(ErrorCode::UpdateError, "Could not update hold")?;
+
+        DomainEvent::create(
+            DomainEventTypes::HoldUpdated,
+            format!("Hold {} updated", hold.name),
+            Tables::Holds,
+            Some(self.id),
+            current_user_id,
+            Some(json!({ "changes": audit::changes(self, &hold, &[]) })),
+        )
+        .commit(conn)?;
+
+        Ok(hold)
     }
 
     pub fn find(id: Uuid, conn: &PgConnection) -> Result<Hold, DatabaseError> {
@@ -406,6 +538,29 @@ impl Hold {
         Ok(())
     }
 
+    /// Schedules `quantity` available tickets to flow back out of the hold at `release_at`,
+    /// defaulting to the hold's `end_at`. Comps return their tickets to the parent hold. If
+    /// `quantity` is `None`, everything remaining in the hold is released.
+    pub fn schedule_release(
+        &self,
+        current_user_id: Option<Uuid>,
+        release_at: Option<NaiveDateTime>,
+        quantity: Option<u32>,
+        conn: &PgConnection,
+    ) -> Result<HoldRelease, DatabaseError> {
+        let release_at = match release_at.or(self.end_at) {
+            Some(release_at) => release_at,
+            None => {
+                return DatabaseError::validation_error(
+                    "release_at",
+                    "Release date is required when the hold has no end date",
+                );
+            }
+        };
+
+        HoldRelease::create(self.id, release_at, quantity).commit(current_user_id, conn)
+    }
+
     pub fn remove_available_quantity(
         &self,
         current_user_id: Option<Uuid>,
@@ -478,6 +633,31 @@ impl Hold {
     }
 
     pub fn into_display(self, conn: &PgConnection) -> Result<DisplayHold, DatabaseError> {
+        let analytics = CodeAnalytics::find_for_hold(&self, conn)?;
+        self.into_display_with_analytics(analytics, conn)
+    }
+
+    /// Converts a page of holds, loading their analytics in a single query
+    pub fn into_displays(
+        holds: Vec<Hold>,
+        conn: &PgConnection,
+    ) -> Result<Vec<DisplayHold>, DatabaseError> {
+        let hold_ids: Vec<Uuid> = holds.iter().map(|h| h.id).collect();
+        let mut analytics = CodeAnalytics::find_for_holds(&hold_ids, conn)?;
+        holds
+            .into_iter()
+            .map(|hold| {
+                let hold_analytics = analytics.remove(&hold.id).unwrap_or_default();
+                hold.into_display_with_analytics(hold_analytics, conn)
+            })
+            .collect()
+    }
+
+    fn into_display_with_analytics(
+        self,
+        analytics: CodeAnalytics,
+        conn: &PgConnection,
+    ) -> Result<DisplayHold, DatabaseError> {
         let (quantity, available) = self.quantity(conn)?;
 
         Ok(DisplayHold {
@@ -492,6 +672,7 @@ impl Hold {
             phone: self.phone,
             available,
             quantity,
+            analytics,
         })
     }
 
@@ -566,6 +747,18 @@ impl NewHold {
     }
 }
 
+/// A comp created by `Hold::issue_comp` along with the order that delivered its tickets
+#[derive(Debug)]
+pub struct IssuedComp {
+    pub comp: Hold,
+    pub order: Order,
+    pub recipient: User,
+    /// Set when the recipient did not have an account, so a stub user was created for them
+    pub recipient_created: bool,
+    /// Transfer of the tickets from the recipient's wallet, sent to them to claim the tickets
+    pub transfer_authorization: TransferAuthorization,
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Debug)]
 pub struct DisplayHold {
     pub id: Uuid,
@@ -579,4 +772,5 @@ pub struct DisplayHold {
     pub phone: Option<String>,
     pub available: u32,
     pub quantity: u32,
+    pub analytics: CodeAnalytics,
 }
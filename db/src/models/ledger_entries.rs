@@ -0,0 +1,315 @@
+use chrono::prelude::*;
+use diesel;
+use diesel::prelude::*;
+use schema::ledger_entries;
+use serde_json;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use tari_client::*;
+use utils::errors::*;
+use uuid::Uuid;
+
+pub const LEDGER_CREATE_ASSET: &str = "create_asset";
+pub const LEDGER_MODIFY_ASSET: &str = "modify_asset";
+pub const LEDGER_TRANSFER_TOKEN: &str = "transfer_token";
+
+/// Signed entry in the append-only ledger used by deployments without a blockchain. Each entry
+/// stores the same message payload that would be sent to Tari.
+#[derive(Clone, Debug, Deserialize, Identifiable, PartialEq, Queryable, Serialize)]
+pub struct LedgerEntry {
+    pub id: Uuid,
+    pub asset_id: String,
+    pub sequence: i64,
+    pub command: String,
+    pub payload: Value,
+    pub public_key: String,
+    pub signature: String,
+    pub previous_signature: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+impl LedgerEntry {
+    /// Validates `payload` against the current state of the asset, then signs and appends it
+    pub fn append(
+        asset_id: &str,
+        command: &str,
+        payload: Value,
+        secret_key: &str,
+        public_key: &str,
+        conn: &PgConnection,
+    ) -> Result<LedgerEntry, DatabaseError> {
+        let mut asset = if command == LEDGER_CREATE_ASSET {
+            LedgerAsset::new(asset_id)
+        } else {
+            LedgerAsset::load(asset_id, conn)?
+        };
+
+        let mut entry = NewLedgerEntry {
+            asset_id: asset_id.to_string(),
+            sequence: asset.sequence,
+            command: command.to_string(),
+            payload,
+            public_key: public_key.to_string(),
+            signature: "".to_string(),
+            previous_signature: asset.last_signature.clone(),
+        };
+        asset.apply(&entry.command, &entry.payload, public_key)?;
+        entry.signature = convert_bytes_to_hexstring(&cryptographic_signature(
+            &entry.signed_message(),
+            &convert_hexstring_to_bytes(&secret_key.to_string()),
+        )?);
+
+        diesel::insert_into(ledger_entries::table)
+            .values(&entry)
+            .get_result(conn)
+            .to_db_error(ErrorCode::InsertError, "Could not append ledger entry")
+    }
+
+    pub fn find_for_asset(
+        asset_id: &str,
+        conn: &PgConnection,
+    ) -> Result<Vec<LedgerEntry>, DatabaseError> {
+        ledger_entries::table
+            .filter(ledger_entries::asset_id.eq(asset_id))
+            .order_by(ledger_entries::sequence.asc())
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load ledger entries")
+    }
+
+    /// Checks the entry was signed by its public key
+    pub fn verify(&self) -> bool {
+        cryptographic_verify(
+            &convert_hexstring_to_bytes(&self.signature),
+            &signed_message(
+                &self.asset_id,
+                self.sequence,
+                &self.command,
+                &self.payload,
+                &self.previous_signature,
+            ),
+            &convert_hexstring_to_bytes(&self.public_key),
+        )
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "ledger_entries"]
+struct NewLedgerEntry {
+    asset_id: String,
+    sequence: i64,
+    command: String,
+    payload: Value,
+    public_key: String,
+    signature: String,
+    previous_signature: Option<String>,
+}
+
+impl NewLedgerEntry {
+    fn signed_message(&self) -> String {
+        signed_message(
+            &self.asset_id,
+            self.sequence,
+            &self.command,
+            &self.payload,
+            &self.previous_signature,
+        )
+    }
+}
+
+/// Entries are chained by signing the previous entry's signature along with their own contents
+fn signed_message(
+    asset_id: &str,
+    sequence: i64,
+    command: &str,
+    payload: &Value,
+    previous_signature: &Option<String>,
+) -> String {
+    format!(
+        "{}:{}:{}:{}:{}",
+        previous_signature
+            .as_ref()
+            .map(|s| s.as_str())
+            .unwrap_or(""),
+        asset_id,
+        sequence,
+        command,
+        payload.to_string()
+    )
+}
+
+/// State of an asset rebuilt by replaying its ledger entries
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct LedgerAsset {
+    pub asset_id: String,
+    pub name: String,
+    pub issuer: String,
+    pub total_supply: u64,
+    pub authorised_signers: Vec<String>,
+    pub rule_flags: i64,
+    pub rule_metadata: String,
+    pub expiry_date: i64,
+    /// Owners of tokens transferred away from the issuer
+    pub token_owners: HashMap<u64, String>,
+    pub nullified_token_ids: HashSet<u64>,
+    pub redeemed_token_ids: HashSet<u64>,
+    /// Number of entries applied, which is the sequence of the next entry
+    pub sequence: i64,
+    pub last_signature: Option<String>,
+}
+
+impl LedgerAsset {
+    fn new(asset_id: &str) -> LedgerAsset {
+        LedgerAsset {
+            asset_id: asset_id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Replays the asset's entries, failing if any entry is unsigned, out of sequence or invalid
+    pub fn load(asset_id: &str, conn: &PgConnection) -> Result<LedgerAsset, DatabaseError> {
+        let entries = LedgerEntry::find_for_asset(asset_id, conn)?;
+        if entries.is_empty() {
+            return Err(DatabaseError::new(
+                ErrorCode::NoResults,
+                Some(format!("Asset {} does not exist on the ledger", asset_id)),
+            ));
+        }
+
+        let mut asset = LedgerAsset::new(asset_id);
+        for entry in entries {
+            if entry.sequence != asset.sequence
+                || entry.previous_signature != asset.last_signature
+                || !entry.verify()
+            {
+                return Err(DatabaseError::new(
+                    ErrorCode::InternalError,
+                    Some(format!(
+                        "Ledger entry {} for asset {} failed verification",
+                        entry.sequence, asset_id
+                    )),
+                ));
+            }
+            asset.apply(&entry.command, &entry.payload, &entry.public_key)?;
+            asset.last_signature = Some(entry.signature);
+        }
+
+        Ok(asset)
+    }
+
+    pub fn token_owner(&self, token_id: u64) -> &str {
+        self.token_owners
+            .get(&token_id)
+            .map(|o| o.as_str())
+            .unwrap_or(&self.issuer)
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expiry_date < Utc::now().timestamp()
+    }
+
+    fn apply(
+        &mut self,
+        command: &str,
+        payload: &Value,
+        public_key: &str,
+    ) -> Result<(), DatabaseError> {
+        match command {
+            LEDGER_CREATE_ASSET => {
+                if self.sequence != 0 {
+                    return DatabaseError::business_process_error("Asset already exists on ledger");
+                }
+                let asset: MessagePayloadCreateAsset = serde_json::from_value(payload.clone())?;
+                self.name = asset.name;
+                self.issuer = public_key.to_string();
+                self.total_supply = asset.total_supply;
+                self.authorised_signers = asset.authorised_signers;
+                self.rule_flags = asset.rule_flags;
+                self.rule_metadata = asset.rule_metadata;
+                self.expiry_date = asset.expiry_date;
+            }
+            LEDGER_MODIFY_ASSET => {
+                let modification: MessagePayloadModifyAsset =
+                    serde_json::from_value(payload.clone())?;
+                let token_ids = modification.token_ids.unwrap_or(Vec::new());
+                match modification.request_type {
+                    INCREASE_SUPPLY_REQUEST => {
+                        self.require_issuer(public_key)?;
+                        let new_supply = modification.new_supply.unwrap_or(0);
+                        if new_supply < self.total_supply {
+                            return DatabaseError::business_process_error(
+                                "Asset supply cannot be decreased",
+                            );
+                        }
+                        self.total_supply = new_supply;
+                    }
+                    NULLIFY_TOKENS_REQUEST => {
+                        self.require_issuer(public_key)?;
+                        self.require_valid_tokens(&token_ids)?;
+                        self.nullified_token_ids.extend(token_ids);
+                    }
+                    REDEEM_TOKENS_REQUEST => {
+                        self.require_valid_tokens(&token_ids)?;
+                        if public_key != self.issuer
+                            && token_ids
+                                .iter()
+                                .any(|id| self.token_owner(*id) != public_key)
+                        {
+                            return DatabaseError::business_process_error(
+                                "Tokens can only be redeemed by their owner or the issuer",
+                            );
+                        }
+                        self.redeemed_token_ids.extend(token_ids);
+                    }
+                    _ => {
+                        return DatabaseError::business_process_error(
+                            "Unsupported asset modification",
+                        );
+                    }
+                }
+            }
+            LEDGER_TRANSFER_TOKEN => {
+                let transfer: MessagePayloadTransferToken =
+                    serde_json::from_value(payload.clone())?;
+                self.require_valid_tokens(&transfer.token_ids)?;
+                if transfer
+                    .token_ids
+                    .iter()
+                    .any(|id| self.token_owner(*id) != public_key)
+                {
+                    return DatabaseError::business_process_error(
+                        "Tokens can only be transferred by their owner",
+                    );
+                }
+                for token_id in transfer.token_ids {
+                    self.token_owners
+                        .insert(token_id, transfer.new_owner.clone());
+                }
+            }
+            _ => {
+                return DatabaseError::business_process_error("Unsupported ledger command");
+            }
+        }
+
+        self.sequence += 1;
+        Ok(())
+    }
+
+    fn require_issuer(&self, public_key: &str) -> Result<(), DatabaseError> {
+        if public_key != self.issuer {
+            return DatabaseError::business_process_error("Only the issuer can modify this asset");
+        }
+        Ok(())
+    }
+
+    fn require_valid_tokens(&self, token_ids: &[u64]) -> Result<(), DatabaseError> {
+        if token_ids
+            .iter()
+            .any(|id| *id >= self.total_supply || self.nullified_token_ids.contains(id))
+        {
+            return DatabaseError::business_process_error(
+                "Tokens do not exist or have been nullified",
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+use chrono::NaiveDateTime;
+use diesel;
+use diesel::expression::dsl;
+use diesel::prelude::*;
+use models::*;
+use schema::marketing_consents;
+use utils::errors::*;
+use uuid::Uuid;
+
+/// A user's answer to whether an organization may send them marketing. Users without a
+/// consent record for an organization are treated as not having opted in.
+#[derive(Clone, Debug, Deserialize, Identifiable, PartialEq, Queryable, Serialize)]
+pub struct MarketingConsent {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub organization_id: Uuid,
+    pub consented: bool,
+    pub source: MarketingConsentSources,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Insertable)]
+#[table_name = "marketing_consents"]
+struct NewMarketingConsent {
+    user_id: Uuid,
+    organization_id: Uuid,
+    consented: bool,
+    source: MarketingConsentSources,
+}
+
+impl MarketingConsent {
+    /// Records the user's latest answer, replacing any earlier one for the organization
+    pub fn record(
+        user_id: Uuid,
+        organization_id: Uuid,
+        consented: bool,
+        source: MarketingConsentSources,
+        conn: &PgConnection,
+    ) -> Result<MarketingConsent, DatabaseError> {
+        diesel::insert_into(marketing_consents::table)
+            .values(&NewMarketingConsent {
+                user_id,
+                organization_id,
+                consented,
+                source,
+            })
+            .on_conflict((
+                marketing_consents::user_id,
+                marketing_consents::organization_id,
+            ))
+            .do_update()
+            .set((
+                marketing_consents::consented.eq(consented),
+                marketing_consents::source.eq(source),
+                marketing_consents::updated_at.eq(dsl::now),
+            ))
+            .get_result(conn)
+            .to_db_error(ErrorCode::InsertError, "Could not record marketing consent")
+    }
+
+    pub fn find_for_user(
+        user_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<MarketingConsent>, DatabaseError> {
+        marketing_consents::table
+            .filter(marketing_consents::user_id.eq(user_id))
+            .order_by(marketing_consents::created_at.asc())
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load marketing consents")
+    }
+
+    /// Users who currently allow the organization to send them marketing
+    pub fn consented_user_ids(
+        organization_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<Uuid>, DatabaseError> {
+        marketing_consents::table
+            .filter(marketing_consents::organization_id.eq(organization_id))
+            .filter(marketing_consents::consented.eq(true))
+            .select(marketing_consents::user_id)
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load marketing consents")
+    }
+}
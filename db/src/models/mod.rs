@@ -1,23 +1,35 @@
 pub use self::artists::*;
 pub use self::assets::*;
 pub use self::broadcasts::*;
+pub use self::code_applications::*;
 pub use self::codes::*;
 pub use self::domain_actions::*;
 pub use self::domain_events::*;
+pub use self::email_suppressions::*;
 pub use self::enums::*;
 pub use self::event_artists::*;
 pub use self::event_interest::*;
+pub use self::event_recommendations::*;
+pub use self::event_reschedules::*;
+pub use self::event_series::*;
 pub use self::events::*;
 pub use self::external_logins::FACEBOOK_SITE;
 pub use self::external_logins::*;
+pub use self::fan_notes::*;
+pub use self::fan_segments::*;
+pub use self::fan_tags::*;
 pub use self::fans::*;
 pub use self::fee_schedule_ranges::*;
 pub use self::fee_schedules::*;
 pub use self::for_display::*;
 pub use self::history_item::*;
+pub use self::hold_releases::*;
 pub use self::holds::*;
+pub use self::ledger_entries::*;
+pub use self::marketing_consents::*;
 pub use self::order_items::*;
 pub use self::orders::*;
+pub use self::organization_email_settings::*;
 pub use self::organization_invites::*;
 pub use self::organization_users::*;
 pub use self::organizations::*;
@@ -26,6 +38,7 @@ pub use self::payment_methods::*;
 pub use self::payments::*;
 pub use self::push_notification_tokens::*;
 pub use self::redeemable_ticket::*;
+pub use self::refund_requests::*;
 pub use self::refunded_tickets::*;
 pub use self::regions::*;
 pub use self::reports::*;
@@ -38,6 +51,8 @@ pub use self::ticket_instances::*;
 pub use self::ticket_pricing::*;
 pub use self::ticket_type_codes::*;
 pub use self::ticket_types::*;
+pub use self::unique_codes::*;
+pub use self::user_data_exports::*;
 pub use self::users::*;
 pub use self::venues::*;
 pub use self::wallets::*;
@@ -50,22 +65,34 @@ pub mod concerns;
 mod artists;
 mod assets;
 mod broadcasts;
+mod code_applications;
 mod codes;
 mod domain_actions;
 mod domain_events;
+mod email_suppressions;
 pub mod enums;
 mod event_artists;
 mod event_interest;
+mod event_recommendations;
+mod event_reschedules;
+mod event_series;
 mod events;
 mod external_logins;
+mod fan_notes;
+mod fan_segments;
+mod fan_tags;
 mod fans;
 mod fee_schedule_ranges;
 mod fee_schedules;
 mod for_display;
 mod history_item;
+mod hold_releases;
 mod holds;
+mod ledger_entries;
+mod marketing_consents;
 mod order_items;
 mod orders;
+mod organization_email_settings;
 mod organization_invites;
 mod organization_users;
 mod organizations;
@@ -74,6 +101,7 @@ mod payment_methods;
 mod payments;
 mod push_notification_tokens;
 mod redeemable_ticket;
+mod refund_requests;
 mod refunded_tickets;
 mod regions;
 mod reports;
@@ -85,6 +113,8 @@ mod ticket_instances;
 mod ticket_pricing;
 mod ticket_type_codes;
 mod ticket_types;
+mod unique_codes;
+mod user_data_exports;
 mod users;
 mod venues;
 mod wallets;
@@ -40,6 +40,7 @@ pub struct OrderItem {
     pub(crate) company_fee_in_cents: i64,
     pub(crate) client_fee_in_cents: i64,
     pub refunded_quantity: i64,
+    pub unique_code_id: Option<Uuid>,
 }
 
 impl OrderItem {
@@ -80,19 +81,60 @@ impl OrderItem {
             return DatabaseError::business_process_error(
                 "Order item refund failed as requested refund quantity exceeds remaining quantity",
             );
+        } else if self.item_type == OrderItemTypes::Discount {
+            return DatabaseError::business_process_error(
+                "Discounts are refunded along with the tickets they apply to",
+            );
         }
 
         self.refunded_quantity += 1;
 
         let mut refund_amount_in_cents = self.unit_price_in_cents;
-        // Refund fees if ticket is being refunded
-        if refund_fees && self.item_type == OrderItemTypes::Tickets {
-            let fee_item = self.find_fee_item(conn)?;
-            if let Some(mut fee_item) = fee_item {
-                refund_amount_in_cents += fee_item.refund_one_unit(true, conn)? as i64;
+        if self.item_type == OrderItemTypes::Tickets {
+            let remaining_quantity = self.quantity - self.refunded_quantity;
+            let code = self.code(conn)?;
+
+            // Take back any discount the remaining tickets no longer qualify for, e.g. the free
+            // ticket of a buy X get Y deal
+            let mut discounted_quantity = 0;
+            if let Some(mut discount_item) = self.find_discount_item(conn)? {
+                discounted_quantity = cmp::min(
+                    discount_item.quantity - discount_item.refunded_quantity,
+                    match code {
+                        Some(ref code) => code.discounted_quantity(remaining_quantity),
+                        None => remaining_quantity,
+                    },
+                );
+                while discount_item.quantity - discount_item.refunded_quantity > discounted_quantity
+                {
+                    discount_item.refunded_quantity += 1;
+                    refund_amount_in_cents += discount_item.unit_price_in_cents;
+                }
+                discount_item.update_refunded_quantity(conn)?;
+            }
+
+            // Refund fees if ticket is being refunded, fees were not charged on units where
+            // the code waives them
+            if refund_fees {
+                if let Some(mut fee_item) = self.find_fee_item(conn)? {
+                    let fee_quantity = match code {
+                        Some(ref code) if code.waive_fees => {
+                            remaining_quantity - discounted_quantity
+                        }
+                        _ => remaining_quantity,
+                    };
+                    if fee_item.quantity - fee_item.refunded_quantity > fee_quantity {
+                        refund_amount_in_cents += fee_item.refund_one_unit(true, conn)? as i64;
+                    }
+                }
             }
         }
 
+        self.update_refunded_quantity(conn)?;
+        Ok(cmp::max(0, refund_amount_in_cents) as u32)
+    }
+
+    fn update_refunded_quantity(&self, conn: &PgConnection) -> Result<(), DatabaseError> {
         diesel::update(order_items::table.filter(order_items::id.eq(self.id)))
             .set((
                 order_items::updated_at.eq(dsl::now),
@@ -100,7 +142,7 @@ impl OrderItem {
             ))
             .execute(conn)
             .to_db_error(ErrorCode::UpdateError, "Could not refund ticket instance")?;
-        Ok(refund_amount_in_cents as u32)
+        Ok(())
     }
 
     pub fn code(&self, conn: &PgConnection) -> Result<Option<Code>, DatabaseError> {
@@ -139,19 +181,22 @@ impl OrderItem {
         if let Some(code_id) = self.code_id {
             let code = Code::find(code_id, conn)?;
             if code.code_type == CodeTypes::Discount {
-                let mut discount = 0;
-                if let Some(discount_percent) = code.discount_as_percentage {
-                    discount = cmp::min(
-                        ((self.unit_price_in_cents as f32) * (discount_percent as f32) / 100.0f32)
-                            as i64,
-                        self.unit_price_in_cents,
-                    );
-                } else if let Some(discount_in_cents) = code.discount_in_cents {
-                    discount = cmp::min(discount_in_cents, self.unit_price_in_cents);
+                let discounted_quantity = code.discounted_quantity(self.quantity);
+                let mut discount = code.discount_per_unit(self.unit_price_in_cents);
+                if let Some(max_order_discount_in_cents) = code.max_order_discount_in_cents {
+                    if discounted_quantity > 0 {
+                        let remaining_discount = cmp::max(
+                            0,
+                            max_order_discount_in_cents
+                                - self.other_discounts_for_code(code.id, conn)?,
+                        );
+                        discount = cmp::min(discount, remaining_discount / discounted_quantity);
+                    }
                 }
-                if discount > 0 {
+
+                if discount > 0 && discounted_quantity > 0 {
                     if let Some(mut di) = discount_item {
-                        di.quantity = self.quantity;
+                        di.quantity = discounted_quantity;
                         di.unit_price_in_cents = -discount;
                         di.update(conn)?;
                     } else {
@@ -159,7 +204,7 @@ impl OrderItem {
                             order_id: self.order_id,
                             item_type: OrderItemTypes::Discount,
                             event_id: self.event_id,
-                            quantity: self.quantity,
+                            quantity: discounted_quantity,
                             unit_price_in_cents: -discount,
                             company_fee_in_cents: 0,
                             client_fee_in_cents: 0,
@@ -180,6 +225,34 @@ impl OrderItem {
         Ok(())
     }
 
+    /// Total discount given by the code on the order's other items, used to apply order limits
+    fn other_discounts_for_code(
+        &self,
+        code_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<i64, DatabaseError> {
+        let parent_ids: Vec<Uuid> = order_items::table
+            .filter(order_items::order_id.eq(self.order_id))
+            .filter(order_items::code_id.eq(code_id))
+            .filter(order_items::id.ne(self.id))
+            .select(order_items::id)
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load order items for code")?;
+        let discounts: Vec<(i64, i64)> = order_items::table
+            .filter(order_items::parent_id.eq_any(parent_ids))
+            .filter(order_items::item_type.eq(OrderItemTypes::Discount))
+            .select((order_items::quantity, order_items::unit_price_in_cents))
+            .load(conn)
+            .to_db_error(
+                ErrorCode::QueryError,
+                "Could not load order discounts for code",
+            )?;
+        Ok(discounts
+            .iter()
+            .map(|(quantity, unit_price_in_cents)| -quantity * unit_price_in_cents)
+            .sum())
+    }
+
     pub(crate) fn update_fees(
         &self,
         order: &Order,
@@ -205,13 +278,24 @@ impl OrderItem {
 
         let discount_item = self.find_discount_item(conn)?;
 
+        // Fees are based on the discounted price when every unit is discounted
         let unit_price_with_discount = match discount_item {
-            Some(di) => self.unit_price_in_cents + di.unit_price_in_cents,
-            None => self.unit_price_in_cents,
+            Some(ref di) if di.quantity >= self.quantity => {
+                self.unit_price_in_cents + di.unit_price_in_cents
+            }
+            _ => self.unit_price_in_cents,
         };
 
+        let mut fee_quantity = self.quantity;
+        if let Some(code) = self.code(conn)? {
+            if code.waive_fees {
+                fee_quantity -= discount_item.map(|di| di.quantity).unwrap_or(0);
+            }
+        }
+
         if fee_schedule_ranges.len() > 0
             && unit_price_with_discount >= fee_schedule_ranges[0].min_price_in_cents
+            && fee_quantity > 0
         {
             let fee_schedule_range = ticket_type
                 .fee_schedule(conn)?
@@ -230,7 +314,7 @@ impl OrderItem {
 
             match fee_item {
                 Some(mut fee_item) => {
-                    fee_item.quantity = self.quantity;
+                    fee_item.quantity = fee_quantity;
                     fee_item.unit_price_in_cents = fee_schedule_range.fee_in_cents;
                     fee_item.company_fee_in_cents = fee_schedule_range.company_fee_in_cents;
                     fee_item.client_fee_in_cents = fee_schedule_range.client_fee_in_cents;
@@ -245,7 +329,7 @@ impl OrderItem {
                         fee_schedule_range_id: Some(fee_schedule_range.id),
                         company_fee_in_cents: fee_schedule_range.company_fee_in_cents,
                         client_fee_in_cents: fee_schedule_range.client_fee_in_cents,
-                        quantity: self.quantity,
+                        quantity: fee_quantity,
                         parent_id: Some(self.id),
                     }
                     .commit(conn)?;
@@ -315,6 +399,11 @@ impl OrderItem {
             "quantity",
             OrderItem::code_id_max_uses_valid(self.order_id, self.code_id, self.quantity, conn)?,
         );
+        let validation_errors = validators::append_validation_error(
+            validation_errors,
+            "unique_code_id",
+            OrderItem::unique_code_id_unused(self.order_id, self.unique_code_id, conn)?,
+        );
         Ok(validation_errors?)
     }
 
@@ -420,6 +509,28 @@ impl OrderItem {
         }
     }
 
+    fn unique_code_id_unused(
+        order_id: Uuid,
+        unique_code_id: Option<Uuid>,
+        conn: &PgConnection,
+    ) -> Result<Result<(), ValidationError>, DatabaseError> {
+        match unique_code_id {
+            None => return Ok(Ok(())),
+            Some(unique_code_id) => {
+                if UniqueCode::find_number_of_uses(unique_code_id, Some(order_id), conn)? > 0 {
+                    let mut validation_error = create_validation_error(
+                        "unique_code_used",
+                        "Redemption code has already been used",
+                    );
+                    validation_error.add_param(Cow::from("order_id"), &order_id);
+                    validation_error.add_param(Cow::from("unique_code_id"), &unique_code_id);
+                    return Ok(Err(validation_error));
+                }
+                Ok(Ok(()))
+            }
+        }
+    }
+
     fn quantity_valid_increment(
         new_record: bool,
         item_type: OrderItemTypes,
@@ -479,7 +590,7 @@ impl OrderItem {
              WHEN item_type = 'Discount' THEN 'Discount'
              ELSE e.name || ' - ' || tt.name
            END AS description,
-           COALESCE(h.redemption_code, c.redemption_code) as redemption_code,
+           COALESCE(h.redemption_code, uc.redemption_code, c.redemption_code) as redemption_code,
            CASE
              -- Null prevents serialization
              WHEN o.status <> 'Draft' THEN null
@@ -508,6 +619,7 @@ impl OrderItem {
                LIMIT 1
            )
            LEFT JOIN codes c ON oi.code_id = c.id
+           LEFT JOIN unique_codes uc ON oi.unique_code_id = uc.id
            LEFT JOIN (
                SELECT count(ti.id) as count, oi.id
                FROM order_items oi
@@ -587,6 +699,7 @@ pub(crate) struct NewTicketsOrderItem {
     pub ticket_pricing_id: Uuid,
     pub hold_id: Option<Uuid>,
     pub code_id: Option<Uuid>,
+    pub unique_code_id: Option<Uuid>,
 }
 
 impl NewTicketsOrderItem {
@@ -626,6 +739,11 @@ impl NewTicketsOrderItem {
             "quantity",
             OrderItem::code_id_max_uses_valid(self.order_id, self.code_id, self.quantity, conn)?,
         );
+        validation_errors = validators::append_validation_error(
+            validation_errors,
+            "unique_code_id",
+            OrderItem::unique_code_id_unused(self.order_id, self.unique_code_id, conn)?,
+        );
         validation_errors = validators::append_validation_error(
             validation_errors,
             "code_id",
@@ -15,7 +15,7 @@ use serde_json;
 use serde_json::Value;
 use std::borrow::Cow;
 use std::cmp;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use time::Duration;
 use utils::dates::*;
 use utils::errors::*;
@@ -202,8 +202,25 @@ impl Order {
         user_id: Uuid,
         conn: &PgConnection,
     ) -> Result<u32, DatabaseError> {
-        let mut total_to_be_refunded: u32 = 0;
-        for refund_item in refund_items {
+        Ok(self
+            .refund_by_organization(refund_items, user_id, conn)?
+            .values()
+            .sum())
+    }
+
+    /// Refunds the items like `refund`, returning the amount to be refunded for each organization
+    /// the order spans. Items not tied to an event are returned under `None`.
+    pub fn refund_by_organization(
+        &self,
+        refund_items: Vec<RefundItem>,
+        user_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<BTreeMap<Option<Uuid>, u32>, DatabaseError> {
+        // Orders can span organizations, each is sent its own part of the refund
+        let mut event_organization_ids: HashMap<Uuid, Uuid> = HashMap::new();
+        let mut refunds_by_organization: BTreeMap<Option<Uuid>, (Vec<&RefundItem>, u32)> =
+            BTreeMap::new();
+        for refund_item in &refund_items {
             let mut order_item = OrderItem::find(refund_item.order_item_id, conn)?;
 
             if order_item.order_id != self.id {
@@ -212,12 +229,15 @@ impl Order {
                 );
             }
 
+            let organization_id =
+                item_organization_id(&order_item, &mut event_organization_ids, conn)?;
+
             let ticket_instance = match refund_item.ticket_instance_id {
                 Some(id) => Some(TicketInstance::find(id, conn)?),
                 None => None,
             };
 
-            if order_item.item_type == OrderItemTypes::Tickets
+            let refunded_amount = if order_item.item_type == OrderItemTypes::Tickets
                 || order_item.item_type == OrderItemTypes::PerUnitFees
             {
                 match ticket_instance {
@@ -257,19 +277,69 @@ impl Order {
                             )?;
                         }
 
-                        total_to_be_refunded += order_item.refund_one_unit(refund_fees, conn)?;
+                        order_item.refund_one_unit(refund_fees, conn)?
                     }
                 }
             } else {
-                total_to_be_refunded += order_item.refund_one_unit(true, conn)?;
-            }
+                order_item.refund_one_unit(true, conn)?
+            };
+
+            let organization_refund = refunds_by_organization
+                .entry(organization_id)
+                .or_insert((Vec::new(), 0));
+            organization_refund.0.push(refund_item);
+            organization_refund.1 += refunded_amount;
         }
 
         for mut event_fee_item in self.event_fee_items_with_no_associated_items(conn)? {
-            total_to_be_refunded += event_fee_item.refund_one_unit(true, conn)?;
+            let organization_id =
+                item_organization_id(&event_fee_item, &mut event_organization_ids, conn)?;
+            let refunded_amount = event_fee_item.refund_one_unit(true, conn)?;
+            refunds_by_organization
+                .entry(organization_id)
+                .or_insert((Vec::new(), 0))
+                .1 += refunded_amount;
+        }
+
+        let mut amounts_by_organization = BTreeMap::new();
+        for (organization_id, (items, amount)) in refunds_by_organization {
+            amounts_by_organization.insert(organization_id, amount);
+            let mut domain_event = DomainEvent::create(
+                DomainEventTypes::OrderRefunded,
+                "Order refunded".to_string(),
+                Tables::Orders,
+                Some(self.id),
+                Some(user_id),
+                Some(json!({
+                    "refund_items": items,
+                    "amount": amount
+                })),
+            );
+            domain_event.organization_id = organization_id;
+            domain_event.commit(conn)?;
         }
 
-        Ok(total_to_be_refunded)
+        Ok(amounts_by_organization)
+    }
+
+    /// Holders refunding their own tickets during a refund window may only refund tickets that
+    /// have not been used
+    pub fn validate_self_serve_refund(
+        &self,
+        refund_items: &[RefundItem],
+        conn: &PgConnection,
+    ) -> Result<(), DatabaseError> {
+        for refund_item in refund_items {
+            if let Some(ticket_instance_id) = refund_item.ticket_instance_id {
+                let ticket_instance = TicketInstance::find(ticket_instance_id, conn)?;
+                if ticket_instance.status == TicketInstanceStatus::Redeemed {
+                    return DatabaseError::business_process_error(
+                        "Redeemed tickets cannot be refunded",
+                    );
+                }
+            }
+        }
+        Ok(())
     }
 
     fn event_fee_items_with_no_associated_items(
@@ -390,6 +460,33 @@ impl Order {
         Ok(())
     }
 
+    /// Creates an order placed by staff outside of the cart, for example when issuing comps
+    pub(crate) fn create_back_office_order(
+        current_user_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Order, DatabaseError> {
+        let order = NewOrder {
+            user_id: current_user_id,
+            status: OrderStatus::Draft,
+            expires_at: None,
+            order_type: OrderTypes::BackOffice.to_string(),
+            create_user_agent: None,
+        }
+        .commit(conn)?;
+
+        DomainEvent::create(
+            DomainEventTypes::OrderCreated,
+            "Order created".into(),
+            Tables::Orders,
+            Some(order.id),
+            Some(current_user_id),
+            Some(json!(order)),
+        )
+        .commit(conn)?;
+
+        Ok(order)
+    }
+
     pub fn find_or_create_cart(user: &User, conn: &PgConnection) -> Result<Order, DatabaseError> {
         // Do a quick check to find the cart linked to the user.
         let cart = Order::find_cart_for_user(user.id, conn)?;
@@ -679,6 +776,7 @@ impl Order {
             hold: Option<Hold>,
             code_id: Option<Uuid>,
             code: Option<Code>,
+            unique_code_id: Option<Uuid>,
             update_order_item: &'a UpdateOrderItem,
         }
 
@@ -690,12 +788,14 @@ impl Order {
                 Some(r) => match Hold::find_by_redemption_code(r, conn).optional()? {
                     Some(hold) => {
                         hold.confirm_hold_valid()?;
+                        CodeApplication::record(self.id, None, Some(hold.id), conn)?;
                         MatchData {
                             index: Some(index),
                             hold_id: Some(hold.id),
                             hold: Some(hold),
                             code_id: None,
                             code: None,
+                            unique_code_id: None,
                             update_order_item: item,
                         }
                     }
@@ -704,12 +804,19 @@ impl Order {
                     {
                         Some(code_availability) => {
                             code_availability.code.confirm_code_valid()?;
+                            CodeApplication::record(
+                                self.id,
+                                Some(code_availability.code.id),
+                                None,
+                                conn,
+                            )?;
                             MatchData {
                                 index: Some(index),
                                 hold_id: None,
                                 hold: None,
                                 code_id: Some(code_availability.code.id),
                                 code: Some(code_availability.code),
+                                unique_code_id: code_availability.unique_code_id,
                                 update_order_item: item,
                             }
                         }
@@ -727,6 +834,7 @@ impl Order {
                     hold: None,
                     code_id: None,
                     code: None,
+                    unique_code_id: None,
                     update_order_item: item,
                 },
             });
@@ -745,6 +853,7 @@ impl Order {
                             == current_line.ticket_type_id
                         && match_data.hold_id == current_line.hold_id
                         && match_data.code_id == current_line.code_id
+                        && match_data.unique_code_id == current_line.unique_code_id
                 });
 
                 if let Some(match_data) = matching_result {
@@ -821,6 +930,7 @@ impl Order {
                                 unit_price_in_cents: price_in_cents,
                                 hold_id: match_data.hold_id,
                                 code_id: match_data.code_id,
+                                unique_code_id: match_data.unique_code_id,
                             }
                             .commit(conn)?;
                             TicketInstance::reserve_tickets(
@@ -917,6 +1027,7 @@ impl Order {
                 unit_price_in_cents: price_in_cents,
                 hold_id: match_data.hold_id,
                 code_id: match_data.code_id,
+                unique_code_id: match_data.unique_code_id,
             }
             .commit(conn)?;
 
@@ -1027,8 +1138,10 @@ impl Order {
                         let discount_item = o.find_discount_item(conn)?;
 
                         let unit_price_with_discount = match discount_item {
-                            Some(di) => o.unit_price_in_cents + di.unit_price_in_cents,
-                            None => o.unit_price_in_cents,
+                            Some(ref di) if di.quantity >= o.quantity => {
+                                o.unit_price_in_cents + di.unit_price_in_cents
+                            }
+                            _ => o.unit_price_in_cents,
                         };
 
                         o.update_fees(&self, conn)?;
@@ -1405,6 +1518,11 @@ impl Order {
                         provider: PaymentProviders::Globee,
                         display_name: "Pay with crypto".to_string(),
                     }),
+                    PaymentProviders::Paypal => Some(AllowedPaymentMethod {
+                        method: "Provider".to_string(),
+                        provider: PaymentProviders::Paypal,
+                        display_name: "PayPal".to_string(),
+                    }),
                     _ => None,
                 })
                 .collect();
@@ -1922,6 +2040,20 @@ impl Order {
         Ok(total)
     }
 
+    /// Portion of the order total owed to the platform rather than the organization
+    pub fn company_fees_in_cents(&self, conn: &PgConnection) -> Result<i64, DatabaseError> {
+        let order_items = self.items(conn)?;
+        let mut total = 0;
+
+        for item in order_items.iter().filter(|i| {
+            i.item_type == OrderItemTypes::PerUnitFees || i.item_type == OrderItemTypes::EventFees
+        }) {
+            total += item.company_fee_in_cents * (item.quantity - item.refunded_quantity);
+        }
+
+        Ok(total)
+    }
+
     /// Updates the lock version in the database and forces a Concurrency error if
     /// another process has updated it
     pub fn lock_version(&mut self, conn: &PgConnection) -> Result<(), DatabaseError> {
@@ -2055,6 +2187,24 @@ pub struct UpdateOrderItem {
     pub redemption_code: Option<String>,
 }
 
+/// Organization of the event an order item is for, looked up once per event
+fn item_organization_id(
+    order_item: &OrderItem,
+    event_organization_ids: &mut HashMap<Uuid, Uuid>,
+    conn: &PgConnection,
+) -> Result<Option<Uuid>, DatabaseError> {
+    match order_item.event_id {
+        Some(event_id) => {
+            if !event_organization_ids.contains_key(&event_id) {
+                let event = Event::find(event_id, conn)?;
+                event_organization_ids.insert(event_id, event.organization_id);
+            }
+            Ok(event_organization_ids.get(&event_id).cloned())
+        }
+        None => Ok(None),
+    }
+}
+
 #[test]
 fn parse_order_number() {
     let id = Uuid::parse_str("01234567-1234-1234-1234-1234567890ab").unwrap();
@@ -0,0 +1,180 @@
+use chrono::NaiveDateTime;
+use diesel;
+use diesel::expression::dsl;
+use diesel::prelude::*;
+use models::*;
+use schema::organization_email_settings;
+use utils::errors::*;
+use uuid::Uuid;
+use validator::Validate;
+use validators;
+
+/// Branding and sender identity used for an organization's transactional emails. Any value
+/// left blank falls back to the platform default.
+#[derive(
+    Associations, Clone, Debug, Deserialize, Identifiable, PartialEq, Queryable, Serialize,
+)]
+#[belongs_to(Organization)]
+#[table_name = "organization_email_settings"]
+pub struct OrganizationEmailSettings {
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    /// Only used for emails sent through the organization's own SendGrid account
+    pub from_email: Option<String>,
+    pub from_name: Option<String>,
+    pub logo_url: Option<String>,
+    pub primary_color: Option<String>,
+    pub secondary_color: Option<String>,
+    pub purchase_completed_template_id: Option<String>,
+    pub transfer_tickets_template_id: Option<String>,
+    pub org_invite_template_id: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(AsChangeset, Default, Deserialize, Validate)]
+#[table_name = "organization_email_settings"]
+pub struct OrganizationEmailSettingsEditableAttributes {
+    #[validate(email(message = "From email is invalid"))]
+    #[serde(default, deserialize_with = "double_option_deserialize_unless_blank")]
+    pub from_email: Option<Option<String>>,
+    #[serde(default, deserialize_with = "double_option_deserialize_unless_blank")]
+    pub from_name: Option<Option<String>>,
+    #[validate(url(message = "Logo URL is invalid"))]
+    #[serde(default, deserialize_with = "double_option_deserialize_unless_blank")]
+    pub logo_url: Option<Option<String>>,
+    #[serde(default, deserialize_with = "double_option_deserialize_unless_blank")]
+    pub primary_color: Option<Option<String>>,
+    #[serde(default, deserialize_with = "double_option_deserialize_unless_blank")]
+    pub secondary_color: Option<Option<String>>,
+    #[serde(default, deserialize_with = "double_option_deserialize_unless_blank")]
+    pub purchase_completed_template_id: Option<Option<String>>,
+    #[serde(default, deserialize_with = "double_option_deserialize_unless_blank")]
+    pub transfer_tickets_template_id: Option<Option<String>>,
+    #[serde(default, deserialize_with = "double_option_deserialize_unless_blank")]
+    pub org_invite_template_id: Option<Option<String>>,
+}
+
+#[derive(Insertable)]
+#[table_name = "organization_email_settings"]
+struct NewOrganizationEmailSettings {
+    organization_id: Uuid,
+}
+
+impl OrganizationEmailSettings {
+    pub fn find_by_organization_id(
+        organization_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Option<OrganizationEmailSettings>, DatabaseError> {
+        organization_email_settings::table
+            .filter(organization_email_settings::organization_id.eq(organization_id))
+            .first(conn)
+            .to_db_error(
+                ErrorCode::QueryError,
+                "Could not load organization email settings",
+            )
+            .optional()
+    }
+
+    /// Updates the organization's email settings, creating them if this is the first change
+    pub fn create_or_update(
+        organization_id: Uuid,
+        attributes: OrganizationEmailSettingsEditableAttributes,
+        current_user_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<OrganizationEmailSettings, DatabaseError> {
+        attributes.validate()?;
+        let existing_settings =
+            OrganizationEmailSettings::find_by_organization_id(organization_id, conn)?;
+        OrganizationEmailSettings::validate_from_email(
+            organization_id,
+            existing_settings.as_ref(),
+            &attributes,
+            conn,
+        )?;
+
+        let settings = match existing_settings {
+            Some(settings) => settings,
+            None => diesel::insert_into(organization_email_settings::table)
+                .values(NewOrganizationEmailSettings { organization_id })
+                .get_result(conn)
+                .to_db_error(
+                    ErrorCode::InsertError,
+                    "Could not create organization email settings",
+                )?,
+        };
+
+        let settings: OrganizationEmailSettings = diesel::update(&settings)
+            .set((
+                &attributes,
+                organization_email_settings::updated_at.eq(dsl::now),
+            ))
+            .get_result(conn)
+            .to_db_error(
+                ErrorCode::UpdateError,
+                "Could not update organization email settings",
+            )?;
+
+        DomainEvent::create(
+            DomainEventTypes::OrganizationEmailSettingsUpdated,
+            "Organization email settings updated".to_string(),
+            Tables::Organizations,
+            Some(organization_id),
+            Some(current_user_id),
+            Some(json!(settings)),
+        )
+        .commit(conn)?;
+
+        Ok(settings)
+    }
+
+    /// The sender address is only used with the organization's own SendGrid account and
+    /// templates, so it cannot be set before those are configured
+    fn validate_from_email(
+        organization_id: Uuid,
+        settings: Option<&OrganizationEmailSettings>,
+        attributes: &OrganizationEmailSettingsEditableAttributes,
+        conn: &PgConnection,
+    ) -> Result<(), DatabaseError> {
+        // Values being changed take precedence over the saved settings
+        let value = |attribute: &Option<Option<String>>, current: Option<&Option<String>>| {
+            attribute
+                .clone()
+                .unwrap_or_else(|| current.cloned().unwrap_or(None))
+        };
+        if value(&attributes.from_email, settings.map(|s| &s.from_email)).is_none() {
+            return Ok(());
+        }
+
+        let has_template = vec![
+            (
+                &attributes.purchase_completed_template_id,
+                settings.map(|s| &s.purchase_completed_template_id),
+            ),
+            (
+                &attributes.transfer_tickets_template_id,
+                settings.map(|s| &s.transfer_tickets_template_id),
+            ),
+            (
+                &attributes.org_invite_template_id,
+                settings.map(|s| &s.org_invite_template_id),
+            ),
+        ]
+        .into_iter()
+        .any(|(attribute, current)| value(attribute, current).is_some());
+        let organization = Organization::find(organization_id, conn)?;
+
+        if organization.sendgrid_api_key.is_none() || !has_template {
+            let validation_errors = validators::append_validation_error(
+                Ok(()),
+                "from_email",
+                Err(validators::create_validation_error(
+                    "from_email_requires_sendgrid_template",
+                    "From email requires a SendGrid API key and template for the organization",
+                )),
+            );
+            return Ok(validation_errors?);
+        }
+        Ok(())
+    }
+}
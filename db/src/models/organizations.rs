@@ -8,14 +8,18 @@ use diesel::sql_types::{BigInt, Text, Timestamp};
 use models::scopes;
 use models::*;
 use schema::{
-    assets, events, fee_schedules, order_items, organization_users, organizations, ticket_types,
-    users, venues,
+    assets, domain_events, events, fee_schedules, order_items, organization_users, organizations,
+    ticket_types, users, venues,
 };
+use serde_with::rust::double_option;
 use std::collections::HashMap;
+use utils::audit;
 use utils::encryption::*;
 use utils::errors::*;
 use utils::text;
 use uuid::Uuid;
+use validator::ValidationError;
+use validators;
 
 #[derive(
     Identifiable,
@@ -53,6 +57,10 @@ pub struct Organization {
     pub cc_fee_percent: f32,
     pub globee_api_key: Option<String>,
     pub max_instances_per_ticket_type: i64,
+    pub stripe_connect_account_id: Option<String>,
+    pub stripe_connect_enabled: bool,
+    pub refund_request_auto_approve_days: Option<i32>,
+    pub mailchimp_api_key: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -87,6 +95,10 @@ pub struct NewOrganization {
     #[serde(default, deserialize_with = "deserialize_unless_blank")]
     pub globee_api_key: Option<String>,
     pub max_instances_per_ticket_type: Option<i64>,
+    #[serde(default, deserialize_with = "double_option::deserialize")]
+    pub refund_request_auto_approve_days: Option<Option<i32>>,
+    #[serde(default, deserialize_with = "deserialize_unless_blank")]
+    pub mailchimp_api_key: Option<String>,
 }
 
 #[derive(Default, Serialize, Clone)]
@@ -102,6 +114,10 @@ impl NewOrganization {
         current_user_id: Uuid,
         conn: &PgConnection,
     ) -> Result<Organization, DatabaseError> {
+        Organization::validate_refund_request_auto_approve_days(
+            self.refund_request_auto_approve_days,
+        )?;
+
         let mut updated_organisation = self;
         if encryption_key.len() > 0 {
             if let Some(key) = updated_organisation.sendgrid_api_key.clone() {
@@ -116,6 +132,9 @@ impl NewOrganization {
             if let Some(key) = updated_organisation.globee_api_key.clone() {
                 updated_organisation.globee_api_key = Some(encrypt(&key, encryption_key)?);
             }
+            if let Some(key) = updated_organisation.mailchimp_api_key.clone() {
+                updated_organisation.mailchimp_api_key = Some(encrypt(&key, encryption_key)?);
+            }
         }
 
         let org: Organization = diesel::insert_into(organizations::table)
@@ -138,6 +157,7 @@ impl NewOrganization {
                 ErrorCode::UpdateError,
                 "Could not set the fee schedule for this organization",
             )?;
+        assign_fee_schedule_domain_events(org.fee_schedule_id, org.id, conn)?;
 
         DomainEvent::create(
             DomainEventTypes::OrganizationCreated,
@@ -184,9 +204,23 @@ pub struct OrganizationEditableAttributes {
     #[serde(default, deserialize_with = "double_option_deserialize_unless_blank")]
     pub globee_api_key: Option<Option<String>>,
     pub max_instances_per_ticket_type: Option<i64>,
+    #[serde(default, deserialize_with = "double_option::deserialize")]
+    pub refund_request_auto_approve_days: Option<Option<i32>>,
+    #[serde(default, deserialize_with = "double_option_deserialize_unless_blank")]
+    pub mailchimp_api_key: Option<Option<String>>,
 }
 
 impl Organization {
+    /// Keys kept out of the audit log
+    const REDACTED_FIELDS: [&'static str; 6] = [
+        "sendgrid_api_key",
+        "google_ga_key",
+        "facebook_pixel_key",
+        "globee_api_key",
+        "mailchimp_api_key",
+        "stripe_connect_account_id",
+    ];
+
     pub fn create(name: &str, fee_schedule_id: Uuid) -> NewOrganization {
         NewOrganization {
             name: name.into(),
@@ -197,10 +231,15 @@ impl Organization {
 
     pub fn update(
         &self,
+        current_user_id: Option<Uuid>,
         mut attributes: OrganizationEditableAttributes,
         encryption_key: &String,
         conn: &PgConnection,
     ) -> Result<Organization, DatabaseError> {
+        Organization::validate_refund_request_auto_approve_days(
+            attributes.refund_request_auto_approve_days,
+        )?;
+
         if encryption_key.len() > 0 {
             if let Some(Some(key)) = attributes.sendgrid_api_key {
                 attributes.sendgrid_api_key = Some(Some(encrypt(&key, encryption_key)?));
@@ -214,6 +253,9 @@ impl Organization {
             if let Some(Some(key)) = attributes.globee_api_key {
                 attributes.globee_api_key = Some(Some(encrypt(&key, encryption_key)?));
             }
+            if let Some(Some(key)) = attributes.mailchimp_api_key {
+                attributes.mailchimp_api_key = Some(Some(encrypt(&key, encryption_key)?));
+            }
         }
 
         let event_fee = attributes
@@ -225,14 +267,76 @@ impl Organization {
                 .clone()
                 .unwrap_or(self.company_event_fee_in_cents);
 
-        diesel::update(&*self)
+        let organization: Organization = diesel::update(&*self)
             .set((
                 attributes,
                 organizations::updated_at.eq(dsl::now),
                 organizations::event_fee_in_cents.eq(event_fee),
             ))
             .get_result(conn)
-            .to_db_error(ErrorCode::UpdateError, "Could not update organization")
+            .to_db_error(ErrorCode::UpdateError, "Could not update organization")?;
+
+        DomainEvent::create(
+            DomainEventTypes::OrganizationUpdated,
+            "Organization updated".to_string(),
+            Tables::Organizations,
+            Some(self.id),
+            current_user_id,
+            Some(json!({
+                "changes": audit::changes(self, &organization, &Organization::REDACTED_FIELDS)
+            })),
+        )
+        .commit(conn)?;
+
+        Ok(organization)
+    }
+
+    fn validate_refund_request_auto_approve_days(
+        refund_request_auto_approve_days: Option<Option<i32>>,
+    ) -> Result<(), DatabaseError> {
+        let days_valid: Result<(), ValidationError> = match refund_request_auto_approve_days {
+            Some(Some(days)) => validators::validate_greater_than(
+                days,
+                0,
+                "number_must_be_positive",
+                "Refund request auto approve days must not be negative",
+            ),
+            _ => Ok(()),
+        };
+        Ok(validators::append_validation_error(
+            Ok(()),
+            "refund_request_auto_approve_days",
+            days_valid,
+        )?)
+    }
+
+    pub fn update_stripe_connect_account(
+        &self,
+        stripe_connect_account_id: &str,
+        stripe_connect_enabled: bool,
+        conn: &PgConnection,
+    ) -> Result<Organization, DatabaseError> {
+        diesel::update(self)
+            .set((
+                organizations::stripe_connect_account_id.eq(stripe_connect_account_id),
+                organizations::stripe_connect_enabled.eq(stripe_connect_enabled),
+                organizations::updated_at.eq(dsl::now),
+            ))
+            .get_result(conn)
+            .to_db_error(
+                ErrorCode::UpdateError,
+                "Could not update Stripe Connect account for organization",
+            )
+    }
+
+    /// Connected account that charges should be paid out to, if the organization has finished
+    /// Stripe Connect onboarding
+    pub fn stripe_connect_destination(&self) -> Option<&str> {
+        if self.stripe_connect_enabled {
+            self.stripe_connect_account_id.as_ref().map(|a| a.as_str())
+        } else {
+            None
+        }
     }
 
     pub fn find_by_asset_id(
@@ -525,7 +629,30 @@ impl Organization {
         Ok(result_list)
     }
 
-    pub fn remove_user(&self, user_id: Uuid, conn: &PgConnection) -> Result<usize, DatabaseError> {
+    pub fn remove_user(
+        &self,
+        current_user_id: Option<Uuid>,
+        user_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<usize, DatabaseError> {
+        let organization_user =
+            OrganizationUser::find_by_user_id(user_id, self.id, conn).optional()?;
+        if let Some(organization_user) = organization_user {
+            DomainEvent::create(
+                DomainEventTypes::OrganizationUserRemoved,
+                "User removed from organization".to_string(),
+                Tables::Organizations,
+                Some(self.id),
+                current_user_id,
+                Some(json!({
+                    "user_id": user_id,
+                    "role": organization_user.role,
+                    "event_ids": organization_user.event_ids
+                })),
+            )
+            .commit(conn)?;
+        }
+
         diesel::delete(
             organization_users::table
                 .filter(organization_users::user_id.eq(user_id))
@@ -537,12 +664,51 @@ impl Organization {
 
     pub fn add_user(
         &self,
+        current_user_id: Option<Uuid>,
         user_id: Uuid,
         role: Vec<Roles>,
         event_ids: Vec<Uuid>,
         conn: &PgConnection,
     ) -> Result<OrganizationUser, DatabaseError> {
+        let existing_user = OrganizationUser::find_by_user_id(user_id, self.id, conn).optional()?;
         let org_user = OrganizationUser::create(self.id, user_id, role, event_ids).commit(conn)?;
+
+        match existing_user {
+            Some(existing_user) => {
+                if existing_user.role != org_user.role
+                    || existing_user.event_ids != org_user.event_ids
+                {
+                    DomainEvent::create(
+                        DomainEventTypes::OrganizationUserRolesChanged,
+                        "Organization user roles changed".to_string(),
+                        Tables::Organizations,
+                        Some(self.id),
+                        current_user_id,
+                        Some(json!({
+                            "user_id": user_id,
+                            "changes": audit::changes(&existing_user, &org_user, &[])
+                        })),
+                    )
+                    .commit(conn)?;
+                }
+            }
+            None => {
+                DomainEvent::create(
+                    DomainEventTypes::OrganizationUserAdded,
+                    "User added to organization".to_string(),
+                    Tables::Organizations,
+                    Some(self.id),
+                    current_user_id,
+                    Some(json!({
+                        "user_id": user_id,
+                        "role": org_user.role,
+                        "event_ids": org_user.event_ids
+                    })),
+                )
+                .commit(conn)?;
+            }
+        }
+
         Ok(org_user)
     }
 
@@ -563,6 +729,7 @@ impl Organization {
 
     pub fn add_fee_schedule(
         &self,
+        current_user_id: Option<Uuid>,
         fee_schedule: &FeeSchedule,
         conn: &PgConnection,
     ) -> Result<Organization, DatabaseError> {
@@ -577,7 +744,9 @@ impl Organization {
                 "Could not set the fee schedule for this organization",
             )?;
 
-        diesel::update(self)
+        assign_fee_schedule_domain_events(fee_schedule.id, self.id, conn)?;
+
+        let organization: Organization = diesel::update(self)
             .set((
                 organizations::fee_schedule_id.eq(fee_schedule.id),
                 organizations::updated_at.eq(dsl::now),
@@ -586,7 +755,21 @@ impl Organization {
             .to_db_error(
                 ErrorCode::UpdateError,
                 "Could not set the fee schedule for this organization",
-            )
+            )?;
+
+        DomainEvent::create(
+            DomainEventTypes::OrganizationUpdated,
+            "Organization fee schedule changed".to_string(),
+            Tables::Organizations,
+            Some(self.id),
+            current_user_id,
+            Some(json!({
+                "changes": audit::changes(self, &organization, &Organization::REDACTED_FIELDS)
+            })),
+        )
+        .commit(conn)?;
+
+        Ok(organization)
     }
 
     pub fn search_fans(
@@ -728,8 +911,32 @@ impl Organization {
             if let Some(key) = self.globee_api_key.clone() {
                 self.globee_api_key = Some(decrypt(&key, &encryption_key)?);
             }
+            if let Some(key) = self.mailchimp_api_key.clone() {
+                self.mailchimp_api_key = Some(decrypt(&key, &encryption_key)?);
+            }
         }
 
         Ok(())
     }
 }
+
+/// Fee schedules are usually created before the organization they belong to, so their events could
+/// not be linked to it when they were recorded
+fn assign_fee_schedule_domain_events(
+    fee_schedule_id: Uuid,
+    organization_id: Uuid,
+    conn: &PgConnection,
+) -> Result<usize, DatabaseError> {
+    diesel::update(
+        domain_events::table
+            .filter(domain_events::main_table.eq(Tables::FeeSchedules))
+            .filter(domain_events::main_id.eq(fee_schedule_id))
+            .filter(domain_events::organization_id.is_null()),
+    )
+    .set(domain_events::organization_id.eq(organization_id))
+    .execute(conn)
+    .to_db_error(
+        ErrorCode::UpdateError,
+        "Could not set the organization for fee schedule events",
+    )
+}
@@ -6,9 +6,13 @@ use diesel::sql_types::Text;
 use models::{DomainEvent, DomainEventTypes, ForDisplay, PaymentProviders, Tables};
 use schema::*;
 use serde_json;
+use time::Duration;
 use utils::errors::*;
 use uuid::Uuid;
 
+/// Cards expiring within this many days are flagged so the fan can replace them
+pub const CARD_EXPIRY_WARNING_DAYS: i64 = 30;
+
 #[derive(Clone, Debug, Identifiable, PartialEq, Queryable)]
 pub struct PaymentMethod {
     pub id: Uuid,
@@ -46,6 +50,13 @@ impl PaymentMethod {
         }
     }
 
+    pub fn find(id: Uuid, conn: &PgConnection) -> Result<PaymentMethod, DatabaseError> {
+        payment_methods::table
+            .find(id)
+            .first(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load payment method")
+    }
+
     pub fn find_default_for_user(
         user_id: Uuid,
         conn: &PgConnection,
@@ -75,6 +86,8 @@ impl PaymentMethod {
 
         query
             .order_by(payment_methods::name)
+            .then_order_by(payment_methods::is_default.desc())
+            .then_order_by(payment_methods::created_at)
             .load(conn)
             .to_db_error(
                 ErrorCode::QueryError,
@@ -107,6 +120,137 @@ impl PaymentMethod {
             query.get_result(conn),
         )
     }
+
+    pub fn set_default(
+        &self,
+        current_user_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<PaymentMethod, DatabaseError> {
+        PaymentMethod::clear_default_for_user(self.user_id, conn)?;
+
+        DomainEvent::create(
+            DomainEventTypes::PaymentMethodUpdated,
+            "Payment method was set as default".to_string(),
+            Tables::PaymentMethods,
+            Some(self.id),
+            Some(current_user_id),
+            None,
+        )
+        .commit(conn)?;
+
+        diesel::update(self)
+            .set((
+                payment_methods::is_default.eq(true),
+                payment_methods::updated_at.eq(dsl::now),
+            ))
+            .get_result(conn)
+            .to_db_error(
+                ErrorCode::UpdateError,
+                "Could not set default payment method",
+            )
+    }
+
+    /// Removes the payment method. If it was the default, the most recently added of the
+    /// user's remaining payment methods becomes the new default.
+    pub fn destroy(&self, current_user_id: Uuid, conn: &PgConnection) -> Result<(), DatabaseError> {
+        DomainEvent::create(
+            DomainEventTypes::PaymentMethodDeleted,
+            "Payment method was deleted".to_string(),
+            Tables::PaymentMethods,
+            Some(self.id),
+            Some(current_user_id),
+            None,
+        )
+        .commit(conn)?;
+
+        diesel::delete(self)
+            .execute(conn)
+            .to_db_error(ErrorCode::DeleteError, "Could not delete payment method")?;
+
+        if self.is_default {
+            let replacement: Option<PaymentMethod> = payment_methods::table
+                .filter(payment_methods::user_id.eq(self.user_id))
+                .order_by(payment_methods::created_at.desc())
+                .first(conn)
+                .optional()
+                .to_db_error(
+                    ErrorCode::QueryError,
+                    "Could not load payment methods for user",
+                )?;
+            if let Some(replacement) = replacement {
+                replacement.set_default(current_user_id, conn)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Card brand, last 4 digits and expiry of the card stored with the provider, if the
+    /// provider data describes one.
+    pub fn card_details(&self) -> Option<CardDetails> {
+        let sources = self.provider_data.get("sources")?.get("data")?.as_array()?;
+        let default_source = self
+            .provider_data
+            .get("default_source")
+            .and_then(|s| s.as_str());
+        let card = sources
+            .iter()
+            .find(|s| s.get("id").and_then(|id| id.as_str()) == default_source)
+            .or_else(|| sources.first())?;
+
+        Some(CardDetails {
+            brand: card.get("brand")?.as_str()?.to_string(),
+            last4: card.get("last4")?.as_str()?.to_string(),
+            exp_month: card.get("exp_month")?.as_u64()? as u32,
+            exp_year: card.get("exp_year")?.as_i64()? as i32,
+        })
+    }
+
+    fn clear_default_for_user(user_id: Uuid, conn: &PgConnection) -> Result<(), DatabaseError> {
+        diesel::update(
+            payment_methods::table
+                .filter(payment_methods::user_id.eq(user_id))
+                .filter(payment_methods::is_default.eq(true)),
+        )
+        .set((
+            payment_methods::is_default.eq(false),
+            payment_methods::updated_at.eq(dsl::now),
+        ))
+        .execute(conn)
+        .to_db_error(
+            ErrorCode::UpdateError,
+            "Could not clear default payment method",
+        )?;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CardDetails {
+    pub brand: String,
+    pub last4: String,
+    pub exp_month: u32,
+    pub exp_year: i32,
+}
+
+impl CardDetails {
+    /// Cards are valid until the end of their expiry month
+    pub fn expires_at(&self) -> NaiveDateTime {
+        let (year, month) = if self.exp_month >= 12 {
+            (self.exp_year + 1, 1)
+        } else {
+            (self.exp_year, self.exp_month + 1)
+        };
+        NaiveDate::from_ymd(year, month, 1).and_hms(0, 0, 0)
+    }
+
+    pub fn is_expired(&self, now: NaiveDateTime) -> bool {
+        self.expires_at() <= now
+    }
+
+    pub fn is_expiring_soon(&self, now: NaiveDateTime) -> bool {
+        !self.is_expired(now) && self.expires_at() <= now + Duration::days(CARD_EXPIRY_WARNING_DAYS)
+    }
 }
 
 impl ForDisplay<DisplayPaymentMethod> for PaymentMethod {
@@ -131,6 +275,10 @@ impl NewPaymentMethod {
         current_user_id: Uuid,
         conn: &PgConnection,
     ) -> Result<PaymentMethod, DatabaseError> {
+        if self.is_default {
+            PaymentMethod::clear_default_for_user(self.user_id, conn)?;
+        }
+
         let payment_method = diesel::insert_into(payment_methods::table)
             .values(self)
             .get_result::<PaymentMethod>(conn)
@@ -152,15 +300,28 @@ impl NewPaymentMethod {
 
 #[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
 pub struct DisplayPaymentMethod {
+    pub id: Uuid,
     pub name: PaymentProviders,
     pub is_default: bool,
+    pub card: Option<CardDetails>,
+    pub expired: bool,
+    pub expiring_soon: bool,
 }
 
 impl From<PaymentMethod> for DisplayPaymentMethod {
     fn from(payment_method: PaymentMethod) -> Self {
+        let now = Utc::now().naive_utc();
+        let card = payment_method.card_details();
         DisplayPaymentMethod {
+            id: payment_method.id,
             name: payment_method.name,
             is_default: payment_method.is_default,
+            expired: card.as_ref().map(|c| c.is_expired(now)).unwrap_or(false),
+            expiring_soon: card
+                .as_ref()
+                .map(|c| c.is_expiring_soon(now))
+                .unwrap_or(false),
+            card,
         }
     }
 }
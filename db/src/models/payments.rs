@@ -11,6 +11,9 @@ use time::Duration;
 use utils::errors::*;
 use uuid::Uuid;
 
+/// Time allowed for a customer to complete payment authentication (e.g. 3-D Secure)
+pub const PAYMENT_ACTION_EXPIRY_MINUTES: i64 = 30;
+
 #[allow(dead_code)]
 #[derive(Debug, Identifiable, PartialEq, Queryable)]
 pub struct Payment {
@@ -248,6 +251,66 @@ impl Payment {
         Ok(())
     }
 
+    /// Holds the order in `PendingPayment` while the customer completes an action required
+    /// by the payment provider, extending its expiry so the tickets remain reserved
+    pub fn mark_requires_action(
+        &self,
+        raw_data: serde_json::Value,
+        current_user_id: Option<Uuid>,
+        conn: &PgConnection,
+    ) -> Result<(), DatabaseError> {
+        if self.status != PaymentStatus::Requested && self.status != PaymentStatus::RequiresAction {
+            return DatabaseError::business_process_error(
+                "Could not mark payment as requiring action because it is not in the correct status",
+            );
+        }
+        self.update_status(PaymentStatus::RequiresAction, current_user_id, conn)?;
+        DomainEvent::create(
+            DomainEventTypes::PaymentUpdated,
+            "Payment requires customer action".to_string(),
+            Tables::Payments,
+            Some(self.id),
+            current_user_id,
+            Some(raw_data),
+        )
+        .commit(conn)?;
+
+        let mut order = self.order(conn)?;
+        if order.status != OrderStatus::PendingPayment {
+            order.update_status(current_user_id, OrderStatus::PendingPayment, conn)?;
+        }
+        order.set_expiry(
+            current_user_id,
+            Some(Utc::now().naive_utc() + Duration::minutes(PAYMENT_ACTION_EXPIRY_MINUTES)),
+            conn,
+        )?;
+        Ok(())
+    }
+
+    pub fn mark_authorized(
+        &self,
+        raw_data: serde_json::Value,
+        current_user_id: Option<Uuid>,
+        conn: &PgConnection,
+    ) -> Result<(), DatabaseError> {
+        if self.status != PaymentStatus::RequiresAction {
+            return DatabaseError::business_process_error(
+                "Could not mark payment as authorized because it is not awaiting customer action",
+            );
+        }
+        self.update_status(PaymentStatus::Authorized, current_user_id, conn)?;
+        DomainEvent::create(
+            DomainEventTypes::PaymentUpdated,
+            "Payment was authorized".to_string(),
+            Tables::Payments,
+            Some(self.id),
+            current_user_id,
+            Some(raw_data),
+        )
+        .commit(conn)?;
+        Ok(())
+    }
+
     pub fn mark_cancelled(
         &self,
         raw_data: serde_json::Value,
@@ -259,7 +322,7 @@ impl Payment {
             Completed | Authorized | Refunded | PendingConfirmation => {
                 DatabaseError::business_process_error("Could not mark payment as cancelled because it is in a status that doesn't allow cancelling")
             }
-            Requested | Unpaid | Draft | Unknown | PendingIpn => {
+            Requested | Unpaid | Draft | Unknown | PendingIpn | RequiresAction => {
 
                 DomainEvent::create(
                     DomainEventTypes::PaymentCancelled,
@@ -0,0 +1,350 @@
+use chrono::prelude::*;
+use chrono::Duration;
+use diesel;
+use diesel::dsl;
+use diesel::prelude::*;
+use models::*;
+use schema::refund_requests;
+use utils::errors::*;
+use uuid::Uuid;
+
+#[derive(Clone, Debug, Deserialize, Identifiable, PartialEq, Queryable, Serialize)]
+pub struct RefundRequest {
+    pub id: Uuid,
+    pub order_id: Uuid,
+    pub organization_id: Uuid,
+    pub user_id: Uuid,
+    pub ticket_instance_ids: Vec<Uuid>,
+    pub reason: String,
+    pub status: RefundRequestStatus,
+    pub reviewed_by_user_id: Option<Uuid>,
+    pub reviewed_at: Option<NaiveDateTime>,
+    pub review_notes: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl RefundRequest {
+    pub fn create(
+        order_id: Uuid,
+        user_id: Uuid,
+        ticket_instance_ids: Vec<Uuid>,
+        reason: String,
+    ) -> NewRefundRequest {
+        NewRefundRequest {
+            order_id,
+            user_id,
+            ticket_instance_ids,
+            reason,
+        }
+    }
+
+    pub fn find(id: Uuid, conn: &PgConnection) -> Result<RefundRequest, DatabaseError> {
+        refund_requests::table
+            .find(id)
+            .first(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load refund request")
+    }
+
+    /// Refund requests made for the order, most recent first
+    pub fn find_for_order(
+        order_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<RefundRequest>, DatabaseError> {
+        refund_requests::table
+            .filter(refund_requests::order_id.eq(order_id))
+            .order_by(refund_requests::created_at.desc())
+            .load(conn)
+            .to_db_error(
+                ErrorCode::QueryError,
+                "Could not load refund requests for order",
+            )
+    }
+
+    /// The organization's refund requests, oldest first so that the queue is worked in order
+    pub fn find_for_organization(
+        organization_id: Uuid,
+        status: Option<RefundRequestStatus>,
+        page: u32,
+        limit: u32,
+        conn: &PgConnection,
+    ) -> Result<Payload<RefundRequest>, DatabaseError> {
+        let mut query = refund_requests::table
+            .filter(refund_requests::organization_id.eq(organization_id))
+            .into_boxed();
+        let mut count_query = refund_requests::table
+            .filter(refund_requests::organization_id.eq(organization_id))
+            .into_boxed();
+        if let Some(status) = status {
+            query = query.filter(refund_requests::status.eq(status));
+            count_query = count_query.filter(refund_requests::status.eq(status));
+        }
+
+        let total: i64 = count_query
+            .count()
+            .first(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not count refund requests")?;
+
+        let mut payload = Payload::new(
+            query
+                .order_by(refund_requests::created_at.asc())
+                .limit(limit as i64)
+                .offset((page * limit) as i64)
+                .load(conn)
+                .to_db_error(ErrorCode::QueryError, "Could not load refund requests")?,
+            Paging::new(page, limit),
+        );
+        payload.paging.total = total as u64;
+        Ok(payload)
+    }
+
+    pub fn order(&self, conn: &PgConnection) -> Result<Order, DatabaseError> {
+        Order::find(self.order_id, conn)
+    }
+
+    pub fn organization(&self, conn: &PgConnection) -> Result<Organization, DatabaseError> {
+        Organization::find(self.organization_id, conn)
+    }
+
+    pub fn ticket_instances(
+        &self,
+        conn: &PgConnection,
+    ) -> Result<Vec<TicketInstance>, DatabaseError> {
+        self.ticket_instance_ids
+            .iter()
+            .map(|id| TicketInstance::find(*id, conn))
+            .collect()
+    }
+
+    /// The items to pass to `Order::refund` when the request is approved
+    pub fn refund_items(&self, conn: &PgConnection) -> Result<Vec<RefundItem>, DatabaseError> {
+        let mut result = Vec::new();
+        for ticket_instance in self.ticket_instances(conn)? {
+            match ticket_instance.order_item_id {
+                Some(order_item_id) => result.push(RefundItem {
+                    order_item_id,
+                    ticket_instance_id: Some(ticket_instance.id),
+                }),
+                None => {
+                    return DatabaseError::business_process_error(
+                        "Ticket must have an associated order item id to be refunded",
+                    );
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// Requests are approved automatically when every ticket type is refundable, or when the
+    /// organization auto approves requests made at least `refund_request_auto_approve_days`
+    /// before the event starts
+    pub fn qualifies_for_auto_approval(&self, conn: &PgConnection) -> Result<bool, DatabaseError> {
+        let auto_approve_days = self.organization(conn)?.refund_request_auto_approve_days;
+        let cutoff =
+            auto_approve_days.map(|days| Utc::now().naive_utc() + Duration::days(days as i64));
+
+        let mut all_refundable = true;
+        let mut all_before_cutoff = cutoff.is_some();
+        for ticket_instance in self.ticket_instances(conn)? {
+            let ticket_type = ticket_instance.ticket_type(conn)?;
+            all_refundable = all_refundable && ticket_type.refundable;
+            if let Some(cutoff) = cutoff {
+                let event = Event::find(ticket_type.event_id, conn)?;
+                all_before_cutoff = all_before_cutoff
+                    && event
+                        .event_start
+                        .map(|start| start > cutoff)
+                        .unwrap_or(false);
+            }
+        }
+
+        Ok(all_refundable || all_before_cutoff)
+    }
+
+    /// Marks the request as approved. The refund itself is processed by the caller.
+    /// `reviewed_by_user_id` is `None` when the request was approved automatically.
+    pub fn approve(
+        &self,
+        reviewed_by_user_id: Option<Uuid>,
+        conn: &PgConnection,
+    ) -> Result<RefundRequest, DatabaseError> {
+        let result = self.review(
+            RefundRequestStatus::Approved,
+            reviewed_by_user_id,
+            None,
+            conn,
+        )?;
+
+        DomainEvent::create(
+            DomainEventTypes::RefundRequestApproved,
+            "Refund request approved".to_string(),
+            Tables::RefundRequests,
+            Some(self.id),
+            reviewed_by_user_id,
+            Some(json!({ "automatic": reviewed_by_user_id.is_none() })),
+        )
+        .commit(conn)?;
+
+        Ok(result)
+    }
+
+    pub fn deny(
+        &self,
+        reviewed_by_user_id: Uuid,
+        review_notes: Option<String>,
+        conn: &PgConnection,
+    ) -> Result<RefundRequest, DatabaseError> {
+        let result = self.review(
+            RefundRequestStatus::Denied,
+            Some(reviewed_by_user_id),
+            review_notes.clone(),
+            conn,
+        )?;
+
+        DomainEvent::create(
+            DomainEventTypes::RefundRequestDenied,
+            "Refund request denied".to_string(),
+            Tables::RefundRequests,
+            Some(self.id),
+            Some(reviewed_by_user_id),
+            Some(json!({ "review_notes": review_notes })),
+        )
+        .commit(conn)?;
+
+        Ok(result)
+    }
+
+    fn review(
+        &self,
+        status: RefundRequestStatus,
+        reviewed_by_user_id: Option<Uuid>,
+        review_notes: Option<String>,
+        conn: &PgConnection,
+    ) -> Result<RefundRequest, DatabaseError> {
+        if self.status != RefundRequestStatus::Pending {
+            return DatabaseError::business_process_error(
+                "Refund request has already been reviewed",
+            );
+        }
+
+        diesel::update(self)
+            .set((
+                refund_requests::status.eq(status),
+                refund_requests::reviewed_by_user_id.eq(reviewed_by_user_id),
+                refund_requests::reviewed_at.eq(dsl::now.nullable()),
+                refund_requests::review_notes.eq(review_notes),
+                refund_requests::updated_at.eq(dsl::now),
+            ))
+            .get_result(conn)
+            .to_db_error(ErrorCode::UpdateError, "Could not update refund request")
+    }
+}
+
+#[derive(Insertable, Serialize)]
+#[table_name = "refund_requests"]
+pub struct NewRefundRequest {
+    pub order_id: Uuid,
+    pub user_id: Uuid,
+    pub ticket_instance_ids: Vec<Uuid>,
+    pub reason: String,
+}
+
+impl NewRefundRequest {
+    pub fn commit(self, conn: &PgConnection) -> Result<RefundRequest, DatabaseError> {
+        if self.reason.trim().is_empty() {
+            return DatabaseError::validation_error("reason", "Reason is required");
+        }
+        if self.ticket_instance_ids.is_empty() {
+            return DatabaseError::validation_error(
+                "ticket_instance_ids",
+                "At least one ticket is required",
+            );
+        }
+
+        let order = Order::find(self.order_id, conn)?;
+        if order.status != OrderStatus::Paid {
+            return DatabaseError::business_process_error(
+                "Refunds can only be requested for paid orders",
+            );
+        }
+
+        let mut organization_ids = Vec::new();
+        for ticket_instance_id in &self.ticket_instance_ids {
+            let ticket_instance = TicketInstance::find(*ticket_instance_id, conn)?;
+            let order_item = match ticket_instance.order_item_id {
+                Some(order_item_id) => OrderItem::find(order_item_id, conn)?,
+                None => {
+                    return DatabaseError::business_process_error(
+                        "Ticket does not belong to this order",
+                    );
+                }
+            };
+            if order_item.order_id != order.id {
+                return DatabaseError::business_process_error(
+                    "Ticket does not belong to this order",
+                );
+            }
+            if ticket_instance.status != TicketInstanceStatus::Purchased {
+                return DatabaseError::business_process_error(
+                    "Only tickets that have not been redeemed can be refunded",
+                );
+            }
+            if ticket_instance.was_transferred(conn)? {
+                return DatabaseError::business_process_error(
+                    "Ticket was transferred so ineligible for refund",
+                );
+            }
+
+            let event = Event::find(ticket_instance.ticket_type(conn)?.event_id, conn)?;
+            if !organization_ids.contains(&event.organization_id) {
+                organization_ids.push(event.organization_id);
+            }
+        }
+        if organization_ids.len() != 1 {
+            return DatabaseError::business_process_error(
+                "Tickets from different organizations must be requested separately",
+            );
+        }
+
+        let refunded_tickets =
+            RefundedTicket::find_by_ticket_instance_ids(self.ticket_instance_ids.clone(), conn)?;
+        if refunded_tickets
+            .iter()
+            .any(|r| r.ticket_refunded_at.is_some())
+        {
+            return DatabaseError::business_process_error("Already refunded");
+        }
+        for refund_request in RefundRequest::find_for_order(order.id, conn)? {
+            if refund_request.status == RefundRequestStatus::Pending
+                && refund_request
+                    .ticket_instance_ids
+                    .iter()
+                    .any(|id| self.ticket_instance_ids.contains(id))
+            {
+                return DatabaseError::business_process_error(
+                    "A refund has already been requested for this ticket",
+                );
+            }
+        }
+
+        let result: RefundRequest = diesel::insert_into(refund_requests::table)
+            .values((
+                &self,
+                refund_requests::organization_id.eq(organization_ids[0]),
+            ))
+            .get_result(conn)
+            .to_db_error(ErrorCode::InsertError, "Could not create refund request")?;
+
+        DomainEvent::create(
+            DomainEventTypes::RefundRequestCreated,
+            "Refund requested".to_string(),
+            Tables::RefundRequests,
+            Some(result.id),
+            Some(self.user_id),
+            Some(json!(self)),
+        )
+        .commit(conn)?;
+
+        Ok(result)
+    }
+}
@@ -1,7 +1,7 @@
 use chrono::prelude::*;
 use diesel;
 use diesel::prelude::*;
-use diesel::sql_types::{BigInt, Nullable, Text, Timestamp, Uuid as dUuid};
+use diesel::sql_types::{Array, BigInt, Double, Nullable, Text, Timestamp, Uuid as dUuid};
 use itertools::Itertools;
 use models::*;
 use std::collections::HashMap;
@@ -430,6 +430,68 @@ pub struct ReconciliationDetailEventResult {
     pub entries: Vec<ReconciliationDetailResult>,
 }
 
+/// Sales performance of a code or hold, refunded tickets are excluded
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Queryable, QueryableByName)]
+pub struct CodeAnalytics {
+    #[sql_type = "BigInt"]
+    pub tickets_sold: i64,
+    /// Amount paid by customers including fees
+    #[sql_type = "BigInt"]
+    pub gross_revenue_in_cents: i64,
+    #[sql_type = "BigInt"]
+    pub discount_given_in_cents: i64,
+    /// Ticket revenue after discounts, excluding fees
+    #[sql_type = "BigInt"]
+    pub net_revenue_in_cents: i64,
+    #[sql_type = "BigInt"]
+    pub unique_buyers: i64,
+    /// Number of carts the code was entered into
+    #[sql_type = "BigInt"]
+    pub applications: i64,
+    #[sql_type = "BigInt"]
+    pub purchases: i64,
+    /// Fraction of applications that ended in a purchase, empty when never applied
+    #[sql_type = "Nullable<Double>"]
+    pub conversion_rate: Option<f64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Queryable, QueryableByName)]
+pub struct CodeAnalyticsRow {
+    #[sql_type = "Nullable<dUuid>"]
+    pub code_id: Option<Uuid>,
+    #[sql_type = "Nullable<dUuid>"]
+    pub hold_id: Option<Uuid>,
+    #[sql_type = "dUuid"]
+    pub event_id: Uuid,
+    #[sql_type = "Text"]
+    pub name: String,
+    #[sql_type = "Nullable<Text>"]
+    pub redemption_code: Option<String>,
+    #[diesel(embed)]
+    pub analytics: CodeAnalytics,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Queryable, QueryableByName)]
+pub struct CodeUsageRow {
+    #[sql_type = "Nullable<dUuid>"]
+    pub code_id: Option<Uuid>,
+    #[sql_type = "Nullable<dUuid>"]
+    pub hold_id: Option<Uuid>,
+    #[sql_type = "Timestamp"]
+    pub date: NaiveDateTime,
+    #[sql_type = "BigInt"]
+    pub tickets_sold: i64,
+    #[sql_type = "BigInt"]
+    pub purchases: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct CodeAnalyticsReport {
+    pub codes: Vec<CodeAnalyticsRow>,
+    /// Daily sales for each code and hold
+    pub usage: Vec<CodeUsageRow>,
+}
+
 pub fn group_by_string(
     group_by_ticket_type: bool,
     group_by_ticket_pricing: bool,
@@ -544,6 +606,106 @@ impl TicketCountRow {
     }
 }
 
+impl CodeAnalyticsRow {
+    pub fn fetch(
+        event_id: Option<Uuid>,
+        organization_id: Option<Uuid>,
+        start: Option<NaiveDateTime>,
+        end: Option<NaiveDateTime>,
+        conn: &PgConnection,
+    ) -> Result<Vec<CodeAnalyticsRow>, DatabaseError> {
+        CodeAnalyticsRow::fetch_for_ids(event_id, organization_id, start, end, None, None, conn)
+    }
+
+    /// Restricts the rows to the given codes and holds when either list is provided
+    fn fetch_for_ids(
+        event_id: Option<Uuid>,
+        organization_id: Option<Uuid>,
+        start: Option<NaiveDateTime>,
+        end: Option<NaiveDateTime>,
+        code_ids: Option<Vec<Uuid>>,
+        hold_ids: Option<Vec<Uuid>>,
+        conn: &PgConnection,
+    ) -> Result<Vec<CodeAnalyticsRow>, DatabaseError> {
+        diesel::sql_query(
+            "SELECT * FROM code_analytics($1, $2, $3, $4, $5, $6) ORDER BY name, code_id, hold_id;",
+        )
+        .bind::<Nullable<dUuid>, _>(event_id)
+        .bind::<Nullable<dUuid>, _>(organization_id)
+        .bind::<Nullable<Timestamp>, _>(start)
+        .bind::<Nullable<Timestamp>, _>(end)
+        .bind::<Nullable<Array<dUuid>>, _>(code_ids)
+        .bind::<Nullable<Array<dUuid>>, _>(hold_ids)
+        .get_results(conn)
+        .to_db_error(ErrorCode::QueryError, "Could not fetch code analytics")
+    }
+}
+
+impl CodeAnalytics {
+    pub fn find_for_code(code: &Code, conn: &PgConnection) -> Result<CodeAnalytics, DatabaseError> {
+        Ok(CodeAnalytics::find_for_codes(&[code.id], conn)?
+            .remove(&code.id)
+            .unwrap_or_default())
+    }
+
+    pub fn find_for_hold(hold: &Hold, conn: &PgConnection) -> Result<CodeAnalytics, DatabaseError> {
+        Ok(CodeAnalytics::find_for_holds(&[hold.id], conn)?
+            .remove(&hold.id)
+            .unwrap_or_default())
+    }
+
+    /// Analytics for the given codes keyed by code id
+    pub fn find_for_codes(
+        code_ids: &[Uuid],
+        conn: &PgConnection,
+    ) -> Result<HashMap<Uuid, CodeAnalytics>, DatabaseError> {
+        Ok(CodeAnalyticsRow::fetch_for_ids(
+            None,
+            None,
+            None,
+            None,
+            Some(code_ids.to_vec()),
+            Some(Vec::new()),
+            conn,
+        )?
+        .into_iter()
+        .filter_map(|row| row.code_id.map(|code_id| (code_id, row.analytics)))
+        .collect())
+    }
+
+    /// Analytics for the given holds keyed by hold id
+    pub fn find_for_holds(
+        hold_ids: &[Uuid],
+        conn: &PgConnection,
+    ) -> Result<HashMap<Uuid, CodeAnalytics>, DatabaseError> {
+        Ok(CodeAnalyticsRow::fetch_for_ids(
+            None,
+            None,
+            None,
+            None,
+            Some(Vec::new()),
+            Some(hold_ids.to_vec()),
+            conn,
+        )?
+        .into_iter()
+        .filter_map(|row| row.hold_id.map(|hold_id| (hold_id, row.analytics)))
+        .collect())
+    }
+
+    /// Analytics for all of the event's holds keyed by hold id
+    pub fn find_for_event_holds(
+        event_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<HashMap<Uuid, CodeAnalytics>, DatabaseError> {
+        Ok(
+            CodeAnalyticsRow::fetch(Some(event_id), None, None, None, conn)?
+                .into_iter()
+                .filter_map(|row| row.hold_id.map(|hold_id| (hold_id, row.analytics)))
+                .collect(),
+        )
+    }
+}
+
 impl Report {
     fn external_payment_type_row_title(external_payment_type: ExternalPaymentType) -> String {
         match external_payment_type {
@@ -715,7 +877,7 @@ impl Report {
         conn: &PgConnection,
     ) -> Result<EventSummarySalesResult, DatabaseError> {
         let mut results =
-            Report::summary_event_report_core(Some(event_id), None, start, end, conn)?;
+            Report::summary_event_report_core(Some(event_id), None, None, start, end, conn)?;
 
         let result = match results.is_empty() {
             true => {
@@ -737,12 +899,23 @@ impl Report {
         end: Option<NaiveDateTime>,
         conn: &PgConnection,
     ) -> Result<Vec<EventSummarySalesResult>, DatabaseError> {
-        Report::summary_event_report_core(None, Some(organization_id), start, end, conn)
+        Report::summary_event_report_core(None, Some(organization_id), None, start, end, conn)
+    }
+
+    /// Summary of sales for every occurrence of an event series
+    pub fn event_series_summary_report(
+        event_series_id: Uuid,
+        start: Option<NaiveDateTime>,
+        end: Option<NaiveDateTime>,
+        conn: &PgConnection,
+    ) -> Result<Vec<EventSummarySalesResult>, DatabaseError> {
+        Report::summary_event_report_core(None, None, Some(event_series_id), start, end, conn)
     }
 
     fn summary_event_report_core(
         event_id: Option<Uuid>,
         organization_id: Option<Uuid>,
+        event_series_id: Option<Uuid>,
         start: Option<NaiveDateTime>,
         end: Option<NaiveDateTime>,
         conn: &PgConnection,
@@ -753,7 +926,8 @@ impl Report {
             .bind::<Nullable<dUuid>, _>(event_id)
             .bind::<Nullable<dUuid>, _>(organization_id)
             .bind::<Nullable<Timestamp>, _>(start)
-            .bind::<Nullable<Timestamp>, _>(end);
+            .bind::<Nullable<Timestamp>, _>(end)
+            .bind::<Nullable<dUuid>, _>(event_series_id);
 
         let sales_rows: Vec<EventSummarySalesRow> = q.get_results(conn).to_db_error(
             ErrorCode::QueryError,
@@ -774,7 +948,8 @@ impl Report {
             .bind::<Nullable<dUuid>, _>(event_id)
             .bind::<Nullable<dUuid>, _>(organization_id)
             .bind::<Nullable<Timestamp>, _>(start)
-            .bind::<Nullable<Timestamp>, _>(end);
+            .bind::<Nullable<Timestamp>, _>(end)
+            .bind::<Nullable<dUuid>, _>(event_series_id);
 
         let fees_rows: Vec<EventSummaryFeesRow> = q
             .get_results(conn)
@@ -795,7 +970,8 @@ impl Report {
             .bind::<Nullable<dUuid>, _>(event_id)
             .bind::<Nullable<dUuid>, _>(organization_id)
             .bind::<Nullable<Timestamp>, _>(start)
-            .bind::<Nullable<Timestamp>, _>(end);
+            .bind::<Nullable<Timestamp>, _>(end)
+            .bind::<Nullable<dUuid>, _>(event_series_id);
 
         let other_fees_rows: Vec<EventSummaryOtherFees> = q
             .get_results(conn)
@@ -1141,4 +1317,25 @@ impl Report {
 
         Ok(results)
     }
+
+    pub fn code_analytics_report(
+        event_id: Option<Uuid>,
+        organization_id: Option<Uuid>,
+        start: Option<NaiveDateTime>,
+        end: Option<NaiveDateTime>,
+        conn: &PgConnection,
+    ) -> Result<CodeAnalyticsReport, DatabaseError> {
+        let codes = CodeAnalyticsRow::fetch(event_id, organization_id, start, end, conn)?;
+
+        let query = include_str!("../queries/reports/reports_code_usage.sql");
+        let usage: Vec<CodeUsageRow> = diesel::sql_query(query)
+            .bind::<Nullable<dUuid>, _>(event_id)
+            .bind::<Nullable<dUuid>, _>(organization_id)
+            .bind::<Nullable<Timestamp>, _>(start)
+            .bind::<Nullable<Timestamp>, _>(end)
+            .get_results(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not fetch code usage")?;
+
+        Ok(CodeAnalyticsReport { codes, usage })
+    }
 }
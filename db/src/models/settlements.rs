@@ -94,6 +94,16 @@ impl NewSettlementRequest {
                 .get_result::<Settlement>(conn),
         )?;
 
+        DomainEvent::create(
+            DomainEventTypes::SettlementCreated,
+            "Settlement created".to_string(),
+            Tables::Settlements,
+            Some(settlement.id),
+            Some(user_id),
+            Some(json!(settlement)),
+        )
+        .commit(conn)?;
+
         let new_settlement_transactions = Settlement::create_base_transactions(
             Some(settlement.id),
             organization_id.clone(),
@@ -218,7 +228,21 @@ impl Settlement {
             events,
         })
     }
-    pub fn destroy(self, conn: &PgConnection) -> Result<usize, DatabaseError> {
+    pub fn destroy(
+        self,
+        current_user_id: Option<Uuid>,
+        conn: &PgConnection,
+    ) -> Result<usize, DatabaseError> {
+        DomainEvent::create(
+            DomainEventTypes::SettlementDeleted,
+            "Settlement deleted".to_string(),
+            Tables::Settlements,
+            Some(self.id),
+            current_user_id,
+            Some(json!(self)),
+        )
+        .commit(conn)?;
+
         diesel::delete(settlements::table.filter(settlements::id.eq(self.id)))
             .execute(conn)
             .to_db_error(ErrorCode::DeleteError, "Error removing user")
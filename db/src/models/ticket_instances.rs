@@ -19,6 +19,9 @@ use time::Duration;
 use utils::errors::*;
 use uuid::Uuid;
 
+/// Ownership proofs are short lived so a screenshot of one cannot be passed off for long
+pub const DEFAULT_OWNERSHIP_PROOF_VALIDITY_SECONDS: u32 = 300;
+
 #[derive(Debug, Identifiable, PartialEq, Deserialize, Serialize, Queryable, QueryableByName)]
 #[table_name = "ticket_instances"]
 pub struct TicketInstance {
@@ -798,7 +801,7 @@ impl TicketInstance {
             Some(sent_via),
             conn,
         )?;
-        let wallet = Wallet::find_default_for_user(from_user_id, conn)?;
+        let wallet = Wallet::find_custodial_for_user(from_user_id, conn)?;
         let receiver_wallet = Wallet::find_default_for_user(to_user_id, conn)?;
         TicketInstance::receive_ticket_transfer(auth, &wallet, receiver_wallet.id, conn)?;
         Ok(())
@@ -808,11 +811,10 @@ impl TicketInstance {
         user_id: Uuid,
         ticket_ids: &[Uuid],
         conn: &PgConnection,
-    ) -> Result<(WalletId, Vec<(Uuid, NaiveDateTime, Option<Uuid>)>), DatabaseError> {
+    ) -> Result<Vec<(Uuid, WalletId, NaiveDateTime, Option<Uuid>)>, DatabaseError> {
         let tickets = TicketInstance::find_for_user(user_id, conn)?;
         let mut ticket_ids_and_updated_at = vec![];
         let mut all_tickets_valid = true;
-        let mut wallet_id = Uuid::nil();
 
         for ti in ticket_ids {
             let mut found_and_purchased = false;
@@ -826,8 +828,12 @@ impl TicketInstance {
                     } else {
                         None
                     };
-                    ticket_ids_and_updated_at.push((*ti, t.updated_at, existing_transfer_key));
-                    wallet_id = t.wallet_id;
+                    ticket_ids_and_updated_at.push((
+                        *ti,
+                        WalletId::new(t.wallet_id),
+                        t.updated_at,
+                        existing_transfer_key,
+                    ));
                     break;
                 }
             }
@@ -844,7 +850,7 @@ impl TicketInstance {
             ));
         }
 
-        Ok((WalletId::new(wallet_id), ticket_ids_and_updated_at))
+        Ok(ticket_ids_and_updated_at)
     }
 
     pub fn authorize_ticket_transfer(
@@ -856,7 +862,7 @@ impl TicketInstance {
         conn: &PgConnection,
     ) -> Result<TransferAuthorization, DatabaseError> {
         //Confirm that tickets are purchased and owned by user
-        let (wallet_id, ticket_ids_and_updated_at) =
+        let ticket_ids_and_updated_at =
             TicketInstance::verify_tickets_belong_to_user(user_id, ticket_ids, conn)?;
 
         //Generate transfer_key and store keys and set transfer_expiry date
@@ -865,7 +871,7 @@ impl TicketInstance {
             Utc::now().naive_utc() + Duration::seconds(validity_period_in_seconds as i64);
 
         let mut update_count = 0;
-        for (t_id, t_updated_at, existing_transfer) in ticket_ids_and_updated_at {
+        for (t_id, wallet_id, t_updated_at, existing_transfer) in ticket_ids_and_updated_at {
             update_count += diesel::update(
                 ticket_instances::table
                     .filter(ticket_instances::id.eq(t_id))
@@ -918,7 +924,7 @@ impl TicketInstance {
         let mut message: String = transfer_key.to_string();
         message.push_str(user_id.to_string().as_str());
         message.push_str((ticket_ids.len() as u32).to_string().as_str());
-        let secret_key = Wallet::find_default_for_user(user_id, conn)?.secret_key;
+        let secret_key = Wallet::find_custodial_for_user(user_id, conn)?.secret_key;
         Ok(TransferAuthorization {
             transfer_key,
             sender_user_id: user_id,
@@ -930,6 +936,96 @@ impl TicketInstance {
         })
     }
 
+    /// Signs a proof with the holder's wallet that they own the ticket, valid for
+    /// `validity_period_in_seconds` up to `DEFAULT_OWNERSHIP_PROOF_VALIDITY_SECONDS`
+    pub fn create_ownership_proof(
+        id: Uuid,
+        user_id: Uuid,
+        validity_period_in_seconds: u32,
+        conn: &PgConnection,
+    ) -> Result<TicketOwnershipProof, DatabaseError> {
+        let ticket = TicketInstance::find(id, conn)?;
+        let wallet = Wallet::find(ticket.wallet_id, conn)?;
+        if wallet.user_id != Some(user_id)
+            || (ticket.status != TicketInstanceStatus::Purchased
+                && ticket.status != TicketInstanceStatus::Redeemed)
+        {
+            return DatabaseError::business_process_error("User does not own this ticket");
+        }
+        if wallet.self_custodied {
+            return DatabaseError::business_process_error(
+                "Tickets in a self-custodied wallet must be signed with your own key",
+            );
+        }
+
+        let validity_period_in_seconds = cmp::min(
+            validity_period_in_seconds,
+            DEFAULT_OWNERSHIP_PROOF_VALIDITY_SECONDS,
+        );
+        let expires_at = (Utc::now().naive_utc()
+            + Duration::seconds(validity_period_in_seconds as i64))
+        .timestamp();
+        let message = TicketOwnershipProof::message(id, expires_at);
+        Ok(TicketOwnershipProof {
+            ticket_instance_id: id,
+            expires_at,
+            public_key: wallet.public_key,
+            signature: convert_bytes_to_hexstring(&cryptographic_signature(
+                &message,
+                &convert_hexstring_to_bytes(&wallet.secret_key),
+            )?),
+        })
+    }
+
+    /// Checks a ticket exists and whether `proof` was signed by its current owner. Proofs expiring
+    /// further out than a proof could be issued for are treated as expired. Only public details
+    /// of the ticket and event are returned.
+    pub fn verify_ownership_proof(
+        proof: &TicketOwnershipProof,
+        conn: &PgConnection,
+    ) -> Result<TicketAuthenticity, DatabaseError> {
+        let signature_valid = cryptographic_verify(
+            &convert_hexstring_to_bytes(&proof.signature),
+            &TicketOwnershipProof::message(proof.ticket_instance_id, proof.expires_at),
+            &convert_hexstring_to_bytes(&proof.public_key),
+        );
+        let now = Utc::now().timestamp();
+        let mut authenticity = TicketAuthenticity {
+            ticket_instance_id: proof.ticket_instance_id,
+            exists: false,
+            status: None,
+            event: None,
+            signature_valid,
+            expired: proof.expires_at < now
+                || proof.expires_at > now + DEFAULT_OWNERSHIP_PROOF_VALIDITY_SECONDS as i64,
+            owned_by_signer: false,
+        };
+
+        // Unsold tickets are not genuine tickets yet
+        let ticket: Option<TicketInstance> = ticket_instances::table
+            .find(proof.ticket_instance_id)
+            .filter(ticket_instances::status.eq_any(vec![
+                TicketInstanceStatus::Purchased,
+                TicketInstanceStatus::Redeemed,
+                TicketInstanceStatus::Nullified,
+            ]))
+            .first(conn)
+            .optional()
+            .to_db_error(ErrorCode::QueryError, "Unable to load ticket")?;
+        if let Some(ticket) = ticket {
+            let owner_wallet = Wallet::find(ticket.wallet_id, conn)?;
+            let event = Event::find(ticket.ticket_type(conn)?.event_id, conn)?;
+            authenticity.exists = true;
+            authenticity.status = Some(ticket.status);
+            authenticity.event = Some(event.for_display(conn)?);
+            authenticity.owned_by_signer = signature_valid
+                && !authenticity.expired
+                && owner_wallet.public_key == proof.public_key;
+        }
+
+        Ok(authenticity)
+    }
+
     pub fn receive_ticket_transfer(
         transfer_authorization: TransferAuthorization,
         sender_wallet: &Wallet,
@@ -958,14 +1054,25 @@ impl TicketInstance {
             .get_results(conn)
             .to_db_error(ErrorCode::QueryError, "Could not load ticket instances")?;
 
+        // Tickets may be held in any of the sender's wallets, but Big Neon can only sign transfers
+        // out of the wallets it holds the keys for
+        let sender_wallets = Wallet::find_for_user(transfer_authorization.sender_user_id, conn)?;
         let mut own_all = true;
-        let mut ticket_ids_to_transfer: Vec<(Uuid, NaiveDateTime)> = Vec::new();
+        let mut ticket_ids_to_transfer: Vec<(Uuid, Uuid, NaiveDateTime)> = Vec::new();
         for t in &tickets {
-            if t.wallet_id != sender_wallet.id {
-                own_all = false;
-                break;
+            match sender_wallets.iter().find(|w| w.id == t.wallet_id) {
+                Some(w) if w.self_custodied => {
+                    return DatabaseError::business_process_error(
+                        "Tickets in a self-custodied wallet must be transferred with your own key",
+                    );
+                }
+                Some(_) => (),
+                None => {
+                    own_all = false;
+                    break;
+                }
             }
-            ticket_ids_to_transfer.push((t.id, t.updated_at));
+            ticket_ids_to_transfer.push((t.id, t.wallet_id, t.updated_at));
         }
 
         if !own_all || tickets.len() != transfer_authorization.num_tickets as usize {
@@ -984,7 +1091,7 @@ impl TicketInstance {
 
         //Perform transfer
         let mut update_count = 0;
-        for (t_id, updated_at) in &ticket_ids_to_transfer {
+        for (t_id, t_wallet_id, updated_at) in &ticket_ids_to_transfer {
             update_count += diesel::update(
                 ticket_instances::table
                     .filter(ticket_instances::id.eq(t_id))
@@ -1006,7 +1113,7 @@ impl TicketInstance {
                 Tables::TicketInstances,
                 Some(t_id.clone()),
                 None,
-                Some(json!({"receiver_wallet_id": receiver_wallet_id, "sender_wallet_id": sender_wallet.id, "num_tickets": transfer_authorization.num_tickets, "transfer_key": transfer_authorization.transfer_key})),
+                Some(json!({"receiver_wallet_id": receiver_wallet_id, "sender_wallet_id": t_wallet_id, "num_tickets": transfer_authorization.num_tickets, "transfer_key": transfer_authorization.transfer_key})),
             )
             .commit(conn)?;
         }
@@ -1068,6 +1175,36 @@ pub struct TransferAuthorization {
     pub signature: String,
 }
 
+/// Proof signed by a ticket holder's wallet that they own the ticket
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct TicketOwnershipProof {
+    pub ticket_instance_id: Uuid,
+    /// Unix timestamp after which the proof is no longer accepted
+    pub expires_at: i64,
+    pub public_key: String,
+    pub signature: String,
+}
+
+impl TicketOwnershipProof {
+    /// Message signed by the holder, for holders of self-custodied wallets signing their own proofs
+    pub fn message(ticket_instance_id: Uuid, expires_at: i64) -> String {
+        format!("{}{}", ticket_instance_id, expires_at)
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct TicketAuthenticity {
+    pub ticket_instance_id: Uuid,
+    pub exists: bool,
+    pub status: Option<TicketInstanceStatus>,
+    pub event: Option<DisplayEvent>,
+    pub signature_valid: bool,
+    pub expired: bool,
+    /// True when the signature is valid, the proof has not expired and the signer currently owns
+    /// the ticket
+    pub owned_by_signer: bool,
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct DisplayTicket {
     pub id: Uuid,
@@ -3,9 +3,10 @@ use diesel;
 use diesel::dsl::{self, select};
 use diesel::prelude::*;
 use diesel::sql_types::{Bool, Timestamp, Uuid as dUuid};
-use models::{TicketPricingStatus, TicketType};
+use models::{DomainEvent, DomainEventTypes, Tables, TicketPricingStatus, TicketType};
 use schema::{order_items, ticket_pricing};
 use std::borrow::Cow;
+use utils::audit;
 use utils::errors::*;
 use uuid::Uuid;
 use validator::*;
@@ -13,7 +14,7 @@ use validators::{self, *};
 
 sql_function!(fn ticket_pricing_no_overlapping_periods(id: dUuid, ticket_type_id: dUuid, start_date: Timestamp, end_date: Timestamp, is_box_office_only: Bool, is_default_status: Bool) -> Bool);
 
-#[derive(Clone, Identifiable, Associations, Queryable, PartialEq, Debug)]
+#[derive(Clone, Identifiable, Associations, Queryable, PartialEq, Debug, Serialize)]
 #[belongs_to(TicketType)]
 #[table_name = "ticket_pricing"]
 pub struct TicketPricing {
@@ -77,33 +78,50 @@ impl TicketPricing {
 
     pub fn update(
         &self,
+        current_user_id: Option<Uuid>,
         attributes: TicketPricingEditableAttributes,
         conn: &PgConnection,
     ) -> Result<TicketPricing, DatabaseError> {
         self.validate_record(&attributes)?;
 
-        if self.affected_order_count(conn)? == 0 || attributes.price_in_cents.is_none() {
-            // No orders affected or price does not change, update existing record
-            diesel::update(self)
-                .set((attributes, ticket_pricing::updated_at.eq(dsl::now)))
-                .get_result(conn)
-                .to_db_error(ErrorCode::UpdateError, "Could not update ticket_pricing")
-        } else {
-            // Orders affected, create new ticket pricing and delete old
-            let new_ticket_pricing = TicketPricing::create(
-                self.ticket_type_id,
-                attributes.name.unwrap_or(self.name.clone()),
-                attributes.start_date.unwrap_or(self.start_date),
-                attributes.end_date.unwrap_or(self.end_date),
-                attributes.price_in_cents.unwrap(),
-                attributes
-                    .is_box_office_only
-                    .unwrap_or(self.is_box_office_only),
-                Some(self.status),
-            );
-            self.destroy(conn)?;
-            new_ticket_pricing.commit(conn)
-        }
+        let result: TicketPricing =
+            if self.affected_order_count(conn)? == 0 || attributes.price_in_cents.is_none() {
+                // No orders affected or price does not change, update existing record
+                diesel::update(self)
+                    .set((attributes, ticket_pricing::updated_at.eq(dsl::now)))
+                    .get_result(conn)
+                    .to_db_error(ErrorCode::UpdateError, "Could not update ticket_pricing")?
+            } else {
+                // Orders affected, create new ticket pricing and delete old
+                let new_ticket_pricing = TicketPricing::create(
+                    self.ticket_type_id,
+                    attributes.name.unwrap_or(self.name.clone()),
+                    attributes.start_date.unwrap_or(self.start_date),
+                    attributes.end_date.unwrap_or(self.end_date),
+                    attributes.price_in_cents.unwrap(),
+                    attributes
+                        .is_box_office_only
+                        .unwrap_or(self.is_box_office_only),
+                    Some(self.status),
+                );
+                self.destroy(conn)?;
+                new_ticket_pricing.commit(conn)?
+            };
+
+        DomainEvent::create(
+            DomainEventTypes::TicketPricingUpdated,
+            format!("Ticket pricing {} updated", result.name),
+            Tables::TicketPricing,
+            Some(result.id),
+            current_user_id,
+            Some(json!({
+                "previous_ticket_pricing_id": self.id,
+                "changes": audit::changes(self, &result, &[])
+            })),
+        )
+        .commit(conn)?;
+
+        Ok(result)
     }
 
     pub fn ticket_pricing_does_not_overlap_ticket_type_start_date(
@@ -10,12 +10,15 @@ use schema::{
     ticket_type_codes, ticket_types,
 };
 use std::cmp::Ordering;
+use utils::audit;
 use utils::errors::*;
 use uuid::Uuid;
 use validator::*;
 use validators;
 
-#[derive(Associations, Clone, Debug, Identifiable, PartialEq, Queryable, QueryableByName)]
+#[derive(
+    Associations, Clone, Debug, Identifiable, PartialEq, Queryable, QueryableByName, Serialize,
+)]
 #[table_name = "ticket_types"]
 #[belongs_to(Event)]
 pub struct TicketType {
@@ -34,6 +37,7 @@ pub struct TicketType {
     pub cancelled_at: Option<NaiveDateTime>,
     pub sold_out_behavior: SoldOutBehavior,
     pub is_private: bool,
+    pub refundable: bool,
 }
 
 impl PartialOrd for TicketType {
@@ -54,6 +58,7 @@ pub struct TicketTypeEditableAttributes {
     pub price_in_cents: Option<i64>,
     pub sold_out_behavior: Option<SoldOutBehavior>,
     pub is_private: Option<bool>,
+    pub refundable: Option<bool>,
 }
 
 impl TicketType {
@@ -114,6 +119,7 @@ impl TicketType {
 
     pub fn update(
         &self,
+        current_user_id: Option<Uuid>,
         attributes: TicketTypeEditableAttributes,
         conn: &PgConnection,
     ) -> Result<TicketType, DatabaseError> {
@@ -145,6 +151,16 @@ impl TicketType {
             conn,
         )?;
 
+        DomainEvent::create(
+            DomainEventTypes::TicketTypeUpdated,
+            format!("Ticket type {} updated", result.name),
+            Tables::TicketTypes,
+            Some(self.id),
+            current_user_id,
+            Some(json!({ "changes": audit::changes(self, &result, &[]) })),
+        )
+        .commit(conn)?;
+
         Ok(result)
     }
 
@@ -255,6 +271,13 @@ impl TicketType {
                         JOIN codes c ON ttc.code_id = c.id
                         JOIN ticket_types tt ON tt.id = ttc.ticket_type_id
                         WHERE c.code_type = 'Access' AND tt.event_id = $1
+                        UNION ALL
+                        SELECT ttc.ticket_type_id, uc.redemption_code, c.start_date, c.end_date
+                        FROM ticket_type_codes ttc
+                        JOIN codes c ON ttc.code_id = c.id
+                        JOIN unique_codes uc ON uc.code_id = c.id
+                        JOIN ticket_types tt ON tt.id = ttc.ticket_type_id
+                        WHERE c.code_type = 'Access' AND tt.event_id = $1 AND uc.redemption_code = $2
                     ) ttc ON ttc.ticket_type_id = tt.id
                     WHERE tt.event_id = $1
                     AND (
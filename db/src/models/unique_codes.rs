@@ -0,0 +1,387 @@
+use chrono::NaiveDateTime;
+use diesel;
+use diesel::dsl;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Nullable, Text, Timestamp, Uuid as dUuid};
+use models::*;
+use rand::{thread_rng, Rng};
+use schema::{codes, holds, order_items, orders, unique_codes};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use utils::errors::*;
+use uuid::Uuid;
+use validator::*;
+use validators::{self, *};
+
+/// Characters used for generated codes when none are specified, excludes characters that are
+/// easily confused such as 0/O and 1/I
+pub const UNIQUE_CODE_DEFAULT_CHARACTER_SET: &'static str = "ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+pub const UNIQUE_CODE_DEFAULT_LENGTH: u32 = 8;
+pub const UNIQUE_CODE_MAX_QUANTITY: u32 = 10_000;
+const UNIQUE_CODE_MAX_GENERATION_ATTEMPTS: u32 = 10;
+
+/// A single-use redemption code generated in bulk for a `Code`. The discount and access rules
+/// are taken from the parent code and its uses count towards the parent's `max_uses`.
+#[derive(
+    Associations, Clone, Debug, Deserialize, Identifiable, PartialEq, Queryable, Serialize,
+)]
+#[belongs_to(Code)]
+#[table_name = "unique_codes"]
+pub struct UniqueCode {
+    pub id: Uuid,
+    pub code_id: Uuid,
+    pub redemption_code: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, PartialEq, QueryableByName, Serialize)]
+pub struct DisplayUniqueCode {
+    #[sql_type = "dUuid"]
+    pub id: Uuid,
+    #[sql_type = "Text"]
+    pub redemption_code: String,
+    #[sql_type = "Timestamp"]
+    pub created_at: NaiveDateTime,
+    /// The order the code has been used on, this includes carts that have not been paid yet
+    #[sql_type = "Nullable<dUuid>"]
+    pub order_id: Option<Uuid>,
+    /// When the order the code was used on was paid
+    #[sql_type = "Nullable<Timestamp>"]
+    pub redeemed_at: Option<NaiveDateTime>,
+}
+
+#[derive(Insertable)]
+#[table_name = "unique_codes"]
+struct NewUniqueCode {
+    code_id: Uuid,
+    redemption_code: String,
+}
+
+impl UniqueCode {
+    /// Generates `quantity` codes made up of `prefix` followed by `length` random characters
+    /// taken from `character_set`. Codes already in use by other codes or holds are skipped.
+    pub fn generate(
+        code: &Code,
+        quantity: u32,
+        prefix: Option<String>,
+        character_set: Option<String>,
+        length: Option<u32>,
+        current_user_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<UniqueCode>, DatabaseError> {
+        let prefix = prefix.unwrap_or("".to_string()).to_uppercase();
+        let mut characters: Vec<char> = character_set
+            .unwrap_or(UNIQUE_CODE_DEFAULT_CHARACTER_SET.to_string())
+            .to_uppercase()
+            .chars()
+            .collect();
+        characters.sort();
+        characters.dedup();
+        let length = length.unwrap_or(UNIQUE_CODE_DEFAULT_LENGTH);
+
+        UniqueCode::validate_generation(quantity, &prefix, &characters, length)?;
+
+        let mut rng = thread_rng();
+        let mut redemption_codes: HashSet<String> = HashSet::new();
+        let mut attempts = 0;
+        while redemption_codes.len() < quantity as usize {
+            attempts += 1;
+            if attempts > UNIQUE_CODE_MAX_GENERATION_ATTEMPTS {
+                return DatabaseError::business_process_error(
+                    "Could not generate enough unique codes, try a longer length or larger character set",
+                );
+            }
+
+            let candidates: Vec<String> = (redemption_codes.len()..quantity as usize)
+                .map(|_| {
+                    let suffix: String = (0..length)
+                        .map(|_| characters[rng.gen_range(0, characters.len())])
+                        .collect();
+                    format!("{}{}", prefix, suffix)
+                })
+                .filter(|candidate| !redemption_codes.contains(candidate))
+                .collect();
+            let in_use = UniqueCode::redemption_codes_in_use(&candidates, conn)?;
+            redemption_codes.extend(
+                candidates
+                    .into_iter()
+                    .filter(|candidate| !in_use.contains(candidate)),
+            );
+        }
+
+        let mut redemption_codes: Vec<String> = redemption_codes.into_iter().collect();
+        redemption_codes.truncate(quantity as usize);
+        let unique_codes: Vec<UniqueCode> = diesel::insert_into(unique_codes::table)
+            .values(
+                &redemption_codes
+                    .into_iter()
+                    .map(|redemption_code| NewUniqueCode {
+                        code_id: code.id,
+                        redemption_code,
+                    })
+                    .collect::<Vec<NewUniqueCode>>(),
+            )
+            .get_results(conn)
+            .to_db_error(ErrorCode::InsertError, "Could not create unique codes")?;
+
+        DomainEvent::create(
+            DomainEventTypes::UniqueCodesGenerated,
+            format!("{} unique codes generated", unique_codes.len()),
+            Tables::Codes,
+            Some(code.id),
+            Some(current_user_id),
+            Some(json!({
+                "quantity": unique_codes.len(),
+                "prefix": prefix,
+                "length": length,
+            })),
+        )
+        .commit(conn)?;
+
+        Ok(unique_codes)
+    }
+
+    /// Random redemption code not used by any code or hold, for when one is needed but the
+    /// person will never have to type it in
+    pub(crate) fn generate_redemption_code(conn: &PgConnection) -> Result<String, DatabaseError> {
+        let characters: Vec<char> = UNIQUE_CODE_DEFAULT_CHARACTER_SET.chars().collect();
+        let mut rng = thread_rng();
+        for _ in 0..UNIQUE_CODE_MAX_GENERATION_ATTEMPTS {
+            let candidate: String = (0..UNIQUE_CODE_DEFAULT_LENGTH)
+                .map(|_| characters[rng.gen_range(0, characters.len())])
+                .collect();
+            if UniqueCode::redemption_codes_in_use(&[candidate.clone()], conn)?.is_empty() {
+                return Ok(candidate);
+            }
+        }
+        DatabaseError::business_process_error("Could not generate a unique redemption code")
+    }
+
+    fn validate_generation(
+        quantity: u32,
+        prefix: &str,
+        characters: &[char],
+        length: u32,
+    ) -> Result<(), DatabaseError> {
+        let mut validation_errors = Ok(());
+
+        if quantity == 0 || quantity > UNIQUE_CODE_MAX_QUANTITY {
+            let mut validation_error =
+                create_validation_error("out_of_range", "Quantity must be between 1 and 10000");
+            validation_error.add_param(Cow::from("quantity"), &quantity);
+            validation_errors = validators::append_validation_error(
+                validation_errors,
+                "quantity",
+                Err(validation_error),
+            );
+        }
+
+        if !prefix
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-')
+        {
+            let mut validation_error = create_validation_error(
+                "invalid",
+                "Prefix may only contain letters, numbers and dashes",
+            );
+            validation_error.add_param(Cow::from("prefix"), &prefix);
+            validation_errors = validators::append_validation_error(
+                validation_errors,
+                "prefix",
+                Err(validation_error),
+            );
+        }
+
+        if characters.len() < 2 || !characters.iter().all(|c| c.is_ascii_alphanumeric()) {
+            let validation_error = create_validation_error(
+                "invalid",
+                "Character set must contain at least two letters or numbers",
+            );
+            validation_errors = validators::append_validation_error(
+                validation_errors,
+                "character_set",
+                Err(validation_error),
+            );
+        } else if (characters.len() as f64).powi(length as i32) < 10.0 * quantity as f64 {
+            // Leave enough headroom that random codes are unlikely to collide or be guessed
+            let mut validation_error = create_validation_error(
+                "too_few_combinations",
+                "Length is too short to generate this many codes",
+            );
+            validation_error.add_param(Cow::from("length"), &length);
+            validation_error.add_param(Cow::from("quantity"), &quantity);
+            validation_errors = validators::append_validation_error(
+                validation_errors,
+                "length",
+                Err(validation_error),
+            );
+        }
+
+        if prefix.len() + (length as usize) < 6 {
+            let mut validation_error = create_validation_error(
+                "length",
+                "Redemption code must be at least 6 characters long",
+            );
+            validation_error.add_param(Cow::from("length"), &length);
+            validation_errors = validators::append_validation_error(
+                validation_errors,
+                "length",
+                Err(validation_error),
+            );
+        }
+
+        Ok(validation_errors?)
+    }
+
+    fn redemption_codes_in_use(
+        candidates: &[String],
+        conn: &PgConnection,
+    ) -> Result<HashSet<String>, DatabaseError> {
+        let mut in_use: HashSet<String> = HashSet::new();
+        in_use.extend(
+            codes::table
+                .filter(codes::redemption_code.eq_any(candidates))
+                .select(codes::redemption_code)
+                .load::<String>(conn)
+                .to_db_error(ErrorCode::QueryError, "Could not check redemption codes")?,
+        );
+        in_use.extend(
+            holds::table
+                .filter(holds::redemption_code.eq_any(candidates))
+                .select(holds::redemption_code)
+                .load::<Option<String>>(conn)
+                .to_db_error(ErrorCode::QueryError, "Could not check redemption codes")?
+                .into_iter()
+                .filter_map(|r| r),
+        );
+        in_use.extend(
+            unique_codes::table
+                .filter(unique_codes::redemption_code.eq_any(candidates))
+                .select(unique_codes::redemption_code)
+                .load::<String>(conn)
+                .to_db_error(ErrorCode::QueryError, "Could not check redemption codes")?,
+        );
+        Ok(in_use)
+    }
+
+    pub fn find(id: Uuid, conn: &PgConnection) -> Result<UniqueCode, DatabaseError> {
+        unique_codes::table
+            .filter(unique_codes::id.eq(id))
+            .first(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not retrieve unique code")
+    }
+
+    pub fn find_by_redemption_code(
+        redemption_code: &str,
+        event_id: Option<Uuid>,
+        conn: &PgConnection,
+    ) -> Result<UniqueCode, DatabaseError> {
+        let mut query = unique_codes::table
+            .inner_join(codes::table)
+            .filter(unique_codes::redemption_code.eq(redemption_code.to_uppercase()))
+            .select(unique_codes::all_columns)
+            .into_boxed();
+        if let Some(event_id) = event_id {
+            query = query.filter(codes::event_id.eq(event_id));
+        }
+        query.first(conn).to_db_error(
+            ErrorCode::QueryError,
+            "Could not load code with that redeem code",
+        )
+    }
+
+    /// Number of orders, paid or still in a cart, the code has been used on
+    pub fn find_number_of_uses(
+        unique_code_id: Uuid,
+        order_id_to_exclude: Option<Uuid>,
+        conn: &PgConnection,
+    ) -> Result<i64, DatabaseError> {
+        order_items::table
+            .inner_join(orders::table.on(order_items::order_id.eq(orders::id)))
+            .filter(order_items::order_id.ne(order_id_to_exclude.unwrap_or(Uuid::nil())))
+            .filter(order_items::unique_code_id.eq(unique_code_id))
+            .filter(order_items::refunded_quantity.eq(0))
+            .filter(
+                orders::expires_at
+                    .gt(dsl::now.nullable())
+                    .or(orders::status.eq(OrderStatus::Paid)),
+            )
+            .select(order_items::order_id)
+            .distinct()
+            .load::<Uuid>(conn)
+            .to_db_error(ErrorCode::QueryError, "Error loading unique code uses")
+            .map(|order_ids| order_ids.len() as i64)
+    }
+
+    pub fn find_for_code(
+        code_id: Uuid,
+        page: u32,
+        limit: u32,
+        conn: &PgConnection,
+    ) -> Result<Payload<DisplayUniqueCode>, DatabaseError> {
+        let total: i64 = unique_codes::table
+            .filter(unique_codes::code_id.eq(code_id))
+            .count()
+            .first(conn)
+            .to_db_error(
+                ErrorCode::QueryError,
+                "Could not get total unique codes for code",
+            )?;
+
+        let paging = Paging::new(page, limit);
+        let mut payload = Payload::new(
+            UniqueCode::find_for_display(code_id, Some(limit as i64), (page * limit) as i64, conn)?,
+            paging,
+        );
+        payload.paging.total = total as u64;
+        payload.paging.page = page;
+        payload.paging.limit = limit;
+        Ok(payload)
+    }
+
+    /// All of the code's generated codes along with their redemption status, used for exports
+    pub fn find_all_for_code(
+        code_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<DisplayUniqueCode>, DatabaseError> {
+        UniqueCode::find_for_display(code_id, None, 0, conn)
+    }
+
+    fn find_for_display(
+        code_id: Uuid,
+        limit: Option<i64>,
+        offset: i64,
+        conn: &PgConnection,
+    ) -> Result<Vec<DisplayUniqueCode>, DatabaseError> {
+        let query = r#"
+                SELECT
+                    uc.id,
+                    uc.redemption_code,
+                    uc.created_at,
+                    redemption.order_id,
+                    redemption.paid_at AS redeemed_at
+                FROM unique_codes uc
+                LEFT JOIN LATERAL (
+                    SELECT o.id AS order_id, o.paid_at
+                    FROM order_items oi
+                    JOIN orders o ON o.id = oi.order_id
+                    WHERE oi.unique_code_id = uc.id
+                    AND oi.refunded_quantity = 0
+                    AND (o.status = 'Paid' OR o.expires_at > now())
+                    ORDER BY o.status = 'Paid' DESC, o.order_date
+                    LIMIT 1
+                ) redemption ON true
+                WHERE uc.code_id = $1
+                ORDER BY uc.redemption_code
+                LIMIT $2
+                OFFSET $3;"#;
+
+        diesel::sql_query(query)
+            .bind::<dUuid, _>(code_id)
+            .bind::<Nullable<BigInt>, _>(limit)
+            .bind::<BigInt, _>(offset)
+            .get_results(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load unique codes")
+    }
+}
@@ -0,0 +1,172 @@
+use chrono::prelude::*;
+use diesel::prelude::*;
+use models::*;
+use schema::{domain_events, event_interest, orders, payments};
+use utils::errors::*;
+use uuid::Uuid;
+
+/// Everything stored about a user, bundled so they can download a copy of their data
+#[derive(Serialize)]
+pub struct UserDataExport {
+    pub profile: DisplayUser,
+    pub created_at: NaiveDateTime,
+    pub last_used: Option<NaiveDateTime>,
+    pub accepted_terms_date: Option<NaiveDateTime>,
+    pub orders: Vec<DisplayOrder>,
+    pub tickets: Vec<(DisplayEvent, Vec<DisplayTicket>)>,
+    pub transfers: Vec<DomainEvent>,
+    pub payments: Vec<UserDataExportPayment>,
+    pub payment_methods: Vec<DisplayPaymentMethod>,
+    pub event_interest: Vec<EventInterest>,
+    pub push_notification_tokens: Vec<DisplayPushNotificationToken>,
+    pub marketing_consents: Vec<MarketingConsent>,
+    pub domain_events: Vec<DomainEvent>,
+    pub generated_at: NaiveDateTime,
+}
+
+/// Payment without the provider's references and raw data
+#[derive(Debug, Deserialize, PartialEq, Queryable, Serialize)]
+pub struct UserDataExportPayment {
+    pub id: Uuid,
+    pub order_id: Uuid,
+    pub status: PaymentStatus,
+    pub payment_method: PaymentMethods,
+    pub provider: PaymentProviders,
+    pub amount: i64,
+    pub created_at: NaiveDateTime,
+}
+
+impl UserDataExport {
+    pub fn for_user(user: &User, conn: &PgConnection) -> Result<UserDataExport, DatabaseError> {
+        let transfer_event_types = vec![
+            DomainEventTypes::TransferTicketStarted,
+            DomainEventTypes::TransferTicketCancelled,
+            DomainEventTypes::TransferTicketCompleted,
+        ];
+        // Tickets the user holds now or has sent to someone else
+        let mut ticket_ids: Vec<Uuid> = TicketInstance::find_for_user(user.id, conn)?
+            .into_iter()
+            .map(|t| t.id)
+            .collect();
+        let sent_ticket_ids: Vec<Option<Uuid>> = domain_events::table
+            .filter(domain_events::event_type.eq_any(transfer_event_types.clone()))
+            .filter(domain_events::user_id.eq(user.id))
+            .select(domain_events::main_id)
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load ticket transfers")?;
+        ticket_ids.extend(sent_ticket_ids.into_iter().filter_map(|id| id));
+
+        let mut transfers: Vec<DomainEvent> = domain_events::table
+            .filter(domain_events::event_type.eq_any(transfer_event_types))
+            .filter(domain_events::main_table.eq(Tables::TicketInstances))
+            .filter(domain_events::main_id.eq_any(ticket_ids))
+            .order_by(domain_events::created_at)
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load ticket transfers")?;
+        // Transfers of the same tickets between other people only show that a transfer
+        // happened, their details belong to the people involved
+        let addresses: Vec<String> = user
+            .email
+            .iter()
+            .chain(user.phone.iter())
+            .map(|a| a.trim().to_lowercase())
+            .collect();
+        let wallet_ids: Vec<String> = Wallet::find_for_user(user.id, conn)?
+            .into_iter()
+            .map(|w| w.id.to_string())
+            .collect();
+        for transfer in transfers.iter_mut() {
+            if transfer.user_id == Some(user.id) {
+                continue;
+            }
+            let involves_user = match transfer.event_data {
+                Some(ref data) => {
+                    data["address"]
+                        .as_str()
+                        .map(|a| addresses.contains(&a.trim().to_lowercase()))
+                        .unwrap_or(false)
+                        || data["receiver_wallet_id"]
+                            .as_str()
+                            .map(|w| wallet_ids.contains(&w.to_string()))
+                            .unwrap_or(false)
+                }
+                None => false,
+            };
+            if !involves_user {
+                transfer.user_id = None;
+                transfer.event_data = None;
+            }
+        }
+
+        let payments = payments::table
+            .inner_join(orders::table.on(payments::order_id.eq(orders::id)))
+            .filter(
+                orders::user_id
+                    .eq(user.id)
+                    .or(orders::on_behalf_of_user_id.eq(user.id)),
+            )
+            .select((
+                payments::id,
+                payments::order_id,
+                payments::status,
+                payments::payment_method,
+                payments::provider,
+                payments::amount,
+                payments::created_at,
+            ))
+            .order_by(payments::created_at)
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load payments for user")?;
+
+        let event_interest = event_interest::table
+            .filter(event_interest::user_id.eq(user.id))
+            .order_by(event_interest::created_at)
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load event interest")?;
+
+        let mut domain_events: Vec<DomainEvent> = domain_events::table
+            .filter(
+                domain_events::user_id
+                    .eq(user.id)
+                    .or(domain_events::main_table
+                        .eq(Tables::Users)
+                        .and(domain_events::main_id.eq(user.id))),
+            )
+            .order_by(domain_events::created_at)
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load domain events")?;
+        // Payment events carry the provider's raw responses
+        for domain_event in domain_events.iter_mut() {
+            if domain_event.main_table == Tables::Payments
+                || domain_event.main_table == Tables::PaymentMethods
+            {
+                domain_event.event_data = None;
+            }
+        }
+
+        Ok(UserDataExport {
+            profile: user.clone().for_display()?,
+            created_at: user.created_at,
+            last_used: user.last_used,
+            accepted_terms_date: user.accepted_terms_date,
+            orders: Order::find_for_user_for_display(user.id, conn)?,
+            tickets: TicketInstance::find_for_user_for_display(user.id, None, None, None, conn)?,
+            transfers,
+            payments,
+            payment_methods: user
+                .payment_methods(conn)?
+                .into_iter()
+                .map(|p| p.into())
+                .collect(),
+            event_interest,
+            push_notification_tokens: user
+                .push_notification_tokens(conn)?
+                .into_iter()
+                .map(|t| t.into())
+                .collect(),
+            marketing_consents: MarketingConsent::find_for_user(user.id, conn)?,
+            domain_events,
+            generated_at: Utc::now().naive_utc(),
+        })
+    }
+}
@@ -4,9 +4,12 @@ use diesel;
 use diesel::expression::dsl;
 use diesel::expression::sql_literal::sql;
 use diesel::prelude::*;
-use diesel::sql_types::{BigInt, Nullable, Text, Timestamp, Uuid as dUuid};
+use diesel::sql_types::{Array, BigInt, Nullable, Text, Timestamp, Uuid as dUuid};
 use models::*;
-use schema::{events, organization_users, organizations, users};
+use schema::{
+    email_suppressions, event_interest, events, external_logins, fan_notes, fan_tags,
+    marketing_consents, organization_users, organizations, push_notification_tokens, users,
+};
 use serde_json::Value;
 use std::collections::HashMap;
 use time::Duration;
@@ -100,6 +103,11 @@ pub struct FanProfile {
     pub attendance_information: Vec<AttendanceInformation>,
 }
 
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct DeleteUserPayload {
+    pub requested_by_user_id: Option<Uuid>,
+}
+
 #[derive(Debug, Deserialize, PartialEq, Queryable, QueryableByName, Serialize)]
 pub struct AttendanceInformation {
     #[sql_type = "dUuid"]
@@ -713,6 +721,151 @@ impl User {
     ) -> Result<Vec<PushNotificationToken>, DatabaseError> {
         PushNotificationToken::find_by_user_id(self.id, conn)
     }
+
+    /// Queues the account for deletion, which also removes stored cards from payment providers
+    pub fn queue_deletion(
+        &self,
+        requested_by_user_id: Option<Uuid>,
+        conn: &PgConnection,
+    ) -> Result<DomainAction, DatabaseError> {
+        let pending = DomainAction::find_by_main_table(
+            DomainActionTypes::DeleteUser,
+            Tables::Users.to_string(),
+            self.id,
+            conn,
+        )?
+        .into_iter()
+        .find(|a| a.status == DomainActionStatus::Pending);
+        if let Some(action) = pending {
+            return Ok(action);
+        }
+
+        DomainAction::create(
+            None,
+            DomainActionTypes::DeleteUser,
+            None,
+            json!(DeleteUserPayload {
+                requested_by_user_id
+            }),
+            Some(Tables::Users.to_string()),
+            Some(self.id),
+        )
+        .commit(conn)
+    }
+
+    /// Removes the user's personal details and everything linking them to outside accounts.
+    /// The user row itself is kept so orders, payments and tickets stay intact for reporting.
+    /// Card details held by payment providers must be removed before calling this.
+    pub fn anonymize(
+        &self,
+        current_user_id: Option<Uuid>,
+        conn: &PgConnection,
+    ) -> Result<User, DatabaseError> {
+        if let Some(mut cart) = Order::find_cart_for_user(self.id, conn)? {
+            cart.clear_cart(self.id, conn)?;
+        }
+
+        let payment_methods = self.payment_methods(conn)?;
+        for payment_method in &payment_methods {
+            payment_method.destroy(current_user_id.unwrap_or(self.id), conn)?;
+        }
+
+        let external_logins =
+            diesel::delete(external_logins::table.filter(external_logins::user_id.eq(self.id)))
+                .execute(conn)
+                .to_db_error(ErrorCode::DeleteError, "Could not remove external logins")?;
+        let push_notification_tokens = diesel::delete(
+            push_notification_tokens::table.filter(push_notification_tokens::user_id.eq(self.id)),
+        )
+        .execute(conn)
+        .to_db_error(
+            ErrorCode::DeleteError,
+            "Could not remove push notification tokens",
+        )?;
+        diesel::delete(event_interest::table.filter(event_interest::user_id.eq(self.id)))
+            .execute(conn)
+            .to_db_error(ErrorCode::DeleteError, "Could not remove event interest")?;
+        diesel::delete(marketing_consents::table.filter(marketing_consents::user_id.eq(self.id)))
+            .execute(conn)
+            .to_db_error(
+                ErrorCode::DeleteError,
+                "Could not remove marketing consents",
+            )?;
+        diesel::delete(fan_notes::table.filter(fan_notes::user_id.eq(self.id)))
+            .execute(conn)
+            .to_db_error(ErrorCode::DeleteError, "Could not remove fan notes")?;
+        diesel::delete(fan_tags::table.filter(fan_tags::user_id.eq(self.id)))
+            .execute(conn)
+            .to_db_error(ErrorCode::DeleteError, "Could not remove fan tags")?;
+
+        // The address is also kept wherever tickets were sent to it and wherever it was suppressed
+        let addresses: Vec<String> = self
+            .email
+            .iter()
+            .chain(self.phone.iter())
+            .map(|a| a.trim().to_lowercase())
+            .collect();
+        diesel::sql_query(
+            r#"
+            UPDATE domain_events
+            SET event_data = (event_data::jsonb || '{"address": null}'::jsonb)::json
+            WHERE event_type = $1
+              AND lower(trim(event_data ->> 'address')) = ANY($2)
+            "#,
+        )
+        .bind::<Text, _>(DomainEventTypes::TransferTicketStarted.to_string())
+        .bind::<Array<Text>, _>(addresses.clone())
+        .execute(conn)
+        .to_db_error(
+            ErrorCode::UpdateError,
+            "Could not remove addresses from ticket transfers",
+        )?;
+        diesel::delete(
+            email_suppressions::table.filter(email_suppressions::email.eq_any(addresses)),
+        )
+        .execute(conn)
+        .to_db_error(
+            ErrorCode::DeleteError,
+            "Could not remove email suppressions",
+        )?;
+
+        // Nobody knows this password, so the account can no longer be signed into
+        let hashed_pw = PasswordHash::generate(&random_alpha_string(32), None).to_string();
+        let user: User = diesel::update(self)
+            .set((
+                users::first_name.eq(None::<String>),
+                users::last_name.eq(None::<String>),
+                users::email.eq(None::<String>),
+                users::phone.eq(None::<String>),
+                users::profile_pic_url.eq(None::<String>),
+                users::thumb_profile_pic_url.eq(None::<String>),
+                users::cover_photo_url.eq(None::<String>),
+                users::hashed_pw.eq(hashed_pw),
+                users::password_modified_at.eq(dsl::now),
+                users::password_reset_token.eq(None::<Uuid>),
+                users::password_reset_requested_at.eq(None::<NaiveDateTime>),
+                users::active.eq(false),
+                users::updated_at.eq(dsl::now),
+            ))
+            .get_result(conn)
+            .to_db_error(ErrorCode::UpdateError, "Could not anonymize user")?;
+
+        DomainEvent::create(
+            DomainEventTypes::UserDeleted,
+            "User account was deleted".to_string(),
+            Tables::Users,
+            Some(self.id),
+            current_user_id,
+            Some(json!({
+                "payment_methods_removed": payment_methods.len(),
+                "external_logins_removed": external_logins,
+                "push_notification_tokens_removed": push_notification_tokens,
+            })),
+        )
+        .commit(conn)?;
+
+        Ok(user)
+    }
 }
 
 impl From<User> for DisplayUser {
@@ -1,24 +1,79 @@
 use chrono::NaiveDateTime;
 use diesel;
+use diesel::dsl;
 use diesel::prelude::*;
-use schema::wallets;
+use models::TicketInstanceStatus;
+use schema::{assets, ticket_instances, ticket_types, wallets};
 use std::default::Default;
-use tari_client::{convert_bytes_to_hexstring, cryptographic_keypair};
+use tari_client::{
+    convert_bytes_to_hexstring, convert_hexstring_to_bytes, cryptographic_keypair,
+    cryptographic_verify,
+};
+use utils::encryption;
 use utils::errors;
 use utils::errors::*;
 use uuid::Uuid;
 
+/// Shortest passphrase accepted when exporting wallet keys
+const MIN_EXPORT_PASSPHRASE_LENGTH: usize = 12;
+
 #[derive(Identifiable, Queryable, Clone)]
 pub struct Wallet {
     pub id: Uuid,
-    user_id: Option<Uuid>,
+    pub user_id: Option<Uuid>,
     organization_id: Option<Uuid>,
-    name: String,
+    pub name: String,
+    /// Empty for self-custodied wallets, whose secret key is only known to the user
     pub secret_key: String,
     pub public_key: String,
-    default_flag: bool,
+    pub default_flag: bool,
     created_at: NaiveDateTime,
     updated_at: NaiveDateTime,
+    pub self_custodied: bool,
+}
+
+/// Wallet keys exported for the user, with the secret key encrypted by a key derived from their
+/// passphrase with PBKDF2-HMAC-SHA256
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct WalletExport {
+    pub wallet_id: Uuid,
+    pub public_key: String,
+    pub encrypted_secret_key: String,
+    /// Hex encoded salt, random for each export
+    pub salt: String,
+    pub iterations: u32,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct DisplayWallet {
+    pub id: Uuid,
+    pub name: String,
+    pub public_key: String,
+    pub default_flag: bool,
+    pub self_custodied: bool,
+}
+
+impl From<Wallet> for DisplayWallet {
+    fn from(wallet: Wallet) -> Self {
+        DisplayWallet {
+            id: wallet.id,
+            name: wallet.name,
+            public_key: wallet.public_key,
+            default_flag: wallet.default_flag,
+            self_custodied: wallet.self_custodied,
+        }
+    }
+}
+
+/// Token held in one of a user's wallets
+#[derive(Debug, Deserialize, PartialEq, Queryable, Serialize)]
+pub struct WalletToken {
+    pub ticket_instance_id: Uuid,
+    pub wallet_id: Uuid,
+    pub event_id: Uuid,
+    pub blockchain_asset_id: Option<String>,
+    pub token_id: i32,
+    pub status: TicketInstanceStatus,
 }
 
 #[derive(Serialize, Copy, Clone)]
@@ -48,6 +103,17 @@ impl Wallet {
             .to_db_error(errors::ErrorCode::QueryError, "Could not find wallet")
     }
 
+    pub fn find_by_public_key(
+        public_key: &str,
+        conn: &PgConnection,
+    ) -> Result<Option<Wallet>, DatabaseError> {
+        wallets::table
+            .filter(wallets::public_key.eq(public_key))
+            .first(conn)
+            .optional()
+            .to_db_error(errors::ErrorCode::QueryError, "Could not find wallet")
+    }
+
     pub fn create_for_user(
         user_id: Uuid,
         name: String,
@@ -139,6 +205,124 @@ impl Wallet {
         Ok(result_wallet)
     }
 
+    /// Wallet whose keys are held by Big Neon, used to sign on the user's behalf. Differs from the
+    /// default wallet once the user has registered their own key.
+    pub fn find_custodial_for_user(
+        user_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Wallet, DatabaseError> {
+        let mut wallets: Vec<Wallet> = Wallet::find_for_user(user_id, conn)?
+            .into_iter()
+            .filter(|w| !w.self_custodied)
+            .collect();
+        if wallets.is_empty() {
+            return Wallet::create_for_user(user_id, "Default".to_string(), false, conn);
+        }
+        let index = wallets.iter().position(|w| w.default_flag).unwrap_or(0);
+        Ok(wallets.remove(index))
+    }
+
+    /// Message the user signs with their own key to prove they hold it
+    pub fn self_custody_message(user_id: Uuid, public_key: &str) -> String {
+        format!("{}{}", user_id, public_key)
+    }
+
+    /// Registers a public key whose secret key is held by the user. The new wallet becomes the
+    /// default so future purchases and transfers land in it, while tickets already held stay in
+    /// the existing wallets.
+    pub fn register_self_custody_key(
+        user_id: Uuid,
+        public_key: String,
+        signature: String,
+        conn: &PgConnection,
+    ) -> Result<Wallet, DatabaseError> {
+        if !cryptographic_verify(
+            &convert_hexstring_to_bytes(&signature),
+            &Wallet::self_custody_message(user_id, &public_key),
+            &convert_hexstring_to_bytes(&public_key),
+        ) {
+            return DatabaseError::business_process_error(
+                "Signature does not match the public key",
+            );
+        }
+        if Wallet::find_by_public_key(&public_key, conn)?.is_some() {
+            return DatabaseError::business_process_error("Public key is already registered");
+        }
+
+        // Make sure tickets already held keep a custodial wallet to sign for them
+        Wallet::find_custodial_for_user(user_id, conn)?;
+        diesel::update(wallets::table.filter(wallets::user_id.eq(user_id)))
+            .set((
+                wallets::default_flag.eq(false),
+                wallets::updated_at.eq(dsl::now),
+            ))
+            .execute(conn)
+            .to_db_error(ErrorCode::UpdateError, "Could not update wallets")?;
+
+        NewWallet {
+            user_id: Some(user_id),
+            name: "Self custody".to_string(),
+            public_key,
+            default_flag: true,
+            self_custodied: true,
+            ..Default::default()
+        }
+        .commit(conn)
+    }
+
+    /// Exports the wallet keys, encrypting the secret key with `passphrase`
+    pub fn export(&self, passphrase: &str) -> Result<WalletExport, DatabaseError> {
+        if self.self_custodied {
+            return DatabaseError::business_process_error(
+                "Self-custodied wallet keys are not held by Big Neon",
+            );
+        }
+        if passphrase.chars().count() < MIN_EXPORT_PASSPHRASE_LENGTH {
+            return DatabaseError::business_process_error(&format!(
+                "Passphrase must be at least {} characters",
+                MIN_EXPORT_PASSPHRASE_LENGTH
+            ));
+        }
+        let salt = encryption::random_salt()?;
+        let iterations = encryption::PASSPHRASE_KEY_ITERATIONS;
+        Ok(WalletExport {
+            wallet_id: self.id,
+            public_key: self.public_key.clone(),
+            encrypted_secret_key: encryption::encrypt_with_passphrase(
+                &self.secret_key,
+                passphrase,
+                &salt,
+                iterations,
+            )?,
+            salt,
+            iterations,
+        })
+    }
+
+    /// Tokens held in the user's wallets along with their assets on the blockchain
+    pub fn tokens_for_user(
+        user_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<WalletToken>, DatabaseError> {
+        ticket_instances::table
+            .inner_join(wallets::table.on(ticket_instances::wallet_id.eq(wallets::id)))
+            .inner_join(assets::table.on(ticket_instances::asset_id.eq(assets::id)))
+            .inner_join(ticket_types::table.on(assets::ticket_type_id.eq(ticket_types::id)))
+            .filter(wallets::user_id.eq(user_id))
+            .select((
+                ticket_instances::id,
+                ticket_instances::wallet_id,
+                ticket_types::event_id,
+                assets::blockchain_asset_id,
+                ticket_instances::token_id,
+                ticket_instances::status,
+            ))
+            .order_by(ticket_types::event_id)
+            .then_order_by(ticket_instances::token_id)
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load wallet tokens")
+    }
+
     pub fn find_default_for_organization(
         organization_id: Uuid,
         conn: &PgConnection,
@@ -171,6 +355,7 @@ pub struct NewWallet {
     secret_key: String,
     public_key: String,
     default_flag: bool,
+    self_custodied: bool,
 }
 
 impl NewWallet {
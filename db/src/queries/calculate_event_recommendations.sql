@@ -0,0 +1,99 @@
+-- Events the user has shown interest in, bought tickets for or checked in to, with the weight of
+-- each signal
+WITH user_events AS (
+    SELECT ei.event_id, 1.0 AS weight
+    FROM event_interest ei
+    WHERE ei.user_id = $1
+    UNION ALL
+    SELECT tt.event_id, CASE WHEN ti.status = 'Redeemed' THEN 3.0 ELSE 2.0 END AS weight
+    FROM ticket_instances ti
+    JOIN wallets w ON w.id = ti.wallet_id
+    JOIN assets a ON a.id = ti.asset_id
+    JOIN ticket_types tt ON tt.id = a.ticket_type_id
+    WHERE w.user_id = $1
+    AND ti.status IN ('Purchased', 'Redeemed')
+),
+owned_events AS (
+    SELECT DISTINCT tt.event_id
+    FROM ticket_instances ti
+    JOIN wallets w ON w.id = ti.wallet_id
+    JOIN assets a ON a.id = ti.asset_id
+    JOIN ticket_types tt ON tt.id = a.ticket_type_id
+    WHERE w.user_id = $1
+    AND ti.status IN ('Purchased', 'Redeemed')
+),
+artist_affinity AS (
+    SELECT ea.artist_id, sum(ue.weight) AS affinity
+    FROM user_events ue
+    JOIN event_artists ea ON ea.event_id = ue.event_id
+    GROUP BY ea.artist_id
+),
+venue_affinity AS (
+    SELECT e.venue_id, sum(ue.weight) AS affinity
+    FROM user_events ue
+    JOIN events e ON e.id = ue.event_id
+    WHERE e.venue_id IS NOT NULL
+    GROUP BY e.venue_id
+),
+region_affinity AS (
+    SELECT v.region_id, sum(ue.weight) AS affinity
+    FROM user_events ue
+    JOIN events e ON e.id = ue.event_id
+    JOIN venues v ON v.id = e.venue_id
+    WHERE v.region_id IS NOT NULL
+    GROUP BY v.region_id
+),
+-- Fans who bought tickets to the same events as the user
+co_purchasers AS (
+    SELECT DISTINCT w.user_id
+    FROM owned_events oe
+    JOIN ticket_types tt ON tt.event_id = oe.event_id
+    JOIN assets a ON a.ticket_type_id = tt.id
+    JOIN ticket_instances ti ON ti.asset_id = a.id
+    JOIN wallets w ON w.id = ti.wallet_id
+    WHERE ti.status IN ('Purchased', 'Redeemed')
+    AND w.user_id <> $1
+),
+co_purchases AS (
+    SELECT tt.event_id, count(DISTINCT w.user_id) AS fan_count
+    FROM co_purchasers cp
+    JOIN wallets w ON w.user_id = cp.user_id
+    JOIN ticket_instances ti ON ti.wallet_id = w.id
+    JOIN assets a ON a.id = ti.asset_id
+    JOIN ticket_types tt ON tt.id = a.ticket_type_id
+    WHERE ti.status IN ('Purchased', 'Redeemed')
+    GROUP BY tt.event_id
+),
+scores AS (
+    SELECT
+        e.id AS event_id,
+        (
+            3 * coalesce((SELECT sum(aa.affinity)
+                          FROM event_artists ea
+                          JOIN artist_affinity aa ON aa.artist_id = ea.artist_id
+                          WHERE ea.event_id = e.id), 0)
+            + 2 * (SELECT count(*)
+                   FROM event_artists ea
+                   JOIN artists ar ON ar.id = ea.artist_id
+                   WHERE ea.event_id = e.id
+                   AND ar.spotify_id = ANY($2))
+            + coalesce((SELECT va.affinity FROM venue_affinity va WHERE va.venue_id = e.venue_id), 0)
+            + 0.5 * coalesce((SELECT ra.affinity
+                              FROM region_affinity ra
+                              JOIN venues v ON v.region_id = ra.region_id
+                              WHERE v.id = e.venue_id), 0)
+            + coalesce((SELECT cp.fan_count FROM co_purchases cp WHERE cp.event_id = e.id), 0)
+        )::FLOAT8 AS score
+    FROM events e
+    WHERE e.status = 'Published'
+    AND e.publish_date <= now()
+    AND e.event_start > now()
+    AND e.cancelled_at IS NULL
+    AND e.private_access_code IS NULL
+    AND e.id NOT IN (SELECT oe.event_id FROM owned_events oe)
+)
+SELECT s.event_id, s.score
+FROM scores s
+WHERE s.score > 0
+ORDER BY s.score DESC, s.event_id
+LIMIT $3;
@@ -0,0 +1,96 @@
+-- Fans of the organization matching the filters of a segment. Fans are users who bought tickets
+-- to, or showed interest in, one of the organization's events or who were tagged by its staff.
+-- Orders placed on behalf of a fan count towards that fan. Filters that are NULL are not applied.
+WITH fans AS (
+    SELECT COALESCE(o.on_behalf_of_user_id, o.user_id) AS user_id
+    FROM orders o
+    JOIN order_items oi ON oi.order_id = o.id
+    JOIN events e ON e.id = oi.event_id
+    WHERE o.status = 'Paid'
+    AND e.organization_id = $1
+    UNION
+    SELECT ei.user_id
+    FROM event_interest ei
+    JOIN events e ON e.id = ei.event_id
+    WHERE e.organization_id = $1
+    UNION
+    SELECT ft.user_id
+    FROM fan_tags ft
+    WHERE ft.organization_id = $1
+),
+order_stats AS (
+    SELECT
+        COALESCE(o.on_behalf_of_user_id, o.user_id) AS user_id,
+        count(DISTINCT o.id) AS order_count,
+        min(o.order_date) AS first_order_time,
+        max(o.order_date) AS last_order_time,
+        cast(sum(oi.unit_price_in_cents * (oi.quantity - oi.refunded_quantity)) AS BIGINT) AS revenue_in_cents
+    FROM orders o
+    JOIN order_items oi ON oi.order_id = o.id
+    JOIN events e ON e.id = oi.event_id
+    WHERE o.status = 'Paid'
+    AND e.organization_id = $1
+    GROUP BY COALESCE(o.on_behalf_of_user_id, o.user_id)
+),
+attendance AS (
+    SELECT w.user_id, count(DISTINCT tt.event_id) AS events_attended
+    FROM ticket_instances ti
+    JOIN wallets w ON w.id = ti.wallet_id
+    JOIN assets a ON a.id = ti.asset_id
+    JOIN ticket_types tt ON tt.id = a.ticket_type_id
+    JOIN events e ON e.id = tt.event_id
+    WHERE ti.status = 'Redeemed'
+    AND e.organization_id = $1
+    GROUP BY w.user_id
+)
+SELECT
+    u.id AS user_id,
+    u.first_name,
+    u.last_name,
+    u.email,
+    u.phone,
+    u.thumb_profile_pic_url,
+    u.created_at,
+    os.order_count,
+    os.first_order_time,
+    os.last_order_time,
+    os.revenue_in_cents,
+    count(*) OVER () AS total
+FROM fans f
+JOIN users u ON u.id = f.user_id
+LEFT JOIN order_stats os ON os.user_id = u.id
+LEFT JOIN attendance att ON att.user_id = u.id
+WHERE ($2 IS NULL OR coalesce(os.revenue_in_cents, 0) >= $2)
+AND ($3 IS NULL OR coalesce(att.events_attended, 0) >= $3)
+AND ($4 IS NULL OR EXISTS (
+    SELECT 1
+    FROM orders o
+    JOIN order_items oi ON oi.order_id = o.id
+    WHERE COALESCE(o.on_behalf_of_user_id, o.user_id) = u.id
+    AND o.status = 'Paid'
+    AND oi.ticket_type_id = $4
+    AND oi.quantity > oi.refunded_quantity
+))
+AND ($5 IS NULL OR (
+    EXISTS (SELECT 1 FROM event_interest ei WHERE ei.user_id = u.id AND ei.event_id = $5)
+    AND NOT EXISTS (
+        SELECT 1
+        FROM orders o
+        JOIN order_items oi ON oi.order_id = o.id
+        WHERE COALESCE(o.on_behalf_of_user_id, o.user_id) = u.id
+        AND o.status = 'Paid'
+        AND oi.event_id = $5
+        AND oi.item_type = 'Tickets'
+        AND oi.quantity > oi.refunded_quantity
+    )
+))
+AND ($6 IS NULL OR EXISTS (
+    SELECT 1
+    FROM fan_tags ft
+    WHERE ft.organization_id = $1
+    AND ft.user_id = u.id
+    AND lower(ft.name) = lower($6)
+))
+ORDER BY u.last_name, u.first_name, u.id
+LIMIT $7
+OFFSET $8;
@@ -0,0 +1,17 @@
+SELECT oi.code_id,
+       oi.hold_id,
+       date_trunc('day', o.paid_at)                                   AS date,
+       CAST(SUM(oi.quantity - oi.refunded_quantity) AS BIGINT)        AS tickets_sold,
+       CAST(COUNT(DISTINCT o.id) AS BIGINT)                           AS purchases
+FROM order_items oi
+       INNER JOIN orders o ON o.id = oi.order_id
+       INNER JOIN events e ON e.id = oi.event_id
+WHERE o.status = 'Paid'
+  AND oi.item_type = 'Tickets'
+  AND (oi.code_id IS NOT NULL OR oi.hold_id IS NOT NULL)
+  AND ($1 IS NULL OR oi.event_id = $1)
+  AND ($2 IS NULL OR e.organization_id = $2)
+  AND ($3 IS NULL OR o.paid_at >= $3)
+  AND ($4 IS NULL OR o.paid_at <= $4)
+GROUP BY oi.code_id, oi.hold_id, date_trunc('day', o.paid_at)
+ORDER BY date_trunc('day', o.paid_at), oi.code_id, oi.hold_id;
@@ -24,6 +24,7 @@ FROM orders
 WHERE orders.status = 'Paid'
   AND ($1 is null or oi.event_id = $1)
   AND ($2 is null or e.organization_id = $2)
+  AND ($5 is null or e.event_series_id = $5)
   AND oi.item_type = 'Tickets'
   AND ($3 IS NULL OR orders.paid_at >= $3)
   AND ($4 IS NULL OR orders.paid_at <= $4)
@@ -41,6 +41,7 @@ FROM (
          WHERE orders.status = 'Paid'
            AND ($1 IS NULL OR oi.event_id = $1)
            AND ($2 IS NULL OR e.organization_id = $2)
+           AND ($5 IS NULL OR e.event_series_id = $5)
            AND oi.item_type = 'Tickets'
            AND ($3 IS NULL OR orders.paid_at >= $3)
            AND ($4 IS NULL OR orders.paid_at <= $4)
@@ -30,6 +30,7 @@ table! {
         status -> Text,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        last_synced_at -> Nullable<Timestamp>,
     }
 }
 
@@ -46,6 +47,18 @@ table! {
         progress -> Int4,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        fan_segment_id -> Nullable<Uuid>,
+    }
+}
+
+table! {
+    code_applications (id) {
+        id -> Uuid,
+        code_id -> Nullable<Uuid>,
+        hold_id -> Nullable<Uuid>,
+        order_id -> Uuid,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
     }
 }
 
@@ -64,6 +77,11 @@ table! {
         created_at -> Timestamp,
         updated_at -> Timestamp,
         discount_as_percentage -> Nullable<Int8>,
+        min_quantity -> Nullable<Int8>,
+        buy_quantity -> Nullable<Int8>,
+        get_quantity -> Nullable<Int8>,
+        max_order_discount_in_cents -> Nullable<Int8>,
+        waive_fees -> Bool,
     }
 }
 
@@ -101,6 +119,18 @@ table! {
         created_at -> Timestamp,
         updated_at -> Timestamp,
         user_id -> Nullable<Uuid>,
+        organization_id -> Nullable<Uuid>,
+    }
+}
+
+table! {
+    email_suppressions (id) {
+        id -> Uuid,
+        email -> Text,
+        reason -> Text,
+        source -> Text,
+        details -> Nullable<Text>,
+        created_at -> Timestamp,
     }
 }
 
@@ -128,6 +158,40 @@ table! {
     }
 }
 
+table! {
+    event_recommendations (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        event_id -> Uuid,
+        score -> Float8,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    event_reschedules (id) {
+        id -> Uuid,
+        event_id -> Uuid,
+        previous_event_start -> Timestamp,
+        new_event_start -> Timestamp,
+        refund_window_ends_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+table! {
+    event_series (id) {
+        id -> Uuid,
+        organization_id -> Uuid,
+        template_event_id -> Uuid,
+        name -> Text,
+        recurrence_rule -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
 table! {
     events (id) {
         id -> Uuid,
@@ -159,6 +223,7 @@ table! {
         event_type -> Text,
         cover_image_url -> Nullable<Text>,
         private_access_code -> Nullable<Text>,
+        event_series_id -> Nullable<Uuid>,
     }
 }
 
@@ -174,6 +239,42 @@ table! {
     }
 }
 
+table! {
+    fan_notes (id) {
+        id -> Uuid,
+        organization_id -> Uuid,
+        user_id -> Uuid,
+        note -> Text,
+        created_by_user_id -> Uuid,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+table! {
+    fan_segments (id) {
+        id -> Uuid,
+        organization_id -> Uuid,
+        name -> Text,
+        filters -> Json,
+        sendgrid_list_id -> Nullable<Int8>,
+        created_by_user_id -> Uuid,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+table! {
+    fan_tags (id) {
+        id -> Uuid,
+        organization_id -> Uuid,
+        user_id -> Uuid,
+        name -> Text,
+        created_by_user_id -> Uuid,
+        created_at -> Timestamp,
+    }
+}
+
 table! {
     fee_schedule_ranges (id) {
         id -> Uuid,
@@ -198,6 +299,21 @@ table! {
     }
 }
 
+table! {
+    hold_releases (id) {
+        id -> Uuid,
+        hold_id -> Uuid,
+        release_at -> Timestamp,
+        quantity -> Nullable<Int8>,
+        status -> Text,
+        released_quantity -> Nullable<Int8>,
+        used_quantity -> Nullable<Int8>,
+        released_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
 table! {
     holds (id) {
         id -> Uuid,
@@ -218,6 +334,32 @@ table! {
     }
 }
 
+table! {
+    ledger_entries (id) {
+        id -> Uuid,
+        asset_id -> Text,
+        sequence -> Int8,
+        command -> Text,
+        payload -> Json,
+        public_key -> Text,
+        signature -> Text,
+        previous_signature -> Nullable<Text>,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    marketing_consents (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        organization_id -> Uuid,
+        consented -> Bool,
+        source -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
 table! {
     order_items (id) {
         id -> Uuid,
@@ -237,6 +379,7 @@ table! {
         company_fee_in_cents -> Int8,
         client_fee_in_cents -> Int8,
         refunded_quantity -> Int8,
+        unique_code_id -> Nullable<Uuid>,
     }
 }
 
@@ -263,6 +406,23 @@ table! {
     }
 }
 
+table! {
+    organization_email_settings (id) {
+        id -> Uuid,
+        organization_id -> Uuid,
+        from_email -> Nullable<Text>,
+        from_name -> Nullable<Text>,
+        logo_url -> Nullable<Text>,
+        primary_color -> Nullable<Text>,
+        secondary_color -> Nullable<Text>,
+        purchase_completed_template_id -> Nullable<Text>,
+        transfer_tickets_template_id -> Nullable<Text>,
+        org_invite_template_id -> Nullable<Text>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
 table! {
     organization_invites (id) {
         id -> Uuid,
@@ -304,6 +464,10 @@ table! {
         cc_fee_percent -> Float4,
         globee_api_key -> Nullable<Text>,
         max_instances_per_ticket_type -> Int8,
+        stripe_connect_account_id -> Nullable<Text>,
+        stripe_connect_enabled -> Bool,
+        refund_request_auto_approve_days -> Nullable<Int4>,
+        mailchimp_api_key -> Nullable<Text>,
     }
 }
 
@@ -360,6 +524,23 @@ table! {
     }
 }
 
+table! {
+    refund_requests (id) {
+        id -> Uuid,
+        order_id -> Uuid,
+        organization_id -> Uuid,
+        user_id -> Uuid,
+        ticket_instance_ids -> Array<Uuid>,
+        reason -> Text,
+        status -> Text,
+        reviewed_by_user_id -> Nullable<Uuid>,
+        reviewed_at -> Nullable<Timestamp>,
+        review_notes -> Nullable<Text>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
 table! {
     refunded_tickets (id) {
         id -> Uuid,
@@ -483,6 +664,17 @@ table! {
         cancelled_at -> Nullable<Timestamp>,
         sold_out_behavior -> Text,
         is_private -> Bool,
+        refundable -> Bool,
+    }
+}
+
+table! {
+    unique_codes (id) {
+        id -> Uuid,
+        code_id -> Uuid,
+        redemption_code -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
     }
 }
 
@@ -545,12 +737,17 @@ table! {
         default_flag -> Bool,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        self_custodied -> Bool,
     }
 }
 
 joinable!(artists -> organizations (organization_id));
 joinable!(assets -> ticket_types (ticket_type_id));
 joinable!(broadcasts -> events (event_id));
+joinable!(broadcasts -> fan_segments (fan_segment_id));
+joinable!(code_applications -> codes (code_id));
+joinable!(code_applications -> holds (hold_id));
+joinable!(code_applications -> orders (order_id));
 joinable!(codes -> events (event_id));
 joinable!(domain_actions -> domain_events (domain_event_id));
 joinable!(domain_events -> users (user_id));
@@ -559,12 +756,23 @@ joinable!(event_artists -> events (event_id));
 joinable!(event_artists -> stages (stage_id));
 joinable!(event_interest -> events (event_id));
 joinable!(event_interest -> users (user_id));
+joinable!(event_recommendations -> events (event_id));
+joinable!(event_recommendations -> users (user_id));
+joinable!(event_reschedules -> events (event_id));
+joinable!(event_series -> organizations (organization_id));
+joinable!(events -> event_series (event_series_id));
 joinable!(events -> organizations (organization_id));
 joinable!(events -> venues (venue_id));
 joinable!(external_logins -> users (user_id));
+joinable!(fan_notes -> organizations (organization_id));
+joinable!(fan_segments -> organizations (organization_id));
+joinable!(fan_tags -> organizations (organization_id));
 joinable!(fee_schedule_ranges -> fee_schedules (fee_schedule_id));
+joinable!(hold_releases -> holds (hold_id));
 joinable!(holds -> events (event_id));
 joinable!(holds -> ticket_types (ticket_type_id));
+joinable!(marketing_consents -> organizations (organization_id));
+joinable!(marketing_consents -> users (user_id));
 joinable!(order_items -> codes (code_id));
 joinable!(order_items -> events (event_id));
 joinable!(order_items -> fee_schedule_ranges (fee_schedule_range_id));
@@ -572,6 +780,8 @@ joinable!(order_items -> holds (hold_id));
 joinable!(order_items -> orders (order_id));
 joinable!(order_items -> ticket_pricing (ticket_pricing_id));
 joinable!(order_items -> ticket_types (ticket_type_id));
+joinable!(order_items -> unique_codes (unique_code_id));
+joinable!(organization_email_settings -> organizations (organization_id));
 joinable!(organization_invites -> organizations (organization_id));
 joinable!(organization_users -> organizations (organization_id));
 joinable!(organization_users -> users (user_id));
@@ -580,6 +790,9 @@ joinable!(payment_methods -> users (user_id));
 joinable!(payments -> orders (order_id));
 joinable!(payments -> users (created_by));
 joinable!(push_notification_tokens -> users (user_id));
+joinable!(refund_requests -> orders (order_id));
+joinable!(refund_requests -> organizations (organization_id));
+joinable!(refund_requests -> users (user_id));
 joinable!(refunded_tickets -> order_items (order_item_id));
 joinable!(refunded_tickets -> ticket_instances (ticket_instance_id));
 joinable!(settlement_transactions -> events (event_id));
@@ -594,6 +807,7 @@ joinable!(ticket_pricing -> ticket_types (ticket_type_id));
 joinable!(ticket_type_codes -> codes (code_id));
 joinable!(ticket_type_codes -> ticket_types (ticket_type_id));
 joinable!(ticket_types -> events (event_id));
+joinable!(unique_codes -> codes (code_id));
 joinable!(venues -> organizations (organization_id));
 joinable!(venues -> regions (region_id));
 joinable!(wallets -> organizations (organization_id));
@@ -603,24 +817,37 @@ allow_tables_to_appear_in_same_query!(
     artists,
     assets,
     broadcasts,
+    code_applications,
     codes,
     domain_actions,
     domain_events,
+    email_suppressions,
     event_artists,
     event_interest,
+    event_recommendations,
+    event_reschedules,
+    event_series,
     events,
     external_logins,
+    fan_notes,
+    fan_segments,
+    fan_tags,
     fee_schedule_ranges,
     fee_schedules,
+    hold_releases,
     holds,
+    ledger_entries,
+    marketing_consents,
     order_items,
     orders,
+    organization_email_settings,
     organization_invites,
     organizations,
     organization_users,
     payment_methods,
     payments,
     push_notification_tokens,
+    refund_requests,
     refunded_tickets,
     regions,
     settlements,
@@ -630,6 +857,7 @@ allow_tables_to_appear_in_same_query!(
     ticket_pricing,
     ticket_type_codes,
     ticket_types,
+    unique_codes,
     users,
     venues,
     wallets,
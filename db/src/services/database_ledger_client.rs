@@ -0,0 +1,225 @@
+use diesel::r2d2::{ConnectionManager, Pool, PooledConnection};
+use diesel::PgConnection;
+use models::*;
+use serde_json;
+use serde_json::Value;
+use tari_client::*;
+use utils::errors::DatabaseError;
+use uuid::Uuid;
+
+const LEDGER_POOL_SIZE: u32 = 2;
+
+/// Ledger backend for deployments without a blockchain. Operations are signed with the wallet
+/// keys and appended to the `ledger_entries` table outside of the caller's transaction, the same
+/// as calls to a blockchain.
+#[derive(Clone)]
+pub struct DatabaseLedgerClient {
+    connection_pool: Pool<ConnectionManager<PgConnection>>,
+}
+
+impl DatabaseLedgerClient {
+    pub fn new(database_url: String) -> DatabaseLedgerClient {
+        DatabaseLedgerClient {
+            connection_pool: Pool::builder()
+                .max_size(LEDGER_POOL_SIZE)
+                .build_unchecked(ConnectionManager::new(database_url)),
+        }
+    }
+
+    fn connection(&self) -> Result<PooledConnection<ConnectionManager<PgConnection>>, TariError> {
+        self.connection_pool.get().map_err(|e| TariError {
+            description: format!("Could not connect to ledger database: {}", e),
+            cause: None,
+        })
+    }
+
+    fn append(
+        &self,
+        asset_id: &str,
+        command: &str,
+        payload: Value,
+        secret_key: &String,
+        public_key: &String,
+    ) -> Result<(), TariError> {
+        let connection = self.connection()?;
+        LedgerEntry::append(
+            asset_id,
+            command,
+            payload,
+            secret_key,
+            public_key,
+            &*connection,
+        )
+        .map_err(ledger_error)?;
+        Ok(())
+    }
+}
+
+fn ledger_error(e: DatabaseError) -> TariError {
+    TariError {
+        description: format!("Ledger error: {}", e),
+        cause: None,
+    }
+}
+
+impl TariClient for DatabaseLedgerClient {
+    fn create_asset(
+        &self,
+        secret_key: &String,
+        public_key: &String,
+        asset: MessagePayloadCreateAsset,
+    ) -> Result<String, TariError> {
+        let asset_id = Uuid::new_v4().to_string();
+        self.append(
+            &asset_id,
+            LEDGER_CREATE_ASSET,
+            serde_json::to_value(asset)?,
+            secret_key,
+            public_key,
+        )?;
+        Ok(asset_id)
+    }
+
+    fn modify_asset_increase_supply(
+        &self,
+        secret_key: &String,
+        public_key: &String,
+        asset_id: &String,
+        new_supply: u64,
+    ) -> Result<(), TariError> {
+        let msg_payload = serde_json::to_value(MessagePayloadModifyAsset {
+            request_type: INCREASE_SUPPLY_REQUEST,
+            asset_id: asset_id.clone(),
+            authorised_signer: None,
+            token_ids: None,
+            token_metadata: None,
+            new_supply: Some(new_supply),
+        })?;
+        self.append(
+            asset_id,
+            LEDGER_MODIFY_ASSET,
+            msg_payload,
+            secret_key,
+            public_key,
+        )
+    }
+
+    fn modify_asset_nullify_tokens(
+        &self,
+        secret_key: &String,
+        public_key: &String,
+        asset_id: &String,
+        token_ids: Vec<u64>,
+    ) -> Result<(), TariError> {
+        let msg_payload = serde_json::to_value(MessagePayloadModifyAsset {
+            request_type: NULLIFY_TOKENS_REQUEST,
+            asset_id: asset_id.clone(),
+            authorised_signer: None,
+            token_ids: Some(token_ids),
+            token_metadata: None,
+            new_supply: None,
+        })?;
+        self.append(
+            asset_id,
+            LEDGER_MODIFY_ASSET,
+            msg_payload,
+            secret_key,
+            public_key,
+        )
+    }
+
+    fn modify_asset_redeem_token(
+        &self,
+        secret_key: &String,
+        public_key: &String,
+        asset_id: &String,
+        token_ids: Vec<u64>,
+    ) -> Result<(), TariError> {
+        let msg_payload = serde_json::to_value(MessagePayloadModifyAsset {
+            request_type: REDEEM_TOKENS_REQUEST,
+            asset_id: asset_id.clone(),
+            authorised_signer: None,
+            token_ids: Some(token_ids),
+            token_metadata: None,
+            new_supply: None,
+        })?;
+        self.append(
+            asset_id,
+            LEDGER_MODIFY_ASSET,
+            msg_payload,
+            secret_key,
+            public_key,
+        )
+    }
+
+    fn transfer_tokens(
+        &self,
+        secret_key: &String,
+        public_key: &String,
+        asset_id: &String,
+        token_ids: Vec<u64>,
+        new_owner: String,
+    ) -> Result<(), TariError> {
+        let msg_payload = serde_json::to_value(MessagePayloadTransferToken {
+            asset_id: asset_id.clone(),
+            token_ids,
+            new_owner,
+        })?;
+        self.append(
+            asset_id,
+            LEDGER_TRANSFER_TOKEN,
+            msg_payload,
+            secret_key,
+            public_key,
+        )
+    }
+
+    fn get_asset_info(
+        &self,
+        _secret_key: &String,
+        _public_key: &String,
+        asset_id: &String,
+    ) -> Result<ResponsePayloadReadAsset, TariError> {
+        let connection = self.connection()?;
+        let asset = LedgerAsset::load(asset_id, &*connection).map_err(ledger_error)?;
+        Ok(ResponsePayloadReadAsset {
+            id: asset.asset_id.clone(),
+            name: asset.name.clone(),
+            symbol: "".to_string(),
+            decimals: 0,
+            total_supply: asset.total_supply,
+            authorised_signers: asset.authorised_signers.clone(),
+            issuer: asset.issuer.clone(),
+            rule_flags: asset.rule_flags,
+            rule_metadata: asset.rule_metadata.clone(),
+            expired: asset.is_expired(),
+        })
+    }
+
+    fn get_tokens(
+        &self,
+        _secret_key: &String,
+        _public_key: &String,
+        asset_id: &String,
+        token_ids: Vec<u64>,
+    ) -> Result<Vec<Token>, TariError> {
+        let connection = self.connection()?;
+        let asset = LedgerAsset::load(asset_id, &*connection).map_err(ledger_error)?;
+        Ok(token_ids
+            .into_iter()
+            .filter(|id| *id < asset.total_supply)
+            .map(|id| Token {
+                id,
+                asset_id: asset.asset_id.clone(),
+                owner: asset.token_owner(id).to_string(),
+                used: asset.redeemed_token_ids.contains(&id),
+                valid: !asset.nullified_token_ids.contains(&id),
+                metadata: 0,
+            })
+            .collect())
+    }
+
+    fn box_clone(&self) -> Box<TariClient + Send + Sync> {
+        Box::new((*self).clone())
+    }
+}
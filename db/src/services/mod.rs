@@ -1,3 +1,5 @@
+mod database_ledger_client;
 mod domain_event_publisher;
 
+pub use self::database_ledger_client::*;
 pub use self::domain_event_publisher::*;
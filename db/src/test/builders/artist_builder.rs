@@ -9,6 +9,7 @@ pub struct ArtistBuilder<'a> {
     is_private: bool,
     bio: String,
     website_url: String,
+    spotify_id: Option<String>,
     connection: &'a PgConnection,
 }
 
@@ -19,6 +20,7 @@ impl<'a> ArtistBuilder<'a> {
             name: format!("Artist {}", x).into(),
             bio: "Bigraphy".into(),
             website_url: "http://www.example.com".into(),
+            spotify_id: None,
             connection,
             is_private: false,
             organization_id: None,
@@ -35,20 +37,25 @@ impl<'a> ArtistBuilder<'a> {
         self
     }
 
+    pub fn with_spotify_id(mut self, spotify_id: String) -> Self {
+        self.spotify_id = Some(spotify_id);
+        self
+    }
+
     pub fn make_private(mut self) -> Self {
         self.is_private = true;
         self
     }
 
     pub fn finish(&self) -> Artist {
-        let artist = Artist::create(
+        let mut artist = Artist::create(
             &self.name,
             self.organization_id,
             &self.bio,
             &self.website_url,
-        )
-        .commit(self.connection)
-        .unwrap();
+        );
+        artist.spotify_id = self.spotify_id.clone();
+        let artist = artist.commit(self.connection).unwrap();
         artist
             .set_privacy(self.is_private, self.connection)
             .unwrap()
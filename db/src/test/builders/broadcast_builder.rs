@@ -14,6 +14,7 @@ pub struct BroadcastBuilder<'a> {
     message: Option<String>,
     send_at: Option<NaiveDateTime>,
     status: BroadcastStatus,
+    fan_segment_id: Option<Uuid>,
     connection: &'a PgConnection,
 }
 
@@ -28,6 +29,7 @@ impl<'a> BroadcastBuilder<'a> {
             message: None,
             send_at: None,
             status: BroadcastStatus::Pending,
+            fan_segment_id: None,
             connection,
         }
     }
@@ -52,6 +54,11 @@ impl<'a> BroadcastBuilder<'a> {
         self
     }
 
+    pub fn with_fan_segment(mut self, fan_segment: &FanSegment) -> Self {
+        self.fan_segment_id = Some(fan_segment.id);
+        self
+    }
+
     pub fn with_event_id(mut self, event_id: Uuid) -> Self {
         self.event_id = Some(event_id);
         self
@@ -62,7 +69,7 @@ impl<'a> BroadcastBuilder<'a> {
             self.event_id = Some(EventBuilder::new(self.connection).finish().id);
         }
 
-        let broadcast = Broadcast::create(
+        let mut broadcast = Broadcast::create(
             self.event_id.unwrap(),
             self.notification_type,
             self.channel,
@@ -71,6 +78,7 @@ impl<'a> BroadcastBuilder<'a> {
             self.send_at,
             Some(self.status),
         );
+        broadcast.fan_segment_id = self.fan_segment_id;
 
         broadcast.commit(self.connection).unwrap()
     }
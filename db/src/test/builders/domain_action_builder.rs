@@ -45,6 +45,11 @@ impl<'a> DomainActionBuilder<'a> {
         self
     }
 
+    pub fn with_domain_action_type(mut self, domain_action_type: DomainActionTypes) -> Self {
+        self.domain_action_type = Some(domain_action_type);
+        self
+    }
+
     pub fn with_payload(mut self, payload: serde_json::Value) -> Self {
         self.payload = Some(payload);
         self
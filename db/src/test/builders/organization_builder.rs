@@ -14,6 +14,7 @@ pub struct OrganizationBuilder<'a> {
     company_fee_in_cents: Option<i64>,
     client_fee_in_cents: Option<i64>,
     use_address: bool,
+    sendgrid_api_key: Option<String>,
 }
 
 impl<'a> OrganizationBuilder<'a> {
@@ -28,6 +29,7 @@ impl<'a> OrganizationBuilder<'a> {
             event_fee_in_cents: None,
             company_fee_in_cents: None,
             client_fee_in_cents: None,
+            sendgrid_api_key: None,
         }
     }
 
@@ -58,6 +60,11 @@ impl<'a> OrganizationBuilder<'a> {
         self
     }
 
+    pub fn with_sendgrid_api_key(mut self, sendgrid_api_key: &str) -> Self {
+        self.sendgrid_api_key = Some(sendgrid_api_key.to_string());
+        self
+    }
+
     pub fn finish(mut self) -> Organization {
         let members = self.members.clone();
         let current_user_id = match members.iter().find(|(_, v)| *v.clone() == Roles::OrgOwner) {
@@ -80,7 +87,9 @@ impl<'a> OrganizationBuilder<'a> {
             self.fee_schedule = Some(fee_schedule.unwrap());
         }
 
-        let mut organization = Organization::create(&self.name, self.fee_schedule.unwrap().id)
+        let mut new_organization = Organization::create(&self.name, self.fee_schedule.unwrap().id);
+        new_organization.sendgrid_api_key = self.sendgrid_api_key.clone();
+        let mut organization = new_organization
             .commit("encryption_key", current_user_id, self.connection)
             .unwrap();
 
@@ -92,6 +101,7 @@ impl<'a> OrganizationBuilder<'a> {
 
         let _ = organization
             .update(
+                Some(current_user_id),
                 event_fee_update,
                 &"encryption_key".to_string(),
                 self.connection,
@@ -122,7 +132,12 @@ impl<'a> OrganizationBuilder<'a> {
             attrs.phone = Some(<String>::from("+27123456789"));
 
             organization = organization
-                .update(attrs, &"encryption_key".to_string(), self.connection)
+                .update(
+                    Some(current_user_id),
+                    attrs,
+                    &"encryption_key".to_string(),
+                    self.connection,
+                )
                 .unwrap();
         }
         organization
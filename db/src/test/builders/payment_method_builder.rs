@@ -1,5 +1,6 @@
 use diesel::prelude::*;
 use models::*;
+use serde_json;
 use test::builders::UserBuilder;
 use uuid::Uuid;
 
@@ -7,6 +8,7 @@ pub struct PaymentMethodBuilder<'a> {
     name: PaymentProviders,
     user_id: Option<Uuid>,
     is_default: bool,
+    provider_data: serde_json::Value,
     connection: &'a PgConnection,
 }
 
@@ -16,6 +18,7 @@ impl<'a> PaymentMethodBuilder<'a> {
             name: PaymentProviders::Stripe,
             user_id: None,
             is_default: false,
+            provider_data: "abc".into(),
             connection,
         }
     }
@@ -35,6 +38,11 @@ impl<'a> PaymentMethodBuilder<'a> {
         self
     }
 
+    pub fn with_provider_data(mut self, provider_data: serde_json::Value) -> Self {
+        self.provider_data = provider_data;
+        self
+    }
+
     pub fn finish(mut self) -> PaymentMethod {
         if self.user_id.is_none() {
             let user = UserBuilder::new(self.connection).finish();
@@ -48,7 +56,7 @@ impl<'a> PaymentMethodBuilder<'a> {
             self.name,
             self.is_default,
             "cus_example".into(),
-            "abc".into(),
+            self.provider_data,
         )
         .commit(user_id, self.connection)
         .unwrap()
@@ -8,6 +8,8 @@ pub struct VenueBuilder<'a> {
     organization_id: Option<Uuid>,
     is_private: bool,
     timezone: String,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
     connection: &'a PgConnection,
 }
 
@@ -20,6 +22,8 @@ impl<'a> VenueBuilder<'a> {
             is_private: false,
             organization_id: None,
             timezone: "America/Los_Angeles".into(),
+            latitude: None,
+            longitude: None,
         }
     }
 
@@ -48,15 +52,22 @@ impl<'a> VenueBuilder<'a> {
         self
     }
 
+    pub fn with_coordinates(mut self, latitude: f64, longitude: f64) -> Self {
+        self.latitude = Some(latitude);
+        self.longitude = Some(longitude);
+        self
+    }
+
     pub fn finish(self) -> Venue {
-        let venue = Venue::create(
+        let mut venue = Venue::create(
             &self.name,
             self.region_id,
             self.organization_id,
             self.timezone,
-        )
-        .commit(self.connection)
-        .unwrap();
+        );
+        venue.latitude = self.latitude;
+        venue.longitude = self.longitude;
+        let venue = venue.commit(self.connection).unwrap();
         venue.set_privacy(self.is_private, self.connection).unwrap()
     }
 }
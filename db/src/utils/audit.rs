@@ -0,0 +1,75 @@
+use serde::Serialize;
+use serde_json::{self, Map, Value};
+
+const REDACTED: &'static str = "[redacted]";
+
+/// Fields that differ between two versions of a record, as
+/// `{"field": {"before": .., "after": ..}}`. Values of `redacted_fields` are masked so that
+/// secrets do not end up in the audit log, only whether they were set is kept.
+pub fn changes<T: Serialize>(before: &T, after: &T, redacted_fields: &[&str]) -> Value {
+    let mut changes = Map::new();
+    if let (Ok(Value::Object(before)), Ok(Value::Object(after))) =
+        (serde_json::to_value(before), serde_json::to_value(after))
+    {
+        for (field, after_value) in after {
+            if field == "updated_at" {
+                continue;
+            }
+            let before_value = before.get(&field).cloned().unwrap_or(Value::Null);
+            if before_value == after_value {
+                continue;
+            }
+
+            let (before_value, after_value) = if redacted_fields.contains(&field.as_str()) {
+                (redact(before_value), redact(after_value))
+            } else {
+                (before_value, after_value)
+            };
+            changes.insert(
+                field,
+                json!({ "before": before_value, "after": after_value }),
+            );
+        }
+    }
+    Value::Object(changes)
+}
+
+fn redact(value: Value) -> Value {
+    match value {
+        Value::Null => Value::Null,
+        _ => REDACTED.into(),
+    }
+}
+
+#[test]
+fn changes_lists_modified_fields() {
+    #[derive(Serialize)]
+    struct Record {
+        name: String,
+        api_key: Option<String>,
+        count: i64,
+        updated_at: i64,
+    }
+
+    let before = Record {
+        name: "Before".to_string(),
+        api_key: None,
+        count: 1,
+        updated_at: 1,
+    };
+    let after = Record {
+        name: "After".to_string(),
+        api_key: Some("secret".to_string()),
+        count: 1,
+        updated_at: 2,
+    };
+
+    assert_eq!(
+        changes(&before, &after, &["api_key"]),
+        json!({
+            "name": { "before": "Before", "after": "After" },
+            "api_key": { "before": null, "after": "[redacted]" }
+        })
+    );
+    assert_eq!(changes(&before, &before, &[]), json!({}));
+}
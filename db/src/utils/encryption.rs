@@ -1,18 +1,80 @@
 use hex;
 use ring::aead::*;
+use ring::digest;
+use ring::pbkdf2;
 use ring::rand::{SecureRandom, SystemRandom};
 use utils::errors::*;
 
+/// PBKDF2 iterations used to derive keys from passphrases
+pub const PASSPHRASE_KEY_ITERATIONS: u32 = 100_000;
+const PASSPHRASE_SALT_LENGTH: usize = 16;
+const KEY_LENGTH: usize = 32;
+
 pub fn encrypt(plaintext: &str, encryption_key: &str) -> Result<String, DatabaseError> {
+    seal(plaintext, &pad_key(encryption_key))
+}
+
+pub fn decrypt(ciphertext: &str, encryption_key: &str) -> Result<String, DatabaseError> {
+    open(ciphertext, &pad_key(encryption_key))
+}
+
+/// Generates a random salt for `encrypt_with_passphrase`, hex encoded
+pub fn random_salt() -> Result<String, DatabaseError> {
+    let mut salt = vec![0; PASSPHRASE_SALT_LENGTH];
+    SystemRandom::new().fill(&mut salt)?;
+    Ok(hex::encode(salt))
+}
+
+/// Encrypts with a key derived from `passphrase` using PBKDF2-HMAC-SHA256, for secrets handed
+/// to users rather than stored by Big Neon
+pub fn encrypt_with_passphrase(
+    plaintext: &str,
+    passphrase: &str,
+    salt: &str,
+    iterations: u32,
+) -> Result<String, DatabaseError> {
+    seal(plaintext, &passphrase_key(passphrase, salt, iterations)?)
+}
+
+pub fn decrypt_with_passphrase(
+    ciphertext: &str,
+    passphrase: &str,
+    salt: &str,
+    iterations: u32,
+) -> Result<String, DatabaseError> {
+    open(ciphertext, &passphrase_key(passphrase, salt, iterations)?)
+}
+
+fn passphrase_key(passphrase: &str, salt: &str, iterations: u32) -> Result<Vec<u8>, DatabaseError> {
+    let salt = hex::decode(salt).map_err(|_| decrypt_error())?;
+    if iterations == 0 {
+        return Err(decrypt_error());
+    }
+    let mut key = vec![0; KEY_LENGTH];
+    pbkdf2::derive(
+        &digest::SHA256,
+        iterations,
+        &salt,
+        passphrase.as_bytes(),
+        &mut key,
+    );
+    Ok(key)
+}
+
+fn pad_key(encryption_key: &str) -> Vec<u8> {
     let mut key = encryption_key.to_string().into_bytes();
-    if key.len() > 32 {
-        key.truncate(32);
+    if key.len() > KEY_LENGTH {
+        key.truncate(KEY_LENGTH);
     }
     //Pad the key
-    for _ in key.len()..32 {
+    for _ in key.len()..KEY_LENGTH {
         key.push(0);
     }
-    let sealing_key = SealingKey::new(&CHACHA20_POLY1305, &key)?;
+    key
+}
+
+fn seal(plaintext: &str, key: &[u8]) -> Result<String, DatabaseError> {
+    let sealing_key = SealingKey::new(&CHACHA20_POLY1305, key)?;
     let mut nonce = vec![0; 12];
     let rng = SystemRandom::new();
     rng.fill(&mut nonce)?;
@@ -37,22 +99,11 @@ pub fn encrypt(plaintext: &str, encryption_key: &str) -> Result<String, Database
     Ok(nonce_data)
 }
 
-pub fn decrypt(ciphertext: &str, encryption_key: &str) -> Result<String, DatabaseError> {
-    let mut key = encryption_key.to_string().into_bytes();
-    if key.len() > 32 {
-        key.truncate(32);
-    }
-    for _ in key.len()..32 {
-        key.push(0);
-    }
-
-    let opening_key = OpeningKey::new(&CHACHA20_POLY1305, &key)?;
+fn open(ciphertext: &str, key: &[u8]) -> Result<String, DatabaseError> {
+    let opening_key = OpeningKey::new(&CHACHA20_POLY1305, key)?;
     //check that the data is long enough to contain a nonce
     if ciphertext.len() < 24 {
-        return Err(DatabaseError::new(
-            ErrorCode::InternalError,
-            Some("Cannot decrypt data".to_string()),
-        ));
+        return Err(decrypt_error());
     }
 
     //split up nonce and data
@@ -61,24 +112,20 @@ pub fn decrypt(ciphertext: &str, encryption_key: &str) -> Result<String, Databas
     let new_data = hex::decode(d);
 
     if new_nonce.is_err() || new_data.is_err() {
-        return Err(DatabaseError::new(
-            ErrorCode::InternalError,
-            Some("Cannot decrypt data".to_string()),
-        ));
+        return Err(decrypt_error());
     }
 
     let mut in_out = new_data.unwrap();
-    let decrypted_data =
-        open_in_place(&opening_key, &new_nonce.unwrap(), &[], 0, &mut in_out).unwrap();
+    let decrypted_data = open_in_place(&opening_key, &new_nonce.unwrap(), &[], 0, &mut in_out)
+        .map_err(|_| decrypt_error())?;
 
-    let plaintext = String::from_utf8(decrypted_data.to_vec());
     //Doing this rather that implement a From for just this once instance
-    if plaintext.is_err() {
-        return Err(DatabaseError::new(
-            ErrorCode::InternalError,
-            Some("Cannot decrypt data".to_string()),
-        ));
-    }
+    String::from_utf8(decrypted_data.to_vec()).map_err(|_| decrypt_error())
+}
 
-    Ok(plaintext.unwrap())
+fn decrypt_error() -> DatabaseError {
+    DatabaseError::new(
+        ErrorCode::InternalError,
+        Some("Cannot decrypt data".to_string()),
+    )
 }
@@ -5,6 +5,7 @@ use diesel::result::Error as DieselError;
 use diesel::result::QueryResult;
 use log::Level;
 use serde::ser::{Serialize, SerializeStruct, Serializer};
+use serde_json;
 use std::collections::HashMap;
 use std::error::Error;
 use std::fmt;
@@ -256,6 +257,12 @@ impl From<EnumParseError> for DatabaseError {
     }
 }
 
+impl From<serde_json::Error> for DatabaseError {
+    fn from(e: serde_json::Error) -> Self {
+        DatabaseError::new(ErrorCode::ParseError, Some(e.to_string()))
+    }
+}
+
 impl From<TariError> for DatabaseError {
     fn from(e: TariError) -> Self {
         DatabaseError::new(ErrorCode::InternalError, Some(e.to_string()))
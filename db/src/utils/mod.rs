@@ -1,3 +1,4 @@
+pub mod audit;
 pub mod dates;
 pub mod encryption;
 pub mod errors;
@@ -7,6 +8,7 @@ mod math;
 pub mod migration;
 pub mod passwords;
 pub mod rand;
+pub mod recurrence;
 pub mod text;
 
 pub use self::math::*;
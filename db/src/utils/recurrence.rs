@@ -0,0 +1,236 @@
+use chrono::prelude::*;
+use chrono::Duration;
+use std::str::FromStr;
+use utils::errors::DatabaseError;
+
+/// Upper bound on the number of occurrences a single rule can generate
+pub const MAX_OCCURRENCES: usize = 365;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RecurrenceFrequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// A subset of the iCalendar RRULE format, e.g. `FREQ=WEEKLY;INTERVAL=2;BYDAY=FR,SA;COUNT=10`.
+/// Supports the `FREQ`, `INTERVAL`, `BYDAY` (weekly only), `COUNT` and `UNTIL` (`YYYYMMDD`)
+/// parts. Either `COUNT` or `UNTIL` is required so that a rule always ends.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecurrenceRule {
+    pub frequency: RecurrenceFrequency,
+    pub interval: u32,
+    pub by_day: Vec<Weekday>,
+    pub count: Option<u32>,
+    pub until: Option<NaiveDate>,
+}
+
+impl RecurrenceRule {
+    /// Returns the occurrences of the rule starting at `start`, which counts as the first
+    /// occurrence. Times are kept as given, so pass in local times to recur in a timezone.
+    pub fn occurrences(&self, start: NaiveDateTime) -> Vec<NaiveDateTime> {
+        let limit = self
+            .count
+            .map(|c| c as usize)
+            .unwrap_or(MAX_OCCURRENCES)
+            .min(MAX_OCCURRENCES);
+        let time = start.time();
+        let start_date = start.date();
+        let mut result = Vec::new();
+
+        let mut period: i64 = 0;
+        while result.len() < limit && period < MAX_OCCURRENCES as i64 * 12 {
+            let step = period * self.interval as i64;
+            let dates = match self.frequency {
+                RecurrenceFrequency::Daily => vec![start_date + Duration::days(step)],
+                RecurrenceFrequency::Weekly => {
+                    let week_start = start_date
+                        - Duration::days(start_date.weekday().num_days_from_monday() as i64)
+                        + Duration::weeks(step);
+                    let mut days = if self.by_day.is_empty() {
+                        vec![start_date.weekday()]
+                    } else {
+                        self.by_day.clone()
+                    };
+                    days.sort_by_key(|d| d.num_days_from_monday());
+                    days.into_iter()
+                        .map(|d| week_start + Duration::days(d.num_days_from_monday() as i64))
+                        .filter(|d| *d >= start_date)
+                        .collect()
+                }
+                RecurrenceFrequency::Monthly => {
+                    let months = start_date.month0() as i64 + step;
+                    // Months without the day (e.g. the 31st) are skipped
+                    NaiveDate::from_ymd_opt(
+                        start_date.year() + (months / 12) as i32,
+                        (months % 12) as u32 + 1,
+                        start_date.day(),
+                    )
+                    .into_iter()
+                    .collect()
+                }
+            };
+
+            for date in dates {
+                if self.until.map(|until| date > until).unwrap_or(false) {
+                    return result;
+                }
+                if result.len() < limit {
+                    result.push(date.and_time(time));
+                }
+            }
+            period += 1;
+        }
+
+        result
+    }
+}
+
+impl FromStr for RecurrenceRule {
+    type Err = DatabaseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut frequency = None;
+        let mut interval = 1;
+        let mut by_day = Vec::new();
+        let mut count = None;
+        let mut until = None;
+
+        for part in s.trim().trim_start_matches("RRULE:").split(';') {
+            if part.is_empty() {
+                continue;
+            }
+            let mut pair = part.splitn(2, '=');
+            let key = pair.next().unwrap_or("").trim().to_uppercase();
+            let value = pair.next().unwrap_or("").trim().to_uppercase();
+            match key.as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.as_str() {
+                        "DAILY" => RecurrenceFrequency::Daily,
+                        "WEEKLY" => RecurrenceFrequency::Weekly,
+                        "MONTHLY" => RecurrenceFrequency::Monthly,
+                        _ => return invalid("Frequency must be DAILY, WEEKLY or MONTHLY"),
+                    })
+                }
+                "INTERVAL" => match value.parse::<u32>() {
+                    Ok(i) if i > 0 => interval = i,
+                    _ => return invalid("Interval must be a positive number"),
+                },
+                "BYDAY" => {
+                    for day in value.split(',') {
+                        by_day.push(match day {
+                            "MO" => Weekday::Mon,
+                            "TU" => Weekday::Tue,
+                            "WE" => Weekday::Wed,
+                            "TH" => Weekday::Thu,
+                            "FR" => Weekday::Fri,
+                            "SA" => Weekday::Sat,
+                            "SU" => Weekday::Sun,
+                            _ => return invalid("Days must be one of MO,TU,WE,TH,FR,SA,SU"),
+                        });
+                    }
+                }
+                "COUNT" => match value.parse::<u32>() {
+                    Ok(c) if c > 0 => count = Some(c),
+                    _ => return invalid("Count must be a positive number"),
+                },
+                "UNTIL" => {
+                    match NaiveDate::parse_from_str(&value[..value.len().min(8)], "%Y%m%d") {
+                        Ok(d) => until = Some(d),
+                        _ => return invalid("Until must be a date in the format YYYYMMDD"),
+                    }
+                }
+                _ => return invalid("Unsupported recurrence rule part"),
+            }
+        }
+
+        let frequency = match frequency {
+            Some(f) => f,
+            None => return invalid("Frequency is required"),
+        };
+        if count.is_none() && until.is_none() {
+            return invalid("Either a count or an until date is required");
+        }
+        if !by_day.is_empty() && frequency != RecurrenceFrequency::Weekly {
+            return invalid("Days can only be specified for weekly recurrence");
+        }
+
+        Ok(RecurrenceRule {
+            frequency,
+            interval,
+            by_day,
+            count,
+            until,
+        })
+    }
+}
+
+fn invalid(message: &'static str) -> Result<RecurrenceRule, DatabaseError> {
+    DatabaseError::validation_error("recurrence_rule", message)
+}
+
+#[test]
+fn parse() {
+    let rule: RecurrenceRule = "FREQ=WEEKLY;INTERVAL=2;BYDAY=FR,SA;COUNT=10"
+        .parse()
+        .unwrap();
+    assert_eq!(rule.frequency, RecurrenceFrequency::Weekly);
+    assert_eq!(rule.interval, 2);
+    assert_eq!(rule.by_day, vec![Weekday::Fri, Weekday::Sat]);
+    assert_eq!(rule.count, Some(10));
+    assert_eq!(rule.until, None);
+
+    let rule: RecurrenceRule = "RRULE:FREQ=DAILY;UNTIL=20190405T000000Z".parse().unwrap();
+    assert_eq!(rule.frequency, RecurrenceFrequency::Daily);
+    assert_eq!(rule.until, Some(NaiveDate::from_ymd(2019, 4, 5)));
+
+    assert!("FREQ=WEEKLY".parse::<RecurrenceRule>().is_err());
+    assert!("FREQ=YEARLY;COUNT=2".parse::<RecurrenceRule>().is_err());
+    assert!("FREQ=DAILY;BYDAY=MO;COUNT=2"
+        .parse::<RecurrenceRule>()
+        .is_err());
+    assert!("FREQ=DAILY;INTERVAL=0;COUNT=2"
+        .parse::<RecurrenceRule>()
+        .is_err());
+}
+
+#[test]
+fn occurrences() {
+    // Friday 1 March 2019
+    let start = NaiveDate::from_ymd(2019, 3, 1).and_hms(20, 0, 0);
+
+    let rule: RecurrenceRule = "FREQ=WEEKLY;BYDAY=FR,SA;COUNT=4".parse().unwrap();
+    assert_eq!(
+        rule.occurrences(start),
+        vec![
+            NaiveDate::from_ymd(2019, 3, 1).and_hms(20, 0, 0),
+            NaiveDate::from_ymd(2019, 3, 2).and_hms(20, 0, 0),
+            NaiveDate::from_ymd(2019, 3, 8).and_hms(20, 0, 0),
+            NaiveDate::from_ymd(2019, 3, 9).and_hms(20, 0, 0),
+        ]
+    );
+
+    let rule: RecurrenceRule = "FREQ=DAILY;INTERVAL=3;UNTIL=20190307".parse().unwrap();
+    assert_eq!(
+        rule.occurrences(start),
+        vec![
+            NaiveDate::from_ymd(2019, 3, 1).and_hms(20, 0, 0),
+            NaiveDate::from_ymd(2019, 3, 4).and_hms(20, 0, 0),
+            NaiveDate::from_ymd(2019, 3, 7).and_hms(20, 0, 0),
+        ]
+    );
+
+    let start = NaiveDate::from_ymd(2019, 1, 31).and_hms(19, 30, 0);
+    let rule: RecurrenceRule = "FREQ=MONTHLY;COUNT=3".parse().unwrap();
+    assert_eq!(
+        rule.occurrences(start),
+        vec![
+            NaiveDate::from_ymd(2019, 1, 31).and_hms(19, 30, 0),
+            NaiveDate::from_ymd(2019, 3, 31).and_hms(19, 30, 0),
+            NaiveDate::from_ymd(2019, 5, 31).and_hms(19, 30, 0),
+        ]
+    );
+
+    let rule: RecurrenceRule = "FREQ=DAILY;COUNT=1000".parse().unwrap();
+    assert_eq!(rule.occurrences(start).len(), MAX_OCCURRENCES);
+}
@@ -14,6 +14,7 @@ extern crate diesel;
 extern crate rand;
 #[macro_use]
 extern crate serde_json;
+extern crate tari_client;
 extern crate time;
 extern crate uuid;
 extern crate validator;
@@ -2,7 +2,7 @@ use bigneon_db::dev::TestProject;
 use bigneon_db::prelude::*;
 use bigneon_db::schema::ticket_instances;
 
-use chrono::NaiveDate;
+use chrono::prelude::*;
 use diesel::prelude::*;
 use uuid::Uuid;
 
@@ -76,3 +76,189 @@ fn asset_find() {
 
     assert_eq!(asset.id, ticket_instance.asset_id);
 }
+
+#[test]
+fn queue_blockchain_sync() {
+    let project = TestProject::new();
+    let conn = project.get_connection();
+    let event = project.create_event().with_ticket_pricing().finish();
+    let ticket_type = &event.ticket_types(true, None, conn).unwrap()[0];
+    let wallet_id = event.issuer_wallet(conn).unwrap().id;
+    let asset = Asset::find_by_ticket_type(ticket_type.id, conn).unwrap();
+
+    let operation = BlockchainOperation::IncreaseSupply {
+        wallet_id,
+        total_supply: 110,
+    };
+    let action = asset.queue_blockchain_sync(operation, conn).unwrap();
+    assert_eq!(action.domain_action_type, DomainActionTypes::BlockchainSync);
+    assert_eq!(action.main_table, Some(Tables::Assets.to_string()));
+    assert_eq!(action.main_table_id, Some(asset.id));
+    assert_eq!(
+        action.payload,
+        json!({"operation": "IncreaseSupply", "wallet_id": wallet_id, "total_supply": 110})
+    );
+    assert_eq!(
+        Asset::find(asset.id, conn).unwrap().status,
+        AssetStatus::Pending
+    );
+    assert_eq!(asset.blockchain_syncs(conn).unwrap(), vec![action]);
+}
+
+#[test]
+fn retry_failed_blockchain_syncs() {
+    let project = TestProject::new();
+    let conn = project.get_connection();
+    let event = project.create_event().with_ticket_pricing().finish();
+    let ticket_type = &event.ticket_types(true, None, conn).unwrap()[0];
+    let wallet_id = event.issuer_wallet(conn).unwrap().id;
+    let asset = Asset::find_by_ticket_type(ticket_type.id, conn).unwrap();
+    let action = asset
+        .queue_blockchain_sync(
+            BlockchainOperation::RedeemTokens {
+                wallet_id,
+                token_ids: vec![1],
+            },
+            conn,
+        )
+        .unwrap();
+    let asset = Asset::find(asset.id, conn).unwrap();
+
+    // Nothing to retry while the sync is queued
+    assert_eq!(asset.retry_failed_blockchain_syncs(conn).unwrap(), 0);
+
+    action.set_errored("Blockchain unavailable", conn).unwrap();
+    let asset = asset.update_status(AssetStatus::Failed, conn).unwrap();
+    assert_eq!(asset.retry_failed_blockchain_syncs(conn).unwrap(), 1);
+
+    let action = DomainAction::find(action.id, conn).unwrap();
+    assert_eq!(action.status, DomainActionStatus::Pending);
+    assert_eq!(action.attempt_count, 0);
+    assert!(action.expires_at > Utc::now().naive_utc());
+    assert_eq!(
+        Asset::find(asset.id, conn).unwrap().status,
+        AssetStatus::Pending
+    );
+}
+
+#[test]
+fn update_status() {
+    let project = TestProject::new();
+    let conn = project.get_connection();
+    let event = project.create_event().with_ticket_pricing().finish();
+    let ticket_type = &event.ticket_types(true, None, conn).unwrap()[0];
+    let asset = Asset::find_by_ticket_type(ticket_type.id, conn).unwrap();
+
+    let asset = asset.update_status(AssetStatus::Failed, conn).unwrap();
+    assert_eq!(asset.status, AssetStatus::Failed);
+    assert_eq!(asset.last_synced_at, None);
+
+    let asset = asset.update_status(AssetStatus::Synced, conn).unwrap();
+    assert_eq!(asset.status, AssetStatus::Synced);
+    assert!(asset.last_synced_at.is_some());
+    assert!(Asset::find_unsynced(conn)
+        .unwrap()
+        .iter()
+        .all(|a| a.id != asset.id));
+
+    // Last synced time is kept while further syncs are pending
+    let last_synced_at = asset.last_synced_at;
+    let asset = asset.update_status(AssetStatus::Pending, conn).unwrap();
+    assert_eq!(asset.last_synced_at, last_synced_at);
+    assert!(Asset::find_unsynced(conn)
+        .unwrap()
+        .iter()
+        .any(|a| a.id == asset.id));
+}
+
+#[test]
+fn find_synced() {
+    let project = TestProject::new();
+    let conn = project.get_connection();
+    let event = project.create_event().with_ticket_pricing().finish();
+    let ticket_type = &event.ticket_types(true, None, conn).unwrap()[0];
+    let asset = Asset::find_by_ticket_type(ticket_type.id, conn).unwrap();
+
+    // Assets only count as synced once they exist on the blockchain
+    let asset = asset.update_status(AssetStatus::Synced, conn).unwrap();
+    assert!(Asset::find_synced(conn)
+        .unwrap()
+        .iter()
+        .all(|a| a.id != asset.id));
+
+    let asset = asset
+        .update_blockchain_id("asset-1".to_string(), conn)
+        .unwrap();
+    assert!(Asset::find_synced(conn)
+        .unwrap()
+        .iter()
+        .any(|a| a.id == asset.id));
+}
+
+#[test]
+fn token_wallets() {
+    let project = TestProject::new();
+    let conn = project.get_connection();
+    let event = project.create_event().with_ticket_pricing().finish();
+    let ticket_type = &event.ticket_types(true, None, conn).unwrap()[0];
+    let wallet = event.issuer_wallet(conn).unwrap();
+    let asset = Asset::find_by_ticket_type(ticket_type.id, conn).unwrap();
+
+    let token_wallets = asset.token_wallets(conn).unwrap();
+    let ticket_count: i64 = ticket_instances::table
+        .filter(ticket_instances::asset_id.eq(asset.id))
+        .count()
+        .get_result(conn)
+        .unwrap();
+    assert_eq!(token_wallets.len() as i64, ticket_count);
+    assert!(token_wallets.iter().all(|(_, w)| w.id == wallet.id));
+    assert!(token_wallets.windows(2).all(|w| w[0].0 < w[1].0));
+}
+
+#[test]
+fn sync_summaries() {
+    let project = TestProject::new();
+    let conn = project.get_connection();
+    let event = project.create_event().with_ticket_pricing().finish();
+    let other_event = project.create_event().with_ticket_pricing().finish();
+    let ticket_type = &event.ticket_types(true, None, conn).unwrap()[0];
+    let wallet_id = event.issuer_wallet(conn).unwrap().id;
+    let asset = Asset::find_by_ticket_type(ticket_type.id, conn).unwrap();
+    let action = asset
+        .queue_blockchain_sync(
+            BlockchainOperation::NullifyTokens {
+                wallet_id,
+                token_ids: vec![1, 2],
+            },
+            conn,
+        )
+        .unwrap();
+    asset
+        .queue_blockchain_sync(
+            BlockchainOperation::RedeemTokens {
+                wallet_id,
+                token_ids: vec![3],
+            },
+            conn,
+        )
+        .unwrap();
+    action.set_errored("Blockchain unavailable", conn).unwrap();
+
+    let payload = Asset::sync_summaries(Some(event.id), 0, 100, conn).unwrap();
+    assert_eq!(payload.paging.total, 1);
+    let summary = &payload.data[0];
+    assert_eq!(summary.asset_id, asset.id);
+    assert_eq!(summary.ticket_type_id, ticket_type.id);
+    assert_eq!(summary.event_id, event.id);
+    assert_eq!(summary.status, AssetStatus::Pending);
+    assert_eq!(
+        summary.ticket_count,
+        ticket_type.ticket_count(conn).unwrap() as i64
+    );
+    assert_eq!(summary.pending_sync_count, 1);
+    assert_eq!(summary.failed_sync_count, 1);
+
+    let payload = Asset::sync_summaries(None, 0, 100, conn).unwrap();
+    assert!(payload.data.iter().any(|s| s.event_id == other_event.id));
+    assert!(payload.data.iter().any(|s| s.event_id == event.id));
+}
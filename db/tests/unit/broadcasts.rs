@@ -43,6 +43,47 @@ fn new_broadcast_commit() {
     );
 }
 
+#[test]
+fn new_broadcast_commit_with_fan_segment() {
+    let project = TestProject::new();
+    let conn = project.get_connection();
+    let user = project.create_user().finish();
+    let organization = project.create_organization().finish();
+    let event = project
+        .create_event()
+        .with_organization(&organization)
+        .finish();
+    let fan_segment = FanSegment::create(
+        organization.id,
+        "Segment".to_string(),
+        Default::default(),
+        user.id,
+    )
+    .commit(conn)
+    .unwrap();
+
+    let broadcast = project
+        .create_broadcast()
+        .with_event_id(event.id)
+        .with_fan_segment(&fan_segment)
+        .finish();
+    assert_eq!(broadcast.fan_segment_id, Some(fan_segment.id));
+
+    // Segments of other organizations cannot be used
+    let other_event = project.create_event().finish();
+    let mut broadcast = Broadcast::create(
+        other_event.id,
+        BroadcastType::LastCall,
+        BroadcastChannel::PushNotification,
+        "myname".to_string(),
+        None,
+        None,
+        None,
+    );
+    broadcast.fan_segment_id = Some(fan_segment.id);
+    assert!(broadcast.commit(conn).is_err());
+}
+
 #[test]
 fn broadcast_find() {
     let project = TestProject::new();
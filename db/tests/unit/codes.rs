@@ -272,14 +272,31 @@ pub fn create_with_validation_errors() {
 #[test]
 fn update() {
     let db = TestProject::new();
+    let user = db.create_user().finish();
     let code = db.create_code().finish();
 
     let update_patch = UpdateCodeAttributes {
         name: Some("New name".into()),
         ..Default::default()
     };
-    let new_code = code.update(update_patch, db.get_connection()).unwrap();
+    let new_code = code
+        .update(Some(user.id), update_patch, db.get_connection())
+        .unwrap();
     assert_eq!(new_code.name, "New name".to_string());
+
+    let domain_events = DomainEvent::find(
+        Tables::Codes,
+        Some(code.id),
+        Some(DomainEventTypes::CodeUpdated),
+        db.get_connection(),
+    )
+    .unwrap();
+    assert_eq!(domain_events.len(), 1);
+    assert_eq!(domain_events[0].user_id, Some(user.id));
+    let changes = &domain_events[0].event_data.as_ref().unwrap()["changes"];
+    assert_eq!(changes["name"]["before"], json!(code.name));
+    assert_eq!(changes["name"]["after"], json!("New name"));
+    assert_eq!(changes.get("redemption_code"), None);
 }
 
 #[test]
@@ -296,7 +313,7 @@ pub fn update_with_validation_errors() {
         discount_in_cents: Some(None),
         ..Default::default()
     };
-    let result = code.update(update_patch, db.get_connection());
+    let result = code.update(None, update_patch, db.get_connection());
     match result {
         Ok(_) => {
             panic!("Expected validation error");
@@ -352,7 +369,7 @@ pub fn update_with_validation_errors() {
         redemption_code: Some(code2.redemption_code),
         ..Default::default()
     };
-    let result = code.update(update_patch, db.get_connection());
+    let result = code.update(None, update_patch, db.get_connection());
     match result {
         Ok(_) => {
             panic!("Expected validation error");
@@ -381,7 +398,7 @@ pub fn update_with_validation_errors() {
         redemption_code: hold.redemption_code,
         ..Default::default()
     };
-    let result = code.update(update_patch, db.get_connection());
+    let result = code.update(None, update_patch, db.get_connection());
     match result {
         Ok(_) => {
             panic!("Expected validation error");
@@ -405,6 +422,142 @@ pub fn update_with_validation_errors() {
     }
 }
 
+#[test]
+fn update_with_discount_rules() {
+    let db = TestProject::new();
+    let connection = db.get_connection();
+    let code = db.create_code().finish();
+
+    let code = code
+        .update(
+            None,
+            UpdateCodeAttributes {
+                min_quantity: Some(Some(2)),
+                buy_quantity: Some(Some(2)),
+                get_quantity: Some(Some(1)),
+                max_order_discount_in_cents: Some(Some(500)),
+                waive_fees: Some(true),
+                ..Default::default()
+            },
+            connection,
+        )
+        .unwrap();
+    assert_eq!(code.min_quantity, Some(2));
+    assert_eq!(code.buy_quantity, Some(2));
+    assert_eq!(code.get_quantity, Some(1));
+    assert_eq!(code.max_order_discount_in_cents, Some(500));
+    assert!(code.waive_fees);
+
+    // Buy quantity without get quantity
+    let result = code.update(
+        None,
+        UpdateCodeAttributes {
+            get_quantity: Some(None),
+            ..Default::default()
+        },
+        connection,
+    );
+    match result {
+        Ok(_) => {
+            panic!("Expected validation error");
+        }
+        Err(error) => match &error.error_code {
+            ValidationError { errors } => {
+                assert!(errors.contains_key("discount_rules"));
+                assert_eq!(errors["discount_rules"].len(), 1);
+                assert_eq!(
+                    errors["discount_rules"][0].code,
+                    "buy_and_get_quantity_required"
+                );
+            }
+            _ => panic!("Expected validation error"),
+        },
+    }
+
+    // Rules are only available to discount codes
+    let code = db.create_code().with_code_type(CodeTypes::Access).finish();
+    let result = code.update(
+        None,
+        UpdateCodeAttributes {
+            waive_fees: Some(true),
+            ..Default::default()
+        },
+        connection,
+    );
+    match result {
+        Ok(_) => {
+            panic!("Expected validation error");
+        }
+        Err(error) => match &error.error_code {
+            ValidationError { errors } => {
+                assert!(errors.contains_key("discount_rules"));
+                assert_eq!(errors["discount_rules"].len(), 1);
+                assert_eq!(
+                    errors["discount_rules"][0].code,
+                    "discount_rules_require_discount_type"
+                );
+            }
+            _ => panic!("Expected validation error"),
+        },
+    }
+}
+
+#[test]
+fn discounted_quantity() {
+    let db = TestProject::new();
+    let connection = db.get_connection();
+    let code = db.create_code().finish();
+    assert_eq!(code.discounted_quantity(1), 1);
+    assert_eq!(code.discounted_quantity(7), 7);
+
+    let code = code
+        .update(
+            None,
+            UpdateCodeAttributes {
+                min_quantity: Some(Some(3)),
+                ..Default::default()
+            },
+            connection,
+        )
+        .unwrap();
+    assert_eq!(code.discounted_quantity(2), 0);
+    assert_eq!(code.discounted_quantity(3), 3);
+
+    // Buy 2 get 1
+    let code = code
+        .update(
+            None,
+            UpdateCodeAttributes {
+                min_quantity: Some(None),
+                buy_quantity: Some(Some(2)),
+                get_quantity: Some(Some(1)),
+                ..Default::default()
+            },
+            connection,
+        )
+        .unwrap();
+    assert_eq!(code.discounted_quantity(2), 0);
+    assert_eq!(code.discounted_quantity(3), 1);
+    assert_eq!(code.discounted_quantity(5), 1);
+    assert_eq!(code.discounted_quantity(6), 2);
+
+    // Buy 3 get 2, partially completed groups get what they can
+    let code = code
+        .update(
+            None,
+            UpdateCodeAttributes {
+                buy_quantity: Some(Some(3)),
+                get_quantity: Some(Some(2)),
+                ..Default::default()
+            },
+            connection,
+        )
+        .unwrap();
+    assert_eq!(code.discounted_quantity(4), 1);
+    assert_eq!(code.discounted_quantity(5), 2);
+    assert_eq!(code.discounted_quantity(9), 3);
+}
+
 #[test]
 fn find_by_redemption_code() {
     let db = TestProject::new();
@@ -522,6 +675,54 @@ fn destroy() {
     assert!(Code::find(code.id, project.get_connection()).is_err());
 }
 
+#[test]
+fn destroy_with_redemptions() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let event = project
+        .create_event()
+        .with_ticket_pricing()
+        .with_ticket_type_count(1)
+        .finish();
+    let ticket_type = &event.ticket_types(true, None, connection).unwrap()[0];
+    let code = project
+        .create_code()
+        .with_event(&event)
+        .with_code_type(CodeTypes::Discount)
+        .finish();
+    let unique_code = UniqueCode::generate(&code, 1, None, None, None, user.id, connection)
+        .unwrap()
+        .remove(0);
+
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
+    cart.update_quantities(
+        user.id,
+        &[UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 2,
+            redemption_code: Some(unique_code.redemption_code.clone()),
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+
+    assert!(code.destroy(connection).unwrap() > 0);
+    assert!(Code::find(code.id, connection).is_err());
+
+    // Order items that redeemed the code are kept without the reference
+    let items = cart.items(connection).unwrap();
+    let order_item = items
+        .iter()
+        .find(|i| i.item_type == OrderItemTypes::Tickets)
+        .unwrap();
+    assert_eq!(order_item.quantity, 2);
+    assert_eq!(order_item.code_id, None);
+    assert_eq!(order_item.unique_code_id, None);
+}
+
 #[test]
 pub fn find_for_event() {
     let db = TestProject::new();
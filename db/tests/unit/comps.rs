@@ -70,7 +70,9 @@ fn update() {
         email: Some(Some("new@email.com".to_string())),
         ..Default::default()
     };
-    let new_comp = comp.update(update_patch, db.get_connection()).unwrap();
+    let new_comp = comp
+        .update(None, update_patch, db.get_connection())
+        .unwrap();
     assert_eq!(new_comp.name, "New name".to_string());
     assert_eq!(new_comp.email, Some("new@email.com".to_string()));
 }
@@ -85,7 +87,7 @@ pub fn update_with_validation_errors() {
         ..Default::default()
     };
 
-    let result = comp.update(update_patch, db.get_connection());
+    let result = comp.update(None, update_patch, db.get_connection());
 
     match result {
         Ok(_) => {
@@ -165,7 +167,7 @@ fn find_for_hold() {
         discount_in_cents: Some(Some(0)),
         ..Default::default()
     };
-    let _hold2 = hold2.update(update_patch, connection).unwrap();
+    let _hold2 = hold2.update(None, update_patch, connection).unwrap();
 
     let found_comps =
         Hold::find_by_parent_id(hold1.id, HoldTypes::Comp, 0, 1000, connection).unwrap();
@@ -97,6 +97,80 @@ fn find_pending() {
     assert_eq!(pending_example.id, pending_actions[0].id);
 }
 
+#[test]
+fn find_scheduled() {
+    let project = TestProject::new();
+    let conn = project.get_connection();
+
+    let mut scheduled_example = DomainAction::create(
+        None,
+        DomainActionTypes::ReconcileBlockchainAssets,
+        None,
+        json!({}),
+        None,
+        None,
+    );
+    scheduled_example.schedule_at(Utc::now().naive_utc() + Duration::hours(1));
+    let scheduled_example = scheduled_example.commit(conn).unwrap();
+    let _ = project.create_domain_action().finish();
+
+    let scheduled_actions =
+        DomainAction::find_scheduled(DomainActionTypes::ReconcileBlockchainAssets, conn).unwrap();
+    assert_eq!(1, scheduled_actions.len());
+    assert_eq!(scheduled_example.id, scheduled_actions[0].id);
+
+    // Pending actions that are not yet due are not returned by find_pending
+    assert!(
+        DomainAction::find_pending(Some(DomainActionTypes::ReconcileBlockchainAssets), conn)
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn retry() {
+    let project = TestProject::new();
+    let conn = project.get_connection();
+
+    let example = project
+        .create_domain_action()
+        .with_status(DomainActionStatus::Pending)
+        .with_attempt_count(1)
+        .with_max_attempt_count(2)
+        .finish();
+    example.set_failed("test", conn).unwrap();
+    let example = DomainAction::find(example.id, conn).unwrap();
+    assert_eq!(DomainActionStatus::RetriesExceeded, example.status);
+
+    let expires_at = Utc::now().naive_utc() + Duration::days(1);
+    let updated = example.retry(expires_at, conn).unwrap();
+    assert_eq!(DomainActionStatus::Pending, updated.status);
+    assert_eq!(0, updated.attempt_count);
+    assert_eq!(expires_at.timestamp(), updated.expires_at.timestamp());
+    assert!(updated.blocked_until <= Utc::now().naive_utc());
+}
+
+#[test]
+fn reschedule() {
+    let project = TestProject::new();
+    let conn = project.get_connection();
+
+    let example = project
+        .create_domain_action()
+        .with_status(DomainActionStatus::Pending)
+        .with_attempt_count(1)
+        .finish();
+    let blocked_until = Utc::now().naive_utc() + Duration::seconds(30);
+    let updated = example.reschedule(blocked_until, conn).unwrap();
+    assert_eq!(DomainActionStatus::Pending, updated.status);
+    assert_eq!(1, updated.attempt_count);
+    assert_eq!(blocked_until.timestamp(), updated.blocked_until.timestamp());
+    assert!(DomainAction::find_pending(None, conn)
+        .unwrap()
+        .iter()
+        .all(|a| a.id != example.id));
+}
+
 #[test]
 fn has_pending_action() {
     let project = TestProject::new();
@@ -193,6 +267,32 @@ fn set_failed() {
     assert_eq!("test", updated.last_failure_reason.unwrap());
     assert_eq!(DomainActionStatus::Pending, updated.status);
     assert_eq!(1, updated.attempt_count);
+    assert_eq!(example.blocked_until, updated.blocked_until);
+
+    // Refund retries back off
+    let example = project
+        .create_domain_action()
+        .with_domain_action_type(DomainActionTypes::RefundCancelledEventOrder)
+        .with_status(DomainActionStatus::Pending)
+        .with_attempt_count(1)
+        .finish();
+    example.set_failed("test", conn).unwrap();
+
+    let updated = DomainAction::find(example.id, conn).unwrap();
+    assert_eq!(2, updated.attempt_count);
+    assert!(updated.blocked_until > Utc::now().naive_utc() + Duration::seconds(50));
+
+    // Blockchain sync retries back off
+    let example = project
+        .create_domain_action()
+        .with_domain_action_type(DomainActionTypes::BlockchainSync)
+        .with_status(DomainActionStatus::Pending)
+        .finish();
+    example.set_failed("test", conn).unwrap();
+
+    let updated = DomainAction::find(example.id, conn).unwrap();
+    assert_eq!(1, updated.attempt_count);
+    assert!(updated.blocked_until > Utc::now().naive_utc() + Duration::seconds(20));
 
     // Exceeding max failures
     let example = project
@@ -1,6 +1,6 @@
 use bigneon_db::dev::TestProject;
 use bigneon_db::prelude::*;
-use chrono::Utc;
+use chrono::{Duration, Utc};
 use serde_json::Value;
 use std::str::FromStr;
 use uuid::Uuid;
@@ -41,6 +41,7 @@ fn partial_ord() {
         created_at: Utc::now().naive_utc(),
         updated_at: Utc::now().naive_utc(),
         user_id: None,
+        organization_id: None,
     };
 
     let high_id = "e2cf68a4-76bb-49e1-993c-2576a4fc1220";
@@ -157,6 +158,152 @@ fn find() {
     );
 }
 
+#[test]
+fn commit_sets_organization_id() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let organization = project.create_organization().finish();
+    let event = project
+        .create_event()
+        .with_organization(&organization)
+        .finish();
+
+    let domain_event = DomainEvent::create(
+        DomainEventTypes::EventUpdated,
+        "Event updated".to_string(),
+        Tables::Events,
+        Some(event.id),
+        None,
+        None,
+    )
+    .commit(connection)
+    .unwrap();
+    assert_eq!(domain_event.organization_id, Some(organization.id));
+
+    let domain_event = DomainEvent::create(
+        DomainEventTypes::PaymentMethodCreated,
+        "Payment method was created".to_string(),
+        Tables::PaymentMethods,
+        Some(Uuid::new_v4()),
+        None,
+        None,
+    )
+    .commit(connection)
+    .unwrap();
+    assert_eq!(domain_event.organization_id, None);
+}
+
+#[test]
+fn find_for_organization() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let user2 = project.create_user().finish();
+    let organization = project.create_organization().finish();
+    let organization2 = project.create_organization().finish();
+    let event = project
+        .create_event()
+        .with_organization(&organization)
+        .finish();
+
+    let domain_event = DomainEvent::create(
+        DomainEventTypes::OrganizationUpdated,
+        "Organization updated".to_string(),
+        Tables::Organizations,
+        Some(organization.id),
+        Some(user.id),
+        Some(json!({"changes": {}})),
+    )
+    .commit(connection)
+    .unwrap();
+    let domain_event2 = DomainEvent::create(
+        DomainEventTypes::EventUpdated,
+        "Event updated".to_string(),
+        Tables::Events,
+        Some(event.id),
+        Some(user2.id),
+        None,
+    )
+    .commit(connection)
+    .unwrap();
+    DomainEvent::create(
+        DomainEventTypes::OrganizationUpdated,
+        "Organization updated".to_string(),
+        Tables::Organizations,
+        Some(organization2.id),
+        Some(user.id),
+        None,
+    )
+    .commit(connection)
+    .unwrap();
+
+    let find = |user_id, main_table, event_type| {
+        DomainEvent::find_for_organization(
+            organization.id,
+            user_id,
+            main_table,
+            event_type,
+            None,
+            None,
+            0,
+            100,
+            connection,
+        )
+        .unwrap()
+        .data
+        .into_iter()
+        .map(|e| e.id)
+        .filter(|id| *id == domain_event.id || *id == domain_event2.id)
+        .collect::<Vec<Uuid>>()
+    };
+
+    // Other organizations excluded
+    assert_equiv!(find(None, None, None), [domain_event.id, domain_event2.id]);
+    assert_eq!(find(Some(user.id), None, None), vec![domain_event.id]);
+    assert_eq!(
+        find(None, Some(Tables::Events), None),
+        vec![domain_event2.id]
+    );
+    assert_eq!(
+        find(None, None, Some(DomainEventTypes::OrganizationUpdated)),
+        vec![domain_event.id]
+    );
+
+    let payload = DomainEvent::find_for_organization(
+        organization.id,
+        Some(user.id),
+        None,
+        None,
+        None,
+        None,
+        0,
+        100,
+        connection,
+    )
+    .unwrap();
+    assert_eq!(payload.paging.total, 1);
+    let entry = &payload.data[0];
+    assert_eq!(entry.event_type, DomainEventTypes::OrganizationUpdated);
+    assert_eq!(entry.event_data, Some(json!({"changes": {}})));
+    assert_eq!(entry.user.as_ref().map(|u| u.id), Some(user.id));
+    assert_eq!(entry.user.as_ref().unwrap().email, user.email);
+
+    // Outside of date range
+    let payload = DomainEvent::find_for_organization(
+        organization.id,
+        None,
+        None,
+        None,
+        Some(Utc::now().naive_utc() + Duration::days(1)),
+        None,
+        0,
+        100,
+        connection,
+    )
+    .unwrap();
+    assert!(payload.data.is_empty());
+}
+
 #[test]
 pub fn find_unpublished() {
     let project = TestProject::new();
@@ -0,0 +1,127 @@
+use bigneon_db::dev::TestProject;
+use bigneon_db::prelude::*;
+
+#[test]
+fn create() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+
+    let suppression = EmailSuppression::create(
+        " Bounced@Tari.com ",
+        EmailSuppressionReasons::Bounce,
+        "SendGrid",
+        Some("550 Mailbox does not exist".to_string()),
+    )
+    .commit(connection)
+    .unwrap()
+    .unwrap();
+    assert_eq!(suppression.email, "bounced@tari.com");
+    assert_eq!(suppression.reason, EmailSuppressionReasons::Bounce);
+
+    // Suppressing the address again for the same reason is ignored
+    assert!(EmailSuppression::create(
+        "bounced@tari.com",
+        EmailSuppressionReasons::Bounce,
+        "SendGrid",
+        None,
+    )
+    .commit(connection)
+    .unwrap()
+    .is_none());
+
+    // Other reasons are kept alongside the original one
+    let spam_report = EmailSuppression::create(
+        "bounced@tari.com",
+        EmailSuppressionReasons::SpamReport,
+        "SendGrid",
+        None,
+    )
+    .commit(connection)
+    .unwrap()
+    .unwrap();
+    let suppressions = EmailSuppression::find_by_email("BOUNCED@tari.com", connection).unwrap();
+    assert_eq!(suppressions.len(), 2);
+    assert!(suppressions.contains(&suppression));
+    assert!(suppressions.contains(&spam_report));
+
+    assert!(
+        EmailSuppression::create(" ", EmailSuppressionReasons::Bounce, "SendGrid", None)
+            .commit(connection)
+            .is_err()
+    );
+}
+
+#[test]
+fn suppressed_emails() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let suppression = EmailSuppression::create(
+        "unsubscribed@tari.com",
+        EmailSuppressionReasons::Unsubscribe,
+        "SendGrid",
+        None,
+    )
+    .commit(connection)
+    .unwrap()
+    .unwrap();
+
+    let emails = vec![
+        "Unsubscribed@tari.com".to_string(),
+        "subscribed@tari.com".to_string(),
+    ];
+    assert_eq!(
+        EmailSuppression::suppressed_emails(&emails, true, connection).unwrap(),
+        vec!["unsubscribed@tari.com".to_string()]
+    );
+    // Unsubscribes do not stop transactional emails
+    assert!(
+        EmailSuppression::suppressed_emails(&emails, false, connection)
+            .unwrap()
+            .is_empty()
+    );
+
+    suppression.destroy(connection).unwrap();
+    assert!(
+        EmailSuppression::suppressed_emails(&emails, true, connection)
+            .unwrap()
+            .is_empty()
+    );
+
+    // Bounced addresses are suppressed for all emails
+    EmailSuppression::create(
+        "subscribed@tari.com",
+        EmailSuppressionReasons::Bounce,
+        "SendGrid",
+        None,
+    )
+    .commit(connection)
+    .unwrap();
+    assert_eq!(
+        EmailSuppression::suppressed_emails(&emails, false, connection).unwrap(),
+        vec!["subscribed@tari.com".to_string()]
+    );
+    assert_eq!(
+        EmailSuppression::suppressed_emails(&emails, true, connection).unwrap(),
+        vec!["subscribed@tari.com".to_string()]
+    );
+
+    // As are addresses that reported spam
+    EmailSuppression::create(
+        "unsubscribed@tari.com",
+        EmailSuppressionReasons::SpamReport,
+        "SendGrid",
+        None,
+    )
+    .commit(connection)
+    .unwrap();
+    let mut suppressed_emails =
+        EmailSuppression::suppressed_emails(&emails, false, connection).unwrap();
+    suppressed_emails.sort();
+    assert_eq!(
+        suppressed_emails,
+        vec![
+            "subscribed@tari.com".to_string(),
+            "unsubscribed@tari.com".to_string()
+        ]
+    );
+}
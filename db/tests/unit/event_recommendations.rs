@@ -0,0 +1,200 @@
+use bigneon_db::dev::TestProject;
+use bigneon_db::prelude::*;
+use chrono::prelude::*;
+use chrono::Duration;
+use uuid::Uuid;
+
+#[test]
+fn update_for_user() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let artist = project.create_artist().finish();
+    let upcoming = NaiveDateTime::from(Utc::now().naive_utc() + Duration::days(7));
+
+    // Past event the user was interested in
+    let past_event = project.create_event().finish();
+    past_event.add_artist(None, artist.id, connection).unwrap();
+    EventInterest::create(past_event.id, user.id)
+        .commit(connection)
+        .unwrap();
+
+    // Upcoming event with the same artist
+    let recommended_event = project.create_event().with_event_start(upcoming).finish();
+    recommended_event
+        .add_artist(None, artist.id, connection)
+        .unwrap();
+
+    // Upcoming event with the same artist that the user already has tickets for
+    let owned_event = project
+        .create_event()
+        .with_event_start(upcoming)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    owned_event.add_artist(None, artist.id, connection).unwrap();
+    project
+        .create_order()
+        .for_user(&user)
+        .for_event(&owned_event)
+        .quantity(1)
+        .is_paid()
+        .finish();
+
+    // Upcoming event with no connection to the user
+    let unrelated_event = project.create_event().with_event_start(upcoming).finish();
+
+    let recommendations = EventRecommendation::update_for_user(user.id, &[], connection).unwrap();
+    let event_ids: Vec<Uuid> = recommendations.iter().map(|r| r.event_id).collect();
+    assert!(event_ids.contains(&recommended_event.id));
+    assert!(!event_ids.contains(&owned_event.id));
+    assert!(!event_ids.contains(&past_event.id));
+    assert!(!event_ids.contains(&unrelated_event.id));
+
+    // Recalculating replaces the existing recommendations
+    let recommendations = EventRecommendation::update_for_user(user.id, &[], connection).unwrap();
+    assert_eq!(
+        recommendations.len(),
+        EventRecommendation::find_for_user(user.id, 0, 100, connection)
+            .unwrap()
+            .data
+            .len()
+    );
+}
+
+#[test]
+fn update_for_user_with_related_artists() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let artist = project.create_artist().finish();
+    let related_artist = project
+        .create_artist()
+        .with_spotify_id("related-spotify-id".to_string())
+        .finish();
+    let upcoming = NaiveDateTime::from(Utc::now().naive_utc() + Duration::days(7));
+
+    let past_event = project.create_event().finish();
+    past_event.add_artist(None, artist.id, connection).unwrap();
+    EventInterest::create(past_event.id, user.id)
+        .commit(connection)
+        .unwrap();
+
+    let event = project.create_event().with_event_start(upcoming).finish();
+    event
+        .add_artist(None, related_artist.id, connection)
+        .unwrap();
+
+    let recommendations = EventRecommendation::update_for_user(user.id, &[], connection).unwrap();
+    assert!(recommendations.iter().all(|r| r.event_id != event.id));
+
+    let recommendations = EventRecommendation::update_for_user(
+        user.id,
+        &["related-spotify-id".to_string()],
+        connection,
+    )
+    .unwrap();
+    assert_eq!(recommendations.len(), 1);
+    assert_eq!(recommendations[0].event_id, event.id);
+}
+
+#[test]
+fn find_for_user() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let artist = project.create_artist().finish();
+    let upcoming = NaiveDateTime::from(Utc::now().naive_utc() + Duration::days(7));
+
+    let past_event = project.create_event().finish();
+    past_event.add_artist(None, artist.id, connection).unwrap();
+    EventInterest::create(past_event.id, user.id)
+        .commit(connection)
+        .unwrap();
+
+    let event = project.create_event().with_event_start(upcoming).finish();
+    event.add_artist(None, artist.id, connection).unwrap();
+    EventRecommendation::update_for_user(user.id, &[], connection).unwrap();
+
+    let event2 = project.create_event().with_event_start(upcoming).finish();
+    event2.add_artist(None, artist.id, connection).unwrap();
+    EventRecommendation::update_for_user(user.id, &[], connection).unwrap();
+
+    let recommendations = EventRecommendation::find_for_user(user.id, 0, 100, connection).unwrap();
+    assert_eq!(recommendations.paging.total, 2);
+    assert_eq!(recommendations.data.len(), 2);
+    assert!(recommendations.data[0].score > 0.0);
+
+    // Paging
+    let recommendations = EventRecommendation::find_for_user(user.id, 1, 1, connection).unwrap();
+    assert_eq!(recommendations.paging.total, 2);
+    assert_eq!(recommendations.data.len(), 1);
+
+    // Cancelled events are no longer recommended
+    event2.cancel(None, connection).unwrap();
+    let recommendations = EventRecommendation::find_for_user(user.id, 0, 100, connection).unwrap();
+    assert_eq!(recommendations.paging.total, 1);
+    assert_eq!(recommendations.data.len(), 1);
+    assert_eq!(recommendations.data[0].event.id, event.id);
+
+    event.cancel(None, connection).unwrap();
+    assert!(
+        EventRecommendation::find_for_user(user.id, 0, 100, connection)
+            .unwrap()
+            .data
+            .is_empty()
+    );
+}
+
+#[test]
+fn affinity_spotify_ids() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let artist = project
+        .create_artist()
+        .with_spotify_id("spotify-id".to_string())
+        .finish();
+    let event = project.create_event().finish();
+    event.add_artist(None, artist.id, connection).unwrap();
+
+    assert!(
+        EventRecommendation::affinity_spotify_ids(user.id, connection)
+            .unwrap()
+            .is_empty()
+    );
+    EventInterest::create(event.id, user.id)
+        .commit(connection)
+        .unwrap();
+    assert_eq!(
+        EventRecommendation::affinity_spotify_ids(user.id, connection).unwrap(),
+        vec!["spotify-id".to_string()]
+    );
+    assert!(
+        EventRecommendation::find_users_to_update(None, 100, connection)
+            .unwrap()
+            .contains(&user.id)
+    );
+}
+
+#[test]
+fn find_users_to_update() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project.create_event().finish();
+    let mut user_ids = Vec::new();
+    for _ in 0..3 {
+        let user = project.create_user().finish();
+        EventInterest::create(event.id, user.id)
+            .commit(connection)
+            .unwrap();
+        user_ids.push(user.id);
+    }
+    user_ids.sort();
+
+    // Users are returned in batches
+    let batch = EventRecommendation::find_users_to_update(None, 2, connection).unwrap();
+    assert_eq!(batch, user_ids[0..2].to_vec());
+    let batch = EventRecommendation::find_users_to_update(Some(batch[1]), 2, connection).unwrap();
+    assert_eq!(batch, user_ids[2..].to_vec());
+}
@@ -0,0 +1,345 @@
+use bigneon_db::dev::TestProject;
+use bigneon_db::models::*;
+use bigneon_db::utils::errors::ErrorCode::{BusinessProcessError, ValidationError};
+use chrono::prelude::*;
+use chrono::Duration;
+
+#[test]
+fn create() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let event = project.create_event().finish();
+
+    let event_series = EventSeries::create(
+        &event,
+        "Weekly".to_string(),
+        "FREQ=WEEKLY;COUNT=4".to_string(),
+    )
+    .commit(Some(user.id), connection)
+    .unwrap();
+    assert_eq!(event_series.organization_id, event.organization_id);
+    assert_eq!(event_series.template_event_id, event.id);
+    let event = Event::find(event.id, connection).unwrap();
+    assert_eq!(event.event_series_id, Some(event_series.id));
+    assert_eq!(
+        event_series.events(connection).unwrap(),
+        vec![event.clone()]
+    );
+
+    // Event can only belong to one series
+    let result = EventSeries::create(
+        &event,
+        "Daily".to_string(),
+        "FREQ=DAILY;COUNT=4".to_string(),
+    )
+    .commit(Some(user.id), connection);
+    match result {
+        Ok(_) => panic!("Expected business process error"),
+        Err(error) => match &error.error_code {
+            BusinessProcessError => {}
+            _ => panic!("Expected business process error"),
+        },
+    }
+
+    // Invalid rule
+    let event = project.create_event().finish();
+    let result = EventSeries::create(&event, "Weekly".to_string(), "FREQ=WEEKLY".to_string())
+        .commit(Some(user.id), connection);
+    match result {
+        Ok(_) => panic!("Expected validation error"),
+        Err(error) => match &error.error_code {
+            ValidationError { errors } => {
+                assert!(errors.contains_key("recurrence_rule"));
+            }
+            _ => panic!("Expected validation error"),
+        },
+    }
+}
+
+#[test]
+fn generate_events() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let venue = project
+        .create_venue()
+        .with_timezone("Africa/Johannesburg".to_string())
+        .finish();
+    let event = project
+        .create_event()
+        .with_venue(&venue)
+        .with_event_start(Utc::now().naive_utc() + Duration::days(7))
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let artist = project.create_artist().finish();
+    event.add_artist(None, artist.id, connection).unwrap();
+
+    let event_series = EventSeries::create(
+        &event,
+        "Weekly".to_string(),
+        "FREQ=WEEKLY;COUNT=3".to_string(),
+    )
+    .commit(None, connection)
+    .unwrap();
+    let events = event_series.generate_events(None, connection).unwrap();
+    assert_eq!(events.len(), 2);
+
+    let ticket_type = &event.ticket_types(false, None, connection).unwrap()[0];
+    let pricing = ticket_type.valid_ticket_pricing(false, connection).unwrap();
+    for (i, generated) in events.iter().enumerate() {
+        let offset = Duration::weeks(i as i64 + 1);
+        assert_eq!(generated.name, event.name);
+        assert_eq!(generated.status, EventStatus::Draft);
+        assert_eq!(generated.event_series_id, Some(event_series.id));
+        assert_eq!(generated.venue_id, Some(venue.id));
+        assert_eq!(generated.event_start, event.event_start.map(|d| d + offset));
+        assert_eq!(generated.event_end, event.event_end.map(|d| d + offset));
+        assert_eq!(generated.door_time, event.door_time.map(|d| d + offset));
+
+        let ticket_types = generated.ticket_types(false, None, connection).unwrap();
+        assert_eq!(ticket_types.len(), 1);
+        assert_eq!(ticket_types[0].name, ticket_type.name);
+        assert_eq!(ticket_types[0].start_date, ticket_type.start_date + offset);
+        assert_eq!(
+            ticket_types[0].valid_ticket_count(connection).unwrap(),
+            ticket_type.valid_ticket_count(connection).unwrap()
+        );
+        let generated_pricing = ticket_types[0]
+            .valid_ticket_pricing(false, connection)
+            .unwrap();
+        assert_eq!(generated_pricing.len(), pricing.len());
+        for (generated_pricing, pricing) in generated_pricing.iter().zip(pricing.iter()) {
+            assert_eq!(generated_pricing.name, pricing.name);
+            assert_eq!(generated_pricing.price_in_cents, pricing.price_in_cents);
+            assert_eq!(generated_pricing.start_date, pricing.start_date + offset);
+            assert_eq!(generated_pricing.end_date, pricing.end_date + offset);
+        }
+
+        let artists = generated.artists(connection).unwrap();
+        assert_eq!(artists.len(), 1);
+        assert_eq!(artists[0].artist.id, artist.id);
+    }
+
+    assert_eq!(event_series.events(connection).unwrap().len(), 3);
+
+    // Existing occurrences are not generated again
+    assert!(event_series
+        .generate_events(None, connection)
+        .unwrap()
+        .is_empty());
+
+    // Extending the rule only generates the new occurrence
+    let event_series = event_series
+        .update(
+            None,
+            EventSeriesEditableAttributes {
+                recurrence_rule: Some("FREQ=WEEKLY;COUNT=4".to_string()),
+                ..Default::default()
+            },
+            connection,
+        )
+        .unwrap();
+    let events = event_series.generate_events(None, connection).unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(
+        events[0].event_start,
+        event.event_start.map(|d| d + Duration::weeks(3))
+    );
+}
+
+#[test]
+fn destroy() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project.create_event().finish();
+    let event_series = EventSeries::create(
+        &event,
+        "Weekly".to_string(),
+        "FREQ=WEEKLY;COUNT=4".to_string(),
+    )
+    .commit(None, connection)
+    .unwrap();
+
+    event_series.destroy(None, connection).unwrap();
+    assert!(EventSeries::find(event_series.id, connection).is_err());
+    let event = Event::find(event.id, connection).unwrap();
+    assert_eq!(event.event_series_id, None);
+}
+
+#[test]
+fn update_future_events() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project
+        .create_event()
+        .with_event_start(Utc::now().naive_utc() + Duration::days(1))
+        .finish();
+    let event_series = EventSeries::create(
+        &event,
+        "Daily".to_string(),
+        "FREQ=DAILY;COUNT=3".to_string(),
+    )
+    .commit(None, connection)
+    .unwrap();
+    event_series.generate_events(None, connection).unwrap();
+
+    // Occurrences that have started are left alone
+    let past_event = project
+        .create_event()
+        .with_event_start(Utc::now().naive_utc() - Duration::days(1))
+        .finish();
+    let past_event = past_event
+        .duplicate(
+            None,
+            past_event.event_start.unwrap(),
+            Some(event_series.id),
+            connection,
+        )
+        .unwrap();
+
+    let updated_events = event_series
+        .update_future_events(
+            None,
+            EventEditableAttributes {
+                name: Some("New name".to_string()),
+                event_start: Some(Utc::now().naive_utc() + Duration::days(30)),
+                ..Default::default()
+            },
+            connection,
+        )
+        .unwrap();
+    assert_eq!(updated_events.len(), 3);
+    for (i, updated_event) in updated_events.iter().enumerate() {
+        assert_eq!(updated_event.name, "New name");
+        assert_eq!(
+            updated_event.event_start,
+            event.event_start.map(|d| d + Duration::days(i as i64))
+        );
+    }
+    let past_event = Event::find(past_event.id, connection).unwrap();
+    assert_ne!(past_event.name, "New name");
+}
+
+#[test]
+fn update_future_events_propagates_ticket_types() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project
+        .create_event()
+        .with_event_start(Utc::now().naive_utc() + Duration::days(1))
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let event_series = EventSeries::create(
+        &event,
+        "Daily".to_string(),
+        "FREQ=DAILY;COUNT=2".to_string(),
+    )
+    .commit(None, connection)
+    .unwrap();
+    let generated = event_series.generate_events(None, connection).unwrap();
+    assert_eq!(generated.len(), 1);
+
+    // Edit the template's ticket type and pricing
+    let ticket_type = &event.ticket_types(false, None, connection).unwrap()[0];
+    let ticket_type = ticket_type
+        .update(
+            None,
+            TicketTypeEditableAttributes {
+                limit_per_person: Some(4),
+                ..Default::default()
+            },
+            connection,
+        )
+        .unwrap();
+    let pricing = &ticket_type.ticket_pricing(connection).unwrap()[0];
+    pricing
+        .update(
+            None,
+            TicketPricingEditableAttributes {
+                price_in_cents: Some(2500),
+                ..Default::default()
+            },
+            connection,
+        )
+        .unwrap();
+
+    event_series
+        .update_future_events(None, Default::default(), connection)
+        .unwrap();
+
+    let generated_ticket_type = &generated[0].ticket_types(false, None, connection).unwrap()[0];
+    assert_eq!(generated_ticket_type.limit_per_person, 4);
+    assert_eq!(
+        generated_ticket_type.start_date,
+        ticket_type.start_date + Duration::days(1)
+    );
+    let generated_pricing = generated_ticket_type
+        .ticket_pricing(connection)
+        .unwrap()
+        .into_iter()
+        .find(|tp| tp.name == pricing.name)
+        .unwrap();
+    assert_eq!(generated_pricing.price_in_cents, 2500);
+    assert_eq!(
+        generated_pricing.start_date,
+        pricing.start_date + Duration::days(1)
+    );
+}
+
+#[test]
+fn event_series_summary_report() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project
+        .create_event()
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let other_event = project
+        .create_event()
+        .with_organization(&event.organization(connection).unwrap())
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let event_series = EventSeries::create(
+        &event,
+        "Weekly".to_string(),
+        "FREQ=WEEKLY;COUNT=2".to_string(),
+    )
+    .commit(None, connection)
+    .unwrap();
+
+    let user = project.create_user().finish();
+    for e in vec![&event, &other_event] {
+        let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
+        cart.update_quantities(
+            user.id,
+            &[UpdateOrderItem {
+                ticket_type_id: e.ticket_types(true, None, connection).unwrap()[0].id,
+                quantity: 2,
+                redemption_code: None,
+            }],
+            false,
+            false,
+            connection,
+        )
+        .unwrap();
+        let total = cart.calculate_total(connection).unwrap();
+        cart.add_external_payment(
+            Some("Test".to_string()),
+            ExternalPaymentType::CreditCard,
+            user.id,
+            total,
+            connection,
+        )
+        .unwrap();
+    }
+
+    let result =
+        Report::event_series_summary_report(event_series.id, None, None, connection).unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].event_id, event.id);
+    assert_eq!(result[0].sales.iter().map(|s| s.total_sold).sum::<i64>(), 2);
+}
@@ -1,5 +1,6 @@
 use bigneon_db::dev::TestProject;
 use bigneon_db::models::*;
+use bigneon_db::utils::errors::ErrorCode;
 use chrono::prelude::*;
 use chrono::Duration;
 use uuid::Uuid;
@@ -47,13 +48,30 @@ fn update() {
         door_time: Some(NaiveDate::from_ymd(2016, 7, 8).and_hms(4, 10, 11)),
         ..Default::default()
     };
-    let event = event
-        .update(None, parameters, project.get_connection())
+    let updated_event = event
+        .update(Some(user.id), parameters, project.get_connection())
         .unwrap();
     assert_eq!(
-        event.door_time,
+        updated_event.door_time,
         Some(NaiveDate::from_ymd(2016, 7, 8).and_hms(4, 10, 11))
     );
+
+    let domain_events = DomainEvent::find(
+        Tables::Events,
+        Some(event.id),
+        Some(DomainEventTypes::EventUpdated),
+        project.get_connection(),
+    )
+    .unwrap();
+    assert_eq!(domain_events.len(), 1);
+    assert_eq!(domain_events[0].user_id, Some(user.id));
+    let changes = &domain_events[0].event_data.as_ref().unwrap()["changes"];
+    assert_eq!(changes["door_time"]["before"], json!(event.door_time));
+    assert_eq!(
+        changes["door_time"]["after"],
+        json!(updated_event.door_time)
+    );
+    assert_eq!(changes.get("name"), None);
 }
 
 #[test]
@@ -335,6 +353,139 @@ fn cancel() {
     assert!(!event.cancelled_at.is_none());
 }
 
+#[test]
+fn queue_refunds() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project.create_event().with_ticket_pricing().finish();
+    let other_event = project.create_event().with_ticket_pricing().finish();
+    let order = project
+        .create_order()
+        .for_event(&event)
+        .quantity(2)
+        .is_paid()
+        .finish();
+    let order2 = project.create_order().for_event(&event).is_paid().finish();
+    project.create_order().for_event(&event).finish();
+    project
+        .create_order()
+        .for_event(&other_event)
+        .is_paid()
+        .finish();
+
+    // Refunds are only queued for cancelled events
+    assert!(event.queue_refunds(None, connection).is_err());
+
+    // Cancelling queues a refund per paid order and cancels ticket types so tickets are nullified
+    let event = event.cancel(None, connection).unwrap();
+    let ticket_type = &event.ticket_types(false, None, connection).unwrap()[0];
+    assert_eq!(ticket_type.status, TicketTypeStatus::Cancelled);
+
+    let actions = DomainAction::find_by_main_table(
+        DomainActionTypes::RefundCancelledEventOrder,
+        Tables::Events.to_string(),
+        event.id,
+        connection,
+    )
+    .unwrap();
+    assert_eq!(actions.len(), 2);
+    let mut order_ids: Vec<Uuid> = actions
+        .iter()
+        .map(|action| {
+            serde_json::from_value::<RefundCancelledEventOrderPayload>(action.payload.clone())
+                .unwrap()
+                .order_id
+        })
+        .collect();
+    order_ids.sort();
+    let mut expected_order_ids = vec![order.id, order2.id];
+    expected_order_ids.sort();
+    assert_eq!(order_ids, expected_order_ids);
+    assert_eq!(actions[0].max_attempt_count, 8);
+    assert!(actions[0].expires_at > Utc::now().naive_utc() + Duration::days(6));
+
+    // Refunds that are pending are not queued again
+    assert!(event.queue_refunds(None, connection).unwrap().is_empty());
+
+    // Failed refunds are requeued
+    actions[0].set_errored("Card declined", connection).unwrap();
+    let requeued = event.queue_refunds(None, connection).unwrap();
+    assert_eq!(requeued.len(), 1);
+    assert_eq!(requeued[0].payload, actions[0].payload);
+}
+
+#[test]
+fn refund_progress() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project.create_event().with_ticket_pricing().finish();
+    for _ in 0..3 {
+        project.create_order().for_event(&event).is_paid().finish();
+    }
+    let event = event.cancel(None, connection).unwrap();
+    let actions = DomainAction::find_by_main_table(
+        DomainActionTypes::RefundCancelledEventOrder,
+        Tables::Events.to_string(),
+        event.id,
+        connection,
+    )
+    .unwrap();
+    actions[0].set_done(connection).unwrap();
+    actions[1].set_errored("Card declined", connection).unwrap();
+
+    let progress = event.refund_progress(connection).unwrap();
+    assert_eq!(progress.total, 3);
+    assert_eq!(progress.succeeded, 1);
+    assert_eq!(progress.pending, 1);
+    assert_eq!(progress.failed, 1);
+    assert_eq!(progress.failures.len(), 1);
+    let failure = &progress.failures[0];
+    assert_eq!(failure.domain_action_id, actions[1].id);
+    assert_eq!(
+        json!(RefundCancelledEventOrderPayload {
+            order_id: failure.order_id
+        }),
+        actions[1].payload
+    );
+    assert_eq!(failure.status, DomainActionStatus::Errored);
+    assert_eq!(
+        failure.last_failure_reason,
+        Some("Card declined".to_string())
+    );
+
+    // Requeued refunds are no longer counted as failures
+    event.queue_refunds(None, connection).unwrap();
+    let progress = event.refund_progress(connection).unwrap();
+    assert_eq!(progress.total, 3);
+    assert_eq!(progress.succeeded, 1);
+    assert_eq!(progress.pending, 2);
+    assert_eq!(progress.failed, 0);
+
+    // Refunds leaving transferred tickets for a manual refund are listed as failures
+    let payload: RefundCancelledEventOrderPayload =
+        serde_json::from_value(actions[0].payload.clone()).unwrap();
+    DomainEvent::create(
+        DomainEventTypes::OrderManualRefundRequired,
+        "Transferred tickets require a manual refund".to_string(),
+        Tables::Orders,
+        Some(payload.order_id),
+        None,
+        Some(json!({ "event_id": event.id })),
+    )
+    .commit(connection)
+    .unwrap();
+    let progress = event.refund_progress(connection).unwrap();
+    assert_eq!(progress.total, 3);
+    assert_eq!(progress.succeeded, 0);
+    assert_eq!(progress.pending, 2);
+    assert_eq!(progress.failed, 1);
+    assert_eq!(progress.failures[0].order_id, payload.order_id);
+    assert_eq!(
+        progress.failures[0].last_failure_reason,
+        Some("Transferred tickets require a manual refund".to_string())
+    );
+}
+
 #[test]
 fn get_sales_by_date_range() {
     let project = TestProject::new();
@@ -820,6 +971,7 @@ fn search() {
         SortingDir::Asc,
         None,
         PastOrUpcoming::Past,
+        EventSearchFilters::default(),
         project.get_connection(),
     )
     .unwrap();
@@ -838,6 +990,7 @@ fn search() {
         SortingDir::Asc,
         Some(organization_owner),
         PastOrUpcoming::Past,
+        EventSearchFilters::default(),
         project.get_connection(),
     )
     .unwrap();
@@ -856,6 +1009,7 @@ fn search() {
         SortingDir::Asc,
         Some(organization_user),
         PastOrUpcoming::Past,
+        EventSearchFilters::default(),
         project.get_connection(),
     )
     .unwrap();
@@ -874,6 +1028,7 @@ fn search() {
         SortingDir::Asc,
         Some(user),
         PastOrUpcoming::Past,
+        EventSearchFilters::default(),
         project.get_connection(),
     )
     .unwrap();
@@ -892,6 +1047,7 @@ fn search() {
         SortingDir::Asc,
         Some(admin),
         PastOrUpcoming::Past,
+        EventSearchFilters::default(),
         project.get_connection(),
     )
     .unwrap();
@@ -910,6 +1066,7 @@ fn search() {
         SortingDir::Asc,
         None,
         PastOrUpcoming::Past,
+        EventSearchFilters::default(),
         project.get_connection(),
     )
     .unwrap();
@@ -928,6 +1085,7 @@ fn search() {
         SortingDir::Asc,
         None,
         PastOrUpcoming::Past,
+        EventSearchFilters::default(),
         project.get_connection(),
     )
     .unwrap();
@@ -947,6 +1105,7 @@ fn search() {
         SortingDir::Asc,
         None,
         PastOrUpcoming::Past,
+        EventSearchFilters::default(),
         project.get_connection(),
     )
     .unwrap();
@@ -967,6 +1126,7 @@ fn search() {
         SortingDir::Asc,
         None,
         PastOrUpcoming::Past,
+        EventSearchFilters::default(),
         project.get_connection(),
     )
     .unwrap();
@@ -986,6 +1146,7 @@ fn search() {
         SortingDir::Asc,
         None,
         PastOrUpcoming::Past,
+        EventSearchFilters::default(),
         project.get_connection(),
     )
     .unwrap();
@@ -1006,6 +1167,7 @@ fn search() {
         SortingDir::Asc,
         None,
         PastOrUpcoming::Past,
+        EventSearchFilters::default(),
         project.get_connection(),
     )
     .unwrap();
@@ -1025,6 +1187,7 @@ fn search() {
         SortingDir::Asc,
         None,
         PastOrUpcoming::Past,
+        EventSearchFilters::default(),
         project.get_connection(),
     )
     .unwrap();
@@ -1044,6 +1207,7 @@ fn search() {
         SortingDir::Asc,
         None,
         PastOrUpcoming::Past,
+        EventSearchFilters::default(),
         project.get_connection(),
     )
     .unwrap();
@@ -1064,6 +1228,7 @@ fn search() {
         SortingDir::Asc,
         None,
         PastOrUpcoming::Past,
+        EventSearchFilters::default(),
         project.get_connection(),
     )
     .unwrap();
@@ -1083,6 +1248,7 @@ fn search() {
         SortingDir::Asc,
         None,
         PastOrUpcoming::Past,
+        EventSearchFilters::default(),
         project.get_connection(),
     )
     .unwrap();
@@ -1101,6 +1267,7 @@ fn search() {
         SortingDir::Asc,
         None,
         PastOrUpcoming::Past,
+        EventSearchFilters::default(),
         project.get_connection(),
     )
     .unwrap();
@@ -1120,6 +1287,7 @@ fn search() {
         SortingDir::Asc,
         None,
         PastOrUpcoming::Past,
+        EventSearchFilters::default(),
         project.get_connection(),
     )
     .unwrap();
@@ -1140,6 +1308,7 @@ fn search() {
         SortingDir::Asc,
         None,
         PastOrUpcoming::Past,
+        EventSearchFilters::default(),
         project.get_connection(),
     )
     .unwrap();
@@ -1158,6 +1327,7 @@ fn search() {
         SortingDir::Asc,
         None,
         PastOrUpcoming::Past,
+        EventSearchFilters::default(),
         project.get_connection(),
     )
     .unwrap();
@@ -1176,6 +1346,7 @@ fn search() {
         SortingDir::Asc,
         None,
         PastOrUpcoming::Past,
+        EventSearchFilters::default(),
         project.get_connection(),
     )
     .unwrap();
@@ -1195,6 +1366,7 @@ fn search() {
         SortingDir::Asc,
         None,
         PastOrUpcoming::Past,
+        EventSearchFilters::default(),
         project.get_connection(),
     )
     .unwrap();
@@ -1202,6 +1374,184 @@ fn search() {
     assert_eq!(all_events[0], all_found_events[0]);
 }
 
+#[test]
+fn search_with_filters() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event_end = Utc::now().naive_utc() + Duration::days(30);
+    let cape_town = project
+        .create_venue()
+        .with_coordinates(-33.9249, 18.4241)
+        .finish();
+    let johannesburg = project
+        .create_venue()
+        .with_coordinates(-26.2041, 28.0473)
+        .finish();
+    let event = project
+        .create_event()
+        .with_name("Jazz by the sea".to_string())
+        .with_venue(&cape_town)
+        .with_event_end(event_end)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let event2 = project
+        .create_event()
+        .with_name("Rock festival".to_string())
+        .with_venue(&johannesburg)
+        .with_event_end(event_end)
+        .finish()
+        .update(
+            None,
+            EventEditableAttributes {
+                age_limit: Some("18".to_string()),
+                top_line_info: Some(Some("Jazz and blues".to_string())),
+                ..Default::default()
+            },
+            connection,
+        )
+        .unwrap();
+
+    let search = |query: Option<&str>,
+                  sort_field: EventSearchSortField,
+                  sort_direction: SortingDir,
+                  filters: EventSearchFilters| {
+        Event::search(
+            query.map(|q| q.to_string()),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            sort_field,
+            sort_direction,
+            None,
+            PastOrUpcoming::Upcoming,
+            filters,
+            connection,
+        )
+        .unwrap()
+    };
+
+    // Full text matches rank event names above top line info
+    let found_events = search(
+        Some("jazz"),
+        EventSearchSortField::Relevance,
+        SortingDir::Desc,
+        EventSearchFilters::default(),
+    );
+    assert_eq!(found_events, vec![event.clone(), event2.clone()]);
+
+    // Events near a point
+    let near_cape_town = EventSearchFilters {
+        latitude: Some(-33.9),
+        longitude: Some(18.4),
+        ..Default::default()
+    };
+    let found_events = search(
+        None,
+        EventSearchSortField::Distance,
+        SortingDir::Asc,
+        EventSearchFilters {
+            radius_in_km: Some(50.0),
+            ..near_cape_town.clone()
+        },
+    );
+    assert_eq!(found_events, vec![event.clone()]);
+    let found_events = search(
+        None,
+        EventSearchSortField::Distance,
+        SortingDir::Desc,
+        near_cape_town,
+    );
+    assert_eq!(found_events, vec![event2.clone(), event.clone()]);
+
+    // Age limit and event type
+    let found_events = search(
+        None,
+        EventSearchSortField::EventStart,
+        SortingDir::Asc,
+        EventSearchFilters {
+            age_limit: Some("18".to_string()),
+            ..Default::default()
+        },
+    );
+    assert_eq!(found_events, vec![event2.clone()]);
+    let found_events = search(
+        None,
+        EventSearchSortField::EventStart,
+        SortingDir::Asc,
+        EventSearchFilters {
+            event_type: Some(EventTypes::Conference),
+            ..Default::default()
+        },
+    );
+    assert!(found_events.is_empty());
+
+    // Price range, excluding events without tickets on sale
+    let found_events = search(
+        None,
+        EventSearchSortField::EventStart,
+        SortingDir::Asc,
+        EventSearchFilters {
+            max_price_in_cents: Some(1_000_000),
+            ..Default::default()
+        },
+    );
+    assert_eq!(found_events, vec![event.clone()]);
+    let found_events = search(
+        None,
+        EventSearchSortField::EventStart,
+        SortingDir::Asc,
+        EventSearchFilters {
+            min_price_in_cents: Some(1_000_000),
+            ..Default::default()
+        },
+    );
+    assert!(found_events.is_empty());
+
+    // Availability
+    let found_events = search(
+        None,
+        EventSearchSortField::EventStart,
+        SortingDir::Asc,
+        EventSearchFilters {
+            availability: Some(EventAvailability::SoldOut),
+            ..Default::default()
+        },
+    );
+    assert_eq!(found_events, vec![event2.clone()]);
+
+    // Facets are counted without their own filter
+    let (found_events, facets) = Event::search_with_facets(
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        EventSearchSortField::EventStart,
+        SortingDir::Asc,
+        None,
+        PastOrUpcoming::Upcoming,
+        EventSearchFilters {
+            availability: Some(EventAvailability::SoldOut),
+            ..Default::default()
+        },
+        true,
+        connection,
+    )
+    .unwrap();
+    assert_eq!(found_events, vec![event2]);
+    let facets = facets.unwrap();
+    assert_eq!(facets.event_types.get("Music"), Some(&1));
+    assert_eq!(facets.age_limits.get("18"), Some(&1));
+    assert_eq!(facets.availability.get("Available"), Some(&1));
+    assert_eq!(facets.availability.get("SoldOut"), Some(&1));
+}
+
 #[test]
 fn current_ticket_pricing_range() {
     let project = TestProject::new();
@@ -1643,3 +1993,268 @@ fn get_all_localized_times() {
         "Tue,  1 Jan 2019 13:00:00 +0200"
     );
 }
+
+#[test]
+fn clone_event() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let venue = project.create_venue().finish();
+    let stage = project.create_stage().with_venue_id(venue.id).finish();
+    let event = project
+        .create_event()
+        .with_venue(&venue)
+        .with_event_start(NaiveDate::from_ymd(2030, 7, 8).and_hms(20, 0, 0))
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let ticket_type = &event.ticket_types(false, None, connection).unwrap()[0];
+    let artist = project.create_artist().finish();
+    EventArtist::create(
+        event.id,
+        artist.id,
+        1,
+        Some(NaiveDate::from_ymd(2030, 7, 8).and_hms(21, 0, 0)),
+        2,
+        Some(stage.id),
+    )
+    .commit(None, connection)
+    .unwrap();
+    let hold = project
+        .create_hold()
+        .with_hold_type(HoldTypes::Discount)
+        .with_quantity(10)
+        .with_end_at(NaiveDate::from_ymd(2030, 7, 1).and_hms(0, 0, 0))
+        .with_ticket_type_id(ticket_type.id)
+        .finish();
+    project
+        .create_comp()
+        .with_hold(&hold)
+        .with_quantity(2)
+        .finish();
+    let code = project
+        .create_code()
+        .with_event(&event)
+        .for_ticket_type(&ticket_type)
+        .finish();
+
+    let event_start = NaiveDate::from_ymd(2030, 8, 5).and_hms(20, 0, 0);
+    let offset = event_start - event.event_start.unwrap();
+
+    // Codes are only copied when asked for
+    let new_event = event
+        .clone_event(Some(user.id), event_start, false, connection)
+        .unwrap();
+    assert!(Code::find_for_event(new_event.id, None, connection)
+        .unwrap()
+        .is_empty());
+
+    let new_event = event
+        .clone_event(Some(user.id), event_start, true, connection)
+        .unwrap();
+    assert_ne!(new_event.id, event.id);
+    assert_eq!(new_event.name, event.name);
+    assert_eq!(new_event.status, EventStatus::Draft);
+    assert_eq!(new_event.event_start, Some(event_start));
+    assert_eq!(new_event.event_end, event.event_end.map(|d| d + offset));
+    assert_eq!(new_event.event_series_id, None);
+
+    let new_ticket_types = new_event.ticket_types(false, None, connection).unwrap();
+    assert_eq!(new_ticket_types.len(), 1);
+    let new_ticket_type = &new_ticket_types[0];
+    assert_eq!(new_ticket_type.name, ticket_type.name);
+    assert_eq!(new_ticket_type.start_date, ticket_type.start_date + offset);
+    assert_eq!(new_ticket_type.end_date, ticket_type.end_date + offset);
+    assert_eq!(
+        new_ticket_type.valid_ticket_count(connection).unwrap(),
+        ticket_type.valid_ticket_count(connection).unwrap()
+    );
+    assert_eq!(
+        new_ticket_type
+            .valid_ticket_pricing(false, connection)
+            .unwrap()
+            .len(),
+        ticket_type
+            .valid_ticket_pricing(false, connection)
+            .unwrap()
+            .len()
+    );
+
+    let artists = new_event.artists(connection).unwrap();
+    assert_eq!(artists.len(), 1);
+    assert_eq!(artists[0].artist.id, artist.id);
+    assert_eq!(
+        artists[0].set_time,
+        Some(NaiveDate::from_ymd(2030, 8, 5).and_hms(21, 0, 0))
+    );
+    assert_eq!(artists[0].stage_id, Some(stage.id));
+
+    // Holds are copied without tickets and comps are left out
+    let holds = Hold::find_for_event(new_event.id, connection).unwrap();
+    assert_eq!(holds.len(), 1);
+    assert_eq!(holds[0].name, hold.name);
+    assert_eq!(holds[0].ticket_type_id, new_ticket_type.id);
+    assert_eq!(holds[0].hold_type, HoldTypes::Discount);
+    assert_eq!(holds[0].discount_in_cents, hold.discount_in_cents);
+    assert_eq!(holds[0].end_at, hold.end_at.map(|d| d + offset));
+    assert!(holds[0].redemption_code.is_some());
+    assert_ne!(holds[0].redemption_code, hold.redemption_code);
+    assert_eq!(holds[0].quantity(connection).unwrap(), (0, 0));
+
+    let codes = Code::find_for_event(new_event.id, None, connection).unwrap();
+    assert_eq!(codes.len(), 1);
+    assert_eq!(codes[0].name, code.name);
+    assert_ne!(codes[0].redemption_code, code.redemption_code);
+    assert_eq!(codes[0].start_date, code.start_date + offset);
+    assert_eq!(codes[0].end_date, code.end_date + offset);
+    assert_eq!(codes[0].ticket_type_ids, vec![new_ticket_type.id]);
+}
+
+#[test]
+fn reschedule() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let event_start = Utc::now().naive_utc() + Duration::days(10);
+    let event = project
+        .create_event()
+        .with_event_start(event_start)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let event = Event::find(event.id, connection).unwrap();
+    let ticket_type = &event.ticket_types(false, None, connection).unwrap()[0];
+    let pricing = ticket_type.valid_ticket_pricing(false, connection).unwrap();
+    assert!(!event.refund_window_open(connection).unwrap());
+
+    let new_event_start = event.event_start.unwrap() + Duration::days(7);
+    let refund_window_ends_at = Utc::now().naive_utc() + Duration::days(5);
+    let rescheduled_event = event
+        .reschedule(
+            Some(user.id),
+            new_event_start,
+            Some(refund_window_ends_at),
+            connection,
+        )
+        .unwrap();
+    assert_eq!(rescheduled_event.event_start, Some(new_event_start));
+    assert_eq!(
+        rescheduled_event.door_time,
+        event.door_time.map(|d| d + Duration::days(7))
+    );
+    assert_eq!(
+        rescheduled_event.override_status,
+        Some(EventOverrideStatus::Rescheduled)
+    );
+    assert!(rescheduled_event.refund_window_open(connection).unwrap());
+
+    let reschedules = EventReschedule::find_for_event(event.id, connection).unwrap();
+    assert_eq!(reschedules.len(), 1);
+    assert_eq!(
+        reschedules[0].previous_event_start,
+        event.event_start.unwrap()
+    );
+    assert_eq!(reschedules[0].new_event_start, new_event_start);
+
+    // Sales windows in the future move with the event, ones that have opened stay open
+    let now = Utc::now().naive_utc();
+    let shift = |date: NaiveDateTime| {
+        if date > now {
+            date + Duration::days(7)
+        } else {
+            date
+        }
+    };
+    let rescheduled_ticket_type = TicketType::find(ticket_type.id, connection).unwrap();
+    assert_eq!(
+        rescheduled_ticket_type.start_date,
+        shift(ticket_type.start_date)
+    );
+    assert_eq!(
+        rescheduled_ticket_type.end_date,
+        shift(ticket_type.end_date)
+    );
+    let rescheduled_pricing = rescheduled_ticket_type
+        .valid_ticket_pricing(false, connection)
+        .unwrap();
+    assert_eq!(rescheduled_pricing.len(), pricing.len());
+    for (rescheduled, original) in rescheduled_pricing.iter().zip(pricing.iter()) {
+        assert_eq!(rescheduled.start_date, shift(original.start_date));
+        assert_eq!(rescheduled.end_date, shift(original.end_date));
+    }
+
+    // Refund window closes when the event starts at the latest
+    let rescheduled_event = rescheduled_event
+        .reschedule(
+            Some(user.id),
+            new_event_start,
+            Some(new_event_start + Duration::days(3)),
+            connection,
+        )
+        .unwrap();
+    let reschedules = EventReschedule::find_for_event(event.id, connection).unwrap();
+    assert!(reschedules
+        .iter()
+        .any(|r| r.refund_window_ends_at == Some(new_event_start)));
+    assert!(!reschedules
+        .iter()
+        .any(|r| r.refund_window_ends_at == Some(new_event_start + Duration::days(3))));
+
+    // Refund window must end in the future
+    let result = rescheduled_event.reschedule(
+        Some(user.id),
+        new_event_start + Duration::days(1),
+        Some(now - Duration::days(1)),
+        connection,
+    );
+    match result {
+        Ok(_) => panic!("Expected validation error"),
+        Err(error) => match &error.error_code {
+            ErrorCode::ValidationError { errors } => {
+                assert!(errors.contains_key("refund_window_ends_at"));
+            }
+            _ => panic!("Expected validation error"),
+        },
+    }
+
+    // Cancelled events cannot be rescheduled
+    let cancelled_event = rescheduled_event.cancel(None, connection).unwrap();
+    let result = cancelled_event.reschedule(Some(user.id), new_event_start, None, connection);
+    match result {
+        Ok(_) => panic!("Expected business process error"),
+        Err(error) => match &error.error_code {
+            ErrorCode::BusinessProcessError => {}
+            _ => panic!("Expected business process error"),
+        },
+    }
+}
+
+#[test]
+fn ticket_holders() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project
+        .create_event()
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let user = project.create_user().finish();
+    let user2 = project.create_user().finish();
+    project
+        .create_order()
+        .for_event(&event)
+        .for_user(&user)
+        .quantity(2)
+        .is_paid()
+        .finish();
+    project
+        .create_order()
+        .for_event(&event)
+        .for_user(&user2)
+        .finish();
+
+    assert_eq!(
+        Event::ticket_holders(event.id, connection).unwrap(),
+        vec![user]
+    );
+}
@@ -0,0 +1,52 @@
+use bigneon_db::dev::TestProject;
+use bigneon_db::prelude::*;
+
+#[test]
+fn create() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let organization = project.create_organization().finish();
+    let staff = project.create_user().finish();
+    let fan = project.create_user().finish();
+
+    let note = FanNote::create(
+        organization.id,
+        fan.id,
+        "Asked about accessible seating".to_string(),
+        staff.id,
+    )
+    .commit(connection)
+    .unwrap();
+    assert_eq!(note.note, "Asked about accessible seating");
+    assert_eq!(
+        FanNote::find_for_fan(organization.id, fan.id, connection).unwrap(),
+        vec![note]
+    );
+
+    assert!(
+        FanNote::create(organization.id, fan.id, "".to_string(), staff.id)
+            .commit(connection)
+            .is_err()
+    );
+}
+
+#[test]
+fn update_and_destroy() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let organization = project.create_organization().finish();
+    let staff = project.create_user().finish();
+    let fan = project.create_user().finish();
+    let note = FanNote::create(organization.id, fan.id, "Note".to_string(), staff.id)
+        .commit(connection)
+        .unwrap();
+
+    let note = note.update("Updated note".to_string(), connection).unwrap();
+    assert_eq!(note.note, "Updated note");
+    assert!(note.update("".to_string(), connection).is_err());
+
+    note.destroy(connection).unwrap();
+    assert!(FanNote::find_for_fan(organization.id, fan.id, connection)
+        .unwrap()
+        .is_empty());
+}
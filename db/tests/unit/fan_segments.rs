@@ -0,0 +1,303 @@
+use bigneon_db::dev::TestProject;
+use bigneon_db::prelude::*;
+use bigneon_db::utils::errors::ErrorCode::ValidationError;
+use uuid::Uuid;
+
+#[test]
+fn create() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let organization = project.create_organization().finish();
+    let user = project.create_user().finish();
+    let filters = FanSegmentFilters {
+        min_revenue_in_cents: Some(1000),
+        ..Default::default()
+    };
+
+    let fan_segment = FanSegment::create(
+        organization.id,
+        "Big spenders".to_string(),
+        filters.clone(),
+        user.id,
+    )
+    .commit(connection)
+    .unwrap();
+    assert_eq!(fan_segment.organization_id, organization.id);
+    assert_eq!(fan_segment.name, "Big spenders");
+    assert_eq!(fan_segment.parsed_filters().unwrap(), filters);
+
+    let result =
+        FanSegment::create(organization.id, "".to_string(), filters, user.id).commit(connection);
+    match result.unwrap_err().error_code {
+        ValidationError { errors } => assert!(errors.contains_key("name")),
+        _ => panic!("Expected validation error"),
+    }
+}
+
+#[test]
+fn create_with_filters_for_other_organization() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let organization = project.create_organization().finish();
+    let user = project.create_user().finish();
+    let other_event = project.create_event().finish();
+
+    let result = FanSegment::create(
+        organization.id,
+        "Interested".to_string(),
+        FanSegmentFilters {
+            interested_in_event_id: Some(other_event.id),
+            ..Default::default()
+        },
+        user.id,
+    )
+    .commit(connection);
+    match result.unwrap_err().error_code {
+        ValidationError { errors } => assert!(errors.contains_key("interested_in_event_id")),
+        _ => panic!("Expected validation error"),
+    }
+}
+
+#[test]
+fn update() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let organization = project.create_organization().finish();
+    let user = project.create_user().finish();
+    let fan_segment = FanSegment::create(
+        organization.id,
+        "Segment".to_string(),
+        Default::default(),
+        user.id,
+    )
+    .commit(connection)
+    .unwrap();
+
+    let filters = FanSegmentFilters {
+        tag: Some("VIP".to_string()),
+        ..Default::default()
+    };
+    let fan_segment = fan_segment
+        .update(
+            FanSegmentEditableAttributes {
+                name: Some("VIPs".to_string()),
+                filters: Some(filters.clone()),
+            },
+            connection,
+        )
+        .unwrap();
+    assert_eq!(fan_segment.name, "VIPs");
+    assert_eq!(fan_segment.parsed_filters().unwrap(), filters);
+}
+
+#[test]
+fn destroy() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let organization = project.create_organization().finish();
+    let event = project
+        .create_event()
+        .with_organization(&organization)
+        .finish();
+    let fan_segment = FanSegment::create(
+        organization.id,
+        "Segment".to_string(),
+        Default::default(),
+        user.id,
+    )
+    .commit(connection)
+    .unwrap();
+    let pending_broadcast = project
+        .create_broadcast()
+        .with_event_id(event.id)
+        .with_fan_segment(&fan_segment)
+        .finish();
+    let sent_broadcast = project
+        .create_broadcast()
+        .with_event_id(event.id)
+        .with_fan_segment(&fan_segment)
+        .finish()
+        .set_in_progress(connection)
+        .unwrap();
+
+    fan_segment.destroy(connection).unwrap();
+    assert!(FanSegment::find(fan_segment.id, connection).is_err());
+
+    // Broadcasts that have not been sent are not sent to a removed segment
+    let pending_broadcast = Broadcast::find(pending_broadcast.id, connection).unwrap();
+    assert_eq!(pending_broadcast.status, BroadcastStatus::Cancelled);
+    let sent_broadcast = Broadcast::find(sent_broadcast.id, connection).unwrap();
+    assert_eq!(sent_broadcast.status, BroadcastStatus::InProgress);
+    assert_eq!(sent_broadcast.fan_segment_id, None);
+}
+
+#[test]
+fn members() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let organization = project.create_organization().finish();
+    let staff = project.create_user().finish();
+    let event = project
+        .create_event()
+        .with_organization(&organization)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let ticket_type = &event.ticket_types(true, None, connection).unwrap()[0];
+
+    // Bought tickets
+    let buyer = project.create_user().finish();
+    project
+        .create_order()
+        .for_user(&buyer)
+        .for_event(&event)
+        .quantity(2)
+        .is_paid()
+        .finish();
+    // Interested without buying
+    let interested = project.create_user().finish();
+    EventInterest::create(event.id, interested.id)
+        .commit(connection)
+        .unwrap();
+    // Fan of another organization
+    let other_event = project.create_event().finish();
+    let other_fan = project.create_user().finish();
+    EventInterest::create(other_event.id, other_fan.id)
+        .commit(connection)
+        .unwrap();
+    FanTag::create(organization.id, buyer.id, "vip", staff.id)
+        .commit(connection)
+        .unwrap();
+
+    let member_ids = |filters: FanSegmentFilters| -> Vec<Uuid> {
+        let fan_segment =
+            FanSegment::create(organization.id, "Segment".to_string(), filters, staff.id)
+                .commit(connection)
+                .unwrap();
+        let mut ids: Vec<Uuid> = fan_segment
+            .all_members(connection)
+            .unwrap()
+            .into_iter()
+            .map(|f| f.user_id)
+            .collect();
+        ids.sort();
+        ids
+    };
+    let mut all = vec![buyer.id, interested.id];
+    all.sort();
+
+    assert_eq!(member_ids(Default::default()), all);
+    assert_eq!(
+        member_ids(FanSegmentFilters {
+            min_revenue_in_cents: Some(1),
+            ..Default::default()
+        }),
+        vec![buyer.id]
+    );
+    assert_eq!(
+        member_ids(FanSegmentFilters {
+            ticket_type_id: Some(ticket_type.id),
+            ..Default::default()
+        }),
+        vec![buyer.id]
+    );
+    assert_eq!(
+        member_ids(FanSegmentFilters {
+            interested_in_event_id: Some(event.id),
+            ..Default::default()
+        }),
+        vec![interested.id]
+    );
+    assert_eq!(
+        member_ids(FanSegmentFilters {
+            tag: Some("VIP".to_string()),
+            ..Default::default()
+        }),
+        vec![buyer.id]
+    );
+    assert!(member_ids(FanSegmentFilters {
+        min_events_attended: Some(1),
+        ..Default::default()
+    })
+    .is_empty());
+
+    // Paged members include order statistics
+    let fan_segment = FanSegment::create(
+        organization.id,
+        "Buyers".to_string(),
+        FanSegmentFilters {
+            min_revenue_in_cents: Some(1),
+            ..Default::default()
+        },
+        staff.id,
+    )
+    .commit(connection)
+    .unwrap();
+    let payload = fan_segment.members(0, 10, connection).unwrap();
+    assert_eq!(payload.paging.total, 1);
+    assert_eq!(payload.data[0].user_id, buyer.id);
+    assert_eq!(payload.data[0].order_count, Some(1));
+    assert!(payload.data[0].revenue_in_cents.unwrap() > 0);
+}
+
+#[test]
+fn members_for_box_office_orders() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let box_office_user = project.create_user().finish();
+    let organization = project
+        .create_organization()
+        .with_member(&box_office_user, Roles::OrgBoxOffice)
+        .finish();
+    let event = project
+        .create_event()
+        .with_organization(&organization)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let ticket_type = &event.ticket_types(true, None, connection).unwrap()[0];
+    let fan = project.create_user().finish();
+    project
+        .create_order()
+        .for_user(&box_office_user)
+        .on_behalf_of_user(&fan)
+        .for_event(&event)
+        .is_paid()
+        .finish();
+
+    // Orders are attributed to the fan they were placed for rather than the box office user
+    let fan_segment = FanSegment::create(
+        organization.id,
+        "Ticket holders".to_string(),
+        FanSegmentFilters {
+            min_revenue_in_cents: Some(1),
+            ticket_type_id: Some(ticket_type.id),
+            ..Default::default()
+        },
+        box_office_user.id,
+    )
+    .commit(connection)
+    .unwrap();
+    let payload = fan_segment.members(0, 10, connection).unwrap();
+    assert_eq!(payload.paging.total, 1);
+    assert_eq!(payload.data[0].user_id, fan.id);
+    assert_eq!(payload.data[0].order_count, Some(1));
+
+    // Interest in the event is fulfilled by the box office purchase
+    EventInterest::create(event.id, fan.id)
+        .commit(connection)
+        .unwrap();
+    let fan_segment = FanSegment::create(
+        organization.id,
+        "Interested".to_string(),
+        FanSegmentFilters {
+            interested_in_event_id: Some(event.id),
+            ..Default::default()
+        },
+        box_office_user.id,
+    )
+    .commit(connection)
+    .unwrap();
+    assert!(fan_segment.all_members(connection).unwrap().is_empty());
+}
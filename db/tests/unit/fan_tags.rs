@@ -0,0 +1,62 @@
+use bigneon_db::dev::TestProject;
+use bigneon_db::prelude::*;
+
+#[test]
+fn create() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let organization = project.create_organization().finish();
+    let staff = project.create_user().finish();
+    let fan = project.create_user().finish();
+
+    let tag = FanTag::create(organization.id, fan.id, " VIP ", staff.id)
+        .commit(connection)
+        .unwrap();
+    assert_eq!(tag.name, "VIP");
+    assert_eq!(tag.created_by_user_id, staff.id);
+
+    // Adding the same tag again returns the existing tag
+    let duplicate = FanTag::create(organization.id, fan.id, "vip", staff.id)
+        .commit(connection)
+        .unwrap();
+    assert_eq!(duplicate, tag);
+
+    assert!(FanTag::create(organization.id, fan.id, "  ", staff.id)
+        .commit(connection)
+        .is_err());
+}
+
+#[test]
+fn find_for_fan() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let organization = project.create_organization().finish();
+    let other_organization = project.create_organization().finish();
+    let staff = project.create_user().finish();
+    let fan = project.create_user().finish();
+
+    let tag = FanTag::create(organization.id, fan.id, "VIP", staff.id)
+        .commit(connection)
+        .unwrap();
+    let tag2 = FanTag::create(organization.id, fan.id, "Early bird", staff.id)
+        .commit(connection)
+        .unwrap();
+    FanTag::create(other_organization.id, fan.id, "Press", staff.id)
+        .commit(connection)
+        .unwrap();
+
+    assert_eq!(
+        FanTag::find_for_fan(organization.id, fan.id, connection).unwrap(),
+        vec![tag2.clone(), tag.clone()]
+    );
+    assert_eq!(
+        FanTag::names_for_organization(organization.id, connection).unwrap(),
+        vec!["Early bird".to_string(), "VIP".to_string()]
+    );
+
+    tag.destroy(connection).unwrap();
+    assert_eq!(
+        FanTag::find_for_fan(organization.id, fan.id, connection).unwrap(),
+        vec![tag2]
+    );
+}
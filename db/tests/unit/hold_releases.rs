@@ -0,0 +1,206 @@
+use bigneon_db::dev::TestProject;
+use bigneon_db::models::*;
+use bigneon_db::utils::errors::ErrorCode::{BusinessProcessError, ValidationError};
+use chrono::prelude::*;
+use time::Duration;
+
+#[test]
+fn create() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let hold = project.create_hold().finish();
+    let release_at = Utc::now().naive_utc() + Duration::days(2);
+
+    let release = HoldRelease::create(hold.id, release_at, Some(3))
+        .commit(None, connection)
+        .unwrap();
+    assert_eq!(release.hold_id, hold.id);
+    assert_eq!(release.quantity, Some(3));
+    assert_eq!(release.status, HoldReleaseStatus::Pending);
+    assert!(DomainAction::has_pending_action(
+        DomainActionTypes::ReleaseHoldInventory,
+        Tables::HoldReleases.to_string(),
+        release.id,
+        connection
+    )
+    .unwrap());
+
+    // Scheduled in the future so not yet picked up
+    let pending =
+        DomainAction::find_pending(Some(DomainActionTypes::ReleaseHoldInventory), connection)
+            .unwrap();
+    assert!(pending.iter().all(|a| a.main_table_id != Some(release.id)));
+
+    let result = HoldRelease::create(hold.id, release_at, Some(0)).commit(None, connection);
+    match result {
+        Ok(_) => panic!("Expected validation error"),
+        Err(error) => match &error.error_code {
+            ValidationError { errors } => {
+                assert!(errors.contains_key("quantity"));
+                assert_eq!(
+                    errors["quantity"][0].code,
+                    "Quantity to release must be greater than 0"
+                );
+            }
+            _ => panic!("Expected validation error"),
+        },
+    }
+}
+
+#[test]
+fn release() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let hold = project.create_hold().with_quantity(10).finish();
+    let user = project.create_user().finish();
+
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
+    cart.update_quantities(
+        user.id,
+        &vec![UpdateOrderItem {
+            ticket_type_id: hold.ticket_type_id,
+            quantity: 2,
+            redemption_code: hold.redemption_code.clone(),
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+    let total = cart.calculate_total(connection).unwrap();
+    cart.add_external_payment(
+        Some("test".to_string()),
+        ExternalPaymentType::CreditCard,
+        user.id,
+        total,
+        connection,
+    )
+    .unwrap();
+
+    // Staged release of a fixed quantity
+    let release = HoldRelease::create(hold.id, Utc::now().naive_utc(), Some(3))
+        .commit(None, connection)
+        .unwrap()
+        .release(connection)
+        .unwrap();
+    assert_eq!(release.status, HoldReleaseStatus::Released);
+    assert_eq!(release.released_quantity, Some(3));
+    assert_eq!(release.used_quantity, Some(2));
+    assert!(release.released_at.is_some());
+    assert_eq!(hold.quantity(connection).unwrap(), (7, 5));
+
+    // Releasing again has no effect
+    let release = release.release(connection).unwrap();
+    assert_eq!(release.released_quantity, Some(3));
+    assert_eq!(hold.quantity(connection).unwrap(), (7, 5));
+
+    // Releasing everything remaining
+    let release = HoldRelease::create(hold.id, Utc::now().naive_utc(), None)
+        .commit(None, connection)
+        .unwrap()
+        .release(connection)
+        .unwrap();
+    assert_eq!(release.released_quantity, Some(5));
+    assert_eq!(release.used_quantity, Some(2));
+    assert_eq!(hold.quantity(connection).unwrap(), (2, 0));
+
+    let domain_events = DomainEvent::find(
+        Tables::Holds,
+        Some(hold.id),
+        Some(DomainEventTypes::HoldReleased),
+        connection,
+    )
+    .unwrap();
+    assert_eq!(domain_events.len(), 2);
+
+    let summary = HoldRelease::summary_for_hold(&hold, connection).unwrap();
+    assert_eq!(summary.quantity, 2);
+    assert_eq!(summary.available, 0);
+    assert_eq!(summary.used_quantity, 2);
+    assert_eq!(summary.released_quantity, 8);
+    assert_eq!(summary.releases.len(), 2);
+}
+
+#[test]
+fn release_more_than_available() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let hold = project.create_hold().with_quantity(4).finish();
+
+    let release = HoldRelease::create(hold.id, Utc::now().naive_utc(), Some(10))
+        .commit(None, connection)
+        .unwrap()
+        .release(connection)
+        .unwrap();
+    assert_eq!(release.released_quantity, Some(4));
+    assert_eq!(release.used_quantity, Some(0));
+    assert_eq!(hold.quantity(connection).unwrap(), (0, 0));
+}
+
+#[test]
+fn release_deleted_hold() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let hold = project.create_hold().with_quantity(4).finish();
+    let release = HoldRelease::create(hold.id, Utc::now().naive_utc(), None)
+        .commit(None, connection)
+        .unwrap();
+    hold.destroy(None, connection).unwrap();
+
+    let release = release.release(connection).unwrap();
+    assert_eq!(release.status, HoldReleaseStatus::Cancelled);
+    assert_eq!(release.released_quantity, None);
+}
+
+#[test]
+fn cancel() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let hold = project.create_hold().with_quantity(4).finish();
+    let user = project.create_user().finish();
+    let release = HoldRelease::create(hold.id, Utc::now().naive_utc(), None)
+        .commit(None, connection)
+        .unwrap();
+
+    let release = release.cancel(Some(user.id), connection).unwrap();
+    assert_eq!(release.status, HoldReleaseStatus::Cancelled);
+
+    // Cancelled releases are skipped when the action runs
+    let release = release.release(connection).unwrap();
+    assert_eq!(release.status, HoldReleaseStatus::Cancelled);
+    assert_eq!(hold.quantity(connection).unwrap(), (4, 4));
+
+    let result = release.cancel(Some(user.id), connection);
+    match result {
+        Ok(_) => panic!("Expected business process error"),
+        Err(error) => match &error.error_code {
+            BusinessProcessError => {}
+            _ => panic!("Expected business process error"),
+        },
+    }
+}
+
+#[test]
+fn schedule_release() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let end_at = Utc::now().naive_utc() + Duration::days(2);
+    let hold = project.create_hold().with_end_at(end_at).finish();
+
+    // Defaults to the end of the hold
+    let release = hold.schedule_release(None, None, None, connection).unwrap();
+    assert_eq!(Some(release.release_at), hold.end_at);
+    assert_eq!(release.quantity, None);
+
+    let hold = project.create_hold().finish();
+    let result = hold.schedule_release(None, None, Some(2), connection);
+    match result {
+        Ok(_) => panic!("Expected validation error"),
+        Err(error) => match &error.error_code {
+            ValidationError { errors } => {
+                assert!(errors.contains_key("release_at"));
+            }
+            _ => panic!("Expected validation error"),
+        },
+    }
+}
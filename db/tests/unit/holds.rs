@@ -140,6 +140,7 @@ pub fn confirm_hold_valid() {
 #[test]
 fn update() {
     let db = TestProject::new();
+    let user = db.create_user().finish();
     let hold = db.create_hold().finish();
 
     let update_patch = UpdateHoldAttributes {
@@ -149,11 +150,28 @@ fn update() {
         name: Some("New name".to_string()),
         ..Default::default()
     };
-    let new_hold = hold.update(update_patch, db.get_connection()).unwrap();
+    let new_hold = hold
+        .update(Some(user.id), update_patch, db.get_connection())
+        .unwrap();
     assert_eq!(new_hold.name, "New name".to_string());
     assert_eq!(new_hold.max_per_user, None);
     assert_eq!(new_hold.end_at, None);
     assert_eq!(new_hold.discount_in_cents, Some(10));
+
+    let domain_events = DomainEvent::find(
+        Tables::Holds,
+        Some(hold.id),
+        Some(DomainEventTypes::HoldUpdated),
+        db.get_connection(),
+    )
+    .unwrap();
+    assert_eq!(domain_events.len(), 1);
+    assert_eq!(domain_events[0].user_id, Some(user.id));
+    let changes = &domain_events[0].event_data.as_ref().unwrap()["changes"];
+    assert_eq!(changes["name"]["before"], json!(hold.name));
+    assert_eq!(changes["name"]["after"], json!("New name"));
+    // Unchanged fields are left out
+    assert_eq!(changes.get("discount_in_cents"), None);
 }
 
 #[test]
@@ -172,7 +190,7 @@ fn update_with_validation_errors() {
         hold_type: Some(HoldTypes::Discount),
         ..Default::default()
     };
-    let result = hold.update(update_patch, connection);
+    let result = hold.update(None, update_patch, connection);
     match result {
         Ok(_) => {
             panic!("Expected validation error");
@@ -193,7 +211,7 @@ fn update_with_validation_errors() {
         redemption_code: Some(hold2.redemption_code),
         ..Default::default()
     };
-    let result = hold.update(update_patch, connection);
+    let result = hold.update(None, update_patch, connection);
     match result {
         Ok(_) => {
             panic!("Expected validation error");
@@ -214,7 +232,7 @@ fn update_with_validation_errors() {
         redemption_code: Some(Some(code.redemption_code)),
         ..Default::default()
     };
-    let result = hold.update(update_patch, connection);
+    let result = hold.update(None, update_patch, connection);
     match result {
         Ok(_) => {
             panic!("Expected validation error");
@@ -373,6 +391,114 @@ fn destroy() {
     );
 }
 
+#[test]
+fn issue_comp() {
+    let db = TestProject::new();
+    let connection = db.get_connection();
+    let creator = db.create_user().finish();
+    let hold = db.create_hold().with_hold_type(HoldTypes::Comp).finish();
+
+    let issued_comp = hold
+        .issue_comp(
+            creator.id,
+            "Jane Smith".to_string(),
+            Some("jane@example.com".to_string()),
+            None,
+            2,
+            None,
+            connection,
+        )
+        .unwrap();
+    assert_eq!(issued_comp.comp.parent_hold_id, Some(hold.id));
+    assert_eq!(issued_comp.comp.hold_type, HoldTypes::Comp);
+    assert!(issued_comp.comp.redemption_code.is_some());
+
+    // Recipient without an account gets a stub user
+    assert!(issued_comp.recipient_created);
+    assert_eq!(issued_comp.recipient.first_name, Some("Jane".to_string()));
+    assert_eq!(issued_comp.recipient.last_name, Some("Smith".to_string()));
+
+    let order = Order::find(issued_comp.order.id, connection).unwrap();
+    assert_eq!(order.status, OrderStatus::Paid);
+    assert_eq!(order.user_id, creator.id);
+    assert_eq!(order.on_behalf_of_user_id, Some(issued_comp.recipient.id));
+    assert_eq!(order.calculate_total(connection).unwrap(), 0);
+
+    // Tickets are in the recipient's wallet, with a transfer they can claim them from
+    let transfer_authorization = &issued_comp.transfer_authorization;
+    assert_eq!(
+        transfer_authorization.sender_user_id,
+        issued_comp.recipient.id
+    );
+    assert_eq!(transfer_authorization.num_tickets, 2);
+    let wallet = issued_comp.recipient.default_wallet(connection).unwrap();
+    let ticket_ids = TicketInstance::find_ids_for_order(order.id, connection).unwrap();
+    assert_eq!(ticket_ids.len(), 2);
+    for ticket_id in ticket_ids {
+        let ticket = TicketInstance::find(ticket_id, connection).unwrap();
+        assert_eq!(ticket.wallet_id, wallet.id);
+        assert_eq!(ticket.status, TicketInstanceStatus::Purchased);
+        assert_eq!(
+            ticket.transfer_key,
+            Some(transfer_authorization.transfer_key)
+        );
+    }
+
+    // Existing user is found by email
+    let recipient = db
+        .create_user()
+        .with_email("existing@example.com".to_string())
+        .finish();
+    let issued_comp2 = hold
+        .issue_comp(
+            creator.id,
+            "Jane".to_string(),
+            recipient.email.clone(),
+            None,
+            1,
+            Some("JANESECONDCOMP".to_string()),
+            connection,
+        )
+        .unwrap();
+    assert!(!issued_comp2.recipient_created);
+    assert_eq!(issued_comp2.recipient.id, recipient.id);
+    assert_eq!(
+        issued_comp2.comp.redemption_code,
+        Some("JANESECONDCOMP".to_string())
+    );
+    let wallet = recipient.default_wallet(connection).unwrap();
+    let ticket_ids = TicketInstance::find_ids_for_order(issued_comp2.order.id, connection).unwrap();
+    assert_eq!(ticket_ids.len(), 1);
+    assert_eq!(
+        TicketInstance::find(ticket_ids[0], connection)
+            .unwrap()
+            .wallet_id,
+        wallet.id
+    );
+
+    // Email or phone is required
+    let result = hold.issue_comp(
+        creator.id,
+        "Nobody".to_string(),
+        None,
+        None,
+        1,
+        None,
+        connection,
+    );
+    match result {
+        Ok(_) => {
+            panic!("Expected validation error");
+        }
+        Err(error) => match &error.error_code {
+            ValidationError { errors } => {
+                assert!(errors.contains_key("email"));
+            }
+            _ => panic!("Expected validation error"),
+        },
+    }
+}
+
 #[test]
 fn set_quantity() {
     let db = TestProject::new();
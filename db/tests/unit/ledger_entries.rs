@@ -0,0 +1,122 @@
+use bigneon_db::dev::TestProject;
+use bigneon_db::prelude::*;
+use bigneon_db::schema::ledger_entries;
+use diesel;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+fn create_asset(issuer: &Wallet, conn: &PgConnection) -> String {
+    let asset_id = Uuid::new_v4().to_string();
+    LedgerEntry::append(
+        &asset_id,
+        LEDGER_CREATE_ASSET,
+        json!({
+            "name": "VIP",
+            "total_supply": 10,
+            "authorised_signers": [],
+            "rule_flags": 0,
+            "rule_metadata": "",
+            "expiry_date": 0
+        }),
+        &issuer.secret_key,
+        &issuer.public_key,
+        conn,
+    )
+    .unwrap();
+    asset_id
+}
+
+#[test]
+fn append() {
+    let project = TestProject::new();
+    let conn = project.get_connection();
+    let issuer = Wallet::find_default_for_user(project.create_user().finish().id, conn).unwrap();
+    let owner = Wallet::find_default_for_user(project.create_user().finish().id, conn).unwrap();
+    let asset_id = create_asset(&issuer, conn);
+
+    let entry = LedgerEntry::append(
+        &asset_id,
+        LEDGER_TRANSFER_TOKEN,
+        json!({"asset_id": asset_id, "token_ids": [1, 2], "new_owner": owner.public_key}),
+        &issuer.secret_key,
+        &issuer.public_key,
+        conn,
+    )
+    .unwrap();
+    assert_eq!(entry.sequence, 1);
+    assert!(entry.verify());
+    let entries = LedgerEntry::find_for_asset(&asset_id, conn).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entry.previous_signature, Some(entries[0].signature.clone()));
+
+    // Only the owner can transfer tokens
+    let result = LedgerEntry::append(
+        &asset_id,
+        LEDGER_TRANSFER_TOKEN,
+        json!({"asset_id": asset_id, "token_ids": [1], "new_owner": issuer.public_key}),
+        &issuer.secret_key,
+        &issuer.public_key,
+        conn,
+    );
+    assert!(result.is_err());
+
+    // Only the issuer can increase the supply
+    let result = LedgerEntry::append(
+        &asset_id,
+        LEDGER_MODIFY_ASSET,
+        json!({"asset_id": asset_id, "request_type": 2, "new_supply": 20}),
+        &owner.secret_key,
+        &owner.public_key,
+        conn,
+    );
+    assert!(result.is_err());
+
+    LedgerEntry::append(
+        &asset_id,
+        LEDGER_MODIFY_ASSET,
+        json!({"asset_id": asset_id, "request_type": 5, "token_ids": [1]}),
+        &owner.secret_key,
+        &owner.public_key,
+        conn,
+    )
+    .unwrap();
+
+    let asset = LedgerAsset::load(&asset_id, conn).unwrap();
+    assert_eq!(asset.total_supply, 10);
+    assert_eq!(asset.issuer, issuer.public_key);
+    assert_eq!(asset.token_owner(1), owner.public_key.as_str());
+    assert_eq!(asset.token_owner(3), issuer.public_key.as_str());
+    assert!(asset.redeemed_token_ids.contains(&1));
+    assert_eq!(asset.sequence, 3);
+
+    // Entries cannot be changed once written
+    let result = diesel::update(ledger_entries::table.filter(ledger_entries::id.eq(entry.id)))
+        .set(ledger_entries::payload.eq(json!({})))
+        .execute(conn);
+    assert!(result.is_err());
+}
+
+#[test]
+fn verify() {
+    let project = TestProject::new();
+    let conn = project.get_connection();
+    let issuer = Wallet::find_default_for_user(project.create_user().finish().id, conn).unwrap();
+    let asset_id = create_asset(&issuer, conn);
+    let mut entry = LedgerEntry::find_for_asset(&asset_id, conn)
+        .unwrap()
+        .remove(0);
+    assert!(entry.verify());
+
+    entry.payload = json!({
+        "name": "VIP",
+        "total_supply": 1000,
+        "authorised_signers": [],
+        "rule_flags": 0,
+        "rule_metadata": "",
+        "expiry_date": 0
+    });
+    assert!(!entry.verify());
+
+    // Unknown assets do not load
+    assert!(LedgerAsset::load("unknown", conn).is_err());
+}
@@ -0,0 +1,83 @@
+use bigneon_db::dev::TestProject;
+use bigneon_db::prelude::*;
+
+#[test]
+fn record() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let organization = project.create_organization().finish();
+    let user = project.create_user().finish();
+
+    let consent = MarketingConsent::record(
+        user.id,
+        organization.id,
+        true,
+        MarketingConsentSources::Registration,
+        connection,
+    )
+    .unwrap();
+    assert!(consent.consented);
+    assert_eq!(consent.source, MarketingConsentSources::Registration);
+
+    // A later answer replaces the earlier one
+    let updated = MarketingConsent::record(
+        user.id,
+        organization.id,
+        false,
+        MarketingConsentSources::Checkout,
+        connection,
+    )
+    .unwrap();
+    assert_eq!(updated.id, consent.id);
+    assert!(!updated.consented);
+    assert_eq!(updated.source, MarketingConsentSources::Checkout);
+    assert_eq!(
+        MarketingConsent::find_for_user(user.id, connection).unwrap(),
+        vec![updated]
+    );
+}
+
+#[test]
+fn consented_user_ids() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let organization = project.create_organization().finish();
+    let other_organization = project.create_organization().finish();
+    let user = project.create_user().finish();
+    let user2 = project.create_user().finish();
+    let user3 = project.create_user().finish();
+
+    MarketingConsent::record(
+        user.id,
+        organization.id,
+        true,
+        MarketingConsentSources::Checkout,
+        connection,
+    )
+    .unwrap();
+    MarketingConsent::record(
+        user2.id,
+        organization.id,
+        false,
+        MarketingConsentSources::Checkout,
+        connection,
+    )
+    .unwrap();
+    MarketingConsent::record(
+        user3.id,
+        other_organization.id,
+        true,
+        MarketingConsentSources::Checkout,
+        connection,
+    )
+    .unwrap();
+
+    assert_eq!(
+        MarketingConsent::consented_user_ids(organization.id, connection).unwrap(),
+        vec![user.id]
+    );
+    assert_eq!(
+        MarketingConsent::consented_user_ids(other_organization.id, connection).unwrap(),
+        vec![user3.id]
+    );
+}
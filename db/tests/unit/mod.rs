@@ -6,20 +6,31 @@ pub mod comps;
 pub mod concerns;
 pub mod domain_actions;
 pub mod domain_events;
+pub mod email_suppressions;
 pub mod event_artists;
 pub mod event_interest;
+pub mod event_recommendations;
+pub mod event_series;
 pub mod events;
+pub mod fan_notes;
+pub mod fan_segments;
+pub mod fan_tags;
 pub mod fee_schedule_ranges;
 pub mod fee_schedules;
+pub mod hold_releases;
 pub mod holds;
+pub mod ledger_entries;
+pub mod marketing_consents;
 pub mod order_items;
 pub mod orders;
+pub mod organization_email_settings;
 pub mod organization_invites;
 pub mod organization_users;
 pub mod organizations;
 pub mod payment_methods;
 pub mod payments;
 pub mod push_notification_tokens;
+pub mod refund_requests;
 pub mod refunded_tickets;
 pub mod regions;
 pub mod reports;
@@ -30,5 +41,8 @@ pub mod ticket_instances;
 pub mod ticket_pricing;
 pub mod ticket_type_codes;
 pub mod ticket_types;
+pub mod unique_codes;
+pub mod user_data_exports;
 pub mod users;
 pub mod venues;
+pub mod wallets;
@@ -193,6 +193,7 @@ fn confirm_code_valid() {
     let start_date = NaiveDateTime::from(Utc::now().naive_utc() - Duration::days(3));
     let end_date = NaiveDateTime::from(Utc::now().naive_utc() - Duration::days(2));
     code.update(
+        None,
         UpdateCodeAttributes {
             start_date: Some(start_date),
             end_date: Some(end_date),
@@ -2,10 +2,11 @@ use bigneon_db::dev::times;
 use bigneon_db::dev::TestProject;
 use bigneon_db::models::*;
 use bigneon_db::schema::{orders, ticket_instances};
-use bigneon_db::utils::errors::ErrorCode::ValidationError;
+use bigneon_db::utils::errors::ErrorCode::{BusinessProcessError, ValidationError};
 use chrono::prelude::*;
 use diesel;
 use diesel::prelude::*;
+use std::collections::HashMap;
 use time::Duration;
 use uuid::Uuid;
 
@@ -113,6 +114,7 @@ fn update_quantities_check_limits() {
     // Ticket type with limit
     let ticket_type = ticket_type
         .update(
+            None,
             TicketTypeEditableAttributes {
                 limit_per_person: Some(3),
                 ..Default::default()
@@ -569,6 +571,7 @@ fn items_valid_for_purchase() {
     assert!(cart.items_valid_for_purchase(connection).unwrap());
 
     code.update(
+        None,
         UpdateCodeAttributes {
             end_date: Some(one_minute_ago),
             ..Default::default()
@@ -596,6 +599,7 @@ fn items_valid_for_purchase() {
     assert!(cart.items_valid_for_purchase(connection).unwrap());
 
     hold.update(
+        None,
         UpdateHoldAttributes {
             end_at: Some(Some(one_minute_ago)),
             ..Default::default()
@@ -974,6 +978,292 @@ fn refund() {
     // Reload fee item
     let fee_item = order_item.find_fee_item(connection).unwrap().unwrap();
     assert_eq!(fee_item.refunded_quantity, 1);
+
+    let domain_events = DomainEvent::find(
+        Tables::Orders,
+        Some(cart.id),
+        Some(DomainEventTypes::OrderRefunded),
+        connection,
+    )
+    .unwrap();
+    assert_eq!(domain_events.len(), 1);
+    assert_eq!(domain_events[0].organization_id, Some(organization.id));
+    assert_eq!(
+        domain_events[0].event_data.as_ref().unwrap()["amount"],
+        json!(refund_amount)
+    );
+}
+
+#[test]
+fn refund_spanning_organizations() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let organization = project.create_organization().finish();
+    let organization2 = project.create_organization().finish();
+    let event = project
+        .create_event()
+        .with_organization(&organization)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let event2 = project
+        .create_event()
+        .with_organization(&organization2)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let user = project.create_user().finish();
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
+    let ticket_type = &event.ticket_types(true, None, connection).unwrap()[0];
+    let ticket_type2 = &event2.ticket_types(true, None, connection).unwrap()[0];
+    cart.update_quantities(
+        user.id,
+        &[
+            UpdateOrderItem {
+                ticket_type_id: ticket_type.id,
+                quantity: 1,
+                redemption_code: None,
+            },
+            UpdateOrderItem {
+                ticket_type_id: ticket_type2.id,
+                quantity: 1,
+                redemption_code: None,
+            },
+        ],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+    let total = cart.calculate_total(connection).unwrap();
+    cart.add_external_payment(
+        Some("Test".to_string()),
+        ExternalPaymentType::CreditCard,
+        user.id,
+        total,
+        connection,
+    )
+    .unwrap();
+
+    let items = cart.items(&connection).unwrap();
+    let mut refund_items = Vec::new();
+    let mut refund_amounts = HashMap::new();
+    for (organization_id, ticket_type_id) in &[
+        (organization.id, ticket_type.id),
+        (organization2.id, ticket_type2.id),
+    ] {
+        let order_item = items
+            .iter()
+            .find(|i| i.ticket_type_id == Some(*ticket_type_id))
+            .unwrap();
+        let ticket = &TicketInstance::find_for_order_item(order_item.id, connection).unwrap()[0];
+        refund_items.push(RefundItem {
+            order_item_id: order_item.id,
+            ticket_instance_id: Some(ticket.id),
+        });
+        let fee_item = order_item.find_fee_item(connection).unwrap();
+        refund_amounts.insert(
+            *organization_id,
+            order_item.unit_price_in_cents + fee_item.map(|f| f.unit_price_in_cents).unwrap_or(0),
+        );
+    }
+    let refunds_by_organization = cart
+        .refund_by_organization(refund_items, user.id, connection)
+        .unwrap();
+    assert_eq!(refunds_by_organization.len(), 2);
+    for (organization_id, amount) in &refunds_by_organization {
+        assert_eq!(*amount, refund_amounts[&organization_id.unwrap()] as u32);
+    }
+
+    // Each organization's audit log gets its own part of the refund
+    let domain_events = DomainEvent::find(
+        Tables::Orders,
+        Some(cart.id),
+        Some(DomainEventTypes::OrderRefunded),
+        connection,
+    )
+    .unwrap();
+    assert_eq!(domain_events.len(), 2);
+    for domain_event in domain_events {
+        let organization_id = domain_event.organization_id.unwrap();
+        let event_data = domain_event.event_data.unwrap();
+        assert_eq!(event_data["refund_items"].as_array().unwrap().len(), 1);
+        assert_eq!(
+            event_data["amount"],
+            json!(refund_amounts[&organization_id])
+        );
+    }
+}
+
+#[test]
+fn validate_self_serve_refund() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let admin = project.create_user().finish();
+    let organization = project
+        .create_organization()
+        .with_fee_schedule(&project.create_fee_schedule().finish(admin.id))
+        .finish();
+    let event = project
+        .create_event()
+        .with_organization(&organization)
+        .with_ticket_pricing()
+        .finish();
+    let user = project.create_user().finish();
+    let order = project
+        .create_order()
+        .for_event(&event)
+        .for_user(&user)
+        .quantity(2)
+        .is_paid()
+        .finish();
+    let mut tickets = TicketInstance::find_for_user(user.id, connection).unwrap();
+    let redeemed_ticket = tickets.remove(0);
+    let ticket = tickets.remove(0);
+    TicketInstance::redeem_ticket(
+        redeemed_ticket.id,
+        redeemed_ticket.redeem_key.clone().unwrap(),
+        admin.id,
+        connection,
+    )
+    .unwrap();
+
+    let refund_item = |ticket: &TicketInstance| RefundItem {
+        order_item_id: ticket.order_item_id.unwrap(),
+        ticket_instance_id: Some(ticket.id),
+    };
+    assert!(order
+        .validate_self_serve_refund(&[refund_item(&ticket)], connection)
+        .is_ok());
+
+    let result = order.validate_self_serve_refund(
+        &[refund_item(&ticket), refund_item(&redeemed_ticket)],
+        connection,
+    );
+    match result {
+        Ok(_) => panic!("Expected business process error"),
+        Err(error) => match &error.error_code {
+            BusinessProcessError => {}
+            _ => panic!("Expected business process error"),
+        },
+    }
+}
+
+#[test]
+fn refund_with_buy_x_get_y_code() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let creator = project.create_user().finish();
+    let organization = project
+        .create_organization()
+        .with_fee_schedule(&project.create_fee_schedule().finish(creator.id))
+        .finish();
+    let event = project
+        .create_event()
+        .with_organization(&organization)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let user = project.create_user().finish();
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
+    let ticket_type = &event.ticket_types(true, None, connection).unwrap()[0];
+    let code = project
+        .create_code()
+        .with_event(&event)
+        .for_ticket_type(&ticket_type)
+        .finish();
+    let code = code
+        .update(
+            None,
+            UpdateCodeAttributes {
+                buy_quantity: Some(Some(2)),
+                get_quantity: Some(Some(1)),
+                discount_in_cents: Some(None),
+                discount_as_percentage: Some(Some(100)),
+                ..Default::default()
+            },
+            connection,
+        )
+        .unwrap();
+    cart.update_quantities(
+        user.id,
+        &[UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 3,
+            redemption_code: Some(code.redemption_code.clone()),
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+
+    let total = cart.calculate_total(connection).unwrap();
+    cart.add_external_payment(
+        Some("Test".to_string()),
+        ExternalPaymentType::CreditCard,
+        user.id,
+        total,
+        connection,
+    )
+    .unwrap();
+
+    let items = cart.items(&connection).unwrap();
+    let order_item = items
+        .iter()
+        .find(|i| i.ticket_type_id == Some(ticket_type.id))
+        .unwrap();
+    let discount_item = order_item.find_discount_item(connection).unwrap().unwrap();
+    assert_eq!(discount_item.quantity, 1);
+    assert_eq!(
+        discount_item.unit_price_in_cents,
+        -order_item.unit_price_in_cents
+    );
+    let fee_item = order_item.find_fee_item(connection).unwrap().unwrap();
+    let tickets = TicketInstance::find_for_order_item(order_item.id, connection).unwrap();
+
+    // Discount items cannot be refunded on their own
+    assert!(cart
+        .refund(
+            vec![RefundItem {
+                order_item_id: discount_item.id,
+                ticket_instance_id: None,
+            }],
+            user.id,
+            connection,
+        )
+        .is_err());
+
+    // Remaining 2 tickets no longer qualify for the free ticket so only the fee is returned
+    assert_eq!(
+        cart.refund(
+            vec![RefundItem {
+                order_item_id: order_item.id,
+                ticket_instance_id: Some(tickets[0].id),
+            }],
+            user.id,
+            connection,
+        )
+        .unwrap(),
+        fee_item.unit_price_in_cents as u32
+    );
+    let discount_item = order_item.find_discount_item(connection).unwrap().unwrap();
+    assert_eq!(discount_item.refunded_quantity, 1);
+
+    assert_eq!(
+        cart.refund(
+            vec![RefundItem {
+                order_item_id: order_item.id,
+                ticket_instance_id: Some(tickets[1].id),
+            }],
+            user.id,
+            connection,
+        )
+        .unwrap(),
+        (order_item.unit_price_in_cents + fee_item.unit_price_in_cents) as u32
+    );
+    let fee_item = order_item.find_fee_item(connection).unwrap().unwrap();
+    assert_eq!(fee_item.refunded_quantity, 2);
 }
 
 #[test]
@@ -1085,7 +1375,9 @@ fn add_tickets_with_increment() {
         increment: Some(4),
         ..Default::default()
     };
-    let ticket_type = ticket_type.update(update_parameters, connection).unwrap();
+    let ticket_type = ticket_type
+        .update(None, update_parameters, connection)
+        .unwrap();
 
     let add_tickets_result = cart.update_quantities(
         user.id,
@@ -1632,7 +1924,9 @@ fn remove_tickets_with_increment() {
         increment: Some(4),
         ..Default::default()
     };
-    let ticket_type = ticket_type.update(update_parameters, connection).unwrap();
+    let ticket_type = ticket_type
+        .update(None, update_parameters, connection)
+        .unwrap();
     let add_tickets_result = cart.update_quantities(
         user.id,
         &[UpdateOrderItem {
@@ -2003,6 +2297,7 @@ fn add_external_payment_for_expired_code() {
     let start_date = NaiveDateTime::from(Utc::now().naive_utc() - Duration::days(3));
     let end_date = NaiveDateTime::from(Utc::now().naive_utc() - Duration::days(2));
     code.update(
+        None,
         UpdateCodeAttributes {
             start_date: Some(start_date),
             end_date: Some(end_date),
@@ -2040,6 +2335,123 @@ fn add_external_payment_for_expired_code() {
     }
 }
 
+#[test]
+fn update_quantities_with_discount_rules() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let creator = project.create_user().finish();
+    let organization = project
+        .create_organization()
+        .with_fee_schedule(&project.create_fee_schedule().finish(creator.id))
+        .finish();
+    let event = project
+        .create_event()
+        .with_organization(&organization)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let user = project.create_user().finish();
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
+    let ticket_type = &event.ticket_types(true, None, connection).unwrap()[0];
+    let code = project
+        .create_code()
+        .with_event(&event)
+        .for_ticket_type(&ticket_type)
+        .finish();
+    let code = code
+        .update(
+            None,
+            UpdateCodeAttributes {
+                buy_quantity: Some(Some(2)),
+                get_quantity: Some(Some(1)),
+                discount_in_cents: Some(None),
+                discount_as_percentage: Some(Some(100)),
+                ..Default::default()
+            },
+            connection,
+        )
+        .unwrap();
+    let update_quantity = |cart: &mut Order, quantity: u32| {
+        cart.update_quantities(
+            user.id,
+            &[UpdateOrderItem {
+                ticket_type_id: ticket_type.id,
+                quantity,
+                redemption_code: Some(code.redemption_code.clone()),
+            }],
+            false,
+            false,
+            connection,
+        )
+        .unwrap();
+        let items = cart.items(&connection).unwrap();
+        items
+            .into_iter()
+            .find(|i| i.ticket_type_id == Some(ticket_type.id))
+            .unwrap()
+    };
+
+    // Buy 2 get 1 free
+    let order_item = update_quantity(&mut cart, 7);
+    let unit_price_in_cents = order_item.unit_price_in_cents;
+    let discount_item = order_item.find_discount_item(connection).unwrap().unwrap();
+    assert_eq!(discount_item.quantity, 2);
+    assert_eq!(discount_item.unit_price_in_cents, -unit_price_in_cents);
+    let fee_item = order_item.find_fee_item(connection).unwrap().unwrap();
+    assert_eq!(fee_item.quantity, 7);
+    assert_eq!(
+        cart.calculate_total(connection).unwrap(),
+        5 * unit_price_in_cents + 7 * fee_item.unit_price_in_cents
+    );
+
+    // Order discount is capped at the price of one ticket
+    code.update(
+        None,
+        UpdateCodeAttributes {
+            max_order_discount_in_cents: Some(Some(unit_price_in_cents)),
+            ..Default::default()
+        },
+        connection,
+    )
+    .unwrap();
+    let order_item = update_quantity(&mut cart, 6);
+    let discount_item = order_item.find_discount_item(connection).unwrap().unwrap();
+    assert_eq!(discount_item.quantity, 2);
+    assert_eq!(discount_item.unit_price_in_cents, -unit_price_in_cents / 2);
+
+    // Fees are not charged on the free tickets
+    code.update(
+        None,
+        UpdateCodeAttributes {
+            max_order_discount_in_cents: Some(None),
+            waive_fees: Some(true),
+            ..Default::default()
+        },
+        connection,
+    )
+    .unwrap();
+    let order_item = update_quantity(&mut cart, 8);
+    let discount_item = order_item.find_discount_item(connection).unwrap().unwrap();
+    assert_eq!(discount_item.quantity, 2);
+    let fee_item = order_item.find_fee_item(connection).unwrap().unwrap();
+    assert_eq!(fee_item.quantity, 6);
+
+    // Below the minimum quantity no discount is given
+    code.update(
+        None,
+        UpdateCodeAttributes {
+            min_quantity: Some(Some(10)),
+            ..Default::default()
+        },
+        connection,
+    )
+    .unwrap();
+    let order_item = update_quantity(&mut cart, 9);
+    assert!(order_item.find_discount_item(connection).unwrap().is_none());
+    let fee_item = order_item.find_fee_item(connection).unwrap().unwrap();
+    assert_eq!(fee_item.quantity, 9);
+}
+
 #[test]
 fn add_free_payment() {
     let project = TestProject::new();
@@ -2332,6 +2744,7 @@ fn for_display_with_invalid_items() {
     )
     .unwrap();
     code.update(
+        None,
         UpdateCodeAttributes {
             end_date: Some(one_minute_ago),
             ..Default::default()
@@ -2355,6 +2768,7 @@ fn for_display_with_invalid_items() {
     .unwrap();
 
     hold.update(
+        None,
         UpdateHoldAttributes {
             end_at: Some(Some(one_minute_ago)),
             ..Default::default()
@@ -2806,6 +3220,52 @@ fn adding_event_fees() {
     assert_eq!(event_fees_count, 1);
 }
 
+#[test]
+fn company_fees_in_cents() {
+    let project = TestProject::new();
+    let creator = project.create_user().finish();
+    let connection = project.get_connection();
+    let organization = project
+        .create_organization()
+        .with_fee_schedule(&project.create_fee_schedule().finish(creator.id))
+        .with_event_fee()
+        .finish();
+    let event = project
+        .create_event()
+        .with_organization(&organization)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let user = project.create_user().finish();
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
+    assert_eq!(cart.company_fees_in_cents(connection).unwrap(), 0);
+
+    let ticket = &event.ticket_types(true, None, connection).unwrap()[0];
+    cart.update_quantities(
+        user.id,
+        &[UpdateOrderItem {
+            ticket_type_id: ticket.id,
+            quantity: 10,
+            redemption_code: None,
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+
+    let expected: i64 = OrderItem::find_for_order(cart.id, connection)
+        .unwrap()
+        .iter()
+        .filter(|i| {
+            i.item_type == OrderItemTypes::PerUnitFees || i.item_type == OrderItemTypes::EventFees
+        })
+        .map(|i| i.company_fee_in_cents * i.quantity)
+        .sum();
+    assert!(expected > 0);
+    assert_eq!(cart.company_fees_in_cents(connection).unwrap(), expected);
+}
+
 #[test]
 pub fn update() {
     let project = TestProject::new();
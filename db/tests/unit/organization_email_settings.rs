@@ -0,0 +1,182 @@
+use bigneon_db::dev::TestProject;
+use bigneon_db::prelude::*;
+use bigneon_db::utils::errors::ErrorCode::ValidationError;
+
+#[test]
+fn create_or_update() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let organization = project
+        .create_organization()
+        .with_sendgrid_api_key("SG.key")
+        .finish();
+    assert!(
+        OrganizationEmailSettings::find_by_organization_id(organization.id, connection)
+            .unwrap()
+            .is_none()
+    );
+
+    let settings = OrganizationEmailSettings::create_or_update(
+        organization.id,
+        OrganizationEmailSettingsEditableAttributes {
+            from_email: Some(Some("tickets@example.com".to_string())),
+            from_name: Some(Some("Example Venue".to_string())),
+            purchase_completed_template_id: Some(Some("d-123".to_string())),
+            ..Default::default()
+        },
+        user.id,
+        connection,
+    )
+    .unwrap();
+    assert_eq!(settings.organization_id, organization.id);
+    assert_eq!(settings.from_email, Some("tickets@example.com".to_string()));
+    assert_eq!(settings.from_name, Some("Example Venue".to_string()));
+
+    let updated_settings = OrganizationEmailSettings::create_or_update(
+        organization.id,
+        OrganizationEmailSettingsEditableAttributes {
+            from_name: Some(None),
+            primary_color: Some(Some("#ff0000".to_string())),
+            ..Default::default()
+        },
+        user.id,
+        connection,
+    )
+    .unwrap();
+    assert_eq!(updated_settings.id, settings.id);
+    assert_eq!(
+        updated_settings.from_email,
+        Some("tickets@example.com".to_string())
+    );
+    assert_eq!(updated_settings.from_name, None);
+    assert_eq!(updated_settings.primary_color, Some("#ff0000".to_string()));
+    assert_eq!(
+        OrganizationEmailSettings::find_by_organization_id(organization.id, connection).unwrap(),
+        Some(updated_settings)
+    );
+
+    let domain_events = DomainEvent::find(
+        Tables::Organizations,
+        Some(organization.id),
+        Some(DomainEventTypes::OrganizationEmailSettingsUpdated),
+        connection,
+    )
+    .unwrap();
+    assert_eq!(domain_events.len(), 2);
+}
+
+#[test]
+fn create_or_update_with_validation_errors() {
+    let project = TestProject::new();
+    let user = project.create_user().finish();
+    let organization = project.create_organization().finish();
+    let result = OrganizationEmailSettings::create_or_update(
+        organization.id,
+        OrganizationEmailSettingsEditableAttributes {
+            from_email: Some(Some("invalid-email".to_string())),
+            logo_url: Some(Some("not a url".to_string())),
+            ..Default::default()
+        },
+        user.id,
+        project.get_connection(),
+    );
+
+    match result {
+        Ok(_) => {
+            panic!("Expected validation error");
+        }
+        Err(error) => match &error.error_code {
+            ValidationError { errors } => {
+                assert!(errors.contains_key("from_email"));
+                assert_eq!(errors["from_email"][0].code, "email");
+                assert!(errors.contains_key("logo_url"));
+                assert_eq!(errors["logo_url"][0].code, "url");
+            }
+            _ => panic!("Expected validation error"),
+        },
+    }
+}
+
+#[test]
+fn create_or_update_from_email_without_sendgrid_template() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let expect_from_email_error =
+        |result: Result<OrganizationEmailSettings, DatabaseError>| match result {
+            Ok(_) => panic!("Expected validation error"),
+            Err(error) => match &error.error_code {
+                ValidationError { errors } => {
+                    assert!(errors.contains_key("from_email"));
+                    assert_eq!(
+                        errors["from_email"][0].code,
+                        "from_email_requires_sendgrid_template"
+                    );
+                }
+                _ => panic!("Expected validation error"),
+            },
+        };
+
+    // Organization without a SendGrid API key
+    let organization = project.create_organization().finish();
+    expect_from_email_error(OrganizationEmailSettings::create_or_update(
+        organization.id,
+        OrganizationEmailSettingsEditableAttributes {
+            from_email: Some(Some("tickets@example.com".to_string())),
+            purchase_completed_template_id: Some(Some("d-123".to_string())),
+            ..Default::default()
+        },
+        user.id,
+        connection,
+    ));
+
+    // Organization with a key but no templates
+    let organization = project
+        .create_organization()
+        .with_sendgrid_api_key("SG.key")
+        .finish();
+    expect_from_email_error(OrganizationEmailSettings::create_or_update(
+        organization.id,
+        OrganizationEmailSettingsEditableAttributes {
+            from_email: Some(Some("tickets@example.com".to_string())),
+            ..Default::default()
+        },
+        user.id,
+        connection,
+    ));
+
+    // Templates saved earlier are taken into account
+    OrganizationEmailSettings::create_or_update(
+        organization.id,
+        OrganizationEmailSettingsEditableAttributes {
+            org_invite_template_id: Some(Some("d-456".to_string())),
+            ..Default::default()
+        },
+        user.id,
+        connection,
+    )
+    .unwrap();
+    let settings = OrganizationEmailSettings::create_or_update(
+        organization.id,
+        OrganizationEmailSettingsEditableAttributes {
+            from_email: Some(Some("tickets@example.com".to_string())),
+            ..Default::default()
+        },
+        user.id,
+        connection,
+    )
+    .unwrap();
+    assert_eq!(settings.from_email, Some("tickets@example.com".to_string()));
+
+    // Removing the last template while keeping the sender address is rejected
+    expect_from_email_error(OrganizationEmailSettings::create_or_update(
+        organization.id,
+        OrganizationEmailSettingsEditableAttributes {
+            org_invite_template_id: Some(None),
+            ..Default::default()
+        },
+        user.id,
+        connection,
+    ));
+}
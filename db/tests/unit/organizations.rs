@@ -1,5 +1,6 @@
 use bigneon_db::dev::TestProject;
 use bigneon_db::prelude::*;
+use bigneon_db::utils::errors::ErrorCode::ValidationError;
 use uuid::Uuid;
 
 #[test]
@@ -273,6 +274,7 @@ fn update() {
     changed_attrs.sendgrid_api_key = Some(Some("A_Test_Key".to_string()));
     let mut updated_organization = Organization::update(
         &edited_organization,
+        Some(user.id),
         changed_attrs,
         &"encryption_key".to_string(),
         project.get_connection(),
@@ -284,6 +286,98 @@ fn update() {
         .unwrap();
 
     assert_eq!(edited_organization, updated_organization);
+
+    let domain_events = DomainEvent::find(
+        Tables::Organizations,
+        Some(updated_organization.id),
+        Some(DomainEventTypes::OrganizationUpdated),
+        project.get_connection(),
+    )
+    .unwrap();
+    // The builder's own update is logged as well
+    let changes = domain_events
+        .iter()
+        .map(|e| &e.event_data.as_ref().unwrap()["changes"])
+        .find(|c| !c["name"].is_null())
+        .unwrap();
+    assert_eq!(changes["name"]["after"], json!("Test Org"));
+    // Keys are not written to the audit log
+    assert_eq!(
+        changes["sendgrid_api_key"],
+        json!({"before": null, "after": "[redacted]"})
+    );
+}
+
+#[test]
+fn update_with_negative_refund_request_auto_approve_days() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let organization = project.create_organization().finish();
+
+    let result = organization.update(
+        None,
+        OrganizationEditableAttributes {
+            refund_request_auto_approve_days: Some(Some(-1)),
+            ..Default::default()
+        },
+        &"encryption_key".to_string(),
+        connection,
+    );
+    match result {
+        Ok(_) => panic!("Expected validation error"),
+        Err(error) => match &error.error_code {
+            ValidationError { errors } => {
+                assert!(errors.contains_key("refund_request_auto_approve_days"));
+                assert_eq!(
+                    errors["refund_request_auto_approve_days"][0].code,
+                    "number_must_be_positive"
+                );
+            }
+            _ => panic!("Expected validation error"),
+        },
+    }
+
+    // Creating an organization is validated as well
+    let user = project.create_user().finish();
+    let fee_schedule = project.create_fee_schedule().finish(user.id);
+    let mut new_organization = Organization::create("Organization", fee_schedule.id);
+    new_organization.refund_request_auto_approve_days = Some(Some(-1));
+    let result = new_organization.commit(&"encryption_key".to_string(), user.id, connection);
+    match result {
+        Ok(_) => panic!("Expected validation error"),
+        Err(error) => match &error.error_code {
+            ValidationError { errors } => {
+                assert!(errors.contains_key("refund_request_auto_approve_days"));
+            }
+            _ => panic!("Expected validation error"),
+        },
+    }
+}
+
+#[test]
+fn update_stripe_connect_account() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let organization = project.create_organization().finish();
+    assert_eq!(organization.stripe_connect_account_id, None);
+    assert_eq!(organization.stripe_connect_destination(), None);
+
+    let organization = organization
+        .update_stripe_connect_account("acct_123", false, connection)
+        .unwrap();
+    assert_eq!(
+        organization.stripe_connect_account_id,
+        Some("acct_123".to_string())
+    );
+    assert!(!organization.stripe_connect_enabled);
+    // Charges are not routed to the account until onboarding is complete
+    assert_eq!(organization.stripe_connect_destination(), None);
+
+    let organization = organization
+        .update_stripe_connect_account("acct_123", true, connection)
+        .unwrap();
+    assert!(organization.stripe_connect_enabled);
+    assert_eq!(organization.stripe_connect_destination(), Some("acct_123"));
 }
 
 #[test]
@@ -543,9 +637,22 @@ fn remove_users() {
 
     //remove user
     let result = organization
-        .remove_user(user2_id, project.get_connection())
+        .remove_user(Some(user.id), user2_id, project.get_connection())
         .unwrap();
     assert_eq!(result, 1);
+    let domain_events = DomainEvent::find(
+        Tables::Organizations,
+        Some(organization.id),
+        Some(DomainEventTypes::OrganizationUserRemoved),
+        project.get_connection(),
+    )
+    .unwrap();
+    assert_eq!(domain_events.len(), 1);
+    assert_eq!(domain_events[0].user_id, Some(user.id));
+    assert_eq!(
+        domain_events[0].event_data.as_ref().unwrap()["user_id"],
+        json!(user2_id)
+    );
     let user_results2: Vec<User> = organization
         .users(None, project.get_connection())
         .unwrap()
@@ -898,7 +1005,13 @@ fn add_user() {
         .with_member(&user, Roles::OrgOwner)
         .finish();
     let organization_user = organization
-        .add_user(user2.id, vec![Roles::OrgMember], Vec::new(), connection)
+        .add_user(
+            Some(user.id),
+            user2.id,
+            vec![Roles::OrgMember],
+            Vec::new(),
+            connection,
+        )
         .unwrap();
 
     assert_eq!(organization_user.user_id, user2.id);
@@ -909,6 +1022,43 @@ fn add_user() {
         .get_roles_for_user(&user2, connection)
         .unwrap()
         .contains(&Roles::OrgMember));
+
+    let domain_events = DomainEvent::find(
+        Tables::Organizations,
+        Some(organization.id),
+        Some(DomainEventTypes::OrganizationUserAdded),
+        connection,
+    )
+    .unwrap();
+    assert_eq!(domain_events.len(), 1);
+    assert_eq!(domain_events[0].user_id, Some(user.id));
+    assert_eq!(
+        domain_events[0].event_data.as_ref().unwrap()["user_id"],
+        json!(user2.id)
+    );
+
+    // Changing the roles of an existing member
+    organization
+        .add_user(
+            Some(user.id),
+            user2.id,
+            vec![Roles::OrgAdmin],
+            Vec::new(),
+            connection,
+        )
+        .unwrap();
+    let domain_events = DomainEvent::find(
+        Tables::Organizations,
+        Some(organization.id),
+        Some(DomainEventTypes::OrganizationUserRolesChanged),
+        connection,
+    )
+    .unwrap();
+    assert_eq!(domain_events.len(), 1);
+    assert_eq!(
+        domain_events[0].event_data.as_ref().unwrap()["changes"]["role"],
+        json!({"before": ["OrgMember"], "after": ["OrgAdmin"]})
+    );
 }
 
 #[test]
@@ -924,7 +1074,7 @@ fn add_fee_schedule() {
     assert_eq!(updated_fee_schedule.organization_id, Uuid::nil());
 
     organization
-        .add_fee_schedule(&fee_structure, project.get_connection())
+        .add_fee_schedule(Some(creator.id), &fee_structure, project.get_connection())
         .unwrap();
     let organization = Organization::find(organization.id, project.get_connection()).unwrap();
     assert_eq!(organization.fee_schedule_id, fee_structure.id);
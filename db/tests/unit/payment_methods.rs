@@ -1,5 +1,6 @@
 use bigneon_db::dev::TestProject;
 use bigneon_db::models::*;
+use chrono::prelude::*;
 
 #[test]
 fn create() {
@@ -192,3 +193,128 @@ fn find_for_user() {
             .unwrap();
     assert!(found_payment_methods.is_empty());
 }
+
+#[test]
+fn commit_default_replaces_existing_default() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let payment_method = project
+        .create_payment_method()
+        .with_user(&user)
+        .make_default()
+        .finish();
+    let payment_method2 = project
+        .create_payment_method()
+        .with_user(&user)
+        .make_default()
+        .finish();
+
+    let payment_method = PaymentMethod::find(payment_method.id, connection).unwrap();
+    assert!(!payment_method.is_default);
+    assert!(payment_method2.is_default);
+    assert_eq!(
+        PaymentMethod::find_default_for_user(user.id, connection).unwrap(),
+        payment_method2
+    );
+}
+
+#[test]
+fn set_default() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let payment_method = project
+        .create_payment_method()
+        .with_user(&user)
+        .make_default()
+        .finish();
+    let payment_method2 = project.create_payment_method().with_user(&user).finish();
+
+    let payment_method2 = payment_method2.set_default(user.id, connection).unwrap();
+    assert!(payment_method2.is_default);
+    let payment_method = PaymentMethod::find(payment_method.id, connection).unwrap();
+    assert!(!payment_method.is_default);
+
+    let domain_events = DomainEvent::find(
+        Tables::PaymentMethods,
+        Some(payment_method2.id),
+        Some(DomainEventTypes::PaymentMethodUpdated),
+        connection,
+    )
+    .unwrap();
+    assert_eq!(domain_events.len(), 1);
+}
+
+#[test]
+fn destroy() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let payment_method = project.create_payment_method().with_user(&user).finish();
+    let payment_method2 = project
+        .create_payment_method()
+        .with_user(&user)
+        .make_default()
+        .finish();
+
+    // Removing the default promotes the remaining payment method
+    payment_method2.destroy(user.id, connection).unwrap();
+    assert!(PaymentMethod::find(payment_method2.id, connection).is_err());
+    let payment_method = PaymentMethod::find(payment_method.id, connection).unwrap();
+    assert!(payment_method.is_default);
+
+    let domain_events = DomainEvent::find(
+        Tables::PaymentMethods,
+        Some(payment_method2.id),
+        Some(DomainEventTypes::PaymentMethodDeleted),
+        connection,
+    )
+    .unwrap();
+    assert_eq!(domain_events.len(), 1);
+
+    payment_method.destroy(user.id, connection).unwrap();
+    assert!(user.payment_methods(connection).unwrap().is_empty());
+}
+
+#[test]
+fn card_details() {
+    let project = TestProject::new();
+    let payment_method = project.create_payment_method().finish();
+    assert_eq!(payment_method.card_details(), None);
+
+    let payment_method = project
+        .create_payment_method()
+        .with_provider_data(json!({
+            "id": "cus_example",
+            "default_source": "card_2",
+            "sources": {
+                "data": [
+                    {"id": "card_1", "brand": "Visa", "last4": "4242", "exp_month": 1, "exp_year": 2020},
+                    {"id": "card_2", "brand": "MasterCard", "last4": "4444", "exp_month": 12, "exp_year": 2021}
+                ]
+            }
+        }))
+        .finish();
+    let card = payment_method.card_details().unwrap();
+    assert_eq!(
+        card,
+        CardDetails {
+            brand: "MasterCard".to_string(),
+            last4: "4444".to_string(),
+            exp_month: 12,
+            exp_year: 2021,
+        }
+    );
+
+    // Valid until the end of the expiry month
+    assert_eq!(
+        card.expires_at(),
+        NaiveDate::from_ymd(2022, 1, 1).and_hms(0, 0, 0)
+    );
+    assert!(!card.is_expired(NaiveDate::from_ymd(2021, 12, 31).and_hms(23, 59, 59)));
+    assert!(card.is_expired(NaiveDate::from_ymd(2022, 1, 1).and_hms(0, 0, 0)));
+    assert!(card.is_expiring_soon(NaiveDate::from_ymd(2021, 12, 15).and_hms(0, 0, 0)));
+    assert!(!card.is_expiring_soon(NaiveDate::from_ymd(2021, 10, 1).and_hms(0, 0, 0)));
+    assert!(!card.is_expiring_soon(NaiveDate::from_ymd(2022, 1, 2).and_hms(0, 0, 0)));
+}
@@ -1,5 +1,7 @@
 use bigneon_db::dev::TestProject;
 use bigneon_db::models::*;
+use chrono::prelude::*;
+use time::Duration;
 
 #[test]
 fn log_refund() {
@@ -276,3 +278,133 @@ fn mark_cancelled() {
         true
     )
 }
+
+#[test]
+fn mark_requires_action() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let creator = project.create_user().finish();
+    let organization = project
+        .create_organization()
+        .with_event_fee()
+        .with_fee_schedule(&project.create_fee_schedule().finish(creator.id))
+        .finish();
+    let event = project
+        .create_event()
+        .with_organization(&organization)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let user = project.create_user().finish();
+    let payment = project
+        .create_payment()
+        .with_user(&user)
+        .with_organization(&organization)
+        .with_event(&event)
+        .with_status(PaymentStatus::Requested)
+        .finish();
+    payment
+        .mark_requires_action(json!(null), Some(user.id), connection)
+        .unwrap();
+
+    let payment = Payment::find(payment.id, connection).unwrap();
+    assert_eq!(payment.status, PaymentStatus::RequiresAction);
+    let order = Order::find(payment.order_id, connection).unwrap();
+    assert_eq!(order.status, OrderStatus::PendingPayment);
+    assert!(order.expires_at.unwrap() > Utc::now().naive_utc() + Duration::minutes(25));
+
+    // Completed payments cannot require action
+    let payment = project
+        .create_payment()
+        .with_user(&user)
+        .with_organization(&organization)
+        .with_event(&event)
+        .finish();
+    assert!(payment
+        .mark_requires_action(json!(null), Some(user.id), connection)
+        .is_err());
+}
+
+#[test]
+fn mark_authorized() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let creator = project.create_user().finish();
+    let organization = project
+        .create_organization()
+        .with_event_fee()
+        .with_fee_schedule(&project.create_fee_schedule().finish(creator.id))
+        .finish();
+    let event = project
+        .create_event()
+        .with_organization(&organization)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let user = project.create_user().finish();
+    let payment = project
+        .create_payment()
+        .with_user(&user)
+        .with_organization(&organization)
+        .with_event(&event)
+        .with_status(PaymentStatus::Requested)
+        .finish();
+    assert!(payment
+        .mark_authorized(json!(null), Some(user.id), connection)
+        .is_err());
+
+    payment
+        .mark_requires_action(json!(null), Some(user.id), connection)
+        .unwrap();
+    let payment = Payment::find(payment.id, connection).unwrap();
+    payment
+        .mark_authorized(json!(null), Some(user.id), connection)
+        .unwrap();
+    let payment = Payment::find(payment.id, connection).unwrap();
+    assert_eq!(payment.status, PaymentStatus::Authorized);
+}
+
+#[test]
+fn complete_after_requires_action() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let creator = project.create_user().finish();
+    let organization = project
+        .create_organization()
+        .with_event_fee()
+        .with_fee_schedule(&project.create_fee_schedule().finish(creator.id))
+        .finish();
+    let event = project
+        .create_event()
+        .with_organization(&organization)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let user = project.create_user().finish();
+    let payment = project
+        .create_payment()
+        .with_user(&user)
+        .with_organization(&organization)
+        .with_event(&event)
+        .with_status(PaymentStatus::Requested)
+        .finish();
+
+    payment
+        .mark_requires_action(json!(null), Some(user.id), connection)
+        .unwrap();
+    let payment = Payment::find(payment.id, connection).unwrap();
+    assert_eq!(payment.status, PaymentStatus::RequiresAction);
+
+    // Customer has confirmed the payment intent
+    payment
+        .mark_authorized(json!(null), Some(user.id), connection)
+        .unwrap();
+    let payment = Payment::find(payment.id, connection).unwrap();
+    payment
+        .mark_complete(json!(null), Some(user.id), connection)
+        .unwrap();
+    let payment = Payment::find(payment.id, connection).unwrap();
+    assert_eq!(payment.status, PaymentStatus::Completed);
+    let order = Order::find(payment.order_id, connection).unwrap();
+    assert_eq!(order.status, OrderStatus::Paid);
+}
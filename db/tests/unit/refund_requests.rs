@@ -0,0 +1,277 @@
+use bigneon_db::dev::TestProject;
+use bigneon_db::models::*;
+use bigneon_db::utils::errors::ErrorCode;
+use chrono::prelude::*;
+use chrono::Duration;
+
+#[test]
+fn commit() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let event = project.create_event().with_ticket_pricing().finish();
+    let ticket_type = &event.ticket_types(true, None, connection).unwrap()[0];
+    let order = project
+        .create_order()
+        .for_user(&user)
+        .for_event(&event)
+        .quantity(2)
+        .is_paid()
+        .finish();
+    let tickets = order.tickets(ticket_type.id, connection).unwrap();
+
+    let refund_request = RefundRequest::create(
+        order.id,
+        user.id,
+        vec![tickets[0].id],
+        "Can no longer attend".to_string(),
+    )
+    .commit(connection)
+    .unwrap();
+    assert_eq!(refund_request.status, RefundRequestStatus::Pending);
+    assert_eq!(refund_request.organization_id, event.organization_id);
+    assert_eq!(refund_request.ticket_instance_ids, vec![tickets[0].id]);
+    assert_eq!(
+        RefundRequest::find_for_order(order.id, connection).unwrap(),
+        vec![refund_request]
+    );
+
+    // A reason is required
+    let result = RefundRequest::create(order.id, user.id, vec![tickets[1].id], " ".to_string())
+        .commit(connection);
+    match result {
+        Ok(_) => panic!("Expected validation error"),
+        Err(error) => match &error.error_code {
+            ErrorCode::ValidationError { errors } => {
+                assert!(errors.contains_key("reason"));
+            }
+            _ => panic!("Expected validation error"),
+        },
+    }
+
+    // Tickets already requested cannot be requested again while pending
+    let result = RefundRequest::create(
+        order.id,
+        user.id,
+        vec![tickets[0].id, tickets[1].id],
+        "Can no longer attend".to_string(),
+    )
+    .commit(connection);
+    assert!(result.is_err());
+
+    // Tickets must belong to the order
+    let other_order = project.create_order().for_event(&event).is_paid().finish();
+    let other_ticket = &other_order.tickets(ticket_type.id, connection).unwrap()[0];
+    let result = RefundRequest::create(
+        order.id,
+        user.id,
+        vec![other_ticket.id],
+        "Can no longer attend".to_string(),
+    )
+    .commit(connection);
+    assert!(result.is_err());
+}
+
+#[test]
+fn qualifies_for_auto_approval() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let organization = project.create_organization().finish();
+    let event = project
+        .create_event()
+        .with_organization(&organization)
+        .with_event_start(Utc::now().naive_utc() + Duration::days(10))
+        .with_ticket_pricing()
+        .finish();
+    let ticket_type = &event.ticket_types(true, None, connection).unwrap()[0];
+    let user = project.create_user().finish();
+    let order = project
+        .create_order()
+        .for_user(&user)
+        .for_event(&event)
+        .is_paid()
+        .finish();
+    let tickets = order.tickets(ticket_type.id, connection).unwrap();
+    let refund_request = RefundRequest::create(
+        order.id,
+        user.id,
+        vec![tickets[0].id],
+        "Can no longer attend".to_string(),
+    )
+    .commit(connection)
+    .unwrap();
+    assert!(!refund_request
+        .qualifies_for_auto_approval(connection)
+        .unwrap());
+
+    // Requests made long enough before the event
+    let organization = organization
+        .update(
+            None,
+            OrganizationEditableAttributes {
+                refund_request_auto_approve_days: Some(Some(7)),
+                ..Default::default()
+            },
+            &"encryption_key".to_string(),
+            connection,
+        )
+        .unwrap();
+    assert!(refund_request
+        .qualifies_for_auto_approval(connection)
+        .unwrap());
+    organization
+        .update(
+            None,
+            OrganizationEditableAttributes {
+                refund_request_auto_approve_days: Some(Some(14)),
+                ..Default::default()
+            },
+            &"encryption_key".to_string(),
+            connection,
+        )
+        .unwrap();
+    assert!(!refund_request
+        .qualifies_for_auto_approval(connection)
+        .unwrap());
+
+    // Refundable ticket types
+    ticket_type
+        .update(
+            None,
+            TicketTypeEditableAttributes {
+                refundable: Some(true),
+                ..Default::default()
+            },
+            connection,
+        )
+        .unwrap();
+    assert!(refund_request
+        .qualifies_for_auto_approval(connection)
+        .unwrap());
+}
+
+#[test]
+fn approve_and_deny() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let reviewer = project.create_user().finish();
+    let event = project.create_event().with_ticket_pricing().finish();
+    let ticket_type = &event.ticket_types(true, None, connection).unwrap()[0];
+    let user = project.create_user().finish();
+    let order = project
+        .create_order()
+        .for_user(&user)
+        .for_event(&event)
+        .quantity(2)
+        .is_paid()
+        .finish();
+    let tickets = order.tickets(ticket_type.id, connection).unwrap();
+    let refund_request = RefundRequest::create(
+        order.id,
+        user.id,
+        vec![tickets[0].id],
+        "Can no longer attend".to_string(),
+    )
+    .commit(connection)
+    .unwrap();
+    let refund_request2 = RefundRequest::create(
+        order.id,
+        user.id,
+        vec![tickets[1].id],
+        "Can no longer attend".to_string(),
+    )
+    .commit(connection)
+    .unwrap();
+
+    let refund_items = refund_request.refund_items(connection).unwrap();
+    assert_eq!(refund_items.len(), 1);
+    assert_eq!(
+        Some(refund_items[0].order_item_id),
+        tickets[0].order_item_id
+    );
+    assert_eq!(refund_items[0].ticket_instance_id, Some(tickets[0].id));
+
+    let approved = refund_request.approve(None, connection).unwrap();
+    assert_eq!(approved.status, RefundRequestStatus::Approved);
+    assert_eq!(approved.reviewed_by_user_id, None);
+    assert!(approved.reviewed_at.is_some());
+
+    // Requests can only be reviewed once
+    assert!(approved.deny(reviewer.id, None, connection).is_err());
+
+    let denied = refund_request2
+        .deny(
+            reviewer.id,
+            Some("Outside of the refund policy".to_string()),
+            connection,
+        )
+        .unwrap();
+    assert_eq!(denied.status, RefundRequestStatus::Denied);
+    assert_eq!(denied.reviewed_by_user_id, Some(reviewer.id));
+    assert_eq!(
+        denied.review_notes,
+        Some("Outside of the refund policy".to_string())
+    );
+}
+
+#[test]
+fn find_for_organization() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project.create_event().with_ticket_pricing().finish();
+    let ticket_type = &event.ticket_types(true, None, connection).unwrap()[0];
+    let user = project.create_user().finish();
+    let order = project
+        .create_order()
+        .for_user(&user)
+        .for_event(&event)
+        .quantity(2)
+        .is_paid()
+        .finish();
+    let tickets = order.tickets(ticket_type.id, connection).unwrap();
+    let refund_request = RefundRequest::create(
+        order.id,
+        user.id,
+        vec![tickets[0].id],
+        "Can no longer attend".to_string(),
+    )
+    .commit(connection)
+    .unwrap();
+    let refund_request2 = RefundRequest::create(
+        order.id,
+        user.id,
+        vec![tickets[1].id],
+        "Can no longer attend".to_string(),
+    )
+    .commit(connection)
+    .unwrap()
+    .deny(user.id, None, connection)
+    .unwrap();
+
+    let payload =
+        RefundRequest::find_for_organization(event.organization_id, None, 0, 100, connection)
+            .unwrap();
+    assert_eq!(payload.paging.total, 2);
+    assert_eq!(payload.data.len(), 2);
+
+    let payload = RefundRequest::find_for_organization(
+        event.organization_id,
+        Some(RefundRequestStatus::Pending),
+        0,
+        100,
+        connection,
+    )
+    .unwrap();
+    assert_eq!(payload.paging.total, 1);
+    assert_eq!(payload.data, vec![refund_request]);
+
+    let payload = RefundRequest::find_for_organization(
+        event.organization_id,
+        Some(RefundRequestStatus::Denied),
+        0,
+        100,
+        connection,
+    )
+    .unwrap();
+    assert_eq!(payload.data, vec![refund_request2]);
+}
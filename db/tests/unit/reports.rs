@@ -182,3 +182,142 @@ fn box_office_sales_summary_report() {
         Report::box_office_sales_summary_report(organization.id, None, None, connection).unwrap();
     assert_eq!(expected_report_data, report_data);
 }
+
+#[test]
+fn code_analytics_report() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let creator = project.create_user().finish();
+    let organization = project
+        .create_organization()
+        .with_fee_schedule(&project.create_fee_schedule().finish(creator.id))
+        .finish();
+    let event = project
+        .create_event()
+        .with_organization(&organization)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let ticket_type = &event.ticket_types(true, None, connection).unwrap()[0];
+    let code = project
+        .create_code()
+        .with_event(&event)
+        .for_ticket_type(&ticket_type)
+        .finish();
+    let hold = project
+        .create_hold()
+        .with_event(&event)
+        .with_ticket_type_id(ticket_type.id)
+        .finish();
+
+    // Code applied in two carts, only one of which is purchased
+    let user = project.create_user().finish();
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
+    cart.update_quantities(
+        user.id,
+        &[UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 2,
+            redemption_code: Some(code.redemption_code.clone()),
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+    let total = cart.calculate_total(connection).unwrap();
+    cart.add_external_payment(
+        Some("Test".to_string()),
+        ExternalPaymentType::CreditCard,
+        user.id,
+        total,
+        connection,
+    )
+    .unwrap();
+    let order_item = cart
+        .items(connection)
+        .unwrap()
+        .into_iter()
+        .find(|i| i.ticket_type_id == Some(ticket_type.id))
+        .unwrap();
+
+    let user2 = project.create_user().finish();
+    let mut cart2 = Order::find_or_create_cart(&user2, connection).unwrap();
+    cart2
+        .update_quantities(
+            user2.id,
+            &[UpdateOrderItem {
+                ticket_type_id: ticket_type.id,
+                quantity: 1,
+                redemption_code: Some(code.redemption_code.clone()),
+            }],
+            false,
+            false,
+            connection,
+        )
+        .unwrap();
+    assert_eq!(
+        CodeApplication::find_for_order(cart2.id, connection)
+            .unwrap()
+            .len(),
+        1
+    );
+
+    let report = Report::code_analytics_report(
+        Some(event.id),
+        Some(organization.id),
+        None,
+        None,
+        connection,
+    )
+    .unwrap();
+    let code_row = report
+        .codes
+        .iter()
+        .find(|row| row.code_id == Some(code.id))
+        .unwrap();
+    let fee_item = order_item.find_fee_item(connection).unwrap().unwrap();
+    assert_eq!(
+        code_row.analytics,
+        CodeAnalytics {
+            tickets_sold: 2,
+            gross_revenue_in_cents: total,
+            discount_given_in_cents: 200,
+            net_revenue_in_cents: 2 * order_item.unit_price_in_cents - 200,
+            unique_buyers: 1,
+            applications: 2,
+            purchases: 1,
+            conversion_rate: Some(0.5),
+        }
+    );
+    assert_eq!(
+        total,
+        2 * order_item.unit_price_in_cents - 200 + 2 * fee_item.unit_price_in_cents
+    );
+
+    // Unused hold is still listed
+    let hold_row = report
+        .codes
+        .iter()
+        .find(|row| row.hold_id == Some(hold.id))
+        .unwrap();
+    assert_eq!(hold_row.analytics, CodeAnalytics::default());
+
+    assert_eq!(report.usage.len(), 1);
+    assert_eq!(report.usage[0].code_id, Some(code.id));
+    assert_eq!(report.usage[0].tickets_sold, 2);
+    assert_eq!(report.usage[0].purchases, 1);
+
+    assert_eq!(
+        code.for_display(connection).unwrap().analytics,
+        code_row.analytics
+    );
+
+    // Loading by id only returns the requested codes and holds
+    let code_analytics = CodeAnalytics::find_for_codes(&[code.id], connection).unwrap();
+    assert_eq!(code_analytics.len(), 1);
+    assert_eq!(code_analytics[&code.id], code_row.analytics);
+    let hold_analytics = CodeAnalytics::find_for_holds(&[hold.id], connection).unwrap();
+    assert_eq!(hold_analytics.len(), 1);
+    assert_eq!(hold_analytics[&hold.id], CodeAnalytics::default());
+}
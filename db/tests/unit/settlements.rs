@@ -69,6 +69,52 @@ fn create() {
     assert_eq!(settlement.organization_id, organization.id);
     assert_eq!(settlement.user_id, user.id);
     assert_eq!(settlement.comment, Some("test comment".to_string()));
+
+    let domain_events = DomainEvent::find(
+        Tables::Settlements,
+        Some(settlement.id),
+        Some(DomainEventTypes::SettlementCreated),
+        connection,
+    )
+    .unwrap();
+    assert_eq!(domain_events.len(), 1);
+    assert_eq!(domain_events[0].organization_id, Some(organization.id));
+}
+
+#[test]
+fn destroy() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let organization = project
+        .create_organization()
+        .with_member(&user, Roles::OrgOwner)
+        .finish();
+    let settlement = NewSettlementRequest {
+        start_utc: NaiveDate::from_ymd(2016, 7, 8).and_hms(4, 10, 11),
+        end_utc: NaiveDate::from_ymd(2020, 7, 8).and_hms(4, 10, 11),
+        comment: None,
+        only_finished_events: None,
+        adjustments: None,
+    }
+    .commit(organization.id, user.id, connection)
+    .unwrap();
+    let settlement_id = settlement.id;
+
+    assert_eq!(settlement.destroy(Some(user.id), connection).unwrap(), 1);
+    assert!(Settlement::read(settlement_id, connection).is_err());
+
+    let domain_events = DomainEvent::find(
+        Tables::Settlements,
+        Some(settlement_id),
+        Some(DomainEventTypes::SettlementDeleted),
+        connection,
+    )
+    .unwrap();
+    assert_eq!(domain_events.len(), 1);
+    assert_eq!(domain_events[0].user_id, Some(user.id));
+    // Still listed for the organization after the settlement is gone
+    assert_eq!(domain_events[0].organization_id, Some(organization.id));
 }
 
 #[test]
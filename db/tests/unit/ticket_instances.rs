@@ -11,6 +11,7 @@ use uuid::Uuid;
 use bigneon_db::dev::times;
 use bigneon_db::dev::TestProject;
 use bigneon_db::prelude::*;
+use tari_client::*;
 
 #[test]
 fn find_for_user_for_display() {
@@ -1279,3 +1280,153 @@ fn transfer_to_existing_user() {
     )
     .unwrap();
 }
+
+#[test]
+fn transfer_from_self_custodied_wallet() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let receiver = project.create_user().finish();
+    project
+        .create_order()
+        .for_user(&user)
+        .quantity(2)
+        .is_paid()
+        .finish();
+    let tickets = TicketInstance::find_for_user(user.id, connection).unwrap();
+
+    let (secret_key, public_key) = cryptographic_keypair();
+    let public_key = convert_bytes_to_hexstring(&public_key);
+    let signature = convert_bytes_to_hexstring(
+        &cryptographic_signature(
+            &Wallet::self_custody_message(user.id, &public_key),
+            &secret_key,
+        )
+        .unwrap(),
+    );
+    let wallet =
+        Wallet::register_self_custody_key(user.id, public_key, signature, connection).unwrap();
+    TicketInstance::find(tickets[0].id, connection)
+        .unwrap()
+        .set_wallet(&wallet, connection)
+        .unwrap();
+
+    // Big Neon cannot sign for tickets held in the self-custodied wallet
+    let result = TicketInstance::direct_transfer(
+        user.id,
+        &vec![tickets[0].id],
+        "nowhere",
+        "Test",
+        receiver.id,
+        connection,
+    );
+    assert!(result.is_err());
+
+    TicketInstance::direct_transfer(
+        user.id,
+        &vec![tickets[1].id],
+        "nowhere",
+        "Test",
+        receiver.id,
+        connection,
+    )
+    .unwrap();
+}
+
+#[test]
+fn create_and_verify_ownership_proof() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project.create_event().with_ticket_pricing().finish();
+    let ticket_type = &event.ticket_types(true, None, connection).unwrap()[0];
+    let user = project.create_user().finish();
+    let user2 = project.create_user().finish();
+    let order = project
+        .create_order()
+        .for_user(&user)
+        .for_event(&event)
+        .is_paid()
+        .finish();
+    let ticket = &order.tickets(ticket_type.id, connection).unwrap()[0];
+
+    // Only the holder can create a proof
+    assert!(TicketInstance::create_ownership_proof(ticket.id, user2.id, 60, connection).is_err());
+
+    let proof = TicketInstance::create_ownership_proof(ticket.id, user.id, 60, connection).unwrap();
+    let authenticity = TicketInstance::verify_ownership_proof(&proof, connection).unwrap();
+    assert!(authenticity.exists);
+    assert_eq!(authenticity.status, Some(TicketInstanceStatus::Purchased));
+    assert_eq!(authenticity.event.map(|e| e.id), Some(event.id));
+    assert!(authenticity.signature_valid);
+    assert!(!authenticity.expired);
+    assert!(authenticity.owned_by_signer);
+
+    // Validity is capped
+    let long_proof =
+        TicketInstance::create_ownership_proof(ticket.id, user.id, 86_400, connection).unwrap();
+    assert!(
+        long_proof.expires_at
+            <= Utc::now().timestamp() + DEFAULT_OWNERSHIP_PROOF_VALIDITY_SECONDS as i64
+    );
+
+    // Expired proofs and proofs valid for longer than the cap do not prove ownership
+    let wallet = Wallet::find_default_for_user(user.id, connection).unwrap();
+    for expires_at in vec![Utc::now().timestamp() - 60, Utc::now().timestamp() + 86_400] {
+        let signed_proof = TicketOwnershipProof {
+            ticket_instance_id: ticket.id,
+            expires_at,
+            public_key: wallet.public_key.clone(),
+            signature: convert_bytes_to_hexstring(
+                &cryptographic_signature(
+                    &TicketOwnershipProof::message(ticket.id, expires_at),
+                    &convert_hexstring_to_bytes(&wallet.secret_key),
+                )
+                .unwrap(),
+            ),
+        };
+        let authenticity =
+            TicketInstance::verify_ownership_proof(&signed_proof, connection).unwrap();
+        assert!(authenticity.signature_valid);
+        assert!(authenticity.expired);
+        assert!(!authenticity.owned_by_signer);
+    }
+
+    // Tampered proofs are rejected
+    let mut tampered_proof = proof.clone();
+    tampered_proof.expires_at += 3600;
+    let authenticity = TicketInstance::verify_ownership_proof(&tampered_proof, connection).unwrap();
+    assert!(authenticity.exists);
+    assert!(!authenticity.signature_valid);
+    assert!(!authenticity.owned_by_signer);
+
+    // Proofs from previous owners are no longer valid once the ticket is transferred
+    let transfer_authorization = TicketInstance::authorize_ticket_transfer(
+        user.id,
+        &[ticket.id],
+        3600,
+        None,
+        None,
+        connection,
+    )
+    .unwrap();
+    let sender_wallet = Wallet::find_default_for_user(user.id, connection).unwrap();
+    let receiver_wallet = Wallet::find_default_for_user(user2.id, connection).unwrap();
+    TicketInstance::receive_ticket_transfer(
+        transfer_authorization,
+        &sender_wallet,
+        receiver_wallet.id,
+        connection,
+    )
+    .unwrap();
+    let authenticity = TicketInstance::verify_ownership_proof(&proof, connection).unwrap();
+    assert!(authenticity.signature_valid);
+    assert!(!authenticity.owned_by_signer);
+
+    // Unknown tickets
+    let mut unknown_proof = proof.clone();
+    unknown_proof.ticket_instance_id = Uuid::new_v4();
+    let authenticity = TicketInstance::verify_ownership_proof(&unknown_proof, connection).unwrap();
+    assert!(!authenticity.exists);
+    assert_eq!(authenticity.status, None);
+    assert_eq!(authenticity.event, None);
+}
@@ -287,7 +287,11 @@ fn update_with_validation_errors() {
     let mut ticket_pricing_parameters: TicketPricingEditableAttributes = Default::default();
     ticket_pricing_parameters.start_date = Some(NaiveDate::from_ymd(2016, 7, 9).and_hms(4, 10, 11));
     ticket_pricing_parameters.end_date = Some(NaiveDate::from_ymd(2016, 7, 8).and_hms(4, 10, 11));
-    let result = ticket_pricing.update(ticket_pricing_parameters.clone(), project.get_connection());
+    let result = ticket_pricing.update(
+        None,
+        ticket_pricing_parameters.clone(),
+        project.get_connection(),
+    );
     match result {
         Ok(_) => {
             panic!("Expected validation error");
@@ -316,7 +320,11 @@ fn update_with_validation_errors() {
     // Updates without start date validation triggering
     ticket_pricing_parameters.start_date = Some(end_date1);
     ticket_pricing_parameters.end_date = Some(NaiveDate::from_ymd(2016, 7, 15).and_hms(4, 10, 11));
-    let result = ticket_pricing.update(ticket_pricing_parameters.clone(), project.get_connection());
+    let result = ticket_pricing.update(
+        None,
+        ticket_pricing_parameters.clone(),
+        project.get_connection(),
+    );
     assert!(result.is_ok());
 }
 
@@ -351,8 +359,9 @@ fn update() {
         end_date: Some(update_end_date),
         is_box_office_only: Some(false),
     };
+    let user = project.create_user().finish();
     let updated_ticket_pricing = ticket_pricing
-        .update(update_parameters, connection)
+        .update(Some(user.id), update_parameters, connection)
         .unwrap();
     assert_eq!(updated_ticket_pricing.id, ticket_pricing.id);
     assert_eq!(updated_ticket_pricing.name, update_name);
@@ -364,6 +373,26 @@ fn update() {
         updated_ticket_pricing.ticket_type_id,
         ticket_pricing.ticket_type_id
     );
+
+    let domain_events = DomainEvent::find(
+        Tables::TicketPricing,
+        Some(ticket_pricing.id),
+        Some(DomainEventTypes::TicketPricingUpdated),
+        connection,
+    )
+    .unwrap();
+    assert_eq!(domain_events.len(), 1);
+    assert_eq!(domain_events[0].user_id, Some(user.id));
+    assert_eq!(
+        domain_events[0].organization_id,
+        Some(event.organization_id)
+    );
+    let changes = &domain_events[0].event_data.as_ref().unwrap()["changes"];
+    assert_eq!(changes["price_in_cents"]["before"], json!(100));
+    assert_eq!(
+        changes["price_in_cents"]["after"],
+        json!(update_price_in_cents)
+    );
 }
 
 #[test]
@@ -420,7 +449,7 @@ fn update_with_affected_orders() {
         is_box_office_only: Some(false),
     };
     let updated_ticket_pricing = ticket_pricing
-        .update(update_parameters, connection)
+        .update(None, update_parameters, connection)
         .unwrap();
 
     // ID should be new but everything else should match updated logic
@@ -466,6 +466,7 @@ fn validate_ticket_pricing() {
     // Set short window for validations to detect dates outside of ticket type window
     let ticket_type = ticket_type
         .update(
+            None,
             TicketTypeEditableAttributes {
                 start_date: Some(NaiveDate::from_ymd(2016, 7, 8).and_hms(4, 10, 11)),
                 end_date: Some(NaiveDate::from_ymd(2016, 7, 9).and_hms(4, 10, 11)),
@@ -560,6 +561,7 @@ fn validate_ticket_pricing() {
     // Ticket type adjusted so ticket pricing inclusive of its dates
     let ticket_type = ticket_type
         .update(
+            None,
             TicketTypeEditableAttributes {
                 start_date: Some(NaiveDate::from_ymd(2016, 6, 1).and_hms(4, 10, 11)),
                 end_date: Some(NaiveDate::from_ymd(2055, 7, 6).and_hms(4, 10, 11)),
@@ -573,7 +575,11 @@ fn validate_ticket_pricing() {
     ticket_pricing_parameters.start_date = Some(end_date1);
     ticket_pricing_parameters.end_date = Some(NaiveDate::from_ymd(2016, 7, 15).and_hms(4, 10, 11));
     ticket_pricing
-        .update(ticket_pricing_parameters.clone(), project.get_connection())
+        .update(
+            None,
+            ticket_pricing_parameters.clone(),
+            project.get_connection(),
+        )
         .unwrap();
     ticket_type
         .validate_ticket_pricing(project.get_connection())
@@ -583,7 +589,11 @@ fn validate_ticket_pricing() {
     ticket_pricing_parameters.start_date = Some(NaiveDate::from_ymd(2016, 7, 4).and_hms(4, 10, 11));
     ticket_pricing_parameters.end_date = Some(start_date1);
     ticket_pricing
-        .update(ticket_pricing_parameters.clone(), project.get_connection())
+        .update(
+            None,
+            ticket_pricing_parameters.clone(),
+            project.get_connection(),
+        )
         .unwrap();
 
     ticket_type
@@ -654,6 +664,7 @@ pub fn remaining_ticket_count() {
 fn update() {
     let db = TestProject::new();
     let connection = db.get_connection();
+    let user = db.create_user().finish();
     let event = db.create_event().with_tickets().finish();
     let ticket_type = &event.ticket_types(true, None, connection).unwrap()[0];
     //Change editable parameter and submit ticket type update request
@@ -666,11 +677,31 @@ fn update() {
         end_date: Some(update_end_date),
         ..Default::default()
     };
-    let updated_ticket_type = ticket_type.update(update_parameters, connection).unwrap();
+    let updated_ticket_type = ticket_type
+        .update(Some(user.id), update_parameters, connection)
+        .unwrap();
     assert_eq!(updated_ticket_type.id, ticket_type.id);
     assert_eq!(updated_ticket_type.name, update_name);
     assert_eq!(updated_ticket_type.start_date, update_start_date);
     assert_eq!(updated_ticket_type.end_date, update_end_date);
+
+    let domain_events = DomainEvent::find(
+        Tables::TicketTypes,
+        Some(ticket_type.id),
+        Some(DomainEventTypes::TicketTypeUpdated),
+        connection,
+    )
+    .unwrap();
+    assert_eq!(domain_events.len(), 1);
+    assert_eq!(domain_events[0].user_id, Some(user.id));
+    assert_eq!(
+        domain_events[0].organization_id,
+        Some(event.organization_id)
+    );
+    let changes = &domain_events[0].event_data.as_ref().unwrap()["changes"];
+    assert_eq!(changes["name"]["before"], json!(ticket_type.name));
+    assert_eq!(changes["name"]["after"], json!(update_name));
+    assert_eq!(changes.get("price_in_cents"), None);
 }
 
 #[test]
@@ -701,7 +732,7 @@ pub fn update_with_validation_errors() {
         end_date: Some(update_end_date),
         ..Default::default()
     };
-    let result = ticket_type.update(update_parameters, connection);
+    let result = ticket_type.update(None, update_parameters, connection);
     match result {
         Ok(_) => {
             panic!("Expected validation error");
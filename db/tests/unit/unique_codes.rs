@@ -0,0 +1,221 @@
+use bigneon_db::dev::TestProject;
+use bigneon_db::models::*;
+use bigneon_db::utils::errors::ErrorCode::ValidationError;
+use std::collections::HashSet;
+
+#[test]
+fn generate() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let event = project.create_event().with_tickets().finish();
+    let code = project.create_code().with_event(&event).finish();
+
+    let unique_codes = UniqueCode::generate(
+        &code,
+        5,
+        Some("radio-".to_string()),
+        Some("abc123".to_string()),
+        Some(6),
+        user.id,
+        connection,
+    )
+    .unwrap();
+    assert_eq!(unique_codes.len(), 5);
+    let redemption_codes: HashSet<String> = unique_codes
+        .iter()
+        .map(|unique_code| unique_code.redemption_code.clone())
+        .collect();
+    assert_eq!(redemption_codes.len(), 5);
+    for unique_code in &unique_codes {
+        assert_eq!(unique_code.code_id, code.id);
+        assert_eq!(unique_code.redemption_code.len(), 12);
+        assert!(unique_code.redemption_code.starts_with("RADIO-"));
+        assert!(unique_code.redemption_code[6..]
+            .chars()
+            .all(|c| "ABC123".contains(c)));
+    }
+
+    let domain_events = DomainEvent::find(
+        Tables::Codes,
+        Some(code.id),
+        Some(DomainEventTypes::UniqueCodesGenerated),
+        connection,
+    )
+    .unwrap();
+    assert_eq!(domain_events.len(), 1);
+}
+
+#[test]
+fn generate_with_validation_errors() {
+    let project = TestProject::new();
+    let user = project.create_user().finish();
+    let code = project.create_code().finish();
+
+    let result = UniqueCode::generate(
+        &code,
+        0,
+        Some("not valid".to_string()),
+        None,
+        Some(2),
+        user.id,
+        project.get_connection(),
+    );
+
+    match result {
+        Ok(_) => {
+            panic!("Expected validation error");
+        }
+        Err(error) => match &error.error_code {
+            ValidationError { errors } => {
+                assert!(errors.contains_key("quantity"));
+                assert_eq!(errors["quantity"][0].code, "out_of_range");
+                assert!(errors.contains_key("prefix"));
+                assert_eq!(errors["prefix"][0].code, "invalid");
+                assert!(errors.contains_key("length"));
+                assert_eq!(errors["length"][0].code, "length");
+            }
+            _ => panic!("Expected validation error"),
+        },
+    }
+}
+
+#[test]
+fn find_by_redemption_code_with_availability() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let event = project.create_event().with_tickets().finish();
+    let code = project.create_code().with_event(&event).finish();
+    let unique_code = UniqueCode::generate(&code, 1, None, None, None, user.id, connection)
+        .unwrap()
+        .remove(0);
+
+    let code_availability = Code::find_by_redemption_code_with_availability(
+        &unique_code.redemption_code.to_lowercase(),
+        Some(event.id),
+        connection,
+    )
+    .unwrap();
+    assert_eq!(code_availability.code, code);
+    assert_eq!(code_availability.unique_code_id, Some(unique_code.id));
+    assert_eq!(code_availability.available, 30);
+
+    let code_availability = Code::find_by_redemption_code_with_availability(
+        &code.redemption_code,
+        Some(event.id),
+        connection,
+    )
+    .unwrap();
+    assert_eq!(code_availability.unique_code_id, None);
+}
+
+#[test]
+fn single_use() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let user2 = project.create_user().finish();
+    let event = project
+        .create_event()
+        .with_ticket_pricing()
+        .with_ticket_type_count(1)
+        .finish();
+    let ticket_type = &event.ticket_types(true, None, connection).unwrap()[0];
+    let code = project
+        .create_code()
+        .with_event(&event)
+        .with_code_type(CodeTypes::Discount)
+        .finish();
+    let unique_code = UniqueCode::generate(&code, 1, None, None, None, user.id, connection)
+        .unwrap()
+        .remove(0);
+
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
+    cart.update_quantities(
+        user.id,
+        &[UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 2,
+            redemption_code: Some(unique_code.redemption_code.clone()),
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+    let items = cart.items(connection).unwrap();
+    let order_item = items
+        .iter()
+        .find(|i| i.item_type == OrderItemTypes::Tickets)
+        .unwrap();
+    assert_eq!(order_item.code_id, Some(code.id));
+    assert_eq!(order_item.unique_code_id, Some(unique_code.id));
+
+    // The generated code counts towards the parent code's uses
+    assert_eq!(
+        Code::find_number_of_uses(code.id, None, connection).unwrap(),
+        2
+    );
+    assert_eq!(
+        UniqueCode::find_number_of_uses(unique_code.id, None, connection).unwrap(),
+        1
+    );
+    let code_availability = Code::find_by_redemption_code_with_availability(
+        &unique_code.redemption_code,
+        None,
+        connection,
+    )
+    .unwrap();
+    assert_eq!(code_availability.available, 0);
+
+    let mut cart2 = Order::find_or_create_cart(&user2, connection).unwrap();
+    let result = cart2.update_quantities(
+        user2.id,
+        &[UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 1,
+            redemption_code: Some(unique_code.redemption_code.clone()),
+        }],
+        false,
+        false,
+        connection,
+    );
+    match result {
+        Ok(_) => {
+            panic!("Expected validation error");
+        }
+        Err(error) => match &error.error_code {
+            ValidationError { errors } => {
+                assert!(errors.contains_key("unique_code_id"));
+                assert_eq!(errors["unique_code_id"][0].code, "unique_code_used");
+            }
+            _ => panic!("Expected validation error"),
+        },
+    }
+
+    let display_unique_codes = UniqueCode::find_all_for_code(code.id, connection).unwrap();
+    assert_eq!(display_unique_codes.len(), 1);
+    assert_eq!(display_unique_codes[0].order_id, Some(cart.id));
+    assert_eq!(display_unique_codes[0].redeemed_at, None);
+}
+
+#[test]
+fn find_for_code() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let code = project.create_code().finish();
+    UniqueCode::generate(&code, 3, None, None, None, user.id, connection).unwrap();
+
+    let payload = UniqueCode::find_for_code(code.id, 0, 2, connection).unwrap();
+    assert_eq!(payload.paging.total, 3);
+    assert_eq!(payload.data.len(), 2);
+    assert!(payload
+        .data
+        .iter()
+        .all(|unique_code| unique_code.order_id.is_none()));
+
+    let payload = UniqueCode::find_for_code(code.id, 1, 2, connection).unwrap();
+    assert_eq!(payload.data.len(), 1);
+}
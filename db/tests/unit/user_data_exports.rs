@@ -0,0 +1,161 @@
+use bigneon_db::dev::TestProject;
+use bigneon_db::prelude::*;
+
+#[test]
+fn for_user() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let event = project.create_event().with_ticket_pricing().finish();
+    let ticket_type = &event.ticket_types(true, None, connection).unwrap()[0];
+    let order = project
+        .create_order()
+        .for_user(&user)
+        .for_event(&event)
+        .quantity(2)
+        .is_paid()
+        .finish();
+    project.create_payment_method().with_user(&user).finish();
+    PushNotificationToken::create(user.id, "example".to_string(), "token".to_string())
+        .commit(connection)
+        .unwrap();
+    EventInterest::create(event.id, user.id)
+        .commit(connection)
+        .unwrap();
+    MarketingConsent::record(
+        user.id,
+        event.organization_id,
+        true,
+        MarketingConsentSources::Checkout,
+        connection,
+    )
+    .unwrap();
+
+    // Another fan's data is not included
+    let other_user = project.create_user().finish();
+    project
+        .create_order()
+        .for_user(&other_user)
+        .for_event(&event)
+        .is_paid()
+        .finish();
+    project
+        .create_payment_method()
+        .with_user(&other_user)
+        .finish();
+
+    let export = UserDataExport::for_user(&user, connection).unwrap();
+    assert_eq!(export.profile.id, user.id);
+    assert_eq!(export.profile.email, user.email);
+    assert_eq!(
+        export.orders.iter().map(|o| o.id).collect::<Vec<_>>(),
+        vec![order.id]
+    );
+    assert_eq!(export.tickets.len(), 1);
+    assert_eq!(export.tickets[0].0.id, event.id);
+    assert_eq!(export.tickets[0].1.len(), 2);
+    assert!(export.tickets[0]
+        .1
+        .iter()
+        .all(|t| t.ticket_type_id == ticket_type.id));
+    assert!(!export.payments.is_empty());
+    assert!(export.payments.iter().all(|p| p.order_id == order.id));
+    assert_eq!(export.payment_methods.len(), 1);
+    assert_eq!(export.event_interest.len(), 1);
+    assert_eq!(export.push_notification_tokens.len(), 1);
+    assert_eq!(export.marketing_consents.len(), 1);
+    assert!(export.transfers.is_empty());
+
+    let payment_method_events: Vec<&DomainEvent> = export
+        .domain_events
+        .iter()
+        .filter(|e| e.event_type == DomainEventTypes::PaymentMethodCreated)
+        .collect();
+    assert_eq!(payment_method_events.len(), 1);
+    assert_eq!(payment_method_events[0].event_data, None);
+    assert!(export
+        .domain_events
+        .iter()
+        .all(|e| e.user_id == Some(user.id) || e.main_id == Some(user.id)));
+}
+
+#[test]
+fn for_user_includes_transfers() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let receiver = project.create_user().finish();
+    project.create_order().for_user(&user).is_paid().finish();
+    let ticket = TicketInstance::find_for_user(user.id, connection)
+        .unwrap()
+        .remove(0);
+
+    let transfer_authorization = TicketInstance::authorize_ticket_transfer(
+        user.id,
+        &[ticket.id],
+        3600,
+        None,
+        None,
+        connection,
+    )
+    .unwrap();
+    TicketInstance::receive_ticket_transfer(
+        transfer_authorization,
+        &user.default_wallet(connection).unwrap(),
+        receiver.default_wallet(connection).unwrap().id,
+        connection,
+    )
+    .unwrap();
+
+    let export = UserDataExport::for_user(&user, connection).unwrap();
+    assert_eq!(
+        export
+            .transfers
+            .iter()
+            .map(|t| t.event_type)
+            .collect::<Vec<DomainEventTypes>>(),
+        vec![
+            DomainEventTypes::TransferTicketStarted,
+            DomainEventTypes::TransferTicketCompleted
+        ]
+    );
+    assert!(export.tickets.is_empty());
+
+    // The receiver sees the transfer of the ticket they now hold
+    let export = UserDataExport::for_user(&receiver, connection).unwrap();
+    assert_eq!(
+        export
+            .transfers
+            .iter()
+            .map(|t| t.event_type)
+            .collect::<Vec<DomainEventTypes>>(),
+        vec![
+            DomainEventTypes::TransferTicketStarted,
+            DomainEventTypes::TransferTicketCompleted
+        ]
+    );
+    assert!(export.transfers.iter().any(|t| t.event_type
+        == DomainEventTypes::TransferTicketCompleted
+        && t.event_data.is_some()));
+
+    // Later holders do not see the details of transfers between earlier holders
+    let next_receiver = project.create_user().finish();
+    TicketInstance::direct_transfer(
+        receiver.id,
+        &[ticket.id],
+        next_receiver.email.as_ref().unwrap(),
+        "email",
+        next_receiver.id,
+        connection,
+    )
+    .unwrap();
+    let export = UserDataExport::for_user(&next_receiver, connection).unwrap();
+    assert_eq!(export.transfers.len(), 4);
+    let (own_transfers, earlier_transfers): (Vec<&DomainEvent>, Vec<&DomainEvent>) = export
+        .transfers
+        .iter()
+        .partition(|t| t.event_data.is_some());
+    assert_eq!(own_transfers.len(), 2);
+    assert_eq!(earlier_transfers.len(), 2);
+    assert!(earlier_transfers.iter().all(|t| t.user_id.is_none()));
+}
@@ -1378,3 +1378,135 @@ fn add_role() {
     let user2 = User::find(user.id, project.get_connection()).unwrap();
     assert_eq!(user2.role, vec![Roles::User, Roles::Admin]);
 }
+
+#[test]
+fn queue_deletion() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+
+    let action = user.queue_deletion(Some(user.id), connection).unwrap();
+    assert_eq!(action.domain_action_type, DomainActionTypes::DeleteUser);
+    assert_eq!(action.main_table_id, Some(user.id));
+    let payload: DeleteUserPayload = serde_json::from_value(action.payload.clone()).unwrap();
+    assert_eq!(payload.requested_by_user_id, Some(user.id));
+
+    // Requesting again while the deletion is pending does not queue another
+    let action2 = user.queue_deletion(Some(user.id), connection).unwrap();
+    assert_eq!(action.id, action2.id);
+}
+
+#[test]
+fn anonymize() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let order = project.create_order().for_user(&user).is_paid().finish();
+    let event = project.create_event().with_ticket_pricing().finish();
+    let cart = project
+        .create_order()
+        .for_user(&user)
+        .for_event(&event)
+        .finish();
+    project.create_payment_method().with_user(&user).finish();
+    user.add_external_login(
+        "123".to_string(),
+        FACEBOOK_SITE.to_string(),
+        "abc".to_string(),
+        connection,
+    )
+    .unwrap();
+    PushNotificationToken::create(user.id, "example".to_string(), "token".to_string())
+        .commit(connection)
+        .unwrap();
+    EventInterest::create(event.id, user.id)
+        .commit(connection)
+        .unwrap();
+    MarketingConsent::record(
+        user.id,
+        event.organization_id,
+        true,
+        MarketingConsentSources::Checkout,
+        connection,
+    )
+    .unwrap();
+    EmailSuppression::create(
+        user.email.as_ref().unwrap(),
+        EmailSuppressionReasons::Unsubscribe,
+        "SendGrid",
+        None,
+    )
+    .commit(connection)
+    .unwrap();
+    // Tickets sent to the user keep the address they were sent to
+    let sender = project.create_user().finish();
+    project.create_order().for_user(&sender).is_paid().finish();
+    let sent_ticket = TicketInstance::find_for_user(sender.id, connection)
+        .unwrap()
+        .remove(0);
+    let address = user.email.clone().unwrap().to_uppercase();
+    TicketInstance::authorize_ticket_transfer(
+        sender.id,
+        &[sent_ticket.id],
+        3600,
+        Some(address.as_str()),
+        Some("email"),
+        connection,
+    )
+    .unwrap();
+    let user = User::find(user.id, connection).unwrap();
+
+    let anonymized = user.anonymize(Some(user.id), connection).unwrap();
+    assert_eq!(anonymized.first_name, None);
+    assert_eq!(anonymized.last_name, None);
+    assert_eq!(anonymized.email, None);
+    assert_eq!(anonymized.phone, None);
+    assert!(!anonymized.active);
+    assert!(!anonymized.check_password("examplePassword"));
+    assert!(user.payment_methods(connection).unwrap().is_empty());
+    assert!(user
+        .find_external_login(FACEBOOK_SITE, connection)
+        .unwrap()
+        .is_none());
+    assert!(user
+        .push_notification_tokens(connection)
+        .unwrap()
+        .is_empty());
+    assert!(MarketingConsent::find_for_user(user.id, connection)
+        .unwrap()
+        .is_empty());
+    assert!(!EventInterest::user_interest(event.id, user.id, connection).unwrap());
+    assert!(cart.items(connection).unwrap().is_empty());
+    assert!(
+        EmailSuppression::find_by_email(user.email.as_ref().unwrap(), connection)
+            .unwrap()
+            .is_empty()
+    );
+    let transfers = DomainEvent::find(
+        Tables::TicketInstances,
+        Some(sent_ticket.id),
+        Some(DomainEventTypes::TransferTicketStarted),
+        connection,
+    )
+    .unwrap();
+    assert_eq!(transfers.len(), 1);
+    let transfer_data = transfers[0].event_data.clone().unwrap();
+    assert_eq!(transfer_data["address"], json!(null));
+    assert_eq!(transfer_data["sent_via"], json!("email"));
+
+    // Financial records stay in place
+    assert_eq!(Order::find(order.id, connection).unwrap().user_id, user.id);
+    assert!(!TicketInstance::find_for_user(user.id, connection)
+        .unwrap()
+        .is_empty());
+
+    let domain_events = DomainEvent::find(
+        Tables::Users,
+        Some(user.id),
+        Some(DomainEventTypes::UserDeleted),
+        connection,
+    )
+    .unwrap();
+    assert_eq!(domain_events.len(), 1);
+    assert_eq!(domain_events[0].user_id, Some(user.id));
+}
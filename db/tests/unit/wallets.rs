@@ -0,0 +1,161 @@
+use bigneon_db::dev::TestProject;
+use bigneon_db::prelude::*;
+use bigneon_db::utils::encryption;
+use tari_client::*;
+
+fn sign_self_custody_key(user: &User, secret_key: &Vec<u8>, public_key: &String) -> String {
+    convert_bytes_to_hexstring(
+        &cryptographic_signature(
+            &Wallet::self_custody_message(user.id, public_key),
+            secret_key,
+        )
+        .unwrap(),
+    )
+}
+
+#[test]
+fn register_self_custody_key() {
+    let project = TestProject::new();
+    let conn = project.get_connection();
+    let user = project.create_user().finish();
+    let custodial_wallet = Wallet::find_default_for_user(user.id, conn).unwrap();
+
+    let (secret_key, public_key) = cryptographic_keypair();
+    let public_key = convert_bytes_to_hexstring(&public_key);
+
+    // Signature must be made with the registered key
+    let (other_secret_key, _) = cryptographic_keypair();
+    let signature = sign_self_custody_key(&user, &other_secret_key, &public_key);
+    assert!(
+        Wallet::register_self_custody_key(user.id, public_key.clone(), signature, conn).is_err()
+    );
+
+    let signature = sign_self_custody_key(&user, &secret_key, &public_key);
+    let wallet =
+        Wallet::register_self_custody_key(user.id, public_key.clone(), signature.clone(), conn)
+            .unwrap();
+    assert!(wallet.self_custodied);
+    assert!(wallet.default_flag);
+    assert_eq!(wallet.secret_key, "");
+    assert_eq!(wallet.public_key, public_key);
+
+    // New tickets land in the self-custodied wallet while Big Neon keeps signing with its own
+    assert_eq!(
+        Wallet::find_default_for_user(user.id, conn).unwrap().id,
+        wallet.id
+    );
+    assert_eq!(
+        Wallet::find_custodial_for_user(user.id, conn).unwrap().id,
+        custodial_wallet.id
+    );
+
+    // Keys can only be registered once
+    assert!(Wallet::register_self_custody_key(user.id, public_key, signature, conn).is_err());
+}
+
+#[test]
+fn find_by_public_key() {
+    let project = TestProject::new();
+    let conn = project.get_connection();
+    let user = project.create_user().finish();
+    let wallet = Wallet::find_default_for_user(user.id, conn).unwrap();
+
+    assert_eq!(
+        Wallet::find_by_public_key(&wallet.public_key, conn)
+            .unwrap()
+            .map(|w| w.id),
+        Some(wallet.id)
+    );
+    assert!(Wallet::find_by_public_key("unknown", conn)
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn find_custodial_for_user() {
+    let project = TestProject::new();
+    let conn = project.get_connection();
+    let user = project.create_user().finish();
+
+    let wallet = Wallet::find_custodial_for_user(user.id, conn).unwrap();
+    assert!(!wallet.self_custodied);
+    assert_eq!(wallet.user_id, Some(user.id));
+    assert_eq!(
+        Wallet::find_custodial_for_user(user.id, conn).unwrap().id,
+        wallet.id
+    );
+}
+
+#[test]
+fn export() {
+    let project = TestProject::new();
+    let conn = project.get_connection();
+    let user = project.create_user().finish();
+    let wallet = Wallet::find_default_for_user(user.id, conn).unwrap();
+
+    assert!(wallet.export("short").is_err());
+
+    let export = wallet.export("correct horse battery staple").unwrap();
+    assert_eq!(export.wallet_id, wallet.id);
+    assert_eq!(export.public_key, wallet.public_key);
+    assert_ne!(export.encrypted_secret_key, wallet.secret_key);
+    assert_eq!(
+        encryption::decrypt_with_passphrase(
+            &export.encrypted_secret_key,
+            "correct horse battery staple",
+            &export.salt,
+            export.iterations
+        )
+        .unwrap(),
+        wallet.secret_key
+    );
+    assert!(encryption::decrypt_with_passphrase(
+        &export.encrypted_secret_key,
+        "incorrect horse battery staple",
+        &export.salt,
+        export.iterations
+    )
+    .is_err());
+
+    // Each export is salted separately
+    let second_export = wallet.export("correct horse battery staple").unwrap();
+    assert_ne!(second_export.salt, export.salt);
+    assert_ne!(
+        second_export.encrypted_secret_key,
+        export.encrypted_secret_key
+    );
+}
+
+#[test]
+fn tokens_for_user() {
+    let project = TestProject::new();
+    let conn = project.get_connection();
+    let user = project.create_user().finish();
+    let event = project
+        .create_event()
+        .with_ticket_pricing()
+        .with_tickets()
+        .finish();
+    let ticket_type = &event.ticket_types(true, None, conn).unwrap()[0];
+    project
+        .create_order()
+        .for_user(&user)
+        .for_event(&event)
+        .quantity(2)
+        .is_paid()
+        .finish();
+
+    let tokens = Wallet::tokens_for_user(user.id, conn).unwrap();
+    assert_eq!(tokens.len(), 2);
+    let asset = Asset::find_by_ticket_type(ticket_type.id, conn).unwrap();
+    for token in tokens {
+        assert_eq!(token.event_id, event.id);
+        assert_eq!(token.blockchain_asset_id, asset.blockchain_asset_id);
+        assert_eq!(token.status, TicketInstanceStatus::Purchased);
+    }
+    assert!(
+        Wallet::tokens_for_user(project.create_user().finish().id, conn)
+            .unwrap()
+            .is_empty()
+    );
+}
@@ -0,0 +1,19 @@
+#[macro_use]
+extern crate derive_error;
+extern crate reqwest;
+#[macro_use]
+extern crate serde_json;
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate logging;
+extern crate log;
+extern crate serde;
+
+pub use self::models::*;
+pub use self::paypal_client::PaypalClient;
+pub use self::paypal_error::PaypalError;
+
+mod models;
+mod paypal_client;
+mod paypal_error;
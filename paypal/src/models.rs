@@ -0,0 +1,197 @@
+use serde_json;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Money {
+    pub currency_code: String,
+    pub value: String,
+}
+
+impl Money {
+    pub fn from_cents(amount: i64, currency: &str) -> Money {
+        let sign = if amount < 0 { "-" } else { "" };
+        let amount = amount.abs();
+        Money {
+            currency_code: currency.to_uppercase(),
+            value: format!("{}{}.{:02}", sign, amount / 100, amount % 100),
+        }
+    }
+
+    /// PayPal amounts are decimal strings, e.g. "10.50"
+    pub fn to_cents(&self) -> Option<i64> {
+        let value = self.value.trim();
+        let (negative, value) = if value.starts_with('-') {
+            (true, &value[1..])
+        } else {
+            (false, value)
+        };
+        let mut parts = value.splitn(2, '.');
+        let units: i64 = parts.next()?.parse().ok()?;
+        let cents: i64 = match parts.next() {
+            Some(fraction) if fraction.len() == 1 => fraction.parse::<i64>().ok()? * 10,
+            Some(fraction) if fraction.len() == 2 => fraction.parse().ok()?,
+            Some(_) => return None,
+            None => 0,
+        };
+        let total = units * 100 + cents;
+        Some(if negative { -total } else { total })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Link {
+    pub href: String,
+    pub rel: String,
+    pub method: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CreateOrderRequest {
+    pub intent: String,
+    pub purchase_units: Vec<PurchaseUnitRequest>,
+    pub application_context: ApplicationContext,
+}
+
+impl CreateOrderRequest {
+    pub fn new(
+        amount: Money,
+        reference_id: String,
+        description: Option<String>,
+        return_url: String,
+        cancel_url: String,
+    ) -> CreateOrderRequest {
+        CreateOrderRequest {
+            intent: "CAPTURE".to_string(),
+            purchase_units: vec![PurchaseUnitRequest {
+                reference_id: reference_id.clone(),
+                custom_id: reference_id,
+                description,
+                amount,
+            }],
+            application_context: ApplicationContext {
+                return_url,
+                cancel_url,
+                user_action: "PAY_NOW".to_string(),
+                shipping_preference: "NO_SHIPPING".to_string(),
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PurchaseUnitRequest {
+    pub reference_id: String,
+    /// Returned on captures and webhooks so payments can be linked back to the order
+    pub custom_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub amount: Money,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApplicationContext {
+    pub return_url: String,
+    pub cancel_url: String,
+    pub user_action: String,
+    pub shipping_preference: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Order {
+    pub id: String,
+    pub status: String,
+    #[serde(default)]
+    pub links: Vec<Link>,
+    #[serde(default)]
+    pub purchase_units: Vec<PurchaseUnit>,
+}
+
+impl Order {
+    /// The url the buyer must be redirected to in order to approve the payment
+    pub fn approve_url(&self) -> Option<&str> {
+        self.links
+            .iter()
+            .find(|l| l.rel == "approve")
+            .map(|l| l.href.as_str())
+    }
+
+    pub fn captures(&self) -> Vec<&Capture> {
+        self.purchase_units
+            .iter()
+            .filter_map(|p| p.payments.as_ref())
+            .flat_map(|p| p.captures.iter())
+            .collect()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PurchaseUnit {
+    pub reference_id: Option<String>,
+    pub custom_id: Option<String>,
+    pub amount: Option<Money>,
+    pub payments: Option<PurchaseUnitPayments>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PurchaseUnitPayments {
+    #[serde(default)]
+    pub captures: Vec<Capture>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Capture {
+    pub id: String,
+    pub status: String,
+    pub amount: Option<Money>,
+    pub custom_id: Option<String>,
+    pub supplementary_data: Option<SupplementaryData>,
+}
+
+impl Capture {
+    /// The PayPal order this capture was made against
+    pub fn order_id(&self) -> Option<&str> {
+        self.supplementary_data
+            .as_ref()
+            .and_then(|s| s.related_ids.order_id.as_ref())
+            .map(|o| o.as_str())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SupplementaryData {
+    pub related_ids: RelatedIds,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RelatedIds {
+    pub order_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Refund {
+    pub id: String,
+    pub status: String,
+    pub amount: Option<Money>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct WebhookEvent {
+    pub id: String,
+    pub event_type: String,
+    pub resource_type: Option<String>,
+    pub resource: serde_json::Value,
+}
+
+/// Transmission headers PayPal sends with every webhook, required to verify its signature
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct WebhookHeaders {
+    pub auth_algo: String,
+    pub cert_url: String,
+    pub transmission_id: String,
+    pub transmission_sig: String,
+    pub transmission_time: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub(crate) struct AccessToken {
+    pub access_token: String,
+}
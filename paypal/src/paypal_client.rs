@@ -0,0 +1,142 @@
+use log::Level::Debug;
+use models::*;
+use paypal_error::PaypalError;
+use reqwest;
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json;
+
+#[derive(Clone)]
+pub struct PaypalClient {
+    client_id: String,
+    secret: String,
+    base_url: String,
+}
+
+impl PaypalClient {
+    /// Creates a new PayPal client
+    /// base_url: Live: https://api.paypal.com/, sandbox: https://api.sandbox.paypal.com/
+    pub fn new(client_id: String, secret: String, base_url: String) -> PaypalClient {
+        PaypalClient {
+            client_id,
+            secret,
+            base_url: if base_url.ends_with("/") {
+                base_url
+            } else {
+                format!("{}/", base_url)
+            },
+        }
+    }
+
+    /// Creates an order that the buyer must approve by following its `approve_url`
+    pub fn create_order(&self, request: CreateOrderRequest) -> Result<Order, PaypalError> {
+        jlog!(Debug, "Creating PayPal order", { "request": &request });
+        self.post("v2/checkout/orders", &request)
+    }
+
+    pub fn get_order(&self, order_id: &str) -> Result<Order, PaypalError> {
+        let client = reqwest::Client::new();
+        let mut resp = client
+            .get(&format!("{}v2/checkout/orders/{}", self.base_url, order_id))
+            .bearer_auth(self.access_token()?)
+            .send()?;
+        PaypalClient::parse_response(&mut resp)
+    }
+
+    /// Captures the payment for an order once the buyer has approved it
+    pub fn capture_order(&self, order_id: &str) -> Result<Order, PaypalError> {
+        jlog!(Debug, "Capturing PayPal order", { "order_id": order_id });
+        self.post(
+            &format!("v2/checkout/orders/{}/capture", order_id),
+            &json!({}),
+        )
+    }
+
+    /// Refunds a capture. When `amount` is `None` the full remaining amount is refunded.
+    pub fn refund_capture(
+        &self,
+        capture_id: &str,
+        amount: Option<Money>,
+    ) -> Result<Refund, PaypalError> {
+        jlog!(Debug, "Refunding PayPal capture", {
+            "capture_id": capture_id,
+            "amount": &amount
+        });
+        let body = match amount {
+            Some(amount) => json!({ "amount": amount }),
+            None => json!({}),
+        };
+        self.post(
+            &format!("v2/payments/captures/{}/refund", capture_id),
+            &body,
+        )
+    }
+
+    /// Asks PayPal to confirm that a webhook was sent by them for the given webhook id
+    pub fn verify_webhook_signature(
+        &self,
+        webhook_id: &str,
+        headers: &WebhookHeaders,
+        webhook_event: &serde_json::Value,
+    ) -> Result<(), PaypalError> {
+        #[derive(Deserialize)]
+        struct R {
+            verification_status: String,
+        }
+        let body = json!({
+            "auth_algo": headers.auth_algo,
+            "cert_url": headers.cert_url,
+            "transmission_id": headers.transmission_id,
+            "transmission_sig": headers.transmission_sig,
+            "transmission_time": headers.transmission_time,
+            "webhook_id": webhook_id,
+            "webhook_event": webhook_event,
+        });
+        let result: R = self.post("v1/notifications/verify-webhook-signature", &body)?;
+        if result.verification_status == "SUCCESS" {
+            Ok(())
+        } else {
+            Err(PaypalError::WebhookVerificationError(format!(
+                "Webhook signature verification returned {}",
+                result.verification_status
+            )))
+        }
+    }
+
+    fn access_token(&self) -> Result<String, PaypalError> {
+        let client = reqwest::Client::new();
+        let mut resp = client
+            .post(&format!("{}v1/oauth2/token", self.base_url))
+            .basic_auth(&self.client_id, Some(&self.secret))
+            .form(&[("grant_type", "client_credentials")])
+            .send()?;
+        let token: AccessToken = PaypalClient::parse_response(&mut resp)?;
+        Ok(token.access_token)
+    }
+
+    fn post<B: Serialize, T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<T, PaypalError> {
+        let client = reqwest::Client::new();
+        let mut resp = client
+            .post(&format!("{}{}", self.base_url, path))
+            .bearer_auth(self.access_token()?)
+            .json(body)
+            .send()?;
+        PaypalClient::parse_response(&mut resp)
+    }
+
+    fn parse_response<T: DeserializeOwned>(resp: &mut reqwest::Response) -> Result<T, PaypalError> {
+        match resp.status() {
+            StatusCode::OK | StatusCode::CREATED => {
+                let value: serde_json::Value = resp.json()?;
+                jlog!(Debug, "Response from PayPal", { "response": &value });
+                Ok(serde_json::from_value(value)?)
+            }
+            _ => Err(PaypalError::from_response(resp)),
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use reqwest;
+use serde_json;
+
+#[derive(Error, Debug)]
+pub enum PaypalError {
+    HttpError(reqwest::Error),
+    #[error(msg_embedded, no_from, non_std)]
+    UnexpectedResponseError(String),
+    DeserializationError(serde_json::Error),
+    #[error(msg_embedded, no_from, non_std)]
+    WebhookVerificationError(String),
+}
+
+impl PaypalError {
+    pub fn from_response(response: &mut reqwest::Response) -> PaypalError {
+        let response_text = response
+            .text()
+            .unwrap_or("<Error reading response body>".to_string());
+        PaypalError::UnexpectedResponseError(format!(
+            "Error calling PayPal: HTTP Code {}: Body:{}",
+            response.status(),
+            response_text
+        ))
+    }
+}
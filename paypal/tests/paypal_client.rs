@@ -0,0 +1,253 @@
+extern crate paypal;
+#[macro_use]
+extern crate serde_json;
+
+use paypal::*;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::mpsc;
+use std::thread;
+
+/// Serves the given responses, in order, to consecutive requests on a local port and reports
+/// each request line and body back over the returned channel.
+fn mock_server(
+    responses: Vec<(u16, serde_json::Value)>,
+) -> (String, mpsc::Receiver<(String, String)>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}/", listener.local_addr().unwrap());
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        for (status, body) in responses {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+
+            let mut content_length = 0;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header.trim().is_empty() {
+                    break;
+                }
+                let lower = header.to_lowercase();
+                if lower.starts_with("content-length:") {
+                    content_length = lower["content-length:".len()..].trim().parse().unwrap();
+                }
+            }
+            let mut request_body = vec![0; content_length];
+            reader.read_exact(&mut request_body).unwrap();
+            sender
+                .send((
+                    request_line.trim().to_string(),
+                    String::from_utf8(request_body).unwrap(),
+                ))
+                .unwrap();
+
+            let body = body.to_string();
+            write!(
+                stream,
+                "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            )
+            .unwrap();
+        }
+    });
+
+    (base_url, receiver)
+}
+
+fn token_response() -> (u16, serde_json::Value) {
+    (
+        200,
+        json!({"access_token": "A21AAE", "token_type": "Bearer"}),
+    )
+}
+
+#[test]
+fn create_order() {
+    let (base_url, requests) = mock_server(vec![
+        token_response(),
+        (
+            201,
+            json!({
+                "id": "5O190127TN364715T",
+                "status": "CREATED",
+                "links": [
+                    {"href": "https://api.sandbox.paypal.com/v2/checkout/orders/5O190127TN364715T", "rel": "self", "method": "GET"},
+                    {"href": "https://www.sandbox.paypal.com/checkoutnow?token=5O190127TN364715T", "rel": "approve", "method": "GET"}
+                ]
+            }),
+        ),
+    ]);
+    let client = PaypalClient::new("client".to_string(), "secret".to_string(), base_url);
+
+    let order = client
+        .create_order(CreateOrderRequest::new(
+            Money::from_cents(1050, "usd"),
+            "order-1".to_string(),
+            None,
+            "http://localhost/success".to_string(),
+            "http://localhost/cancel".to_string(),
+        ))
+        .unwrap();
+    assert_eq!(order.id, "5O190127TN364715T");
+    assert_eq!(
+        order.approve_url(),
+        Some("https://www.sandbox.paypal.com/checkoutnow?token=5O190127TN364715T")
+    );
+
+    let (token_request, _) = requests.recv().unwrap();
+    assert_eq!(token_request, "POST /v1/oauth2/token HTTP/1.1");
+    let (order_request, body) = requests.recv().unwrap();
+    assert_eq!(order_request, "POST /v2/checkout/orders HTTP/1.1");
+    let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(body["intent"], json!("CAPTURE"));
+    assert_eq!(
+        body["purchase_units"][0]["amount"],
+        json!({"currency_code": "USD", "value": "10.50"})
+    );
+    assert_eq!(body["purchase_units"][0]["custom_id"], json!("order-1"));
+}
+
+#[test]
+fn capture_order() {
+    let (base_url, requests) = mock_server(vec![
+        token_response(),
+        (
+            201,
+            json!({
+                "id": "5O190127TN364715T",
+                "status": "COMPLETED",
+                "purchase_units": [{
+                    "reference_id": "order-1",
+                    "payments": {
+                        "captures": [{
+                            "id": "3C679366HH908993F",
+                            "status": "COMPLETED",
+                            "amount": {"currency_code": "USD", "value": "10.50"}
+                        }]
+                    }
+                }]
+            }),
+        ),
+    ]);
+    let client = PaypalClient::new("client".to_string(), "secret".to_string(), base_url);
+
+    let order = client.capture_order("5O190127TN364715T").unwrap();
+    assert_eq!(order.status, "COMPLETED");
+    let captures = order.captures();
+    assert_eq!(captures.len(), 1);
+    assert_eq!(captures[0].id, "3C679366HH908993F");
+    assert_eq!(captures[0].amount.as_ref().unwrap().to_cents(), Some(1050));
+
+    requests.recv().unwrap();
+    let (capture_request, _) = requests.recv().unwrap();
+    assert_eq!(
+        capture_request,
+        "POST /v2/checkout/orders/5O190127TN364715T/capture HTTP/1.1"
+    );
+}
+
+#[test]
+fn refund_capture() {
+    let (base_url, requests) = mock_server(vec![
+        token_response(),
+        (
+            201,
+            json!({
+                "id": "1JU08902781691411",
+                "status": "COMPLETED",
+                "amount": {"currency_code": "USD", "value": "5.00"}
+            }),
+        ),
+    ]);
+    let client = PaypalClient::new("client".to_string(), "secret".to_string(), base_url);
+
+    let refund = client
+        .refund_capture("3C679366HH908993F", Some(Money::from_cents(500, "USD")))
+        .unwrap();
+    assert_eq!(refund.id, "1JU08902781691411");
+
+    requests.recv().unwrap();
+    let (refund_request, body) = requests.recv().unwrap();
+    assert_eq!(
+        refund_request,
+        "POST /v2/payments/captures/3C679366HH908993F/refund HTTP/1.1"
+    );
+    let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(
+        body,
+        json!({"amount": {"currency_code": "USD", "value": "5.00"}})
+    );
+}
+
+#[test]
+fn verify_webhook_signature() {
+    let headers = WebhookHeaders {
+        auth_algo: "SHA256withRSA".to_string(),
+        cert_url: "https://api.sandbox.paypal.com/v1/notifications/certs/CERT".to_string(),
+        transmission_id: "69cd13f0-d67a-11e5-baa3-778b53f4ae55".to_string(),
+        transmission_sig: "sig".to_string(),
+        transmission_time: "2016-02-18T20:01:35Z".to_string(),
+    };
+    let event = json!({"id": "WH-1", "event_type": "PAYMENT.CAPTURE.COMPLETED", "resource": {}});
+
+    let (base_url, _requests) = mock_server(vec![
+        token_response(),
+        (200, json!({"verification_status": "SUCCESS"})),
+        token_response(),
+        (200, json!({"verification_status": "FAILURE"})),
+    ]);
+    let client = PaypalClient::new("client".to_string(), "secret".to_string(), base_url);
+
+    assert!(client
+        .verify_webhook_signature("WH-ID", &headers, &event)
+        .is_ok());
+    assert!(client
+        .verify_webhook_signature("WH-ID", &headers, &event)
+        .is_err());
+}
+
+#[test]
+fn error_response() {
+    let (base_url, _requests) = mock_server(vec![
+        token_response(),
+        (422, json!({"name": "UNPROCESSABLE_ENTITY"})),
+    ]);
+    let client = PaypalClient::new("client".to_string(), "secret".to_string(), base_url);
+
+    match client.capture_order("5O190127TN364715T") {
+        Err(PaypalError::UnexpectedResponseError(message)) => {
+            assert!(message.contains("UNPROCESSABLE_ENTITY"))
+        }
+        _ => panic!("Expected an unexpected response error"),
+    }
+}
+
+#[test]
+fn money_conversion() {
+    assert_eq!(Money::from_cents(1050, "usd").value, "10.50");
+    assert_eq!(Money::from_cents(5, "usd").value, "0.05");
+    assert_eq!(Money::from_cents(-250, "usd").value, "-2.50");
+    assert_eq!(Money::from_cents(1050, "usd").to_cents(), Some(1050));
+    assert_eq!(
+        Money {
+            currency_code: "USD".to_string(),
+            value: "10.5".to_string()
+        }
+        .to_cents(),
+        Some(1050)
+    );
+    assert_eq!(
+        Money {
+            currency_code: "USD".to_string(),
+            value: "10".to_string()
+        }
+        .to_cents(),
+        Some(1000)
+    );
+}
@@ -0,0 +1,38 @@
+use reqwest;
+use serde_json;
+use StripeError;
+
+pub struct Account {
+    pub id: String,
+    pub charges_enabled: bool,
+    pub payouts_enabled: bool,
+    pub details_submitted: bool,
+    pub raw_data: String,
+}
+
+impl Account {
+    pub fn to_json(&self) -> String {
+        self.raw_data.clone()
+    }
+    pub fn from_response(mut resp: reqwest::Response) -> Result<Account, StripeError> {
+        let raw: String = resp.text()?;
+        #[derive(Deserialize)]
+        struct R {
+            id: String,
+            #[serde(default)]
+            charges_enabled: bool,
+            #[serde(default)]
+            payouts_enabled: bool,
+            #[serde(default)]
+            details_submitted: bool,
+        }
+        let result: R = serde_json::from_str(&raw)?;
+        Ok(Account {
+            id: result.id,
+            charges_enabled: result.charges_enabled,
+            payouts_enabled: result.payouts_enabled,
+            details_submitted: result.details_submitted,
+            raw_data: raw,
+        })
+    }
+}
@@ -0,0 +1,17 @@
+use reqwest;
+use serde_json;
+use StripeError;
+
+#[derive(Deserialize, Serialize)]
+pub struct AccountLink {
+    pub url: String,
+    pub expires_at: i64,
+}
+
+impl AccountLink {
+    pub fn from_response(mut resp: reqwest::Response) -> Result<AccountLink, StripeError> {
+        let raw: String = resp.text()?;
+        let result: AccountLink = serde_json::from_str(&raw)?;
+        Ok(result)
+    }
+}
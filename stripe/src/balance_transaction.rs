@@ -0,0 +1,35 @@
+use reqwest;
+use serde_json;
+use StripeError;
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct BalanceTransaction {
+    pub id: String,
+    pub amount: i64,
+    pub fee: i64,
+    pub net: i64,
+    pub currency: String,
+    pub created: i64,
+    pub available_on: i64,
+    pub status: String,
+    #[serde(rename = "type")]
+    pub transaction_type: String,
+    pub source: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct BalanceTransactionList {
+    pub data: Vec<BalanceTransaction>,
+    pub has_more: bool,
+}
+
+impl BalanceTransactionList {
+    pub fn from_response(
+        mut resp: reqwest::Response,
+    ) -> Result<BalanceTransactionList, StripeError> {
+        let raw: String = resp.text()?;
+        let result: BalanceTransactionList = serde_json::from_str(&raw)?;
+        Ok(result)
+    }
+}
@@ -9,14 +9,22 @@ extern crate serde_json;
 #[macro_use]
 extern crate serde_derive;
 
+pub use self::account::Account;
+pub use self::account_link::AccountLink;
+pub use self::balance_transaction::*;
 pub use self::charge_result::ChargeResult;
 pub use self::customer::*;
+pub use self::payment_intent::*;
 pub use self::refund_result::RefundResult;
 pub use self::stripe_client::StripeClient;
 pub use self::stripe_error::StripeError;
 
+mod account;
+mod account_link;
+mod balance_transaction;
 mod charge_result;
 mod customer;
+mod payment_intent;
 mod refund_result;
 mod stripe_client;
 mod stripe_error;
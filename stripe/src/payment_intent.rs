@@ -0,0 +1,85 @@
+use reqwest;
+use serde_json;
+use StripeError;
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentIntentStatus {
+    RequiresPaymentMethod,
+    RequiresConfirmation,
+    RequiresAction,
+    /// Older API versions report `requires_action` as `requires_source_action`
+    RequiresSourceAction,
+    Processing,
+    RequiresCapture,
+    Canceled,
+    Succeeded,
+}
+
+pub struct PaymentIntent {
+    pub id: String,
+    pub status: PaymentIntentStatus,
+    /// Passed to Stripe.js so the customer can complete authentication (e.g. 3-D Secure)
+    pub client_secret: Option<String>,
+    /// The charge created once the payment has been authorized
+    pub charge_id: Option<String>,
+    /// Connected account the payment is transferred to, set on destination charges
+    pub transfer_destination: Option<String>,
+    pub raw_data: String,
+}
+
+impl PaymentIntent {
+    pub fn to_json(&self) -> String {
+        self.raw_data.clone()
+    }
+
+    pub fn from_response(mut resp: reqwest::Response) -> Result<PaymentIntent, StripeError> {
+        let raw: String = resp.text()?;
+        PaymentIntent::from_json(raw)
+    }
+
+    pub fn from_json(raw: String) -> Result<PaymentIntent, StripeError> {
+        #[derive(Deserialize)]
+        struct Charge {
+            id: String,
+        }
+        #[derive(Deserialize)]
+        struct Charges {
+            #[serde(default)]
+            data: Vec<Charge>,
+        }
+        #[derive(Deserialize)]
+        struct TransferData {
+            destination: String,
+        }
+        #[derive(Deserialize)]
+        struct R {
+            id: String,
+            status: PaymentIntentStatus,
+            #[serde(default)]
+            client_secret: Option<String>,
+            #[serde(default)]
+            latest_charge: Option<String>,
+            #[serde(default)]
+            charges: Option<Charges>,
+            #[serde(default)]
+            transfer_data: Option<TransferData>,
+        }
+        let result: R = serde_json::from_str(&raw)?;
+        let charge_id = match result.latest_charge {
+            Some(charge_id) => Some(charge_id),
+            None => result
+                .charges
+                .and_then(|c| c.data.into_iter().next())
+                .map(|c| c.id),
+        };
+        Ok(PaymentIntent {
+            id: result.id,
+            status: result.status,
+            client_secret: result.client_secret,
+            charge_id,
+            transfer_destination: result.transfer_data.map(|t| t.destination),
+            raw_data: raw,
+        })
+    }
+}
@@ -1,6 +1,10 @@
 use reqwest;
+use Account;
+use AccountLink;
+use BalanceTransactionList;
 use ChargeResult;
 use Customer;
+use PaymentIntent;
 use RefundResult;
 use StripeError;
 
@@ -101,6 +105,123 @@ impl StripeClient {
         }
     }
 
+    /// Creates and confirms a manually captured payment intent. `token` may be a payment
+    /// method (`pm_`), a customer (`cus_`) or a legacy card token or source.
+    ///
+    /// If the card requires authentication the returned intent has a status of
+    /// `requires_action` and must be confirmed again once the customer has completed it.
+    pub fn create_payment_intent(
+        &self,
+        token: &str,
+        amount: i64,
+        currency: &str,
+        description: &str,
+        destination: Option<(&str, i64)>,
+        metadata: Vec<(String, String)>,
+    ) -> Result<PaymentIntent, StripeError> {
+        let mut params = vec![
+            ("currency".to_string(), currency.to_string()),
+            ("amount".to_string(), amount.to_string()),
+            ("description".to_string(), description.to_string()),
+            ("capture_method".to_string(), "manual".to_string()),
+            ("confirmation_method".to_string(), "manual".to_string()),
+            ("confirm".to_string(), "true".to_string()),
+            (
+                if token.starts_with("pm_") {
+                    "payment_method".to_string()
+                } else if token.starts_with("cus_") {
+                    "customer".to_string()
+                } else {
+                    "source".to_string()
+                },
+                token.to_string(),
+            ),
+        ];
+
+        if let Some((destination_account_id, application_fee)) = destination {
+            params.push((
+                "transfer_data[destination]".to_string(),
+                destination_account_id.to_string(),
+            ));
+            params.push((
+                "application_fee_amount".to_string(),
+                application_fee.to_string(),
+            ));
+        }
+
+        for key_value in metadata {
+            params.push((format!("metadata[{}]", key_value.0), key_value.1));
+        }
+        let client = reqwest::Client::new();
+        let mut resp = client
+            .post("https://api.stripe.com/v1/payment_intents")
+            .basic_auth(&self.api_key, Some(""))
+            .form(&params)
+            .send()?;
+        match resp.status() {
+            reqwest::StatusCode::OK => {
+                return PaymentIntent::from_response(resp);
+            }
+            _ => return Err(StripeError::from_response(&mut resp)),
+        }
+    }
+
+    pub fn retrieve_payment_intent(
+        &self,
+        payment_intent_id: &str,
+    ) -> Result<PaymentIntent, StripeError> {
+        let client = reqwest::Client::new();
+        let mut resp = client
+            .get(&format!(
+                "https://api.stripe.com/v1/payment_intents/{}",
+                payment_intent_id
+            ))
+            .basic_auth(&self.api_key, Some(""))
+            .send()?;
+        match resp.status() {
+            reqwest::StatusCode::OK => {
+                return PaymentIntent::from_response(resp);
+            }
+            _ => return Err(StripeError::from_response(&mut resp)),
+        }
+    }
+
+    /// Confirms a payment intent after the customer has completed any required action
+    pub fn confirm_payment_intent(
+        &self,
+        payment_intent_id: &str,
+    ) -> Result<PaymentIntent, StripeError> {
+        self.post_payment_intent_action(payment_intent_id, "confirm")
+    }
+
+    pub fn capture_payment_intent(
+        &self,
+        payment_intent_id: &str,
+    ) -> Result<PaymentIntent, StripeError> {
+        self.post_payment_intent_action(payment_intent_id, "capture")
+    }
+
+    fn post_payment_intent_action(
+        &self,
+        payment_intent_id: &str,
+        action: &str,
+    ) -> Result<PaymentIntent, StripeError> {
+        let client = reqwest::Client::new();
+        let mut resp = client
+            .post(&format!(
+                "https://api.stripe.com/v1/payment_intents/{}/{}",
+                payment_intent_id, action
+            ))
+            .basic_auth(&self.api_key, Some(""))
+            .send()?;
+        match resp.status() {
+            reqwest::StatusCode::OK => {
+                return PaymentIntent::from_response(resp);
+            }
+            _ => return Err(StripeError::from_response(&mut resp)),
+        }
+    }
+
     pub fn refund(&self, charge_id: &str) -> Result<RefundResult, StripeError> {
         let params = vec![("charge".to_string(), charge_id.to_string())];
 
@@ -142,6 +263,36 @@ impl StripeClient {
         }
     }
 
+    /// Refunds a destination charge, pulling the refunded amount back from the connected
+    /// account and returning the matching share of the application fee.
+    pub fn refund_with_transfer_reversal(
+        &self,
+        charge_id: &str,
+        amount: Option<u32>,
+    ) -> Result<RefundResult, StripeError> {
+        let mut params = vec![
+            ("charge".to_string(), charge_id.to_string()),
+            ("reverse_transfer".to_string(), "true".to_string()),
+            ("refund_application_fee".to_string(), "true".to_string()),
+        ];
+        if let Some(amount) = amount {
+            params.push(("amount".to_string(), amount.to_string()));
+        }
+
+        let client = reqwest::Client::new();
+        let mut resp = client
+            .post("https://api.stripe.com/v1/refunds")
+            .basic_auth(&self.api_key, Some(""))
+            .form(&params)
+            .send()?;
+        match resp.status() {
+            reqwest::StatusCode::OK => {
+                return RefundResult::from_response(resp);
+            }
+            _ => return Err(StripeError::from_response(&mut resp)),
+        }
+    }
+
     pub fn complete(&self, charge_id: &str) -> Result<ChargeResult, StripeError> {
         let client = reqwest::Client::new();
 
@@ -219,4 +370,141 @@ impl StripeClient {
             _ => return Err(StripeError::from_response(&mut resp)),
         }
     }
+
+    pub fn delete_customer(&self, customer_id: &str) -> Result<(), StripeError> {
+        let client = reqwest::Client::new();
+        let mut resp = client
+            .delete(&format!(
+                "https://api.stripe.com/v1/customers/{}",
+                customer_id
+            ))
+            .basic_auth(&self.api_key, Some(""))
+            .send()?;
+        match resp.status() {
+            // Already deleted, e.g. by an earlier attempt that failed afterwards
+            reqwest::StatusCode::OK | reqwest::StatusCode::NOT_FOUND => Ok(()),
+            _ => return Err(StripeError::from_response(&mut resp)),
+        }
+    }
+
+    pub fn create_connected_account(
+        &self,
+        email: Option<&str>,
+        country: Option<&str>,
+        metadata: Vec<(String, String)>,
+    ) -> Result<Account, StripeError> {
+        let mut params = vec![
+            ("type".to_string(), "express".to_string()),
+            (
+                "requested_capabilities[]".to_string(),
+                "card_payments".to_string(),
+            ),
+            (
+                "requested_capabilities[]".to_string(),
+                "transfers".to_string(),
+            ),
+        ];
+        if let Some(email) = email {
+            params.push(("email".to_string(), email.to_string()));
+        }
+        if let Some(country) = country {
+            params.push(("country".to_string(), country.to_string()));
+        }
+
+        for key_value in metadata {
+            params.push((format!("metadata[{}]", key_value.0), key_value.1));
+        }
+        let client = reqwest::Client::new();
+        let mut resp = client
+            .post("https://api.stripe.com/v1/accounts")
+            .basic_auth(&self.api_key, Some(""))
+            .form(&params)
+            .send()?;
+        match resp.status() {
+            reqwest::StatusCode::OK => {
+                return Account::from_response(resp);
+            }
+            _ => return Err(StripeError::from_response(&mut resp)),
+        }
+    }
+
+    pub fn retrieve_account(&self, account_id: &str) -> Result<Account, StripeError> {
+        let client = reqwest::Client::new();
+        let mut resp = client
+            .get(&format!(
+                "https://api.stripe.com/v1/accounts/{}",
+                account_id
+            ))
+            .basic_auth(&self.api_key, Some(""))
+            .send()?;
+        match resp.status() {
+            reqwest::StatusCode::OK => {
+                return Account::from_response(resp);
+            }
+            _ => return Err(StripeError::from_response(&mut resp)),
+        }
+    }
+
+    pub fn create_account_link(
+        &self,
+        account_id: &str,
+        refresh_url: &str,
+        return_url: &str,
+    ) -> Result<AccountLink, StripeError> {
+        let params = vec![
+            ("account".to_string(), account_id.to_string()),
+            ("refresh_url".to_string(), refresh_url.to_string()),
+            ("return_url".to_string(), return_url.to_string()),
+            ("type".to_string(), "account_onboarding".to_string()),
+        ];
+
+        let client = reqwest::Client::new();
+        let mut resp = client
+            .post("https://api.stripe.com/v1/account_links")
+            .basic_auth(&self.api_key, Some(""))
+            .form(&params)
+            .send()?;
+        match resp.status() {
+            reqwest::StatusCode::OK => {
+                return AccountLink::from_response(resp);
+            }
+            _ => return Err(StripeError::from_response(&mut resp)),
+        }
+    }
+
+    /// Lists balance transactions on a connected account, newest first. `created_gte` and
+    /// `created_lte` are unix timestamps.
+    pub fn list_balance_transactions(
+        &self,
+        account_id: &str,
+        created_gte: Option<i64>,
+        created_lte: Option<i64>,
+        starting_after: Option<&str>,
+        limit: u32,
+    ) -> Result<BalanceTransactionList, StripeError> {
+        let mut params = vec![("limit".to_string(), limit.to_string())];
+        if let Some(created_gte) = created_gte {
+            params.push(("created[gte]".to_string(), created_gte.to_string()));
+        }
+        if let Some(created_lte) = created_lte {
+            params.push(("created[lte]".to_string(), created_lte.to_string()));
+        }
+        if let Some(starting_after) = starting_after {
+            params.push(("starting_after".to_string(), starting_after.to_string()));
+        }
+
+        let client = reqwest::Client::new();
+        let mut resp = client
+            .get("https://api.stripe.com/v1/balance_transactions")
+            .basic_auth(&self.api_key, Some(""))
+            .header("Stripe-Account", account_id)
+            .query(&params)
+            .send()?;
+        match resp.status() {
+            reqwest::StatusCode::OK => {
+                return BalanceTransactionList::from_response(resp);
+            }
+            _ => return Err(StripeError::from_response(&mut resp)),
+        }
+    }
 }
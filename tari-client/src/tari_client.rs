@@ -54,6 +54,14 @@ pub trait TariClient {
         asset_id: &String,
     ) -> Result<ResponsePayloadReadAsset, TariError>;
 
+    fn get_tokens(
+        &self,
+        secret_key: &String,
+        public_key: &String,
+        asset_id: &String,
+        token_ids: Vec<u64>,
+    ) -> Result<Vec<Token>, TariError>;
+
     fn box_clone(&self) -> Box<TariClient + Send + Sync>;
 }
 
@@ -115,7 +123,7 @@ impl TariClient for HttpTariClient {
     ) -> Result<(), TariError> {
         let header_command = String::from("modify_asset");
         let msg_payload = serde_json::to_value(MessagePayloadModifyAsset {
-            request_type: 2,
+            request_type: INCREASE_SUPPLY_REQUEST,
             asset_id: asset_id.clone(),
             authorised_signer: None,
             token_ids: None,
@@ -154,7 +162,7 @@ impl TariClient for HttpTariClient {
     ) -> Result<(), TariError> {
         let header_command = String::from("modify_asset");
         let msg_payload = serde_json::to_value(MessagePayloadModifyAsset {
-            request_type: 4,
+            request_type: NULLIFY_TOKENS_REQUEST,
             asset_id: asset_id.clone(),
             authorised_signer: None,
             token_ids: Some(token_ids),
@@ -193,7 +201,7 @@ impl TariClient for HttpTariClient {
     ) -> Result<(), TariError> {
         let header_command = String::from("modify_asset");
         let msg_payload = serde_json::to_value(MessagePayloadModifyAsset {
-            request_type: 5,
+            request_type: REDEEM_TOKENS_REQUEST,
             asset_id: asset_id.clone(),
             authorised_signer: None,
             token_ids: Some(token_ids),
@@ -292,6 +300,43 @@ impl TariClient for HttpTariClient {
         Ok(response_message_result)
     }
 
+    fn get_tokens(
+        &self,
+        secret_key: &String,
+        public_key: &String,
+        asset_id: &String,
+        token_ids: Vec<u64>,
+    ) -> Result<Vec<Token>, TariError> {
+        let header_command = String::from("read_asset");
+        let msg_payload = serde_json::to_value(MessagePayloadReadAsset {
+            request_type: READ_TOKENS_REQUEST,
+            user: None,
+            asset_id: asset_id.clone(),
+            token_ids: Some(token_ids),
+        })?;
+        let secret_key = convert_hexstring_to_bytes(&secret_key);
+        let public_key = convert_hexstring_to_bytes(&public_key);
+        let jsonrpc_request =
+            construct_jsonrpc_request(header_command, msg_payload, &secret_key, &public_key)?;
+
+        let client = reqwest::Client::new();
+        let mut resp = client.post(&self.tari_url).json(&jsonrpc_request).send()?;
+        let raw: String = resp.text()?;
+        jlog!(Level::Info, &format!("Response from read_asset: {}", raw));
+        let response_message: RPCResponse = serde_json::from_str(&raw)?;
+        let response_message_result: ResponsePayloadSuccessTokens =
+            serde_json::from_value(response_message.result)?;
+
+        if response_message_result.success {
+            Ok(response_message_result.tokens.unwrap_or_default())
+        } else {
+            Err(TariError {
+                description: "Failed to read tokens on Tari".to_string(),
+                cause: None,
+            })
+        }
+    }
+
     fn box_clone(&self) -> Box<TariClient + Send + Sync> {
         Box::new((*self).clone())
     }
@@ -3,6 +3,7 @@ use secp256k1;
 use serde_json;
 use std::error::Error;
 use std::fmt;
+use std::io;
 use std::sync::Arc;
 
 #[derive(Debug)]
@@ -52,3 +53,12 @@ impl From<serde_json::Error> for TariError {
         }
     }
 }
+
+impl From<io::Error> for TariError {
+    fn from(r: io::Error) -> Self {
+        TariError {
+            description: format!("IO error: {}", r),
+            cause: Some(Arc::new(r)),
+        }
+    }
+}
@@ -15,7 +15,7 @@ pub struct Token {
     pub metadata: u64,
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct RPCRequest {
     pub jsonrpc: String,
     pub method: String,
@@ -60,6 +60,9 @@ pub struct MessagePayloadCreateAsset {
     pub expiry_date: i64,
 }
 
+/// `MessagePayloadReadAsset` request type returning the tokens listed in `token_ids`
+pub const READ_TOKENS_REQUEST: i8 = 1;
+
 #[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
 pub struct MessagePayloadReadAsset {
     pub request_type: i8,
@@ -74,6 +77,11 @@ pub struct MessagePayloadTransferAsset {
     pub new_issuer: String,
 }
 
+// `MessagePayloadModifyAsset` request types
+pub const INCREASE_SUPPLY_REQUEST: i8 = 2;
+pub const NULLIFY_TOKENS_REQUEST: i8 = 4;
+pub const REDEEM_TOKENS_REQUEST: i8 = 5;
+
 #[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
 pub struct MessagePayloadModifyAsset {
     pub asset_id: String,
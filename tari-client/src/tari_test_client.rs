@@ -85,6 +85,16 @@ impl TariClient for TariTestClient {
         })
     }
 
+    fn get_tokens(
+        &self,
+        _secret_key: &String,
+        _public_key: &String,
+        _asset_id: &String,
+        _token_ids: Vec<u64>,
+    ) -> Result<Vec<Token>, TariError> {
+        Ok(Vec::new())
+    }
+
     fn box_clone(&self) -> Box<TariClient + Send + Sync> {
         Box::new((*self).clone())
     }
@@ -0,0 +1,93 @@
+extern crate log;
+#[macro_use]
+extern crate logging;
+extern crate serde_json;
+extern crate tari_client;
+extern crate uuid;
+
+mod support;
+
+use support::TariMockServer;
+use tari_client::*;
+
+fn keys() -> (String, String) {
+    let (secret_key, public_key) = cryptographic_keypair();
+    (
+        convert_bytes_to_hexstring(&secret_key),
+        convert_bytes_to_hexstring(&public_key),
+    )
+}
+
+#[test]
+fn create_and_modify_asset() {
+    let server = TariMockServer::start().unwrap();
+    let client = server.client();
+    let (secret_key, public_key) = keys();
+
+    let asset_id = client
+        .create_asset(
+            &secret_key,
+            &public_key,
+            MessagePayloadCreateAsset {
+                name: "VIP".to_string(),
+                total_supply: 10,
+                authorised_signers: Vec::new(),
+                rule_flags: 0,
+                rule_metadata: "".to_string(),
+                expiry_date: 0,
+            },
+        )
+        .unwrap();
+    assert_eq!(server.total_supply(&asset_id), Some(10));
+
+    client
+        .modify_asset_increase_supply(&secret_key, &public_key, &asset_id, 20)
+        .unwrap();
+    let (_, receiver_public_key) = keys();
+    client
+        .transfer_tokens(
+            &secret_key,
+            &public_key,
+            &asset_id,
+            vec![1, 2],
+            receiver_public_key.clone(),
+        )
+        .unwrap();
+
+    let asset_info = client
+        .get_asset_info(&secret_key, &public_key, &asset_id)
+        .unwrap();
+    assert_eq!(asset_info.total_supply, 20);
+    assert_eq!(asset_info.issuer, public_key);
+
+    // Tokens outside of the supply are left out
+    let tokens = client
+        .get_tokens(&secret_key, &public_key, &asset_id, vec![0, 1, 25])
+        .unwrap();
+    assert_eq!(
+        tokens
+            .iter()
+            .map(|t| (t.id, t.owner.as_str()))
+            .collect::<Vec<(u64, &str)>>(),
+        vec![(0, public_key.as_str()), (1, receiver_public_key.as_str())]
+    );
+    assert_eq!(
+        server
+            .requests()
+            .iter()
+            .map(|r| r.method.as_str())
+            .collect::<Vec<&str>>(),
+        vec![
+            "create_asset",
+            "modify_asset",
+            "transfer_token",
+            "read_asset",
+            "read_asset"
+        ]
+    );
+
+    // Unknown assets are rejected
+    assert!(client
+        .modify_asset_increase_supply(&secret_key, &public_key, &"unknown".to_string(), 20)
+        .is_err());
+}
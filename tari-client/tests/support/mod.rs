@@ -0,0 +1,204 @@
+use log::Level;
+use serde_json;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use tari_client::*;
+use uuid::Uuid;
+
+#[derive(Default)]
+struct MockState {
+    assets: HashMap<String, ResponsePayloadReadAsset>,
+    token_owners: HashMap<(String, u64), String>,
+    requests: Vec<RPCRequest>,
+}
+
+/// Local JSON-RPC server answering the Tari messages sent by `HttpTariClient`, for use in
+/// integration tests. Message signatures are verified and assets are kept in memory.
+pub struct TariMockServer {
+    url: String,
+    state: Arc<Mutex<MockState>>,
+}
+
+impl TariMockServer {
+    /// Starts the server on a random local port
+    pub fn start() -> Result<TariMockServer, TariError> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let url = format!("http://{}", listener.local_addr()?);
+        let state = Arc::new(Mutex::new(MockState::default()));
+
+        let server_state = state.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if let Ok(stream) = stream {
+                    if let Err(e) = handle_connection(stream, &server_state) {
+                        jlog!(Level::Warn, &format!("Mock Tari server error: {}", e));
+                    }
+                }
+            }
+        });
+
+        Ok(TariMockServer { url, state })
+    }
+
+    pub fn url(&self) -> String {
+        self.url.clone()
+    }
+
+    pub fn client(&self) -> HttpTariClient {
+        HttpTariClient::new(self.url())
+    }
+
+    /// Requests received so far, oldest first
+    pub fn requests(&self) -> Vec<RPCRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    pub fn total_supply(&self, asset_id: &str) -> Option<u64> {
+        self.state
+            .lock()
+            .unwrap()
+            .assets
+            .get(asset_id)
+            .map(|a| a.total_supply)
+    }
+}
+
+fn handle_connection(stream: TcpStream, state: &Mutex<MockState>) -> Result<(), TariError> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let line = line.trim();
+        if line.is_empty() {
+            break;
+        }
+        let header = line.to_lowercase();
+        if header.starts_with("content-length:") {
+            content_length = header["content-length:".len()..]
+                .trim()
+                .parse()
+                .unwrap_or(0);
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    let request: RPCRequest = serde_json::from_slice(&body)?;
+    let result = respond(&request, &mut state.lock().unwrap())?;
+    let response = serde_json::to_string(&RPCResponse {
+        jsonrpc: String::from("2.0"),
+        result,
+        id: request.id,
+    })?;
+
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.len(),
+        response
+    )?;
+    stream.flush()?;
+    Ok(())
+}
+
+fn respond(request: &RPCRequest, state: &mut MockState) -> Result<Value, TariError> {
+    state.requests.push(request.clone());
+    let message: MessageRequest = serde_json::from_value(request.params.clone())?;
+    if !message_verification(
+        &message.header,
+        &message.payload.to_string(),
+        &message.signature,
+    )? {
+        return Ok(serde_json::to_value(ResponsePayloadSuccess {
+            success: false,
+        })?);
+    }
+
+    let result = match request.method.as_str() {
+        "create_asset" => {
+            let asset: MessagePayloadCreateAsset = serde_json::from_value(message.payload)?;
+            let id = Uuid::new_v4().to_string();
+            state.assets.insert(
+                id.clone(),
+                ResponsePayloadReadAsset {
+                    id: id.clone(),
+                    name: asset.name,
+                    symbol: "".to_string(),
+                    decimals: 0,
+                    total_supply: asset.total_supply,
+                    authorised_signers: asset.authorised_signers,
+                    issuer: message.signature.public_key,
+                    rule_flags: asset.rule_flags,
+                    rule_metadata: asset.rule_metadata,
+                    expired: false,
+                },
+            );
+            serde_json::to_value(ResponsePayloadSuccessId { success: true, id })?
+        }
+        "modify_asset" => {
+            let modification: MessagePayloadModifyAsset = serde_json::from_value(message.payload)?;
+            let success = match state.assets.get_mut(&modification.asset_id) {
+                Some(asset) => {
+                    if let Some(new_supply) = modification.new_supply {
+                        asset.total_supply = new_supply;
+                    }
+                    true
+                }
+                None => false,
+            };
+            serde_json::to_value(ResponsePayloadSuccess { success })?
+        }
+        "transfer_token" => {
+            let transfer: MessagePayloadTransferToken = serde_json::from_value(message.payload)?;
+            let success = state.assets.contains_key(&transfer.asset_id);
+            if success {
+                for token_id in transfer.token_ids {
+                    state.token_owners.insert(
+                        (transfer.asset_id.clone(), token_id),
+                        transfer.new_owner.clone(),
+                    );
+                }
+            }
+            serde_json::to_value(ResponsePayloadSuccess { success })?
+        }
+        "read_asset" => {
+            let read: MessagePayloadReadAsset = serde_json::from_value(message.payload)?;
+            match state.assets.get(&read.asset_id) {
+                Some(asset) if read.request_type == READ_TOKENS_REQUEST => {
+                    let tokens = read
+                        .token_ids
+                        .unwrap_or_default()
+                        .into_iter()
+                        .filter(|id| *id < asset.total_supply)
+                        .map(|id| Token {
+                            id,
+                            asset_id: asset.id.clone(),
+                            owner: state
+                                .token_owners
+                                .get(&(asset.id.clone(), id))
+                                .unwrap_or(&asset.issuer)
+                                .clone(),
+                            used: false,
+                            valid: true,
+                            metadata: 0,
+                        })
+                        .collect();
+                    serde_json::to_value(ResponsePayloadSuccessTokens {
+                        success: true,
+                        tokens: Some(tokens),
+                    })?
+                }
+                Some(asset) => serde_json::to_value(asset)?,
+                None => Value::Null,
+            }
+        }
+        _ => serde_json::to_value(ResponsePayloadSuccess { success: false })?,
+    };
+    Ok(result)
+}